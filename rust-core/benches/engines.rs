@@ -0,0 +1,143 @@
+//! Benchmarks de todos los engines (por evento) y de los paths batch, sobre
+//! el flujo sintético de `indicators_core::bench` (1 000 trades, 4 símbolos,
+//! libro de 10 niveles por lado)
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use indicators_core::bench::{SyntheticConfig, SyntheticData};
+#[cfg(feature = "python")]
+use indicators_core::numpy_batch;
+use indicators_core::*;
+
+fn data() -> (SyntheticConfig, SyntheticData) {
+    let config = SyntheticConfig { n_events: 1_000, n_symbols: 4, ..Default::default() };
+    let data = SyntheticData::generate(&config);
+    (config, data)
+}
+
+/// Cicla sobre `events` llamando a `f` con uno por iteración
+fn bench_each<T>(c: &mut Criterion, group: &str, name: &str, events: &[T], mut f: impl FnMut(&T)) {
+    let mut g = c.benchmark_group(group);
+    g.throughput(Throughput::Elements(1));
+    g.bench_function(name, |b| {
+        let mut i = 0;
+        b.iter(|| {
+            f(black_box(&events[i % events.len()]));
+            i += 1;
+        })
+    });
+    g.finish();
+}
+
+fn bench_trade_engines(c: &mut Criterion) {
+    let (config, data) = data();
+    let trades = &data.trades;
+
+    let cvd = CVDEngine::new();
+    bench_each(c, "trade", "cvd", trades, |t| { black_box(cvd.on_trade(t)); });
+    let cvd_bars = CVDBarEngine::new("1m").unwrap();
+    bench_each(c, "trade", "cvd_bars", trades, |t| { black_box(cvd_bars.on_trade(t)); });
+    let footprint = FootprintEngine::new("1m", config.tick_size).unwrap();
+    bench_each(c, "trade", "footprint", trades, |t| { black_box(footprint.on_trade(t)); });
+    let vwap = VWAPEngine::new();
+    bench_each(c, "trade", "vwap", trades, |t| { black_box(vwap.on_trade(t)); });
+    let rolling_vwap = RollingVWAPEngine::new(Some(60_000), None).unwrap();
+    bench_each(c, "trade", "rolling_vwap", trades, |t| { black_box(rolling_vwap.on_trade(t)); });
+    let volume_profile = VolumeProfileEngine::new(0.7, config.tick_size).unwrap();
+    bench_each(c, "trade", "volume_profile", trades, |t| { black_box(volume_profile.on_trade(t)); });
+    let vpin = VPINEngine::new(1_000.0, 50).unwrap();
+    bench_each(c, "trade", "vpin", trades, |t| { black_box(vpin.on_trade(t)); });
+    let tape = TapeEngine::new(10_000, 0.99, 1000, 100).unwrap();
+    bench_each(c, "trade", "tape", trades, |t| { black_box(tape.on_trade(t)); });
+    let volatility = VolatilityEngine::new(vec![20], 2.0, 1000, 60).unwrap();
+    bench_each(c, "trade", "volatility", trades, |t| { black_box(volatility.on_trade(t)); });
+    let bars = BarAggregator::new(vec!["1s".to_string(), "1m".to_string()]).unwrap();
+    bench_each(c, "trade", "bar_aggregator", trades, |t| { black_box(bars.on_trade(t)); });
+    let oi = OIEngine::new(300_000).unwrap();
+    let events: Vec<_> = trades.iter()
+        .map(|t| OpenInterest::new(t.ts, t.symbol.clone(), 1_000_000.0 + t.size * 10.0, None))
+        .collect();
+    bench_each(c, "trade", "open_interest", &events, |e| { black_box(oi.on_open_interest(e)); });
+    let liquidations = LiquidationEngine::new(60_000, 1.0, 3_600_000, 10_000, None).unwrap();
+    let events: Vec<_> = trades.iter()
+        .map(|t| Liquidation::new(t.ts, t.symbol.clone(), t.side.clone().unwrap_or_default(), t.price, t.size, None))
+        .collect();
+    bench_each(c, "trade", "liquidations", &events, |e| { black_box(liquidations.on_liquidation(e)); });
+}
+
+fn bench_book_engines(c: &mut Criterion) {
+    let (config, data) = data();
+    let snapshots = &data.snapshots;
+
+    let liquidity = LiquidityEngine::new();
+    bench_each(c, "snapshot", "liquidity", snapshots, |s| { black_box(liquidity.on_snapshot(s)); });
+    let heatmap = HeatmapEngine::default();
+    bench_each(c, "snapshot", "heatmap", snapshots, |s| { black_box(heatmap.on_snapshot(s)); });
+    let pressure = BookPressureEngine::new(10_000, None, 10).unwrap();
+    bench_each(c, "snapshot", "book_pressure", snapshots, |s| { black_box(pressure.on_snapshot(s)); });
+    let iceberg = IcebergDetector::new(2.0, 2, config.tick_size).unwrap();
+    let events: Vec<_> = data.trades.iter().zip(snapshots).collect();
+    bench_each(c, "snapshot", "iceberg", &events, |(t, s)| {
+        iceberg.on_trade(t);
+        black_box(iceberg.on_snapshot(s));
+    });
+    let books = OrderBookManager::new(None, None);
+    bench_each(c, "snapshot", "order_book", snapshots, |s| books.apply_snapshot(s, None));
+}
+
+fn bench_bar_engines(c: &mut Criterion) {
+    let (_, data) = data();
+    let bars = &data.bars;
+
+    let ma = MovingAverageEngine::new(vec![9, 21, 50]).unwrap();
+    bench_each(c, "bar", "moving_averages", bars, |b| { black_box(ma.on_bar(b)); });
+    let rsi = RSIEngine::new(vec![14]).unwrap();
+    bench_each(c, "bar", "rsi", bars, |b| { black_box(rsi.on_bar(b)); });
+    let atr = ATREngine::new(vec![14]).unwrap();
+    bench_each(c, "bar", "atr", bars, |b| { black_box(atr.on_bar(b)); });
+    let volatility = VolatilityEngine::new(vec![20], 2.0, 1000, 60).unwrap();
+    bench_each(c, "bar", "volatility", bars, |b| { black_box(volatility.on_bar(b)); });
+}
+
+fn bench_batch_paths(c: &mut Criterion) {
+    let mut g = c.benchmark_group("batch");
+    for n_events in [1_000, 10_000] {
+        let config = SyntheticConfig { n_events, n_symbols: 4, ..Default::default() };
+        let data = SyntheticData::generate(&config);
+        let trades = &data.trades;
+        g.throughput(Throughput::Elements(n_events as u64));
+
+        let vwap = VWAPEngine::new();
+        g.bench_with_input(BenchmarkId::new("vwap_trade_batch", n_events), trades, |b, trades| {
+            b.iter(|| black_box(vwap.trade_batch(trades)))
+        });
+        #[cfg(feature = "python")]
+        {
+            let cvd = CVDEngine::new();
+            g.bench_with_input(BenchmarkId::new("cvd_numpy_columns", n_events), trades, |b, trades| {
+                b.iter(|| {
+                    let results: Vec<_> = trades.iter().map(|t| cvd.on_trade(t)).collect();
+                    black_box(numpy_batch::cvd_columns(&results))
+                })
+            });
+        }
+        let rsi = RSIEngine::new(vec![14]).unwrap();
+        g.bench_with_input(BenchmarkId::new("rsi_bar_batch", n_events), &data.bars, |b, bars| {
+            b.iter(|| black_box(bars.iter().flat_map(|bar| rsi.on_bar(bar)).collect::<Vec<_>>()))
+        });
+        let atr = ATREngine::new(vec![14]).unwrap();
+        g.bench_with_input(BenchmarkId::new("atr_bar_batch", n_events), &data.bars, |b, bars| {
+            b.iter(|| black_box(bars.iter().flat_map(|bar| atr.on_bar(bar)).collect::<Vec<_>>()))
+        });
+        let manager = EngineManager::new(true, true, true, true, None, None, None).unwrap();
+        g.bench_with_input(BenchmarkId::new("manager_sequential", n_events), trades, |b, trades| {
+            b.iter(|| black_box(trades.iter().map(|t| manager.on_trade(t)).collect::<Vec<_>>()))
+        });
+        g.bench_with_input(BenchmarkId::new("manager_parallel", n_events), trades, |b, trades| {
+            b.iter(|| black_box(manager.dispatch_parallel(trades)))
+        });
+    }
+    g.finish();
+}
+
+criterion_group!(benches, bench_trade_engines, bench_book_engines, bench_bar_engines, bench_batch_paths);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+//! Benchmark de HeatmapEngine::on_snapshot con un libro de 20 niveles por lado
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use indicators_core::{BookSnapshot, HeatmapEngine, Level};
+
+fn create_snapshot(ts: u64) -> BookSnapshot {
+    let bids = (0..20).map(|i| Level::new(150.0 - 0.01 * (i + 1) as f64, 100.0 + i as f64)).collect();
+    let asks = (0..20).map(|i| Level::new(150.0 + 0.01 * (i + 1) as f64, 100.0 + i as f64)).collect();
+    BookSnapshot::new(ts, "AAPL".to_string(), bids, asks)
+}
+
+fn bench_heatmap_on_snapshot(c: &mut Criterion) {
+    let engine = HeatmapEngine::default();
+    let snapshots: Vec<BookSnapshot> = (0..100).map(|i| create_snapshot(i * 10)).collect();
+    
+    c.bench_function("heatmap_on_snapshot_20_levels", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            let result = engine.on_snapshot(black_box(&snapshots[i % snapshots.len()]));
+            i += 1;
+            black_box(result)
+        })
+    });
+}
+
+criterion_group!(benches, bench_heatmap_on_snapshot);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+//! Benchmark de los núcleos SIMD de utils y de los caminos que los usan
+//! (`HeatmapEngine::on_snapshot`, `VWAPEngine::trade_batch`), cada uno con
+//! los núcleos vectoriales y forzando el escalar con `simd::set_enabled`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use indicators_core::bench::{SyntheticConfig, SyntheticData};
+use indicators_core::utils::{aggregate_volume_simd, price_binning_simd, price_to_tick, simd};
+use indicators_core::{BookSnapshot, HeatmapEngine, Level, VWAPEngine};
+
+const MODES: [(&str, bool); 2] = [("simd", true), ("scalar", false)];
+
+fn create_prices(n: usize) -> Vec<f64> {
+    (0..n).map(|i| 150.0 + 0.01 * (i % 500) as f64).collect()
+}
+
+fn create_snapshot(ts: u64, depth: usize) -> BookSnapshot {
+    let bids = (0..depth).map(|i| Level::new(150.0 - 0.01 * (i + 1) as f64, 100.0 + i as f64)).collect();
+    let asks = (0..depth).map(|i| Level::new(150.0 + 0.01 * (i + 1) as f64, 100.0 + i as f64)).collect();
+    BookSnapshot::new(ts, "AAPL".to_string(), bids, asks)
+}
+
+fn bench_aggregate_volume(c: &mut Criterion) {
+    let volumes: Vec<f64> = (0..4096).map(|i| 1.0 + (i % 100) as f64).collect();
+    
+    let mut group = c.benchmark_group("aggregate_volume_4096");
+    group.bench_function("simd", |b| b.iter(|| aggregate_volume_simd(black_box(&volumes))));
+    group.bench_function("scalar", |b| b.iter(|| black_box(&volumes).iter().sum::<f64>()));
+    group.finish();
+}
+
+fn bench_price_binning(c: &mut Criterion) {
+    let prices = create_prices(4096);
+    
+    let mut group = c.benchmark_group("price_binning_4096");
+    group.bench_function("simd", |b| b.iter(|| price_binning_simd(black_box(&prices), 0.01)));
+    group.bench_function("scalar", |b| {
+        b.iter(|| black_box(&prices).iter().map(|&p| price_to_tick(p, 0.01)).collect::<Vec<i64>>())
+    });
+    group.finish();
+}
+
+fn bench_heatmap_on_snapshot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("heatmap_on_snapshot");
+    for depth in [20, 200] {
+        let snapshots: Vec<BookSnapshot> = (0..100).map(|i| create_snapshot(i * 10, depth)).collect();
+        for (mode, enabled) in MODES {
+            simd::set_enabled(enabled);
+            let engine = HeatmapEngine::default();
+            group.bench_with_input(BenchmarkId::new(mode, depth), &snapshots, |b, snapshots| {
+                let mut i = 0;
+                b.iter(|| {
+                    let result = engine.on_snapshot(black_box(&snapshots[i % snapshots.len()]));
+                    i += 1;
+                    black_box(result)
+                })
+            });
+        }
+    }
+    simd::set_enabled(true);
+    group.finish();
+}
+
+fn bench_vwap_trade_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vwap_trade_batch");
+    for n_events in [1_000, 100_000] {
+        let data = SyntheticData::generate(&SyntheticConfig { n_events, n_symbols: 1, ..Default::default() });
+        let engine = VWAPEngine::new();
+        group.throughput(Throughput::Elements(n_events as u64));
+        for (mode, enabled) in MODES {
+            simd::set_enabled(enabled);
+            group.bench_with_input(BenchmarkId::new(mode, n_events), &data.trades, |b, trades| {
+                b.iter(|| black_box(engine.trade_batch(black_box(trades))))
+            });
+        }
+    }
+    simd::set_enabled(true);
+    group.finish();
+}
+
+criterion_group!(benches, bench_aggregate_volume, bench_price_binning, bench_heatmap_on_snapshot, bench_vwap_trade_batch);
+criterion_main!(benches);
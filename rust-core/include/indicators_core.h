@@ -0,0 +1,145 @@
+/* Generado por cbindgen desde src/capi.rs: no editar a mano.
+ *
+ * La API solo usa el núcleo Rust y nunca toma el GIL. Compilada con
+ * `cargo build --release --no-default-features --features capi` la
+ * librería no depende de Python; con la feature `python` (por defecto)
+ * enlaza además libpython, que debe estar disponible al cargarla. */
+
+#ifndef INDICATORS_CORE_H
+#define INDICATORS_CORE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Métricas en cola por defecto (capacidad 0 en `ic_engine_new`)
+#define IC_DEFAULT_QUEUE_CAPACITY 65536
+
+// Resultado de las funciones push: >= 0 correcto, < 0 error
+#define IC_NO_METRICS 0
+
+#define IC_METRICS_QUEUED 1
+
+#define IC_ERR_NULL -1
+
+#define IC_ERR_UTF8 -2
+
+#define IC_ERR_PANIC -3
+
+// Lado del agresor (`IcTrade.side`); con otro valor lo deduce el
+// clasificador del engine. Es un entero y no un enum para que un valor
+// inesperado desde C no sea comportamiento indefinido.
+#define IC_SIDE_UNKNOWN 0
+
+#define IC_SIDE_BUY 1
+
+#define IC_SIDE_SELL 2
+
+// Engine opaco para C: el `EngineManager` y su cola de métricas
+typedef struct IcEngine IcEngine;
+
+// Trade de entrada; `symbol` es una cadena UTF-8 terminada en NUL
+typedef struct {
+  uint64_t ts;
+  double price;
+  double size;
+  const char *symbol;
+  int32_t side;
+} IcTrade;
+
+// Nivel de libro de entrada
+typedef struct {
+  double price;
+  double size;
+} IcLevel;
+
+// Métricas de un evento. Los bloques sin `has_*` quedan a 0.
+typedef struct {
+  // Válido hasta la siguiente llamada a `ic_engine_poll` o `ic_engine_free`
+  const char *symbol;
+  uint64_t ts;
+  bool has_cvd;
+  double cvd;
+  double buy_volume;
+  double sell_volume;
+  uint64_t trade_count;
+  bool has_vwap;
+  double vwap;
+  double vwap_std_dev;
+  double vwap_upper_band_1;
+  double vwap_lower_band_1;
+  bool has_liquidity;
+  double mid;
+  double spread;
+  double best_bid;
+  double best_ask;
+  double depth_imbalance;
+  double microprice;
+} IcMetrics;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Crea un engine con los indicadores indicados; `queue_capacity` 0 usa
+// `IC_DEFAULT_QUEUE_CAPACITY`. Devuelve NULL si no se pudo crear.
+IcEngine *ic_engine_new(bool cvd, bool vwap, bool liquidity, bool heatmap, size_t queue_capacity);
+
+// Libera el engine (NULL no hace nada)
+//
+// # Safety
+// `engine` debe venir de `ic_engine_new` y no usarse después
+void ic_engine_free(IcEngine *engine);
+
+// Procesa un trade. Devuelve `IC_METRICS_QUEUED` si produjo métricas,
+// `IC_NO_METRICS` si no (inválido, duplicado o sin engine) o un error < 0.
+//
+// # Safety
+// `engine` debe ser un engine vivo y `trade` NULL o un `IcTrade` válido
+int32_t ic_engine_push_trade(const IcEngine *engine, const IcTrade *trade);
+
+// Procesa un snapshot de libro (bids de mejor a peor, asks de mejor a peor)
+//
+// # Safety
+// `engine` debe ser un engine vivo, `symbol` una cadena terminada en NUL y
+// `bids` / `asks` apuntar a `n_bids` / `n_asks` niveles
+int32_t ic_engine_push_book(const IcEngine *engine,
+                            uint64_t ts,
+                            const char *symbol,
+                            const IcLevel *bids,
+                            size_t n_bids,
+                            const IcLevel *asks,
+                            size_t n_asks);
+
+// Saca las métricas más antiguas de la cola; false si está vacía
+//
+// # Safety
+// `engine` debe ser un engine vivo y `out` apuntar a un `IcMetrics` escribible
+bool ic_engine_poll(const IcEngine *engine, IcMetrics *out);
+
+// Métricas pendientes en la cola
+//
+// # Safety
+// `engine` debe ser NULL o un engine vivo
+size_t ic_engine_pending(const IcEngine *engine);
+
+// Métricas descartadas por cola llena
+//
+// # Safety
+// `engine` debe ser NULL o un engine vivo
+uint64_t ic_engine_dropped(const IcEngine *engine);
+
+// Borra el estado de un símbolo en todos los indicadores
+//
+// # Safety
+// `engine` debe ser un engine vivo y `symbol` una cadena terminada en NUL
+int32_t ic_engine_reset_symbol(const IcEngine *engine, const char *symbol);
+
+// Versión de la librería (cadena estática)
+const char *ic_version(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* INDICATORS_CORE_H */
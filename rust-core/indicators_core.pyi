@@ -0,0 +1,2799 @@
+# Generado por `cargo xtask stubs` a partir de las firmas #[pyclass] /
+# #[pymethods] / #[pyfunction] del crate. No editar a mano.
+
+from typing import Any
+
+import numpy
+import numpy.typing
+
+
+class Trade:
+    """Trade individual"""
+    ts: int
+    price: float
+    size: float
+    symbol: str
+    side: str | None
+    exchange: str | None
+    trade_id: str | None
+    def __init__(self, ts: int, price: float, size: float, symbol: str, side: str | None = None, exchange: str | None = None, trade_id: str | None = None) -> None:
+        """Constructor de Python: los campos opcionales se pueden pasar por nombre,
+        p. ej. `Trade(ts=..., price=..., size=..., symbol=..., side="BUY")`
+        """
+    def __repr__(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Trade) -> bool: ...
+    def __le__(self, other: Trade) -> bool: ...
+    def __gt__(self, other: Trade) -> bool: ...
+    def __ge__(self, other: Trade) -> bool: ...
+    def __hash__(self) -> int:
+        """Hash de todos los campos, coherente con `==` (no mutar un trade usado como clave)"""
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> Trade: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Trade: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Trade: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> Trade: ...
+
+class Bar:
+    """Barra OHLCV"""
+    ts: int
+    open: float
+    high: float
+    low: float
+    close: float
+    volume: float
+    tf: str
+    symbol: str
+    def __init__(self, ts: int, open: float, high: float, low: float, close: float, volume: float = 0.0, tf: str = ..., symbol: str = "") -> None: ...
+    def __repr__(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Bar) -> bool: ...
+    def __le__(self, other: Bar) -> bool: ...
+    def __gt__(self, other: Bar) -> bool: ...
+    def __ge__(self, other: Bar) -> bool: ...
+    def __hash__(self) -> int:
+        """Hash de todos los campos, coherente con `==`"""
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> Bar: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Bar: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Bar: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> Bar: ...
+
+class Level:
+    """Nivel del libro de órdenes"""
+    price: float
+    size: float
+    def __init__(self, price: float, size: float) -> None: ...
+    def __repr__(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Level) -> bool: ...
+    def __le__(self, other: Level) -> bool: ...
+    def __gt__(self, other: Level) -> bool: ...
+    def __ge__(self, other: Level) -> bool: ...
+    def __hash__(self) -> int: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> Level: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Level: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Level: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> Level: ...
+
+class BookSnapshot:
+    """Snapshot del libro de órdenes"""
+    ts: int
+    symbol: str
+    bids: list[Level]
+    asks: list[Level]
+    def __init__(self, ts: int, symbol: str, bids: list[Level] = ..., asks: list[Level] = ...) -> None: ...
+    def __repr__(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: BookSnapshot) -> bool: ...
+    def __le__(self, other: BookSnapshot) -> bool: ...
+    def __gt__(self, other: BookSnapshot) -> bool: ...
+    def __ge__(self, other: BookSnapshot) -> bool: ...
+    def __hash__(self) -> int:
+        """Hash de ts, símbolo y todos los niveles, coherente con `==`"""
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> BookSnapshot: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> BookSnapshot: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> BookSnapshot: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> BookSnapshot: ...
+
+class Quote:
+    """Cotización top-of-book (BBO)"""
+    ts: int
+    symbol: str
+    bid: float
+    bid_size: float
+    ask: float
+    ask_size: float
+    exchange: str | None
+    def __init__(self, ts: int, symbol: str, bid: float, bid_size: float, ask: float, ask_size: float) -> None: ...
+    def mid(self) -> float:
+        """Precio medio entre bid y ask"""
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> Quote: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Quote: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Quote: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> Quote: ...
+
+class BookDelta:
+    """Actualización incremental L2 de un nivel de precio"""
+    ts: int
+    symbol: str
+    side: str
+    action: str
+    price: float
+    size: float
+    sequence: int | None
+    def __init__(self, ts: int, symbol: str, side: str, action: str, price: float, size: float, sequence: int | None = None) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> BookDelta: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> BookDelta: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> BookDelta: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> BookDelta: ...
+
+class OpenInterest:
+    """Open interest de un contrato (perpetuo/futuro)"""
+    ts: int
+    symbol: str
+    open_interest: float
+    exchange: str | None
+    def __init__(self, ts: int, symbol: str, open_interest: float, exchange: str | None = None) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> OpenInterest: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> OpenInterest: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> OpenInterest: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> OpenInterest: ...
+
+class FundingRate:
+    """Funding rate de un perpetuo"""
+    ts: int
+    symbol: str
+    rate: float
+    mark_price: float | None
+    next_funding_ts: int | None
+    def __init__(self, ts: int, symbol: str, rate: float, mark_price: float | None = None, next_funding_ts: int | None = None) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> FundingRate: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> FundingRate: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> FundingRate: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> FundingRate: ...
+
+class Liquidation:
+    """Liquidación forzosa de una posición (feeds de exchanges cripto)"""
+    ts: int
+    symbol: str
+    side: str
+    price: float
+    size: float
+    exchange: str | None
+    def __init__(self, ts: int, symbol: str, side: str, price: float, size: float, exchange: str | None = None) -> None: ...
+    def notional(self) -> float:
+        """Nocional liquidado (price × size)"""
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> Liquidation: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Liquidation: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Liquidation: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> Liquidation: ...
+
+class TickSizeRegistry:
+    """Registro de tick size por símbolo con un valor por defecto.
+    Clonar el registro comparte el estado (Arc), de modo que varios engines
+    pueden consumir el mismo registro.
+    """
+    default_tick: float
+    def __init__(self, default_tick: float = 0.01) -> None: ...
+    def set_tick_size(self, symbol: str, tick_size: float) -> None:
+        """Registra el tick size de un símbolo"""
+    def get_tick_size(self, symbol: str) -> float:
+        """Tick size de un símbolo (o el de por defecto)"""
+    def remove(self, symbol: str) -> None:
+        """Elimina el tick propio de un símbolo (vuelve al de por defecto)"""
+    def __repr__(self) -> str: ...
+
+class StreamingStats:
+    """Estadísticos en streaming de una serie: media, varianza, asimetría y curtosis"""
+    def __init__(self) -> None: ...
+    def update(self, value: float) -> None:
+        """Añade un valor (los no finitos se ignoran)"""
+    def update_many(self, values: list[float]) -> None:
+        """Añade varios valores en orden"""
+    def merge(self, other: StreamingStats) -> None:
+        """Incorpora los datos de otro acumulador (p. ej. de otra partición o proceso)"""
+    @property
+    def count(self) -> int:
+        """Valores acumulados"""
+    @property
+    def mean(self) -> float | None: ...
+    def variance(self, ddof: int = 0) -> float | None:
+        """Varianza con divisor n - ddof; None si n <= ddof"""
+    def std_dev(self, ddof: int = 0) -> float | None: ...
+    @property
+    def skewness(self) -> float | None:
+        """Asimetría poblacional; None con varianza nula"""
+    @property
+    def kurtosis(self) -> float | None:
+        """Curtosis en exceso (0 para la normal); None con varianza nula"""
+    def reset(self) -> None:
+        """Descarta los valores acumulados"""
+    def __len__(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class QuantileSketch:
+    """Sketch de cuantiles (DDSketch) para usar desde Python"""
+    def __init__(self, relative_accuracy: float = 0.01, max_bins: int = 2048) -> None: ...
+    def add(self, value: float) -> None:
+        """Añade un valor (los no finitos se ignoran)"""
+    def add_many(self, values: list[float]) -> None:
+        """Añade varios valores"""
+    def merge(self, other: QuantileSketch) -> None:
+        """Incorpora los valores de otro sketch con la misma relative_accuracy"""
+    def quantile(self, q: float) -> float | None:
+        """Cuantil q en [0, 1]; None sin valores"""
+    def quantiles(self, quantiles: list[float] = ...) -> list[float | None]:
+        """Varios cuantiles (por defecto p50, p95 y p99)"""
+    @property
+    def count(self) -> int:
+        """Valores añadidos"""
+    @property
+    def mean(self) -> float | None: ...
+    @property
+    def min(self) -> float | None: ...
+    @property
+    def max(self) -> float | None: ...
+    @property
+    def relative_accuracy(self) -> float: ...
+    @property
+    def max_bins(self) -> int: ...
+    @property
+    def bins(self) -> int:
+        """Buckets ocupados"""
+    def reset(self) -> None:
+        """Descarta los valores añadidos"""
+    def __len__(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class SymbolConfig:
+    """Perfil de configuración; los campos None heredan del perfil por defecto"""
+    tick_size: float | None
+    depth_levels: int | None
+    bucket_ms: int | None
+    session_period_ms: int | None
+    session_offset_ms: int | None
+    indicators: list[str] | None
+    def __init__(self, tick_size: float | None = None, depth_levels: int | None = None, bucket_ms: int | None = None, session_period_ms: int | None = None, session_offset_ms: int | None = None, indicators: list[str] | None = None) -> None: ...
+    def __repr__(self) -> str: ...
+
+class ConfigRegistry:
+    """Registro de configuración por símbolo.
+    Clonar el registro comparte el estado (Arc), igual que `TickSizeRegistry`.
+    """
+    def __init__(self, default: SymbolConfig | None = None) -> None: ...
+    @property
+    def default(self) -> SymbolConfig:
+        """Perfil por defecto"""
+    @default.setter
+    def default(self, config: SymbolConfig) -> None: ...
+    def set(self, symbol: str, config: SymbolConfig) -> None:
+        """Registra (o reemplaza) el perfil de un símbolo"""
+    def get(self, symbol: str) -> SymbolConfig:
+        """Perfil efectivo del símbolo: el suyo superpuesto al de por defecto"""
+    def remove(self, symbol: str) -> None:
+        """Elimina el perfil propio del símbolo (vuelve al de por defecto)"""
+    def symbols(self) -> list[str]:
+        """Símbolos con perfil propio"""
+    def is_indicator_enabled(self, symbol: str, name: str) -> bool:
+        """Indica si un indicador está activo para el símbolo"""
+    def tick_registry(self, fallback_tick: float = 0.01) -> TickSizeRegistry:
+        """Registro de tick sizes equivalente, para engines que ya usan `TickSizeRegistry`"""
+    def __repr__(self) -> str: ...
+
+class SessionCalendar:
+    """Calendario de sesiones de un mercado"""
+    @property
+    def open_ms(self) -> int:
+        """Apertura en ms desde medianoche local"""
+    @property
+    def close_ms(self) -> int:
+        """Cierre en ms desde medianoche local (<= apertura: cruza medianoche)"""
+    @property
+    def weekdays(self) -> bytes:
+        """Días en que abre la sesión (0 = lunes ... 6 = domingo)"""
+    def __init__(self, open: str = "00:00", close: str = "24:00", weekdays: bytes | None = None, timezone: str = "UTC", holidays: list[str] | None = None) -> None: ...
+    @staticmethod
+    def preset(name: str) -> SessionCalendar:
+        """Calendario de un mercado conocido (ver `presets()`), sin festivos"""
+    @staticmethod
+    def presets() -> list[str]:
+        """Nombres de los presets disponibles"""
+    @property
+    def timezone(self) -> str:
+        """Zona horaria IANA del calendario"""
+    @property
+    def holidays(self) -> list[str]:
+        """Festivos ("YYYY-MM-DD") ordenados"""
+    def add_holiday(self, date: str) -> None:
+        """Marca un día ("YYYY-MM-DD") como festivo: la sesión que abre ese día no existe"""
+    def remove_holiday(self, date: str) -> bool:
+        """Quita un festivo; false si no estaba"""
+    def is_open(self, ts: int) -> bool:
+        """Indica si el mercado está abierto en ts"""
+    def open_ms_between(self, start: int, end: int) -> int:
+        """Tiempo de mercado abierto (ms) en el intervalo [start, end)"""
+    def session_bounds(self, ts: int) -> tuple[int, int] | None:
+        """Inicio y fin de la sesión abierta en ts"""
+    def session_start(self, ts: int) -> int | None:
+        """Apertura de la última sesión que empezó en o antes de ts (la sesión
+        a la que pertenece ts, aunque el mercado ya haya cerrado)
+        """
+    def next_open(self, ts: int) -> int | None:
+        """Próxima apertura estrictamente posterior a ts"""
+    def __repr__(self) -> str: ...
+
+class CVDMetrics:
+    """Métricas de CVD"""
+    cvd: float
+    last_side: str
+    last_size: float
+    timestamp: int
+    buy_volume: float
+    sell_volume: float
+    trade_count: int
+    def __init__(self, cvd: float, last_side: str, last_size: float, timestamp: int, buy_volume: float = 0.0, sell_volume: float = 0.0, trade_count: int = 0) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> CVDMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> CVDMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> CVDMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> CVDMetrics: ...
+
+class CVDBar:
+    """Barra de delta (CVD en forma OHLC por timeframe)"""
+    symbol: str
+    tf: str
+    bucket_ts: int
+    open: float
+    high: float
+    low: float
+    close: float
+    buy_volume: float
+    sell_volume: float
+    delta: float
+    def __init__(self, symbol: str, tf: str, bucket_ts: int, open: float) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> CVDBar: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> CVDBar: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> CVDBar: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> CVDBar: ...
+
+class FootprintLevel:
+    """Volumen por lado en un nivel de precio de un footprint"""
+    price: float
+    buy_volume: float
+    sell_volume: float
+    delta: float
+    volume: float
+    def __init__(self, price: float, buy_volume: float, sell_volume: float, volume: float) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> FootprintLevel: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> FootprintLevel: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> FootprintLevel: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> FootprintLevel: ...
+
+class FootprintBar:
+    """Barra footprint: OHLC de precio y volumen por lado en cada nivel"""
+    symbol: str
+    tf: str
+    bucket_ts: int
+    open: float
+    high: float
+    low: float
+    close: float
+    volume: float
+    delta: float
+    levels: list[FootprintLevel]
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> FootprintBar: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> FootprintBar: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> FootprintBar: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> FootprintBar: ...
+
+class LiquidityMetrics:
+    """Métricas de Liquidity"""
+    mid: float
+    spread: float
+    bids_depth: float
+    asks_depth: float
+    depth_imbalance: float
+    top_imbalance: float
+    best_bid: float
+    best_ask: float
+    bid1_size: float
+    ask1_size: float
+    bid_levels: int
+    ask_levels: int
+    top_bids: list[Level]
+    top_asks: list[Level]
+    microprice: float
+    weighted_mid: float
+    bid_slope: float
+    ask_slope: float
+    bid_added: float
+    bid_pulled: float
+    ask_added: float
+    ask_pulled: float
+    near_bid_added: float
+    near_bid_pulled: float
+    near_ask_added: float
+    near_ask_pulled: float
+    bids_notional: float | None
+    asks_notional: float | None
+    smoothed_spread: float | None
+    smoothed_depth_imbalance: float | None
+    def __init__(self, mid: float, spread: float, bids_depth: float, asks_depth: float, depth_imbalance: float, top_imbalance: float, best_bid: float, best_ask: float, bid1_size: float, ask1_size: float, levels: str, microprice: float = 0.0, weighted_mid: float = 0.0, bid_slope: float = 0.0, ask_slope: float = 0.0) -> None: ...
+    @property
+    def levels(self) -> str:
+        """Obsoleto: emite DeprecationWarning; usar `bid_levels` / `ask_levels`"""
+    @levels.setter
+    def levels(self, levels: str) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LiquidityMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LiquidityMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LiquidityMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LiquidityMetrics: ...
+
+class LiquidityRollingStats:
+    """Medias móviles de liquidez de un símbolo sobre una ventana temporal"""
+    symbol: str
+    window_ms: int
+    samples: int
+    avg_spread: float
+    avg_depth_imbalance: float
+    avg_bids_depth: float
+    avg_asks_depth: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LiquidityRollingStats: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LiquidityRollingStats: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LiquidityRollingStats: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LiquidityRollingStats: ...
+
+class EnrichedTrade:
+    """Trade anotado con el contexto del libro vigente al ejecutarse"""
+    ts: int
+    symbol: str
+    price: float
+    size: float
+    side: str
+    exchange: str | None
+    trade_id: str | None
+    mid: float | None
+    distance_bps: float | None
+    levels_swept: int
+    quote_age_ms: int | None
+    @property
+    def is_sweep(self) -> bool:
+        """El trade consumió más de un nivel del libro"""
+    def to_trade(self) -> Trade:
+        """Trade original con el lado del libro explícito, para alimentar otros
+        engines con la misma clasificación
+        """
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> EnrichedTrade: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> EnrichedTrade: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> EnrichedTrade: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> EnrichedTrade: ...
+
+class EffectiveSpreadMetrics:
+    """Spread efectivo de un trade respecto a la quote vigente al ejecutarse"""
+    ts: int
+    symbol: str
+    price: float
+    size: float
+    side: str
+    bid: float
+    ask: float
+    mid: float
+    quoted_spread: float
+    effective_spread: float
+    effective_spread_bps: float
+    price_improvement: float
+    quote_age_ms: int
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> EffectiveSpreadMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> EffectiveSpreadMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> EffectiveSpreadMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> EffectiveSpreadMetrics: ...
+
+class SpreadWindowStats:
+    """Spread cotizado medio ponderado por tiempo y spread efectivo medio de un
+    símbolo sobre una ventana temporal
+    """
+    symbol: str
+    window_ms: int
+    twa_quoted_spread: float
+    quoted_ms: int
+    trades: int
+    avg_effective_spread: float
+    avg_price_improvement: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> SpreadWindowStats: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> SpreadWindowStats: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> SpreadWindowStats: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> SpreadWindowStats: ...
+
+class LiquidityAlertConfig:
+    """Umbrales de alertas de liquidez (None = alerta desactivada)"""
+    spread_median_multiple: float | None
+    min_depth: float | None
+    imbalance_limit: float | None
+    def __init__(self, spread_median_multiple: float | None = None, min_depth: float | None = None, imbalance_limit: float | None = None) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LiquidityAlertConfig: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LiquidityAlertConfig: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LiquidityAlertConfig: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LiquidityAlertConfig: ...
+
+class LiquidityAlert:
+    """Evento de alerta de liquidez"""
+    ts: int
+    symbol: str
+    kind: str
+    value: float
+    threshold: float
+    def __init__(self, ts: int, symbol: str, kind: str, value: float, threshold: float) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LiquidityAlert: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LiquidityAlert: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LiquidityAlert: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LiquidityAlert: ...
+
+class BookQualityAlert:
+    """Problema de calidad en un snapshot del libro"""
+    ts: int
+    symbol: str
+    kind: str
+    message: str
+    def __init__(self, ts: int, symbol: str, kind: str, message: str) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> BookQualityAlert: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> BookQualityAlert: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> BookQualityAlert: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> BookQualityAlert: ...
+
+class IcebergDetection:
+    """Detección de iceberg (liquidez oculta que se repone en un nivel)"""
+    ts: int
+    symbol: str
+    side: str
+    price: float
+    executed_volume: float
+    displayed_size: float
+    estimated_hidden_size: float
+    reloads: int
+    confidence: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> IcebergDetection: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> IcebergDetection: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> IcebergDetection: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> IcebergDetection: ...
+
+class AbsorptionEvent:
+    """Absorción: volumen agresivo ejecutado contra un nivel que no cede"""
+    ts: int
+    symbol: str
+    side: str
+    price: float
+    absorbed_volume: float
+    trades: int
+    start_ts: int
+    duration_ms: int
+    resting_size: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> AbsorptionEvent: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> AbsorptionEvent: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> AbsorptionEvent: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> AbsorptionEvent: ...
+
+class BookPressureMetrics:
+    """Presión del libro: imbalance integrado en el tiempo"""
+    symbol: str
+    ts: int
+    imbalance: float
+    pressure: float
+    normalized_pressure: float
+    decayed_pressure: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> BookPressureMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> BookPressureMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> BookPressureMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> BookPressureMetrics: ...
+
+class Tile:
+    """Tile individual (precio + tamaño comprimido)"""
+    price_bin: float
+    total_size: float
+    side: str
+    value: float
+    def __init__(self, price_bin: float, total_size: float, side: str, value: float | None = None) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> Tile: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Tile: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Tile: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> Tile: ...
+
+class HeatmapMetrics:
+    """Métricas de Heatmap con tiles comprimidos"""
+    bucket_ts: int
+    bucket_ms: int
+    tiles: list[Tile]
+    max_sz: float
+    compression_ratio: float
+    symbol: str
+    normalization: str
+    mid: float | None
+    notional: bool
+    def __init__(self, bucket_ts: int, bucket_ms: int, tiles: list[Tile], max_sz: float, compression_ratio: float, symbol: str = "", normalization: str = "raw", mid: float | None = None, notional: bool = False) -> None: ...
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> HeatmapMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> HeatmapMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> HeatmapMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> HeatmapMetrics: ...
+
+class VWAPMetrics:
+    """Métricas de VWAP"""
+    vwap: float
+    pv_sum: float
+    v_sum: float
+    session_id: str | None
+    anchor_ts: int | None
+    std_dev: float
+    upper_band_1: float
+    lower_band_1: float
+    upper_band_2: float
+    lower_band_2: float
+    upper_band_3: float
+    lower_band_3: float
+    last_ts: int
+    def __init__(self, vwap: float, pv_sum: float, v_sum: float, session_id: str | None = None, anchor_ts: int | None = None) -> None:
+        """Las bandas se inicializan sobre el VWAP (desviación 0)"""
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> VWAPMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> VWAPMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> VWAPMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> VWAPMetrics: ...
+
+class VolumeProfileMetrics:
+    """Perfil de volumen (volumen ejecutado por nivel de precio)"""
+    symbol: str
+    ts: int
+    session_start: int | None
+    total_volume: float
+    total_notional: float | None
+    poc: float
+    value_area_high: float
+    value_area_low: float
+    tiles: list[Tile]
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> VolumeProfileMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> VolumeProfileMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> VolumeProfileMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> VolumeProfileMetrics: ...
+
+class MovingAverageMetrics:
+    """Medias móviles de un símbolo para un periodo (None hasta completar el periodo)"""
+    symbol: str
+    ts: int
+    period: int
+    sma: float | None
+    ema: float | None
+    wma: float | None
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> MovingAverageMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> MovingAverageMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> MovingAverageMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> MovingAverageMetrics: ...
+
+class RSIMetrics:
+    """Métricas de RSI (Wilder) de un símbolo para un periodo"""
+    symbol: str
+    ts: int
+    period: int
+    rsi: float | None
+    avg_gain: float
+    avg_loss: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> RSIMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> RSIMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> RSIMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> RSIMetrics: ...
+
+class VolatilityMetrics:
+    """Bandas de Bollinger y volatilidad rolling de cierres"""
+    symbol: str
+    ts: int
+    period: int
+    mean: float
+    std_dev: float
+    upper_band: float
+    lower_band: float
+    bandwidth: float
+    percent_b: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> VolatilityMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> VolatilityMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> VolatilityMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> VolatilityMetrics: ...
+
+class ATRMetrics:
+    """True range y ATR (Wilder) de un símbolo/timeframe para un periodo"""
+    symbol: str
+    tf: str
+    ts: int
+    period: int
+    true_range: float
+    atr: float | None
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> ATRMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> ATRMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> ATRMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> ATRMetrics: ...
+
+class OIMetrics:
+    """Métricas de open interest: cambio, precio medio de entrada y divergencias"""
+    symbol: str
+    ts: int
+    open_interest: float
+    oi_change: float
+    oi_change_rate: float
+    oi_weighted_price: float
+    window_oi_change_pct: float
+    window_price_change_pct: float
+    window_cvd_change: float
+    price_divergence: bool
+    cvd_divergence: bool
+    funding_rate: float | None
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> OIMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> OIMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> OIMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> OIMetrics: ...
+
+class LiquidationMetrics:
+    """Liquidaciones agregadas de un bucket temporal, con tiles por precio"""
+    symbol: str
+    bucket_ts: int
+    bucket_ms: int
+    long_notional: float
+    short_notional: float
+    tiles: list[Tile]
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LiquidationMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LiquidationMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LiquidationMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LiquidationMetrics: ...
+
+class LiquidationCascade:
+    """Alerta de cascada: nocional liquidado de un lado en la ventana supera el umbral"""
+    ts: int
+    symbol: str
+    side: str
+    notional: float
+    count: int
+    threshold: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LiquidationCascade: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LiquidationCascade: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LiquidationCascade: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LiquidationCascade: ...
+
+class VPINMetrics:
+    """VPIN (volume-synchronized probability of informed trading)"""
+    symbol: str
+    ts: int
+    vpin: float | None
+    buckets: int
+    bucket_imbalance: float
+    vpin_zscore: float | None
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> VPINMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> VPINMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> VPINMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> VPINMetrics: ...
+
+class TapeMetrics:
+    """Velocidad de la cinta en una ventana rolling"""
+    symbol: str
+    ts: int
+    window_ms: int
+    trade_count: int
+    trades_per_second: float
+    avg_trade_size: float
+    volume_per_second: float
+    notional_per_second: float
+    smoothed_trades_per_second: float | None
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> TapeMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> TapeMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> TapeMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> TapeMetrics: ...
+
+class LargePrint:
+    """Print de bloque: trade con tamaño por encima del percentil configurado"""
+    ts: int
+    symbol: str
+    price: float
+    size: float
+    side: str
+    size_threshold: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> LargePrint: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> LargePrint: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> LargePrint: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> LargePrint: ...
+
+class QuoteMetrics:
+    """Mejor bid/ask entre venues (estilo NBBO), ritmo de quotes y spread
+    medio ponderado por tiempo
+    """
+    ts: int
+    symbol: str
+    best_bid: float
+    best_bid_size: float
+    best_ask: float
+    best_ask_size: float
+    mid: float
+    spread: float
+    spread_bps: float
+    updates_per_second: float
+    twa_spread: float
+    venues: int
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> QuoteMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> QuoteMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> QuoteMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> QuoteMetrics: ...
+
+class SweepEvent:
+    """Barrido: ráfaga de trades a precios crecientes (compra) o decrecientes
+    (venta) que consume varios niveles del libro en poco tiempo
+    """
+    symbol: str
+    start_ts: int
+    end_ts: int
+    direction: str
+    trades: int
+    levels: int
+    start_price: float
+    end_price: float
+    volume: float
+    notional: float
+    vwap: float
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> SweepEvent: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> SweepEvent: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> SweepEvent: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> SweepEvent: ...
+
+class FlowSignal:
+    """Señal de flujo compuesta: CVD, imbalance del libro y velocidad de la
+    cinta combinados en un score en [-1, 1] (positivo = presión compradora)
+    """
+    symbol: str
+    ts: int
+    score: float
+    cvd_flow: float | None
+    book_imbalance: float | None
+    tape_intensity: float | None
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> FlowSignal: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> FlowSignal: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> FlowSignal: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> FlowSignal: ...
+
+class DataGapAlert:
+    """Hueco de datos: un símbolo sin trades o sin quotes durante más del
+    intervalo máximo en horario de mercado
+    """
+    symbol: str
+    stream: str
+    last_ts: int
+    ts: int
+    gap_ms: int
+    resumed: bool
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> DataGapAlert: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> DataGapAlert: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> DataGapAlert: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> DataGapAlert: ...
+
+class WallEvent:
+    """Evento de un muro de liquidez: nivel significativo que aparece, se
+    repone o desaparece del libro
+    """
+    ts: int
+    symbol: str
+    side: str
+    price: float
+    event: str
+    size: float
+    peak_size: float
+    first_seen_ts: int
+    lifetime_ms: int
+    refreshes: int
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> WallEvent: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> WallEvent: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> WallEvent: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> WallEvent: ...
+
+class SpreadMetrics:
+    """Valor de un instrumento sintético (combinación ponderada de símbolos) con
+    su z-score sobre la ventana y la correlación de retornos de las dos
+    primeras patas
+    """
+    name: str
+    ts: int
+    value: float
+    mean: float
+    std: float
+    zscore: float | None
+    correlation: float | None
+    samples: int
+    leg_prices: list[float]
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> SpreadMetrics: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> SpreadMetrics: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> SpreadMetrics: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> SpreadMetrics: ...
+
+class MetricsBundle:
+    """Resultado combinado de despachar un evento a todos los engines del manager"""
+    symbol: str
+    ts: int
+    cvd: CVDMetrics | None
+    cvd_bar: CVDBar | None
+    footprint: FootprintBar | None
+    vwap: VWAPMetrics | None
+    rolling_vwap: VWAPMetrics | None
+    volume_profile: VolumeProfileMetrics | None
+    liquidity: LiquidityMetrics | None
+    heatmap: HeatmapMetrics | None
+    book_pressure: BookPressureMetrics | None
+    icebergs: list[IcebergDetection]
+    moving_averages: list[MovingAverageMetrics]
+    rsi: list[RSIMetrics]
+    atr: list[ATRMetrics]
+    volatility: list[VolatilityMetrics]
+    tape: TapeMetrics | None
+    vpin: VPINMetrics | None
+    enriched_trade: EnrichedTrade | None
+    sweep: SweepEvent | None
+    absorptions: list[AbsorptionEvent]
+    walls: list[WallEvent]
+    spreads: list[SpreadMetrics]
+    data_gaps: list[DataGapAlert]
+    flow_signal: FlowSignal | None
+    custom: dict[str, dict[str, float]]
+    def __repr__(self) -> str: ...
+    def to_dict(self) -> Any:
+        """Dict con los campos (mismos nombres que el JSON)"""
+    @staticmethod
+    def from_dict(data: Any) -> MetricsBundle: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> MetricsBundle: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> MetricsBundle: ...
+    def __reduce__(self) -> tuple[Any, tuple[str, Any]]: ...
+    def __deepcopy__(self, _memo: Any) -> MetricsBundle: ...
+
+class ProcessingError(ValueError):
+    """Evento rechazado por un engine"""
+
+class InvalidPriceError(ProcessingError):
+    """Precio no finito o <= 0"""
+
+class InvalidSizeError(ProcessingError):
+    """Tamaño no finito o <= 0"""
+
+class StaleTimestampError(ProcessingError):
+    """Timestamp anterior al último procesado"""
+
+class CrossedBookError(ProcessingError):
+    """Libro cruzado (bid >= ask)"""
+
+class EmptyBookError(ProcessingError):
+    """Libro sin niveles"""
+
+class MalformedBookError(ProcessingError):
+    """Niveles duplicados o desordenados"""
+
+class CVDEngine:
+    """Engine para calcular CVD (Cumulative Volume Delta)"""
+    def __init__(self) -> None: ...
+    def set_reset_schedule(self, period_ms: int = 86400000, offset_ms: int = 0) -> None:
+        """Configura el reset automático del CVD al inicio de cada sesión.
+        Por defecto diario a las 00:00 UTC; offset_ms desplaza la apertura.
+        """
+    def clear_reset_schedule(self) -> None:
+        """Desactiva el reset automático"""
+    @property
+    def reset_schedule(self) -> tuple[int, int] | None:
+        """Calendario de reset activo como (period_ms, offset_ms)"""
+    @property
+    def session_calendar(self) -> SessionCalendar | None:
+        """Calendario de mercado activo"""
+    @session_calendar.setter
+    def session_calendar(self, calendar: SessionCalendar | None) -> None: ...
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> CVDMetrics | None:
+        """Procesa un trade y calcula CVD. En modo `strict` un trade inválido lanza
+        InvalidPriceError / InvalidSizeError; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def set_fixed_point(self, price_decimals: int, size_decimals: int, symbol: str | None = None) -> None:
+        """Acumula los volúmenes en punto fijo redondeando los tamaños a
+        `size_decimals` (global o solo para `symbol`). Resetea el estado afectado.
+        """
+    def disable_fixed_point(self, symbol: str | None = None) -> None:
+        """Vuelve a acumular en f64 (global o solo para `symbol`); resetea el estado afectado"""
+    def get_fixed_point(self, symbol: str) -> tuple[int, int] | None:
+        """Precisión (price_decimals, size_decimals) del símbolo; None si acumula en f64"""
+    def get_cvd_decimal(self, symbol: str) -> Any | None:
+        """CVD exacto como `decimal.Decimal`; None si no hay estado o el símbolo
+        acumula en f64
+        """
+    def get_cvd(self, symbol: str) -> float | None:
+        """Obtiene el CVD actual para un símbolo"""
+    def get_metrics(self, symbol: str) -> CVDMetrics | None:
+        """Métricas completas del símbolo (como las del último on_trade): CVD,
+        volúmenes, número de trades y lado, tamaño y ts del último trade
+        """
+    def symbols(self) -> list[str]:
+        """Símbolos con estado, ordenados"""
+    def get_all(self) -> dict[str, CVDMetrics]:
+        """Métricas actuales de todos los símbolos (symbol -> CVDMetrics)"""
+    def get_cvd_by_exchange(self, symbol: str) -> dict[str, float]:
+        """CVD del símbolo desglosado por exchange ("UNKNOWN" si el trade no lo informa)"""
+    def get_cvd_aggregate(self, symbol: str) -> float | None:
+        """CVD agregado de todos los exchanges del símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el CVD para un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def on_trade_numpy(self, symbol: str, ts: numpy.typing.NDArray[numpy.int64], price: numpy.typing.NDArray[numpy.float64], size: numpy.typing.NDArray[numpy.float64], side: numpy.typing.NDArray[numpy.int8] | None = None) -> dict[Any, Any]:
+        """Procesa arrays numpy de un símbolo; devuelve dict de arrays (cvd, buy_volume,
+        sell_volume, side) con una fila por trade
+        """
+    def on_trade_arrow(self, batch: Any, columns: dict[str, str] | None = None, symbol: str | None = None) -> Any:
+        """Procesa un `pyarrow.RecordBatch` de trades en Rust y devuelve otro
+        RecordBatch con una fila por trade (nulos en trades descartados)
+        """
+    def __repr__(self) -> str: ...
+
+class CVDBarEngine:
+    """Engine de barras de CVD por símbolo"""
+    @property
+    def tf(self) -> str: ...
+    @property
+    def bucket_ms(self) -> int: ...
+    def __init__(self, tf: str) -> None: ...
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> CVDBar | None:
+        """Procesa un trade; devuelve la barra anterior si el bucket ha cambiado.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_current_bar(self, symbol: str) -> CVDBar | None:
+        """Obtiene la barra en curso (aún no cerrada) de un símbolo"""
+    def symbols(self) -> list[str]:
+        """Símbolos con estado, ordenados"""
+    def get_all(self) -> dict[str, CVDBar]:
+        """Barras en curso de todos los símbolos (symbol -> CVDBar)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class FootprintEngine:
+    """Engine de barras footprint por símbolo"""
+    @property
+    def tf(self) -> str: ...
+    @property
+    def bucket_ms(self) -> int: ...
+    def __init__(self, tf: str, tick_size: float = 0.01) -> None: ...
+    @property
+    def tick_registry(self) -> TickSizeRegistry:
+        """Registro de tick sizes por símbolo (compartible con otros engines)"""
+    @tick_registry.setter
+    def tick_registry(self, registry: TickSizeRegistry) -> None: ...
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> FootprintBar | None:
+        """Procesa un trade; devuelve la barra anterior si el bucket ha cambiado.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_current_bar(self, symbol: str) -> FootprintBar | None:
+        """Obtiene la barra en curso (aún no cerrada) de un símbolo"""
+    def symbols(self) -> list[str]:
+        """Símbolos con estado, ordenados"""
+    def get_all(self) -> dict[str, FootprintBar]:
+        """Barras en curso de todos los símbolos (symbol -> FootprintBar)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class LiquidityEngine:
+    """Engine para calcular métricas de liquidez del libro de órdenes"""
+    depth_levels: int
+    near_touch_levels: int
+    top_levels: int
+    notional: bool
+    rolling_windows_ms: list[int]
+    def __init__(self, depth_levels: int = ...) -> None: ...
+    def set_alert_config(self, config: LiquidityAlertConfig, symbol: str | None = None) -> None:
+        """Configura alertas globales o, si se indica símbolo, solo para ese símbolo"""
+    def clear_alert_config(self, symbol: str | None = None) -> None:
+        """Desactiva las alertas globales o las de un símbolo"""
+    def drain_alerts(self) -> list[LiquidityAlert]:
+        """Devuelve y vacía las alertas pendientes"""
+    def set_smoothing(self, half_life_ms: int | None = None, half_life_events: float | None = None) -> None:
+        """Activa `smoothed_spread` y `smoothed_depth_imbalance` con vida media
+        en ms (ponderada por tiempo) o en snapshots; sin argumentos lo
+        desactiva. Reinicia las medias.
+        """
+    def smoothing(self) -> tuple[int | None, float | None]:
+        """(half_life_ms, half_life_events) del suavizado"""
+    def get_rolling_stats(self, symbol: str) -> list[LiquidityRollingStats]:
+        """Medias móviles del símbolo, una por ventana configurada"""
+    def on_quote(self, quote: Quote) -> None:
+        """Registra la quote vigente del símbolo para el spread cotizado y el
+        cruce con trades. En modo `strict` una quote inválida lanza su excepción
+        """
+    def on_trade(self, trade: Trade) -> EffectiveSpreadMetrics | None:
+        """Cruza el trade con la quote vigente y devuelve su spread efectivo
+        (None si aún no hay quote del símbolo o el trade no es válido)
+        """
+    def get_spread_stats(self, symbol: str, now_ts: int | None = None) -> list[SpreadWindowStats]:
+        """Spread cotizado ponderado por tiempo y spread efectivo medio del
+        símbolo, una entrada por ventana configurada, hasta `now_ts` (por
+        defecto el último evento visto)
+        """
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def on_snapshot(self, snapshot: BookSnapshot) -> LiquidityMetrics | None:
+        """Procesa un snapshot del libro y calcula métricas de liquidez.
+        Un libro inválido lanza su excepción en modo `strict` o con
+        `book_policy = "raise"`; si no, devuelve None
+        """
+    def on_snapshot_with_depth(self, snapshot: BookSnapshot, levels: int) -> LiquidityMetrics | None:
+        """Métricas del snapshot con `levels` niveles por lado en lugar de
+        `depth_levels`, sin tocar el estado del engine (medias móviles,
+        alertas, snapshot anterior ni rechazos). Sirve para consultar otra
+        profundidad del mismo snapshot ya procesado con `on_snapshot`
+        """
+    @property
+    def book_policy(self) -> str:
+        """Política ante libros inválidos: "skip" (descartar y contar), "alert"
+        (emitir BookQualityAlert y procesar si hay ambos lados) o "raise\"
+        """
+    @book_policy.setter
+    def book_policy(self, policy: str) -> None: ...
+    def drain_book_alerts(self) -> list[BookQualityAlert]:
+        """Devuelve y vacía las alertas de calidad del libro pendientes"""
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def __repr__(self) -> str: ...
+
+class HeatmapEngine:
+    """Engine para calcular heatmap del libro de órdenes"""
+    bucket_ms: int
+    max_buckets: int | None
+    retention_ms: int | None
+    half_life_ms: int | None
+    relative_bps: float | None
+    notional: bool
+    min_tile_pct: float
+    top_k: int | None
+    def __init__(self, bucket_ms: int = 1000, tick_size: float = 0.01, retention_buckets: int | None = None) -> None:
+        """`retention_buckets` fija `max_buckets` (buckets conservados por símbolo)"""
+    @property
+    def tick_registry(self) -> TickSizeRegistry:
+        """Registro de tick sizes por símbolo (compartible con otros engines)"""
+    @tick_registry.setter
+    def tick_registry(self, registry: TickSizeRegistry) -> None: ...
+    def set_symbol_tick_size(self, symbol: str, tick_size: float) -> None:
+        """Configura el tick de un símbolo; limpia su histórico porque cambia la cuantización"""
+    def get_tick_size(self, symbol: str) -> float:
+        """Tick size efectivo de un símbolo"""
+    @property
+    def normalization(self) -> str:
+        """Normalización de `Tile.value`: "raw", "percent_max", "percent_side" o "log\""""
+    @normalization.setter
+    def normalization(self, normalization: str) -> None: ...
+    def set_fixed_point(self, price_decimals: int, size_decimals: int, symbol: str | None = None) -> None:
+        """Cuantiza precios y acumula tamaños en punto fijo con `price_decimals` /
+        `size_decimals` (global o solo para `symbol`). Limpia el grid afectado.
+        """
+    def disable_fixed_point(self, symbol: str | None = None) -> None:
+        """Vuelve a f64 (global o solo para `symbol`); limpia el grid afectado"""
+    def get_fixed_point(self, symbol: str) -> tuple[int, int] | None:
+        """Precisión (price_decimals, size_decimals) del símbolo; None si acumula en f64"""
+    def get_tiles_decimal(self, symbol: str, bucket_ts: int) -> list[tuple[Any, Any, str]]:
+        """Tiles exactos de (symbol, bucket) como (price_bin, total_size, side) con
+        `decimal.Decimal`, ordenados por precio (por bps en modo relativo).
+        Vacío si el símbolo acumula en f64 o en modo decay.
+        """
+    def on_snapshot(self, snapshot: BookSnapshot) -> HeatmapMetrics | None:
+        """Procesa un snapshot del libro y calcula heatmap.
+        En modo `strict` un libro vacío lanza EmptyBookError; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_heatmap(self, symbol: str, bucket_ts: int) -> HeatmapMetrics | None:
+        """Heatmap comprimido de un símbolo en un bucket (None si no hay datos)"""
+    def get_heatmap_range(self, symbol: str, from_ts: int, to_ts: int, bucket_ms: int | None = None) -> list[HeatmapMetrics]:
+        """Heatmaps de todos los buckets retenidos del símbolo entre from_ts y
+        to_ts, en orden temporal (backfill de un chart en una sola llamada).
+        Con `bucket_ms` (múltiplo del bucket del símbolo) los buckets se
+        agregan sumando sus celdas en buckets más gruesos.
+        """
+    def to_matrix(self, symbol: str, from_bucket: int, to_bucket: int, price_min: float, price_max: float, side: str | None = None) -> list[list[float]]:
+        """Exporta el heatmap de un símbolo como matriz densa (tiempo × precio).
+        Filas: buckets de from_bucket a to_bucket; columnas: ticks de price_min a
+        price_max (ambos inclusive; en bps respecto al mid en modo relativo).
+        `side` = "bid" | "ask" | None (suma de ambos).
+        """
+    def memory_usage(self) -> int:
+        """Memoria aproximada ocupada por el grid (bytes)"""
+    def reset(self) -> None:
+        """Limpia todos los buckets"""
+    def reset_bucket(self, bucket_ts: int, symbol: str | None = None) -> None:
+        """Limpia un bucket específico (de un símbolo o de todos)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea todos los buckets de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def get_tile_delta(self, symbol: str, bucket_ts: int) -> list[Tile]:
+        """Obtiene solo tiles incrementales: los que cambiaron de tamaño (o son
+        nuevos) desde el último mark_published del bucket
+        """
+    def mark_published(self, bucket_ts: int, symbol: str | None = None) -> None:
+        """Registra el estado actual del bucket como publicado (de un símbolo o de todos)"""
+    def __repr__(self) -> str: ...
+
+class IcebergDetector:
+    """Detector de icebergs por símbolo"""
+    @property
+    def min_ratio(self) -> float:
+        """Ratio mínimo ejecutado / máximo mostrado para marcar un iceberg"""
+    @property
+    def min_reloads(self) -> int:
+        """Reposiciones mínimas observadas"""
+    @property
+    def tick_size(self) -> float: ...
+    def __init__(self, min_ratio: float = 2.0, min_reloads: int = 2, tick_size: float = 0.01) -> None: ...
+    def on_trade(self, trade: Trade) -> None:
+        """Atribuye el volumen del trade al nivel visible con el mismo precio"""
+    def on_snapshot(self, snapshot: BookSnapshot) -> list[IcebergDetection]:
+        """Actualiza los niveles con el snapshot y devuelve las nuevas detecciones"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class AbsorptionDetector:
+    """Detector de absorción por símbolo"""
+    @property
+    def min_volume(self) -> float:
+        """Volumen agresivo mínimo absorbido por el nivel"""
+    @property
+    def window_ms(self) -> int:
+        """Separación máxima entre trades de un mismo episodio (ms)"""
+    @property
+    def tick_size(self) -> float: ...
+    def __init__(self, min_volume: float = 100.0, window_ms: int = 5000, tick_size: float = 0.01) -> None: ...
+    def on_trade(self, trade: Trade) -> None:
+        """Atribuye el volumen del trade al mejor nivel que golpea. El lado sale
+        del trade o, si no viene, del nivel del libro en el que se ejecutó
+        """
+    def on_snapshot(self, snapshot: BookSnapshot) -> list[AbsorptionEvent]:
+        """Actualiza los mejores niveles y devuelve las absorciones confirmadas"""
+    def get_absorbed_volume(self, symbol: str, side: str) -> float | None:
+        """Volumen absorbido en el episodio abierto de un lado ("bid" | "ask")"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class BookPressureEngine:
+    """Engine de presión del libro por símbolo"""
+    @property
+    def window_ms(self) -> int: ...
+    @property
+    def half_life_ms(self) -> int | None: ...
+    @property
+    def depth_levels(self) -> int: ...
+    def __init__(self, window_ms: int = 10000, half_life_ms: int | None = None, depth_levels: int = 10) -> None: ...
+    def on_snapshot(self, snapshot: BookSnapshot) -> BookPressureMetrics | None:
+        """Procesa un snapshot y devuelve la presión actualizada.
+        En modo `strict` un libro vacío lanza EmptyBookError; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_pressure(self, symbol: str) -> float | None:
+        """Presión actual del símbolo (Σ imbalance × dt en la ventana)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class VWAPEngine:
+    """Engine para calcular VWAP por símbolo"""
+    band_multipliers: tuple[float, float, float]
+    def __init__(self) -> None: ...
+    @property
+    def session_calendar(self) -> SessionCalendar | None:
+        """Calendario de mercado activo"""
+    @session_calendar.setter
+    def session_calendar(self, calendar: SessionCalendar | None) -> None: ...
+    def on_trade(self, trade: Trade) -> VWAPMetrics | None:
+        """Procesa un trade y actualiza VWAP.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def on_bar(self, bar: Bar) -> VWAPMetrics | None:
+        """Procesa una barra y actualiza VWAP usando typical price"""
+    def get_vwap(self, symbol: str) -> float | None:
+        """Obtiene el VWAP actual para un símbolo"""
+    def get_metrics(self, symbol: str) -> VWAPMetrics | None:
+        """Métricas completas del VWAP de sesión del símbolo (sumas, bandas,
+        sesión y último update), las mismas que devolvió el último on_trade
+        """
+    def symbols(self) -> list[str]:
+        """Símbolos con VWAP de sesión o anclas, ordenados"""
+    def get_all(self) -> dict[str, VWAPMetrics]:
+        """Métricas de sesión de todos los símbolos que la tienen (symbol -> VWAPMetrics)"""
+    def set_fixed_point(self, price_decimals: int, size_decimals: int, symbol: str | None = None) -> None:
+        """Acumula pv_sum y v_sum en punto fijo redondeando precios a
+        `price_decimals` y tamaños a `size_decimals` (global o solo para
+        `symbol`). Resetea el estado afectado; las anclas vuelven a acumular
+        desde cero.
+        """
+    def set_tick_arithmetic(self, price_tick: float, size_lot: float, symbol: str | None = None) -> None:
+        """Acumula pv_sum y v_sum en enteros exactos: precios en ticks de
+        `price_tick` y tamaños en lotes de `size_lot` (global o solo para
+        `symbol`). Pensado para backtesting con datos en múltiplos del tick;
+        sustituye al punto fijo y resetea el estado afectado como `set_fixed_point`.
+        """
+    def get_tick_arithmetic(self, symbol: str) -> tuple[float, float] | None:
+        """(price_tick, size_lot) del símbolo; None si no acumula en ticks"""
+    def disable_fixed_point(self, symbol: str | None = None) -> None:
+        """Vuelve a acumular en f64, sin punto fijo ni ticks (global o solo para
+        `symbol`); resetea el estado afectado
+        """
+    def get_fixed_point(self, symbol: str) -> tuple[int, int] | None:
+        """Precisión (price_decimals, size_decimals) del símbolo; None si acumula en f64"""
+    def get_sums_decimal(self, symbol: str) -> tuple[Any, Any] | None:
+        """(pv_sum, v_sum) exactos como `decimal.Decimal`; None si no hay estado
+        o el símbolo acumula en f64
+        """
+    def anchor(self, symbol: str, anchor_ts: int) -> None:
+        """Registra un ancla para el símbolo; los trades con ts >= anchor_ts acumulan"""
+    def remove_anchor(self, symbol: str, anchor_ts: int) -> bool:
+        """Elimina un ancla; devuelve true si existía"""
+    def get_anchored_vwap(self, symbol: str, anchor_ts: int) -> float | None:
+        """Obtiene el VWAP anclado para un símbolo y ancla concretos"""
+    def get_anchored_vwaps(self, symbol: str) -> list[VWAPMetrics]:
+        """Obtiene las métricas de todas las anclas activas del símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el VWAP para un símbolo (incluye sus anclas)"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def on_trade_batch(self, trades: list[Trade]) -> list[VWAPMetrics]:
+        """Calcula VWAP en batch; libera el GIL mientras tanto"""
+    def on_trade_numpy(self, symbol: str, ts: numpy.typing.NDArray[numpy.int64], price: numpy.typing.NDArray[numpy.float64], size: numpy.typing.NDArray[numpy.float64], side: numpy.typing.NDArray[numpy.int8] | None = None) -> dict[Any, Any]:
+        """Procesa arrays numpy de un símbolo; devuelve dict de arrays (vwap, v_sum,
+        std_dev, bandas) con una fila por trade
+        """
+    def on_trade_arrow(self, batch: Any, columns: dict[str, str] | None = None, symbol: str | None = None) -> Any:
+        """Procesa un `pyarrow.RecordBatch` de trades en Rust y devuelve otro
+        RecordBatch con una fila por trade (nulos en trades descartados)
+        """
+    def __repr__(self) -> str: ...
+
+class RollingVWAPEngine:
+    """Engine de VWAP sobre ventana deslizante por símbolo"""
+    @property
+    def window_ms(self) -> int | None:
+        """Ventana temporal en ms (None = sin límite temporal)"""
+    @property
+    def window_volume(self) -> float | None:
+        """Ventana de volumen en contratos (None = sin límite de volumen)"""
+    def __init__(self, window_ms: int | None = None, window_volume: float | None = None) -> None: ...
+    def on_trade(self, trade: Trade) -> VWAPMetrics | None:
+        """Procesa un trade, desaloja lo que sale de la ventana y devuelve el VWAP.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_vwap(self, symbol: str) -> float | None:
+        """Obtiene el VWAP de la ventana actual para un símbolo"""
+    def window_len(self, symbol: str) -> int:
+        """Número de trades retenidos en la ventana del símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea la ventana de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class VolumeProfileEngine:
+    """Engine de perfil de volumen por símbolo"""
+    @property
+    def value_area_pct(self) -> float: ...
+    notional: bool
+    def __init__(self, value_area_pct: float = 0.7, tick_size: float = 0.01) -> None: ...
+    @property
+    def tick_registry(self) -> TickSizeRegistry:
+        """Registro de tick sizes por símbolo (compartible con HeatmapEngine)"""
+    @tick_registry.setter
+    def tick_registry(self, registry: TickSizeRegistry) -> None: ...
+    def set_reset_schedule(self, period_ms: int = 86400000, offset_ms: int = 0) -> None:
+        """Configura el perfil por sesión (por defecto diario a las 00:00 UTC)"""
+    def clear_reset_schedule(self) -> None:
+        """Desactiva las sesiones: el perfil se acumula hasta un reset explícito"""
+    @property
+    def reset_schedule(self) -> tuple[int, int] | None:
+        """Calendario de sesiones activo como (period_ms, offset_ms)"""
+    def on_trade(self, trade: Trade) -> VolumeProfileMetrics | None:
+        """Procesa un trade y devuelve el perfil actualizado.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_profile(self, symbol: str) -> VolumeProfileMetrics | None:
+        """Perfil actual de un símbolo"""
+    def symbols(self) -> list[str]:
+        """Símbolos con estado, ordenados"""
+    def get_all(self) -> dict[str, VolumeProfileMetrics]:
+        """Perfiles actuales de todos los símbolos (symbol -> VolumeProfileMetrics)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el perfil de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los perfiles"""
+    def __repr__(self) -> str: ...
+
+class MovingAverageEngine:
+    """Engine de medias móviles por símbolo"""
+    @property
+    def periods(self) -> list[int]: ...
+    def __init__(self, periods: list[int] = ...) -> None: ...
+    def on_bar(self, bar: Bar) -> list[MovingAverageMetrics]:
+        """Procesa una barra (usa el cierre)"""
+    def on_trade(self, trade: Trade) -> list[MovingAverageMetrics]:
+        """Procesa un trade (usa el precio)"""
+    def on_trade_numpy(self, symbol: str, ts: numpy.typing.NDArray[numpy.int64], price: numpy.typing.NDArray[numpy.float64], size: numpy.typing.NDArray[numpy.float64]) -> dict[Any, Any]:
+        """Procesa arrays numpy de un símbolo; devuelve dict de arrays sma_{p}, ema_{p}
+        y wma_{p} por periodo con una fila por trade
+        """
+    def get_ma(self, symbol: str, kind: str, period: int) -> float | None:
+        """Media actual de un símbolo: kind = "SMA" | "EMA" | "WMA\""""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class RSIEngine:
+    """Engine de RSI por símbolo"""
+    @property
+    def periods(self) -> list[int]: ...
+    def __init__(self, periods: list[int] = ...) -> None: ...
+    def on_bar(self, bar: Bar) -> list[RSIMetrics]:
+        """Procesa una barra (usa el cierre)"""
+    def on_bar_batch(self, bars: list[Bar]) -> list[RSIMetrics]:
+        """Procesa barras históricas en orden (backfill); libera el GIL mientras tanto"""
+    def get_rsi(self, symbol: str, period: int) -> float | None:
+        """RSI actual de un símbolo para un periodo"""
+    @staticmethod
+    def compute_batch(closes: list[float], period: int = 14) -> list[float | None]:
+        """RSI de una serie de cierres sin tocar el estado (None en el warm-up);
+        libera el GIL mientras tanto
+        """
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class VolatilityEngine:
+    """Engine de volatilidad por símbolo"""
+    @property
+    def periods(self) -> list[int]: ...
+    @property
+    def num_std(self) -> float: ...
+    @property
+    def sample_interval_ms(self) -> int: ...
+    @property
+    def rv_window(self) -> int: ...
+    def __init__(self, periods: list[int] = ..., num_std: float = 2.0, sample_interval_ms: int = 1000, rv_window: int = 60) -> None: ...
+    def on_bar(self, bar: Bar) -> list[VolatilityMetrics]:
+        """Procesa una barra; devuelve las bandas de los periodos ya completos"""
+    def on_trade(self, trade: Trade) -> float | None:
+        """Procesa un trade; devuelve la volatilidad realizada cuando se cierra una muestra"""
+    def get_realized_vol(self, symbol: str) -> float | None:
+        """Volatilidad realizada actual: sqrt(Σ r²) sobre los últimos rv_window retornos"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class ATREngine:
+    """Engine de ATR por símbolo y timeframe"""
+    @property
+    def periods(self) -> list[int]: ...
+    def __init__(self, periods: list[int] = ...) -> None: ...
+    def on_bar(self, bar: Bar) -> list[ATRMetrics]:
+        """Procesa una barra del stream (symbol/tf salen de la barra)"""
+    def on_bar_batch(self, bars: list[Bar]) -> list[ATRMetrics]:
+        """Procesa barras históricas en orden (backfill); libera el GIL mientras tanto"""
+    def get_atr(self, symbol: str, tf: str, period: int) -> float | None:
+        """ATR actual de un símbolo/timeframe para un periodo"""
+    @staticmethod
+    def compute_batch(highs: list[float], lows: list[float], closes: list[float], period: int = 14) -> list[float | None]:
+        """ATR de una serie (high, low, close) sin tocar el estado (None en el
+        warm-up); libera el GIL mientras tanto
+        """
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo (todos sus timeframes)"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class OIEngine:
+    """Engine de open interest por símbolo"""
+    @property
+    def window_ms(self) -> int: ...
+    def __init__(self, window_ms: int = 300000) -> None: ...
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> None:
+        """Procesa un trade: actualiza último precio y CVD del símbolo"""
+    def on_funding(self, funding: FundingRate) -> None:
+        """Registra el último funding rate del símbolo"""
+    def on_open_interest(self, oi: OpenInterest) -> OIMetrics | None:
+        """Procesa una lectura de OI y devuelve las métricas actualizadas"""
+    def get_open_interest(self, symbol: str) -> float | None:
+        """OI total actual de un símbolo"""
+    def get_funding_rate(self, symbol: str) -> float | None:
+        """Último funding rate de un símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class LiquidationEngine:
+    """Engine de liquidaciones por símbolo"""
+    @property
+    def bucket_ms(self) -> int: ...
+    @property
+    def retention_ms(self) -> int: ...
+    @property
+    def cascade_window_ms(self) -> int: ...
+    @property
+    def cascade_threshold(self) -> float | None: ...
+    def __init__(self, bucket_ms: int = 60000, tick_size: float = 1.0, retention_ms: int = 3600000, cascade_window_ms: int = 10000, cascade_threshold: float | None = None) -> None: ...
+    @property
+    def tick_registry(self) -> TickSizeRegistry:
+        """Registro de tick sizes por símbolo (compartible con otros engines)"""
+    @tick_registry.setter
+    def tick_registry(self, registry: TickSizeRegistry) -> None: ...
+    def on_liquidation(self, liq: Liquidation) -> LiquidationMetrics | None:
+        """Procesa una liquidación y devuelve el bucket actualizado"""
+    def get_bucket(self, symbol: str, bucket_ts: int) -> LiquidationMetrics | None:
+        """Liquidaciones de un símbolo en un bucket"""
+    def get_window_notional(self, symbol: str) -> tuple[float, float]:
+        """Nocional liquidado en la ventana de cascada actual: (long, short)"""
+    def drain_alerts(self) -> list[LiquidationCascade]:
+        """Extrae las alertas de cascada pendientes"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class VPINEngine:
+    """Engine de VPIN por símbolo"""
+    @property
+    def bucket_volume(self) -> float: ...
+    @property
+    def window(self) -> int: ...
+    def __init__(self, bucket_volume: float, window: int = 50) -> None: ...
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> VPINMetrics | None:
+        """Procesa un trade; devuelve métricas si completa al menos un bucket.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_vpin(self, symbol: str) -> float | None:
+        """VPIN actual de un símbolo (None hasta completar la ventana)"""
+    def get_vpin_stats(self, symbol: str) -> StreamingStats | None:
+        """Distribución de VPIN del símbolo en la sesión (un valor por bucket
+        completado con la ventana llena)
+        """
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class TapeEngine:
+    """Engine de analítica de cinta por símbolo"""
+    @property
+    def window_ms(self) -> int: ...
+    @property
+    def percentile(self) -> float: ...
+    @property
+    def lookback(self) -> int: ...
+    @property
+    def min_samples(self) -> int: ...
+    def __init__(self, window_ms: int = 10000, percentile: float = 0.99, lookback: int = 1000, min_samples: int = 100) -> None: ...
+    def set_smoothing(self, half_life_ms: int | None = None, half_life_events: float | None = None) -> None:
+        """Activa `smoothed_trades_per_second` con vida media en ms (ponderada
+        por tiempo) o en trades; sin argumentos lo desactiva. Reinicia la media.
+        """
+    def smoothing(self) -> tuple[int | None, float | None]:
+        """(half_life_ms, half_life_events) del suavizado"""
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> TapeMetrics | None:
+        """Procesa un trade y devuelve la velocidad de la cinta.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_size_threshold(self, symbol: str) -> float | None:
+        """Umbral de tamaño actual para prints de bloque"""
+    def get_size_quantiles(self, symbol: str, quantiles: list[float] = ...) -> list[float] | None:
+        """Cuantiles del tamaño de trade en la sesión (por defecto p50, p95 y
+        p99, con error relativo del 1%); None sin trades del símbolo
+        """
+    def get_size_sketch(self, symbol: str) -> QuantileSketch | None:
+        """Copia del sketch de tamaños de la sesión (p. ej. para combinar símbolos)"""
+    def drain_large_prints(self) -> list[LargePrint]:
+        """Extrae los prints de bloque pendientes"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class QuoteEngine:
+    """Engine de NBBO, ritmo de quotes y spread ponderado por tiempo"""
+    @property
+    def window_ms(self) -> int: ...
+    def __init__(self, window_ms: int = 1000) -> None: ...
+    def on_quote(self, quote: Quote) -> QuoteMetrics | None:
+        """Procesa una quote y devuelve el NBBO y sus métricas.
+        En modo `strict` una quote inválida lanza su excepción; si no, devuelve None
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def get_nbbo(self, symbol: str) -> tuple[float, float, float, float] | None:
+        """NBBO actual como (bid, bid_size, ask, ask_size)"""
+    def get_spread(self, symbol: str) -> float | None:
+        """Spread actual del NBBO"""
+    def get_twa_spread(self, symbol: str) -> float | None:
+        """Spread medio ponderado por tiempo desde la primera quote"""
+    def get_venues(self, symbol: str) -> list[str]:
+        """Venues con quote vigente"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class TradeEnricher:
+    """Enriquecedor de trades con el contexto del libro por símbolo"""
+    def __init__(self) -> None: ...
+    def on_quote(self, quote: Quote) -> None:
+        """Registra la quote vigente. En modo `strict` una quote inválida lanza
+        su excepción
+        """
+    def on_snapshot(self, snapshot: BookSnapshot) -> None:
+        """Registra el libro vigente. En modo `strict` un libro inválido lanza
+        su excepción
+        """
+    def enrich(self, trade: Trade) -> EnrichedTrade | None:
+        """Anota el trade con el contexto del libro vigente.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    def enrich_batch(self, trades: list[Trade]) -> list[EnrichedTrade]:
+        """Anota una lista de trades en orden; los inválidos se descartan"""
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class SweepDetector:
+    """Detector de barridos por símbolo"""
+    @property
+    def window_ms(self) -> int:
+        """Separación máxima entre trades consecutivos de una ráfaga (ms)"""
+    @property
+    def min_levels(self) -> int: ...
+    @property
+    def min_notional(self) -> float: ...
+    def __init__(self, window_ms: int = 50, min_levels: int = 3, min_notional: float = 0.0) -> None: ...
+    def on_trade(self, trade: Trade) -> SweepEvent | None:
+        """Procesa un trade y devuelve el barrido que este cierra, si lo hay.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+        """
+    def flush(self, now_ts: int | None = None) -> list[SweepEvent]:
+        """Cierra las ráfagas sin trades desde hace más de `window_ms` a `now_ts`
+        (todas si es None) y devuelve los barridos resultantes
+        """
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class FlowSignalEngine:
+    """Engine de señal de flujo compuesta por símbolo"""
+    @property
+    def window_ms(self) -> int: ...
+    def __init__(self, window_ms: int = 60000, cvd_weight: float = 1.0, imbalance_weight: float = 1.0, tape_weight: float = 0.5) -> None: ...
+    def set_weights(self, cvd_weight: float, imbalance_weight: float, tape_weight: float) -> None:
+        """Pesos de los componentes: finitos, >= 0 y no todos 0"""
+    @property
+    def weights(self) -> tuple[float, float, float]:
+        """Pesos activos como (cvd, imbalance, tape)"""
+    def on_cvd(self, symbol: str, metrics: CVDMetrics) -> FlowSignal | None:
+        """Incorpora el trade reflejado en unas métricas de CVD del símbolo"""
+    def on_liquidity(self, symbol: str, ts: int, metrics: LiquidityMetrics) -> FlowSignal | None:
+        """Incorpora el imbalance de profundidad de un snapshot del símbolo"""
+    def on_tape(self, metrics: TapeMetrics) -> FlowSignal | None:
+        """Incorpora la velocidad de la cinta"""
+    def on_bundle(self, bundle: MetricsBundle) -> FlowSignal | None:
+        """Incorpora las métricas presentes en un resultado del `EngineManager`"""
+    def get_signal(self, symbol: str) -> FlowSignal | None:
+        """Señal actual del símbolo sin incorporar métricas nuevas"""
+    def symbols(self) -> list[str]:
+        """Símbolos con estado, ordenados"""
+    def get_all(self) -> dict[str, FlowSignal]:
+        """Señales actuales de los símbolos que ya tienen alguna (symbol -> FlowSignal)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class SpreadEngine:
+    """Engine de spreads entre símbolos"""
+    @property
+    def window(self) -> int:
+        """Muestras en la ventana de media, z-score y correlación"""
+    def __init__(self, window: int = 100) -> None: ...
+    def add_spread(self, name: str, legs: list[tuple[str, float]], kind: str = "difference") -> None:
+        """Define (o redefine, descartando su histórico) un spread a partir de
+        sus patas (símbolo, peso) y el tipo: "difference" o "ratio\"
+        """
+    def remove_spread(self, name: str) -> bool:
+        """Elimina un spread; false si no existía"""
+    def get_spreads(self) -> list[str]:
+        """Nombres de los spreads definidos, ordenados"""
+    def get_definition(self, name: str) -> tuple[list[tuple[str, float]], str] | None:
+        """Patas y tipo de un spread"""
+    def on_trade(self, trade: Trade) -> list[SpreadMetrics]:
+        """Procesa un trade y devuelve las métricas de los spreads en los que
+        participa el símbolo. En modo `strict` un trade inválido lanza su
+        excepción; si no, devuelve una lista vacía
+        """
+    def get_spread(self, name: str) -> SpreadMetrics | None:
+        """Últimas métricas de un spread"""
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Olvida el precio de un símbolo y el histórico de los spreads en los
+        que participa (las definiciones se mantienen)
+        """
+    def reset_all(self) -> None:
+        """Resetea precios e históricos de todos los spreads"""
+    def __repr__(self) -> str: ...
+
+class CorrelationEngine:
+    """Engine de correlaciones y betas entre símbolos"""
+    @property
+    def symbols(self) -> list[str]:
+        """Universo de símbolos, en el orden de filas y columnas de las matrices"""
+    @property
+    def interval_ms(self) -> int: ...
+    @property
+    def window(self) -> int:
+        """Retornos en la ventana"""
+    def __init__(self, symbols: list[str], interval_ms: int = 1000, window: int = 100, source: str = "last") -> None: ...
+    @property
+    def source(self) -> str:
+        """Precio muestreado: "last" o "mid\""""
+    def on_trade(self, trade: Trade) -> bool:
+        """Procesa un trade; true si cerró un intervalo con muestra.
+        En modo `strict` un trade inválido lanza su excepción; si no, devuelve False
+        """
+    def on_quote(self, quote: Quote) -> bool:
+        """Procesa una quote; true si cerró un intervalo con muestra.
+        En modo `strict` una quote inválida lanza su excepción
+        """
+    def on_snapshot(self, snapshot: BookSnapshot) -> bool:
+        """Procesa un snapshot; true si cerró un intervalo con muestra.
+        En modo `strict` un libro inválido lanza su excepción
+        """
+    def get_correlation_matrix(self) -> numpy.typing.NDArray[numpy.float64]:
+        """Matriz N×N de correlaciones de los log-retornos (NaN si no hay
+        suficientes muestras o algún símbolo no tiene varianza)
+        """
+    def get_beta_matrix(self) -> numpy.typing.NDArray[numpy.float64]:
+        """Matriz N×N de betas: la celda [i][j] es la beta del símbolo i
+        respecto al símbolo j, cov(i, j) / var(j)
+        """
+    def get_correlation(self, a: str, b: str) -> float | None:
+        """Correlación entre dos símbolos del universo"""
+    def get_beta(self, symbol: str, benchmark: str) -> float | None:
+        """Beta de un símbolo respecto a otro usado como benchmark"""
+    def sample_count(self) -> int:
+        """Retornos en la ventana"""
+    def get_last_sample_ts(self) -> int | None:
+        """Inicio del intervalo de la última muestra"""
+    @property
+    def strict(self) -> bool:
+        """Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse"""
+    @strict.setter
+    def strict(self, strict: bool) -> None: ...
+    def get_rejections(self, symbol: str | None = None) -> dict[str, int]:
+        """Eventos rechazados por tipo de error, de un símbolo o de todos (None)"""
+    def set_timestamp_policy(self, policy: str, tolerance_ms: int = 0) -> None:
+        """Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+        al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject\"
+        """
+    @property
+    def timestamp_policy(self) -> tuple[str, int]:
+        """Política de timestamps activa como (policy, tolerance_ms)"""
+    def get_max_ts(self, symbol: str) -> int | None:
+        """Máximo ts aceptado de un símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Olvida el precio de un símbolo. Las filas de retornos incluyen a
+        todo el universo, así que la ventana se vacía
+        """
+    def reset_all(self) -> None:
+        """Resetea precios, muestras y rechazos"""
+    def __repr__(self) -> str: ...
+
+class GapDetector:
+    """Detector de huecos de datos por símbolo y stream"""
+    @property
+    def max_gap_ms(self) -> int:
+        """Tiempo máximo de mercado abierto sin eventos (ms)"""
+    def __init__(self, max_gap_ms: int = 60000, calendar: SessionCalendar | None = None) -> None: ...
+    @property
+    def calendar(self) -> SessionCalendar:
+        """Calendario de mercado usado para medir los huecos (24/7 por defecto)"""
+    @calendar.setter
+    def calendar(self, calendar: SessionCalendar) -> None: ...
+    def on_trade(self, trade: Trade) -> DataGapAlert | None:
+        """Registra un trade; devuelve la alerta del hueco que cierra, si lo hay"""
+    def on_quote(self, quote: Quote) -> DataGapAlert | None:
+        """Registra una quote; devuelve la alerta del hueco que cierra, si lo hay"""
+    def on_snapshot(self, snapshot: BookSnapshot) -> DataGapAlert | None:
+        """Registra un snapshot como quote del símbolo"""
+    def watch(self, symbol: str, ts: int) -> None:
+        """Empieza a vigilar un símbolo desde ts aunque aún no haya recibido
+        eventos (p. ej. al suscribirse)
+        """
+    def check(self, now_ts: int) -> list[DataGapAlert]:
+        """Streams sin eventos durante más de `max_gap_ms` de mercado abierto a
+        `now_ts`; cada hueco se avisa una vez. Con el mercado cerrado no avisa
+        """
+    def get_last_ts(self, symbol: str, stream: str) -> int | None:
+        """Último evento de un stream ("trade" | "quote") del símbolo"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Deja de vigilar un símbolo"""
+    def reset_all(self) -> None:
+        """Deja de vigilar todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class WallTracker:
+    """Seguimiento de muros de liquidez por símbolo"""
+    @property
+    def min_size(self) -> float:
+        """Tamaño mínimo absoluto de un muro"""
+    @property
+    def size_ratio(self) -> float:
+        """Múltiplo mínimo del tamaño medio de los niveles de su lado"""
+    @property
+    def tick_size(self) -> float: ...
+    def __init__(self, min_size: float = 0.0, size_ratio: float = 3.0, tick_size: float = 0.01) -> None: ...
+    def on_snapshot(self, snapshot: BookSnapshot) -> list[WallEvent]:
+        """Compara el snapshot con el anterior del símbolo y devuelve los eventos
+        de muros, ordenados por precio
+        """
+    def get_walls(self, symbol: str, ts: int) -> list[WallEvent]:
+        """Muros vivos de un símbolo (event = "active", vida hasta `ts`)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class OrderBookManager:
+    """Gestor de libros incrementales por símbolo"""
+    @property
+    def snapshot_interval_ms(self) -> int | None:
+        """Cadencia de emisión automática de snapshots (None = solo bajo demanda)"""
+    @property
+    def snapshot_depth(self) -> int | None:
+        """Niveles por lado en los snapshots emitidos (None = libro completo)"""
+    def __init__(self, snapshot_interval_ms: int | None = None, snapshot_depth: int | None = None) -> None: ...
+    def apply_snapshot(self, snapshot: BookSnapshot, sequence: int | None = None) -> None:
+        """Carga (o resincroniza) el libro de un símbolo desde un snapshot completo"""
+    def on_delta(self, delta: BookDelta) -> BookSnapshot | None:
+        """Aplica un delta; devuelve un snapshot si se cumple la cadencia configurada"""
+    def get_snapshot(self, symbol: str, depth: int | None = None) -> BookSnapshot | None:
+        """Snapshot del libro actual (depth = niveles por lado, None = todos)"""
+    def needs_resync(self, symbol: str) -> bool:
+        """Indica si se detectó un hueco de secuencia desde la última resincronización"""
+    def gap_count(self, symbol: str) -> int:
+        """Número total de huecos de secuencia detectados para el símbolo"""
+    def symbols(self) -> list[str]:
+        """Símbolos con libro"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Elimina el libro de un símbolo"""
+    def reset_all(self) -> None:
+        """Elimina todos los libros"""
+    def __repr__(self) -> str: ...
+
+class BarAggregator:
+    """Agregador de barras por símbolo con múltiples suscripciones"""
+    def __init__(self, specs: list[str] = ...) -> None: ...
+    def subscribe(self, spec: str) -> None:
+        """Añade una suscripción ("1m", "tick:100", "volume:5000", "dollar:1e6", "imbalance:500")"""
+    def unsubscribe(self, spec: str) -> None:
+        """Elimina una suscripción y sus barras en curso"""
+    def subscriptions(self) -> list[str]:
+        """Suscripciones activas"""
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente usada para clasificar trades sin lado"""
+    def on_trade(self, trade: Trade) -> list[Bar]:
+        """Procesa un trade; devuelve las barras completadas en todas las suscripciones"""
+    def on_bar(self, bar: Bar) -> list[Bar]:
+        """Resamplea una barra cerrada en las suscripciones de tiempo de mayor
+        timeframe; devuelve las barras completadas en orden de suscripción
+        """
+    def on_trade_numpy(self, symbol: str, ts: numpy.typing.NDArray[numpy.int64], price: numpy.typing.NDArray[numpy.float64], size: numpy.typing.NDArray[numpy.float64], side: numpy.typing.NDArray[numpy.int8] | None = None) -> dict[Any, Any]:
+        """Procesa arrays numpy de un símbolo; devuelve las barras cerradas como dict de
+        arrays (ts, open, high, low, close, volume, spec = índice en `subscriptions()`)
+        """
+    def get_current_bar(self, symbol: str, spec: str) -> Bar | None:
+        """Barra en curso (aún no cerrada) de un símbolo para una suscripción"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo"""
+    def reset_all(self) -> None:
+        """Resetea todos los símbolos"""
+    def __repr__(self) -> str: ...
+
+class EngineManager:
+    """Manager que posee los indicadores y reparte los eventos entre ellos"""
+    def __init__(self, cvd: bool = True, vwap: bool = True, liquidity: bool = True, heatmap: bool = True, ma_periods: list[int] | None = None, rsi_periods: list[int] | None = None, atr_periods: list[int] | None = None) -> None:
+        """Los engines de trades y libro se activan con flags; los de barras
+        se activan pasando sus periodos.
+        """
+    def engines(self) -> list[str]:
+        """Nombres de los indicadores registrados, en orden de despacho"""
+    def register(self, name: str, engine: Any) -> None:
+        """Registra con `name` un engine creado desde Python (CVDEngine,
+        HeatmapEngine, ...). El manager despacha a una copia que comparte el
+        estado con `engine`: sus getters ven lo que procesa el manager, pero los
+        cambios de configuración posteriores no se propagan.
+        """
+    def unregister(self, name: str) -> bool:
+        """Elimina un indicador; devuelve si existía"""
+    def enable(self, name: str, symbol: str | None = None) -> None:
+        """Activa un indicador globalmente o solo para un símbolo"""
+    def disable(self, name: str, symbol: str | None = None) -> None:
+        """Desactiva un indicador globalmente o solo para un símbolo"""
+    def is_enabled(self, name: str, symbol: str) -> bool:
+        """Indica si el indicador procesa eventos del símbolo"""
+    @property
+    def config(self) -> ConfigRegistry | None:
+        """Configuración por símbolo compartida con los indicadores registrados"""
+    @config.setter
+    def config(self, config: ConfigRegistry | None) -> None: ...
+    def on_metrics(self, callback: Any | None = None, throttle_ms: int | None = None) -> None:
+        """Registra un callable que recibe cada `MetricsBundle` no vacío (None lo
+        quita). Con `throttle_ms` se llama como mucho una vez cada N ms por símbolo.
+        """
+    def on_quote(self, quote: Quote) -> None:
+        """Actualiza la quote vigente de los indicadores que clasifican trades"""
+    def on_trade(self, trade: Trade) -> MetricsBundle:
+        """Despacha un trade a todos los indicadores activos"""
+    def process_batch_parallel(self, trades: list[Trade]) -> list[MetricsBundle]:
+        """Procesa un batch de trades de varios símbolos en paralelo, sin GIL.
+        Cada símbolo se procesa en orden; los bundles vuelven en el orden del batch.
+        """
+    @property
+    def threads(self) -> int:
+        """Hilos usados por `process_batch_parallel`"""
+    @threads.setter
+    def threads(self, threads: int | None) -> None: ...
+    @property
+    def dedup_capacity(self) -> int:
+        """Trade ids recordados por símbolo para descartar retransmisiones"""
+    @dedup_capacity.setter
+    def dedup_capacity(self, capacity: int) -> None: ...
+    def get_duplicates(self, symbol: str | None = None) -> dict[str, int]:
+        """Trades duplicados descartados por símbolo"""
+    def on_snapshot(self, snapshot: BookSnapshot) -> MetricsBundle:
+        """Despacha un snapshot del libro a todos los indicadores activos"""
+    def on_bar(self, bar: Bar) -> MetricsBundle:
+        """Despacha una barra cerrada a todos los indicadores activos"""
+    def snapshot_state(self, symbol: str) -> dict[str, dict[str, float]]:
+        """Estado actual del símbolo por indicador (solo los que tienen valores)"""
+    def reset_symbol(self, symbol: str) -> None:
+        """Resetea el estado de un símbolo en todos los indicadores"""
+    def reset_all(self) -> None:
+        """Resetea todos los indicadores"""
+    @property
+    def symbol_ttl_ms(self) -> int | None:
+        """Tiempo sin eventos tras el que se desaloja un símbolo (None = nunca)"""
+    @symbol_ttl_ms.setter
+    def symbol_ttl_ms(self, ttl_ms: int | None) -> None: ...
+    def active_symbols(self) -> list[str]:
+        """Símbolos con eventos desde el último reset o desalojo, ordenados"""
+    def last_event_ts(self, symbol: str) -> int | None:
+        """Último ts de evento del símbolo"""
+    def evict(self, symbol: str) -> bool:
+        """Elimina el estado del símbolo en todos los indicadores; devuelve si estaba activo"""
+    def evict_expired(self, now_ts: int | None = None) -> list[str]:
+        """Desaloja los símbolos sin eventos en el último `symbol_ttl_ms` respecto
+        a `now_ts` (por defecto el máximo ts visto); devuelve los desalojados
+        """
+    def state_size(self) -> int:
+        """Entradas (indicador, símbolo) con estado, sumadas en todos los indicadores"""
+    def state_sizes(self) -> dict[str, int]:
+        """Símbolos con estado por indicador"""
+    def get_latency_quantiles(self, quantiles: list[float] = ...) -> dict[str, list[float | None]]:
+        """Cuantiles de latencia por evento de cada indicador, en segundos
+        (por defecto p50, p95 y p99; None sin eventos)
+        """
+    def render_metrics(self) -> str:
+        """Métricas operativas en formato de texto de Prometheus"""
+    def __repr__(self) -> str: ...
+
+class NATSConfig:
+    """Configuración del suscriptor NATS"""
+    url: str
+    subject: str
+    stream_name: str
+    consumer_name: str | None
+    subjects: list[tuple[str, str]]
+    @property
+    def reconnect_initial_ms(self) -> int:
+        """Backoff de reconexión: primera espera y espera máxima (ms)"""
+    @property
+    def reconnect_max_ms(self) -> int: ...
+    @property
+    def queue_capacity(self) -> int:
+        """Capacidad de la cola entre la recepción y el procesamiento"""
+    def __init__(self, url: str, subject: str, stream_name: str, consumer_name: str | None = None, subjects: list[tuple[str, str]] = ..., reconnect_initial_ms: int = 500, reconnect_max_ms: int = 30000, wire_format: str = "json", queue_capacity: int = 10000, overflow_policy: str = "block") -> None: ...
+    @property
+    def wire_format(self) -> str: ...
+    @wire_format.setter
+    def wire_format(self, wire_format: str) -> None: ...
+    @property
+    def overflow_policy(self) -> str: ...
+    @overflow_policy.setter
+    def overflow_policy(self, overflow_policy: str) -> None: ...
+    def __repr__(self) -> str: ...
+
+class NATSSubscriber:
+    """Runner async para procesar mensajes NATS"""
+    def __init__(self, config: NATSConfig) -> None: ...
+    def start(self) -> None:
+        """Conecta a NATS y comienza a procesar mensajes en segundo plano"""
+    def stop(self) -> None:
+        """Detiene el consumer y espera a que termine el hilo (sin el GIL, que el
+        hilo puede necesitar para entregar métricas)
+        """
+    def run(self) -> Any:
+        """Awaitable para asyncio: arranca el suscriptor si no está en marcha y
+        se completa cuando se detiene. Cancelar el await detiene el consumer.
+        """
+    def stream(self, capacity: int = ...) -> MetricsStream:
+        """Iterador async de los `MetricsBundle` no vacíos calculados a partir de
+        ahora (`async for metrics in subscriber.stream()`). Con el buffer lleno
+        se descartan los más antiguos; la iteración termina al detenerse.
+        """
+    @property
+    def is_running(self) -> bool: ...
+    @property
+    def last_error(self) -> str | None:
+        """Último error de conexión o de decodificación"""
+    def stats(self) -> dict[str, int]:
+        """Contadores: received, processed, errors, dropped, queued, last_acked_sequence"""
+    @property
+    def connection_state(self) -> str:
+        """Estado de la conexión: disconnected | connecting | connected | reconnecting | stopped"""
+    def drain_connection_events(self) -> list[ConnectionEvent]:
+        """Cambios de estado pendientes desde la última llamada"""
+    def set_state_callback(self, callback: Any | None = None) -> None:
+        """Callback llamado con cada `ConnectionEvent` desde el hilo de red (None lo quita)"""
+    def on_metrics(self, callback: Any | None = None, throttle_ms: int | None = None) -> None:
+        """Callback con cada `MetricsBundle` calculado (ver `EngineManager.on_metrics`)"""
+    def get_duplicates(self, symbol: str | None = None) -> dict[str, int]:
+        """Trades retransmitidos descartados por símbolo (ver `EngineManager.get_duplicates`)"""
+    def last_message_age_ms(self) -> int | None:
+        """Milisegundos desde el último mensaje recibido, para detectar datos obsoletos"""
+    def render_metrics(self) -> str:
+        """Métricas operativas en formato de texto de Prometheus"""
+    def start_metrics_server(self, addr: str = "0.0.0.0:9100") -> str:
+        """Sirve `render_metrics()` en `GET /metrics`; devuelve la dirección
+        efectiva ("host:0" elige un puerto libre)
+        """
+    def stop_metrics_server(self) -> None: ...
+    def snapshot_state(self, symbol: str) -> dict[str, dict[str, float]]:
+        """Estado actual de los indicadores para un símbolo"""
+    def process_trade(self, trade: Trade) -> str:
+        """Procesa un trade recibido de NATS"""
+    def process_book(self, snapshot: BookSnapshot) -> str:
+        """Procesa un snapshot de libro"""
+    def __repr__(self) -> str: ...
+
+class MetricsStream:
+    """Iterador async de los `MetricsBundle` de un suscriptor"""
+    def __aiter__(self) -> MetricsStream: ...
+    def __anext__(self) -> Any:
+        """Awaitable con el siguiente bundle; StopAsyncIteration al cerrarse"""
+    def close(self) -> None:
+        """Deja de recibir bundles; un `__anext__` pendiente termina la iteración"""
+    @property
+    def closed(self) -> bool: ...
+    @property
+    def capacity(self) -> int: ...
+    @property
+    def dropped(self) -> int:
+        """Bundles descartados por buffer lleno"""
+    def __len__(self) -> int:
+        """Bundles en buffer pendientes de consumir"""
+    def __repr__(self) -> str: ...
+
+class NATSPublisher:
+    """Publisher de métricas hacia NATS"""
+    @property
+    def url(self) -> str: ...
+    @property
+    def subject_template(self) -> str: ...
+    @property
+    def batch_size(self) -> int: ...
+    @property
+    def max_rate(self) -> float | None: ...
+    def __init__(self, url: str, subject_template: str = "indicators.{kind}.{symbol}", batch_size: int = 100, max_rate: float | None = None) -> None: ...
+    def set_subject(self, kind: str, template: str) -> None:
+        """Plantilla de subject propia de un tipo ("cvd", "vwap", "liquidity", "heatmap", "sweep")"""
+    def subject_for(self, kind: str, symbol: str) -> str:
+        """Subject al que se publica un tipo de métrica de un símbolo"""
+    def start(self) -> None:
+        """Conecta a NATS y arranca el hilo de publicación"""
+    def stop(self) -> None:
+        """Publica lo pendiente, cierra la conexión y espera al hilo"""
+    @property
+    def is_running(self) -> bool: ...
+    @property
+    def last_error(self) -> str | None:
+        """Último error de conexión, serialización o publicación"""
+    @property
+    def connection_state(self) -> str:
+        """Estado de la conexión: disconnected | connecting | connected | reconnecting | stopped"""
+    def drain_connection_events(self) -> list[ConnectionEvent]:
+        """Cambios de estado pendientes desde la última llamada"""
+    def set_state_callback(self, callback: Any | None = None) -> None:
+        """Callback llamado con cada `ConnectionEvent` desde el hilo de red (None lo quita)"""
+    def stats(self) -> dict[str, int]:
+        """Contadores: queued, published, conflated, errors"""
+    def publish_cvd(self, symbol: str, metrics: CVDMetrics) -> None: ...
+    def publish_vwap(self, symbol: str, metrics: VWAPMetrics) -> None: ...
+    def publish_liquidity(self, symbol: str, metrics: LiquidityMetrics) -> None: ...
+    def publish_heatmap(self, metrics: HeatmapMetrics) -> None: ...
+    def publish_sweep(self, event: SweepEvent) -> None: ...
+    def publish_sweeps(self, events: list[SweepEvent]) -> None:
+        """Publica una lista de barridos (p. ej. el resultado de `SweepDetector.flush()`)"""
+    def publish_bundle(self, bundle: MetricsBundle) -> None:
+        """Publica todas las métricas presentes en el resultado del `EngineManager`"""
+    def __repr__(self) -> str: ...
+
+class ConnectionEvent:
+    """Cambio de estado de la conexión"""
+    ts: int
+    state: str
+    detail: str | None
+    def __repr__(self) -> str: ...
+
+def benchmark_indicators(trades: list[Trade] | None = None, iterations: int = 1, n_events: int = 10000, n_symbols: int = 1, base_price: float = 100.0, tick_size: float = 0.01, depth_levels: int = 10, seed: int = 42) -> dict[str, dict[str, float]]:
+    """Benchmark de todos los engines y paths batch. Usa `trades` si se pasan;
+    si no, genera `n_events` trades sintéticos (snapshots y barras se derivan
+    de los trades). Devuelve por engine: events, total_secs, ops_per_sec,
+    p50_ns y p99_ns (latencia por evento).
+    """
+
+def load_config(text: str, format: str | None = None) -> ConfigRegistry:
+    """Carga un registro desde un texto TOML o JSON.
+    format = "toml" | "json"; si se omite, un texto que empieza por '{' es JSON.
+    """
+
+def init_logging(level: str = "info", json: bool = True, path: str | None = None) -> None:
+    """Instala el subscriber global de tracing: JSON (una línea por evento) o
+    texto, en stderr o anexando a `path`. Solo puede llamarse una vez.
+    """
+
+def set_log_level(level: str) -> None:
+    """Cambia el nivel del subscriber instalado con `init_logging`"""
+
+def load_trades_parquet(path: str, columns: dict[str, str] | None = None, symbol: str | None = None) -> list[Trade]:
+    """Trades desde Parquet"""
+
+def load_trades_csv(path: str, columns: dict[str, str] | None = None, symbol: str | None = None, separator: str = ",") -> list[Trade]:
+    """Trades desde CSV con cabecera"""
+
+def load_bars_parquet(path: str, columns: dict[str, str] | None = None, symbol: str | None = None) -> list[Bar]:
+    """Barras desde Parquet"""
+
+def load_bars_csv(path: str, columns: dict[str, str] | None = None, symbol: str | None = None, separator: str = ",") -> list[Bar]:
+    """Barras desde CSV con cabecera"""
+
+def load_books_parquet(path: str, columns: dict[str, str] | None = None, symbol: str | None = None) -> list[BookSnapshot]:
+    """Snapshots de libro desde Parquet (una fila por nivel)"""
+
+def load_books_csv(path: str, columns: dict[str, str] | None = None, symbol: str | None = None, separator: str = ",") -> list[BookSnapshot]:
+    """Snapshots de libro desde CSV con cabecera (una fila por nivel)"""
+
+def _unpickle(class_: str, state: bytes) -> Any:
+    """Reconstruye un objeto guardado con `__reduce__`"""
@@ -0,0 +1,150 @@
+//! # Arrow FFI
+//! 
+//! Intercambio de RecordBatches con PyArrow mediante el C Data Interface de
+//! Arrow (sin objetos Python por fila):
+//! - entrada: `RecordBatch._export_to_c` exporta el lote como struct array,
+//!   que se importa sin copiar como DataFrame de Polars
+//! - salida: el DataFrame de resultados se exporta como struct array y se
+//!   reconstruye con `RecordBatch._import_from_c`
+//! 
+//! Los engines lo usan en `on_trade_arrow`, con el mismo mapeo de columnas
+//! que los loaders.
+
+use polars::export::arrow::array::StructArray;
+use polars::export::arrow::datatypes::{ArrowDataType, Field as ArrowField};
+use polars::export::arrow::ffi::{self, ArrowArray, ArrowSchema};
+use polars::prelude::*;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use crate::loaders::{polars_err, trades_from};
+use crate::types::{CVDMetrics, Trade, VWAPMetrics};
+
+/// Importa un struct array (un RecordBatch exportado) como DataFrame
+/// 
+/// # Safety
+/// `array` y `schema` deben ser válidos según el C Data Interface.
+pub unsafe fn frame_from_c(array: ArrowArray, schema: &ArrowSchema) -> PolarsResult<DataFrame> {
+    let field = ffi::import_field_from_c(schema)?;
+    let array = ffi::import_array_from_c(array, field.data_type.clone())?;
+    let Some(batch) = array.as_any().downcast_ref::<StructArray>() else {
+        polars_bail!(ComputeError: "expected a RecordBatch (struct array), got {:?}", field.data_type)
+    };
+    let columns = batch.fields().iter().zip(batch.values())
+        .map(|(f, values)| Series::from_arrow(&f.name, values.clone()))
+        .collect::<PolarsResult<Vec<_>>>()?;
+    DataFrame::new(columns)
+}
+
+/// Exporta un DataFrame como struct array con tipos Arrow estándar
+pub fn frame_to_c(df: &DataFrame) -> (ArrowArray, ArrowSchema) {
+    let fields: Vec<ArrowField> = df.get_columns().iter().map(|s| s.field().to_arrow(false)).collect();
+    let arrays = df.get_columns().iter().map(|s| s.rechunk().to_arrow(0, false)).collect();
+    let data_type = ArrowDataType::Struct(fields);
+    let array = StructArray::new(data_type.clone(), arrays, None);
+    let field = ArrowField::new("", data_type, false);
+    (ffi::export_array_to_c(array.boxed()), ffi::export_field_to_c(&field))
+}
+
+/// Importa un `pyarrow.RecordBatch`
+pub fn import_record_batch(batch: &Bound<'_, PyAny>) -> PyResult<DataFrame> {
+    let mut array = Box::new(ArrowArray::empty());
+    let mut schema = Box::new(ArrowSchema::empty());
+    batch.call_method1("_export_to_c", (
+        &mut *array as *mut ArrowArray as usize,
+        &mut *schema as *mut ArrowSchema as usize,
+    ))?;
+    // PyArrow ha rellenado ambas estructuras; su release las libera al soltarlas
+    Ok(unsafe { frame_from_c(*array, &schema) }.map_err(polars_err)?)
+}
+
+/// Exporta un DataFrame como `pyarrow.RecordBatch`
+pub fn export_record_batch(py: Python<'_>, df: &DataFrame) -> PyResult<PyObject> {
+    let (array, schema) = frame_to_c(df);
+    let mut array = Box::new(array);
+    let mut schema = Box::new(schema);
+    // _import_from_c toma posesión (marca ambas estructuras como liberadas)
+    let batch = py.import_bound("pyarrow")?.getattr("RecordBatch")?.call_method1("_import_from_c", (
+        &mut *array as *mut ArrowArray as usize,
+        &mut *schema as *mut ArrowSchema as usize,
+    ))?;
+    Ok(batch.unbind())
+}
+
+/// Trades de un `pyarrow.RecordBatch` (columnas como en `load_trades_*`)
+pub fn trades_from_arrow(batch: &Bound<'_, PyAny>, columns: Option<HashMap<String, String>>,
+                         symbol: Option<&str>) -> PyResult<Vec<Trade>> {
+    Ok(trades_from(&import_record_batch(batch)?, columns, symbol)?)
+}
+
+/// Columnas ts y symbol de los trades de entrada
+fn trade_keys(trades: &[Trade]) -> Vec<Series> {
+    vec![
+        Series::new("ts", trades.iter().map(|t| t.ts).collect::<Vec<_>>()),
+        Series::new("symbol", trades.iter().map(|t| t.symbol.as_str()).collect::<Vec<_>>()),
+    ]
+}
+
+/// Una fila por trade; nulos donde el engine descartó el trade
+pub fn cvd_frame(trades: &[Trade], results: &[Option<CVDMetrics>]) -> PyResult<DataFrame> {
+    let mut columns = trade_keys(trades);
+    let f64_column = |name: &str, f: fn(&CVDMetrics) -> f64| {
+        Series::new(name, results.iter().map(|m| m.as_ref().map(f)).collect::<Vec<_>>())
+    };
+    columns.push(f64_column("cvd", |m| m.cvd));
+    columns.push(f64_column("buy_volume", |m| m.buy_volume));
+    columns.push(f64_column("sell_volume", |m| m.sell_volume));
+    columns.push(Series::new("last_side", results.iter().map(|m| m.as_ref().map(|m| m.last_side.as_str())).collect::<Vec<_>>()));
+    columns.push(Series::new("trade_count", results.iter().map(|m| m.as_ref().map(|m| m.trade_count)).collect::<Vec<_>>()));
+    Ok(DataFrame::new(columns).map_err(polars_err)?)
+}
+
+/// Una fila por trade; nulos donde el engine descartó el trade
+pub fn vwap_frame(trades: &[Trade], results: &[Option<VWAPMetrics>]) -> PyResult<DataFrame> {
+    let mut columns = trade_keys(trades);
+    let f64_column = |name: &str, f: fn(&VWAPMetrics) -> f64| {
+        Series::new(name, results.iter().map(|m| m.as_ref().map(f)).collect::<Vec<_>>())
+    };
+    columns.push(f64_column("vwap", |m| m.vwap));
+    columns.push(f64_column("v_sum", |m| m.v_sum));
+    columns.push(f64_column("std_dev", |m| m.std_dev));
+    columns.push(f64_column("upper_band_1", |m| m.upper_band_1));
+    columns.push(f64_column("lower_band_1", |m| m.lower_band_1));
+    Ok(DataFrame::new(columns).map_err(polars_err)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_frame_c_round_trip() {
+        let df = DataFrame::new(vec![
+            Series::new("ts", &[1000u64, 2000]),
+            Series::new("price", &[100.0, 101.0]),
+            Series::new("size", &[1.0, 2.0]),
+            Series::new("symbol", &["AAPL", "AAPL"]),
+        ]).unwrap();
+        
+        let (array, schema) = frame_to_c(&df);
+        let imported = unsafe { frame_from_c(array, &schema) }.unwrap();
+        assert!(imported.equals(&df));
+        
+        let trades = trades_from(&imported, None, None).unwrap();
+        assert_eq!(trades[1].price, 101.0);
+    }
+    
+    #[test]
+    fn test_cvd_frame_keeps_rows() {
+        let trades = vec![
+            Trade::new(1000, 100.0, 2.0, "AAPL".to_string()),
+            Trade::new(2000, 0.0, 1.0, "AAPL".to_string()),
+        ];
+        let metrics = CVDMetrics::new(2.0, "BUY".to_string(), 2.0, 1000, 2.0, 0.0, 1);
+        let df = cvd_frame(&trades, &[Some(metrics), None]).unwrap();
+        
+        assert_eq!(df.height(), 2);
+        assert_eq!(df.column("cvd").unwrap().f64().unwrap().get(0), Some(2.0));
+        assert_eq!(df.column("cvd").unwrap().null_count(), 1);
+        assert_eq!(df.column("last_side").unwrap().str().unwrap().get(0), Some("BUY"));
+    }
+}
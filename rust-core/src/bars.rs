@@ -0,0 +1,439 @@
+//! # Bar Aggregator
+//! 
+//! Construye barras OHLCV a partir de trades, con varias suscripciones por
+//! agregador. Tipos de barra (spec → `Bar.tf`):
+//! - Tiempo: "1m", "5s"... (cierra al cambiar de bucket)
+//! - Ticks: "tick:N" (cada N trades)
+//! - Volumen: "volume:X" (cada X contratos)
+//! - Dólar: "dollar:Y" (cada Y de nocional precio × size)
+//! - Imbalance: "imbalance:T" (cuando |Σ volumen con signo| alcanza T)
+//! 
+//! Las barras por umbral cierran con el trade que alcanza el umbral; un trade
+//! no se reparte entre barras.
+//! 
+//! Las suscripciones de tiempo también se pueden alimentar con barras cerradas
+//! de menor timeframe (`on_bar`): cada barra se agrega en las suscripciones
+//! cuyo periodo es múltiplo mayor del suyo (1m → 5m → 1h), que cierran con la
+//! última sub-barra de su bucket. Una suscripción se alimenta por un solo
+//! camino, trades o barras, para no contar dos veces el mismo volumen.
+
+#[cfg(feature = "python")]
+use numpy::PyReadonlyArray1;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::indicators::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+#[cfg(feature = "python")]
+use crate::numpy_batch;
+use crate::types::{Bar, Quote, Trade};
+use crate::utils::{calculate_bucket, parse_timeframe_ms};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Regla de cierre de una suscripción
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BarKind {
+    Time(u64),
+    Tick(u64),
+    Volume(f64),
+    Dollar(f64),
+    Imbalance(f64),
+}
+
+/// Suscripción: regla de cierre + etiqueta usada como `Bar.tf`
+#[derive(Clone, Debug, PartialEq)]
+struct BarSpec {
+    kind: BarKind,
+    label: String,
+}
+
+impl BarSpec {
+    fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        let kind = match spec.split_once(':') {
+            Some(("time", tf)) => BarKind::Time(parse_timeframe_ms(tf)?),
+            Some(("tick", n)) => BarKind::Tick(n.parse().ok().filter(|n| *n > 0)?),
+            Some((kind, x)) => {
+                let threshold: f64 = x.parse().ok().filter(|x: &f64| x.is_finite() && *x > 0.0)?;
+                match kind {
+                    "volume" => BarKind::Volume(threshold),
+                    "dollar" => BarKind::Dollar(threshold),
+                    "imbalance" => BarKind::Imbalance(threshold),
+                    _ => return None,
+                }
+            }
+            None => BarKind::Time(parse_timeframe_ms(spec)?),
+        };
+        Some(Self { kind, label: spec.to_string() })
+    }
+}
+
+/// Barra en construcción y acumuladores del umbral
+#[derive(Clone, Debug)]
+struct OpenBar {
+    bar: Bar,
+    trades: u64,
+    notional: f64,
+    imbalance: f64,
+}
+
+impl OpenBar {
+    fn new(ts: u64, price: f64, tf: &str, symbol: &str) -> Self {
+        Self {
+            bar: Bar::new(ts, price, price, price, price, 0.0, tf.to_string(), symbol.to_string()),
+            trades: 0,
+            notional: 0.0,
+            imbalance: 0.0,
+        }
+    }
+    
+    fn apply(&mut self, trade: &Trade, side: &str) {
+        self.bar.high = self.bar.high.max(trade.price);
+        self.bar.low = self.bar.low.min(trade.price);
+        self.bar.close = trade.price;
+        self.bar.volume += trade.size;
+        self.trades += 1;
+        self.notional += trade.price * trade.size;
+        match side {
+            SIDE_BUY => self.imbalance += trade.size,
+            SIDE_SELL => self.imbalance -= trade.size,
+            _ => {}
+        }
+    }
+    
+    /// Agrega una barra cerrada de menor timeframe
+    fn merge(&mut self, bar: &Bar) {
+        self.bar.high = self.bar.high.max(bar.high);
+        self.bar.low = self.bar.low.min(bar.low);
+        self.bar.close = bar.close;
+        self.bar.volume += bar.volume;
+    }
+    
+    /// Indica si la barra alcanzó el umbral de su suscripción
+    fn is_complete(&self, kind: BarKind) -> bool {
+        match kind {
+            BarKind::Time(_) => false,
+            BarKind::Tick(n) => self.trades >= n,
+            BarKind::Volume(x) => self.bar.volume >= x,
+            BarKind::Dollar(y) => self.notional >= y,
+            BarKind::Imbalance(t) => self.imbalance.abs() >= t,
+        }
+    }
+}
+
+py_class! {
+/// Agregador de barras por símbolo con múltiples suscripciones
+#[pyclass]
+pub struct BarAggregator {
+    specs: Vec<BarSpec>,
+    // Barra en curso por (symbol, spec)
+    bars: Arc<DashMap<(String, String), OpenBar>>,
+    classifier: TradeClassifier,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl BarAggregator {
+    #[new]
+    #[pyo3(signature = (specs=Vec::new()))]
+    pub fn new(specs: Vec<String>) -> Result<Self, ProcessError> {
+        let mut aggregator = Self {
+            specs: Vec::new(),
+            bars: Arc::new(DashMap::new()),
+            classifier: TradeClassifier::new(),
+        };
+        for spec in &specs {
+            aggregator.subscribe(spec)?;
+        }
+        Ok(aggregator)
+    }
+    
+    /// Añade una suscripción ("1m", "tick:100", "volume:5000", "dollar:1e6", "imbalance:500")
+    pub fn subscribe(&mut self, spec: &str) -> Result<(), ProcessError> {
+        let spec = BarSpec::parse(spec).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid bar spec: {}", spec))
+        })?;
+        if !self.specs.contains(&spec) {
+            self.specs.push(spec);
+        }
+        Ok(())
+    }
+    
+    /// Elimina una suscripción y sus barras en curso
+    pub fn unsubscribe(&mut self, spec: &str) {
+        let spec = spec.trim();
+        self.specs.retain(|s| s.label != spec);
+        self.bars.retain(|key, _| key.1 != spec);
+    }
+    
+    /// Suscripciones activas
+    pub fn subscriptions(&self) -> Vec<String> {
+        self.specs.iter().map(|s| s.label.clone()).collect()
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade; devuelve las barras completadas en todas las suscripciones
+    pub fn on_trade(&self, trade: &Trade) -> Vec<Bar> {
+        // Validar datos
+        if trade.price <= 0.0 || trade.size <= 0.0 {
+            return Vec::new();
+        }
+        
+        let side = self.classifier.classify(trade);
+        let mut completed = Vec::new();
+        
+        for spec in &self.specs {
+            let key = (trade.symbol.clone(), spec.label.clone());
+            
+            if let BarKind::Time(bucket_ms) = spec.kind {
+                let bucket_ts = calculate_bucket(trade.ts, bucket_ms);
+                let mut bar = self.bars.entry(key)
+                    .or_insert_with(|| OpenBar::new(bucket_ts, trade.price, &spec.label, &trade.symbol));
+                
+                // Trades tardíos se agregan a la barra en curso
+                if bucket_ts > bar.bar.ts {
+                    let next = OpenBar::new(bucket_ts, trade.price, &spec.label, &trade.symbol);
+                    completed.push(std::mem::replace(&mut *bar, next).bar);
+                }
+                bar.apply(trade, side);
+                continue;
+            }
+            
+            let done = {
+                let mut bar = self.bars.entry(key.clone())
+                    .or_insert_with(|| OpenBar::new(trade.ts, trade.price, &spec.label, &trade.symbol));
+                bar.apply(trade, side);
+                bar.is_complete(spec.kind)
+            };
+            if done {
+                if let Some((_, bar)) = self.bars.remove(&key) {
+                    completed.push(bar.bar);
+                }
+            }
+        }
+        
+        completed
+    }
+    
+    /// Resamplea una barra cerrada en las suscripciones de tiempo de mayor
+    /// timeframe; devuelve las barras completadas en orden de suscripción
+    pub fn on_bar(&self, bar: &Bar) -> Vec<Bar> {
+        let tf = bar.tf.strip_prefix("time:").unwrap_or(&bar.tf);
+        let Some(source_ms) = parse_timeframe_ms(tf) else { return Vec::new() };
+        // Validar datos
+        if !(bar.low > 0.0 && bar.high >= bar.low && bar.volume >= 0.0) {
+            return Vec::new();
+        }
+        
+        let mut completed = Vec::new();
+        for spec in &self.specs {
+            let BarKind::Time(bucket_ms) = spec.kind else { continue };
+            if bucket_ms <= source_ms || bucket_ms % source_ms != 0 {
+                continue;
+            }
+            let key = (bar.symbol.clone(), spec.label.clone());
+            let bucket_ts = calculate_bucket(bar.ts, bucket_ms);
+            
+            let done = {
+                let mut open = self.bars.entry(key.clone())
+                    .or_insert_with(|| OpenBar::new(bucket_ts, bar.open, &spec.label, &bar.symbol));
+                // Si faltaron las últimas sub-barras, el bucket cierra con la siguiente
+                if bucket_ts > open.bar.ts {
+                    let next = OpenBar::new(bucket_ts, bar.open, &spec.label, &bar.symbol);
+                    completed.push(std::mem::replace(&mut *open, next).bar);
+                }
+                // Barras tardías se agregan a la barra en curso
+                open.merge(bar);
+                open.bar.ts == bucket_ts && bar.ts + source_ms >= bucket_ts + bucket_ms
+            };
+            if done {
+                if let Some((_, open)) = self.bars.remove(&key) {
+                    completed.push(open.bar);
+                }
+            }
+        }
+        
+        completed
+    }
+    
+    /// Procesa arrays numpy de un símbolo; devuelve las barras cerradas como dict de
+    /// arrays (ts, open, high, low, close, volume, spec = índice en `subscriptions()`)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (symbol, ts, price, size, side=None))]
+    pub fn on_trade_numpy<'py>(&self, py: Python<'py>, symbol: &str, ts: PyReadonlyArray1<'py, i64>,
+                               price: PyReadonlyArray1<'py, f64>, size: PyReadonlyArray1<'py, f64>,
+                               side: Option<PyReadonlyArray1<'py, i8>>) -> PyResult<Bound<'py, PyDict>> {
+        let trades = numpy_batch::trades_from_numpy(symbol, ts, price, size, side)?;
+        let bars: Vec<_> = py.allow_threads(|| trades.iter().flat_map(|t| self.on_trade(t)).collect());
+        numpy_batch::to_dict(py, numpy_batch::bar_columns(&bars, &self.subscriptions()))
+    }
+    
+    /// Barra en curso (aún no cerrada) de un símbolo para una suscripción
+    pub fn get_current_bar(&self, symbol: &str, spec: &str) -> Option<Bar> {
+        self.bars.get(&(symbol.to_string(), spec.trim().to_string())).map(|b| b.bar.clone())
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.bars.retain(|key, _| key.0 != symbol);
+        self.classifier.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.bars.clear();
+        self.classifier.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("BarAggregator(subscriptions={:?}, open_bars={})", self.subscriptions(), self.bars.len())
+    }
+}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, price: f64, size: f64, side: &str) -> Trade {
+        Trade {
+            ts,
+            price,
+            size,
+            symbol: "AAPL".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_bar_spec_parsing() {
+        assert!(BarAggregator::new(vec!["1m".into(), "time:5s".into(), "tick:10".into(),
+                                        "volume:100".into(), "dollar:1e6".into(),
+                                        "imbalance:50".into()]).is_ok());
+        for bad in ["tick:0", "volume:-1", "renko:5", "abc"] {
+            assert!(BarAggregator::new(vec![bad.to_string()]).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_time_bars() {
+        let agg = BarAggregator::new(vec!["1s".into()]).unwrap();
+        
+        assert!(agg.on_trade(&trade(1000, 100.0, 1.0, "BUY")).is_empty());
+        agg.on_trade(&trade(1500, 102.0, 2.0, "BUY"));
+        let bars = agg.on_trade(&trade(2100, 101.0, 1.0, "SELL"));
+        
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].ts, 1000);
+        assert_eq!(bars[0].tf, "1s");
+        assert_eq!((bars[0].open, bars[0].high, bars[0].close), (100.0, 102.0, 102.0));
+        assert_eq!(bars[0].volume, 3.0);
+    }
+
+    #[test]
+    fn test_tick_and_volume_bars() {
+        let agg = BarAggregator::new(vec!["tick:2".into(), "volume:5".into()]).unwrap();
+        
+        assert!(agg.on_trade(&trade(1, 100.0, 2.0, "BUY")).is_empty());
+        let bars = agg.on_trade(&trade(2, 101.0, 2.0, "BUY"));
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].tf, "tick:2");
+        
+        let bars = agg.on_trade(&trade(3, 99.0, 1.0, "SELL"));
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].tf, "volume:5");
+        assert_eq!(bars[0].volume, 5.0);
+        assert_eq!(bars[0].low, 99.0);
+        
+        assert!(agg.get_current_bar("AAPL", "volume:5").is_none());
+        assert_eq!(agg.get_current_bar("AAPL", "tick:2").unwrap().volume, 1.0);
+    }
+
+    #[test]
+    fn test_dollar_and_imbalance_bars() {
+        let agg = BarAggregator::new(vec!["dollar:1000".into(), "imbalance:3".into()]).unwrap();
+        
+        // Compras y ventas se compensan: el imbalance no llega a 3
+        assert!(agg.on_trade(&trade(1, 100.0, 2.0, "BUY")).is_empty());
+        assert!(agg.on_trade(&trade(2, 100.0, 2.0, "SELL")).is_empty());
+        assert!(agg.on_trade(&trade(3, 100.0, 2.0, "BUY")).is_empty());
+        
+        // Nocional 1000 con imbalance 2 - 4 = -2: solo cierra la barra dólar
+        let bars = agg.on_trade(&trade(4, 100.0, 4.0, "SELL"));
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].tf, "dollar:1000");
+        assert_eq!(bars[0].volume, 10.0);
+        
+        let bars = agg.on_trade(&trade(5, 100.0, 1.0, "SELL"));
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].tf, "imbalance:3");
+        assert_eq!(bars[0].volume, 11.0);
+    }
+
+    #[test]
+    fn test_resample_bars() {
+        let agg = BarAggregator::new(vec!["5m".into(), "15m".into(), "tick:5".into()]).unwrap();
+        let minute = |i: u64, price: f64| {
+            Bar::new(i * 60_000, price, price + 1.0, price - 1.0, price + 0.5, 1.0, "1m".to_string(), "AAPL".to_string())
+        };
+        
+        let mut bars = Vec::new();
+        for i in 0..15 {
+            bars.extend(agg.on_bar(&minute(i, 100.0 + i as f64)));
+        }
+        // 5m cierra con su quinta sub-barra y 15m con la decimoquinta
+        let tfs: Vec<&str> = bars.iter().map(|b| b.tf.as_str()).collect();
+        assert_eq!(tfs, vec!["5m", "5m", "5m", "15m"]);
+        
+        let first = &bars[0];
+        assert_eq!(first.ts, 0);
+        assert_eq!((first.open, first.high, first.low, first.close), (100.0, 105.0, 99.0, 104.5));
+        assert_eq!(first.volume, 5.0);
+        let quarter = &bars[3];
+        assert_eq!((quarter.ts, quarter.open, quarter.close, quarter.volume), (0, 100.0, 114.5, 15.0));
+        assert!(agg.get_current_bar("AAPL", "tick:5").is_none());
+    }
+
+    #[test]
+    fn test_resample_with_missing_bars() {
+        let agg = BarAggregator::new(vec!["5m".into()]).unwrap();
+        let bar = |ts: u64, tf: &str| Bar::new(ts, 10.0, 11.0, 9.0, 10.0, 2.0, tf.to_string(), "AAPL".to_string());
+        
+        assert!(agg.on_bar(&bar(0, "1m")).is_empty());
+        assert!(agg.on_bar(&bar(60_000, "1m")).is_empty());
+        // Sin barras de 3m-4m: el bucket cierra al llegar la del siguiente
+        let bars = agg.on_bar(&bar(300_000, "1m"));
+        assert_eq!(bars.len(), 1);
+        assert_eq!((bars[0].ts, bars[0].volume), (0, 4.0));
+        
+        // Timeframes iguales o que no dividen el periodo se ignoran
+        assert!(agg.on_bar(&bar(300_000, "5m")).is_empty());
+        assert!(agg.on_bar(&bar(300_000, "2m")).is_empty());
+        assert!(agg.on_bar(&bar(300_000, "tick:5")).is_empty());
+        assert_eq!(agg.get_current_bar("AAPL", "5m").unwrap().volume, 2.0);
+    }
+
+    #[test]
+    fn test_unsubscribe_and_reset() {
+        let mut agg = BarAggregator::new(vec!["tick:5".into(), "1m".into()]).unwrap();
+        agg.on_trade(&trade(1, 100.0, 1.0, "BUY"));
+        
+        agg.unsubscribe("tick:5");
+        assert_eq!(agg.subscriptions(), vec!["1m".to_string()]);
+        assert!(agg.get_current_bar("AAPL", "tick:5").is_none());
+        
+        agg.reset_symbol("AAPL");
+        assert!(agg.get_current_bar("AAPL", "1m").is_none());
+    }
+}
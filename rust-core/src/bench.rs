@@ -0,0 +1,389 @@
+//! # Bench
+//!
+//! Datos sintéticos y medición de latencia por evento, compartidos por
+//! `benchmark_indicators` (Python) y los benches criterion de `benches/`:
+//! - `SyntheticConfig`: parámetros del flujo sintético (símbolos, precio,
+//!   tick, profundidad del libro, semilla)
+//! - `SyntheticData`: trades con random walk por símbolo y los snapshots y
+//!   barras derivados de ellos
+//! - `run_all`: recorre todos los engines y paths batch y devuelve
+//!   ops/s y latencias p50/p99 por evento
+//!
+//! El generador es determinista (xorshift) para que dos corridas con la
+//! misma semilla sean comparables.
+
+use std::collections::HashMap;
+use std::time::Instant;
+use crate::bars::BarAggregator;
+use crate::indicators::*;
+use crate::manager::EngineManager;
+#[cfg(feature = "python")]
+use crate::numpy_batch;
+use crate::order_book::OrderBookManager;
+use crate::types::{Bar, BookSnapshot, Level, Liquidation, OpenInterest, Trade};
+
+/// Parámetros del flujo sintético
+#[derive(Clone, Debug)]
+pub struct SyntheticConfig {
+    pub n_events: usize,
+    pub n_symbols: usize,
+    pub base_price: f64,
+    pub tick_size: f64,
+    pub depth_levels: usize,
+    /// Separación entre trades consecutivos (ms)
+    pub interval_ms: u64,
+    /// Trades por barra y símbolo
+    pub trades_per_bar: usize,
+    pub seed: u64,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            n_events: 10_000,
+            n_symbols: 1,
+            base_price: 100.0,
+            tick_size: 0.01,
+            depth_levels: 10,
+            interval_ms: 10,
+            trades_per_bar: 10,
+            seed: 42,
+        }
+    }
+}
+
+/// Generador xorshift64 (sin dependencias; suficiente para datos de bench)
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // El estado 0 es un punto fijo
+        Self(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Uniforme en [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Trades, snapshots y barras de un mismo flujo
+#[derive(Clone, Debug, Default)]
+pub struct SyntheticData {
+    pub trades: Vec<Trade>,
+    pub snapshots: Vec<BookSnapshot>,
+    pub bars: Vec<Bar>,
+}
+
+impl SyntheticData {
+    /// Genera `n_events` trades repartidos entre los símbolos y deriva el resto
+    pub fn generate(config: &SyntheticConfig) -> Self {
+        Self::from_trades(synthetic_trades(config), config)
+    }
+
+    /// Deriva snapshots y barras de trades ya existentes (p.ej. los pasados desde Python)
+    pub fn from_trades(trades: Vec<Trade>, config: &SyntheticConfig) -> Self {
+        let snapshots = snapshots_from_trades(&trades, config.depth_levels, config.tick_size, config.seed);
+        let bars = bars_from_trades(&trades, config.trades_per_bar);
+        Self { trades, snapshots, bars }
+    }
+}
+
+/// Trades con random walk de ±1 tick por símbolo, lado y tamaño aleatorios
+pub fn synthetic_trades(config: &SyntheticConfig) -> Vec<Trade> {
+    let n_symbols = config.n_symbols.max(1);
+    let mut rng = XorShift::new(config.seed);
+    let mut prices = vec![config.base_price; n_symbols];
+    (0..config.n_events)
+        .map(|i| {
+            let s = i % n_symbols;
+            let step = match rng.next_u64() % 3 {
+                0 => -config.tick_size,
+                1 => 0.0,
+                _ => config.tick_size,
+            };
+            prices[s] = (prices[s] + step).max(config.tick_size);
+            let size = 1.0 + (rng.next_f64() * 100.0).floor();
+            let mut trade = Trade::new(i as u64 * config.interval_ms, prices[s], size, format!("SYM{}", s));
+            trade.side = Some(if rng.next_u64().is_multiple_of(2) { "BUY" } else { "SELL" }.to_string());
+            trade
+        })
+        .collect()
+}
+
+/// Un snapshot por trade, centrado en su precio, con `depth_levels` niveles por lado
+pub fn snapshots_from_trades(trades: &[Trade], depth_levels: usize, tick_size: f64, seed: u64) -> Vec<BookSnapshot> {
+    let mut rng = XorShift::new(seed ^ 0x9E37_79B9_7F4A_7C15);
+    trades.iter()
+        .map(|t| {
+            let mut side = |sign: f64| -> Vec<Level> {
+                (1..=depth_levels)
+                    .map(|i| Level::new(t.price + sign * tick_size * i as f64, 10.0 + (rng.next_f64() * 500.0).floor()))
+                    .collect()
+            };
+            let bids = side(-1.0);
+            let asks = side(1.0);
+            BookSnapshot::new(t.ts, t.symbol.clone(), bids, asks)
+        })
+        .collect()
+}
+
+/// Agrupa cada `trades_per_bar` trades de un símbolo en una barra "1m"
+pub fn bars_from_trades(trades: &[Trade], trades_per_bar: usize) -> Vec<Bar> {
+    let trades_per_bar = trades_per_bar.max(1);
+    let mut open: HashMap<&str, (Bar, usize)> = HashMap::new();
+    let mut bars = Vec::with_capacity(trades.len() / trades_per_bar);
+    for t in trades {
+        let (bar, count) = open.entry(t.symbol.as_str()).or_insert_with(|| {
+            (Bar::new(t.ts, t.price, t.price, t.price, t.price, 0.0, "1m".to_string(), t.symbol.clone()), 0)
+        });
+        bar.ts = t.ts;
+        bar.high = bar.high.max(t.price);
+        bar.low = bar.low.min(t.price);
+        bar.close = t.price;
+        bar.volume += t.size;
+        *count += 1;
+        if *count == trades_per_bar {
+            if let Some((bar, _)) = open.remove(t.symbol.as_str()) {
+                bars.push(bar);
+            }
+        }
+    }
+    bars
+}
+
+/// Rendimiento de un engine sobre un flujo
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatencyStats {
+    pub events: usize,
+    pub total_secs: f64,
+    pub ops_per_sec: f64,
+    pub p50_ns: f64,
+    pub p99_ns: f64,
+}
+
+impl LatencyStats {
+    /// `samples_ns`: latencia por evento de cada muestra
+    pub fn from_samples(mut samples_ns: Vec<f64>, events: usize, total_secs: f64) -> Self {
+        samples_ns.sort_unstable_by(|a, b| a.total_cmp(b));
+        Self {
+            events,
+            total_secs,
+            ops_per_sec: if total_secs > 0.0 { events as f64 / total_secs } else { 0.0 },
+            p50_ns: percentile(&samples_ns, 0.50),
+            p99_ns: percentile(&samples_ns, 0.99),
+        }
+    }
+
+    pub fn to_map(&self) -> HashMap<String, f64> {
+        HashMap::from([
+            ("events".to_string(), self.events as f64),
+            ("total_secs".to_string(), self.total_secs),
+            ("ops_per_sec".to_string(), self.ops_per_sec),
+            ("p50_ns".to_string(), self.p50_ns),
+            ("p99_ns".to_string(), self.p99_ns),
+        ])
+    }
+}
+
+/// Percentil por rango más cercano sobre muestras ordenadas (0.0 si no hay)
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Cronometra `f` evento a evento, `iterations` pasadas sobre `events`
+pub fn measure_each<T>(events: &[T], iterations: usize, mut f: impl FnMut(&T)) -> LatencyStats {
+    let mut samples = Vec::with_capacity(events.len() * iterations);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for event in events {
+            let t0 = Instant::now();
+            f(event);
+            samples.push(t0.elapsed().as_nanos() as f64);
+        }
+    }
+    LatencyStats::from_samples(samples, events.len() * iterations, start.elapsed().as_secs_f64())
+}
+
+/// Cronometra un path batch de `events` eventos; la latencia por evento de
+/// cada pasada es su duración entre `events`
+pub fn measure_batch(events: usize, iterations: usize, mut f: impl FnMut()) -> LatencyStats {
+    let mut samples = Vec::with_capacity(iterations);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let t0 = Instant::now();
+        f();
+        samples.push(t0.elapsed().as_nanos() as f64 / events.max(1) as f64);
+    }
+    LatencyStats::from_samples(samples, events * iterations, start.elapsed().as_secs_f64())
+}
+
+/// Mide todos los engines (streaming) y los paths batch sobre `data`.
+/// Cada engine empieza con estado vacío.
+pub fn run_all(data: &SyntheticData, iterations: usize, tick_size: f64) -> HashMap<String, LatencyStats> {
+    let SyntheticData { trades, snapshots, bars } = data;
+    let iterations = iterations.max(1);
+    let mut results = HashMap::new();
+    let mut add = |name: &str, stats: LatencyStats| {
+        results.insert(name.to_string(), stats);
+    };
+
+    // Engines de trades
+    let engine = CVDEngine::new();
+    add("cvd", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    if let Ok(engine) = CVDBarEngine::new("1m") {
+        add("cvd_bars", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+    if let Ok(engine) = FootprintEngine::new("1m", tick_size) {
+        add("footprint", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+    let engine = VWAPEngine::new();
+    add("vwap", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    if let Ok(engine) = RollingVWAPEngine::new(Some(60_000), None) {
+        add("rolling_vwap", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+    if let Ok(engine) = VolumeProfileEngine::new(0.7, tick_size) {
+        add("volume_profile", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+    if let Ok(engine) = VPINEngine::new(1_000.0, 50) {
+        add("vpin", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+    if let Ok(engine) = TapeEngine::new(10_000, 0.99, 1000, 100) {
+        add("tape", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+    if let Ok(engine) = BarAggregator::new(vec!["1s".to_string(), "1m".to_string()]) {
+        add("bar_aggregator", measure_each(trades, iterations, |t| { let _ = engine.on_trade(t); }));
+    }
+
+    // Engines del libro
+    let engine = LiquidityEngine::new();
+    add("liquidity", measure_each(snapshots, iterations, |s| { let _ = engine.on_snapshot(s); }));
+    let engine = HeatmapEngine::default();
+    add("heatmap", measure_each(snapshots, iterations, |s| { let _ = engine.on_snapshot(s); }));
+    if let Ok(engine) = BookPressureEngine::new(10_000, None, 10) {
+        add("book_pressure", measure_each(snapshots, iterations, |s| { let _ = engine.on_snapshot(s); }));
+    }
+    if let Ok(engine) = IcebergDetector::new(2.0, 2, tick_size) {
+        let events: Vec<_> = trades.iter().zip(snapshots).collect();
+        add("iceberg", measure_each(&events, iterations, |(t, s)| {
+            engine.on_trade(t);
+            let _ = engine.on_snapshot(s);
+        }));
+    }
+    let engine = OrderBookManager::new(None, None);
+    add("order_book", measure_each(snapshots, iterations, |s| engine.apply_snapshot(s, None)));
+
+    // Engines de barras
+    if let Ok(engine) = MovingAverageEngine::new(vec![9, 21, 50]) {
+        add("moving_averages", measure_each(bars, iterations, |b| { let _ = engine.on_bar(b); }));
+    }
+    if let Ok(engine) = RSIEngine::new(vec![14]) {
+        add("rsi", measure_each(bars, iterations, |b| { let _ = engine.on_bar(b); }));
+    }
+    if let Ok(engine) = ATREngine::new(vec![14]) {
+        add("atr", measure_each(bars, iterations, |b| { let _ = engine.on_bar(b); }));
+    }
+    if let Ok(engine) = VolatilityEngine::new(vec![20], 2.0, 1000, 60) {
+        add("volatility", measure_each(bars, iterations, |b| { let _ = engine.on_bar(b); }));
+    }
+
+    // Derivados: open interest y liquidaciones sintéticos a partir de los trades
+    if let Ok(engine) = OIEngine::new(300_000) {
+        let events: Vec<_> = trades.iter()
+            .map(|t| OpenInterest::new(t.ts, t.symbol.clone(), 1_000_000.0 + t.size * 10.0, None))
+            .collect();
+        add("open_interest", measure_each(&events, iterations, |oi| { let _ = engine.on_open_interest(oi); }));
+    }
+    if let Ok(engine) = LiquidationEngine::new(60_000, 1.0, 3_600_000, 10_000, None) {
+        let events: Vec<_> = trades.iter()
+            .map(|t| Liquidation::new(t.ts, t.symbol.clone(), t.side.clone().unwrap_or_default(), t.price, t.size, None))
+            .collect();
+        add("liquidations", measure_each(&events, iterations, |l| { let _ = engine.on_liquidation(l); }));
+    }
+
+    // Paths batch
+    let engine = VWAPEngine::new();
+    add("vwap_batch", measure_batch(trades.len(), iterations, || { let _ = engine.trade_batch(trades); }));
+    if let Ok(engine) = RSIEngine::new(vec![14]) {
+        add("rsi_batch", measure_batch(bars.len(), iterations, || {
+            let _: Vec<_> = bars.iter().flat_map(|b| engine.on_bar(b)).collect();
+        }));
+    }
+    if let Ok(engine) = ATREngine::new(vec![14]) {
+        add("atr_batch", measure_batch(bars.len(), iterations, || {
+            let _: Vec<_> = bars.iter().flat_map(|b| engine.on_bar(b)).collect();
+        }));
+    }
+    #[cfg(feature = "python")]
+    {
+        let engine = CVDEngine::new();
+        add("cvd_numpy", measure_batch(trades.len(), iterations, || {
+            let results: Vec<_> = trades.iter().map(|t| engine.on_trade(t)).collect();
+            let _ = numpy_batch::cvd_columns(&results);
+        }));
+    }
+    if let Ok(manager) = EngineManager::new(true, true, true, true, None, None, None) {
+        add("manager", measure_each(trades, iterations, |t| { let _ = manager.on_trade(t); }));
+    }
+    if let Ok(manager) = EngineManager::new(true, true, true, true, None, None, None) {
+        add("manager_parallel", measure_batch(trades.len(), iterations, || { let _ = manager.dispatch_parallel(trades); }));
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_data_is_deterministic() {
+        let config = SyntheticConfig { n_events: 200, n_symbols: 3, ..Default::default() };
+        let a = SyntheticData::generate(&config);
+        let b = SyntheticData::generate(&config);
+
+        assert_eq!(a.trades.len(), 200);
+        assert_eq!(a.snapshots.len(), 200);
+        assert_eq!(a.bars.len(), 18);
+        assert!(a.trades.iter().zip(&b.trades).all(|(x, y)| x.price == y.price && x.size == y.size));
+        assert_eq!(a.snapshots[0].bids.len(), config.depth_levels);
+        assert!(a.snapshots.iter().all(|s| s.bids[0].price < s.asks[0].price));
+    }
+
+    #[test]
+    fn test_latency_stats_percentiles() {
+        let samples: Vec<f64> = (1..=100).map(|i| i as f64).collect();
+        let stats = LatencyStats::from_samples(samples, 100, 0.5);
+
+        assert_eq!(stats.ops_per_sec, 200.0);
+        assert_eq!(stats.p50_ns, 51.0);
+        assert_eq!(stats.p99_ns, 99.0);
+    }
+
+    #[test]
+    fn test_run_all_covers_every_engine() {
+        let config = SyntheticConfig { n_events: 100, n_symbols: 2, ..Default::default() };
+        let results = run_all(&SyntheticData::generate(&config), 1, config.tick_size);
+
+        for name in ["cvd", "vwap", "liquidity", "heatmap", "rsi", "atr", "vwap_batch", "manager_parallel"] {
+            let stats = &results[name];
+            assert!(stats.events > 0, "{} sin eventos", name);
+            assert!(stats.p99_ns >= stats.p50_ns);
+        }
+        // cvd_numpy solo con la feature python
+        assert_eq!(results.len(), if cfg!(feature = "python") { 26 } else { 25 });
+    }
+}
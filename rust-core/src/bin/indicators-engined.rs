@@ -0,0 +1,107 @@
+//! `indicators-engined`: servicio que consume market data de NATS o Kafka,
+//! calcula los indicadores y publica las métricas, sin Python.
+//! 
+//! ```text
+//! indicators-engined --config /etc/indicators/daemon.toml
+//! indicators-engined --config daemon.toml --check
+//! ```
+//! 
+//! Formato del fichero: ver `indicators_core::daemon`. Se detiene con
+//! SIGINT o SIGTERM tras enviar las métricas pendientes.
+
+use indicators_core::daemon::{Daemon, DaemonConfig};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: indicators-engined --config <PATH> [--check]
+
+Options:
+  -c, --config <PATH>  daemon config file (TOML)
+      --check          validate the config and exit
+  -h, --help           print this help
+  -V, --version        print the version";
+
+struct Args {
+    config: PathBuf,
+    check: bool,
+}
+
+/// None si se pidió ayuda o versión (ya impresas)
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut config = None;
+    let mut check = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--config" => config = Some(args.next().ok_or("--config requires a path")?),
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                println!("indicators-engined {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            other => match other.strip_prefix("--config=") {
+                Some(path) => config = Some(path.to_string()),
+                None => return Err(format!("unexpected argument {}", other)),
+            },
+        }
+    }
+    let config = config.ok_or("missing --config")?;
+    Ok(Some(Args { config: PathBuf::from(config), check }))
+}
+
+/// Espera a SIGINT (Ctrl+C) o SIGTERM
+#[cfg(unix)]
+async fn shutdown_signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let config = DaemonConfig::load(&args.config).map_err(|e| e.to_string())?;
+    if args.check {
+        config.validate().map_err(|e| e.to_string())?;
+        println!("{}: ok", args.config.display());
+        return Ok(());
+    }
+    config.logging.install().map_err(|e| e.to_string())?;
+    
+    // Solo se reciben señales: los suscriptores tienen su propio runtime
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("tokio runtime error: {}", e))?;
+    let mut daemon = Daemon::start(&config).map_err(|e| e.to_string())?;
+    let signal = runtime.block_on(shutdown_signal());
+    tracing::info!("shutting down");
+    daemon.stop();
+    signal.map_err(|e| format!("cannot listen for signals: {}", e))
+}
+
+fn main() -> ExitCode {
+    let result = match parse_args() {
+        Ok(Some(args)) => run(args),
+        Ok(None) => Ok(()),
+        Err(e) => Err(format!("{}\n\n{}", e, USAGE)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("indicators-engined: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
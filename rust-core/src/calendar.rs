@@ -0,0 +1,375 @@
+//! # Session Calendar
+//! 
+//! Horario de mercado: una sesión diaria [open, close) en hora local de la
+//! zona horaria del mercado, que abre los días de la semana configurados
+//! salvo festivos. Un cierre igual o anterior a la apertura es una sesión
+//! que cruza medianoche (p. ej. CME 17:00 → 16:00) y pertenece, para
+//! `weekdays` y festivos, al día en que abre. Los cambios de horario de
+//! verano se respetan: la apertura es siempre la misma hora de reloj local.
+//! 
+//! Hay presets de los mercados habituales (`SessionCalendar.preset("nyse")`)
+//! y calendarios definidos por el usuario. Es la definición de "sesión" que
+//! comparten el host y el core:
+//! - `VWAPEngine` y `CVDEngine` resetean al empezar cada sesión
+//! - `GapDetector` solo cuenta como hueco el tiempo de mercado abierto
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use std::collections::BTreeSet;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Milisegundos de un día
+pub const DAY_MS: u64 = 86_400_000;
+
+/// Días hacia atrás o adelante en que se busca una sesión (fines de semana
+/// largos y festivos encadenados)
+const MAX_SEARCH_DAYS: u64 = 31;
+
+/// Presets: (nombre, apertura, cierre, días de apertura, zona horaria)
+const PRESETS: &[(&str, &str, &str, &[u8], &str)] = &[
+    ("24/7", "00:00", "24:00", &[0, 1, 2, 3, 4, 5, 6], "UTC"),
+    ("nyse", "09:30", "16:00", &[0, 1, 2, 3, 4], "America/New_York"),
+    ("nasdaq", "09:30", "16:00", &[0, 1, 2, 3, 4], "America/New_York"),
+    ("cme", "17:00", "16:00", &[6, 0, 1, 2, 3], "America/Chicago"),
+    ("lse", "08:00", "16:30", &[0, 1, 2, 3, 4], "Europe/London"),
+    ("xetra", "09:00", "17:30", &[0, 1, 2, 3, 4], "Europe/Berlin"),
+    ("tse", "09:00", "15:00", &[0, 1, 2, 3, 4], "Asia/Tokyo"),
+];
+
+/// Parsea "HH:MM" (o "HH:MM:SS") a ms desde medianoche; admite "24:00"
+pub fn parse_time_of_day(time: &str) -> Option<u64> {
+    let mut parts = time.trim().split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: u64 = match parts.next() {
+        Some(s) => s.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    let ms = ((hours * 60 + minutes) * 60 + seconds) * 1_000;
+    (ms <= DAY_MS).then_some(ms)
+}
+
+/// Día de la semana (0 = lunes) de un día contado desde epoch (jueves)
+pub fn weekday(epoch_day: u64) -> u8 {
+    ((epoch_day + 3) % 7) as u8
+}
+
+/// Parsea una fecha "YYYY-MM-DD"
+fn parse_date(date: &str) -> Result<NaiveDate, ProcessError> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| {
+        ProcessError::invalid(format!("invalid date: {} (YYYY-MM-DD)", date))
+    })
+}
+
+py_class! {
+/// Calendario de sesiones de un mercado
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionCalendar {
+    /// Apertura en ms desde medianoche local
+    #[pyo3(get)]
+    pub open_ms: u64,
+    /// Cierre en ms desde medianoche local (<= apertura: cruza medianoche)
+    #[pyo3(get)]
+    pub close_ms: u64,
+    /// Días en que abre la sesión (0 = lunes ... 6 = domingo)
+    #[pyo3(get)]
+    pub weekdays: Vec<u8>,
+    tz: Tz,
+    holidays: BTreeSet<NaiveDate>,
+}
+}
+
+impl Default for SessionCalendar {
+    /// Mercado abierto 24/7
+    fn default() -> Self {
+        Self {
+            open_ms: 0,
+            close_ms: DAY_MS,
+            weekdays: (0..7).collect(),
+            tz: Tz::UTC,
+            holidays: BTreeSet::new(),
+        }
+    }
+}
+
+py_methods! {
+#[pymethods]
+impl SessionCalendar {
+    #[new]
+    #[pyo3(signature = (open="00:00", close="24:00", weekdays=None, timezone="UTC", holidays=None))]
+    pub fn new(open: &str, close: &str, weekdays: Option<Vec<u8>>, timezone: &str,
+               holidays: Option<Vec<String>>) -> Result<Self, ProcessError> {
+        let (Some(open_ms), Some(close_ms)) = (parse_time_of_day(open), parse_time_of_day(close)) else {
+            return Err(ProcessError::invalid(
+                format!("invalid session hours: {} - {} (HH:MM)", open, close)));
+        };
+        let mut weekdays = weekdays.unwrap_or_else(|| (0..7).collect());
+        weekdays.sort_unstable();
+        weekdays.dedup();
+        if weekdays.is_empty() || weekdays.iter().any(|d| *d > 6) {
+            return Err(ProcessError::invalid(
+                "weekdays must be a non-empty list of 0 (monday) .. 6 (sunday)"));
+        }
+        let tz: Tz = timezone.parse().map_err(|_| {
+            ProcessError::invalid(format!("unknown timezone: {}", timezone))
+        })?;
+        let holidays = holidays.unwrap_or_default()
+            .iter()
+            .map(|d| parse_date(d))
+            .collect::<Result<BTreeSet<_>, ProcessError>>()?;
+        Ok(Self {
+            open_ms: open_ms % DAY_MS,
+            close_ms,
+            weekdays,
+            tz,
+            holidays,
+        })
+    }
+    
+    /// Calendario de un mercado conocido (ver `presets()`), sin festivos
+    #[staticmethod]
+    pub fn preset(name: &str) -> Result<Self, ProcessError> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = if name == "crypto" { "24/7" } else { name.as_str() };
+        let Some((_, open, close, weekdays, timezone)) = PRESETS.iter().find(|p| p.0 == name) else {
+            return Err(ProcessError::invalid(
+                format!("unknown calendar preset: {} ({})", name, Self::presets().join(", "))));
+        };
+        Self::new(open, close, Some(weekdays.to_vec()), timezone, None)
+    }
+    
+    /// Nombres de los presets disponibles
+    #[staticmethod]
+    pub fn presets() -> Vec<String> {
+        PRESETS.iter().map(|p| p.0.to_string()).collect()
+    }
+    
+    /// Zona horaria IANA del calendario
+    #[getter]
+    pub fn timezone(&self) -> String {
+        self.tz.name().to_string()
+    }
+    
+    /// Festivos ("YYYY-MM-DD") ordenados
+    #[getter]
+    pub fn holidays(&self) -> Vec<String> {
+        self.holidays.iter().map(|d| d.format("%Y-%m-%d").to_string()).collect()
+    }
+    
+    /// Marca un día ("YYYY-MM-DD") como festivo: la sesión que abre ese día no existe
+    pub fn add_holiday(&mut self, date: &str) -> Result<(), ProcessError> {
+        self.holidays.insert(parse_date(date)?);
+        Ok(())
+    }
+    
+    /// Quita un festivo; false si no estaba
+    pub fn remove_holiday(&mut self, date: &str) -> Result<bool, ProcessError> {
+        Ok(self.holidays.remove(&parse_date(date)?))
+    }
+    
+    /// Indica si el mercado está abierto en ts
+    pub fn is_open(&self, ts: u64) -> bool {
+        self.sessions(ts, ts + 1).next().is_some()
+    }
+    
+    /// Tiempo de mercado abierto (ms) en el intervalo [start, end)
+    pub fn open_ms_between(&self, start: u64, end: u64) -> u64 {
+        if end <= start {
+            return 0;
+        }
+        self.sessions(start, end)
+            .map(|(open, close)| close.min(end) - open.max(start))
+            .sum()
+    }
+    
+    /// Inicio y fin de la sesión abierta en ts
+    pub fn session_bounds(&self, ts: u64) -> Option<(u64, u64)> {
+        self.sessions(ts, ts + 1).next()
+    }
+    
+    /// Apertura de la última sesión que empezó en o antes de ts (la sesión
+    /// a la que pertenece ts, aunque el mercado ya haya cerrado)
+    pub fn session_start(&self, ts: u64) -> Option<u64> {
+        let date = self.local_date(ts);
+        (0..MAX_SEARCH_DAYS)
+            .filter_map(|back| date.checked_sub_days(Days::new(back)))
+            .filter_map(|day| self.session_on(day))
+            .map(|(open, _)| open)
+            .find(|open| *open <= ts)
+    }
+    
+    /// Próxima apertura estrictamente posterior a ts
+    pub fn next_open(&self, ts: u64) -> Option<u64> {
+        let date = self.local_date(ts);
+        (0..MAX_SEARCH_DAYS)
+            .filter_map(|ahead| date.checked_add_days(Days::new(ahead)))
+            .filter_map(|day| self.session_on(day))
+            .map(|(open, _)| open)
+            .find(|open| *open > ts)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SessionCalendar(open_ms={}, close_ms={}, weekdays={:?}, timezone={}, holidays={})",
+                self.open_ms, self.close_ms, self.weekdays, self.tz.name(), self.holidays.len())
+    }
+}
+}
+
+impl SessionCalendar {
+    /// Duración de reloj local de una sesión
+    fn session_ms(&self) -> u64 {
+        if self.close_ms > self.open_ms {
+            self.close_ms - self.open_ms
+        } else {
+            self.close_ms + DAY_MS - self.open_ms
+        }
+    }
+    
+    /// Fecha local de un timestamp
+    fn local_date(&self, ts: u64) -> NaiveDate {
+        self.tz.timestamp_millis_opt(ts as i64)
+            .single()
+            .map(|dt| dt.date_naive())
+            .unwrap_or_default()
+    }
+    
+    /// Hora local a ms UTC; una hora inexistente (salto de horario de
+    /// verano) se desplaza una hora
+    fn to_utc(&self, local: NaiveDateTime) -> Option<u64> {
+        let dt = self.tz.from_local_datetime(&local).earliest()
+            .or_else(|| self.tz.from_local_datetime(&(local + Duration::hours(1))).earliest())?;
+        u64::try_from(dt.timestamp_millis()).ok()
+    }
+    
+    /// Sesión (inicio, fin) que abre en una fecha local; None si no abre
+    fn session_on(&self, date: NaiveDate) -> Option<(u64, u64)> {
+        let weekday = date.weekday().num_days_from_monday() as u8;
+        if !self.weekdays.contains(&weekday) || self.holidays.contains(&date) {
+            return None;
+        }
+        let midnight = date.and_hms_opt(0, 0, 0)?;
+        let open = self.to_utc(midnight + Duration::milliseconds(self.open_ms as i64))?;
+        let close = self.to_utc(midnight + Duration::milliseconds((self.open_ms + self.session_ms()) as i64))?;
+        Some((open, close))
+    }
+    
+    /// Sesiones (inicio, fin) que se solapan con [start, end), en orden
+    fn sessions(&self, start: u64, end: u64) -> impl Iterator<Item = (u64, u64)> + '_ {
+        // La sesión del día anterior puede cruzar medianoche
+        let first = self.local_date(start);
+        let first = first.pred_opt().unwrap_or(first);
+        let last = self.local_date(end.saturating_sub(1));
+        first.iter_days()
+            .take_while(move |day| *day <= last)
+            .filter_map(move |day| self.session_on(day))
+            .filter(move |(open, close)| *open < end && *close > start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Lunes 2024-01-01 00:00 UTC
+    const MONDAY: u64 = 1_704_067_200_000;
+    const HOUR: u64 = 3_600_000;
+    
+    fn utc(open: &str, close: &str, weekdays: Vec<u8>) -> SessionCalendar {
+        SessionCalendar::new(open, close, Some(weekdays), "UTC", None).unwrap()
+    }
+    
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("09:30"), Some(9 * HOUR + 30 * 60_000));
+        assert_eq!(parse_time_of_day("24:00"), Some(DAY_MS));
+        assert_eq!(parse_time_of_day("16:00:30"), Some(16 * HOUR + 30_000));
+        assert_eq!(parse_time_of_day("24:01"), None);
+        assert_eq!(parse_time_of_day("9h"), None);
+        assert_eq!(weekday(MONDAY / DAY_MS), 0);
+    }
+    
+    #[test]
+    fn test_weekday_session() {
+        let calendar = utc("14:30", "21:00", vec![0, 1, 2, 3, 4]);
+        assert!(!calendar.is_open(MONDAY + 14 * HOUR));
+        assert!(calendar.is_open(MONDAY + 15 * HOUR));
+        assert!(!calendar.is_open(MONDAY + 21 * HOUR));
+        // Sábado cerrado
+        assert!(!calendar.is_open(MONDAY + 5 * DAY_MS + 15 * HOUR));
+        assert_eq!(calendar.session_bounds(MONDAY + 15 * HOUR),
+                   Some((MONDAY + 14 * HOUR + HOUR / 2, MONDAY + 21 * HOUR)));
+        
+        // Del viernes 20:00 al lunes 15:00: 1h del viernes + 30 min del lunes
+        let friday = MONDAY + 4 * DAY_MS;
+        assert_eq!(calendar.open_ms_between(friday + 20 * HOUR, MONDAY + 7 * DAY_MS + 15 * HOUR),
+                   HOUR + HOUR / 2);
+    }
+    
+    #[test]
+    fn test_overnight_session() {
+        // Domingo a jueves 23:00 → 22:00 del día siguiente
+        let calendar = utc("23:00", "22:00", vec![6, 0, 1, 2, 3]);
+        assert!(calendar.is_open(MONDAY + HOUR));
+        assert!(!calendar.is_open(MONDAY + 22 * HOUR + HOUR / 2));
+        assert!(calendar.is_open(MONDAY + 23 * HOUR + HOUR / 2));
+        // El viernes cierra a las 22:00 y no reabre hasta el domingo
+        let friday = MONDAY + 4 * DAY_MS;
+        assert!(calendar.is_open(friday + 21 * HOUR));
+        assert!(!calendar.is_open(friday + 23 * HOUR + HOUR / 2));
+        assert_eq!(calendar.open_ms_between(MONDAY, MONDAY + DAY_MS), 23 * HOUR);
+        
+        assert!(SessionCalendar::new("25:00", "22:00", None, "UTC", None).is_err());
+        assert!(SessionCalendar::new("09:00", "17:00", Some(vec![7]), "UTC", None).is_err());
+        assert!(SessionCalendar::default().is_open(MONDAY));
+    }
+    
+    #[test]
+    fn test_timezone_and_dst() {
+        let nyse = SessionCalendar::preset("NYSE").unwrap();
+        assert_eq!(nyse.timezone(), "America/New_York");
+        // Viernes 2024-03-08 (EST): 09:30 local = 14:30 UTC
+        let friday = MONDAY + 67 * DAY_MS;
+        assert_eq!(nyse.session_bounds(friday + 15 * HOUR),
+                   Some((friday + 14 * HOUR + HOUR / 2, friday + 21 * HOUR)));
+        // Lunes 2024-03-11 (EDT): 09:30 local = 13:30 UTC
+        let monday = MONDAY + 70 * DAY_MS;
+        assert!(nyse.is_open(monday + 14 * HOUR));
+        assert_eq!(nyse.session_start(monday + 12 * HOUR), Some(friday + 14 * HOUR + HOUR / 2));
+        assert_eq!(nyse.next_open(friday + 22 * HOUR), Some(monday + 13 * HOUR + HOUR / 2));
+        
+        // CME: domingo 17:00 a lunes 16:00 de Chicago (UTC-6 en enero)
+        let cme = SessionCalendar::preset("cme").unwrap();
+        assert!(cme.is_open(MONDAY + 21 * HOUR));
+        assert!(!cme.is_open(MONDAY + 22 * HOUR + HOUR / 2));
+        assert!(cme.is_open(MONDAY + 23 * HOUR + HOUR / 2));
+        
+        assert!(SessionCalendar::preset("mars").is_err());
+        assert!(SessionCalendar::new("09:30", "16:00", None, "Mars/Olympus", None).is_err());
+        assert!(SessionCalendar::presets().contains(&"lse".to_string()));
+    }
+    
+    #[test]
+    fn test_holidays() {
+        let mut nyse = SessionCalendar::preset("nyse").unwrap();
+        // Jueves 2024-07-04 15:00 UTC
+        let july_4 = MONDAY + 185 * DAY_MS + 15 * HOUR;
+        assert!(nyse.is_open(july_4));
+        nyse.add_holiday("2024-07-04").unwrap();
+        assert!(!nyse.is_open(july_4));
+        assert_eq!(nyse.holidays(), vec!["2024-07-04".to_string()]);
+        // La sesión vigente sigue siendo la del día anterior
+        assert_eq!(nyse.session_start(july_4), Some(july_4 - DAY_MS - HOUR - HOUR / 2));
+        
+        assert!(nyse.add_holiday("04/07/2024").is_err());
+        assert!(nyse.remove_holiday("2024-07-04").unwrap());
+        assert!(nyse.is_open(july_4));
+    }
+}
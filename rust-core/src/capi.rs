@@ -0,0 +1,386 @@
+//! # C API
+//! 
+//! Funciones `extern "C"` (feature `capi`) para embeber el núcleo en
+//! aplicaciones C, C++ o C# sin Python. La cabecera
+//! `include/indicators_core.h` se genera con cbindgen desde este módulo
+//! mediante `cargo xtask header` (ver `cbindgen.toml`).
+//! 
+//! ```c
+//! IcEngine *engine = ic_engine_new(true, true, true, false, 0);
+//! IcTrade trade = { .ts = 1000, .price = 100.0, .size = 5.0, .symbol = "AAPL", .side = IC_SIDE_BUY };
+//! ic_engine_push_trade(engine, &trade);
+//! IcMetrics metrics;
+//! while (ic_engine_poll(engine, &metrics)) { ... }
+//! ic_engine_free(engine);
+//! ```
+//! 
+//! Solo usa el núcleo Rust (`EngineManager` y sus engines, con errores
+//! `ProcessError`) y nunca toma el GIL. Para una librería sin libpython hay
+//! que compilar sin la feature `python`:
+//! `cargo build --release --no-default-features --features capi`.
+//! 
+//! Cada evento que produce métricas deja un `IcMetrics` en la cola del
+//! engine; con la cola llena se descartan los más antiguos. Un `IcEngine`
+//! se puede usar desde varios hilos, salvo `ic_engine_poll`, cuyo símbolo
+//! solo es válido hasta la siguiente llamada.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::manager::EngineManager;
+use crate::types::{BookSnapshot, Level, MetricsBundle, Trade};
+
+/// Métricas en cola por defecto (capacidad 0 en `ic_engine_new`)
+pub const IC_DEFAULT_QUEUE_CAPACITY: usize = 65_536;
+
+/// Resultado de las funciones push: >= 0 correcto, < 0 error
+pub const IC_NO_METRICS: i32 = 0;
+pub const IC_METRICS_QUEUED: i32 = 1;
+pub const IC_ERR_NULL: i32 = -1;
+pub const IC_ERR_UTF8: i32 = -2;
+pub const IC_ERR_PANIC: i32 = -3;
+
+/// Lado del agresor (`IcTrade.side`); con otro valor lo deduce el
+/// clasificador del engine. Es un entero y no un enum para que un valor
+/// inesperado desde C no sea comportamiento indefinido.
+pub const IC_SIDE_UNKNOWN: i32 = 0;
+pub const IC_SIDE_BUY: i32 = 1;
+pub const IC_SIDE_SELL: i32 = 2;
+
+/// Trade de entrada; `symbol` es una cadena UTF-8 terminada en NUL
+#[repr(C)]
+pub struct IcTrade {
+    pub ts: u64,
+    pub price: f64,
+    pub size: f64,
+    pub symbol: *const c_char,
+    pub side: i32,
+}
+
+/// Nivel de libro de entrada
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct IcLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Métricas de un evento. Los bloques sin `has_*` quedan a 0.
+#[repr(C)]
+pub struct IcMetrics {
+    /// Válido hasta la siguiente llamada a `ic_engine_poll` o `ic_engine_free`
+    pub symbol: *const c_char,
+    pub ts: u64,
+    pub has_cvd: bool,
+    pub cvd: f64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub trade_count: u64,
+    pub has_vwap: bool,
+    pub vwap: f64,
+    pub vwap_std_dev: f64,
+    pub vwap_upper_band_1: f64,
+    pub vwap_lower_band_1: f64,
+    pub has_liquidity: bool,
+    pub mid: f64,
+    pub spread: f64,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub depth_imbalance: f64,
+    pub microprice: f64,
+}
+
+impl Default for IcMetrics {
+    fn default() -> Self {
+        Self {
+            symbol: std::ptr::null(),
+            ts: 0,
+            has_cvd: false,
+            cvd: 0.0,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            trade_count: 0,
+            has_vwap: false,
+            vwap: 0.0,
+            vwap_std_dev: 0.0,
+            vwap_upper_band_1: 0.0,
+            vwap_lower_band_1: 0.0,
+            has_liquidity: false,
+            mid: 0.0,
+            spread: 0.0,
+            best_bid: 0.0,
+            best_ask: 0.0,
+            depth_imbalance: 0.0,
+            microprice: 0.0,
+        }
+    }
+}
+
+/// Engine opaco para C: el `EngineManager` y su cola de métricas
+pub struct IcEngine {
+    manager: EngineManager,
+    capacity: usize,
+    queue: Mutex<VecDeque<MetricsBundle>>,
+    dropped: AtomicU64,
+    // Símbolo del último `IcMetrics` devuelto
+    polled_symbol: Mutex<Option<CString>>,
+}
+
+impl IcEngine {
+    fn enqueue(&self, bundle: MetricsBundle) -> i32 {
+        if bundle.is_empty() {
+            return IC_NO_METRICS;
+        }
+        let mut queue = self.queue.lock();
+        if queue.len() >= self.capacity {
+            queue.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        queue.push_back(bundle);
+        IC_METRICS_QUEUED
+    }
+}
+
+impl IcMetrics {
+    fn fill(&mut self, bundle: &MetricsBundle, symbol: *const c_char) {
+        *self = Self { symbol, ts: bundle.ts, ..Self::default() };
+        if let Some(cvd) = &bundle.cvd {
+            self.has_cvd = true;
+            self.cvd = cvd.cvd;
+            self.buy_volume = cvd.buy_volume;
+            self.sell_volume = cvd.sell_volume;
+            self.trade_count = cvd.trade_count;
+        }
+        if let Some(vwap) = &bundle.vwap {
+            self.has_vwap = true;
+            self.vwap = vwap.vwap;
+            self.vwap_std_dev = vwap.std_dev;
+            self.vwap_upper_band_1 = vwap.upper_band_1;
+            self.vwap_lower_band_1 = vwap.lower_band_1;
+        }
+        if let Some(liquidity) = &bundle.liquidity {
+            self.has_liquidity = true;
+            self.mid = liquidity.mid;
+            self.spread = liquidity.spread;
+            self.best_bid = liquidity.best_bid;
+            self.best_ask = liquidity.best_ask;
+            self.depth_imbalance = liquidity.depth_imbalance;
+            self.microprice = liquidity.microprice;
+        }
+    }
+}
+
+/// Un panic no debe cruzar la frontera con C
+fn guard<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(on_panic)
+}
+
+/// # Safety
+/// `symbol` debe ser NULL o una cadena terminada en NUL
+unsafe fn symbol_str<'a>(symbol: *const c_char) -> Result<&'a str, i32> {
+    if symbol.is_null() {
+        return Err(IC_ERR_NULL);
+    }
+    CStr::from_ptr(symbol).to_str().map_err(|_| IC_ERR_UTF8)
+}
+
+/// # Safety
+/// `levels` debe ser NULL (con `len` 0) o apuntar a `len` niveles
+unsafe fn levels_vec(levels: *const IcLevel, len: usize) -> Result<Vec<Level>, i32> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if levels.is_null() {
+        return Err(IC_ERR_NULL);
+    }
+    Ok(std::slice::from_raw_parts(levels, len).iter().map(|l| Level::new(l.price, l.size)).collect())
+}
+
+/// Crea un engine con los indicadores indicados; `queue_capacity` 0 usa
+/// `IC_DEFAULT_QUEUE_CAPACITY`. Devuelve NULL si no se pudo crear.
+#[no_mangle]
+pub extern "C" fn ic_engine_new(cvd: bool, vwap: bool, liquidity: bool, heatmap: bool, queue_capacity: usize) -> *mut IcEngine {
+    guard(std::ptr::null_mut(), || {
+        let Ok(manager) = EngineManager::new(cvd, vwap, liquidity, heatmap, None, None, None) else {
+            return std::ptr::null_mut();
+        };
+        let capacity = if queue_capacity == 0 { IC_DEFAULT_QUEUE_CAPACITY } else { queue_capacity };
+        Box::into_raw(Box::new(IcEngine {
+            manager,
+            capacity,
+            queue: Mutex::new(VecDeque::new()),
+            dropped: AtomicU64::new(0),
+            polled_symbol: Mutex::new(None),
+        }))
+    })
+}
+
+/// Libera el engine (NULL no hace nada)
+///
+/// # Safety
+/// `engine` debe venir de `ic_engine_new` y no usarse después
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_free(engine: *mut IcEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Procesa un trade. Devuelve `IC_METRICS_QUEUED` si produjo métricas,
+/// `IC_NO_METRICS` si no (inválido, duplicado o sin engine) o un error < 0.
+///
+/// # Safety
+/// `engine` debe ser un engine vivo y `trade` NULL o un `IcTrade` válido
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_push_trade(engine: *const IcEngine, trade: *const IcTrade) -> i32 {
+    let (Some(engine), Some(trade)) = (engine.as_ref(), trade.as_ref()) else {
+        return IC_ERR_NULL;
+    };
+    let symbol = match symbol_str(trade.symbol) {
+        Ok(symbol) => symbol.to_string(),
+        Err(code) => return code,
+    };
+    guard(IC_ERR_PANIC, || {
+        let mut event = Trade::new(trade.ts, trade.price, trade.size, symbol);
+        event.side = match trade.side {
+            IC_SIDE_BUY => Some("BUY".to_string()),
+            IC_SIDE_SELL => Some("SELL".to_string()),
+            _ => None,
+        };
+        engine.enqueue(engine.manager.on_trade(&event))
+    })
+}
+
+/// Procesa un snapshot de libro (bids de mejor a peor, asks de mejor a peor)
+///
+/// # Safety
+/// `engine` debe ser un engine vivo, `symbol` una cadena terminada en NUL y
+/// `bids` / `asks` apuntar a `n_bids` / `n_asks` niveles
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_push_book(
+    engine: *const IcEngine,
+    ts: u64,
+    symbol: *const c_char,
+    bids: *const IcLevel,
+    n_bids: usize,
+    asks: *const IcLevel,
+    n_asks: usize,
+) -> i32 {
+    let Some(engine) = engine.as_ref() else {
+        return IC_ERR_NULL;
+    };
+    let snapshot = symbol_str(symbol).and_then(|symbol| {
+        Ok(BookSnapshot::new(ts, symbol.to_string(), levels_vec(bids, n_bids)?, levels_vec(asks, n_asks)?))
+    });
+    match snapshot {
+        Ok(snapshot) => guard(IC_ERR_PANIC, || engine.enqueue(engine.manager.on_snapshot(&snapshot))),
+        Err(code) => code,
+    }
+}
+
+/// Saca las métricas más antiguas de la cola; false si está vacía
+///
+/// # Safety
+/// `engine` debe ser un engine vivo y `out` apuntar a un `IcMetrics` escribible
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_poll(engine: *const IcEngine, out: *mut IcMetrics) -> bool {
+    let (Some(engine), Some(out)) = (engine.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let Some(bundle) = engine.queue.lock().pop_front() else {
+        return false;
+    };
+    // Los símbolos no contienen NUL (vienen de CStr), pero por si acaso
+    let symbol = CString::new(bundle.symbol.replace('\0', "")).unwrap_or_default();
+    let mut polled = engine.polled_symbol.lock();
+    out.fill(&bundle, symbol.as_ptr());
+    *polled = Some(symbol);
+    true
+}
+
+/// Métricas pendientes en la cola
+///
+/// # Safety
+/// `engine` debe ser NULL o un engine vivo
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_pending(engine: *const IcEngine) -> usize {
+    engine.as_ref().map_or(0, |engine| engine.queue.lock().len())
+}
+
+/// Métricas descartadas por cola llena
+///
+/// # Safety
+/// `engine` debe ser NULL o un engine vivo
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_dropped(engine: *const IcEngine) -> u64 {
+    engine.as_ref().map_or(0, |engine| engine.dropped.load(Ordering::Relaxed))
+}
+
+/// Borra el estado de un símbolo en todos los indicadores
+///
+/// # Safety
+/// `engine` debe ser un engine vivo y `symbol` una cadena terminada en NUL
+#[no_mangle]
+pub unsafe extern "C" fn ic_engine_reset_symbol(engine: *const IcEngine, symbol: *const c_char) -> i32 {
+    let Some(engine) = engine.as_ref() else {
+        return IC_ERR_NULL;
+    };
+    match symbol_str(symbol) {
+        Ok(symbol) => guard(IC_ERR_PANIC, || {
+            engine.manager.reset_symbol(symbol);
+            IC_NO_METRICS
+        }),
+        Err(code) => code,
+    }
+}
+
+/// Versión de la librería (cadena estática)
+#[no_mangle]
+pub extern "C" fn ic_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_push_and_poll() {
+        let engine = ic_engine_new(true, true, true, false, 2);
+        assert!(!engine.is_null());
+        let symbol = CString::new("AAPL").unwrap();
+        let trade = |ts: u64, side| IcTrade { ts, price: 100.0, size: 5.0, symbol: symbol.as_ptr(), side };
+        let bids = [IcLevel { price: 99.0, size: 10.0 }];
+        let asks = [IcLevel { price: 101.0, size: 30.0 }];
+        
+        unsafe {
+            assert_eq!(ic_engine_push_trade(engine, &trade(1_000, IC_SIDE_BUY)), IC_METRICS_QUEUED);
+            assert_eq!(ic_engine_push_trade(engine, std::ptr::null()), IC_ERR_NULL);
+            assert_eq!(ic_engine_push_book(engine, 1_500, symbol.as_ptr(), bids.as_ptr(), 1, asks.as_ptr(), 1), IC_METRICS_QUEUED);
+            assert_eq!(ic_engine_push_book(engine, 1_600, symbol.as_ptr(), std::ptr::null(), 1, asks.as_ptr(), 1), IC_ERR_NULL);
+            assert_eq!(ic_engine_pending(engine), 2);
+            
+            let mut metrics = IcMetrics::default();
+            assert!(ic_engine_poll(engine, &mut metrics));
+            assert_eq!(CStr::from_ptr(metrics.symbol).to_str().unwrap(), "AAPL");
+            assert!(metrics.has_cvd && metrics.has_vwap && !metrics.has_liquidity);
+            assert_eq!((metrics.cvd, metrics.trade_count, metrics.vwap), (5.0, 1, 100.0));
+            assert!(ic_engine_poll(engine, &mut metrics));
+            assert!(metrics.has_liquidity && !metrics.has_cvd);
+            assert_eq!((metrics.mid, metrics.spread), (100.0, 2.0));
+            assert!(!ic_engine_poll(engine, &mut metrics));
+            
+            // Con la cola llena se descarta lo más antiguo
+            for ts in [2_000, 3_000, 4_000] {
+                ic_engine_push_trade(engine, &trade(ts, IC_SIDE_SELL));
+            }
+            assert_eq!((ic_engine_pending(engine), ic_engine_dropped(engine)), (2, 1));
+            assert!(ic_engine_poll(engine, &mut metrics));
+            assert_eq!(metrics.ts, 3_000);
+            ic_engine_free(engine);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! # Symbol Config
+//! 
+//! Configuración por símbolo (tick size, niveles de profundidad, bucket,
+//! calendario de sesión e indicadores activos) con un perfil por defecto.
+//! Los engines consultan el registro y caen en su configuración global para
+//! los campos que no estén definidos.
+//! 
+//! Formato (TOML o JSON con la misma estructura):
+//! ```toml
+//! [default]
+//! tick_size = 0.01
+//! depth_levels = 10
+//! 
+//! [symbols.ESZ5]
+//! tick_size = 0.25
+//! bucket_ms = 500
+//! session_period_ms = 86400000
+//! session_offset_ms = 79200000
+//! indicators = ["cvd", "heatmap"]
+//! ```
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::utils::{SessionSchedule, TickSizeRegistry};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+py_class! {
+/// Perfil de configuración; los campos None heredan del perfil por defecto
+#[pyclass]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolConfig {
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub tick_size: Option<f64>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub depth_levels: Option<usize>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub bucket_ms: Option<u64>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub session_period_ms: Option<u64>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub session_offset_ms: Option<u64>,
+    /// Indicadores activos por nombre (None = todos)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub indicators: Option<Vec<String>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl SymbolConfig {
+    #[new]
+    #[pyo3(signature = (tick_size=None, depth_levels=None, bucket_ms=None, session_period_ms=None, session_offset_ms=None, indicators=None))]
+    pub fn new(
+        tick_size: Option<f64>,
+        depth_levels: Option<usize>,
+        bucket_ms: Option<u64>,
+        session_period_ms: Option<u64>,
+        session_offset_ms: Option<u64>,
+        indicators: Option<Vec<String>>,
+    ) -> Result<Self, ProcessError> {
+        let config = Self { tick_size, depth_levels, bucket_ms, session_period_ms, session_offset_ms, indicators };
+        config.validate()?;
+        Ok(config)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SymbolConfig(tick_size={:?}, depth_levels={:?}, bucket_ms={:?}, session=({:?}, {:?}), indicators={:?})",
+                self.tick_size, self.depth_levels, self.bucket_ms,
+                self.session_period_ms, self.session_offset_ms, self.indicators)
+    }
+}
+}
+
+impl SymbolConfig {
+    /// Superpone los campos definidos de `self` sobre `base`
+    fn over(&self, base: &SymbolConfig) -> SymbolConfig {
+        SymbolConfig {
+            tick_size: self.tick_size.or(base.tick_size),
+            depth_levels: self.depth_levels.or(base.depth_levels),
+            bucket_ms: self.bucket_ms.or(base.bucket_ms),
+            session_period_ms: self.session_period_ms.or(base.session_period_ms),
+            session_offset_ms: self.session_offset_ms.or(base.session_offset_ms),
+            indicators: self.indicators.clone().or_else(|| base.indicators.clone()),
+        }
+    }
+    
+    /// Calendario de sesión si hay periodo (el offset por defecto es 0)
+    pub fn session(&self) -> Option<SessionSchedule> {
+        self.session_period_ms.and_then(|p| SessionSchedule::new(p, self.session_offset_ms.unwrap_or(0)))
+    }
+    
+    fn validate(&self) -> Result<(), ProcessError> {
+        let invalid = |msg: &str| Err(ProcessError::invalid(msg.to_string()));
+        if self.tick_size.is_some_and(|t| !(t.is_finite() && t > 0.0)) {
+            return invalid("tick_size must be > 0");
+        }
+        if self.depth_levels == Some(0) || self.bucket_ms == Some(0) {
+            return invalid("depth_levels and bucket_ms must be > 0");
+        }
+        if let Some(period) = self.session_period_ms {
+            if SessionSchedule::new(period, self.session_offset_ms.unwrap_or(0)).is_none() {
+                return invalid("session_period_ms must be > 0 and session_offset_ms < session_period_ms");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Estructura del fichero de configuración
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    default: SymbolConfig,
+    #[serde(default)]
+    symbols: HashMap<String, SymbolConfig>,
+}
+
+py_class! {
+/// Registro de configuración por símbolo.
+/// Clonar el registro comparte el estado (Arc), igual que `TickSizeRegistry`.
+#[pyclass]
+#[derive(Clone, Debug, Default)]
+pub struct ConfigRegistry {
+    default: Arc<RwLock<SymbolConfig>>,
+    symbols: Arc<DashMap<String, SymbolConfig>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl ConfigRegistry {
+    #[new]
+    #[pyo3(signature = (default=None))]
+    pub fn new(default: Option<SymbolConfig>) -> Result<Self, ProcessError> {
+        let default = default.unwrap_or_default();
+        default.validate()?;
+        Ok(Self {
+            default: Arc::new(RwLock::new(default)),
+            symbols: Arc::new(DashMap::new()),
+        })
+    }
+    
+    /// Perfil por defecto
+    #[getter]
+    pub fn default(&self) -> SymbolConfig {
+        self.default.read().clone()
+    }
+    
+    #[setter]
+    pub fn set_default(&self, config: SymbolConfig) -> Result<(), ProcessError> {
+        config.validate()?;
+        *self.default.write() = config;
+        Ok(())
+    }
+    
+    /// Registra (o reemplaza) el perfil de un símbolo
+    pub fn set(&self, symbol: &str, config: SymbolConfig) -> Result<(), ProcessError> {
+        config.validate()?;
+        self.symbols.insert(symbol.to_string(), config);
+        Ok(())
+    }
+    
+    /// Perfil efectivo del símbolo: el suyo superpuesto al de por defecto
+    pub fn get(&self, symbol: &str) -> SymbolConfig {
+        let default = self.default.read();
+        match self.symbols.get(symbol) {
+            Some(config) => config.over(&default),
+            None => default.clone(),
+        }
+    }
+    
+    /// Elimina el perfil propio del símbolo (vuelve al de por defecto)
+    pub fn remove(&self, symbol: &str) {
+        self.symbols.remove(symbol);
+    }
+    
+    /// Símbolos con perfil propio
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.symbols.iter().map(|e| e.key().clone()).collect();
+        symbols.sort();
+        symbols
+    }
+    
+    /// Indica si un indicador está activo para el símbolo
+    pub fn is_indicator_enabled(&self, symbol: &str, name: &str) -> bool {
+        self.get(symbol).indicators.is_none_or(|names| names.iter().any(|n| n == name))
+    }
+    
+    /// Registro de tick sizes equivalente, para engines que ya usan `TickSizeRegistry`
+    #[pyo3(signature = (fallback_tick=0.01))]
+    pub fn tick_registry(&self, fallback_tick: f64) -> Result<TickSizeRegistry, ProcessError> {
+        let registry = TickSizeRegistry::new(self.default.read().tick_size.unwrap_or(fallback_tick))?;
+        for entry in self.symbols.iter() {
+            if let Some(tick) = entry.value().tick_size {
+                registry.set_tick_size(entry.key(), tick)?;
+            }
+        }
+        Ok(registry)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("ConfigRegistry(symbols={})", self.symbols.len())
+    }
+}
+}
+
+impl ConfigRegistry {
+    pub fn depth_levels(&self, symbol: &str) -> Option<usize> {
+        self.get(symbol).depth_levels
+    }
+    
+    pub fn bucket_ms(&self, symbol: &str) -> Option<u64> {
+        self.get(symbol).bucket_ms
+    }
+    
+    pub fn session(&self, symbol: &str) -> Option<SessionSchedule> {
+        self.get(symbol).session()
+    }
+}
+
+/// Carga un registro desde un texto TOML o JSON.
+/// format = "toml" | "json"; si se omite, un texto que empieza por '{' es JSON.
+#[cfg_attr(feature = "python", pyfunction)]
+#[cfg_attr(feature = "python", pyo3(signature = (text, format=None)))]
+pub fn load_config(text: &str, format: Option<&str>) -> Result<ConfigRegistry, ProcessError> {
+    let format = format.map(|f| f.to_ascii_lowercase())
+        .unwrap_or_else(|| if text.trim_start().starts_with('{') { "json".to_string() } else { "toml".to_string() });
+    let file: ConfigFile = match format.as_str() {
+        "json" => serde_json::from_str(text).map_err(|e| e.to_string()),
+        "toml" => toml::from_str(text).map_err(|e| e.to_string()),
+        other => Err(format!("unsupported config format: {}", other)),
+    }.map_err(|e| ProcessError::invalid(format!("invalid config: {}", e)))?;
+    
+    let registry = ConfigRegistry::new(Some(file.default))?;
+    for (symbol, config) in file.symbols {
+        registry.set(&symbol, config)?;
+    }
+    Ok(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+        [default]
+        tick_size = 0.01
+        depth_levels = 10
+
+        [symbols.ESZ5]
+        tick_size = 0.25
+        bucket_ms = 500
+        session_period_ms = 86400000
+        session_offset_ms = 79200000
+        indicators = ["cvd", "heatmap"]
+    "#;
+
+    #[test]
+    fn test_load_toml_and_merge() {
+        let registry = load_config(TOML, None).unwrap();
+        
+        let es = registry.get("ESZ5");
+        assert_eq!(es.tick_size, Some(0.25));
+        assert_eq!(es.depth_levels, Some(10)); // heredado del default
+        assert_eq!(es.bucket_ms, Some(500));
+        assert_eq!(registry.session("ESZ5"), SessionSchedule::new(86_400_000, 79_200_000));
+        
+        let other = registry.get("AAPL");
+        assert_eq!(other.tick_size, Some(0.01));
+        assert_eq!(other.bucket_ms, None);
+        
+        assert!(registry.is_indicator_enabled("ESZ5", "cvd"));
+        assert!(!registry.is_indicator_enabled("ESZ5", "vwap"));
+        assert!(registry.is_indicator_enabled("AAPL", "vwap"));
+        
+        let ticks = registry.tick_registry(0.01).unwrap();
+        assert_eq!(ticks.get_tick_size("ESZ5"), 0.25);
+        assert_eq!(ticks.get_tick_size("AAPL"), 0.01);
+    }
+
+    #[test]
+    fn test_load_json() {
+        let registry = load_config(r#"{"symbols": {"BTCUSD": {"tick_size": 0.5, "depth_levels": 20}}}"#, None).unwrap();
+        assert_eq!(registry.depth_levels("BTCUSD"), Some(20));
+        assert_eq!(registry.depth_levels("ETHUSD"), None);
+        assert_eq!(registry.symbols(), vec!["BTCUSD"]);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(load_config("[default]\ntick_size = 0.0", None).is_err());
+        assert!(load_config("[symbols.X]\nsession_period_ms = 1000\nsession_offset_ms = 1000", None).is_err());
+        assert!(load_config("[default]\nunknown = 1", None).is_err());
+        assert!(load_config("{}", Some("yaml")).is_err());
+    }
+}
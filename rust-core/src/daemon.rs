@@ -0,0 +1,555 @@
+//! # Daemon
+//! 
+//! Modo servicio sin Python (binario `indicators-engined`): lee un TOML,
+//! consume de NATS o Kafka, pasa los eventos por el `EngineManager` y
+//! publica cada bundle en NATS o Kafka. Opcionalmente expone las métricas
+//! Prometheus y carga la configuración por símbolo de otro fichero.
+//! 
+//! ```toml
+//! metrics_addr = "0.0.0.0:9100"
+//! symbol_config = "symbols.toml"   # relativo al fichero del daemon
+//! 
+//! [source]
+//! kind = "nats"                    # o "kafka" (feature `kafka`)
+//! url = "nats://localhost:4222"
+//! subject = "md.>"
+//! stream_name = "MARKET_DATA"
+//! subjects = [["md.trades.>", "trades"], ["md.books.>", "books"]]
+//! wire_format = "msgpack"
+//! 
+//! [publisher]
+//! kind = "nats"
+//! url = "nats://localhost:4222"
+//! subject_template = "indicators.{kind}.{symbol}"
+//! 
+//! [engines]
+//! heatmap = false
+//! ma_periods = [20, 50]
+//! 
+//! [logging]
+//! level = "info"
+//! json = true
+//! ```
+//! 
+//! Sin `[publisher]` las métricas solo se calculan (útil con `metrics_addr`
+//! o `grpc_addr`, que expone el servicio de `proto/engine_service.proto`
+//! con la feature `grpc`).
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::load_config;
+use crate::logging::init_logging;
+use crate::manager::{EngineManager, MetricsSink};
+use crate::nats_publisher::NATSPublisher;
+use crate::nats_subscriber::{NATSConfig, NATSSubscriber};
+use crate::types::MetricsBundle;
+#[cfg(feature = "kafka")]
+use crate::kafka::{KafkaConfig, KafkaPublisher, KafkaSubscriber};
+#[cfg(feature = "kafka")]
+use crate::telemetry::MetricsServer;
+#[cfg(feature = "grpc")]
+use crate::grpc::GrpcServer;
+use crate::errors::ProcessError;
+
+/// Error al cargar la configuración o arrancar el daemon
+#[derive(Debug, thiserror::Error)]
+pub enum DaemonError {
+    #[error("cannot read {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+    #[error("invalid daemon config: {0}")]
+    Config(String),
+    #[error("cannot start daemon: {0}")]
+    Start(String),
+}
+
+fn config_error(e: ProcessError) -> DaemonError {
+    DaemonError::Config(e.to_string())
+}
+
+fn start_error(e: ProcessError) -> DaemonError {
+    DaemonError::Start(e.to_string())
+}
+
+fn read_file(path: &Path) -> Result<String, DaemonError> {
+    std::fs::read_to_string(path).map_err(|source| DaemonError::Io { path: path.display().to_string(), source })
+}
+
+/// Fichero de configuración del daemon
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonConfig {
+    pub source: SourceConfig,
+    pub publisher: Option<PublisherConfig>,
+    #[serde(default)]
+    pub engines: EnginesConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// Dirección del endpoint Prometheus ("0.0.0.0:9100")
+    pub metrics_addr: Option<String>,
+    /// Dirección del servidor gRPC ("0.0.0.0:50051", feature `grpc`)
+    pub grpc_addr: Option<String>,
+    /// Configuración por símbolo (TOML o JSON, ver `load_config`)
+    pub symbol_config: Option<PathBuf>,
+}
+
+/// Origen de los eventos de mercado
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum SourceConfig {
+    /// JetStream; los campos opcionales toman los valores por defecto de `NATSConfig`
+    Nats {
+        url: String,
+        subject: String,
+        stream_name: String,
+        consumer_name: Option<String>,
+        #[serde(default)]
+        subjects: Vec<(String, String)>,
+        reconnect_initial_ms: Option<u64>,
+        reconnect_max_ms: Option<u64>,
+        wire_format: Option<String>,
+        queue_capacity: Option<usize>,
+        overflow_policy: Option<String>,
+    },
+    /// Consumer group; los campos opcionales toman los valores por defecto de `KafkaConfig`
+    Kafka {
+        brokers: String,
+        group_id: String,
+        topics: Vec<(String, String)>,
+        wire_format: Option<String>,
+        auto_offset_reset: Option<String>,
+        #[serde(default)]
+        properties: HashMap<String, String>,
+    },
+}
+
+/// Destino de los bundles
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PublisherConfig {
+    Nats {
+        url: String,
+        subject_template: Option<String>,
+        batch_size: Option<usize>,
+        max_rate: Option<f64>,
+        /// Plantilla propia por tipo ("cvd", "vwap", "liquidity", "heatmap", "sweep")
+        #[serde(default)]
+        subjects: HashMap<String, String>,
+    },
+    Kafka {
+        brokers: String,
+        topic_template: Option<String>,
+        wire_format: Option<String>,
+        #[serde(default)]
+        properties: HashMap<String, String>,
+        /// Plantilla propia por tipo ("cvd", "vwap", "liquidity", "heatmap")
+        #[serde(default)]
+        topics: HashMap<String, String>,
+    },
+}
+
+/// Engines del manager, con los mismos valores por defecto que `EngineManager(...)`
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnginesConfig {
+    pub cvd: bool,
+    pub vwap: bool,
+    pub liquidity: bool,
+    pub heatmap: bool,
+    pub ma_periods: Option<Vec<usize>>,
+    pub rsi_periods: Option<Vec<usize>>,
+    pub atr_periods: Option<Vec<usize>>,
+}
+
+impl Default for EnginesConfig {
+    fn default() -> Self {
+        Self {
+            cvd: true,
+            vwap: true,
+            liquidity: true,
+            heatmap: true,
+            ma_periods: None,
+            rsi_periods: None,
+            atr_periods: None,
+        }
+    }
+}
+
+/// Parámetros de `init_logging`
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    pub level: String,
+    pub json: bool,
+    pub path: Option<String>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { level: "info".to_string(), json: true, path: None }
+    }
+}
+
+impl LoggingConfig {
+    /// Instala el subscriber global de tracing
+    pub fn install(&self) -> Result<(), DaemonError> {
+        init_logging(&self.level, self.json, self.path.clone()).map_err(config_error)
+    }
+}
+
+impl DaemonConfig {
+    pub fn parse(text: &str) -> Result<Self, DaemonError> {
+        toml::from_str(text).map_err(|e| DaemonError::Config(e.to_string()))
+    }
+    
+    /// Lee el fichero; `symbol_config` relativo se resuelve desde su directorio
+    pub fn load(path: &Path) -> Result<Self, DaemonError> {
+        let mut config = Self::parse(&read_file(path)?)?;
+        if let (Some(symbols), Some(dir)) = (config.symbol_config.as_mut(), path.parent()) {
+            if symbols.is_relative() {
+                *symbols = dir.join(&*symbols);
+            }
+        }
+        Ok(config)
+    }
+    
+    /// Comprueba engines, configuración por símbolo, origen y destino sin conectar
+    pub fn validate(&self) -> Result<(), DaemonError> {
+        self.check_features()?;
+        Source::new(&self.source, Arc::new(build_manager(self)?))?;
+        if let Some(publisher) = &self.publisher {
+            Publisher::new(publisher)?;
+        }
+        Ok(())
+    }
+    
+    /// Opciones que dependen de features no compiladas
+    fn check_features(&self) -> Result<(), DaemonError> {
+        if cfg!(not(feature = "grpc")) && self.grpc_addr.is_some() {
+            return Err(DaemonError::Config("grpc_addr requires building with the grpc feature".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Publisher en marcha, compartido con el sink del manager
+enum Publisher {
+    Nats(NATSPublisher),
+    #[cfg(feature = "kafka")]
+    Kafka(KafkaPublisher),
+}
+
+impl Publisher {
+    fn new(config: &PublisherConfig) -> Result<Self, DaemonError> {
+        match config {
+            PublisherConfig::Nats { url, subject_template, batch_size, max_rate, subjects } => {
+                let mut publisher = NATSPublisher::new(
+                    url.clone(),
+                    subject_template.clone().unwrap_or_else(|| "indicators.{kind}.{symbol}".to_string()),
+                    batch_size.unwrap_or(100),
+                    *max_rate,
+                ).map_err(config_error)?;
+                for (kind, template) in subjects {
+                    publisher.set_subject(kind, template);
+                }
+                Ok(Self::Nats(publisher))
+            }
+            #[cfg(feature = "kafka")]
+            PublisherConfig::Kafka { brokers, topic_template, wire_format, properties, topics } => {
+                let mut publisher = KafkaPublisher::new(
+                    brokers.clone(),
+                    topic_template.clone().unwrap_or_else(|| "indicators.{kind}".to_string()),
+                    wire_format.as_deref().unwrap_or("json"),
+                    properties.clone(),
+                ).map_err(config_error)?;
+                for (kind, template) in topics {
+                    publisher.set_topic(kind, template);
+                }
+                Ok(Self::Kafka(publisher))
+            }
+            #[cfg(not(feature = "kafka"))]
+            PublisherConfig::Kafka { .. } => Err(DaemonError::Config(
+                "kafka publisher requires building with the kafka feature".to_string())),
+        }
+    }
+    
+    fn start(&mut self) -> Result<(), ProcessError> {
+        match self {
+            Self::Nats(publisher) => publisher.start(),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(publisher) => publisher.start(),
+        }
+    }
+    
+    fn publish_bundle(&self, bundle: &MetricsBundle) -> Result<(), ProcessError> {
+        match self {
+            Self::Nats(publisher) => publisher.publish_bundle(bundle),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(publisher) => publisher.publish_bundle(bundle),
+        }
+    }
+}
+
+/// Suscriptor configurado sobre el manager del daemon
+enum Source {
+    Nats(NATSSubscriber),
+    #[cfg(feature = "kafka")]
+    Kafka {
+        subscriber: KafkaSubscriber,
+        manager: Arc<EngineManager>,
+        metrics_server: Option<MetricsServer>,
+    },
+}
+
+impl Source {
+    fn new(config: &SourceConfig, manager: Arc<EngineManager>) -> Result<Self, DaemonError> {
+        match config {
+            SourceConfig::Nats {
+                url, subject, stream_name, consumer_name, subjects, reconnect_initial_ms, reconnect_max_ms,
+                wire_format, queue_capacity, overflow_policy,
+            } => {
+                let config = NATSConfig::new(
+                    url.clone(),
+                    subject.clone(),
+                    stream_name.clone(),
+                    consumer_name.clone(),
+                    subjects.clone(),
+                    reconnect_initial_ms.unwrap_or(500),
+                    reconnect_max_ms.unwrap_or(30_000),
+                    wire_format.as_deref().unwrap_or("json"),
+                    queue_capacity.unwrap_or(10_000),
+                    overflow_policy.as_deref().unwrap_or("block"),
+                ).map_err(config_error)?;
+                Ok(Self::Nats(NATSSubscriber::with_manager(config, manager)))
+            }
+            #[cfg(feature = "kafka")]
+            SourceConfig::Kafka { brokers, group_id, topics, wire_format, auto_offset_reset, properties } => {
+                let config = KafkaConfig::new(
+                    brokers.clone(),
+                    group_id.clone(),
+                    topics.clone(),
+                    wire_format.as_deref().unwrap_or("json"),
+                    auto_offset_reset.clone().unwrap_or_else(|| "earliest".to_string()),
+                    properties.clone(),
+                ).map_err(config_error)?;
+                Ok(Self::Kafka {
+                    subscriber: KafkaSubscriber::with_manager(config, manager.clone()),
+                    manager,
+                    metrics_server: None,
+                })
+            }
+            #[cfg(not(feature = "kafka"))]
+            SourceConfig::Kafka { .. } => Err(DaemonError::Config(
+                "kafka source requires building with the kafka feature".to_string())),
+        }
+    }
+    
+    fn start(&mut self) -> Result<(), ProcessError> {
+        match self {
+            Self::Nats(subscriber) => subscriber.start(),
+            #[cfg(feature = "kafka")]
+            Self::Kafka { subscriber, .. } => subscriber.start(),
+        }
+    }
+    
+    /// Endpoint Prometheus; el de NATS añade la conexión y el lag a las métricas del manager
+    fn serve_metrics(&mut self, addr: &str) -> Result<String, DaemonError> {
+        match self {
+            Self::Nats(subscriber) => subscriber.start_metrics_server(addr).map_err(start_error),
+            #[cfg(feature = "kafka")]
+            Self::Kafka { manager, metrics_server, .. } => {
+                let manager = manager.clone();
+                let server = MetricsServer::start(addr, Arc::new(move || manager.render_metrics()))
+                    .map_err(|e| DaemonError::Start(format!("metrics server error: {}", e)))?;
+                let bound = server.addr().to_string();
+                *metrics_server = Some(server);
+                Ok(bound)
+            }
+        }
+    }
+    
+    fn is_running(&self) -> bool {
+        match self {
+            Self::Nats(subscriber) => subscriber.is_running(),
+            #[cfg(feature = "kafka")]
+            Self::Kafka { subscriber, .. } => subscriber.is_running(),
+        }
+    }
+    
+    fn stop(&mut self) {
+        match self {
+            Self::Nats(subscriber) => subscriber.shutdown(),
+            #[cfg(feature = "kafka")]
+            Self::Kafka { subscriber, metrics_server, .. } => {
+                subscriber.stop();
+                *metrics_server = None;
+            }
+        }
+    }
+}
+
+/// Manager con los engines y la configuración por símbolo del fichero
+fn build_manager(config: &DaemonConfig) -> Result<EngineManager, DaemonError> {
+    let engines = &config.engines;
+    let mut manager = EngineManager::new(
+        engines.cvd,
+        engines.vwap,
+        engines.liquidity,
+        engines.heatmap,
+        engines.ma_periods.clone(),
+        engines.rsi_periods.clone(),
+        engines.atr_periods.clone(),
+    ).map_err(config_error)?;
+    if let Some(path) = &config.symbol_config {
+        manager.set_config(Some(load_config(&read_file(path)?, None).map_err(config_error)?));
+    }
+    Ok(manager)
+}
+
+/// Servicio en marcha: suscriptor, manager y publisher
+pub struct Daemon {
+    manager: Arc<EngineManager>,
+    source: Source,
+    // Sink del publisher en el manager
+    publisher_sink: Option<u64>,
+    #[cfg(feature = "grpc")]
+    grpc_server: Option<GrpcServer>,
+}
+
+impl Daemon {
+    /// Construye el manager, arranca el publisher y después el suscriptor
+    pub fn start(config: &DaemonConfig) -> Result<Self, DaemonError> {
+        config.check_features()?;
+        let manager = Arc::new(build_manager(config)?);
+        let source = Source::new(&config.source, manager.clone())?;
+        let mut daemon = Self {
+            manager,
+            source,
+            publisher_sink: None,
+            #[cfg(feature = "grpc")]
+            grpc_server: None,
+        };
+        
+        // Si algo falla a partir de aquí, Drop detiene lo ya arrancado
+        if let Some(publisher) = &config.publisher {
+            let mut publisher = Publisher::new(publisher)?;
+            publisher.start().map_err(start_error)?;
+            let sink: MetricsSink = Arc::new(move |bundle: &MetricsBundle| {
+                if let Err(e) = publisher.publish_bundle(bundle) {
+                    tracing::warn!(error = %e, symbol = %bundle.symbol, "cannot publish metrics");
+                }
+            });
+            daemon.publisher_sink = Some(daemon.manager.add_sink(sink));
+        }
+        #[cfg(feature = "grpc")]
+        if let Some(addr) = &config.grpc_addr {
+            let server = GrpcServer::start(addr, daemon.manager.clone())
+                .map_err(|e| DaemonError::Start(format!("grpc server error: {}", e)))?;
+            daemon.grpc_server = Some(server);
+        }
+        daemon.source.start().map_err(start_error)?;
+        if let Some(addr) = &config.metrics_addr {
+            let bound = daemon.source.serve_metrics(addr)?;
+            tracing::info!(addr = %bound, "metrics server listening");
+        }
+        tracing::info!(engines = ?daemon.manager.engines(), "daemon started");
+        Ok(daemon)
+    }
+    
+    /// Si el suscriptor sigue consumiendo
+    pub fn is_running(&self) -> bool {
+        self.source.is_running()
+    }
+    
+    /// Estado Prometheus del manager
+    pub fn render_metrics(&self) -> String {
+        self.manager.render_metrics()
+    }
+    
+    /// Detiene el suscriptor y después el gRPC y el publisher, que envía lo pendiente
+    pub fn stop(&mut self) {
+        self.source.stop();
+        #[cfg(feature = "grpc")]
+        if let Some(mut server) = self.grpc_server.take() {
+            server.stop();
+        }
+        if let Some(sink) = self.publisher_sink.take() {
+            self.manager.remove_sink(sink);
+        }
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const TOML: &str = r#"
+        metrics_addr = "127.0.0.1:0"
+        symbol_config = "symbols.toml"
+        
+        [source]
+        kind = "nats"
+        url = "nats://localhost:4222"
+        subject = "md.>"
+        stream_name = "MARKET_DATA"
+        subjects = [["md.trades.>", "trades"], ["md.books.>", "books"]]
+        wire_format = "msgpack"
+        
+        [publisher]
+        kind = "nats"
+        url = "nats://localhost:4222"
+        subjects = { heatmap = "indicators.heatmap" }
+        
+        [engines]
+        heatmap = false
+        ma_periods = [20, 50]
+    "#;
+    
+    #[test]
+    fn test_parse_daemon_config() {
+        let path = std::env::temp_dir().join(format!("indicators-daemon-{}.toml", std::process::id()));
+        std::fs::write(&path, TOML).unwrap();
+        let config = DaemonConfig::load(&path);
+        let _ = std::fs::remove_file(&path);
+        let config = config.unwrap();
+        
+        assert_eq!(config.symbol_config, Some(std::env::temp_dir().join("symbols.toml")));
+        let SourceConfig::Nats { subjects, wire_format, queue_capacity, .. } = &config.source else { panic!() };
+        assert_eq!(subjects[1], ("md.books.>".to_string(), "books".to_string()));
+        assert_eq!((wire_format.as_deref(), *queue_capacity), (Some("msgpack"), None));
+        let Some(PublisherConfig::Nats { subjects, subject_template, .. }) = &config.publisher else { panic!() };
+        assert_eq!(subjects["heatmap"], "indicators.heatmap");
+        assert!(subject_template.is_none());
+        assert!(config.engines.cvd && !config.engines.heatmap);
+        assert_eq!(config.engines.ma_periods, Some(vec![20, 50]));
+        assert_eq!((config.logging.level.as_str(), config.logging.json), ("info", true));
+    }
+    
+    #[test]
+    fn test_invalid_daemon_config() {
+        assert!(DaemonConfig::parse("[engines]\ncvd = true").is_err());
+        assert!(DaemonConfig::parse(&TOML.replace("kind = \"nats\"", "kind = \"zmq\"")).is_err());
+        assert!(DaemonConfig::parse(&TOML.replace("[engines]", "[engines]\nobv = true")).is_err());
+        assert!(matches!(DaemonConfig::load(Path::new("/nonexistent/daemon.toml")), Err(DaemonError::Io { .. })));
+        if cfg!(not(feature = "grpc")) {
+            let config = DaemonConfig::parse(&format!("grpc_addr = \"127.0.0.1:0\"\n{}", TOML)).unwrap();
+            assert!(matches!(config.validate(), Err(DaemonError::Config(_))));
+        }
+        
+        // Los valores se validan con los constructores de los engines antes de conectar
+        let mut config = DaemonConfig::parse(&TOML.replace("wire_format = \"msgpack\"", "queue_capacity = 0")).unwrap();
+        config.symbol_config = None;
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("queue_capacity must be > 0"), "{}", error);
+        let error = Daemon::start(&config).err().unwrap().to_string();
+        assert!(error.contains("queue_capacity must be > 0"), "{}", error);
+    }
+}
@@ -0,0 +1,158 @@
+//! # Trade Deduplication
+//! 
+//! Los exchanges retransmiten trades (reconexiones, replays del feed) y un
+//! trade repetido se contaría dos veces en CVD, VWAP o el volume profile.
+//! `TradeDeduplicator` recuerda por símbolo los últimos `capacity`
+//! `trade_id` vistos y descarta los repetidos:
+//! - la caché es FIFO: al llenarse se olvida el id más antiguo
+//! - los trades sin `trade_id` siempre pasan
+//! - `capacity` 0 desactiva la deduplicación
+//! 
+//! Es seguro usarlo desde varios hilos (`process_batch_parallel`): cada
+//! símbolo tiene su propia entrada en un `DashMap`.
+
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::types::Trade;
+
+/// Ids por símbolo recordados por defecto
+pub const DEFAULT_DEDUP_CAPACITY: usize = 10_000;
+
+/// Ids vistos de un símbolo, en orden de llegada
+#[derive(Default)]
+struct SeenIds {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SeenIds {
+    /// Registra el id; false si ya estaba
+    fn insert(&mut self, id: &str, capacity: usize) -> bool {
+        if self.ids.contains(id) {
+            return false;
+        }
+        self.ids.insert(id.to_string());
+        self.order.push_back(id.to_string());
+        self.truncate(capacity);
+        true
+    }
+    
+    fn truncate(&mut self, capacity: usize) {
+        while self.order.len() > capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Caché acotada de trade ids por símbolo con contadores de duplicados
+pub struct TradeDeduplicator {
+    capacity: usize,
+    seen: DashMap<String, SeenIds>,
+    duplicates: DashMap<String, u64>,
+}
+
+impl Default for TradeDeduplicator {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEDUP_CAPACITY)
+    }
+}
+
+impl TradeDeduplicator {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, seen: DashMap::new(), duplicates: DashMap::new() }
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    /// Cambia la capacidad; al reducirla se olvidan los ids más antiguos
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if capacity == 0 {
+            self.seen.clear();
+            return;
+        }
+        for mut entry in self.seen.iter_mut() {
+            entry.truncate(capacity);
+        }
+    }
+    
+    /// Registra el trade y devuelve si es una retransmisión de uno ya visto
+    pub fn is_duplicate(&self, trade: &Trade) -> bool {
+        let Some(id) = trade.trade_id.as_deref() else { return false };
+        if self.capacity == 0 {
+            return false;
+        }
+        let inserted = self.seen.entry(trade.symbol.clone()).or_default().insert(id, self.capacity);
+        if !inserted {
+            *self.duplicates.entry(trade.symbol.clone()).or_insert(0) += 1;
+        }
+        !inserted
+    }
+    
+    /// Duplicados descartados por símbolo (solo `symbol` si se indica)
+    pub fn duplicates(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        match symbol {
+            Some(symbol) => self.duplicates.get(symbol).map(|n| (symbol.to_string(), *n)).into_iter().collect(),
+            None => self.duplicates.iter().map(|e| (e.key().clone(), *e.value())).collect(),
+        }
+    }
+    
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.seen.remove(symbol);
+        self.duplicates.remove(symbol);
+    }
+    
+    pub fn reset_all(&self) {
+        self.seen.clear();
+        self.duplicates.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn trade(symbol: &str, id: Option<&str>) -> Trade {
+        let mut trade = Trade::new(1000, 100.0, 1.0, symbol.to_string());
+        trade.trade_id = id.map(str::to_string);
+        trade
+    }
+    
+    #[test]
+    fn test_duplicates_per_symbol() {
+        let dedup = TradeDeduplicator::new(10);
+        assert!(!dedup.is_duplicate(&trade("AAPL", Some("1"))));
+        assert!(dedup.is_duplicate(&trade("AAPL", Some("1"))));
+        // El mismo id en otro símbolo es otro trade
+        assert!(!dedup.is_duplicate(&trade("MSFT", Some("1"))));
+        // Sin id no se puede deduplicar
+        assert!(!dedup.is_duplicate(&trade("AAPL", None)));
+        assert!(!dedup.is_duplicate(&trade("AAPL", None)));
+        
+        assert_eq!(dedup.duplicates(None), HashMap::from([("AAPL".to_string(), 1)]));
+        assert!(dedup.duplicates(Some("MSFT")).is_empty());
+        
+        dedup.reset_symbol("AAPL");
+        assert!(dedup.duplicates(None).is_empty());
+        assert!(!dedup.is_duplicate(&trade("AAPL", Some("1"))));
+    }
+    
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let mut dedup = TradeDeduplicator::new(2);
+        for id in ["1", "2", "3"] {
+            assert!(!dedup.is_duplicate(&trade("AAPL", Some(id))));
+        }
+        // "1" salió de la caché; "3" sigue
+        assert!(!dedup.is_duplicate(&trade("AAPL", Some("1"))));
+        assert!(dedup.is_duplicate(&trade("AAPL", Some("3"))));
+        
+        dedup.set_capacity(0);
+        assert!(!dedup.is_duplicate(&trade("AAPL", Some("3"))));
+        assert!(!dedup.is_duplicate(&trade("AAPL", Some("3"))));
+    }
+}
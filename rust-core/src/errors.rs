@@ -0,0 +1,484 @@
+//! # Errors
+//!
+//! Errores de validación de los engines y sus excepciones Python:
+//! - `ProcessError`: motivo por el que un engine rechaza un evento, o
+//!   argumento/operación inválidos en las APIs Rust (constructores,
+//!   setters, loaders, publishers)
+//! - `Rejections`: modo strict, contadores de rechazos y política de
+//!   timestamps (máximo ts visto) por símbolo
+//!
+//! Por defecto un evento inválido se descarta (el engine devuelve None) y
+//! queda contado; con `strict = True` el método Python lanza la excepción
+//! del error. Todas las excepciones heredan de `ProcessingError`, que a su
+//! vez hereda de `ValueError`. Los errores de argumentos y operaciones se
+//! lanzan como `ValueError`, `RuntimeError`, `IOError` o `KeyError`. Con
+//! la feature `python` desactivada solo queda `ProcessError`.
+//!
+//! Un evento cuyo `ts` retrocede más de `tolerance_ms` respecto al máximo
+//! visto del símbolo (replay, duplicado, feed desordenado) se acepta, se
+//! acepta con un warning o se rechaza como `StaleTimestamp` según la
+//! `TimestampPolicy`.
+
+#[cfg(feature = "python")]
+use pyo3::create_exception;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIOError, PyKeyError, PyRuntimeError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::types::{BookSnapshot, Quote, Trade};
+
+#[cfg(feature = "python")]
+create_exception!(indicators_core, ProcessingError, PyValueError, "Evento rechazado por un engine");
+#[cfg(feature = "python")]
+create_exception!(indicators_core, InvalidPriceError, ProcessingError, "Precio no finito o <= 0");
+#[cfg(feature = "python")]
+create_exception!(indicators_core, InvalidSizeError, ProcessingError, "Tamaño no finito o <= 0");
+#[cfg(feature = "python")]
+create_exception!(indicators_core, StaleTimestampError, ProcessingError, "Timestamp anterior al último procesado");
+#[cfg(feature = "python")]
+create_exception!(indicators_core, CrossedBookError, ProcessingError, "Libro cruzado (bid >= ask)");
+#[cfg(feature = "python")]
+create_exception!(indicators_core, EmptyBookError, ProcessingError, "Libro sin niveles");
+#[cfg(feature = "python")]
+create_exception!(indicators_core, MalformedBookError, ProcessingError, "Niveles duplicados o desordenados");
+
+/// Motivo por el que un engine rechaza un evento
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ProcessError {
+    #[error("invalid price for {symbol}: {price}")]
+    InvalidPrice { symbol: String, price: f64 },
+    #[error("invalid size for {symbol}: {size}")]
+    InvalidSize { symbol: String, size: f64 },
+    #[error("stale timestamp for {symbol}: {ts} < {last_ts}")]
+    StaleTimestamp { symbol: String, ts: u64, last_ts: u64 },
+    #[error("crossed book for {symbol}: bid {bid} >= ask {ask}")]
+    CrossedBook { symbol: String, bid: f64, ask: f64 },
+    #[error("empty book for {symbol}")]
+    EmptyBook { symbol: String },
+    /// `reason`: "duplicate_level" | "unsorted_levels"
+    #[error("malformed book for {symbol}: {reason} on {side} at {price}")]
+    MalformedBook { symbol: String, side: &'static str, reason: &'static str, price: f64 },
+    /// Argumento o configuración inválidos (ValueError en Python)
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// Operación imposible en el estado actual (RuntimeError en Python)
+    #[error("{0}")]
+    Runtime(String),
+    /// Fichero o conexión inaccesibles (IOError en Python)
+    #[error("{0}")]
+    Io(String),
+    /// Nombre no registrado (KeyError en Python)
+    #[error("{0}")]
+    NotFound(String),
+}
+
+impl ProcessError {
+    /// Nombre del tipo de error, usado como clave de los contadores
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidPrice { .. } => "invalid_price",
+            Self::InvalidSize { .. } => "invalid_size",
+            Self::StaleTimestamp { .. } => "stale_timestamp",
+            Self::CrossedBook { bid, ask, .. } if bid == ask => "locked_book",
+            Self::CrossedBook { .. } => "crossed_book",
+            Self::EmptyBook { .. } => "empty_book",
+            Self::MalformedBook { reason, .. } => reason,
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::Runtime(_) => "runtime",
+            Self::Io(_) => "io",
+            Self::NotFound(_) => "not_found",
+        }
+    }
+
+    pub fn invalid(msg: impl Into<String>) -> Self {
+        Self::InvalidArgument(msg.into())
+    }
+
+    pub fn runtime(msg: impl Into<String>) -> Self {
+        Self::Runtime(msg.into())
+    }
+
+    pub fn io(msg: impl Into<String>) -> Self {
+        Self::Io(msg.into())
+    }
+
+    pub fn not_found(msg: impl Into<String>) -> Self {
+        Self::NotFound(msg.into())
+    }
+
+    /// Error de calidad del libro (vacío, cruzado o mal formado)
+    pub fn is_book_quality(&self) -> bool {
+        matches!(self, Self::CrossedBook { .. } | Self::EmptyBook { .. } | Self::MalformedBook { .. })
+    }
+
+    /// Símbolo del evento rechazado ("" en los errores de argumentos)
+    pub fn symbol(&self) -> &str {
+        match self {
+            Self::InvalidPrice { symbol, .. }
+            | Self::InvalidSize { symbol, .. }
+            | Self::StaleTimestamp { symbol, .. }
+            | Self::CrossedBook { symbol, .. }
+            | Self::EmptyBook { symbol }
+            | Self::MalformedBook { symbol, .. } => symbol,
+            Self::InvalidArgument(_) | Self::Runtime(_) | Self::Io(_) | Self::NotFound(_) => "",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<ProcessError> for PyErr {
+    fn from(e: ProcessError) -> Self {
+        let msg = e.to_string();
+        match e {
+            ProcessError::InvalidPrice { .. } => InvalidPriceError::new_err(msg),
+            ProcessError::InvalidSize { .. } => InvalidSizeError::new_err(msg),
+            ProcessError::StaleTimestamp { .. } => StaleTimestampError::new_err(msg),
+            ProcessError::CrossedBook { .. } => CrossedBookError::new_err(msg),
+            ProcessError::EmptyBook { .. } => EmptyBookError::new_err(msg),
+            ProcessError::MalformedBook { .. } => MalformedBookError::new_err(msg),
+            ProcessError::InvalidArgument(_) => PyValueError::new_err(msg),
+            ProcessError::Runtime(_) => PyRuntimeError::new_err(msg),
+            ProcessError::Io(_) => PyIOError::new_err(msg),
+            ProcessError::NotFound(_) => PyKeyError::new_err(msg),
+        }
+    }
+}
+
+/// Precio y tamaño finitos y > 0
+pub fn validate_trade(trade: &Trade) -> Result<(), ProcessError> {
+    if !trade.price.is_finite() || trade.price <= 0.0 {
+        return Err(ProcessError::InvalidPrice { symbol: trade.symbol.clone(), price: trade.price });
+    }
+    if !trade.size.is_finite() || trade.size <= 0.0 {
+        return Err(ProcessError::InvalidSize { symbol: trade.symbol.clone(), size: trade.size });
+    }
+    Ok(())
+}
+
+/// Precios finitos y > 0, tamaños finitos y >= 0 y bid < ask
+pub fn validate_quote(quote: &Quote) -> Result<(), ProcessError> {
+    for price in [quote.bid, quote.ask] {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(ProcessError::InvalidPrice { symbol: quote.symbol.clone(), price });
+        }
+    }
+    for size in [quote.bid_size, quote.ask_size] {
+        if !size.is_finite() || size < 0.0 {
+            return Err(ProcessError::InvalidSize { symbol: quote.symbol.clone(), size });
+        }
+    }
+    if quote.bid >= quote.ask {
+        return Err(ProcessError::CrossedBook { symbol: quote.symbol.clone(), bid: quote.bid, ask: quote.ask });
+    }
+    Ok(())
+}
+
+/// Libro con niveles en ambos lados (`two_sided`) o al menos en uno
+pub fn validate_snapshot(snapshot: &BookSnapshot, two_sided: bool) -> Result<(), ProcessError> {
+    let empty = if two_sided {
+        snapshot.bids.is_empty() || snapshot.asks.is_empty()
+    } else {
+        snapshot.bids.is_empty() && snapshot.asks.is_empty()
+    };
+    if empty {
+        return Err(ProcessError::EmptyBook { symbol: snapshot.symbol.clone() });
+    }
+    Ok(())
+}
+
+/// Libro completo y coherente: ambos lados con niveles, bids estrictamente
+/// descendentes, asks estrictamente ascendentes y best bid < best ask
+pub fn validate_book(snapshot: &BookSnapshot) -> Result<(), ProcessError> {
+    validate_snapshot(snapshot, true)?;
+    for (side, levels, descending) in [("bid", &snapshot.bids, true), ("ask", &snapshot.asks, false)] {
+        for pair in levels.windows(2) {
+            let (prev, next) = (pair[0].price, pair[1].price);
+            let reason = if prev == next {
+                "duplicate_level"
+            } else if (next < prev) != descending {
+                "unsorted_levels"
+            } else {
+                continue;
+            };
+            return Err(ProcessError::MalformedBook { symbol: snapshot.symbol.clone(), side, reason, price: next });
+        }
+    }
+    let (bid, ask) = (snapshot.bids[0].price, snapshot.asks[0].price);
+    if bid >= ask {
+        return Err(ProcessError::CrossedBook { symbol: snapshot.symbol.clone(), bid, ask });
+    }
+    Ok(())
+}
+
+/// Qué hacer con un libro que no pasa `validate_book`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BookQualityPolicy {
+    /// Descartarlo y contarlo (o lanzar la excepción en modo strict)
+    #[default]
+    Skip,
+    /// Emitir un `BookQualityAlert` y procesarlo si tiene ambos lados
+    Alert,
+    /// Lanzar la excepción aunque el engine no esté en modo strict
+    Raise,
+}
+
+impl BookQualityPolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.to_ascii_lowercase().as_str() {
+            "skip" => Some(Self::Skip),
+            "alert" => Some(Self::Alert),
+            "raise" => Some(Self::Raise),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Alert => "alert",
+            Self::Raise => "raise",
+        }
+    }
+}
+
+/// Qué hacer con un evento cuyo ts retrocede más allá de la tolerancia
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimestampPolicy {
+    /// Procesarlo como cualquier otro (comportamiento histórico)
+    #[default]
+    Accept,
+    /// Procesarlo y emitir un warning
+    Warn,
+    /// Rechazarlo como `StaleTimestamp`
+    Reject,
+}
+
+impl TimestampPolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.to_ascii_lowercase().as_str() {
+            "accept" => Some(Self::Accept),
+            "warn" => Some(Self::Warn),
+            "reject" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accept => "accept",
+            Self::Warn => "warn",
+            Self::Reject => "reject",
+        }
+    }
+}
+
+/// Modo strict, contadores de rechazos y política de timestamps de un engine
+#[derive(Clone, Debug, Default)]
+pub struct Rejections {
+    pub strict: bool,
+    pub ts_policy: TimestampPolicy,
+    /// Retroceso máximo (ms) respecto al máximo ts visto que no cuenta como stale
+    pub tolerance_ms: u64,
+    // symbol -> tipo de error -> rechazos
+    counts: Arc<DashMap<String, HashMap<&'static str, u64>>>,
+    // symbol -> máximo ts de los eventos aceptados
+    max_ts: Arc<DashMap<String, u64>>,
+}
+
+impl Rejections {
+    /// Cuenta el error (si lo hay) y lo devuelve sin cambios
+    pub fn check(&self, result: Result<(), ProcessError>) -> Result<(), ProcessError> {
+        if let Err(e) = &result {
+            tracing::debug!(symbol = %e.symbol(), kind = e.kind(), "event rejected");
+            *self.counts.entry(e.symbol().to_string()).or_default().entry(e.kind()).or_insert(0) += 1;
+        }
+        result
+    }
+
+    /// Aplica la política de timestamps y actualiza el máximo ts visto del
+    /// símbolo; con `Reject` un ts stale se devuelve (y cuenta) como error
+    pub fn check_timestamp(&self, symbol: &str, ts: u64) -> Result<(), ProcessError> {
+        let mut max_ts = self.max_ts.entry(symbol.to_string()).or_insert(ts);
+        let last_ts = *max_ts;
+        if ts.saturating_add(self.tolerance_ms) >= last_ts {
+            *max_ts = last_ts.max(ts);
+            return Ok(());
+        }
+        drop(max_ts);
+        match self.ts_policy {
+            TimestampPolicy::Accept => Ok(()),
+            TimestampPolicy::Warn => {
+                tracing::warn!(symbol, ts, last_ts, "stale timestamp");
+                Ok(())
+            }
+            TimestampPolicy::Reject => self.check(Err(ProcessError::StaleTimestamp {
+                symbol: symbol.to_string(),
+                ts,
+                last_ts,
+            })),
+        }
+    }
+
+    /// Configura la política de timestamps por nombre
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.ts_policy = TimestampPolicy::parse(policy).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid timestamp policy: {} (accept, warn or reject)", policy))
+        })?;
+        self.tolerance_ms = tolerance_ms;
+        Ok(())
+    }
+
+    /// Máximo ts aceptado de un símbolo
+    pub fn max_ts(&self, symbol: &str) -> Option<u64> {
+        self.max_ts.get(symbol).map(|ts| *ts)
+    }
+
+    /// Resultado para Python: en modo strict el error se lanza como excepción;
+    /// si no, el evento se descarta (valor por defecto: None o lista vacía)
+    pub fn resolve<T: Default>(&self, result: Result<T, ProcessError>) -> Result<T, ProcessError> {
+        match result {
+            Ok(value) => Ok(value),
+            Err(e) if self.strict => Err(e),
+            Err(_) => Ok(T::default()),
+        }
+    }
+
+    /// Rechazos por tipo de un símbolo, o de todos los símbolos si es None
+    pub fn counts(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        let mut totals = HashMap::new();
+        let mut add = |counts: &HashMap<&'static str, u64>| {
+            for (kind, n) in counts {
+                *totals.entry(kind.to_string()).or_insert(0) += n;
+            }
+        };
+        match symbol {
+            Some(symbol) => {
+                if let Some(counts) = self.counts.get(symbol) {
+                    add(&counts);
+                }
+            }
+            None => self.counts.iter().for_each(|entry| add(entry.value())),
+        }
+        totals
+    }
+
+    /// Resetea los contadores y el máximo ts de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.counts.remove(symbol);
+        self.max_ts.remove(symbol);
+    }
+
+    /// Resetea todos los contadores y máximos ts
+    pub fn reset_all(&self) {
+        self.counts.clear();
+        self.max_ts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Level;
+
+    #[test]
+    fn test_validate_trade() {
+        let ok = Trade::new(1000, 100.0, 5.0, "AAPL".to_string());
+        assert!(validate_trade(&ok).is_ok());
+
+        let bad_price = Trade::new(1000, f64::NAN, 5.0, "AAPL".to_string());
+        assert_eq!(validate_trade(&bad_price).unwrap_err().kind(), "invalid_price");
+
+        let bad_size = Trade::new(1000, 100.0, 0.0, "AAPL".to_string());
+        assert_eq!(validate_trade(&bad_size).unwrap_err().kind(), "invalid_size");
+    }
+
+    #[test]
+    fn test_validate_quote() {
+        let quote = |bid: f64, bid_size: f64, ask: f64| Quote::new(1000, "AAPL".to_string(), bid, bid_size, ask, 1.0);
+        let kind = |q: Quote| validate_quote(&q).unwrap_err().kind();
+
+        assert!(validate_quote(&quote(99.0, 0.0, 101.0)).is_ok());
+        assert_eq!(kind(quote(0.0, 1.0, 101.0)), "invalid_price");
+        assert_eq!(kind(quote(99.0, f64::INFINITY, 101.0)), "invalid_size");
+        assert_eq!(kind(quote(101.0, 1.0, 100.0)), "crossed_book");
+        assert_eq!(kind(quote(100.0, 1.0, 100.0)), "locked_book");
+    }
+
+    #[test]
+    fn test_validate_snapshot() {
+        let one_sided = BookSnapshot::new(1000, "AAPL".to_string(), vec![Level::new(99.0, 1.0)], vec![]);
+        assert!(validate_snapshot(&one_sided, false).is_ok());
+        assert_eq!(validate_snapshot(&one_sided, true).unwrap_err().kind(), "empty_book");
+    }
+
+    #[test]
+    fn test_rejections_count_per_symbol() {
+        let rejections = Rejections::default();
+        let trade = Trade::new(1000, -1.0, 5.0, "AAPL".to_string());
+
+        assert!(rejections.check(validate_trade(&trade)).is_err());
+        assert!(rejections.check(validate_trade(&trade)).is_err());
+        assert_eq!(rejections.counts(Some("AAPL")).get("invalid_price"), Some(&2));
+        assert!(rejections.counts(Some("MSFT")).is_empty());
+        assert_eq!(rejections.counts(None).get("invalid_price"), Some(&2));
+
+        rejections.reset_symbol("AAPL");
+        assert!(rejections.counts(None).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_strict_raises() {
+        let mut rejections = Rejections::default();
+        let err = || Err::<Option<f64>, _>(ProcessError::EmptyBook { symbol: "AAPL".to_string() });
+
+        assert_eq!(rejections.resolve(err()).unwrap(), None);
+        rejections.strict = true;
+        assert!(rejections.resolve(err()).is_err());
+    }
+
+    #[test]
+    fn test_validate_book() {
+        let book = |bids: &[f64], asks: &[f64]| BookSnapshot::new(
+            1000,
+            "AAPL".to_string(),
+            bids.iter().map(|&p| Level::new(p, 1.0)).collect(),
+            asks.iter().map(|&p| Level::new(p, 1.0)).collect(),
+        );
+        let kind = |b: BookSnapshot| validate_book(&b).unwrap_err().kind();
+
+        assert!(validate_book(&book(&[99.0, 98.0], &[101.0, 102.0])).is_ok());
+        assert_eq!(kind(book(&[99.0], &[])), "empty_book");
+        assert_eq!(kind(book(&[101.0], &[100.0])), "crossed_book");
+        assert_eq!(kind(book(&[100.0], &[100.0])), "locked_book");
+        assert_eq!(kind(book(&[99.0, 99.0], &[101.0])), "duplicate_level");
+        assert_eq!(kind(book(&[99.0], &[102.0, 101.0])), "unsorted_levels");
+    }
+
+    #[test]
+    fn test_timestamp_policy() {
+        let mut rejections = Rejections { tolerance_ms: 100, ..Default::default() };
+        assert!(rejections.check_timestamp("AAPL", 1000).is_ok());
+
+        // Accept: el evento stale pasa y no mueve el máximo
+        assert!(rejections.check_timestamp("AAPL", 500).is_ok());
+        assert_eq!(rejections.max_ts("AAPL"), Some(1000));
+
+        rejections.ts_policy = TimestampPolicy::Reject;
+        // Dentro de la tolerancia
+        assert!(rejections.check_timestamp("AAPL", 900).is_ok());
+        let err = rejections.check_timestamp("AAPL", 899).unwrap_err();
+        assert_eq!(err, ProcessError::StaleTimestamp { symbol: "AAPL".to_string(), ts: 899, last_ts: 1000 });
+        assert_eq!(rejections.counts(Some("AAPL")).get("stale_timestamp"), Some(&1));
+
+        // Otros símbolos llevan su propio máximo
+        assert!(rejections.check_timestamp("MSFT", 10).is_ok());
+        assert!(rejections.check_timestamp("AAPL", 2000).is_ok());
+        assert_eq!(rejections.max_ts("AAPL"), Some(2000));
+
+        assert_eq!(TimestampPolicy::parse("WARN"), Some(TimestampPolicy::Warn));
+        assert_eq!(TimestampPolicy::parse("drop"), None);
+    }
+}
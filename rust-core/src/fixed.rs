@@ -0,0 +1,247 @@
+//! # Fixed Point
+//! 
+//! Representación en punto fijo para los acumuladores de larga duración
+//! (pv_sum/v_sum de VWAP, CVD y tamaños del heatmap). Sumar millones de
+//! precios y tamaños en f64 acumula error de redondeo; en punto fijo cada
+//! valor se redondea una vez a `decimals` y las sumas son exactas.
+//! 
+//! Un `Fixed` es `mantissa · 10^-scale` con mantissa i128. Cada engine elige
+//! la precisión (decimales de precio y de tamaño) de forma global o por
+//! símbolo con `FixedPointConfig`; sin precisión el símbolo sigue en f64.
+//! 
+//! Hacia Python los valores exactos se devuelven como `decimal.Decimal`
+//! construidos desde su representación decimal, sin pasar por f64.
+
+use dashmap::DashMap;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::sync::Arc;
+use crate::errors::ProcessError;
+
+/// Máximo de decimales de precio o tamaño. Con 9 + 9 un pv de 1e6 · 1e6 por
+/// trade deja margen en i128 para más de 1e12 trades.
+pub const MAX_DECIMALS: u32 = 9;
+
+/// Valor decimal exacto: mantissa · 10^-scale
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Fixed {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+
+impl Fixed {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+    
+    /// Redondea un f64 a `scale` decimales (no finitos => 0)
+    pub fn from_f64(value: f64, scale: u32) -> Self {
+        if !value.is_finite() {
+            return Self::new(0, scale);
+        }
+        Self::new((value * 10f64.powi(scale as i32)).round() as i128, scale)
+    }
+    
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+    
+    /// Mismo valor sin ceros finales en la parte decimal (0.2500 => 0.25)
+    pub fn trimmed(&self) -> Self {
+        let mut value = *self;
+        while value.scale > 0 && value.mantissa % 10 == 0 {
+            value = Self::new(value.mantissa / 10, value.scale - 1);
+        }
+        value
+    }
+    
+    /// Mismo valor con más decimales (una escala menor no se aplica)
+    pub fn rescale(&self, scale: u32) -> Self {
+        if scale <= self.scale {
+            return *self;
+        }
+        Self::new(self.mantissa.saturating_mul(10i128.pow(scale - self.scale)), scale)
+    }
+    
+    /// Cociente redondeado al entero más cercano (índice de tick); None si el
+    /// divisor es 0
+    pub fn div_round(&self, other: Fixed) -> Option<i64> {
+        let scale = self.scale.max(other.scale);
+        let (num, den) = (self.rescale(scale).mantissa, other.rescale(scale).mantissa);
+        if den == 0 {
+            return None;
+        }
+        let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+        // Redondeo "half away from zero", igual que f64::round
+        let rounded = if num >= 0 { (2 * num + den) / (2 * den) } else { -((-2 * num + den) / (2 * den)) };
+        i64::try_from(rounded).ok()
+    }
+    
+    /// Convierte a `decimal.Decimal` sin pérdida
+    #[cfg(feature = "python")]
+    pub fn to_py_decimal(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(py.import_bound("decimal")?.getattr("Decimal")?.call1((self.to_string(),))?.unbind())
+    }
+}
+
+/// Suma exacta; el resultado usa la mayor de las dos escalas
+impl Add for Fixed {
+    type Output = Fixed;
+    
+    fn add(self, other: Fixed) -> Fixed {
+        let scale = self.scale.max(other.scale);
+        Fixed::new(self.rescale(scale).mantissa.saturating_add(other.rescale(scale).mantissa), scale)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    
+    fn sub(self, other: Fixed) -> Fixed {
+        self + Fixed::new(-other.mantissa, other.scale)
+    }
+}
+
+/// Producto exacto; las escalas se suman
+impl Mul for Fixed {
+    type Output = Fixed;
+    
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Fixed) -> Fixed {
+        Fixed::new(self.mantissa.saturating_mul(other.mantissa), self.scale + other.scale)
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+/// Decimales de precio y de tamaño con los que se redondean los eventos
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Precision {
+    pub price_decimals: u32,
+    pub size_decimals: u32,
+}
+
+impl Precision {
+    pub fn new(price_decimals: u32, size_decimals: u32) -> Result<Self, ProcessError> {
+        if price_decimals > MAX_DECIMALS || size_decimals > MAX_DECIMALS {
+            return Err(ProcessError::invalid(
+                format!("price_decimals and size_decimals must be <= {}", MAX_DECIMALS)));
+        }
+        Ok(Self { price_decimals, size_decimals })
+    }
+    
+    pub fn price(&self, price: f64) -> Fixed {
+        Fixed::from_f64(price, self.price_decimals)
+    }
+    
+    pub fn size(&self, size: f64) -> Fixed {
+        Fixed::from_f64(size, self.size_decimals)
+    }
+    
+    pub fn as_tuple(&self) -> (u32, u32) {
+        (self.price_decimals, self.size_decimals)
+    }
+}
+
+/// Ajuste de un engine global y sobrescrito por símbolo (`None` en un
+/// símbolo lo deja sin ajuste aunque haya uno global)
+#[derive(Clone, Debug)]
+pub struct SymbolConfig<T> {
+    default: Option<T>,
+    by_symbol: Arc<DashMap<String, Option<T>>>,
+}
+
+impl<T> Default for SymbolConfig<T> {
+    fn default() -> Self {
+        Self { default: None, by_symbol: Arc::new(DashMap::new()) }
+    }
+}
+
+impl<T: Copy> SymbolConfig<T> {
+    /// Ajuste del símbolo (o el global)
+    pub fn get(&self, symbol: &str) -> Option<T> {
+        match self.by_symbol.get(symbol) {
+            Some(value) => *value,
+            None => self.default,
+        }
+    }
+    
+    /// Fija el ajuste global (symbol None) o el de un símbolo
+    pub fn set(&mut self, value: Option<T>, symbol: Option<&str>) {
+        match symbol {
+            Some(symbol) => { self.by_symbol.insert(symbol.to_string(), value); }
+            None => {
+                self.default = value;
+                self.by_symbol.clear();
+            }
+        }
+    }
+}
+
+/// Precisión de punto fijo de un engine: global y sobrescrita por símbolo
+/// (`None` en un símbolo lo deja en f64 aunque haya precisión global)
+pub type FixedPointConfig = SymbolConfig<Precision>;
+
+impl FixedPointConfig {
+    /// Precisión del símbolo; None = acumulación en f64
+    pub fn precision(&self, symbol: &str) -> Option<Precision> {
+        self.get(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fixed_arithmetic() {
+        let a = Fixed::from_f64(0.1, 8);
+        let b = Fixed::from_f64(0.2, 8);
+        assert_eq!(a + b, Fixed::from_f64(0.3, 8));
+        assert_eq!((a + Fixed::new(5, 0)).to_string(), "5.10000000");
+        assert_eq!((b - a - b).to_string(), "-0.10000000");
+        assert_eq!((Fixed::from_f64(1.5, 2) * Fixed::from_f64(2.25, 2)).to_string(), "3.3750");
+        assert_eq!(Fixed::new(5, 3).to_string(), "0.005");
+        assert_eq!(Fixed::new(-42, 0).to_string(), "-42");
+        assert_eq!(Fixed::from_f64(f64::NAN, 2), Fixed::new(0, 2));
+        assert_eq!(Fixed::from_f64(0.25, MAX_DECIMALS).trimmed(), Fixed::new(25, 2));
+        assert_eq!(Fixed::new(0, 3).trimmed(), Fixed::new(0, 0));
+    }
+    
+    #[test]
+    fn test_fixed_div_round() {
+        let tick = Fixed::from_f64(0.25, 2);
+        assert_eq!(Fixed::from_f64(100.37, 2).div_round(tick), Some(401));
+        assert_eq!(Fixed::from_f64(100.38, 2).div_round(tick), Some(402));
+        assert_eq!(Fixed::from_f64(-0.13, 2).div_round(tick), Some(-1));
+        assert_eq!(tick.div_round(Fixed::new(0, 2)), None);
+    }
+    
+    #[test]
+    fn test_fixed_point_config() {
+        let mut config = FixedPointConfig::default();
+        assert_eq!(config.precision("AAPL"), None);
+        
+        let precision = Precision::new(2, 0).unwrap();
+        config.set(Some(precision), None);
+        config.set(None, Some("BTC"));
+        assert_eq!(config.precision("AAPL"), Some(precision));
+        assert_eq!(config.precision("BTC"), None);
+        
+        assert!(Precision::new(MAX_DECIMALS + 1, 0).is_err());
+    }
+}
@@ -0,0 +1,603 @@
+//! # gRPC
+//! 
+//! Servidor gRPC (tonic, feature `grpc`) para consumidores que no usan
+//! Python. Implementa el servicio `indicators.Engine` de
+//! `proto/engine_service.proto`:
+//! - `GetCVD`, `GetVWAP`, `GetLiquidity`: últimas métricas del símbolo
+//! - `StreamMetrics`: métricas según se calculan, filtradas por símbolos e
+//!   indicadores
+//! - `IngestTrades` (client streaming): trades que se procesan en el manager
+//! 
+//! El servidor se registra como sink del `EngineManager`, así que ve tanto
+//! los eventos del suscriptor como los ingeridos por gRPC. Un stream que no
+//! consume a tiempo pierde las actualizaciones más antiguas.
+
+use dashmap::DashMap;
+use futures::StreamExt;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tonic::codec::{ProstCodec, Streaming};
+use tonic::codegen::{empty_body, http, Body, BoxFuture, BoxStream, Context, Poll, Service, StdError};
+use tonic::server::{ClientStreamingService, Grpc, NamedService, ServerStreamingService, UnaryService};
+use tonic::{Request, Response, Status};
+
+use crate::manager::EngineManager;
+use crate::types::{CVDMetrics, LiquidityMetrics, MetricsBundle, VWAPMetrics};
+use crate::wire::proto::{Level, Trade};
+
+/// Actualizaciones en cola por stream antes de descartar las más antiguas
+const STREAM_CAPACITY: usize = 1024;
+
+/// Espera a que terminen las llamadas en curso (p. ej. un `IngestTrades`
+/// sin cerrar) antes de cortar las conexiones al parar
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Mensajes de `proto/engine_service.proto` (equivalente a la salida de prost-build)
+pub mod proto {
+    use crate::wire::proto::Level;
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct SymbolRequest {
+        #[prost(string, tag = "1")]
+        pub symbol: String,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct StreamRequest {
+        #[prost(string, repeated, tag = "1")]
+        pub symbols: Vec<String>,
+        #[prost(string, repeated, tag = "2")]
+        pub indicators: Vec<String>,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct CvdMetrics {
+        #[prost(double, tag = "1")]
+        pub cvd: f64,
+        #[prost(string, tag = "2")]
+        pub last_side: String,
+        #[prost(double, tag = "3")]
+        pub last_size: f64,
+        #[prost(uint64, tag = "4")]
+        pub timestamp: u64,
+        #[prost(double, tag = "5")]
+        pub buy_volume: f64,
+        #[prost(double, tag = "6")]
+        pub sell_volume: f64,
+        #[prost(uint64, tag = "7")]
+        pub trade_count: u64,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct VwapMetrics {
+        #[prost(double, tag = "1")]
+        pub vwap: f64,
+        #[prost(double, tag = "2")]
+        pub v_sum: f64,
+        #[prost(double, tag = "3")]
+        pub std_dev: f64,
+        #[prost(double, tag = "4")]
+        pub upper_band_1: f64,
+        #[prost(double, tag = "5")]
+        pub lower_band_1: f64,
+        #[prost(double, tag = "6")]
+        pub upper_band_2: f64,
+        #[prost(double, tag = "7")]
+        pub lower_band_2: f64,
+        #[prost(string, optional, tag = "8")]
+        pub session_id: Option<String>,
+        #[prost(uint64, optional, tag = "9")]
+        pub anchor_ts: Option<u64>,
+        #[prost(uint64, tag = "10")]
+        pub last_ts: u64,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct LiquidityMetrics {
+        #[prost(double, tag = "1")]
+        pub mid: f64,
+        #[prost(double, tag = "2")]
+        pub spread: f64,
+        #[prost(double, tag = "3")]
+        pub best_bid: f64,
+        #[prost(double, tag = "4")]
+        pub best_ask: f64,
+        #[prost(double, tag = "5")]
+        pub bids_depth: f64,
+        #[prost(double, tag = "6")]
+        pub asks_depth: f64,
+        #[prost(double, tag = "7")]
+        pub depth_imbalance: f64,
+        #[prost(double, tag = "8")]
+        pub top_imbalance: f64,
+        #[prost(double, tag = "9")]
+        pub microprice: f64,
+        #[prost(double, tag = "10")]
+        pub weighted_mid: f64,
+        #[prost(message, repeated, tag = "11")]
+        pub top_bids: Vec<Level>,
+        #[prost(message, repeated, tag = "12")]
+        pub top_asks: Vec<Level>,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct MetricsUpdate {
+        #[prost(string, tag = "1")]
+        pub symbol: String,
+        #[prost(uint64, tag = "2")]
+        pub ts: u64,
+        #[prost(message, optional, tag = "3")]
+        pub cvd: Option<CvdMetrics>,
+        #[prost(message, optional, tag = "4")]
+        pub vwap: Option<VwapMetrics>,
+        #[prost(message, optional, tag = "5")]
+        pub liquidity: Option<LiquidityMetrics>,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct IngestSummary {
+        #[prost(uint64, tag = "1")]
+        pub received: u64,
+        #[prost(uint64, tag = "2")]
+        pub with_metrics: u64,
+    }
+}
+
+impl From<&CVDMetrics> for proto::CvdMetrics {
+    fn from(m: &CVDMetrics) -> Self {
+        Self {
+            cvd: m.cvd,
+            last_side: m.last_side.clone(),
+            last_size: m.last_size,
+            timestamp: m.timestamp,
+            buy_volume: m.buy_volume,
+            sell_volume: m.sell_volume,
+            trade_count: m.trade_count,
+        }
+    }
+}
+
+impl From<&VWAPMetrics> for proto::VwapMetrics {
+    fn from(m: &VWAPMetrics) -> Self {
+        Self {
+            vwap: m.vwap,
+            v_sum: m.v_sum,
+            std_dev: m.std_dev,
+            upper_band_1: m.upper_band_1,
+            lower_band_1: m.lower_band_1,
+            upper_band_2: m.upper_band_2,
+            lower_band_2: m.lower_band_2,
+            session_id: m.session_id.clone(),
+            anchor_ts: m.anchor_ts,
+            last_ts: m.last_ts,
+        }
+    }
+}
+
+impl From<&LiquidityMetrics> for proto::LiquidityMetrics {
+    fn from(m: &LiquidityMetrics) -> Self {
+        let levels = |levels: &[crate::types::Level]| {
+            levels.iter().map(|l| Level { price: l.price, size: l.size }).collect()
+        };
+        Self {
+            mid: m.mid,
+            spread: m.spread,
+            best_bid: m.best_bid,
+            best_ask: m.best_ask,
+            bids_depth: m.bids_depth,
+            asks_depth: m.asks_depth,
+            depth_imbalance: m.depth_imbalance,
+            top_imbalance: m.top_imbalance,
+            microprice: m.microprice,
+            weighted_mid: m.weighted_mid,
+            top_bids: levels(&m.top_bids),
+            top_asks: levels(&m.top_asks),
+        }
+    }
+}
+
+/// Últimas métricas por símbolo, ya en formato Protobuf
+#[derive(Default)]
+struct Latest {
+    cvd: Option<proto::CvdMetrics>,
+    vwap: Option<proto::VwapMetrics>,
+    liquidity: Option<proto::LiquidityMetrics>,
+}
+
+/// Estado compartido entre el sink del manager y los handlers
+struct Shared {
+    manager: Arc<EngineManager>,
+    latest: DashMap<String, Latest>,
+    // None tras parar: los streams abiertos terminan
+    updates: Mutex<Option<broadcast::Sender<Arc<proto::MetricsUpdate>>>>,
+}
+
+impl Shared {
+    fn new(manager: Arc<EngineManager>, capacity: usize) -> Self {
+        Self {
+            manager,
+            latest: DashMap::new(),
+            updates: Mutex::new(Some(broadcast::channel(capacity).0)),
+        }
+    }
+    
+    fn subscribe(&self) -> Result<broadcast::Receiver<Arc<proto::MetricsUpdate>>, Status> {
+        self.updates.lock().as_ref()
+            .map(broadcast::Sender::subscribe)
+            .ok_or_else(|| Status::unavailable("server is shutting down"))
+    }
+    
+    fn close(&self) {
+        self.updates.lock().take();
+    }
+    
+    fn record(&self, bundle: &MetricsBundle) {
+        let update = proto::MetricsUpdate {
+            symbol: bundle.symbol.clone(),
+            ts: bundle.ts,
+            cvd: bundle.cvd.as_ref().map(Into::into),
+            vwap: bundle.vwap.as_ref().map(Into::into),
+            liquidity: bundle.liquidity.as_ref().map(Into::into),
+        };
+        if update.cvd.is_none() && update.vwap.is_none() && update.liquidity.is_none() {
+            return;
+        }
+        {
+            let mut latest = self.latest.entry(bundle.symbol.clone()).or_default();
+            if update.cvd.is_some() {
+                latest.cvd = update.cvd.clone();
+            }
+            if update.vwap.is_some() {
+                latest.vwap = update.vwap.clone();
+            }
+            if update.liquidity.is_some() {
+                latest.liquidity = update.liquidity.clone();
+            }
+        }
+        // Sin streams abiertos el envío falla y no hay nada que hacer
+        if let Some(updates) = self.updates.lock().as_ref() {
+            let _ = updates.send(Arc::new(update));
+        }
+    }
+    
+    fn latest<T>(&self, symbol: &str, kind: &str, get: impl Fn(&Latest) -> Option<T>) -> Result<Response<T>, Status> {
+        if symbol.is_empty() {
+            return Err(Status::invalid_argument("symbol must not be empty"));
+        }
+        self.latest.get(symbol)
+            .and_then(|latest| get(latest.value()))
+            .map(Response::new)
+            .ok_or_else(|| Status::not_found(format!("no {} metrics for {}", kind, symbol)))
+    }
+}
+
+/// Filtro de `StreamMetrics`; vacío = todo
+struct StreamFilter {
+    symbols: HashSet<String>,
+    cvd: bool,
+    vwap: bool,
+    liquidity: bool,
+}
+
+impl StreamFilter {
+    fn new(request: proto::StreamRequest) -> Result<Self, Status> {
+        let all = request.indicators.is_empty();
+        let mut filter = Self { symbols: request.symbols.into_iter().collect(), cvd: all, vwap: all, liquidity: all };
+        for indicator in &request.indicators {
+            match indicator.to_ascii_lowercase().as_str() {
+                "cvd" => filter.cvd = true,
+                "vwap" => filter.vwap = true,
+                "liquidity" => filter.liquidity = true,
+                other => return Err(Status::invalid_argument(format!(
+                    "unknown indicator {}: must be cvd, vwap or liquidity", other))),
+            }
+        }
+        Ok(filter)
+    }
+    
+    /// La actualización con solo los indicadores pedidos (None si no queda ninguno)
+    fn apply(&self, update: &proto::MetricsUpdate) -> Option<proto::MetricsUpdate> {
+        if !self.symbols.is_empty() && !self.symbols.contains(&update.symbol) {
+            return None;
+        }
+        let filtered = proto::MetricsUpdate {
+            symbol: update.symbol.clone(),
+            ts: update.ts,
+            cvd: update.cvd.clone().filter(|_| self.cvd),
+            vwap: update.vwap.clone().filter(|_| self.vwap),
+            liquidity: update.liquidity.clone().filter(|_| self.liquidity),
+        };
+        (filtered.cvd.is_some() || filtered.vwap.is_some() || filtered.liquidity.is_some()).then_some(filtered)
+    }
+}
+
+// Handlers de cada RPC, con la forma del código que genera tonic-build
+
+struct GetCvd(Arc<Shared>);
+
+impl UnaryService<proto::SymbolRequest> for GetCvd {
+    type Response = proto::CvdMetrics;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+    
+    fn call(&mut self, request: Request<proto::SymbolRequest>) -> Self::Future {
+        let result = self.0.latest(&request.into_inner().symbol, "cvd", |l| l.cvd.clone());
+        Box::pin(std::future::ready(result))
+    }
+}
+
+struct GetVwap(Arc<Shared>);
+
+impl UnaryService<proto::SymbolRequest> for GetVwap {
+    type Response = proto::VwapMetrics;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+    
+    fn call(&mut self, request: Request<proto::SymbolRequest>) -> Self::Future {
+        let result = self.0.latest(&request.into_inner().symbol, "vwap", |l| l.vwap.clone());
+        Box::pin(std::future::ready(result))
+    }
+}
+
+struct GetLiquidity(Arc<Shared>);
+
+impl UnaryService<proto::SymbolRequest> for GetLiquidity {
+    type Response = proto::LiquidityMetrics;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+    
+    fn call(&mut self, request: Request<proto::SymbolRequest>) -> Self::Future {
+        let result = self.0.latest(&request.into_inner().symbol, "liquidity", |l| l.liquidity.clone());
+        Box::pin(std::future::ready(result))
+    }
+}
+
+struct StreamMetrics(Arc<Shared>);
+
+impl ServerStreamingService<proto::StreamRequest> for StreamMetrics {
+    type Response = proto::MetricsUpdate;
+    type ResponseStream = BoxStream<proto::MetricsUpdate>;
+    type Future = BoxFuture<Response<Self::ResponseStream>, Status>;
+    
+    fn call(&mut self, request: Request<proto::StreamRequest>) -> Self::Future {
+        let filter = StreamFilter::new(request.into_inner());
+        let result = filter.and_then(|filter| Ok((self.0.subscribe()?, Arc::new(filter)))).map(|state| {
+            let stream = futures::stream::unfold(state, |(mut updates, filter)| async move {
+                loop {
+                    match updates.recv().await {
+                        Ok(update) => {
+                            if let Some(update) = filter.apply(&update) {
+                                return Some((Ok::<_, Status>(update), (updates, filter)));
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::debug!(skipped, "grpc metrics stream lagging");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            });
+            Response::new(stream.boxed())
+        });
+        Box::pin(std::future::ready(result))
+    }
+}
+
+struct IngestTrades(Arc<Shared>);
+
+impl ClientStreamingService<Trade> for IngestTrades {
+    type Response = proto::IngestSummary;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+    
+    fn call(&mut self, request: Request<Streaming<Trade>>) -> Self::Future {
+        let manager = self.0.manager.clone();
+        Box::pin(async move {
+            let mut trades = request.into_inner();
+            let mut summary = proto::IngestSummary::default();
+            while let Some(trade) = trades.message().await? {
+                summary.received += 1;
+                if !manager.on_trade(&trade.into()).is_empty() {
+                    summary.with_metrics += 1;
+                }
+            }
+            Ok(Response::new(summary))
+        })
+    }
+}
+
+/// Servicio `indicators.Engine` sobre un manager
+#[derive(Clone)]
+pub struct EngineService {
+    shared: Arc<Shared>,
+}
+
+impl NamedService for EngineService {
+    const NAME: &'static str = "indicators.Engine";
+}
+
+impl<B> Service<http::Request<B>> for EngineService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+    
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let shared = self.shared.clone();
+        match request.uri().path() {
+            "/indicators.Engine/GetCVD" => Box::pin(async move {
+                Ok(Grpc::new(ProstCodec::default()).unary(GetCvd(shared), request).await)
+            }),
+            "/indicators.Engine/GetVWAP" => Box::pin(async move {
+                Ok(Grpc::new(ProstCodec::default()).unary(GetVwap(shared), request).await)
+            }),
+            "/indicators.Engine/GetLiquidity" => Box::pin(async move {
+                Ok(Grpc::new(ProstCodec::default()).unary(GetLiquidity(shared), request).await)
+            }),
+            "/indicators.Engine/StreamMetrics" => Box::pin(async move {
+                Ok(Grpc::new(ProstCodec::default()).server_streaming(StreamMetrics(shared), request).await)
+            }),
+            "/indicators.Engine/IngestTrades" => Box::pin(async move {
+                Ok(Grpc::new(ProstCodec::default()).client_streaming(IngestTrades(shared), request).await)
+            }),
+            _ => Box::pin(async move {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", tonic::Code::Unimplemented as i32)
+                    .header(http::header::CONTENT_TYPE, "application/grpc")
+                    .body(empty_body())
+                    .unwrap())
+            }),
+        }
+    }
+}
+
+/// Servidor gRPC en su propio hilo y runtime, registrado como sink del manager
+pub struct GrpcServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    sink: u64,
+    stop_tx: watch::Sender<bool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl GrpcServer {
+    /// Escucha en `addr` ("0.0.0.0:50051"; puerto 0 = uno libre)
+    pub fn start(addr: &str, manager: Arc<EngineManager>) -> std::io::Result<Self> {
+        let listener = std::net::TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        
+        let shared = Arc::new(Shared::new(manager, STREAM_CAPACITY));
+        let service = EngineService { shared: shared.clone() };
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let worker = std::thread::Builder::new()
+            .name("grpc-server".to_string())
+            .spawn(move || runtime.block_on(serve(listener, service, stop_rx)))?;
+        
+        let sink_shared = shared.clone();
+        let sink = shared.manager.add_sink(Arc::new(move |bundle: &MetricsBundle| sink_shared.record(bundle)));
+        tracing::info!(%addr, "grpc server listening");
+        Ok(Self { addr, shared, sink, stop_tx, worker: Some(worker) })
+    }
+    
+    /// Dirección real de escucha
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+    
+    /// Deja de aceptar llamadas, cierra los streams y espera al hilo
+    pub fn stop(&mut self) {
+        self.shared.manager.remove_sink(self.sink);
+        self.shared.close();
+        let _ = self.stop_tx.send(true);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for GrpcServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Sirve hasta la señal de parada; pasado `SHUTDOWN_GRACE` corta lo que quede
+async fn serve(listener: std::net::TcpListener, service: EngineService, stop_rx: watch::Receiver<bool>) {
+    let listener = match tokio::net::TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::warn!(error = %e, "grpc server error");
+            return;
+        }
+    };
+    // Un error de accept (p. ej. sin descriptores libres) no detiene el servidor
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => return Some((Ok::<_, std::io::Error>(stream), listener)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "grpc accept error");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    });
+    let stopped = |mut stop_rx: watch::Receiver<bool>| async move {
+        let _ = stop_rx.wait_for(|stop| *stop).await;
+    };
+    let server = tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_incoming_shutdown(Box::pin(incoming), stopped(stop_rx.clone()));
+    tokio::select! {
+        served = server => {
+            if let Err(e) = served {
+                tracing::warn!(error = %e, "grpc server error");
+            }
+        }
+        _ = async { stopped(stop_rx).await; tokio::time::sleep(SHUTDOWN_GRACE).await } => {
+            tracing::warn!("grpc server forced shutdown");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn shared() -> Shared {
+        Shared::new(Arc::new(EngineManager::new(true, true, false, false, None, None, None).unwrap()), 4)
+    }
+    
+    #[test]
+    fn test_latest_metrics() {
+        let shared = shared();
+        let mut updates = shared.subscribe().unwrap();
+        let bundle = shared.manager.on_trade(&crate::types::Trade::new(1_000, 100.0, 2.0, "AAPL".to_string()));
+        shared.record(&bundle);
+        
+        let cvd = shared.latest("AAPL", "cvd", |l| l.cvd.clone()).unwrap().into_inner();
+        assert_eq!((cvd.trade_count, cvd.timestamp), (1, 1_000));
+        assert_eq!(shared.latest("AAPL", "vwap", |l| l.vwap.clone()).unwrap().into_inner().vwap, 100.0);
+        assert_eq!(shared.latest("AAPL", "liquidity", |l| l.liquidity.clone()).unwrap_err().code(), tonic::Code::NotFound);
+        assert_eq!(shared.latest("", "cvd", |l| l.cvd.clone()).unwrap_err().code(), tonic::Code::InvalidArgument);
+        assert_eq!(updates.try_recv().unwrap().symbol, "AAPL");
+        
+        // Al parar terminan los streams y no se abren más
+        shared.close();
+        assert!(matches!(updates.try_recv(), Err(broadcast::error::TryRecvError::Closed)));
+        assert_eq!(shared.subscribe().unwrap_err().code(), tonic::Code::Unavailable);
+    }
+    
+    #[test]
+    fn test_stream_filter() {
+        let update = proto::MetricsUpdate {
+            symbol: "AAPL".to_string(),
+            ts: 1_000,
+            cvd: Some(proto::CvdMetrics::default()),
+            vwap: Some(proto::VwapMetrics::default()),
+            liquidity: None,
+        };
+        let filter = |symbols: &[&str], indicators: &[&str]| StreamFilter::new(proto::StreamRequest {
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            indicators: indicators.iter().map(|s| s.to_string()).collect(),
+        });
+        
+        assert_eq!(filter(&[], &[]).unwrap().apply(&update), Some(update.clone()));
+        let cvd_only = filter(&["AAPL"], &["CVD"]).unwrap().apply(&update).unwrap();
+        assert!(cvd_only.cvd.is_some() && cvd_only.vwap.is_none());
+        assert!(filter(&["MSFT"], &[]).unwrap().apply(&update).is_none());
+        assert!(filter(&[], &["liquidity"]).unwrap().apply(&update).is_none());
+        assert_eq!(filter(&[], &["rsi"]).err().unwrap().code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,272 @@
+//! # Absorption Detector
+//! 
+//! Cruza trades con snapshots del libro para detectar absorción: volumen
+//! agresivo grande ejecutado contra el mejor nivel de un lado sin que el
+//! precio ceda (el tamaño en reposo absorbe el flujo).
+//! 
+//! Los trades que golpean el mejor bid (ventas) o el mejor ask (compras)
+//! acumulan un episodio por lado; trades separados más de `window_ms` abren
+//! uno nuevo. En cada snapshot:
+//! - si el nivel fue consumido (el mejor precio lo atravesó) el episodio se
+//!   descarta
+//! - si sigue en pie con al menos `min_volume` absorbido se reporta una vez
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::types::{AbsorptionEvent, BookSnapshot, Level, Trade};
+use crate::utils::price_to_tick;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Volumen agresivo acumulado contra un nivel
+#[derive(Clone, Debug)]
+struct Episode {
+    tick: i64,
+    price: f64,
+    start_ts: u64,
+    last_ts: u64,
+    volume: f64,
+    trades: usize,
+    reported: bool,
+}
+
+/// Estado por símbolo; índice 0 = bid, 1 = ask
+#[derive(Clone, Debug, Default)]
+struct SymbolState {
+    best: [Option<(i64, f64)>; 2],
+    episodes: [Option<Episode>; 2],
+}
+
+const BID: usize = 0;
+const ASK: usize = 1;
+
+py_class! {
+/// Detector de absorción por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct AbsorptionDetector {
+    /// Volumen agresivo mínimo absorbido por el nivel
+    #[pyo3(get)]
+    pub min_volume: f64,
+    /// Separación máxima entre trades de un mismo episodio (ms)
+    #[pyo3(get)]
+    pub window_ms: u64,
+    #[pyo3(get)]
+    pub tick_size: f64,
+    state: Arc<DashMap<String, SymbolState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl AbsorptionDetector {
+    #[new]
+    #[pyo3(signature = (min_volume=100.0, window_ms=5_000, tick_size=0.01))]
+    pub fn new(min_volume: f64, window_ms: u64, tick_size: f64) -> Result<Self, ProcessError> {
+        if !(min_volume.is_finite() && min_volume > 0.0 && tick_size.is_finite() && tick_size > 0.0) {
+            return Err(ProcessError::invalid(
+                "min_volume and tick_size must be positive"));
+        }
+        if window_ms == 0 {
+            return Err(ProcessError::invalid("window_ms must be > 0"));
+        }
+        Ok(Self {
+            min_volume,
+            window_ms,
+            tick_size,
+            state: Arc::new(DashMap::new()),
+        })
+    }
+    
+    /// Atribuye el volumen del trade al mejor nivel que golpea. El lado sale
+    /// del trade o, si no viene, del nivel del libro en el que se ejecutó
+    pub fn on_trade(&self, trade: &Trade) {
+        if trade.price <= 0.0 || trade.size <= 0.0 {
+            return;
+        }
+        let Some(mut state) = self.state.get_mut(&trade.symbol) else { return };
+        let tick = price_to_tick(trade.price, self.tick_size);
+        let at_best = |side: usize| state.best[side].is_some_and(|(best, _)| best == tick);
+        let side = match trade.side.as_deref().map(str::to_ascii_uppercase).as_deref() {
+            Some("BUY") => ASK,
+            Some("SELL") => BID,
+            _ if at_best(ASK) => ASK,
+            _ => BID,
+        };
+        if !at_best(side) {
+            return;
+        }
+        
+        let window_ms = self.window_ms;
+        let episode = state.episodes[side].get_or_insert_with(|| Episode::new(tick, trade));
+        if episode.tick != tick || trade.ts.saturating_sub(episode.last_ts) > window_ms {
+            *episode = Episode::new(tick, trade);
+        }
+        episode.last_ts = episode.last_ts.max(trade.ts);
+        episode.volume += trade.size;
+        episode.trades += 1;
+    }
+    
+    /// Actualiza los mejores niveles y devuelve las absorciones confirmadas
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<AbsorptionEvent> {
+        let mut state = self.state.entry(snapshot.symbol.clone()).or_default();
+        let mut events = Vec::new();
+        
+        for (side, levels) in [(BID, &snapshot.bids), (ASK, &snapshot.asks)] {
+            let best = levels.first()
+                .filter(|l| l.price > 0.0 && l.size > 0.0)
+                .map(|l| (price_to_tick(l.price, self.tick_size), l.price));
+            state.best[side] = best;
+            
+            let Some(episode) = state.episodes[side].as_mut() else { continue };
+            // El nivel aguanta mientras el mejor precio no lo haya atravesado
+            let held = best.is_some_and(|(tick, _)| {
+                if side == BID { tick >= episode.tick } else { tick <= episode.tick }
+            });
+            let expired = snapshot.ts.saturating_sub(episode.last_ts) > self.window_ms;
+            if !held || expired {
+                state.episodes[side] = None;
+                continue;
+            }
+            if episode.reported || episode.volume < self.min_volume {
+                continue;
+            }
+            episode.reported = true;
+            tracing::debug!(symbol = %snapshot.symbol, price = episode.price, volume = episode.volume, "absorption");
+            events.push(AbsorptionEvent {
+                ts: snapshot.ts,
+                symbol: snapshot.symbol.clone(),
+                side: if side == BID { "bid" } else { "ask" }.to_string(),
+                price: episode.price,
+                absorbed_volume: episode.volume,
+                trades: episode.trades,
+                start_ts: episode.start_ts,
+                duration_ms: snapshot.ts.saturating_sub(episode.start_ts),
+                resting_size: self.size_at(levels, episode.tick),
+            });
+        }
+        events
+    }
+    
+    /// Volumen absorbido en el episodio abierto de un lado ("bid" | "ask")
+    pub fn get_absorbed_volume(&self, symbol: &str, side: &str) -> Option<f64> {
+        let side = match side.to_ascii_lowercase().as_str() {
+            "bid" => BID,
+            "ask" => ASK,
+            _ => return None,
+        };
+        self.state.get(symbol)
+            .and_then(|state| state.episodes[side].as_ref().map(|e| e.volume))
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("AbsorptionDetector(min_volume={}, window_ms={}, symbols={})",
+                self.min_volume, self.window_ms, self.state.len())
+    }
+}
+}
+
+impl AbsorptionDetector {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.state.len()
+    }
+    
+    /// Tamaño mostrado en el nivel (0 si ya no está en el libro)
+    fn size_at(&self, levels: &[Level], tick: i64) -> f64 {
+        levels.iter()
+            .find(|l| price_to_tick(l.price, self.tick_size) == tick)
+            .map_or(0.0, |l| l.size)
+    }
+}
+
+impl Episode {
+    fn new(tick: i64, trade: &Trade) -> Self {
+        Self {
+            tick,
+            price: trade.price,
+            start_ts: trade.ts,
+            last_ts: trade.ts,
+            volume: 0.0,
+            trades: 0,
+            reported: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn book(ts: u64, bid: f64, ask: f64, ask_size: f64) -> BookSnapshot {
+        BookSnapshot::new(ts, "AAPL".to_string(),
+            vec![Level::new(bid, 50.0)],
+            vec![Level::new(ask, ask_size), Level::new(ask + 0.01, 50.0)])
+    }
+    
+    fn buy(ts: u64, price: f64, size: f64) -> Trade {
+        let mut trade = Trade::new(ts, price, size, "AAPL".to_string());
+        trade.side = Some("BUY".to_string());
+        trade
+    }
+    
+    #[test]
+    fn test_absorption_detector_validation() {
+        assert!(AbsorptionDetector::new(0.0, 5_000, 0.01).is_err());
+        assert!(AbsorptionDetector::new(100.0, 0, 0.01).is_err());
+        assert!(AbsorptionDetector::new(100.0, 5_000, 0.0).is_err());
+    }
+    
+    #[test]
+    fn test_absorption_reported_when_level_holds() {
+        let detector = AbsorptionDetector::new(100.0, 5_000, 0.01).unwrap();
+        detector.on_snapshot(&book(0, 99.99, 100.00, 20.0));
+        
+        // 120 comprados contra el ask de 100.00 y el ask no se mueve
+        detector.on_trade(&buy(100, 100.00, 60.0));
+        assert!(detector.on_snapshot(&book(200, 99.99, 100.00, 20.0)).is_empty());
+        detector.on_trade(&buy(300, 100.00, 60.0));
+        assert_eq!(detector.get_absorbed_volume("AAPL", "ask"), Some(120.0));
+        
+        let events = detector.on_snapshot(&book(400, 99.99, 100.00, 15.0));
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.side, "ask");
+        assert_eq!(event.price, 100.00);
+        assert_eq!(event.absorbed_volume, 120.0);
+        assert_eq!(event.trades, 2);
+        assert_eq!((event.start_ts, event.duration_ms), (100, 300));
+        assert_eq!(event.resting_size, 15.0);
+        
+        // Se reporta una sola vez por episodio
+        assert!(detector.on_snapshot(&book(500, 99.99, 100.00, 15.0)).is_empty());
+    }
+    
+    #[test]
+    fn test_no_absorption_when_level_breaks() {
+        let detector = AbsorptionDetector::new(100.0, 5_000, 0.01).unwrap();
+        detector.on_snapshot(&book(0, 99.99, 100.00, 20.0));
+        detector.on_trade(&buy(100, 100.00, 150.0));
+        
+        // El ask sube a 100.01: el nivel fue consumido
+        assert!(detector.on_snapshot(&book(200, 100.00, 100.01, 20.0)).is_empty());
+        assert_eq!(detector.get_absorbed_volume("AAPL", "ask"), None);
+        
+        // Trades que no golpean el mejor nivel no cuentan
+        detector.on_trade(&buy(300, 100.05, 150.0));
+        assert_eq!(detector.get_absorbed_volume("AAPL", "ask"), None);
+    }
+}
@@ -0,0 +1,227 @@
+//! # ATR Engine
+//! 
+//! True range y Average True Range con suavizado de Wilder por
+//! (símbolo, timeframe, periodo): TR = max(high - low, |high - prev_close|,
+//! |low - prev_close|); el ATR se siembra con la media de los primeros
+//! `period` TR y después ATR = (ATR·(n-1) + TR) / n.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::types::{ATRMetrics, Bar};
+use crate::utils::count_symbols;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Estado de Wilder para un periodo
+#[derive(Clone, Debug)]
+struct ATRState {
+    period: usize,
+    prev_close: Option<f64>,
+    count: usize,
+    atr: f64,
+}
+
+impl ATRState {
+    fn new(period: usize) -> Self {
+        Self { period, prev_close: None, count: 0, atr: 0.0 }
+    }
+    
+    /// Incorpora una barra y devuelve su true range
+    fn push(&mut self, high: f64, low: f64, close: f64) -> f64 {
+        let true_range = match self.prev_close.replace(close) {
+            Some(prev) => (high - low).max((high - prev).abs()).max((low - prev).abs()),
+            None => high - low,
+        };
+        
+        self.count += 1;
+        if self.count <= self.period {
+            self.atr += (true_range - self.atr) / self.count as f64;
+        } else {
+            let n = self.period as f64;
+            self.atr = (self.atr * (n - 1.0) + true_range) / n;
+        }
+        true_range
+    }
+    
+    fn atr(&self) -> Option<f64> {
+        (self.count >= self.period).then_some(self.atr)
+    }
+}
+
+py_class! {
+/// Engine de ATR por símbolo y timeframe
+#[pyclass]
+#[derive(Clone)]
+pub struct ATREngine {
+    #[pyo3(get)]
+    pub periods: Vec<usize>,
+    // Estado por (symbol, tf, period)
+    state: Arc<DashMap<(String, String, usize), ATRState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl ATREngine {
+    #[new]
+    #[pyo3(signature = (periods=vec![14]))]
+    pub fn new(periods: Vec<usize>) -> Result<Self, ProcessError> {
+        if periods.is_empty() || periods.contains(&0) {
+            return Err(ProcessError::invalid("periods must be non-empty and > 0"));
+        }
+        let mut periods = periods;
+        periods.sort_unstable();
+        periods.dedup();
+        Ok(Self { periods, state: Arc::new(DashMap::new()) })
+    }
+    
+    /// Procesa una barra del stream (symbol/tf salen de la barra)
+    pub fn on_bar(&self, bar: &Bar) -> Vec<ATRMetrics> {
+        if bar.high < bar.low || bar.low <= 0.0 {
+            return Vec::new();
+        }
+        
+        self.periods.iter()
+            .map(|&period| {
+                let mut state = self.state.entry((bar.symbol.clone(), bar.tf.clone(), period))
+                    .or_insert_with(|| ATRState::new(period));
+                let true_range = state.push(bar.high, bar.low, bar.close);
+                ATRMetrics {
+                    symbol: bar.symbol.clone(),
+                    tf: bar.tf.clone(),
+                    ts: bar.ts,
+                    period,
+                    true_range,
+                    atr: state.atr(),
+                }
+            })
+            .collect()
+    }
+    
+    /// Procesa barras históricas en orden (backfill); libera el GIL mientras tanto
+    #[cfg(feature = "python")]
+    #[pyo3(name = "on_bar_batch")]
+    pub fn py_on_bar_batch(&self, py: Python<'_>, bars: Vec<Bar>) -> Vec<ATRMetrics> {
+        py.allow_threads(|| self.on_bar_batch(bars))
+    }
+    
+    /// ATR actual de un símbolo/timeframe para un periodo
+    pub fn get_atr(&self, symbol: &str, tf: &str, period: usize) -> Option<f64> {
+        self.state.get(&(symbol.to_string(), tf.to_string(), period)).and_then(|s| s.atr())
+    }
+    
+    /// ATR de una serie (high, low, close) sin tocar el estado (None en el
+    /// warm-up); libera el GIL mientras tanto
+    #[cfg(feature = "python")]
+    #[staticmethod]
+    #[pyo3(name = "compute_batch", signature = (highs, lows, closes, period=14))]
+    pub fn py_compute_batch(py: Python<'_>, highs: Vec<f64>, lows: Vec<f64>, closes: Vec<f64>, period: usize) -> Result<Vec<Option<f64>>, ProcessError> {
+        py.allow_threads(|| Self::compute_batch(highs, lows, closes, period))
+    }
+    
+    /// Resetea el estado de un símbolo (todos sus timeframes)
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.retain(|key, _| key.0 != symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("ATREngine(periods={:?}, entries={})", self.periods, self.state.len())
+    }
+}
+}
+
+impl ATREngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.state.iter().map(|e| e.key().0.clone()))
+    }
+    
+    /// Procesa barras históricas en orden (backfill); el estado queda listo para tiempo real
+    pub fn on_bar_batch(&self, bars: Vec<Bar>) -> Vec<ATRMetrics> {
+        bars.iter().flat_map(|bar| self.on_bar(bar)).collect()
+    }
+    
+    /// ATR de una serie (high, low, close) sin tocar el estado (None en el warm-up)
+    pub fn compute_batch(highs: Vec<f64>, lows: Vec<f64>, closes: Vec<f64>, period: usize) -> Result<Vec<Option<f64>>, ProcessError> {
+        if period == 0 || highs.len() != lows.len() || highs.len() != closes.len() {
+            return Err(ProcessError::invalid(
+                "period must be > 0 and highs/lows/closes must have the same length"));
+        }
+        let mut state = ATRState::new(period);
+        Ok(highs.iter().zip(&lows).zip(&closes)
+            .map(|((high, low), close)| {
+                state.push(*high, *low, *close);
+                state.atr()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: u64, high: f64, low: f64, close: f64, tf: &str) -> Bar {
+        Bar::new(ts, close, high, low, close, 1.0, tf.to_string(), "AAPL".to_string())
+    }
+
+    #[test]
+    fn test_atr_validation() {
+        assert!(ATREngine::new(vec![0]).is_err());
+        assert!(ATREngine::compute_batch(vec![1.0], vec![], vec![1.0], 14).is_err());
+        
+        let engine = ATREngine::new(vec![2]).unwrap();
+        assert!(engine.on_bar(&bar(1, 9.0, 10.0, 9.5, "1m")).is_empty());
+    }
+
+    #[test]
+    fn test_true_range_and_wilder_atr() {
+        let engine = ATREngine::new(vec![2]).unwrap();
+        
+        let m = engine.on_bar(&bar(1, 11.0, 9.0, 10.0, "1m"));
+        assert_eq!(m[0].true_range, 2.0);
+        assert_eq!(m[0].atr, None);
+        
+        // Gap al alza: TR = high - prev_close = 14 - 10
+        let m = engine.on_bar(&bar(2, 14.0, 13.0, 13.5, "1m"));
+        assert_eq!(m[0].true_range, 4.0);
+        assert_eq!(m[0].atr, Some(3.0));
+        
+        // Gap a la baja: TR = prev_close - low = 13.5 - 12.5; ATR = (3·1 + 1) / 2
+        let m = engine.on_bar(&bar(3, 13.0, 12.5, 12.8, "1m"));
+        assert_eq!(m[0].true_range, 1.0);
+        assert_eq!(engine.get_atr("AAPL", "1m", 2), Some(2.0));
+    }
+
+    #[test]
+    fn test_atr_separated_by_timeframe() {
+        let engine = ATREngine::new(vec![1]).unwrap();
+        
+        engine.on_bar(&bar(1, 11.0, 9.0, 10.0, "1m"));
+        engine.on_bar(&bar(1, 15.0, 5.0, 10.0, "1h"));
+        
+        assert_eq!(engine.get_atr("AAPL", "1m", 1), Some(2.0));
+        assert_eq!(engine.get_atr("AAPL", "1h", 1), Some(10.0));
+    }
+
+    #[test]
+    fn test_atr_batch_matches_streaming() {
+        let highs = vec![11.0, 14.0, 13.0, 13.6, 12.9];
+        let lows = vec![9.0, 13.0, 12.5, 12.7, 11.8];
+        let closes = vec![10.0, 13.5, 12.8, 13.1, 12.0];
+        
+        let engine = ATREngine::new(vec![3]).unwrap();
+        let bars: Vec<Bar> = (0..5).map(|i| bar(i as u64, highs[i], lows[i], closes[i], "1m")).collect();
+        let streamed: Vec<Option<f64>> = engine.on_bar_batch(bars).iter().map(|m| m.atr).collect();
+        
+        assert_eq!(streamed, ATREngine::compute_batch(highs, lows, closes, 3).unwrap());
+    }
+}
@@ -0,0 +1,296 @@
+//! # Book Pressure Engine
+//! 
+//! Integra el depth imbalance con signo sobre una ventana temporal
+//! (Σ imbalance × dt) para obtener una presión suavizada por símbolo.
+//! Opcionalmente mantiene una integral con decaimiento exponencial.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::config::ConfigRegistry;
+use crate::types::{BookSnapshot, BookPressureMetrics};
+use crate::utils::safe_div;
+use crate::errors::{validate_snapshot, ProcessError, Rejections};
+use crate::py_macros::{py_class, py_methods};
+
+/// Segmento en el que el imbalance se mantuvo constante: (inicio, fin, imbalance)
+type Segment = (u64, u64, f64);
+
+/// Estado de presión de un símbolo
+#[derive(Clone, Debug, Default)]
+struct PressureState {
+    last: Option<(u64, f64)>,
+    segments: VecDeque<Segment>,
+    pressure: f64,
+    covered_ms: u64,
+    decayed: f64,
+}
+
+impl PressureState {
+    /// Cierra el segmento previo hasta ts e incorpora el nuevo imbalance
+    fn update(&mut self, ts: u64, imbalance: f64, window_ms: u64, half_life_ms: Option<u64>) {
+        if let Some((last_ts, last_imbalance)) = self.last {
+            if ts > last_ts {
+                let dt_ms = ts - last_ts;
+                self.segments.push_back((last_ts, ts, last_imbalance));
+                self.pressure += last_imbalance * dt_ms as f64 / 1000.0;
+                self.covered_ms += dt_ms;
+                
+                if let Some(half_life) = half_life_ms {
+                    let decay = (-std::f64::consts::LN_2 * dt_ms as f64 / half_life as f64).exp();
+                    self.decayed = self.decayed * decay + last_imbalance * dt_ms as f64 / 1000.0;
+                }
+            }
+        }
+        if self.last.is_none_or(|(last_ts, _)| ts >= last_ts) {
+            self.last = Some((ts, imbalance));
+        }
+        self.evict(ts, window_ms);
+    }
+    
+    /// Retira (total o parcialmente) los segmentos anteriores a ts - window_ms
+    fn evict(&mut self, now: u64, window_ms: u64) {
+        let Some(cutoff) = now.checked_sub(window_ms) else { return };
+        while let Some(front) = self.segments.front_mut() {
+            let (start, end, imbalance) = *front;
+            if start >= cutoff {
+                break;
+            }
+            let trimmed_end = end.min(cutoff);
+            let dt_ms = trimmed_end - start;
+            self.pressure -= imbalance * dt_ms as f64 / 1000.0;
+            self.covered_ms -= dt_ms;
+            if end <= cutoff {
+                self.segments.pop_front();
+            } else {
+                front.0 = cutoff;
+                break;
+            }
+        }
+        if self.segments.is_empty() {
+            self.pressure = 0.0;
+            self.covered_ms = 0;
+        }
+    }
+}
+
+py_class! {
+/// Engine de presión del libro por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct BookPressureEngine {
+    #[pyo3(get)]
+    pub window_ms: u64,
+    #[pyo3(get)]
+    pub half_life_ms: Option<u64>,
+    #[pyo3(get)]
+    pub depth_levels: usize,
+    state: Arc<DashMap<String, PressureState>>,
+    // Configuración por símbolo (depth_levels); None = depth_levels global
+    config: Option<ConfigRegistry>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl BookPressureEngine {
+    #[new]
+    #[pyo3(signature = (window_ms=10_000, half_life_ms=None, depth_levels=10))]
+    pub fn new(window_ms: u64, half_life_ms: Option<u64>, depth_levels: usize) -> Result<Self, ProcessError> {
+        if window_ms == 0 || half_life_ms == Some(0) || depth_levels == 0 {
+            return Err(ProcessError::invalid(
+                "window_ms, half_life_ms and depth_levels must be > 0"));
+        }
+        Ok(Self {
+            window_ms,
+            half_life_ms,
+            depth_levels,
+            state: Arc::new(DashMap::new()),
+            config: None,
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Usa la configuración por símbolo (depth_levels del registro)
+    #[setter]
+    pub fn set_config(&mut self, config: Option<ConfigRegistry>) {
+        self.config = config;
+    }
+    
+    /// Procesa un snapshot y devuelve la presión actualizada.
+    /// En modo `strict` un libro vacío lanza EmptyBookError; si no, devuelve None
+    #[pyo3(name = "on_snapshot")]
+    pub fn py_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<Option<BookPressureMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_snapshot(snapshot))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Presión actual del símbolo (Σ imbalance × dt en la ventana)
+    pub fn get_pressure(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol).map(|s| s.pressure)
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("BookPressureEngine(window_ms={}, half_life_ms={:?}, symbols={})",
+                self.window_ms, self.half_life_ms, self.state.len())
+    }
+}
+}
+
+impl BookPressureEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.state.len()
+    }
+    
+    /// Procesa un snapshot; si no es válido se descarta y queda contado en las rejections
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Option<BookPressureMetrics> {
+        self.try_on_snapshot(snapshot).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un snapshot; Err si no es válido (también queda contado)
+    pub fn try_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<Option<BookPressureMetrics>, ProcessError> {
+        self.rejections.check(validate_snapshot(snapshot, true))?;
+        self.rejections.check_timestamp(&snapshot.symbol, snapshot.ts)?;
+        Ok(self.process_snapshot(snapshot))
+    }
+    
+    /// Procesa un snapshot y devuelve la presión actualizada
+    fn process_snapshot(&self, snapshot: &BookSnapshot) -> Option<BookPressureMetrics> {
+        let depth_levels = self.config.as_ref()
+            .and_then(|c| c.depth_levels(&snapshot.symbol))
+            .unwrap_or(self.depth_levels);
+        let bids_depth: f64 = snapshot.bids.iter().take(depth_levels).map(|l| l.size).sum();
+        let asks_depth: f64 = snapshot.asks.iter().take(depth_levels).map(|l| l.size).sum();
+        let total = bids_depth + asks_depth;
+        let imbalance = if total > 0.0 { (bids_depth - asks_depth) / total } else { 0.0 };
+        
+        let mut state = self.state.entry(snapshot.symbol.clone()).or_default();
+        state.update(snapshot.ts, imbalance, self.window_ms, self.half_life_ms);
+        
+        Some(BookPressureMetrics {
+            symbol: snapshot.symbol.clone(),
+            ts: snapshot.ts,
+            imbalance,
+            pressure: state.pressure,
+            normalized_pressure: if state.covered_ms > 0 {
+                safe_div(state.pressure * 1000.0, state.covered_ms as f64)
+            } else {
+                imbalance
+            },
+            decayed_pressure: state.decayed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Level;
+
+    fn book(ts: u64, bid_size: f64, ask_size: f64) -> BookSnapshot {
+        BookSnapshot::new(ts, "AAPL".to_string(),
+            vec![Level::new(99.99, bid_size)],
+            vec![Level::new(100.01, ask_size)])
+    }
+
+    #[test]
+    fn test_book_pressure_validation() {
+        assert!(BookPressureEngine::new(0, None, 10).is_err());
+        assert!(BookPressureEngine::new(1000, Some(0), 10).is_err());
+    }
+
+    #[test]
+    fn test_book_pressure_integrates_imbalance() {
+        let engine = BookPressureEngine::new(10_000, None, 10).unwrap();
+        
+        // Imbalance 0.5 durante 2s y -1.0 durante 1s
+        engine.on_snapshot(&book(0, 30.0, 10.0));
+        engine.on_snapshot(&book(2_000, 0.0, 10.0));
+        let metrics = engine.on_snapshot(&book(3_000, 10.0, 10.0)).unwrap();
+        
+        assert!((metrics.pressure - 0.0).abs() < 1e-9); // 0.5·2 - 1.0·1
+        assert_eq!(metrics.imbalance, 0.0);
+        assert!(metrics.normalized_pressure.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_book_pressure_trailing_window() {
+        let engine = BookPressureEngine::new(1_000, None, 10).unwrap();
+        
+        engine.on_snapshot(&book(0, 30.0, 10.0));      // +0.5
+        engine.on_snapshot(&book(1_000, 10.0, 30.0));  // -0.5
+        let metrics = engine.on_snapshot(&book(1_500, 10.0, 30.0)).unwrap();
+        
+        // Ventana (500, 1500]: 0.5s a +0.5 y 0.5s a -0.5
+        assert!(metrics.pressure.abs() < 1e-9);
+        
+        let metrics = engine.on_snapshot(&book(2_000, 10.0, 30.0)).unwrap();
+        // Ventana (1000, 2000]: 1s a -0.5
+        assert!((metrics.pressure + 0.5).abs() < 1e-9);
+        assert!((metrics.normalized_pressure + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_book_pressure_decay() {
+        let engine = BookPressureEngine::new(60_000, Some(1_000), 10).unwrap();
+        
+        engine.on_snapshot(&book(0, 30.0, 10.0));
+        engine.on_snapshot(&book(1_000, 10.0, 10.0));
+        let metrics = engine.on_snapshot(&book(2_000, 10.0, 10.0)).unwrap();
+        
+        // 0.5·1s decae a la mitad tras un half-life
+        assert!((metrics.decayed_pressure - 0.25).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,159 @@
+//! # Trade Classifier
+//! 
+//! Clasificación del lado agresor de un trade (Lee-Ready):
+//! 1. Lado explícito del feed si existe.
+//! 2. Quote vigente: precio en/above ask = BUY, en/below bid = SELL,
+//!    dentro del spread se compara con el mid.
+//! 3. Tick rule como fallback (uptick = BUY, downtick = SELL,
+//!    zero-tick hereda la última dirección).
+
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::types::{Trade, Quote};
+
+pub const SIDE_BUY: &str = "BUY";
+pub const SIDE_SELL: &str = "SELL";
+pub const SIDE_NA: &str = "NA";
+
+/// Clasificador de lado compartido por CVD y engines de order flow
+#[derive(Clone, Default)]
+pub struct TradeClassifier {
+    // Última quote por símbolo
+    quotes: Arc<DashMap<String, Quote>>,
+    // Último precio y última dirección de tick por símbolo
+    last_tick: Arc<DashMap<String, (f64, &'static str)>>,
+}
+
+impl TradeClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Registra la quote vigente del símbolo
+    pub fn on_quote(&self, quote: &Quote) {
+        if quote.bid > 0.0 && quote.ask > 0.0 && quote.bid <= quote.ask {
+            self.quotes.insert(quote.symbol.clone(), quote.clone());
+        }
+    }
+    
+    /// Clasifica el trade y actualiza el estado de la tick rule
+    pub fn classify(&self, trade: &Trade) -> &'static str {
+        let tick_side = self.update_tick(trade);
+        
+        // Si ya viene especificado el lado, usarlo
+        if let Some(side) = &trade.side {
+            if side.eq_ignore_ascii_case(SIDE_BUY) {
+                return SIDE_BUY;
+            }
+            if side.eq_ignore_ascii_case(SIDE_SELL) {
+                return SIDE_SELL;
+            }
+        }
+        
+        if let Some(quote) = self.quotes.get(&trade.symbol) {
+            if trade.price >= quote.ask {
+                return SIDE_BUY;
+            }
+            if trade.price <= quote.bid {
+                return SIDE_SELL;
+            }
+            let mid = quote.mid();
+            if trade.price > mid {
+                return SIDE_BUY;
+            }
+            if trade.price < mid {
+                return SIDE_SELL;
+            }
+        }
+        
+        tick_side
+    }
+    
+    /// Aplica la tick rule y guarda el precio como referencia del siguiente trade
+    fn update_tick(&self, trade: &Trade) -> &'static str {
+        let mut entry = self.last_tick.entry(trade.symbol.clone()).or_insert((trade.price, SIDE_NA));
+        let (last_price, last_side) = *entry;
+        let side = if trade.price > last_price {
+            SIDE_BUY
+        } else if trade.price < last_price {
+            SIDE_SELL
+        } else {
+            last_side
+        };
+        *entry = (trade.price, side);
+        side
+    }
+    
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.quotes.remove(symbol);
+        self.last_tick.remove(symbol);
+    }
+    
+    pub fn reset_all(&self) {
+        self.quotes.clear();
+        self.last_tick.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(price: f64, side: Option<&str>) -> Trade {
+        Trade {
+            ts: 1000,
+            price,
+            size: 1.0,
+            symbol: "AAPL".to_string(),
+            side: side.map(|s| s.to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_explicit_side_wins() {
+        let classifier = TradeClassifier::new();
+        classifier.on_quote(&Quote::new(1000, "AAPL".to_string(), 99.0, 10.0, 101.0, 10.0));
+        assert_eq!(classifier.classify(&trade(101.0, Some("sell"))), SIDE_SELL);
+    }
+
+    #[test]
+    fn test_quote_rule() {
+        let classifier = TradeClassifier::new();
+        classifier.on_quote(&Quote::new(1000, "AAPL".to_string(), 99.0, 10.0, 101.0, 10.0));
+        
+        assert_eq!(classifier.classify(&trade(101.0, None)), SIDE_BUY);
+        assert_eq!(classifier.classify(&trade(99.0, None)), SIDE_SELL);
+        assert_eq!(classifier.classify(&trade(100.5, None)), SIDE_BUY);
+        assert_eq!(classifier.classify(&trade(99.5, None)), SIDE_SELL);
+    }
+
+    #[test]
+    fn test_tick_rule_fallback() {
+        let classifier = TradeClassifier::new();
+        
+        // Sin historia ni quote no se puede clasificar
+        assert_eq!(classifier.classify(&trade(100.0, None)), SIDE_NA);
+        assert_eq!(classifier.classify(&trade(100.5, None)), SIDE_BUY);
+        // Zero-tick hereda la dirección previa
+        assert_eq!(classifier.classify(&trade(100.5, None)), SIDE_BUY);
+        assert_eq!(classifier.classify(&trade(100.0, None)), SIDE_SELL);
+    }
+
+    #[test]
+    fn test_trade_at_mid_uses_tick_rule() {
+        let classifier = TradeClassifier::new();
+        classifier.classify(&trade(99.5, None));
+        classifier.on_quote(&Quote::new(1000, "AAPL".to_string(), 99.0, 10.0, 101.0, 10.0));
+        
+        assert_eq!(classifier.classify(&trade(100.0, None)), SIDE_BUY);
+    }
+
+    #[test]
+    fn test_crossed_quote_ignored() {
+        let classifier = TradeClassifier::new();
+        classifier.on_quote(&Quote::new(1000, "AAPL".to_string(), 101.0, 10.0, 99.0, 10.0));
+        assert_eq!(classifier.classify(&trade(100.0, None)), SIDE_NA);
+    }
+}
@@ -0,0 +1,472 @@
+//! # Correlation Engine
+//! 
+//! Correlaciones y betas rolling entre los símbolos de un universo fijo.
+//! 
+//! Los precios (último trade o mid de quotes/snapshots, según `source`) se
+//! muestrean cada `interval_ms` sobre una rejilla de tiempo común: el primer
+//! evento de cualquier símbolo que cae en un intervalo nuevo cierra el
+//! anterior con los últimos precios conocidos de todos los símbolos (se
+//! arrastran los que no han cotizado). Los log-retornos entre muestras
+//! consecutivas forman la ventana de `window` filas de la que salen las
+//! matrices, que se exponen a Python como arrays numpy de N×N en el orden
+//! del universo.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use numpy::PyArray2;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::types::{BookSnapshot, Quote, Trade};
+use crate::errors::{validate_book, validate_quote, validate_trade, ProcessError, Rejections};
+use crate::py_macros::{py_class, py_methods};
+
+/// Precio muestreado de cada símbolo
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PriceSource {
+    /// Precio del último trade
+    #[default]
+    Last,
+    /// Mid de la última quote o snapshot
+    Mid,
+}
+
+impl PriceSource {
+    pub fn parse(source: &str) -> Option<Self> {
+        match source.to_ascii_lowercase().as_str() {
+            "last" => Some(Self::Last),
+            "mid" => Some(Self::Mid),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Last => "last",
+            Self::Mid => "mid",
+        }
+    }
+}
+
+/// Precios vigentes y ventana de retornos del universo
+#[derive(Clone, Debug, Default)]
+struct Sampler {
+    prices: Vec<Option<f64>>,
+    // Intervalo del evento más reciente
+    bucket: Option<u64>,
+    last_sample: Option<Vec<f64>>,
+    last_sample_ts: Option<u64>,
+    // Una fila de log-retornos por muestra, en el orden del universo
+    returns: VecDeque<Vec<f64>>,
+}
+
+impl Sampler {
+    /// Cierra el intervalo actual si `ts` cae en uno posterior y después
+    /// registra el precio del evento; true si se tomó una muestra
+    fn observe(&mut self, index: usize, ts: u64, price: Option<f64>, interval_ms: u64, window: usize) -> bool {
+        let bucket = ts / interval_ms;
+        let mut sampled = false;
+        match self.bucket {
+            Some(current) if bucket > current => {
+                sampled = self.sample(bucket * interval_ms, window);
+                self.bucket = Some(bucket);
+            }
+            Some(_) => {}
+            None => self.bucket = Some(bucket),
+        }
+        if let Some(price) = price {
+            self.prices[index] = Some(price);
+        }
+        sampled
+    }
+    
+    /// Muestra con los últimos precios; no hay muestra hasta que todos los
+    /// símbolos tienen precio
+    fn sample(&mut self, ts: u64, window: usize) -> bool {
+        let Some(prices) = self.prices.iter().copied().collect::<Option<Vec<f64>>>() else {
+            return false;
+        };
+        if let Some(prev) = &self.last_sample {
+            self.returns.push_back(prices.iter().zip(prev).map(|(p, q)| (p / q).ln()).collect());
+            if self.returns.len() > window {
+                self.returns.pop_front();
+            }
+        }
+        self.last_sample = Some(prices);
+        self.last_sample_ts = Some(ts);
+        true
+    }
+    
+    /// Matriz de covarianzas muestrales; None con menos de dos retornos
+    fn covariance(&self) -> Option<Vec<Vec<f64>>> {
+        let rows = self.returns.len();
+        if rows < 2 {
+            return None;
+        }
+        let n = self.prices.len();
+        let means: Vec<f64> = (0..n)
+            .map(|i| self.returns.iter().map(|r| r[i]).sum::<f64>() / rows as f64)
+            .collect();
+        let centered: Vec<Vec<f64>> = self.returns.iter()
+            .map(|row| row.iter().zip(&means).map(|(r, mean)| r - mean).collect())
+            .collect();
+        Some((0..n)
+            .map(|i| (0..n)
+                .map(|j| centered.iter().map(|c| c[i] * c[j]).sum::<f64>() / (rows - 1) as f64)
+                .collect())
+            .collect())
+    }
+}
+
+py_class! {
+/// Engine de correlaciones y betas entre símbolos
+#[pyclass]
+#[derive(Clone)]
+pub struct CorrelationEngine {
+    /// Universo de símbolos, en el orden de filas y columnas de las matrices
+    #[pyo3(get)]
+    pub symbols: Vec<String>,
+    #[pyo3(get)]
+    pub interval_ms: u64,
+    /// Retornos en la ventana
+    #[pyo3(get)]
+    pub window: usize,
+    source: PriceSource,
+    index: HashMap<String, usize>,
+    sampler: Arc<Mutex<Sampler>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl CorrelationEngine {
+    #[new]
+    #[pyo3(signature = (symbols, interval_ms=1_000, window=100, source="last"))]
+    pub fn new(symbols: Vec<String>, interval_ms: u64, window: usize, source: &str) -> Result<Self, ProcessError> {
+        let source = PriceSource::parse(source).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid price source: {} (last or mid)", source))
+        })?;
+        if interval_ms == 0 || window < 2 {
+            return Err(ProcessError::invalid(
+                "interval_ms must be > 0 and window >= 2"));
+        }
+        let index: HashMap<String, usize> = symbols.iter()
+            .enumerate()
+            .map(|(i, symbol)| (symbol.clone(), i))
+            .collect();
+        if symbols.len() < 2 || index.len() != symbols.len() {
+            return Err(ProcessError::invalid(
+                "symbols must contain at least 2 distinct symbols"));
+        }
+        let sampler = Sampler {
+            prices: vec![None; symbols.len()],
+            ..Sampler::default()
+        };
+        Ok(Self {
+            symbols,
+            interval_ms,
+            window,
+            source,
+            index,
+            sampler: Arc::new(Mutex::new(sampler)),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Precio muestreado: "last" o "mid"
+    #[getter]
+    pub fn source(&self) -> &'static str {
+        self.source.as_str()
+    }
+    
+    /// Procesa un trade; true si cerró un intervalo con muestra.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve False
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<bool, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Procesa una quote; true si cerró un intervalo con muestra.
+    /// En modo `strict` una quote inválida lanza su excepción
+    #[pyo3(name = "on_quote")]
+    pub fn py_on_quote(&self, quote: &Quote) -> Result<bool, ProcessError> {
+        self.rejections.resolve(self.try_on_quote(quote))
+    }
+    
+    /// Procesa un snapshot; true si cerró un intervalo con muestra.
+    /// En modo `strict` un libro inválido lanza su excepción
+    #[pyo3(name = "on_snapshot")]
+    pub fn py_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<bool, ProcessError> {
+        self.rejections.resolve(self.try_on_snapshot(snapshot))
+    }
+    
+    /// Matriz N×N de correlaciones de los log-retornos (NaN si no hay
+    /// suficientes muestras o algún símbolo no tiene varianza)
+    #[cfg(feature = "python")]
+    pub fn get_correlation_matrix<'py>(&self, py: Python<'py>) -> Result<Bound<'py, PyArray2<f64>>, ProcessError> {
+        PyArray2::from_vec2_bound(py, &self.correlation_matrix())
+            .map_err(|e| ProcessError::invalid(e.to_string()))
+    }
+    
+    /// Matriz N×N de betas: la celda [i][j] es la beta del símbolo i
+    /// respecto al símbolo j, cov(i, j) / var(j)
+    #[cfg(feature = "python")]
+    pub fn get_beta_matrix<'py>(&self, py: Python<'py>) -> Result<Bound<'py, PyArray2<f64>>, ProcessError> {
+        PyArray2::from_vec2_bound(py, &self.beta_matrix())
+            .map_err(|e| ProcessError::invalid(e.to_string()))
+    }
+    
+    /// Correlación entre dos símbolos del universo
+    pub fn get_correlation(&self, a: &str, b: &str) -> Option<f64> {
+        let (i, j) = (*self.index.get(a)?, *self.index.get(b)?);
+        Some(self.correlation_matrix()[i][j]).filter(|c| !c.is_nan())
+    }
+    
+    /// Beta de un símbolo respecto a otro usado como benchmark
+    pub fn get_beta(&self, symbol: &str, benchmark: &str) -> Option<f64> {
+        let (i, j) = (*self.index.get(symbol)?, *self.index.get(benchmark)?);
+        Some(self.beta_matrix()[i][j]).filter(|b| !b.is_nan())
+    }
+    
+    /// Retornos en la ventana
+    pub fn sample_count(&self) -> usize {
+        self.sampler.lock().returns.len()
+    }
+    
+    /// Inicio del intervalo de la última muestra
+    pub fn get_last_sample_ts(&self) -> Option<u64> {
+        self.sampler.lock().last_sample_ts
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Olvida el precio de un símbolo. Las filas de retornos incluyen a
+    /// todo el universo, así que la ventana se vacía
+    pub fn reset_symbol(&self, symbol: &str) {
+        if let Some(&i) = self.index.get(symbol) {
+            let mut sampler = self.sampler.lock();
+            sampler.prices[i] = None;
+            sampler.last_sample = None;
+            sampler.returns.clear();
+        }
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea precios, muestras y rechazos
+    pub fn reset_all(&self) {
+        *self.sampler.lock() = Sampler {
+            prices: vec![None; self.symbols.len()],
+            ..Sampler::default()
+        };
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("CorrelationEngine(symbols={}, interval_ms={}, window={}, source={}, samples={})",
+                self.symbols.len(), self.interval_ms, self.window, self.source.as_str(), self.sample_count())
+    }
+}
+}
+
+impl CorrelationEngine {
+    /// Símbolos del universo con precio
+    pub fn state_len(&self) -> usize {
+        self.sampler.lock().prices.iter().filter(|p| p.is_some()).count()
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> bool {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<bool, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        let price = (self.source == PriceSource::Last).then_some(trade.price);
+        Ok(self.observe(&trade.symbol, trade.ts, price))
+    }
+    
+    /// Procesa una quote; si no es válida se descarta y queda contada en las rejections
+    pub fn on_quote(&self, quote: &Quote) -> bool {
+        self.try_on_quote(quote).unwrap_or_default()
+    }
+    
+    /// Valida y procesa una quote; Err si no es válida (también queda contada)
+    pub fn try_on_quote(&self, quote: &Quote) -> Result<bool, ProcessError> {
+        self.rejections.check(validate_quote(quote))?;
+        self.rejections.check_timestamp(&quote.symbol, quote.ts)?;
+        let mid = (self.source == PriceSource::Mid).then_some((quote.bid + quote.ask) / 2.0);
+        Ok(self.observe(&quote.symbol, quote.ts, mid))
+    }
+    
+    /// Procesa un snapshot; si no es válido se descarta y queda contado en las rejections
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> bool {
+        self.try_on_snapshot(snapshot).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un snapshot; Err si no es válido (también queda contado)
+    pub fn try_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<bool, ProcessError> {
+        self.rejections.check(validate_book(snapshot))?;
+        self.rejections.check_timestamp(&snapshot.symbol, snapshot.ts)?;
+        let mid = (self.source == PriceSource::Mid)
+            .then(|| (snapshot.bids[0].price + snapshot.asks[0].price) / 2.0);
+        Ok(self.observe(&snapshot.symbol, snapshot.ts, mid))
+    }
+    
+    /// Los símbolos fuera del universo se ignoran
+    fn observe(&self, symbol: &str, ts: u64, price: Option<f64>) -> bool {
+        let Some(&index) = self.index.get(symbol) else { return false };
+        self.sampler.lock().observe(index, ts, price, self.interval_ms, self.window)
+    }
+    
+    /// Matriz de correlaciones; NaN donde no está definida
+    pub fn correlation_matrix(&self) -> Vec<Vec<f64>> {
+        let n = self.symbols.len();
+        let Some(cov) = self.sampler.lock().covariance() else {
+            return vec![vec![f64::NAN; n]; n];
+        };
+        (0..n)
+            .map(|i| (0..n)
+                .map(|j| {
+                    let den = (cov[i][i] * cov[j][j]).sqrt();
+                    if den > 0.0 { (cov[i][j] / den).clamp(-1.0, 1.0) } else { f64::NAN }
+                })
+                .collect())
+            .collect()
+    }
+    
+    /// Matriz de betas (fila = símbolo, columna = benchmark); NaN donde no
+    /// está definida
+    pub fn beta_matrix(&self) -> Vec<Vec<f64>> {
+        let n = self.symbols.len();
+        let Some(cov) = self.sampler.lock().covariance() else {
+            return vec![vec![f64::NAN; n]; n];
+        };
+        (0..n)
+            .map(|i| (0..n)
+                .map(|j| if cov[j][j] > 0.0 { cov[i][j] / cov[j][j] } else { f64::NAN })
+                .collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn symbols() -> Vec<String> {
+        vec!["BTC".to_string(), "ETH".to_string(), "SOL".to_string()]
+    }
+    
+    fn trade(ts: u64, symbol: &str, price: f64) -> Trade {
+        Trade::new(ts, price, 1.0, symbol.to_string())
+    }
+    
+    #[test]
+    fn test_correlation_engine_validation() {
+        assert!(CorrelationEngine::new(vec!["BTC".to_string()], 1_000, 100, "last").is_err());
+        assert!(CorrelationEngine::new(vec!["BTC".to_string(), "BTC".to_string()], 1_000, 100, "last").is_err());
+        assert!(CorrelationEngine::new(symbols(), 0, 100, "last").is_err());
+        assert!(CorrelationEngine::new(symbols(), 1_000, 1, "last").is_err());
+        assert!(CorrelationEngine::new(symbols(), 1_000, 100, "close").is_err());
+    }
+    
+    #[test]
+    fn test_sampling_on_interval_grid() {
+        let engine = CorrelationEngine::new(symbols(), 1_000, 100, "last").unwrap();
+        assert!(!engine.on_trade(&trade(100, "BTC", 100.0)));
+        assert!(!engine.on_trade(&trade(200, "ETH", 10.0)));
+        // SOL aún sin precio: el intervalo se cierra sin muestra
+        assert!(!engine.on_trade(&trade(1_100, "BTC", 101.0)));
+        assert!(!engine.on_trade(&trade(1_200, "SOL", 1.0)));
+        // Los símbolos fuera del universo no avanzan el reloj
+        assert!(!engine.on_trade(&trade(2_000, "DOGE", 0.1)));
+        assert!(engine.on_trade(&trade(2_050, "ETH", 10.5)));
+        assert_eq!(engine.get_last_sample_ts(), Some(2_000));
+        assert_eq!(engine.sample_count(), 0);
+        
+        // Segunda muestra con los precios arrastrados: un retorno
+        assert!(engine.on_trade(&trade(3_000, "BTC", 102.0)));
+        assert_eq!(engine.sample_count(), 1);
+        assert!(engine.correlation_matrix()[0][1].is_nan());
+        assert_eq!(engine.get_correlation("BTC", "ETH"), None);
+    }
+    
+    #[test]
+    fn test_correlation_and_beta() {
+        let engine = CorrelationEngine::new(symbols(), 1_000, 100, "last").unwrap();
+        // ETH se mueve el doble que BTC y SOL en sentido contrario
+        let moves = [0.01f64, -0.02, 0.015, 0.005, -0.01];
+        let (mut btc, mut eth, mut sol) = (100.0f64, 10.0f64, 1.0f64);
+        for (k, m) in std::iter::once(0.0).chain(moves).enumerate() {
+            btc *= m.exp();
+            eth *= (2.0 * m).exp();
+            sol *= (-m).exp();
+            let ts = k as u64 * 1_000;
+            engine.on_trade(&trade(ts, "BTC", btc));
+            engine.on_trade(&trade(ts, "ETH", eth));
+            engine.on_trade(&trade(ts, "SOL", sol));
+        }
+        engine.on_trade(&trade(10_000, "BTC", btc));
+        assert_eq!(engine.sample_count(), moves.len());
+        
+        assert!((engine.get_correlation("BTC", "ETH").unwrap() - 1.0).abs() < 1e-9);
+        assert!((engine.get_correlation("BTC", "SOL").unwrap() + 1.0).abs() < 1e-9);
+        assert!((engine.get_beta("ETH", "BTC").unwrap() - 2.0).abs() < 1e-9);
+        assert!((engine.get_beta("BTC", "ETH").unwrap() - 0.5).abs() < 1e-9);
+        assert!((engine.beta_matrix()[2][0] + 1.0).abs() < 1e-9);
+        assert_eq!(engine.get_beta("BTC", "DOGE"), None);
+        
+        engine.reset_symbol("SOL");
+        assert_eq!(engine.sample_count(), 0);
+    }
+    
+    #[test]
+    fn test_mid_source_ignores_trades() {
+        let engine = CorrelationEngine::new(symbols()[..2].to_vec(), 1_000, 100, "mid").unwrap();
+        engine.on_trade(&trade(0, "BTC", 100.0));
+        engine.on_quote(&Quote::new(0, "ETH".to_string(), 9.9, 1.0, 10.1, 1.0));
+        // BTC no tiene mid: sin muestra
+        assert!(!engine.on_quote(&Quote::new(1_000, "ETH".to_string(), 9.9, 1.0, 10.1, 1.0)));
+        engine.on_quote(&Quote::new(1_000, "BTC".to_string(), 99.0, 1.0, 101.0, 1.0));
+        assert!(engine.on_trade(&trade(2_000, "BTC", 100.0)));
+    }
+}
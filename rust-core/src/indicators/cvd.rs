@@ -1,106 +1,465 @@
 //! # CVD Engine
 //! 
 //! Cumulative Volume Delta calculator with ultra-low latency.
+//! 
+//! Los volúmenes se acumulan con suma compensada (Neumaier). Con
+//! `set_fixed_point` se acumulan en punto fijo (ver `crate::fixed`) y
+//! `get_cvd_decimal` devuelve el CVD exacto.
 
+#[cfg(feature = "python")]
+use numpy::PyReadonlyArray1;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::types::{Trade, CVDMetrics};
+#[cfg(feature = "python")]
+use crate::arrow_ffi;
+use crate::calendar::SessionCalendar;
+use crate::config::ConfigRegistry;
+#[cfg(feature = "python")]
+use crate::numpy_batch;
+use crate::types::{Trade, Quote, CVDMetrics};
+use crate::utils::{sorted_symbols, SessionSchedule};
+use crate::numeric::CvdCore;
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::fixed::{Fixed, FixedPointConfig, Precision};
+use super::classifier::{TradeClassifier, SIDE_NA};
+use crate::py_macros::{py_class, py_methods};
+
+/// Estado acumulado de CVD por símbolo
+#[derive(Clone, Debug, Default)]
+pub struct CVDState {
+    pub cvd: f64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub trade_count: u64,
+    // Último trade aplicado
+    pub last_size: f64,
+    pub last_ts: u64,
+    // Acumulados exactos en modo punto fijo
+    pub fixed_cvd: Fixed,
+    pub fixed_buy: Fixed,
+    pub fixed_sell: Fixed,
+    // Sumas compensadas de las que se leen cvd, buy_volume y sell_volume en f64
+    sums: CvdCore<f64>,
+}
 
+impl CVDState {
+    /// Aplica un trade clasificado al estado
+    pub fn apply(&mut self, side: &str, size: f64) {
+        // "NA" no cambia CVD
+        self.sums.apply(side, size);
+        self.cvd = self.sums.cvd();
+        self.buy_volume = self.sums.buy_volume();
+        self.sell_volume = self.sums.sell_volume();
+        self.trade_count += 1;
+    }
+    
+    /// Como `apply` pero sumando en punto fijo; los campos f64 se derivan
+    /// de los acumulados exactos
+    pub fn apply_fixed(&mut self, side: &str, size: Fixed) {
+        match side {
+            "BUY" => {
+                self.fixed_cvd = self.fixed_cvd + size;
+                self.fixed_buy = self.fixed_buy + size;
+            }
+            "SELL" => {
+                self.fixed_cvd = self.fixed_cvd - size;
+                self.fixed_sell = self.fixed_sell + size;
+            }
+            _ => {}
+        }
+        self.cvd = self.fixed_cvd.to_f64();
+        self.buy_volume = self.fixed_buy.to_f64();
+        self.sell_volume = self.fixed_sell.to_f64();
+        self.trade_count += 1;
+    }
+}
+
+/// Exchange asignado a trades que no lo informan
+pub const UNKNOWN_EXCHANGE: &str = "UNKNOWN";
+
+py_class! {
 /// Engine para calcular CVD (Cumulative Volume Delta)
 #[pyclass]
+#[derive(Clone)]
 pub struct CVDEngine {
-    // Estado por símbolo
-    cvd_by_symbol: Arc<DashMap<String, f64>>,
+    // Estado por símbolo (agregado de todos los exchanges)
+    state_by_symbol: Arc<DashMap<String, CVDState>>,
+    // Estado por (symbol, exchange)
+    state_by_exchange: Arc<DashMap<(String, String), CVDState>>,
     last_side_by_symbol: Arc<DashMap<String, String>>,
+    // Clasificación de lado (quotes + tick rule)
+    classifier: TradeClassifier,
+    // Reset automático por sesión (derivado del ts de los trades)
+    reset_schedule: Option<SessionSchedule>,
+    // Sesión vigente por símbolo, para detectar el cambio de sesión
+    session_by_symbol: Arc<DashMap<String, u64>>,
+    // Calendario de mercado; prevalece sobre reset_schedule
+    session_calendar: Option<SessionCalendar>,
+    // Configuración por símbolo; su calendario de sesión prevalece sobre
+    // session_calendar y reset_schedule
+    config: Option<ConfigRegistry>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+    // Precisión de punto fijo (None = f64)
+    fixed_point: FixedPointConfig,
+}
 }
 
+py_methods! {
 #[pymethods]
 impl CVDEngine {
     #[new]
     pub fn new() -> Self {
         Self {
-            cvd_by_symbol: Arc::new(DashMap::new()),
+            state_by_symbol: Arc::new(DashMap::new()),
+            state_by_exchange: Arc::new(DashMap::new()),
             last_side_by_symbol: Arc::new(DashMap::new()),
+            classifier: TradeClassifier::new(),
+            reset_schedule: None,
+            session_by_symbol: Arc::new(DashMap::new()),
+            session_calendar: None,
+            config: None,
+            rejections: Rejections::default(),
+            fixed_point: FixedPointConfig::default(),
         }
     }
     
-    /// Procesa un trade y calcula CVD
-    pub fn on_trade(&self, trade: &Trade) -> Option<CVDMetrics> {
-        // Validar datos
-        if trade.price <= 0.0 || trade.size <= 0.0 {
-            return None;
-        }
-        
-        // Determinar lado del trade
-        let side = self.determine_side(trade);
-        
-        // Actualizar CVD acumulado
-        let mut cvd = self.cvd_by_symbol.get(&trade.symbol)
-            .map(|entry| *entry.value())
-            .unwrap_or(0.0);
-        
-        match side.as_str() {
-            "BUY" => cvd += trade.size,
-            "SELL" => cvd -= trade.size,
-            _ => {} // "NA" - no cambia CVD
+    /// Configura el reset automático del CVD al inicio de cada sesión.
+    /// Por defecto diario a las 00:00 UTC; offset_ms desplaza la apertura.
+    #[pyo3(signature = (period_ms=86_400_000, offset_ms=0))]
+    pub fn set_reset_schedule(&mut self, period_ms: u64, offset_ms: u64) -> Result<(), ProcessError> {
+        let schedule = SessionSchedule::new(period_ms, offset_ms).ok_or_else(|| {
+            ProcessError::invalid("period_ms must be > 0 and offset_ms < period_ms")
+        })?;
+        self.reset_schedule = Some(schedule);
+        self.session_by_symbol.clear();
+        Ok(())
+    }
+    
+    /// Desactiva el reset automático
+    pub fn clear_reset_schedule(&mut self) {
+        self.reset_schedule = None;
+        self.session_by_symbol.clear();
+    }
+    
+    /// Calendario de reset activo como (period_ms, offset_ms)
+    #[getter]
+    pub fn reset_schedule(&self) -> Option<(u64, u64)> {
+        self.reset_schedule.map(|s| (s.period_ms, s.offset_ms))
+    }
+    
+    /// Resetea el CVD al abrir cada sesión del calendario de mercado (None lo
+    /// desactiva). Prevalece sobre `reset_schedule`
+    #[setter]
+    pub fn set_session_calendar(&mut self, calendar: Option<SessionCalendar>) {
+        self.session_calendar = calendar;
+        self.session_by_symbol.clear();
+    }
+    
+    /// Calendario de mercado activo
+    #[getter]
+    pub fn session_calendar(&self) -> Option<SessionCalendar> {
+        self.session_calendar.clone()
+    }
+    
+    /// Usa la configuración por símbolo: el calendario de sesión del registro
+    /// prevalece sobre `reset_schedule` para los símbolos que lo definen
+    #[setter]
+    pub fn set_config(&mut self, config: Option<ConfigRegistry>) {
+        self.config = config;
+        self.session_by_symbol.clear();
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade y calcula CVD. En modo `strict` un trade inválido lanza
+    /// InvalidPriceError / InvalidSizeError; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<CVDMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Acumula los volúmenes en punto fijo redondeando los tamaños a
+    /// `size_decimals` (global o solo para `symbol`). Resetea el estado afectado.
+    #[pyo3(signature = (price_decimals, size_decimals, symbol=None))]
+    pub fn set_fixed_point(&mut self, price_decimals: u32, size_decimals: u32, symbol: Option<&str>) -> Result<(), ProcessError> {
+        self.fixed_point.set(Some(Precision::new(price_decimals, size_decimals)?), symbol);
+        self.reset_fixed_state(symbol);
+        Ok(())
+    }
+    
+    /// Vuelve a acumular en f64 (global o solo para `symbol`); resetea el estado afectado
+    #[pyo3(signature = (symbol=None))]
+    pub fn disable_fixed_point(&mut self, symbol: Option<&str>) {
+        self.fixed_point.set(None, symbol);
+        self.reset_fixed_state(symbol);
+    }
+    
+    /// Precisión (price_decimals, size_decimals) del símbolo; None si acumula en f64
+    pub fn get_fixed_point(&self, symbol: &str) -> Option<(u32, u32)> {
+        self.fixed_point.precision(symbol).map(|p| p.as_tuple())
+    }
+    
+    /// CVD exacto como `decimal.Decimal`; None si no hay estado o el símbolo
+    /// acumula en f64
+    #[cfg(feature = "python")]
+    pub fn get_cvd_decimal(&self, py: Python<'_>, symbol: &str) -> PyResult<Option<PyObject>> {
+        if self.fixed_point.precision(symbol).is_none() {
+            return Ok(None);
         }
-        
-        // Guardar estado
-        self.cvd_by_symbol.insert(trade.symbol.clone(), cvd);
-        self.last_side_by_symbol.insert(trade.symbol.clone(), side.clone());
-        
-        Some(CVDMetrics {
-            cvd,
-            last_side: side,
-            last_size: trade.size,
-            timestamp: trade.ts,
-        })
+        let cvd = self.state_by_symbol.get(symbol).map(|entry| entry.fixed_cvd);
+        cvd.map(|cvd| cvd.to_py_decimal(py)).transpose()
     }
     
     /// Obtiene el CVD actual para un símbolo
     pub fn get_cvd(&self, symbol: &str) -> Option<f64> {
-        self.cvd_by_symbol.get(symbol).map(|entry| *entry.value())
+        self.state_by_symbol.get(symbol).map(|entry| entry.cvd)
+    }
+    
+    /// Métricas completas del símbolo (como las del último on_trade): CVD,
+    /// volúmenes, número de trades y lado, tamaño y ts del último trade
+    pub fn get_metrics(&self, symbol: &str) -> Option<CVDMetrics> {
+        let state = self.state_by_symbol.get(symbol)?;
+        let side = self.last_side_by_symbol.get(symbol)
+            .map(|side| side.clone())
+            .unwrap_or_else(|| SIDE_NA.to_string());
+        Some(Self::metrics(&state, side))
+    }
+    
+    /// Símbolos con estado, ordenados
+    pub fn symbols(&self) -> Vec<String> {
+        sorted_symbols(&self.state_by_symbol)
+    }
+    
+    /// Métricas actuales de todos los símbolos (symbol -> CVDMetrics)
+    pub fn get_all(&self) -> HashMap<String, CVDMetrics> {
+        self.symbols().into_iter()
+            .filter_map(|symbol| self.get_metrics(&symbol).map(|m| (symbol, m)))
+            .collect()
+    }
+    
+    /// CVD del símbolo desglosado por exchange ("UNKNOWN" si el trade no lo informa)
+    pub fn get_cvd_by_exchange(&self, symbol: &str) -> HashMap<String, f64> {
+        self.state_by_exchange.iter()
+            .filter(|entry| entry.key().0 == symbol)
+            .map(|entry| (entry.key().1.clone(), entry.value().cvd))
+            .collect()
+    }
+    
+    /// CVD agregado de todos los exchanges del símbolo
+    pub fn get_cvd_aggregate(&self, symbol: &str) -> Option<f64> {
+        self.get_cvd(symbol)
     }
     
     /// Resetea el CVD para un símbolo
     pub fn reset_symbol(&self, symbol: &str) {
-        self.cvd_by_symbol.remove(symbol);
+        self.state_by_symbol.remove(symbol);
+        self.state_by_exchange.retain(|key, _| key.0 != symbol);
         self.last_side_by_symbol.remove(symbol);
+        self.session_by_symbol.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
     }
     
     /// Resetea todos los símbolos
     pub fn reset_all(&self) {
-        self.cvd_by_symbol.clear();
+        self.state_by_symbol.clear();
+        self.state_by_exchange.clear();
         self.last_side_by_symbol.clear();
+        self.session_by_symbol.clear();
+        self.classifier.reset_all();
+        self.rejections.reset_all();
     }
     
+    /// Procesa arrays numpy de un símbolo; devuelve dict de arrays (cvd, buy_volume,
+    /// sell_volume, side) con una fila por trade
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (symbol, ts, price, size, side=None))]
+    pub fn on_trade_numpy<'py>(&self, py: Python<'py>, symbol: &str, ts: PyReadonlyArray1<'py, i64>,
+                               price: PyReadonlyArray1<'py, f64>, size: PyReadonlyArray1<'py, f64>,
+                               side: Option<PyReadonlyArray1<'py, i8>>) -> PyResult<Bound<'py, PyDict>> {
+        let trades = numpy_batch::trades_from_numpy(symbol, ts, price, size, side)?;
+        let results: Vec<_> = py.allow_threads(|| trades.iter().map(|t| self.on_trade(t)).collect());
+        numpy_batch::to_dict(py, numpy_batch::cvd_columns(&results))
+    }
+    
+    /// Procesa un `pyarrow.RecordBatch` de trades en Rust y devuelve otro
+    /// RecordBatch con una fila por trade (nulos en trades descartados)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (batch, columns=None, symbol=None))]
+    pub fn on_trade_arrow(&self, py: Python<'_>, batch: &Bound<'_, PyAny>,
+                          columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> PyResult<PyObject> {
+        let trades = arrow_ffi::trades_from_arrow(batch, columns, symbol)?;
+        let results: Vec<_> = py.allow_threads(|| trades.iter().map(|t| self.on_trade(t)).collect());
+        arrow_ffi::export_record_batch(py, &arrow_ffi::cvd_frame(&trades, &results)?)
+    }
+    
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("CVDEngine(symbols={})", self.cvd_by_symbol.len())
+        format!("CVDEngine(symbols={})", self.state_by_symbol.len())
+    }
+}
+}
+
+impl Default for CVDEngine {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl CVDEngine {
-    /// Determina el lado del trade basado en el precio y contexto
-    pub fn determine_side(&self, trade: &Trade) -> String {
-        // Si ya viene especificado el lado, usarlo
-        if let Some(side) = &trade.side {
-            let side_upper = side.to_uppercase();
-            if side_upper == "BUY" || side_upper == "SELL" {
-                return side_upper;
+    /// Crea el engine con un clasificador compartido (p. ej. el de un
+    /// `TradeEnricher`), de modo que el lado de cada trade coincida entre engines
+    pub fn with_classifier(classifier: TradeClassifier) -> Self {
+        Self { classifier, ..Self::new() }
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<CVDMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<CVDMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade y calcula CVD
+    fn process_trade(&self, trade: &Trade) -> Option<CVDMetrics> {
+        // Determinar lado del trade
+        let side = self.determine_side(trade);
+        
+        // Reset si el trade abre una nueva sesión
+        self.roll_session(trade);
+        
+        // Actualizar CVD acumulado (en punto fijo si el símbolo tiene precisión)
+        let precision = self.fixed_point.precision(&trade.symbol);
+        let apply = |state: &mut CVDState| match precision {
+            Some(precision) => state.apply_fixed(&side, precision.size(trade.size)),
+            None => state.apply(&side, trade.size),
+        };
+        let state = {
+            let exchange = trade.exchange.as_deref().unwrap_or(UNKNOWN_EXCHANGE);
+            apply(&mut self.state_by_exchange
+                .entry((trade.symbol.clone(), exchange.to_string()))
+                .or_default());
+            
+            let mut state = self.state_by_symbol.entry(trade.symbol.clone()).or_default();
+            apply(&mut state);
+            state.last_size = trade.size;
+            state.last_ts = trade.ts;
+            state.clone()
+        };
+        
+        // Guardar estado
+        self.last_side_by_symbol.insert(trade.symbol.clone(), side.clone());
+        
+        Some(Self::metrics(&state, side))
+    }
+    
+    fn metrics(state: &CVDState, last_side: String) -> CVDMetrics {
+        CVDMetrics {
+            cvd: state.cvd,
+            last_side,
+            last_size: state.last_size,
+            timestamp: state.last_ts,
+            buy_volume: state.buy_volume,
+            sell_volume: state.sell_volume,
+            trade_count: state.trade_count,
+        }
+    }
+    
+    /// Limpia los acumulados que cambian de representación
+    fn reset_fixed_state(&self, symbol: Option<&str>) {
+        match symbol {
+            Some(symbol) => {
+                self.state_by_symbol.remove(symbol);
+                self.state_by_exchange.retain(|key, _| key.0 != symbol);
+            }
+            None => {
+                self.state_by_symbol.clear();
+                self.state_by_exchange.clear();
             }
         }
+    }
+    
+    /// Clasificador del engine, para compartirlo con otros engines de order flow
+    pub fn classifier(&self) -> TradeClassifier {
+        self.classifier.clone()
+    }
+    
+    /// Determina el lado del trade: lado explícito, quote vigente o tick rule.
+    /// Actualiza el estado de la tick rule del símbolo.
+    pub fn determine_side(&self, trade: &Trade) -> String {
+        self.classifier.classify(trade).to_string()
+    }
+    
+    /// Pone a cero el CVD del símbolo cuando el trade pertenece a una sesión posterior
+    /// La sesión se identifica por el calendario del registro, el de mercado
+    /// (por su apertura) o `reset_schedule`, en ese orden
+    fn roll_session(&self, trade: &Trade) {
+        let session = match self.config.as_ref().and_then(|c| c.session(&trade.symbol)) {
+            Some(schedule) => schedule.session_id(trade.ts),
+            None => match (&self.session_calendar, self.reset_schedule) {
+                (Some(calendar), _) => match calendar.session_start(trade.ts) {
+                    Some(open) => open,
+                    None => return,
+                },
+                (None, Some(schedule)) => schedule.session_id(trade.ts),
+                (None, None) => return,
+            },
+        };
         
-        // Por ahora, usar lógica simple
-        // En una implementación real, aquí usarías datos de quotes
-        // para determinar si el trade fue agresivo o pasivo
-        
-        // Lógica temporal: alternar entre BUY y SELL
-        // Esto es solo para testing - en producción usarías quotes reales
-        if trade.price as u64 % 2 == 0 {
-            "BUY".to_string()
-        } else {
-            "SELL".to_string()
+        let mut current = self.session_by_symbol.entry(trade.symbol.clone()).or_insert(session);
+        if session > *current {
+            *current = session;
+            self.state_by_symbol.insert(trade.symbol.clone(), CVDState::default());
+            self.state_by_exchange.retain(|key, _| key.0 != trade.symbol);
         }
     }
 }
@@ -111,6 +470,8 @@ impl CVDEngine {
 mod tests {
     use super::*;
     use crate::types::Trade;
+    #[cfg(feature = "python")]
+    use crate::utils::with_py;
 
     #[test]
     fn test_cvd_engine_creation() {
@@ -128,6 +489,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         let result = engine.on_trade(&trade);
@@ -149,6 +511,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade1);
@@ -161,6 +524,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: Some("SELL".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         let result = engine.on_trade(&trade2);
@@ -182,6 +546,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         assert!(engine.on_trade(&trade).is_none());
@@ -198,11 +563,32 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         assert!(engine.on_trade(&trade).is_none());
     }
 
+    #[test]
+    fn test_cvd_rejections_and_try_on_trade() {
+        let engine = CVDEngine::new();
+        
+        let bad_price = Trade::new(1000, f64::NAN, 100.0, "AAPL".to_string());
+        let bad_size = Trade::new(2000, 150.0, -1.0, "AAPL".to_string());
+        
+        let err = engine.try_on_trade(&bad_price).unwrap_err();
+        assert!(matches!(err, ProcessError::InvalidPrice { .. }));
+        assert!(engine.on_trade(&bad_size).is_none());
+        assert!(engine.get_cvd("AAPL").is_none());
+        
+        let rejections = engine.get_rejections(Some("AAPL"));
+        assert_eq!(rejections.get("invalid_price"), Some(&1));
+        assert_eq!(rejections.get("invalid_size"), Some(&1));
+        
+        engine.reset_symbol("AAPL");
+        assert!(engine.get_rejections(None).is_empty());
+    }
+
     #[test]
     fn test_cvd_multiple_symbols() {
         let engine = CVDEngine::new();
@@ -214,6 +600,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         let trade2 = Trade {
@@ -223,6 +610,7 @@ mod tests {
             symbol: "BTCUSDT".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade1);
@@ -244,6 +632,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade);
@@ -264,6 +653,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         let trade2 = Trade {
@@ -273,6 +663,7 @@ mod tests {
             symbol: "BTCUSDT".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade1);
@@ -295,6 +686,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: Some("BUY".to_string()),
             exchange: None,
+            trade_id: None,
         };
         
         let side = engine.determine_side(&trade);
@@ -312,6 +704,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         let trade2 = Trade {
@@ -321,13 +714,250 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
-        // Side alterna basado en precio
+        // Tick rule: sin referencia previa no hay lado; uptick = BUY
         let side1 = engine.determine_side(&trade1);
         let side2 = engine.determine_side(&trade2);
         
-        assert!(side1 == "BUY" || side1 == "SELL");
-        assert!(side2 == "BUY" || side2 == "SELL");
+        assert_eq!(side1, "NA");
+        assert_eq!(side2, "BUY");
+    }
+
+    #[test]
+    fn test_cvd_uses_quotes_for_side() {
+        let engine = CVDEngine::new();
+        engine.on_quote(&Quote::new(1000, "AAPL".to_string(), 149.99, 100.0, 150.01, 100.0));
+        
+        let at_ask = Trade {
+            ts: 1001,
+            price: 150.01,
+            size: 10.0,
+            symbol: "AAPL".to_string(),
+            side: None,
+            exchange: None,
+            trade_id: None,
+        };
+        let at_bid = Trade {
+            ts: 1002,
+            price: 149.99,
+            size: 4.0,
+            symbol: "AAPL".to_string(),
+            side: None,
+            exchange: None,
+            trade_id: None,
+        };
+        
+        assert_eq!(engine.on_trade(&at_ask).unwrap().last_side, "BUY");
+        let metrics = engine.on_trade(&at_bid).unwrap();
+        assert_eq!(metrics.last_side, "SELL");
+        assert_eq!(metrics.cvd, 6.0);
+    }
+
+    #[test]
+    fn test_cvd_reset_schedule() {
+        let mut engine = CVDEngine::new();
+        assert!(engine.set_reset_schedule(0, 0).is_err());
+        engine.set_reset_schedule(86_400_000, 0).unwrap();
+        assert_eq!(engine.reset_schedule(), Some((86_400_000, 0)));
+        
+        let trade = |ts: u64, side: &str| Trade {
+            ts,
+            price: 150.0,
+            size: 10.0,
+            symbol: "AAPL".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        };
+        
+        engine.on_trade(&trade(1_000, "BUY"));
+        engine.on_trade(&trade(2_000, "BUY"));
+        assert_eq!(engine.get_cvd("AAPL"), Some(20.0));
+        
+        // Primer trade del día siguiente arranca desde cero
+        let metrics = engine.on_trade(&trade(86_400_000 + 1, "SELL")).unwrap();
+        assert_eq!(metrics.cvd, -10.0);
+        
+        // Trade tardío de la sesión anterior no vuelve a resetear
+        engine.on_trade(&trade(3_000, "BUY"));
+        assert_eq!(engine.get_cvd("AAPL"), Some(0.0));
+        
+        engine.clear_reset_schedule();
+        assert_eq!(engine.reset_schedule(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cvd_session_calendar() {
+        let mut engine = CVDEngine::new();
+        engine.set_session_calendar(Some(SessionCalendar::preset("nyse").unwrap()));
+        
+        // Lunes 2024-01-08 00:00 UTC; NYSE abre a las 14:30 UTC en invierno
+        let monday = 1_704_672_000_000u64;
+        let hour = 3_600_000u64;
+        let trade = |ts: u64, side: &str| Trade {
+            ts,
+            price: 150.0,
+            size: 10.0,
+            symbol: "AAPL".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        };
+        
+        engine.on_trade(&trade(monday + 15 * hour, "BUY"));
+        // After-hours del lunes sigue en la misma sesión
+        engine.on_trade(&trade(monday + 22 * hour, "BUY"));
+        assert_eq!(engine.get_cvd("AAPL"), Some(20.0));
+        
+        // Apertura del martes
+        let metrics = engine.on_trade(&trade(monday + 24 * hour + 15 * hour, "SELL")).unwrap();
+        assert_eq!(metrics.cvd, -10.0);
+        
+        // El calendario prevalece sobre el reset periódico
+        engine.set_reset_schedule(hour, 0).unwrap();
+        engine.on_trade(&trade(monday + 24 * hour + 17 * hour, "SELL"));
+        assert_eq!(engine.get_cvd("AAPL"), Some(-20.0));
+        
+        engine.set_session_calendar(None);
+        assert!(engine.session_calendar().is_none());
+    }
+
+    #[test]
+    fn test_cvd_buy_sell_components() {
+        let engine = CVDEngine::new();
+        let trade = |size: f64, side: &str| Trade {
+            ts: 1000,
+            price: 150.0,
+            size,
+            symbol: "AAPL".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        };
+        
+        engine.on_trade(&trade(100.0, "BUY"));
+        engine.on_trade(&trade(30.0, "SELL"));
+        let metrics = engine.on_trade(&trade(20.0, "BUY")).unwrap();
+        
+        assert_eq!(metrics.buy_volume, 120.0);
+        assert_eq!(metrics.sell_volume, 30.0);
+        assert_eq!(metrics.trade_count, 3);
+        assert_eq!(metrics.cvd, metrics.buy_volume - metrics.sell_volume);
+    }
+
+    #[test]
+    fn test_cvd_by_exchange_and_aggregate() {
+        let engine = CVDEngine::new();
+        let trade = |size: f64, side: &str, exchange: Option<&str>| Trade {
+            ts: 1000,
+            price: 3000.0,
+            size,
+            symbol: "BTCUSDT".to_string(),
+            side: Some(side.to_string()),
+            exchange: exchange.map(|e| e.to_string()),
+            trade_id: None,
+        };
+        
+        engine.on_trade(&trade(5.0, "BUY", Some("BINANCE")));
+        engine.on_trade(&trade(2.0, "SELL", Some("COINBASE")));
+        engine.on_trade(&trade(1.0, "BUY", Some("BINANCE")));
+        engine.on_trade(&trade(0.5, "SELL", None));
+        
+        let by_exchange = engine.get_cvd_by_exchange("BTCUSDT");
+        assert_eq!(by_exchange.len(), 3);
+        assert_eq!(by_exchange["BINANCE"], 6.0);
+        assert_eq!(by_exchange["COINBASE"], -2.0);
+        assert_eq!(by_exchange[UNKNOWN_EXCHANGE], -0.5);
+        assert_eq!(engine.get_cvd_aggregate("BTCUSDT"), Some(3.5));
+        
+        engine.reset_symbol("BTCUSDT");
+        assert!(engine.get_cvd_by_exchange("BTCUSDT").is_empty());
+    }
+
+    #[test]
+    fn test_cvd_compensated_totals() {
+        let engine = CVDEngine::new();
+        let mut naive = 0.0f64;
+        for i in 0..100_000 {
+            let mut trade = Trade::new(i, 30_000.0, 0.001, "BTCUSDT".to_string());
+            trade.side = Some("BUY".to_string());
+            naive += 0.001;
+            engine.on_trade(&trade);
+        }
+        assert!((naive - 100.0).abs() > 1e-10);
+        assert_eq!(engine.get_cvd("BTCUSDT"), Some(100.0));
+    }
+
+    #[test]
+    fn test_cvd_fixed_point_is_exact() {
+        let mut engine = CVDEngine::new();
+        engine.set_fixed_point(2, 8, None).unwrap();
+        assert_eq!(engine.get_fixed_point("BTCUSDT"), Some((2, 8)));
+        
+        let mut naive = 0.0;
+        for i in 0..1_000 {
+            let (side, size) = if i % 2 == 0 { ("BUY", 0.1) } else { ("SELL", 0.03) };
+            let mut trade = Trade::new(1000 + i, 30_000.0, size, "BTCUSDT".to_string());
+            trade.side = Some(side.to_string());
+            naive += if side == "BUY" { size } else { -size };
+            engine.on_trade(&trade);
+        }
+        // 500 · 0.1 - 500 · 0.03 = 35 exacto; la suma en f64 se desvía
+        assert_ne!(naive, 35.0);
+        assert_eq!(engine.get_cvd("BTCUSDT"), Some(35.0));
+        #[cfg(feature = "python")]
+        {
+            let decimal = with_py(|py| {
+                engine.get_cvd_decimal(py, "BTCUSDT").unwrap().map(|d| d.bind(py).str().unwrap().to_string())
+            });
+            assert_eq!(decimal.as_deref(), Some("35.00000000"));
+        }
+        
+        // Volver a f64 limpia el estado del símbolo
+        engine.disable_fixed_point(Some("BTCUSDT"));
+        assert_eq!(engine.get_cvd("BTCUSDT"), None);
+        assert_eq!(engine.get_fixed_point("BTCUSDT"), None);
+        #[cfg(feature = "python")]
+        assert!(with_py(|py| engine.get_cvd_decimal(py, "BTCUSDT").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_cvd_get_metrics() {
+        let engine = CVDEngine::new();
+        assert!(engine.get_metrics("AAPL").is_none());
+        let trade = |ts: u64, size: f64, side: &str| Trade {
+            ts,
+            price: 150.0,
+            size,
+            symbol: "AAPL".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        };
+        
+        engine.on_trade(&trade(1_000, 100.0, "BUY"));
+        let last = engine.on_trade(&trade(2_000, 30.0, "SELL")).unwrap();
+        let metrics = engine.get_metrics("AAPL").unwrap();
+        assert_eq!((metrics.cvd, metrics.buy_volume, metrics.sell_volume), (70.0, 100.0, 30.0));
+        assert_eq!((metrics.last_side.as_str(), metrics.last_size, metrics.timestamp), ("SELL", 30.0, 2_000));
+        assert_eq!(metrics.trade_count, last.trade_count);
+    }
+
+    #[test]
+    fn test_cvd_symbols_and_get_all() {
+        let engine = CVDEngine::new();
+        assert!(engine.symbols().is_empty());
+        for (symbol, side) in [("MSFT", "SELL"), ("AAPL", "BUY")] {
+            let trade = Trade { side: Some(side.to_string()), ..Trade::new(1_000, 100.0, 5.0, symbol.to_string()) };
+            engine.on_trade(&trade);
+        }
+        assert_eq!(engine.symbols(), vec!["AAPL", "MSFT"]);
+        let all = engine.get_all();
+        assert_eq!((all["AAPL"].cvd, all["MSFT"].cvd), (5.0, -5.0));
+        
+        engine.reset_symbol("MSFT");
+        assert_eq!(engine.symbols(), vec!["AAPL"]);
+    }
+}
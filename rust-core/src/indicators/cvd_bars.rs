@@ -0,0 +1,246 @@
+//! # CVD Bar Engine
+//! 
+//! Agrega el delta de volumen en barras OHLC por timeframe (delta bars).
+//! Emite la barra completada cuando el bucket temporal cambia.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::types::{Trade, Quote, CVDBar};
+use crate::utils::{calculate_bucket, parse_timeframe_ms, sorted_symbols};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use super::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+use crate::py_macros::{py_class, py_methods};
+
+py_class! {
+/// Engine de barras de CVD por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct CVDBarEngine {
+    #[pyo3(get)]
+    pub tf: String,
+    #[pyo3(get)]
+    pub bucket_ms: u64,
+    // Barra en curso por símbolo
+    bars: Arc<DashMap<String, CVDBar>>,
+    classifier: TradeClassifier,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl CVDBarEngine {
+    #[new]
+    pub fn new(tf: &str) -> Result<Self, ProcessError> {
+        let bucket_ms = parse_timeframe_ms(tf).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid timeframe: {}", tf))
+        })?;
+        Ok(Self {
+            tf: tf.to_string(),
+            bucket_ms,
+            bars: Arc::new(DashMap::new()),
+            classifier: TradeClassifier::new(),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade; devuelve la barra anterior si el bucket ha cambiado.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<CVDBar>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Obtiene la barra en curso (aún no cerrada) de un símbolo
+    pub fn get_current_bar(&self, symbol: &str) -> Option<CVDBar> {
+        self.bars.get(symbol).map(|bar| bar.clone())
+    }
+    
+    /// Símbolos con estado, ordenados
+    pub fn symbols(&self) -> Vec<String> {
+        sorted_symbols(&self.bars)
+    }
+    
+    /// Barras en curso de todos los símbolos (symbol -> CVDBar)
+    pub fn get_all(&self) -> HashMap<String, CVDBar> {
+        self.symbols().into_iter()
+            .filter_map(|symbol| self.get_current_bar(&symbol).map(|m| (symbol, m)))
+            .collect()
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.bars.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.bars.clear();
+        self.classifier.reset_all();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("CVDBarEngine(tf={}, symbols={})", self.tf, self.bars.len())
+    }
+}
+}
+
+impl CVDBarEngine {
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<CVDBar> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<CVDBar>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade; devuelve la barra anterior si el bucket ha cambiado
+    fn process_trade(&self, trade: &Trade) -> Option<CVDBar> {
+        let side = self.classifier.classify(trade);
+        let bucket_ts = calculate_bucket(trade.ts, self.bucket_ms);
+        
+        let mut completed = None;
+        let mut bar = self.bars.entry(trade.symbol.clone()).or_insert_with(|| {
+            CVDBar::new(trade.symbol.clone(), self.tf.clone(), bucket_ts, 0.0)
+        });
+        
+        // Trades tardíos se agregan a la barra en curso
+        if bucket_ts > bar.bucket_ts {
+            let next = CVDBar::new(trade.symbol.clone(), self.tf.clone(), bucket_ts, bar.close);
+            completed = Some(std::mem::replace(&mut *bar, next));
+        }
+        
+        match side {
+            SIDE_BUY => bar.buy_volume += trade.size,
+            SIDE_SELL => bar.sell_volume += trade.size,
+            _ => return completed, // "NA" - no cambia el delta
+        }
+        bar.delta = bar.buy_volume - bar.sell_volume;
+        bar.close = bar.open + bar.delta;
+        bar.high = bar.high.max(bar.close);
+        bar.low = bar.low.min(bar.close);
+        
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, size: f64, side: &str) -> Trade {
+        Trade {
+            ts,
+            price: 100.0,
+            size,
+            symbol: "AAPL".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_cvd_bar_engine_invalid_tf() {
+        assert!(CVDBarEngine::new("abc").is_err());
+        assert_eq!(CVDBarEngine::new("5m").unwrap().bucket_ms, 300_000);
+    }
+
+    #[test]
+    fn test_cvd_bar_ohlc_within_bucket() {
+        let engine = CVDBarEngine::new("1s").unwrap();
+        
+        assert!(engine.on_trade(&trade(1000, 10.0, "BUY")).is_none());
+        assert!(engine.on_trade(&trade(1200, 15.0, "SELL")).is_none());
+        assert!(engine.on_trade(&trade(1500, 3.0, "BUY")).is_none());
+        
+        let bar = engine.get_current_bar("AAPL").unwrap();
+        assert_eq!(bar.bucket_ts, 1000);
+        assert_eq!(bar.open, 0.0);
+        assert_eq!(bar.high, 10.0);
+        assert_eq!(bar.low, -5.0);
+        assert_eq!(bar.close, -2.0);
+        assert_eq!(bar.buy_volume, 13.0);
+        assert_eq!(bar.sell_volume, 15.0);
+        assert_eq!(bar.delta, -2.0);
+    }
+
+    #[test]
+    fn test_cvd_bar_rollover_emits_completed_bar() {
+        let engine = CVDBarEngine::new("1s").unwrap();
+        
+        engine.on_trade(&trade(1000, 10.0, "BUY"));
+        let completed = engine.on_trade(&trade(2100, 4.0, "SELL")).unwrap();
+        
+        assert_eq!(completed.bucket_ts, 1000);
+        assert_eq!(completed.close, 10.0);
+        
+        // La nueva barra abre en el cierre de la anterior
+        let current = engine.get_current_bar("AAPL").unwrap();
+        assert_eq!(current.bucket_ts, 2000);
+        assert_eq!(current.open, 10.0);
+        assert_eq!(current.close, 6.0);
+        assert_eq!(current.delta, -4.0);
+    }
+
+    #[test]
+    fn test_cvd_bar_reset() {
+        let engine = CVDBarEngine::new("1m").unwrap();
+        engine.on_trade(&trade(1000, 10.0, "BUY"));
+        
+        engine.reset_symbol("AAPL");
+        assert!(engine.get_current_bar("AAPL").is_none());
+    }
+}
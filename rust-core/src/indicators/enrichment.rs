@@ -0,0 +1,316 @@
+//! # Trade Enrichment
+//! 
+//! Etapa de enriquecimiento: con los streams de trades, quotes y snapshots
+//! sincronizados (en orden de ts), anota cada trade con el contexto del
+//! libro vigente y devuelve un `EnrichedTrade`:
+//! - lado agresor según el libro (Lee-Ready sobre el top-of-book)
+//! - distancia al mid en bps
+//! - niveles del lado contrario que atraviesa el precio (barridos)
+//! 
+//! El lado lo decide un `TradeClassifier` que se puede compartir con
+//! `CVDEngine`, `FootprintEngine` y `LiquidityEngine` (`with_classifier`)
+//! para que todos clasifiquen igual; alternativamente
+//! `EnrichedTrade.to_trade()` devuelve el trade con el lado explícito.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::types::{BookSnapshot, EnrichedTrade, Level, Quote, Trade};
+use crate::errors::{validate_book, validate_quote, validate_trade, ProcessError, Rejections};
+use super::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+use crate::utils::count_symbols;
+use crate::py_macros::{py_class, py_methods};
+
+/// Top-of-book vigente: (ts, bid, ask)
+type Top = (u64, f64, f64);
+
+py_class! {
+/// Enriquecedor de trades con el contexto del libro por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct TradeEnricher {
+    classifier: TradeClassifier,
+    // Último top-of-book (de quote o de snapshot) y último libro por símbolo
+    tops: Arc<DashMap<String, Top>>,
+    books: Arc<DashMap<String, BookSnapshot>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl TradeEnricher {
+    #[new]
+    pub fn new() -> Self {
+        Self::with_classifier(TradeClassifier::new())
+    }
+    
+    /// Registra la quote vigente. En modo `strict` una quote inválida lanza
+    /// su excepción
+    #[pyo3(name = "on_quote")]
+    pub fn py_on_quote(&self, quote: &Quote) -> Result<(), ProcessError> {
+        self.rejections.resolve(self.try_on_quote(quote))
+    }
+    
+    /// Registra el libro vigente. En modo `strict` un libro inválido lanza
+    /// su excepción
+    #[pyo3(name = "on_snapshot")]
+    pub fn py_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<(), ProcessError> {
+        self.rejections.resolve(self.try_on_snapshot(snapshot))
+    }
+    
+    /// Anota el trade con el contexto del libro vigente.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "enrich")]
+    pub fn py_enrich(&self, trade: &Trade) -> Result<Option<EnrichedTrade>, ProcessError> {
+        self.rejections.resolve(self.try_enrich(trade))
+    }
+    
+    /// Anota una lista de trades en orden; los inválidos se descartan
+    pub fn enrich_batch(&self, trades: Vec<Trade>) -> Vec<EnrichedTrade> {
+        trades.iter().filter_map(|t| self.enrich(t)).collect()
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.tops.remove(symbol);
+        self.books.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.tops.clear();
+        self.books.clear();
+        self.classifier.reset_all();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("TradeEnricher(symbols={})", self.tops.len())
+    }
+}
+}
+
+impl TradeEnricher {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.tops.iter().map(|e| e.key().clone())
+            .chain(self.books.iter().map(|e| e.key().clone())))
+    }
+    
+    /// Crea el enriquecedor con un clasificador compartido con otros engines
+    pub fn with_classifier(classifier: TradeClassifier) -> Self {
+        Self {
+            classifier,
+            tops: Arc::new(DashMap::new()),
+            books: Arc::new(DashMap::new()),
+            rejections: Rejections::default(),
+        }
+    }
+    
+    /// Clasificador usado para el lado, para compartirlo con otros engines
+    pub fn classifier(&self) -> TradeClassifier {
+        self.classifier.clone()
+    }
+    
+    /// Registra una quote; si no es válida se descarta y queda contada en las rejections
+    pub fn on_quote(&self, quote: &Quote) {
+        let _ = self.try_on_quote(quote);
+    }
+    
+    /// Valida y registra una quote; Err si no es válida (también queda contada)
+    pub fn try_on_quote(&self, quote: &Quote) -> Result<(), ProcessError> {
+        self.rejections.check(validate_quote(quote))?;
+        self.rejections.check_timestamp(&quote.symbol, quote.ts)?;
+        self.classifier.on_quote(quote);
+        self.tops.insert(quote.symbol.clone(), (quote.ts, quote.bid, quote.ask));
+        Ok(())
+    }
+    
+    /// Registra un snapshot; si no es válido se descarta y queda contado en las rejections
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) {
+        let _ = self.try_on_snapshot(snapshot);
+    }
+    
+    /// Valida y registra un snapshot; su top-of-book pasa a ser la quote vigente
+    pub fn try_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<(), ProcessError> {
+        self.rejections.check(validate_book(snapshot))?;
+        self.rejections.check_timestamp(&snapshot.symbol, snapshot.ts)?;
+        let (bid, ask) = (&snapshot.bids[0], &snapshot.asks[0]);
+        self.classifier.on_quote(&Quote::new(
+            snapshot.ts, snapshot.symbol.clone(), bid.price, bid.size, ask.price, ask.size));
+        self.tops.insert(snapshot.symbol.clone(), (snapshot.ts, bid.price, ask.price));
+        self.books.insert(snapshot.symbol.clone(), snapshot.clone());
+        Ok(())
+    }
+    
+    /// Anota un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn enrich(&self, trade: &Trade) -> Option<EnrichedTrade> {
+        self.try_enrich(trade).unwrap_or_default()
+    }
+    
+    /// Valida y anota un trade; Err si no es válido (también queda contado)
+    pub fn try_enrich(&self, trade: &Trade) -> Result<Option<EnrichedTrade>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(Some(self.process_trade(trade)))
+    }
+    
+    fn process_trade(&self, trade: &Trade) -> EnrichedTrade {
+        let side = self.classifier.classify(trade);
+        let top = self.tops.get(&trade.symbol).map(|t| *t);
+        let mid = top.map(|(_, bid, ask)| (bid + ask) / 2.0);
+        let levels_swept = self.books.get(&trade.symbol)
+            .map(|book| match side {
+                SIDE_BUY => swept(&book.asks, |level| level.price <= trade.price),
+                SIDE_SELL => swept(&book.bids, |level| level.price >= trade.price),
+                _ => 0,
+            })
+            .unwrap_or(0);
+        
+        EnrichedTrade {
+            ts: trade.ts,
+            symbol: trade.symbol.clone(),
+            price: trade.price,
+            size: trade.size,
+            side: side.to_string(),
+            exchange: trade.exchange.clone(),
+            trade_id: trade.trade_id.clone(),
+            mid,
+            distance_bps: mid.map(|mid| (trade.price - mid) / mid * 10_000.0),
+            levels_swept,
+            quote_age_ms: top.map(|(ts, _, _)| trade.ts.saturating_sub(ts)),
+        }
+    }
+}
+
+impl Default for TradeEnricher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Niveles consecutivos desde el mejor precio que el trade alcanza
+fn swept(levels: &[Level], reached: impl Fn(&Level) -> bool) -> usize {
+    levels.iter().take_while(|level| reached(level)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::{CVDEngine, FootprintEngine};
+    
+    fn book(ts: u64) -> BookSnapshot {
+        BookSnapshot::new(
+            ts,
+            "AAPL".to_string(),
+            vec![Level::new(99.99, 10.0), Level::new(99.98, 10.0), Level::new(99.97, 10.0)],
+            vec![Level::new(100.01, 10.0), Level::new(100.02, 10.0), Level::new(100.03, 10.0)],
+        )
+    }
+    
+    #[test]
+    fn test_enrich_with_book_context() {
+        let enricher = TradeEnricher::new();
+        
+        // Sin libro: solo el lado (tick rule) y sin contexto
+        let bare = enricher.enrich(&Trade::new(900, 100.0, 1.0, "AAPL".to_string())).unwrap();
+        assert_eq!(bare.mid, None);
+        assert_eq!(bare.levels_swept, 0);
+        
+        enricher.on_snapshot(&book(1000));
+        
+        // Compra que barre hasta 100.02: dos niveles de asks
+        let sweep = enricher.enrich(&Trade::new(1100, 100.02, 15.0, "AAPL".to_string())).unwrap();
+        assert_eq!(sweep.side, "BUY");
+        assert_eq!(sweep.mid, Some(100.0));
+        assert!((sweep.distance_bps.unwrap() - 2.0).abs() < 1e-6);
+        assert_eq!(sweep.levels_swept, 2);
+        assert!(sweep.is_sweep());
+        assert_eq!(sweep.quote_age_ms, Some(100));
+        
+        // Venta en el bid: un nivel, no es barrido
+        let sell = enricher.enrich(&Trade::new(1200, 99.99, 1.0, "AAPL".to_string())).unwrap();
+        assert_eq!(sell.side, "SELL");
+        assert_eq!(sell.levels_swept, 1);
+        assert!(!sell.is_sweep());
+        assert_eq!(sell.to_trade().side.as_deref(), Some("SELL"));
+        
+        // Una quote posterior actualiza el mid pero no el libro
+        enricher.on_quote(&Quote::new(1300, "AAPL".to_string(), 100.00, 5.0, 100.04, 5.0));
+        let inside = enricher.enrich(&Trade::new(1400, 100.03, 1.0, "AAPL".to_string())).unwrap();
+        assert!((inside.mid.unwrap() - 100.02).abs() < 1e-9);
+        assert_eq!(inside.side, "BUY");
+        assert_eq!(inside.levels_swept, 3);
+    }
+    
+    #[test]
+    fn test_enricher_shares_classifier() {
+        let enricher = TradeEnricher::new();
+        let cvd = CVDEngine::with_classifier(enricher.classifier());
+        let footprint = FootprintEngine::with_classifier("1s", 0.01, enricher.classifier()).unwrap();
+        enricher.on_snapshot(&book(1000));
+        
+        // La quote del libro llega a los engines a través del clasificador compartido
+        let trade = Trade::new(1100, 99.99, 2.0, "AAPL".to_string());
+        assert_eq!(enricher.enrich(&trade).unwrap().side, "SELL");
+        assert_eq!(cvd.on_trade(&trade).unwrap().cvd, -2.0);
+        footprint.on_trade(&trade);
+        let bar = footprint.get_current_bar("AAPL").unwrap();
+        assert_eq!(bar.delta, -2.0);
+    }
+    
+    #[test]
+    fn test_enricher_rejects_invalid_events() {
+        let enricher = TradeEnricher::new();
+        let mut crossed = book(1000);
+        crossed.bids[0].price = 100.05;
+        enricher.on_snapshot(&crossed);
+        assert!(enricher.enrich(&Trade::new(1000, -1.0, 1.0, "AAPL".to_string())).is_none());
+        
+        let rejections = enricher.get_rejections(Some("AAPL"));
+        assert_eq!(rejections["crossed_book"], 1);
+        assert_eq!(rejections["invalid_price"], 1);
+    }
+}
@@ -0,0 +1,309 @@
+//! # Flow Signal Engine
+//! 
+//! Señal compuesta de order flow por símbolo a partir de las métricas de
+//! `CVDEngine`, `LiquidityEngine` y `TapeEngine` (o del `MetricsBundle` del
+//! `EngineManager`), para no tener que combinar tres streams en Python.
+//! 
+//! Cada componente se normaliza a [-1, 1]:
+//! - `cvd_flow`: volumen comprador menos vendedor sobre el volumen total de
+//!   los trades de la ventana `window_ms`
+//! - `book_imbalance`: `depth_imbalance` del último snapshot
+//! - `tape_intensity`: tanh(ln(trades/s ÷ línea base EWMA)) con el signo del
+//!   flujo: una cinta que acelera refuerza la dirección del CVD y una que se
+//!   frena la debilita
+//! 
+//! El score es la media ponderada de los componentes disponibles
+//! (Σ wᵢ·cᵢ / Σ wᵢ), así que también queda en [-1, 1].
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::types::{CVDMetrics, FlowSignal, LiquidityMetrics, MetricsBundle, TapeMetrics};
+use crate::utils::{sorted_symbols, NeumaierSum};
+use super::classifier::{SIDE_BUY, SIDE_SELL};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Peso de cada nuevo dato en la línea base EWMA de la velocidad de la cinta
+pub const TAPE_BASELINE_ALPHA: f64 = 0.05;
+
+/// Estado por símbolo
+#[derive(Clone, Debug, Default)]
+struct FlowState {
+    // (ts, volumen con signo, volumen) de los trades en la ventana
+    trades: VecDeque<(u64, f64, f64)>,
+    net: NeumaierSum,
+    gross: NeumaierSum,
+    imbalance: Option<f64>,
+    tape_baseline: Option<f64>,
+    // Aceleración de la cinta sin signo
+    tape_intensity: Option<f64>,
+    last_ts: u64,
+}
+
+impl FlowState {
+    fn cvd_flow(&self) -> Option<f64> {
+        let gross = self.gross.value();
+        (gross > 0.0).then(|| (self.net.value() / gross).clamp(-1.0, 1.0))
+    }
+    
+    fn push_trade(&mut self, ts: u64, side: &str, size: f64, window_ms: u64) {
+        let signed = match side {
+            SIDE_BUY => size,
+            SIDE_SELL => -size,
+            _ => 0.0,
+        };
+        self.trades.push_back((ts, signed, size));
+        self.net.add(signed);
+        self.gross.add(size);
+        self.last_ts = self.last_ts.max(ts);
+        
+        // Ventana (ts - window_ms, ts]
+        let Some(cutoff) = self.last_ts.checked_sub(window_ms) else { return };
+        while let Some(&(old_ts, old_signed, old_size)) = self.trades.front() {
+            if old_ts > cutoff {
+                break;
+            }
+            self.net.add(-old_signed);
+            self.gross.add(-old_size);
+            self.trades.pop_front();
+        }
+    }
+    
+    fn push_tape(&mut self, ts: u64, trades_per_second: f64) {
+        self.last_ts = self.last_ts.max(ts);
+        let baseline = self.tape_baseline.unwrap_or(trades_per_second);
+        self.tape_intensity = Some(if baseline > 0.0 && trades_per_second > 0.0 {
+            (trades_per_second / baseline).ln().tanh()
+        } else {
+            0.0
+        });
+        self.tape_baseline = Some(baseline + TAPE_BASELINE_ALPHA * (trades_per_second - baseline));
+    }
+}
+
+py_class! {
+/// Engine de señal de flujo compuesta por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct FlowSignalEngine {
+    #[pyo3(get)]
+    pub window_ms: u64,
+    // Pesos de [cvd_flow, book_imbalance, tape_intensity]
+    weights: [f64; 3],
+    state: Arc<DashMap<String, FlowState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl FlowSignalEngine {
+    #[new]
+    #[pyo3(signature = (window_ms=60_000, cvd_weight=1.0, imbalance_weight=1.0, tape_weight=0.5))]
+    pub fn new(window_ms: u64, cvd_weight: f64, imbalance_weight: f64, tape_weight: f64) -> Result<Self, ProcessError> {
+        if window_ms == 0 {
+            return Err(ProcessError::invalid("window_ms must be > 0"));
+        }
+        let mut engine = Self {
+            window_ms,
+            weights: [0.0; 3],
+            state: Arc::new(DashMap::new()),
+        };
+        engine.set_weights(cvd_weight, imbalance_weight, tape_weight)?;
+        Ok(engine)
+    }
+    
+    /// Pesos de los componentes: finitos, >= 0 y no todos 0
+    pub fn set_weights(&mut self, cvd_weight: f64, imbalance_weight: f64, tape_weight: f64) -> Result<(), ProcessError> {
+        let weights = [cvd_weight, imbalance_weight, tape_weight];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+            return Err(ProcessError::invalid(
+                "weights must be finite, >= 0 and not all 0"));
+        }
+        self.weights = weights;
+        Ok(())
+    }
+    
+    /// Pesos activos como (cvd, imbalance, tape)
+    #[getter]
+    pub fn weights(&self) -> (f64, f64, f64) {
+        (self.weights[0], self.weights[1], self.weights[2])
+    }
+    
+    /// Incorpora el trade reflejado en unas métricas de CVD del símbolo
+    pub fn on_cvd(&self, symbol: &str, metrics: &CVDMetrics) -> Option<FlowSignal> {
+        let mut state = self.state.entry(symbol.to_string()).or_default();
+        state.push_trade(metrics.timestamp, &metrics.last_side, metrics.last_size, self.window_ms);
+        self.signal(symbol, &state)
+    }
+    
+    /// Incorpora el imbalance de profundidad de un snapshot del símbolo
+    pub fn on_liquidity(&self, symbol: &str, ts: u64, metrics: &LiquidityMetrics) -> Option<FlowSignal> {
+        let mut state = self.state.entry(symbol.to_string()).or_default();
+        state.imbalance = Some(metrics.depth_imbalance.clamp(-1.0, 1.0));
+        state.last_ts = state.last_ts.max(ts);
+        self.signal(symbol, &state)
+    }
+    
+    /// Incorpora la velocidad de la cinta
+    pub fn on_tape(&self, metrics: &TapeMetrics) -> Option<FlowSignal> {
+        let mut state = self.state.entry(metrics.symbol.clone()).or_default();
+        state.push_tape(metrics.ts, metrics.trades_per_second);
+        self.signal(&metrics.symbol, &state)
+    }
+    
+    /// Incorpora las métricas presentes en un resultado del `EngineManager`
+    pub fn on_bundle(&self, bundle: &MetricsBundle) -> Option<FlowSignal> {
+        let mut state = self.state.entry(bundle.symbol.clone()).or_default();
+        if let Some(cvd) = &bundle.cvd {
+            state.push_trade(cvd.timestamp, &cvd.last_side, cvd.last_size, self.window_ms);
+        }
+        if let Some(liquidity) = &bundle.liquidity {
+            state.imbalance = Some(liquidity.depth_imbalance.clamp(-1.0, 1.0));
+        }
+        if let Some(tape) = &bundle.tape {
+            state.push_tape(tape.ts, tape.trades_per_second);
+        }
+        state.last_ts = state.last_ts.max(bundle.ts);
+        self.signal(&bundle.symbol, &state)
+    }
+    
+    /// Señal actual del símbolo sin incorporar métricas nuevas
+    pub fn get_signal(&self, symbol: &str) -> Option<FlowSignal> {
+        self.state.get(symbol).and_then(|state| self.signal(symbol, &state))
+    }
+    
+    /// Símbolos con estado, ordenados
+    pub fn symbols(&self) -> Vec<String> {
+        sorted_symbols(&self.state)
+    }
+    
+    /// Señales actuales de los símbolos que ya tienen alguna (symbol -> FlowSignal)
+    pub fn get_all(&self) -> HashMap<String, FlowSignal> {
+        self.symbols().into_iter()
+            .filter_map(|symbol| self.get_signal(&symbol).map(|m| (symbol, m)))
+            .collect()
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("FlowSignalEngine(window_ms={}, weights={:?}, symbols={})",
+                self.window_ms, self.weights, self.state.len())
+    }
+}
+}
+
+impl FlowSignalEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.state.len()
+    }
+    
+    /// Media ponderada de los componentes disponibles; None si no hay ninguno
+    fn signal(&self, symbol: &str, state: &FlowState) -> Option<FlowSignal> {
+        let cvd_flow = state.cvd_flow();
+        let tape_intensity = match (cvd_flow, state.tape_intensity) {
+            (Some(flow), Some(intensity)) if flow != 0.0 => Some(intensity * flow.signum()),
+            _ => None,
+        };
+        let components = [cvd_flow, state.imbalance, tape_intensity];
+        
+        let (weighted, total) = components.iter()
+            .zip(self.weights)
+            .filter_map(|(c, w)| c.map(|c| (c * w, w)))
+            .fold((0.0, 0.0), |(sum, total), (cw, w)| (sum + cw, total + w));
+        if total <= 0.0 {
+            return None;
+        }
+        Some(FlowSignal {
+            symbol: symbol.to_string(),
+            ts: state.last_ts,
+            score: (weighted / total).clamp(-1.0, 1.0),
+            cvd_flow,
+            book_imbalance: state.imbalance,
+            tape_intensity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn cvd(ts: u64, side: &str, size: f64) -> CVDMetrics {
+        CVDMetrics::new(0.0, side.to_string(), size, ts, 0.0, 0.0, 0)
+    }
+    
+    fn tape(ts: u64, trades_per_second: f64) -> TapeMetrics {
+        TapeMetrics {
+            symbol: "AAPL".to_string(),
+            ts,
+            window_ms: 10_000,
+            trade_count: 0,
+            trades_per_second,
+            avg_trade_size: 0.0,
+            volume_per_second: 0.0,
+            notional_per_second: 0.0,
+            smoothed_trades_per_second: None,
+        }
+    }
+    
+    #[test]
+    fn test_flow_signal_validation() {
+        assert!(FlowSignalEngine::new(0, 1.0, 1.0, 1.0).is_err());
+        assert!(FlowSignalEngine::new(1000, -1.0, 1.0, 1.0).is_err());
+        assert!(FlowSignalEngine::new(1000, 0.0, 0.0, 0.0).is_err());
+    }
+    
+    #[test]
+    fn test_cvd_flow_window() {
+        let engine = FlowSignalEngine::new(1000, 1.0, 1.0, 1.0).unwrap();
+        assert!(engine.get_signal("AAPL").is_none());
+        
+        engine.on_cvd("AAPL", &cvd(0, "BUY", 3.0));
+        let signal = engine.on_cvd("AAPL", &cvd(500, "SELL", 1.0)).unwrap();
+        assert_eq!(signal.cvd_flow, Some(0.5));
+        assert_eq!(signal.score, 0.5);
+        
+        // La compra de ts=0 sale de la ventana
+        let signal = engine.on_cvd("AAPL", &cvd(1200, "SELL", 1.0)).unwrap();
+        assert_eq!(signal.cvd_flow, Some(-1.0));
+    }
+    
+    #[test]
+    fn test_weighted_score() {
+        let engine = FlowSignalEngine::new(60_000, 2.0, 1.0, 1.0).unwrap();
+        let mut bundle = MetricsBundle::empty("AAPL", 1000);
+        bundle.cvd = Some(cvd(1000, "BUY", 1.0));
+        bundle.tape = Some(tape(1000, 10.0));
+        let signal = engine.on_bundle(&bundle).unwrap();
+        // Primera muestra de la cinta: sin aceleración respecto a la base
+        assert_eq!(signal.tape_intensity, Some(0.0));
+        assert!((signal.score - 2.0 / 3.0).abs() < 1e-9);
+        
+        // La cinta acelera en la dirección del flujo
+        let signal = engine.on_tape(&tape(2000, 30.0)).unwrap();
+        let intensity = signal.tape_intensity.unwrap();
+        assert!(intensity > 0.5 && intensity < 1.0);
+        assert!(signal.score > 2.0 / 3.0);
+        assert_eq!(signal.book_imbalance, None);
+        
+        let mut engine = engine;
+        engine.set_weights(0.0, 1.0, 0.0).unwrap();
+        assert_eq!(engine.weights(), (0.0, 1.0, 0.0));
+        // Solo cuenta el imbalance, que aún no existe
+        assert!(engine.get_signal("AAPL").is_none());
+    }
+}
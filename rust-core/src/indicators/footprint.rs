@@ -0,0 +1,316 @@
+//! # Footprint Engine
+//! 
+//! Barras footprint: volumen comprador, vendedor y delta por nivel de precio
+//! dentro de cada barra. Usa el mismo TradeClassifier que CVD para el lado.
+//! Emite la barra completada cuando el bucket temporal cambia.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use crate::types::{FootprintBar, FootprintLevel, Quote, Trade};
+use crate::utils::{calculate_bucket, parse_timeframe_ms, price_to_tick, sorted_symbols, tick_to_price, TickSizeRegistry};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use super::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+use crate::py_macros::{py_class, py_methods};
+
+/// Barra en construcción: OHLC y (buy, sell, volume) por tick
+#[derive(Clone, Debug)]
+struct OpenBar {
+    bucket_ts: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    levels: BTreeMap<i64, (f64, f64, f64)>,
+}
+
+impl OpenBar {
+    fn new(bucket_ts: u64, price: f64) -> Self {
+        Self { bucket_ts, open: price, high: price, low: price, close: price, levels: BTreeMap::new() }
+    }
+    
+    fn apply(&mut self, tick: i64, price: f64, size: f64, side: &str) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        
+        let level = self.levels.entry(tick).or_insert((0.0, 0.0, 0.0));
+        match side {
+            SIDE_BUY => level.0 += size,
+            SIDE_SELL => level.1 += size,
+            _ => {} // "NA" - solo cuenta en el volumen
+        }
+        level.2 += size;
+    }
+    
+    fn to_bar(&self, symbol: &str, tf: &str, tick_size: f64) -> FootprintBar {
+        let levels: Vec<FootprintLevel> = self.levels.iter()
+            .map(|(tick, (buy, sell, volume))| {
+                FootprintLevel::new(tick_to_price(*tick, tick_size), *buy, *sell, *volume)
+            })
+            .collect();
+        
+        FootprintBar {
+            symbol: symbol.to_string(),
+            tf: tf.to_string(),
+            bucket_ts: self.bucket_ts,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: levels.iter().map(|l| l.volume).sum(),
+            delta: levels.iter().map(|l| l.delta).sum(),
+            levels,
+        }
+    }
+}
+
+py_class! {
+/// Engine de barras footprint por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct FootprintEngine {
+    #[pyo3(get)]
+    pub tf: String,
+    #[pyo3(get)]
+    pub bucket_ms: u64,
+    tick_sizes: TickSizeRegistry,
+    // Barra en curso por símbolo
+    bars: Arc<DashMap<String, OpenBar>>,
+    classifier: TradeClassifier,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl FootprintEngine {
+    #[new]
+    #[pyo3(signature = (tf, tick_size=0.01))]
+    pub fn new(tf: &str, tick_size: f64) -> Result<Self, ProcessError> {
+        Self::with_classifier(tf, tick_size, TradeClassifier::new())
+    }
+    
+    /// Registro de tick sizes por símbolo (compartible con otros engines)
+    #[getter]
+    pub fn tick_registry(&self) -> TickSizeRegistry {
+        self.tick_sizes.clone()
+    }
+    
+    /// Usa un registro de tick sizes compartido. Descarta las barras en curso.
+    #[setter]
+    pub fn set_tick_registry(&mut self, registry: TickSizeRegistry) {
+        self.tick_sizes = registry;
+        self.bars.clear();
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade; devuelve la barra anterior si el bucket ha cambiado.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<FootprintBar>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Obtiene la barra en curso (aún no cerrada) de un símbolo
+    pub fn get_current_bar(&self, symbol: &str) -> Option<FootprintBar> {
+        let tick_size = self.tick_sizes.get_tick_size(symbol);
+        self.bars.get(symbol).map(|bar| bar.to_bar(symbol, &self.tf, tick_size))
+    }
+    
+    /// Símbolos con estado, ordenados
+    pub fn symbols(&self) -> Vec<String> {
+        sorted_symbols(&self.bars)
+    }
+    
+    /// Barras en curso de todos los símbolos (symbol -> FootprintBar)
+    pub fn get_all(&self) -> HashMap<String, FootprintBar> {
+        self.symbols().into_iter()
+            .filter_map(|symbol| self.get_current_bar(&symbol).map(|m| (symbol, m)))
+            .collect()
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.bars.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.bars.clear();
+        self.classifier.reset_all();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("FootprintEngine(tf={}, symbols={})", self.tf, self.bars.len())
+    }
+}
+}
+
+impl FootprintEngine {
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<FootprintBar> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<FootprintBar>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade; devuelve la barra anterior si el bucket ha cambiado
+    fn process_trade(&self, trade: &Trade) -> Option<FootprintBar> {
+        let side = self.classifier.classify(trade);
+        let bucket_ts = calculate_bucket(trade.ts, self.bucket_ms);
+        let tick_size = self.tick_sizes.get_tick_size(&trade.symbol);
+        
+        let mut completed = None;
+        let mut bar = self.bars.entry(trade.symbol.clone())
+            .or_insert_with(|| OpenBar::new(bucket_ts, trade.price));
+        
+        // Trades tardíos se agregan a la barra en curso
+        if bucket_ts > bar.bucket_ts {
+            let next = OpenBar::new(bucket_ts, trade.price);
+            let closed = std::mem::replace(&mut *bar, next);
+            completed = Some(closed.to_bar(&trade.symbol, &self.tf, tick_size));
+        }
+        
+        bar.apply(price_to_tick(trade.price, tick_size), trade.price, trade.size, side);
+        completed
+    }
+    
+    /// Crea el engine con un clasificador compartido (p. ej. el de un CVDEngine),
+    /// de modo que ambos vean las mismas quotes y el mismo estado de tick rule
+    pub fn with_classifier(tf: &str, tick_size: f64, classifier: TradeClassifier) -> Result<Self, ProcessError> {
+        let bucket_ms = parse_timeframe_ms(tf).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid timeframe: {}", tf))
+        })?;
+        Ok(Self {
+            tf: tf.to_string(),
+            bucket_ms,
+            tick_sizes: TickSizeRegistry::new(tick_size)?,
+            bars: Arc::new(DashMap::new()),
+            classifier,
+            rejections: Rejections::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, price: f64, size: f64, side: Option<&str>) -> Trade {
+        Trade {
+            ts,
+            price,
+            size,
+            symbol: "AAPL".to_string(),
+            side: side.map(|s| s.to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_footprint_invalid_config() {
+        assert!(FootprintEngine::new("abc", 0.01).is_err());
+        assert!(FootprintEngine::new("1m", 0.0).is_err());
+    }
+
+    #[test]
+    fn test_footprint_levels_within_bar() {
+        let engine = FootprintEngine::new("1s", 0.01).unwrap();
+        
+        engine.on_trade(&trade(1000, 100.00, 10.0, Some("BUY")));
+        engine.on_trade(&trade(1100, 100.00, 4.0, Some("SELL")));
+        engine.on_trade(&trade(1200, 100.01, 6.0, Some("BUY")));
+        
+        let bar = engine.get_current_bar("AAPL").unwrap();
+        assert_eq!(bar.levels.len(), 2);
+        assert_eq!(bar.levels[0].buy_volume, 10.0);
+        assert_eq!(bar.levels[0].sell_volume, 4.0);
+        assert_eq!(bar.levels[0].delta, 6.0);
+        assert_eq!(bar.levels[1].delta, 6.0);
+        assert_eq!(bar.delta, 12.0);
+        assert_eq!(bar.volume, 20.0);
+        assert_eq!(bar.high, 100.01);
+        assert_eq!(bar.close, 100.01);
+    }
+
+    #[test]
+    fn test_footprint_rollover_emits_completed_bar() {
+        let engine = FootprintEngine::new("1s", 0.01).unwrap();
+        
+        assert!(engine.on_trade(&trade(1000, 100.0, 10.0, Some("BUY"))).is_none());
+        let completed = engine.on_trade(&trade(2100, 99.0, 5.0, Some("SELL"))).unwrap();
+        
+        assert_eq!(completed.bucket_ts, 1000);
+        assert_eq!(completed.delta, 10.0);
+        
+        let current = engine.get_current_bar("AAPL").unwrap();
+        assert_eq!(current.open, 99.0);
+        assert_eq!(current.delta, -5.0);
+    }
+
+    #[test]
+    fn test_footprint_shares_classifier() {
+        let classifier = TradeClassifier::new();
+        let engine = FootprintEngine::with_classifier("1s", 0.01, classifier.clone()).unwrap();
+        
+        // Quote registrada fuera del engine: trade en el ask = BUY
+        classifier.on_quote(&Quote::new(900, "AAPL".to_string(), 99.99, 100.0, 100.01, 100.0));
+        engine.on_trade(&trade(1000, 100.01, 3.0, None));
+        
+        let bar = engine.get_current_bar("AAPL").unwrap();
+        assert_eq!(bar.levels[0].buy_volume, 3.0);
+    }
+}
@@ -0,0 +1,244 @@
+//! # Gap Detector
+//! 
+//! Vigila que cada símbolo siga imprimiendo trades y recibiendo quotes: un
+//! stream que pasa más de `max_gap_ms` de mercado abierto sin eventos genera
+//! un `DataGapAlert`. El tiempo se mide con el `SessionCalendar`, así que el
+//! cierre nocturno o el fin de semana no cuentan como hueco.
+//! 
+//! Los huecos se detectan de dos formas:
+//! - `check(now_ts)`, llamado periódicamente por el host, avisa mientras el
+//!   stream sigue parado (una vez por hueco, con `resumed = False`)
+//! - el primer evento tras el hueco lo cierra y devuelve la alerta con
+//!   `resumed = True` y la duración total
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::calendar::SessionCalendar;
+use crate::types::{BookSnapshot, DataGapAlert, Quote, Trade};
+use crate::py_macros::{py_class, py_methods};
+use crate::utils::count_symbols;
+use crate::errors::ProcessError;
+
+pub const STREAM_TRADE: &str = "trade";
+pub const STREAM_QUOTE: &str = "quote";
+
+/// Último evento de un stream
+#[derive(Clone, Copy, Debug)]
+struct StreamState {
+    last_ts: u64,
+    // Hueco ya avisado por `check` y aún abierto
+    flagged: bool,
+}
+
+py_class! {
+/// Detector de huecos de datos por símbolo y stream
+#[pyclass]
+#[derive(Clone)]
+pub struct GapDetector {
+    /// Tiempo máximo de mercado abierto sin eventos (ms)
+    #[pyo3(get)]
+    pub max_gap_ms: u64,
+    calendar: SessionCalendar,
+    // (symbol, stream) -> último evento
+    streams: Arc<DashMap<(String, &'static str), StreamState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl GapDetector {
+    #[new]
+    #[pyo3(signature = (max_gap_ms=60_000, calendar=None))]
+    pub fn new(max_gap_ms: u64, calendar: Option<SessionCalendar>) -> Result<Self, ProcessError> {
+        if max_gap_ms == 0 {
+            return Err(ProcessError::invalid("max_gap_ms must be > 0"));
+        }
+        Ok(Self {
+            max_gap_ms,
+            calendar: calendar.unwrap_or_default(),
+            streams: Arc::new(DashMap::new()),
+        })
+    }
+    
+    /// Calendario de mercado usado para medir los huecos (24/7 por defecto)
+    #[getter]
+    pub fn calendar(&self) -> SessionCalendar {
+        self.calendar.clone()
+    }
+    
+    #[setter]
+    pub fn set_calendar(&mut self, calendar: SessionCalendar) {
+        self.calendar = calendar;
+    }
+    
+    /// Registra un trade; devuelve la alerta del hueco que cierra, si lo hay
+    pub fn on_trade(&self, trade: &Trade) -> Option<DataGapAlert> {
+        self.record(&trade.symbol, STREAM_TRADE, trade.ts)
+    }
+    
+    /// Registra una quote; devuelve la alerta del hueco que cierra, si lo hay
+    pub fn on_quote(&self, quote: &Quote) -> Option<DataGapAlert> {
+        self.record(&quote.symbol, STREAM_QUOTE, quote.ts)
+    }
+    
+    /// Registra un snapshot como quote del símbolo
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Option<DataGapAlert> {
+        self.record(&snapshot.symbol, STREAM_QUOTE, snapshot.ts)
+    }
+    
+    /// Empieza a vigilar un símbolo desde ts aunque aún no haya recibido
+    /// eventos (p. ej. al suscribirse)
+    pub fn watch(&self, symbol: &str, ts: u64) {
+        for stream in [STREAM_TRADE, STREAM_QUOTE] {
+            self.streams.entry((symbol.to_string(), stream))
+                .or_insert(StreamState { last_ts: ts, flagged: false });
+        }
+    }
+    
+    /// Streams sin eventos durante más de `max_gap_ms` de mercado abierto a
+    /// `now_ts`; cada hueco se avisa una vez. Con el mercado cerrado no avisa
+    pub fn check(&self, now_ts: u64) -> Vec<DataGapAlert> {
+        if !self.calendar.is_open(now_ts) {
+            return Vec::new();
+        }
+        let mut alerts = Vec::new();
+        for mut entry in self.streams.iter_mut() {
+            if entry.flagged {
+                continue;
+            }
+            let gap_ms = self.calendar.open_ms_between(entry.last_ts, now_ts);
+            if gap_ms <= self.max_gap_ms {
+                continue;
+            }
+            entry.flagged = true;
+            let (symbol, stream) = entry.key();
+            alerts.push(self.alert(symbol, stream, entry.last_ts, now_ts, gap_ms, false));
+        }
+        alerts.sort_by(|a, b| (&a.symbol, &a.stream).cmp(&(&b.symbol, &b.stream)));
+        alerts
+    }
+    
+    /// Último evento de un stream ("trade" | "quote") del símbolo
+    pub fn get_last_ts(&self, symbol: &str, stream: &str) -> Option<u64> {
+        let stream = match stream {
+            STREAM_TRADE => STREAM_TRADE,
+            STREAM_QUOTE => STREAM_QUOTE,
+            _ => return None,
+        };
+        self.streams.get(&(symbol.to_string(), stream)).map(|s| s.last_ts)
+    }
+    
+    /// Deja de vigilar un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.streams.retain(|key, _| key.0 != symbol);
+    }
+    
+    /// Deja de vigilar todos los símbolos
+    pub fn reset_all(&self) {
+        self.streams.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("GapDetector(max_gap_ms={}, streams={})", self.max_gap_ms, self.streams.len())
+    }
+}
+}
+
+impl GapDetector {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.streams.iter().map(|e| e.key().0.clone()))
+    }
+    
+    /// Actualiza el último evento del stream y cierra el hueco si lo había
+    fn record(&self, symbol: &str, stream: &'static str, ts: u64) -> Option<DataGapAlert> {
+        let mut state = self.streams.entry((symbol.to_string(), stream))
+            .or_insert(StreamState { last_ts: ts, flagged: false });
+        // Eventos tardíos no cambian nada
+        if ts <= state.last_ts {
+            return None;
+        }
+        let gap_ms = self.calendar.open_ms_between(state.last_ts, ts);
+        let alert = (state.flagged || gap_ms > self.max_gap_ms)
+            .then(|| self.alert(symbol, stream, state.last_ts, ts, gap_ms, true));
+        state.last_ts = ts;
+        state.flagged = false;
+        alert
+    }
+    
+    fn alert(&self, symbol: &str, stream: &str, last_ts: u64, ts: u64, gap_ms: u64, resumed: bool) -> DataGapAlert {
+        tracing::warn!(symbol, stream, gap_ms, resumed, "data gap");
+        DataGapAlert {
+            symbol: symbol.to_string(),
+            stream: stream.to_string(),
+            last_ts,
+            ts,
+            gap_ms,
+            resumed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::DAY_MS;
+    
+    // Lunes 2024-01-01 00:00 UTC
+    const MONDAY: u64 = 1_704_067_200_000;
+    const HOUR: u64 = 3_600_000;
+    
+    fn trade(ts: u64) -> Trade {
+        Trade::new(ts, 100.0, 1.0, "AAPL".to_string())
+    }
+    
+    fn detector() -> GapDetector {
+        let calendar = SessionCalendar::new("14:30", "21:00", Some(vec![0, 1, 2, 3, 4]), "UTC", None).unwrap();
+        GapDetector::new(60_000, Some(calendar)).unwrap()
+    }
+    
+    #[test]
+    fn test_gap_flagged_and_resumed() {
+        let detector = detector();
+        let start = MONDAY + 15 * HOUR;
+        assert!(detector.on_trade(&trade(start)).is_none());
+        assert!(detector.check(start + 30_000).is_empty());
+        
+        let alerts = detector.check(start + 120_000);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((alerts[0].stream.as_str(), alerts[0].gap_ms, alerts[0].resumed), ("trade", 120_000, false));
+        // Un solo aviso por hueco
+        assert!(detector.check(start + 180_000).is_empty());
+        
+        let resumed = detector.on_trade(&trade(start + 300_000)).unwrap();
+        assert!(resumed.resumed);
+        assert_eq!((resumed.last_ts, resumed.gap_ms), (start, 300_000));
+        assert_eq!(detector.get_last_ts("AAPL", "trade"), Some(start + 300_000));
+        // Sin quotes nunca vistas no hay stream de quotes
+        assert_eq!(detector.get_last_ts("AAPL", "quote"), None);
+    }
+    
+    #[test]
+    fn test_closed_market_not_counted() {
+        let detector = detector();
+        let last = MONDAY + 21 * HOUR - 30_000;
+        detector.on_trade(&trade(last));
+        
+        // Noche del lunes: mercado cerrado
+        assert!(detector.check(MONDAY + DAY_MS + 10 * HOUR).is_empty());
+        // Apertura del martes: 30 s del lunes + 30 s del martes no superan el máximo
+        let open = MONDAY + DAY_MS + 14 * HOUR + HOUR / 2;
+        assert!(detector.check(open + 30_000).is_empty());
+        let alerts = detector.check(open + 60_000);
+        assert_eq!(alerts[0].gap_ms, 90_000);
+        
+        // Un hueco detectado al reanudar sin haber llamado a check
+        detector.watch("MSFT", open);
+        let alert = detector.on_quote(&Quote::new(open + 120_000, "MSFT".to_string(), 99.0, 1.0, 101.0, 1.0)).unwrap();
+        assert_eq!((alert.stream.as_str(), alert.gap_ms, alert.resumed), ("quote", 120_000, true));
+    }
+
+}
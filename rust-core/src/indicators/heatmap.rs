@@ -1,95 +1,764 @@
 //! # Heatmap Engine
 //! 
 //! Order book heatmap with temporal buckets and price grids.
+//! 
+//! Con `set_fixed_point` el precio se convierte a tick con aritmética
+//! decimal exacta y los tamaños de cada celda se acumulan en punto fijo
+//! (ver `crate::fixed`); `get_tiles_decimal` devuelve los tiles exactos. En
+//! modo decay (`half_life_ms`) los tamaños siguen en f64.
+//! 
+//! Con `relative_bps` los niveles se agrupan por su distancia al mid de cada
+//! snapshot en bins de `relative_bps` puntos básicos en lugar de por tick:
+//! `price_bin` pasa a ser el offset en bps (negativo bajo el mid) y el mapa
+//! queda centrado aunque el precio tienda, y es comparable entre símbolos de
+//! niveles de precio muy distintos. Este modo exige libros con ambos lados.
+//! 
+//! `normalization` rellena `Tile.value` en Rust para que los front-ends no
+//! tengan que re-normalizar cada frame: "raw" (el tamaño), "percent_max" (%
+//! del mayor tile del bucket), "percent_side" (% del total de su lado en el
+//! bucket) o "log" (ln(1 + tamaño)).
+//! 
+//! Con `notional` cada nivel aporta precio × tamaño en vez del tamaño, para
+//! comparar liquidez entre símbolos de precios muy distintos.
+//! 
+//! `get_heatmap` descarta los tiles por debajo de `min_tile_pct` % del mayor
+//! del bucket y, con `top_k`, se queda con los K mayores de cada lado;
+//! `compression_ratio` es celdas del bucket / tiles emitidos.
+//! 
+//! `bucket_ms`, `tick_size` y `retention_buckets` se fijan al construir el
+//! engine. Cambiar la rejilla (bucket o tick) o el modo decay con datos
+//! acumulados limpia el grid para no mezclar celdas incompatibles.
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use dashmap::DashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
-use crate::types::{BookSnapshot, HeatmapMetrics, Tile};
-use crate::utils::{calculate_bucket, quantize_price};
+use crate::config::ConfigRegistry;
+use crate::types::{BookSnapshot, HeatmapMetrics, Level, Tile};
+use crate::utils::{calculate_bucket, count_symbols, price_to_tick, safe_div, simd, tick_to_price, TickSizeRegistry};
+use crate::errors::{validate_snapshot, ProcessError, Rejections};
+use crate::fixed::{Fixed, FixedPointConfig, Precision, MAX_DECIMALS};
+use crate::py_macros::{py_class, py_methods};
+
+/// Clave del grid: (symbol, bucket_ts, tick, side). El precio se guarda como
+/// índice entero de tick; tick_size se aplica solo al construir los tiles.
+type GridKey = (String, u64, i64, &'static str);
+
+/// Umbral por defecto: tiles menores al 1% del mayor del bucket se descartan
+pub const DEFAULT_MIN_TILE_PCT: f64 = 1.0;
+
+/// Normalización de `Tile.value` en los heatmaps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Tamaño sin transformar
+    #[default]
+    Raw,
+    /// Porcentaje del mayor tile del bucket
+    PercentMax,
+    /// Porcentaje del total de su lado (bid/ask) en el bucket
+    PercentSide,
+    /// ln(1 + tamaño), para libros con niveles muy desiguales
+    Log,
+}
+
+impl Normalization {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_ascii_lowercase().as_str() {
+            "raw" => Some(Self::Raw),
+            "percent_max" => Some(Self::PercentMax),
+            "percent_side" => Some(Self::PercentSide),
+            "log" => Some(Self::Log),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::PercentMax => "percent_max",
+            Self::PercentSide => "percent_side",
+            Self::Log => "log",
+        }
+    }
+    
+    /// Valor normalizado de un tamaño dado el máximo del bucket y el total de su lado
+    pub fn apply(&self, size: f64, max: f64, side_total: f64) -> f64 {
+        match self {
+            Self::Raw => size,
+            Self::PercentMax => safe_div(size, max) * 100.0,
+            Self::PercentSide => safe_div(size, side_total) * 100.0,
+            Self::Log => size.max(0.0).ln_1p(),
+        }
+    }
+}
+
+/// Celda del grid: tamaño acumulado y último ts en que se actualizó
+#[derive(Clone, Copy, Debug, Default)]
+struct Cell {
+    size: f64,
+    last_ts: u64,
+    // Tamaño exacto en modo punto fijo
+    fixed: Fixed,
+}
 
+impl Cell {
+    /// Tamaño decaído hasta ts con el half-life dado
+    fn decayed(&self, ts: u64, half_life_ms: u64) -> f64 {
+        let age_ms = ts.saturating_sub(self.last_ts) as f64;
+        self.size * (-std::f64::consts::LN_2 * age_ms / half_life_ms as f64).exp()
+    }
+}
+
+py_class! {
 /// Engine para calcular heatmap del libro de órdenes
 #[pyclass]
+#[derive(Clone)]
 pub struct HeatmapEngine {
+    /// Tamaño del bucket temporal (ms)
+    #[pyo3(get)]
     pub bucket_ms: u64,
+    /// Tick por defecto (símbolos sin tick propio en el registro)
     pub tick_size: f64,
-    // Estado: (bucket_ts, price_bin, side) -> size acumulado
-    grid: Arc<DashMap<(u64, String, String), f64>>,
+    // Tick size por símbolo; su default se mantiene igual a tick_size
+    tick_sizes: TickSizeRegistry,
+    /// Retención: máximo de buckets por símbolo (None = sin límite)
+    #[pyo3(get)]
+    pub max_buckets: Option<usize>,
+    /// Retención: antigüedad máxima de un bucket respecto al actual (ms)
+    #[pyo3(get)]
+    pub retention_ms: Option<u64>,
+    /// Modo decay: los tamaños decaen con la edad en vez de sumarse sin más
+    #[pyo3(get)]
+    pub half_life_ms: Option<u64>,
+    /// Ancho del bin en bps respecto al mid (None = bins absolutos por tick)
+    #[pyo3(get)]
+    pub relative_bps: Option<f64>,
+    /// Modo nocional: las celdas acumulan precio × tamaño
+    #[pyo3(get)]
+    pub notional: bool,
+    // Normalización de Tile.value
+    normalization: Normalization,
+    /// Umbral de compresión: % del mayor tile del bucket por debajo del cual se descarta
+    #[pyo3(get)]
+    pub min_tile_pct: f64,
+    /// Máximo de tiles por lado y bucket (None = todos los que pasan el umbral)
+    #[pyo3(get)]
+    pub top_k: Option<usize>,
+    // Estado: (symbol, bucket_ts, tick, side) -> size acumulado
+    grid: Arc<DashMap<GridKey, Cell>>,
+    // Último tamaño publicado por celda (ver mark_published)
+    published: Arc<DashMap<GridKey, f64>>,
+    // Último bucket visto por símbolo (para detectar rollover)
+    current_bucket: Arc<DashMap<String, u64>>,
+    // Mid del último snapshot por símbolo (modo relativo)
+    last_mid: Arc<DashMap<String, f64>>,
+    // Configuración por símbolo (bucket_ms); None = bucket_ms global
+    config: Option<ConfigRegistry>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+    // Precisión de punto fijo (None = f64)
+    fixed_point: FixedPointConfig,
+}
 }
 
+py_methods! {
 #[pymethods]
 impl HeatmapEngine {
+    /// `retention_buckets` fija `max_buckets` (buckets conservados por símbolo)
     #[new]
-    pub fn new() -> Self {
-        Self {
-            bucket_ms: 1000,
-            tick_size: 0.01,
-            grid: Arc::new(DashMap::new()),
+    #[pyo3(signature = (bucket_ms=1000, tick_size=0.01, retention_buckets=None))]
+    pub fn new(bucket_ms: u64, tick_size: f64, retention_buckets: Option<usize>) -> Result<Self, ProcessError> {
+        if bucket_ms == 0 {
+            return Err(ProcessError::invalid("bucket_ms must be > 0"));
         }
+        let mut engine = Self::default();
+        engine.set_tick_size(tick_size)?;
+        engine.set_max_buckets(retention_buckets)?;
+        engine.bucket_ms = bucket_ms;
+        Ok(engine)
     }
     
-    /// Configura el tamaño del bucket temporal (ms)
+    /// Configura el tamaño del bucket temporal (ms). Los buckets acumulados
+    /// dependen de él, así que se limpia el grid si cambia.
     #[setter]
-    fn set_bucket_ms(&mut self, bucket_ms: u64) {
+    pub fn set_bucket_ms(&mut self, bucket_ms: u64) -> Result<(), ProcessError> {
+        if bucket_ms == 0 {
+            return Err(ProcessError::invalid("bucket_ms must be > 0"));
+        }
+        if bucket_ms != self.bucket_ms {
+            self.reset();
+        }
         self.bucket_ms = bucket_ms;
+        Ok(())
     }
     
-    /// Configura el tamaño del tick para cuantización de precio
+    /// Configura el tamaño del tick por defecto para cuantización de precio.
+    /// Los índices acumulados dependen del tick, así que se limpia el grid.
     #[setter]
-    fn set_tick_size(&mut self, tick_size: f64) {
+    pub fn set_tick_size(&mut self, tick_size: f64) -> Result<(), ProcessError> {
+        self.tick_sizes.set_default_tick(tick_size)?;
+        if tick_size != self.tick_size {
+            self.reset();
+        }
         self.tick_size = tick_size;
+        Ok(())
     }
     
-    /// Procesa un snapshot del libro y calcula heatmap
-    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Option<HeatmapMetrics> {
-        // Validar que hay datos
-        if snapshot.bids.is_empty() && snapshot.asks.is_empty() {
-            return None;
+    /// Registro de tick sizes por símbolo (compartible con otros engines)
+    #[getter]
+    pub fn tick_registry(&self) -> TickSizeRegistry {
+        self.tick_sizes.clone()
+    }
+    
+    /// Usa un registro de tick sizes compartido (su default pasa a ser tick_size).
+    /// Limpia el grid: la cuantización previa ya no es válida.
+    #[setter]
+    pub fn set_tick_registry(&mut self, registry: TickSizeRegistry) {
+        self.tick_size = registry.default_tick;
+        self.tick_sizes = registry;
+        self.reset();
+    }
+    
+    /// Usa la configuración por símbolo: tick sizes y bucket_ms del registro.
+    /// Limpia el grid; con None se vuelve a la configuración global.
+    #[setter]
+    pub fn set_config(&mut self, config: Option<ConfigRegistry>) -> Result<(), ProcessError> {
+        if let Some(config) = &config {
+            self.set_tick_registry(config.tick_registry(self.tick_size)?);
+        } else {
+            self.reset();
         }
+        self.config = config;
+        Ok(())
+    }
+    
+    /// Configura el tick de un símbolo; limpia su histórico porque cambia la cuantización
+    pub fn set_symbol_tick_size(&self, symbol: &str, tick_size: f64) -> Result<(), ProcessError> {
+        self.tick_sizes.set_tick_size(symbol, tick_size)?;
+        self.reset_symbol(symbol);
+        Ok(())
+    }
+    
+    /// Tick size efectivo de un símbolo
+    pub fn get_tick_size(&self, symbol: &str) -> f64 {
+        self.tick_sizes.get_tick_size(symbol)
+    }
+    
+    /// Configura cuántos buckets conservar por símbolo
+    #[setter]
+    pub fn set_max_buckets(&mut self, max_buckets: Option<usize>) -> Result<(), ProcessError> {
+        if max_buckets == Some(0) {
+            return Err(ProcessError::invalid("max_buckets must be > 0"));
+        }
+        self.max_buckets = max_buckets;
+        Ok(())
+    }
+    
+    /// Configura la ventana de retención en ms
+    #[setter]
+    pub fn set_retention_ms(&mut self, retention_ms: Option<u64>) -> Result<(), ProcessError> {
+        if retention_ms == Some(0) {
+            return Err(ProcessError::invalid("retention_ms must be > 0"));
+        }
+        self.retention_ms = retention_ms;
+        Ok(())
+    }
+    
+    /// Activa (Some) o desactiva (None) la acumulación con decaimiento
+    /// exponencial. Limpia el grid si cambia.
+    #[setter]
+    pub fn set_half_life_ms(&mut self, half_life_ms: Option<u64>) -> Result<(), ProcessError> {
+        if half_life_ms == Some(0) {
+            return Err(ProcessError::invalid("half_life_ms must be > 0"));
+        }
+        if half_life_ms != self.half_life_ms {
+            self.reset();
+        }
+        self.half_life_ms = half_life_ms;
+        Ok(())
+    }
+    
+    /// Agrupa los niveles en bins de `relative_bps` puntos básicos respecto al
+    /// mid (None vuelve a bins absolutos por tick). Limpia el grid si cambia.
+    #[setter]
+    pub fn set_relative_bps(&mut self, relative_bps: Option<f64>) -> Result<(), ProcessError> {
+        if relative_bps.is_some_and(|bps| !bps.is_finite() || bps <= 0.0) {
+            return Err(ProcessError::invalid("relative_bps must be finite and > 0"));
+        }
+        if relative_bps != self.relative_bps {
+            self.reset();
+        }
+        self.relative_bps = relative_bps;
+        Ok(())
+    }
+    
+    /// Acumula nocional (precio × tamaño) en vez de tamaño. Limpia el grid si cambia.
+    #[setter]
+    pub fn set_notional(&mut self, notional: bool) {
+        if notional != self.notional {
+            self.reset();
+        }
+        self.notional = notional;
+    }
+    
+    /// Configura el umbral de compresión (0 = conservar todos los tiles)
+    #[setter]
+    pub fn set_min_tile_pct(&mut self, min_tile_pct: f64) -> Result<(), ProcessError> {
+        if !(0.0..=100.0).contains(&min_tile_pct) {
+            return Err(ProcessError::invalid("min_tile_pct must be between 0 and 100"));
+        }
+        self.min_tile_pct = min_tile_pct;
+        Ok(())
+    }
+    
+    /// Configura cuántos tiles conservar por lado y bucket (los de mayor tamaño)
+    #[setter]
+    pub fn set_top_k(&mut self, top_k: Option<usize>) -> Result<(), ProcessError> {
+        if top_k == Some(0) {
+            return Err(ProcessError::invalid("top_k must be > 0"));
+        }
+        self.top_k = top_k;
+        Ok(())
+    }
+    
+    /// Normalización de `Tile.value`: "raw", "percent_max", "percent_side" o "log"
+    #[getter]
+    pub fn normalization(&self) -> &'static str {
+        self.normalization.as_str()
+    }
+    
+    #[setter]
+    pub fn set_normalization(&mut self, normalization: &str) -> Result<(), ProcessError> {
+        self.normalization = Normalization::parse(normalization).ok_or_else(|| {
+            ProcessError::invalid(format!(
+                "invalid normalization: {} (raw, percent_max, percent_side or log)", normalization))
+        })?;
+        Ok(())
+    }
+    
+    /// Cuantiza precios y acumula tamaños en punto fijo con `price_decimals` /
+    /// `size_decimals` (global o solo para `symbol`). Limpia el grid afectado.
+    #[pyo3(signature = (price_decimals, size_decimals, symbol=None))]
+    pub fn set_fixed_point(&mut self, price_decimals: u32, size_decimals: u32, symbol: Option<&str>) -> Result<(), ProcessError> {
+        self.fixed_point.set(Some(Precision::new(price_decimals, size_decimals)?), symbol);
+        self.reset_fixed_state(symbol);
+        Ok(())
+    }
+    
+    /// Vuelve a f64 (global o solo para `symbol`); limpia el grid afectado
+    #[pyo3(signature = (symbol=None))]
+    pub fn disable_fixed_point(&mut self, symbol: Option<&str>) {
+        self.fixed_point.set(None, symbol);
+        self.reset_fixed_state(symbol);
+    }
+    
+    /// Precisión (price_decimals, size_decimals) del símbolo; None si acumula en f64
+    pub fn get_fixed_point(&self, symbol: &str) -> Option<(u32, u32)> {
+        self.fixed_point.precision(symbol).map(|p| p.as_tuple())
+    }
+    
+    /// Tiles exactos de (symbol, bucket) como (price_bin, total_size, side) con
+    /// `decimal.Decimal`, ordenados por precio (por bps en modo relativo).
+    /// Vacío si el símbolo acumula en f64 o en modo decay.
+    #[cfg(feature = "python")]
+    pub fn get_tiles_decimal(&self, py: Python<'_>, symbol: &str, bucket_ts: u64) -> PyResult<Vec<(PyObject, PyObject, String)>> {
+        if self.fixed_point.precision(symbol).is_none() || self.half_life_ms.is_some() {
+            return Ok(Vec::new());
+        }
+        let tick = match self.relative_bps {
+            Some(bps) => Fixed::from_f64(bps, MAX_DECIMALS).trimmed(),
+            None => self.fixed_tick(symbol),
+        };
+        let mut cells: Vec<(i64, &'static str, Fixed)> = self.grid.iter()
+            .filter(|e| e.key().0 == symbol && e.key().1 == bucket_ts)
+            .map(|e| (e.key().2, e.key().3, e.value().fixed))
+            .collect();
+        cells.sort_by_key(|(tick, side, _)| (*tick, *side));
         
-        // Calcular bucket actual
-        let bucket_ts = calculate_bucket(snapshot.ts, self.bucket_ms);
+        cells.into_iter()
+            .map(|(index, side, size)| {
+                let price_bin = Fixed::new(index as i128, 0) * tick;
+                Ok((price_bin.to_py_decimal(py)?, size.to_py_decimal(py)?, side.to_string()))
+            })
+            .collect()
+    }
+    
+    /// Procesa un snapshot del libro y calcula heatmap.
+    /// En modo `strict` un libro vacío lanza EmptyBookError; si no, devuelve None
+    #[pyo3(name = "on_snapshot")]
+    pub fn py_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<Option<HeatmapMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_snapshot(snapshot))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Heatmap comprimido de un símbolo en un bucket (None si no hay datos)
+    pub fn get_heatmap(&self, symbol: &str, bucket_ts: u64) -> Option<HeatmapMetrics> {
+        let cells = self.bucket_cells(symbol, bucket_ts);
+        self.compress(symbol, bucket_ts, self.bucket_ms_for(symbol), &cells)
+    }
+    
+    /// Heatmaps de todos los buckets retenidos del símbolo entre from_ts y
+    /// to_ts, en orden temporal (backfill de un chart en una sola llamada).
+    /// Con `bucket_ms` (múltiplo del bucket del símbolo) los buckets se
+    /// agregan sumando sus celdas en buckets más gruesos.
+    #[pyo3(signature = (symbol, from_ts, to_ts, bucket_ms=None))]
+    pub fn get_heatmap_range(&self, symbol: &str, from_ts: u64, to_ts: u64,
+                             bucket_ms: Option<u64>) -> Result<Vec<HeatmapMetrics>, ProcessError> {
+        let base_ms = self.bucket_ms_for(symbol);
+        let target_ms = bucket_ms.unwrap_or(base_ms);
+        if to_ts < from_ts {
+            return Err(ProcessError::invalid("to_ts must be >= from_ts"));
+        }
+        if target_ms == 0 || !target_ms.is_multiple_of(base_ms) {
+            return Err(ProcessError::invalid(
+                format!("bucket_ms must be a multiple of the symbol bucket ({} ms)", base_ms)));
+        }
+        let first_bucket = calculate_bucket(from_ts, target_ms);
+        let last_bucket = calculate_bucket(to_ts, target_ms);
         
-        // Acumular en el grid
-        for bid in &snapshot.bids {
-            let price_bin = quantize_price(bid.price, self.tick_size);
-            let key = (bucket_ts, price_bin.to_string(), "bid".to_string());
-            *self.grid.entry(key).or_insert(0.0) += bid.size;
+        // Celdas de cada bucket retenido dentro del rango
+        let mut buckets: BTreeMap<u64, Vec<(i64, &'static str, Cell)>> = BTreeMap::new();
+        for entry in self.grid.iter() {
+            let (sym, bucket, tick, side) = entry.key();
+            if sym == symbol && (first_bucket..=last_bucket).contains(&calculate_bucket(*bucket, target_ms)) {
+                buckets.entry(*bucket).or_default().push((*tick, *side, *entry.value()));
+            }
         }
         
-        for ask in &snapshot.asks {
-            let price_bin = quantize_price(ask.price, self.tick_size);
-            let key = (bucket_ts, price_bin.to_string(), "ask".to_string());
-            *self.grid.entry(key).or_insert(0.0) += ask.size;
+        // Agregar por bucket de destino (un bucket del símbolo si no hay downsampling)
+        let mut merged: BTreeMap<u64, BTreeMap<(i64, &'static str), f64>> = BTreeMap::new();
+        for (bucket, cells) in buckets {
+            let target = merged.entry(calculate_bucket(bucket, target_ms)).or_default();
+            for (tick, side, size) in self.cell_sizes(cells) {
+                *target.entry((tick, side)).or_default() += size;
+            }
         }
         
-        // Extraer tiles del bucket actual (comprimidos)
-        let mut tiles: Vec<Tile> = Vec::new();
-        let original_count = self.grid.len();
+        Ok(merged.into_iter()
+            .filter_map(|(bucket_ts, cells)| {
+                let cells: Vec<_> = cells.into_iter().map(|((tick, side), size)| (tick, side, size)).collect();
+                self.compress(symbol, bucket_ts, target_ms, &cells)
+            })
+            .collect())
+    }
+    
+    /// Exporta el heatmap de un símbolo como matriz densa (tiempo × precio).
+    /// Filas: buckets de from_bucket a to_bucket; columnas: ticks de price_min a
+    /// price_max (ambos inclusive; en bps respecto al mid en modo relativo).
+    /// `side` = "bid" | "ask" | None (suma de ambos).
+    #[pyo3(signature = (symbol, from_bucket, to_bucket, price_min, price_max, side=None))]
+    pub fn to_matrix(&self, symbol: &str, from_bucket: u64, to_bucket: u64,
+                     price_min: f64, price_max: f64, side: Option<&str>) -> Result<Vec<Vec<f64>>, ProcessError> {
+        if to_bucket < from_bucket || price_max < price_min {
+            return Err(ProcessError::invalid(
+                "to_bucket must be >= from_bucket and price_max >= price_min"));
+        }
         
-        for entry in self.grid.iter() {
-            let ((bucket, price_str, side), size) = (entry.key(), entry.value());
-            if *bucket == bucket_ts {
-                if let Ok(price) = price_str.parse::<f64>() {
-                    // Solo tiles significativos (>= threshold del 1% del max)
-                    tiles.push(Tile {
-                        price_bin: price,
-                        total_size: *size,
-                        side: side.clone(),
-                    });
-                }
+        let bucket_ms = self.bucket_ms_for(symbol);
+        let first_bucket = calculate_bucket(from_bucket, bucket_ms);
+        let last_bucket = calculate_bucket(to_bucket, bucket_ms);
+        let tick_size = self.bin_width(symbol);
+        let min_tick = price_to_tick(price_min, tick_size);
+        let max_tick = price_to_tick(price_max, tick_size);
+        let rows = ((last_bucket - first_bucket) / bucket_ms + 1) as usize;
+        let cols = (max_tick - min_tick + 1) as usize;
+        
+        let cells: Vec<(u64, i64, Cell)> = self.grid.iter()
+            .filter(|e| {
+                let (sym, bucket, tick, cell_side) = e.key();
+                sym == symbol
+                    && (first_bucket..=last_bucket).contains(bucket)
+                    && (min_tick..=max_tick).contains(tick)
+                    && side.is_none_or(|s| s == *cell_side)
+            })
+            .map(|e| (e.key().1, e.key().2, *e.value()))
+            .collect();
+        
+        // En modo decay cada bucket se evalúa en su último update
+        let mut reference_ts: HashMap<u64, u64> = HashMap::new();
+        for (bucket, _, cell) in &cells {
+            let ts = reference_ts.entry(*bucket).or_insert(0);
+            *ts = (*ts).max(cell.last_ts);
+        }
+        
+        let mut matrix = vec![vec![0.0; cols]; rows];
+        for (bucket, tick, cell) in cells {
+            let size = match self.half_life_ms {
+                Some(half_life) => cell.decayed(reference_ts[&bucket], half_life),
+                None => cell.size,
+            };
+            let row = ((bucket - first_bucket) / bucket_ms) as usize;
+            matrix[row][(tick - min_tick) as usize] += size;
+        }
+        Ok(matrix)
+    }
+    
+    /// Memoria aproximada ocupada por el grid (bytes)
+    pub fn memory_usage(&self) -> usize {
+        let entry_size = std::mem::size_of::<GridKey>() + std::mem::size_of::<Cell>();
+        let published_size = std::mem::size_of::<GridKey>() + std::mem::size_of::<f64>();
+        let grid: usize = self.grid.iter()
+            .map(|e| entry_size + e.key().0.capacity())
+            .sum();
+        let published: usize = self.published.iter()
+            .map(|e| published_size + e.key().0.capacity())
+            .sum();
+        grid + published
+    }
+    
+    /// Limpia todos los buckets
+    pub fn reset(&self) {
+        self.grid.clear();
+        self.published.clear();
+        self.current_bucket.clear();
+        self.last_mid.clear();
+    }
+    
+    /// Limpia un bucket específico (de un símbolo o de todos)
+    #[pyo3(signature = (bucket_ts, symbol=None))]
+    pub fn reset_bucket(&self, bucket_ts: u64, symbol: Option<&str>) {
+        self.retain_cells(|k| k.1 != bucket_ts || symbol.is_some_and(|s| k.0 != s));
+    }
+    
+    /// Resetea todos los buckets de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.retain_cells(|k| k.0 != symbol);
+        self.current_bucket.remove(symbol);
+        self.last_mid.remove(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.reset();
+        self.rejections.reset_all();
+    }
+    
+    /// Obtiene solo tiles incrementales: los que cambiaron de tamaño (o son
+    /// nuevos) desde el último mark_published del bucket
+    pub fn get_tile_delta(&self, symbol: &str, bucket_ts: u64) -> Vec<Tile> {
+        self.build_tiles(symbol, &self.bucket_cells(symbol, bucket_ts), |&(tick, side, size)| {
+            let key = (symbol.to_string(), bucket_ts, tick, side);
+            self.published.get(&key).is_none_or(|p| (*p - size).abs() > f64::EPSILON)
+        })
+    }
+    
+    /// Registra el estado actual del bucket como publicado (de un símbolo o de todos)
+    #[pyo3(signature = (bucket_ts, symbol=None))]
+    pub fn mark_published(&self, bucket_ts: u64, symbol: Option<&str>) {
+        let symbols: BTreeSet<String> = match symbol {
+            Some(s) => BTreeSet::from([s.to_string()]),
+            None => self.grid.iter()
+                .filter(|e| e.key().1 == bucket_ts)
+                .map(|e| e.key().0.clone())
+                .collect(),
+        };
+        
+        for sym in symbols {
+            for (tick, side, size) in self.bucket_cells(&sym, bucket_ts) {
+                self.published.insert((sym.clone(), bucket_ts, tick, side), size);
             }
         }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("HeatmapEngine(bucket_ms={}, tick_size={}, entries={})", 
+                self.bucket_ms, self.tick_size, self.grid.len())
+    }
+}
+}
+
+impl HeatmapEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.grid.iter().map(|e| e.key().0.clone()))
+    }
+    
+    /// Procesa un snapshot; si no es válido se descarta y queda contado en las rejections
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Option<HeatmapMetrics> {
+        self.try_on_snapshot(snapshot).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un snapshot; Err si no es válido (también queda contado)
+    pub fn try_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<Option<HeatmapMetrics>, ProcessError> {
+        // En modo relativo hace falta el mid
+        self.rejections.check(validate_snapshot(snapshot, self.relative_bps.is_some()))?;
+        self.rejections.check_timestamp(&snapshot.symbol, snapshot.ts)?;
+        Ok(self.process_snapshot(snapshot))
+    }
+    
+    /// Procesa un snapshot del libro y calcula heatmap
+    fn process_snapshot(&self, snapshot: &BookSnapshot) -> Option<HeatmapMetrics> {
+        // Calcular bucket actual
+        let bucket_ts = calculate_bucket(snapshot.ts, self.bucket_ms_for(&snapshot.symbol));
         
-        // Ordenar por precio
-        tiles.sort_by(|a, b| a.price_bin.partial_cmp(&b.price_bin).unwrap_or(std::cmp::Ordering::Equal));
+        // Rollover a un bucket nuevo: aplicar retención
+        let rolled = {
+            let mut current = self.current_bucket.entry(snapshot.symbol.clone()).or_insert(bucket_ts);
+            let rolled = bucket_ts > *current;
+            if rolled {
+                *current = bucket_ts;
+            }
+            rolled
+        };
+        if rolled {
+            self.evict(&snapshot.symbol, bucket_ts);
+        }
+        
+        // Acumular en el grid
+        let precision = self.fixed_point.precision(&snapshot.symbol);
+        let tick_size = self.tick_sizes.get_tick_size(&snapshot.symbol);
+        let fixed_tick = precision.map(|_| self.fixed_tick(&snapshot.symbol));
+        let mid = self.relative_bps.map(|_| {
+            let mid = (snapshot.bids[0].price + snapshot.asks[0].price) / 2.0;
+            self.last_mid.insert(snapshot.symbol.clone(), mid);
+            mid
+        });
+        // En modo relativo el bin es la distancia al mid en bps; en punto
+        // fijo la división precio / tick es decimal exacta
+        let tick_of = |price: f64| match (self.relative_bps, mid) {
+            (Some(bps), Some(mid)) => price_to_tick((price / mid - 1.0) * 10_000.0, bps),
+            _ => precision.zip(fixed_tick)
+                .and_then(|(p, tick)| p.price(price).div_round(tick))
+                .unwrap_or_else(|| price_to_tick(price, tick_size)),
+        };
+        let amount = |level: &Level| if self.notional { level.price * level.size } else { level.size };
+        for (levels, side) in [(&snapshot.bids, "bid"), (&snapshot.asks, "ask")] {
+            let add = |level: &Level, tick: i64| {
+                let key = (snapshot.symbol.clone(), bucket_ts, tick, side);
+                self.accumulate(key, amount(level), snapshot.ts, precision);
+            };
+            // Caso habitual (ticks absolutos en f64): todo el lado con SIMD,
+            // leyendo los precios de los niveles
+            if self.relative_bps.is_none() && precision.is_none() {
+                simd::for_each_tick(levels, |l| l.price, tick_size, add);
+            } else {
+                levels.iter().for_each(|l| add(l, tick_of(l.price)));
+            }
+        }
+        
+        self.get_heatmap(&snapshot.symbol, bucket_ts)
+    }
+    
+    /// Bucket del símbolo según la configuración (o el global)
+    fn bucket_ms_for(&self, symbol: &str) -> u64 {
+        self.config.as_ref().and_then(|c| c.bucket_ms(symbol)).unwrap_or(self.bucket_ms)
+    }
+    
+    /// Ancho de un bin de precio: `relative_bps` en modo relativo, si no el tick
+    fn bin_width(&self, symbol: &str) -> f64 {
+        self.relative_bps.unwrap_or_else(|| self.tick_sizes.get_tick_size(symbol))
+    }
+    
+    /// Tick size del símbolo como decimal exacto (hasta MAX_DECIMALS)
+    fn fixed_tick(&self, symbol: &str) -> Fixed {
+        Fixed::from_f64(self.tick_sizes.get_tick_size(symbol), MAX_DECIMALS).trimmed()
+    }
+    
+    /// Suma (o decae y suma, en modo half-life) un tamaño en la celda
+    fn accumulate(&self, key: GridKey, size: f64, ts: u64, precision: Option<Precision>) {
+        let mut cell = self.grid.entry(key).or_default();
+        match (self.half_life_ms, precision) {
+            (Some(half_life), _) => cell.size = cell.decayed(ts, half_life) + size,
+            (None, Some(precision)) => {
+                cell.fixed = cell.fixed + precision.size(size);
+                cell.size = cell.fixed.to_f64();
+            }
+            (None, None) => cell.size += size,
+        }
+        cell.last_ts = cell.last_ts.max(ts);
+    }
+    
+    /// Limpia el grid que cambia de representación
+    fn reset_fixed_state(&self, symbol: Option<&str>) {
+        match symbol {
+            Some(symbol) => {
+                self.retain_cells(|k| k.0 != symbol);
+                self.current_bucket.remove(symbol);
+            }
+            None => self.reset(),
+        }
+    }
+    
+    /// Elimina los buckets de un símbolo que exceden la retención configurada
+    fn evict(&self, symbol: &str, bucket_ts: u64) {
+        if let Some(cutoff) = self.retention_ms.and_then(|r| bucket_ts.checked_sub(r)) {
+            self.retain_cells(|k| k.0 != symbol || k.1 > cutoff);
+        }
+        
+        if let Some(max_buckets) = self.max_buckets {
+            let buckets: BTreeSet<u64> = self.grid.iter()
+                .filter(|e| e.key().0 == symbol && e.key().1 < bucket_ts)
+                .map(|e| e.key().1)
+                .collect();
+            // El bucket actual cuenta como uno de los conservados
+            if let Some(&newest_dropped) = buckets.iter().rev().nth(max_buckets - 1) {
+                self.retain_cells(|k| k.0 != symbol || k.1 > newest_dropped);
+            }
+        }
+    }
+    
+    /// Retiene las celdas (y su estado publicado) que cumplen el predicado
+    fn retain_cells(&self, keep: impl Fn(&GridKey) -> bool) {
+        self.grid.retain(|k, _| keep(k));
+        self.published.retain(|k, _| keep(k));
+    }
+    
+    /// Comprime las celdas (tick, side, size) de un bucket en métricas:
+    /// umbral, top-K y compression_ratio (None si no hay celdas)
+    fn compress(&self, symbol: &str, bucket_ts: u64, bucket_ms: u64,
+                cells: &[(i64, &'static str, f64)]) -> Option<HeatmapMetrics> {
+        let mut tiles = self.collect_tiles(symbol, cells);
+        if tiles.is_empty() {
+            return None;
+        }
+        let original_count = tiles.len();
         
         // Calcular max_sz y compression ratio
         let max_sz = tiles.iter().map(|t| t.total_size).fold(0.0, f64::max);
-        let threshold = max_sz * 0.01; // Filtrar tiles menores al 1% del max
+        let threshold = max_sz * self.min_tile_pct / 100.0;
         tiles.retain(|t| t.total_size >= threshold);
+        if let Some(k) = self.top_k {
+            tiles = top_k_per_side(tiles, k);
+        }
         
-        let compression_ratio = if tiles.len() > 0 {
+        let compression_ratio = if !tiles.is_empty() {
             original_count as f64 / tiles.len() as f64
         } else {
             1.0
@@ -97,47 +766,113 @@ impl HeatmapEngine {
         
         Some(HeatmapMetrics {
             bucket_ts,
-            bucket_ms: self.bucket_ms,
+            bucket_ms,
             tiles,
             max_sz,
             compression_ratio,
+            symbol: symbol.to_string(),
+            normalization: self.normalization.as_str().to_string(),
+            mid: self.relative_bps.and_then(|_| self.last_mid.get(symbol).map(|m| *m)),
+            notional: self.notional,
         })
     }
     
-    /// Limpia todos los buckets
-    fn reset(&self) {
-        self.grid.clear();
+    /// Celdas (tick, side, size) de (symbol, bucket). En modo decay todas se
+    /// decaen hasta el último update del bucket.
+    fn bucket_cells(&self, symbol: &str, bucket_ts: u64) -> Vec<(i64, &'static str, f64)> {
+        let cells: Vec<(i64, &'static str, Cell)> = self.grid.iter()
+            .filter(|e| e.key().0 == symbol && e.key().1 == bucket_ts)
+            .map(|e| (e.key().2, e.key().3, *e.value()))
+            .collect();
+        self.cell_sizes(cells)
     }
     
-    /// Limpia un bucket específico
-    fn reset_bucket(&self, bucket_ts: u64) {
-        self.grid.retain(|k, _| k.0 != bucket_ts);
+    /// Tamaño de las celdas de un bucket (decaídas en modo decay)
+    fn cell_sizes(&self, cells: Vec<(i64, &'static str, Cell)>) -> Vec<(i64, &'static str, f64)> {
+        let reference_ts = cells.iter().map(|(_, _, c)| c.last_ts).max().unwrap_or(0);
+        
+        cells.into_iter()
+            .map(|(tick, side, cell)| {
+                let size = match self.half_life_ms {
+                    Some(half_life) => cell.decayed(reference_ts, half_life),
+                    None => cell.size,
+                };
+                (tick, side, size)
+            })
+            .collect()
     }
     
-    /// Obtiene solo tiles incrementales (delta desde último publish)
-    fn get_tile_delta(&self, bucket_ts: u64) -> Vec<Tile> {
-        let mut tiles: Vec<Tile> = Vec::new();
+    /// Tiles sin filtrar de las celdas de un bucket, ordenados por precio
+    fn collect_tiles(&self, symbol: &str, cells: &[(i64, &'static str, f64)]) -> Vec<Tile> {
+        self.build_tiles(symbol, cells, |_| true)
+    }
+    
+    /// Tiles de las celdas de un bucket que cumplen el predicado, ordenados
+    /// por precio. La normalización usa todas las celdas del bucket.
+    fn build_tiles(&self, symbol: &str, cells: &[(i64, &'static str, f64)],
+                   keep: impl Fn(&(i64, &'static str, f64)) -> bool) -> Vec<Tile> {
+        let tick_size = self.bin_width(symbol);
+        let max = cells.iter().map(|c| c.2).fold(0.0, f64::max);
+        let side_total = |side: &str| cells.iter().filter(|c| c.1 == side).map(|c| c.2).sum::<f64>();
+        let (bid_total, ask_total) = (side_total("bid"), side_total("ask"));
         
-        for entry in self.grid.iter() {
-            let ((bucket, price_str, side), size) = (entry.key(), entry.value());
-            if *bucket == bucket_ts {
-                if let Ok(price) = price_str.parse::<f64>() {
-                    tiles.push(Tile {
-                        price_bin: price,
-                        total_size: *size,
-                        side: side.clone(),
-                    });
-                }
-            }
-        }
+        let mut tiles: Vec<Tile> = cells.iter()
+            .filter(|c| keep(c))
+            .map(|&(tick, side, size)| Tile {
+                price_bin: tick_to_price(tick, tick_size),
+                total_size: size,
+                side: side.to_string(),
+                value: self.normalization.apply(size, max, if side == "bid" { bid_total } else { ask_total }),
+            })
+            .collect();
         
         tiles.sort_by(|a, b| a.price_bin.partial_cmp(&b.price_bin).unwrap_or(std::cmp::Ordering::Equal));
         tiles
     }
+}
+
+/// Los K tiles de mayor tamaño de cada lado, en el orden original
+fn top_k_per_side(tiles: Vec<Tile>, k: usize) -> Vec<Tile> {
+    let mut ranked: Vec<usize> = (0..tiles.len()).collect();
+    ranked.sort_by(|&a, &b| tiles[b].total_size.total_cmp(&tiles[a].total_size));
     
-    fn __repr__(&self) -> String {
-        format!("HeatmapEngine(bucket_ms={}, tick_size={}, entries={})", 
-                self.bucket_ms, self.tick_size, self.grid.len())
+    let mut kept_by_side: HashMap<&str, usize> = HashMap::new();
+    let mut keep = vec![false; tiles.len()];
+    for index in ranked {
+        let kept = kept_by_side.entry(tiles[index].side.as_str()).or_default();
+        if *kept < k {
+            *kept += 1;
+            keep[index] = true;
+        }
+    }
+    tiles.into_iter()
+        .zip(keep)
+        .filter_map(|(tile, keep)| keep.then_some(tile))
+        .collect()
+}
+
+impl Default for HeatmapEngine {
+    fn default() -> Self {
+        Self {
+            bucket_ms: 1000,
+            tick_size: 0.01,
+            tick_sizes: TickSizeRegistry::default(),
+            max_buckets: None,
+            retention_ms: None,
+            half_life_ms: None,
+            relative_bps: None,
+            notional: false,
+            normalization: Normalization::default(),
+            min_tile_pct: DEFAULT_MIN_TILE_PCT,
+            top_k: None,
+            grid: Arc::new(DashMap::new()),
+            published: Arc::new(DashMap::new()),
+            current_bucket: Arc::new(DashMap::new()),
+            last_mid: Arc::new(DashMap::new()),
+            config: None,
+            rejections: Rejections::default(),
+            fixed_point: FixedPointConfig::default(),
+        }
     }
 }
 
@@ -145,6 +880,8 @@ impl HeatmapEngine {
 mod tests {
     use super::*;
     use crate::types::{BookSnapshot, Level};
+    #[cfg(feature = "python")]
+    use crate::utils::with_py;
 
     fn create_test_snapshot() -> BookSnapshot {
         BookSnapshot {
@@ -163,14 +900,14 @@ mod tests {
 
     #[test]
     fn test_heatmap_engine_creation() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         assert_eq!(engine.bucket_ms, 1000);
         assert_eq!(engine.tick_size, 0.01);
     }
 
     #[test]
     fn test_heatmap_empty_snapshot() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         let snapshot = BookSnapshot {
             ts: 1234567890,
             symbol: "AAPL".to_string(),
@@ -184,7 +921,7 @@ mod tests {
 
     #[test]
     fn test_heatmap_single_snapshot() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         let snapshot = create_test_snapshot();
         
         let result = engine.on_snapshot(&snapshot);
@@ -193,12 +930,12 @@ mod tests {
         let metrics = result.unwrap();
         assert_eq!(metrics.bucket_ts, 1234567000); // Bucket de 1000ms
         assert_eq!(metrics.bucket_ms, 1000);
-        assert!(metrics.tiles.len() > 0);
+        assert!(!metrics.tiles.is_empty());
     }
 
     #[test]
     fn test_heatmap_compression() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         let snapshot = create_test_snapshot();
         
         let result = engine.on_snapshot(&snapshot);
@@ -211,7 +948,7 @@ mod tests {
 
     #[test]
     fn test_heatmap_multiple_snapshots() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         
         let snapshot1 = BookSnapshot {
             ts: 1234567890,
@@ -238,7 +975,7 @@ mod tests {
 
     #[test]
     fn test_heatmap_tile_ordering() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         let snapshot = create_test_snapshot();
         
         let result = engine.on_snapshot(&snapshot);
@@ -253,7 +990,7 @@ mod tests {
 
     #[test]
     fn test_heatmap_reset() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         let snapshot = create_test_snapshot();
         
         engine.on_snapshot(&snapshot);
@@ -268,7 +1005,7 @@ mod tests {
 
     #[test]
     fn test_heatmap_reset_bucket() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         
         let snapshot1 = BookSnapshot {
             ts: 1234567890,
@@ -278,7 +1015,7 @@ mod tests {
         };
         
         engine.on_snapshot(&snapshot1);
-        engine.reset_bucket(1234567000);
+        engine.reset_bucket(1234567000, None);
         
         // El bucket debería estar limpio ahora
         let snapshot2 = BookSnapshot {
@@ -294,10 +1031,10 @@ mod tests {
 
     #[test]
     fn test_heatmap_configuration() {
-        let mut engine = HeatmapEngine::new();
+        let mut engine = HeatmapEngine::default();
         
-        engine.set_bucket_ms(5000);
-        engine.set_tick_size(0.05);
+        engine.set_bucket_ms(5000).unwrap();
+        engine.set_tick_size(0.05).unwrap();
         
         assert_eq!(engine.bucket_ms, 5000);
         assert_eq!(engine.tick_size, 0.05);
@@ -305,7 +1042,7 @@ mod tests {
 
     #[test]
     fn test_heatmap_different_buckets() {
-        let engine = HeatmapEngine::new();
+        let engine = HeatmapEngine::default();
         
         let snapshot1 = BookSnapshot {
             ts: 1234567890,
@@ -328,4 +1065,412 @@ mod tests {
         assert!(result2.is_some());
         assert_ne!(result1.unwrap().bucket_ts, result2.unwrap().bucket_ts);
     }
-}
\ No newline at end of file
+
+    fn snapshot_for(symbol: &str, ts: u64, bid_size: f64) -> BookSnapshot {
+        BookSnapshot {
+            ts,
+            symbol: symbol.to_string(),
+            bids: vec![Level { price: 149.99, size: bid_size }],
+            asks: vec![Level { price: 150.01, size: 100.0 }],
+        }
+    }
+
+    #[test]
+    fn test_heatmap_constructor_and_regrid() {
+        let mut engine = HeatmapEngine::new(5_000, 0.05, Some(3)).unwrap();
+        assert_eq!((engine.bucket_ms, engine.tick_size, engine.max_buckets), (5_000, 0.05, Some(3)));
+        assert_eq!(engine.get_tick_size("AAPL"), 0.05);
+        assert!(HeatmapEngine::new(0, 0.01, None).is_err());
+        assert!(HeatmapEngine::new(1_000, 0.0, None).is_err());
+        assert!(HeatmapEngine::new(1_000, 0.01, Some(0)).is_err());
+        
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        engine.set_bucket_ms(5_000).unwrap();
+        assert!(engine.get_heatmap("AAPL", 0).is_some());
+        // Otro bucket con datos: se limpia el grid en vez de mezclar rejillas
+        engine.set_bucket_ms(1_000).unwrap();
+        assert!(engine.get_heatmap("AAPL", 0).is_none());
+        assert!(engine.get_heatmap("AAPL", 1_000).is_none());
+        assert!(engine.set_bucket_ms(0).is_err());
+    }
+
+    #[test]
+    fn test_heatmap_half_life_change_clears_grid() {
+        let mut engine = HeatmapEngine::default();
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        engine.set_half_life_ms(None).unwrap();
+        assert!(engine.get_heatmap("AAPL", 1_000).is_some());
+        
+        // Las celdas sumadas sin decay no son comparables con las decaídas
+        engine.set_half_life_ms(Some(500)).unwrap();
+        assert!(engine.get_heatmap("AAPL", 1_000).is_none());
+        engine.on_snapshot(&snapshot_for("AAPL", 2_000, 100.0));
+        engine.set_half_life_ms(Some(500)).unwrap();
+        assert!(engine.get_heatmap("AAPL", 2_000).is_some());
+        assert!(engine.set_half_life_ms(Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_heatmap_symbols_isolated() {
+        let engine = HeatmapEngine::default();
+        
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        let msft = engine.on_snapshot(&snapshot_for("MSFT", 1_000, 40.0)).unwrap();
+        
+        assert_eq!(msft.symbol, "MSFT");
+        let bid = msft.tiles.iter().find(|t| t.side == "bid").unwrap();
+        assert_eq!(bid.total_size, 40.0);
+        
+        let aapl = engine.get_heatmap("AAPL", 1_000).unwrap();
+        assert_eq!(aapl.symbol, "AAPL");
+        assert_eq!(aapl.tiles.iter().find(|t| t.side == "bid").unwrap().total_size, 100.0);
+        assert!(engine.get_heatmap("TSLA", 1_000).is_none());
+    }
+
+    #[test]
+    fn test_heatmap_reset_symbol() {
+        let engine = HeatmapEngine::default();
+        
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        engine.on_snapshot(&snapshot_for("MSFT", 1_000, 40.0));
+        engine.reset_symbol("AAPL");
+        
+        assert!(engine.get_heatmap("AAPL", 1_000).is_none());
+        assert!(engine.get_heatmap("MSFT", 1_000).is_some());
+        
+        engine.reset_bucket(1_000, Some("MSFT"));
+        assert!(engine.get_heatmap("MSFT", 1_000).is_none());
+    }
+
+    #[test]
+    fn test_heatmap_max_buckets_eviction() {
+        let mut engine = HeatmapEngine::default();
+        engine.set_max_buckets(Some(2)).unwrap();
+        assert!(engine.set_max_buckets(Some(0)).is_err());
+        
+        for ts in [1_000, 2_000, 3_000, 4_000] {
+            engine.on_snapshot(&snapshot_for("AAPL", ts, 100.0));
+        }
+        engine.on_snapshot(&snapshot_for("MSFT", 1_000, 100.0));
+        
+        assert!(engine.get_heatmap("AAPL", 2_000).is_none());
+        assert!(engine.get_heatmap("AAPL", 3_000).is_some());
+        assert!(engine.get_heatmap("AAPL", 4_000).is_some());
+        // La retención es por símbolo
+        assert!(engine.get_heatmap("MSFT", 1_000).is_some());
+    }
+
+    #[test]
+    fn test_heatmap_retention_ms_eviction() {
+        let mut engine = HeatmapEngine::default();
+        engine.set_retention_ms(Some(2_000)).unwrap();
+        
+        for ts in [1_000, 2_000, 3_000, 4_000] {
+            engine.on_snapshot(&snapshot_for("AAPL", ts, 100.0));
+        }
+        
+        // Se conservan buckets con 4000 - bucket < 2000
+        assert!(engine.get_heatmap("AAPL", 2_000).is_none());
+        assert!(engine.get_heatmap("AAPL", 3_000).is_some());
+        assert!(engine.get_heatmap("AAPL", 4_000).is_some());
+    }
+
+    #[test]
+    fn test_heatmap_memory_usage() {
+        let mut engine = HeatmapEngine::default();
+        assert_eq!(engine.memory_usage(), 0);
+        
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        let one_bucket = engine.memory_usage();
+        assert!(one_bucket > 0);
+        
+        engine.set_max_buckets(Some(1)).unwrap();
+        engine.on_snapshot(&snapshot_for("AAPL", 2_000, 100.0));
+        assert!(engine.memory_usage() <= one_bucket);
+        assert!(engine.get_heatmap("AAPL", 1_000).is_none());
+    }
+
+    #[test]
+    fn test_heatmap_decay_mode() {
+        let mut engine = HeatmapEngine::default();
+        engine.set_bucket_ms(10_000).unwrap();
+        engine.set_half_life_ms(Some(1_000)).unwrap();
+        assert!(engine.set_half_life_ms(Some(0)).is_err());
+        
+        // Nivel fijo en dos snapshots separados un half-life: 100·0.5 + 100
+        engine.on_snapshot(&snapshot_for("AAPL", 0, 100.0));
+        let metrics = engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0)).unwrap();
+        let bid = metrics.tiles.iter().find(|t| t.side == "bid").unwrap();
+        assert!((bid.total_size - 150.0).abs() < 1e-9);
+        
+        // Un nivel que desaparece sigue decayendo respecto al último update
+        let flashed = BookSnapshot {
+            ts: 3_000,
+            symbol: "AAPL".to_string(),
+            bids: vec![],
+            asks: vec![Level { price: 150.05, size: 10.0 }],
+        };
+        let metrics = engine.on_snapshot(&flashed).unwrap();
+        let bid = metrics.tiles.iter().find(|t| t.side == "bid").unwrap();
+        assert!((bid.total_size - 37.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_heatmap_to_matrix() {
+        let engine = HeatmapEngine::default();
+        
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        engine.on_snapshot(&snapshot_for("AAPL", 3_000, 40.0));
+        engine.on_snapshot(&snapshot_for("MSFT", 1_000, 999.0));
+        
+        let matrix = engine.to_matrix("AAPL", 1_000, 3_000, 149.99, 150.01, None).unwrap();
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix[0], vec![100.0, 0.0, 100.0]);
+        assert_eq!(matrix[1], vec![0.0, 0.0, 0.0]);
+        assert_eq!(matrix[2], vec![40.0, 0.0, 100.0]);
+        
+        let bids = engine.to_matrix("AAPL", 1_000, 1_000, 149.99, 150.01, Some("bid")).unwrap();
+        assert_eq!(bids, vec![vec![100.0, 0.0, 0.0]]);
+        
+        assert!(engine.to_matrix("AAPL", 3_000, 1_000, 149.99, 150.01, None).is_err());
+    }
+
+    #[test]
+    fn test_heatmap_tile_delta() {
+        let engine = HeatmapEngine::default();
+        
+        engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0));
+        // Sin publicar: el delta es el bucket completo
+        assert_eq!(engine.get_tile_delta("AAPL", 1_000).len(), 2);
+        
+        engine.mark_published(1_000, None);
+        assert!(engine.get_tile_delta("AAPL", 1_000).is_empty());
+        
+        // Solo cambian el bid existente y un ask nuevo
+        let update = BookSnapshot {
+            ts: 1_500,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 149.99, size: 50.0 }],
+            asks: vec![Level { price: 150.02, size: 10.0 }],
+        };
+        engine.on_snapshot(&update);
+        
+        let delta = engine.get_tile_delta("AAPL", 1_000);
+        assert_eq!(delta.len(), 2);
+        assert_eq!(delta[0].total_size, 150.0);
+        assert_eq!(delta[1].side, "ask");
+        assert_eq!(delta[1].total_size, 10.0);
+        
+        engine.mark_published(1_000, Some("AAPL"));
+        assert!(engine.get_tile_delta("AAPL", 1_000).is_empty());
+    }
+
+    #[test]
+    fn test_heatmap_per_symbol_tick_size() {
+        let engine = HeatmapEngine::default();
+        engine.set_symbol_tick_size("BTCUSDT", 0.1).unwrap();
+        assert!(engine.set_symbol_tick_size("BTCUSDT", -1.0).is_err());
+        
+        let btc = BookSnapshot {
+            ts: 1_000,
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![Level { price: 65000.04, size: 1.0 }, Level { price: 64999.98, size: 2.0 }],
+            asks: vec![Level { price: 65000.12, size: 1.0 }],
+        };
+        let metrics = engine.on_snapshot(&btc).unwrap();
+        
+        // Ambos bids caen en el tick 65000.0
+        let bids: Vec<&Tile> = metrics.tiles.iter().filter(|t| t.side == "bid").collect();
+        assert_eq!(bids.len(), 1);
+        assert!((bids[0].price_bin - 65000.0).abs() < 1e-6);
+        assert_eq!(bids[0].total_size, 3.0);
+        
+        // Los demás símbolos usan el tick por defecto
+        assert_eq!(engine.get_tick_size("AAPL"), 0.01);
+        let aapl = engine.on_snapshot(&snapshot_for("AAPL", 1_000, 100.0)).unwrap();
+        assert!((aapl.tiles[0].price_bin - 149.99).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_heatmap_fixed_point_ticks_and_sizes() {
+        let snapshot = BookSnapshot {
+            ts: 1_000,
+            symbol: "ETH".to_string(),
+            bids: vec![Level { price: 0.15, size: 0.1 }, Level { price: 0.15, size: 0.2 }],
+            asks: vec![Level { price: 0.35, size: 1.0 }],
+        };
+        let mut engine = HeatmapEngine::default();
+        engine.set_tick_size(0.1).unwrap();
+        
+        // En f64 0.15 / 0.1 = 1.4999… cae en el tick 1 y 0.1 + 0.2 != 0.3
+        let tiles = engine.on_snapshot(&snapshot).unwrap().tiles;
+        assert!((tiles[0].price_bin - 0.1).abs() < 1e-12);
+        assert_ne!(tiles[0].total_size, 0.3);
+        
+        engine.set_fixed_point(2, 8, None).unwrap();
+        assert_eq!(engine.get_fixed_point("ETH"), Some((2, 8)));
+        let tiles = engine.on_snapshot(&snapshot).unwrap().tiles;
+        assert!((tiles[0].price_bin - 0.2).abs() < 1e-12);
+        assert_eq!(tiles[0].total_size, 0.3);
+        
+        #[cfg(feature = "python")]
+        {
+            let exact: Vec<(String, String, String)> = with_py(|py| {
+                engine.get_tiles_decimal(py, "ETH", 1_000).unwrap().into_iter()
+                    .map(|(price, size, side)| (price.bind(py).str().unwrap().to_string(), size.bind(py).str().unwrap().to_string(), side))
+                    .collect()
+            });
+            assert_eq!(exact[0], ("0.2".to_string(), "0.30000000".to_string(), "bid".to_string()));
+            assert_eq!(exact[1].0, "0.4");
+            assert!(with_py(|py| engine.get_tiles_decimal(py, "BTC", 1_000).unwrap()).is_empty());
+        }
+    }
+    
+    #[test]
+    fn test_heatmap_relative_bps_bins() {
+        let mut engine = HeatmapEngine::default();
+        assert!(engine.set_relative_bps(Some(0.0)).is_err());
+        engine.set_relative_bps(Some(10.0)).unwrap();
+        
+        let snapshot = |ts: u64, mid: f64| BookSnapshot {
+            ts,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: mid * 0.999, size: 5.0 }],
+            asks: vec![Level { price: mid * 1.001, size: 7.0 }],
+        };
+        engine.on_snapshot(&snapshot(1_000, 100.0));
+        // El precio se duplica pero los niveles siguen a ±10 bps del mid
+        let metrics = engine.on_snapshot(&snapshot(1_500, 200.0)).unwrap();
+        assert_eq!(metrics.mid, Some(200.0));
+        assert_eq!(metrics.tiles.len(), 2);
+        assert!((metrics.tiles[0].price_bin + 10.0).abs() < 1e-9);
+        assert_eq!((metrics.tiles[0].side.as_str(), metrics.tiles[0].total_size), ("bid", 10.0));
+        assert!((metrics.tiles[1].price_bin - 10.0).abs() < 1e-9);
+        assert_eq!(engine.to_matrix("AAPL", 1_000, 1_000, -20.0, 20.0, None).unwrap(),
+                   vec![vec![0.0, 10.0, 0.0, 14.0, 0.0]]);
+        
+        // Sin mid no hay bins relativos
+        let one_sided = BookSnapshot { asks: vec![], ..snapshot(2_000, 100.0) };
+        assert!(engine.on_snapshot(&one_sided).is_none());
+        
+        // Volver a bins absolutos limpia el grid
+        engine.set_relative_bps(None).unwrap();
+        let metrics = engine.on_snapshot(&snapshot(3_000, 100.0)).unwrap();
+        assert_eq!(metrics.mid, None);
+        assert!((metrics.tiles[0].price_bin - 99.9).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_heatmap_normalization_modes() {
+        let mut engine = HeatmapEngine::default();
+        assert!(engine.set_normalization("zscore").is_err());
+        let snapshot = BookSnapshot {
+            ts: 1_000,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 149.99, size: 100.0 }, Level { price: 149.98, size: 300.0 }],
+            asks: vec![Level { price: 150.01, size: 200.0 }],
+        };
+        let values = |metrics: HeatmapMetrics| -> Vec<f64> { metrics.tiles.iter().map(|t| t.value).collect() };
+        
+        // Raw: value == total_size
+        let metrics = engine.on_snapshot(&snapshot).unwrap();
+        assert_eq!(metrics.normalization, "raw");
+        assert_eq!(values(metrics), vec![300.0, 100.0, 200.0]);
+        
+        engine.set_normalization("percent_max").unwrap();
+        let pct = values(engine.get_heatmap("AAPL", 1_000).unwrap());
+        assert_eq!(pct[0], 100.0);
+        assert!((pct[1] - 100.0 / 3.0).abs() < 1e-9);
+        assert!((pct[2] - 200.0 / 3.0).abs() < 1e-9);
+        
+        engine.set_normalization("percent_side").unwrap();
+        assert_eq!(values(engine.get_heatmap("AAPL", 1_000).unwrap()), vec![75.0, 25.0, 100.0]);
+        // El delta se normaliza con los totales de todo el bucket
+        engine.mark_published(1_000, None);
+        let update = BookSnapshot { asks: vec![], ..snapshot.clone() };
+        engine.on_snapshot(&BookSnapshot { bids: vec![Level { price: 149.99, size: 400.0 }], ..update });
+        let delta = engine.get_tile_delta("AAPL", 1_000);
+        assert_eq!(delta.len(), 1);
+        assert_eq!((delta[0].total_size, delta[0].value), (500.0, 62.5));
+        
+        engine.set_normalization("LOG").unwrap();
+        assert_eq!(engine.normalization(), "log");
+        let tiles = engine.get_heatmap("AAPL", 1_000).unwrap().tiles;
+        assert!((tiles[0].value - 301f64.ln()).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_heatmap_threshold_and_top_k() {
+        let mut engine = HeatmapEngine::default();
+        assert!(engine.set_min_tile_pct(101.0).is_err());
+        assert!(engine.set_top_k(Some(0)).is_err());
+        let level = |price: f64, size: f64| Level { price, size };
+        engine.on_snapshot(&BookSnapshot {
+            ts: 1_000,
+            symbol: "AAPL".to_string(),
+            bids: vec![level(100.0, 1000.0), level(99.99, 5.0), level(99.98, 300.0), level(99.97, 200.0)],
+            asks: vec![level(100.01, 50.0), level(100.02, 400.0)],
+        });
+        
+        // Umbral por defecto: 99.99 queda por debajo del 1% del máximo
+        let metrics = engine.get_heatmap("AAPL", 1_000).unwrap();
+        assert_eq!(metrics.tiles.len(), 5);
+        assert!((metrics.compression_ratio - 1.2).abs() < 1e-12);
+        
+        engine.set_min_tile_pct(0.0).unwrap();
+        assert_eq!(engine.get_heatmap("AAPL", 1_000).unwrap().compression_ratio, 1.0);
+        
+        engine.set_top_k(Some(1)).unwrap();
+        let metrics = engine.get_heatmap("AAPL", 1_000).unwrap();
+        let kept: Vec<(f64, &str)> = metrics.tiles.iter().map(|t| (t.total_size, t.side.as_str())).collect();
+        assert_eq!(kept, vec![(1000.0, "bid"), (400.0, "ask")]);
+        assert_eq!(metrics.compression_ratio, 3.0);
+        
+        // Umbral y top-K se combinan
+        engine.set_top_k(Some(2)).unwrap();
+        engine.set_min_tile_pct(50.0).unwrap();
+        let metrics = engine.get_heatmap("AAPL", 1_000).unwrap();
+        assert_eq!(metrics.tiles.len(), 1);
+        assert_eq!(metrics.compression_ratio, 6.0);
+    }
+    
+    #[test]
+    fn test_heatmap_range_query() {
+        let engine = HeatmapEngine::default();
+        for (ts, size) in [(1_000, 10.0), (2_000, 20.0), (3_000, 30.0), (4_500, 40.0)] {
+            engine.on_snapshot(&snapshot_for("AAPL", ts, size));
+        }
+        engine.on_snapshot(&snapshot_for("MSFT", 2_000, 100.0));
+        assert!(engine.get_heatmap_range("AAPL", 2_000, 1_000, None).is_err());
+        assert!(engine.get_heatmap_range("AAPL", 0, 5_000, Some(1_500)).is_err());
+        
+        // El bucket que contiene from_ts también entra
+        let range = engine.get_heatmap_range("AAPL", 1_500, 3_000, None).unwrap();
+        let buckets: Vec<u64> = range.iter().map(|m| m.bucket_ts).collect();
+        assert_eq!(buckets, vec![1_000, 2_000, 3_000]);
+        assert!(range.iter().all(|m| m.symbol == "AAPL" && m.bucket_ms == 1_000));
+        
+        // Downsampling a buckets de 2 s: las celdas se suman
+        let range = engine.get_heatmap_range("AAPL", 0, 4_999, Some(2_000)).unwrap();
+        let buckets: Vec<u64> = range.iter().map(|m| m.bucket_ts).collect();
+        assert_eq!(buckets, vec![0, 2_000, 4_000]);
+        assert_eq!(range[1].bucket_ms, 2_000);
+        let bid = range[1].tiles.iter().find(|t| t.side == "bid").unwrap();
+        assert_eq!(bid.total_size, 50.0);
+        assert_eq!(range[1].max_sz, 200.0);
+    }
+    
+    #[test]
+    fn test_heatmap_notional() {
+        let mut engine = HeatmapEngine::default();
+        engine.set_min_tile_pct(0.0).unwrap();
+        engine.on_snapshot(&create_test_snapshot());
+        engine.set_notional(true);
+        assert!(engine.get_heatmap("AAPL", 1_234_567_000).is_none());
+        
+        let metrics = engine.on_snapshot(&create_test_snapshot()).unwrap();
+        assert!(metrics.notional);
+        let bid = metrics.tiles.iter().find(|t| t.side == "bid" && (t.price_bin - 149.98).abs() < 1e-9).unwrap();
+        assert!((bid.total_size - 149.98 * 200.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,230 @@
+//! # Iceberg Detector
+//! 
+//! Cruza trades con snapshots del libro para detectar niveles que se
+//! reponen repetidamente tras ser ejecutados (volumen ejecutado muy por
+//! encima del tamaño mostrado).
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::types::{Trade, BookSnapshot, Level, IcebergDetection};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Estado de un nivel de precio visible en el libro
+#[derive(Clone, Debug, Default)]
+struct LevelState {
+    price: f64,
+    displayed: f64,
+    max_displayed: f64,
+    executed: f64,
+    executed_since_snapshot: f64,
+    reloads: u32,
+    reported: bool,
+}
+
+/// Clave de nivel: (es_bid, índice de tick)
+type LevelKey = (bool, i64);
+
+py_class! {
+/// Detector de icebergs por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct IcebergDetector {
+    /// Ratio mínimo ejecutado / máximo mostrado para marcar un iceberg
+    #[pyo3(get)]
+    pub min_ratio: f64,
+    /// Reposiciones mínimas observadas
+    #[pyo3(get)]
+    pub min_reloads: u32,
+    #[pyo3(get)]
+    pub tick_size: f64,
+    levels: Arc<DashMap<String, HashMap<LevelKey, LevelState>>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl IcebergDetector {
+    #[new]
+    #[pyo3(signature = (min_ratio=2.0, min_reloads=2, tick_size=0.01))]
+    pub fn new(min_ratio: f64, min_reloads: u32, tick_size: f64) -> Result<Self, ProcessError> {
+        if !(min_ratio.is_finite() && min_ratio > 0.0 && tick_size.is_finite() && tick_size > 0.0) {
+            return Err(ProcessError::invalid(
+                "min_ratio and tick_size must be positive"));
+        }
+        Ok(Self {
+            min_ratio,
+            min_reloads,
+            tick_size,
+            levels: Arc::new(DashMap::new()),
+        })
+    }
+    
+    /// Atribuye el volumen del trade al nivel visible con el mismo precio
+    pub fn on_trade(&self, trade: &Trade) {
+        if trade.price <= 0.0 || trade.size <= 0.0 {
+            return;
+        }
+        let Some(mut levels) = self.levels.get_mut(&trade.symbol) else { return };
+        let tick = self.tick(trade.price);
+        for is_bid in [true, false] {
+            if let Some(level) = levels.get_mut(&(is_bid, tick)) {
+                level.executed += trade.size;
+                level.executed_since_snapshot += trade.size;
+                return;
+            }
+        }
+    }
+    
+    /// Actualiza los niveles con el snapshot y devuelve las nuevas detecciones
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IcebergDetection> {
+        let mut levels = self.levels.entry(snapshot.symbol.clone()).or_default();
+        // Se reconstruye desde el snapshot: los niveles que desaparecen se descartan
+        let previous = std::mem::take(&mut *levels);
+        
+        let sides: [(bool, &Vec<Level>); 2] = [(true, &snapshot.bids), (false, &snapshot.asks)];
+        for (is_bid, book_side) in sides {
+            for level in book_side.iter().filter(|l| l.price > 0.0 && l.size > 0.0) {
+                let key = (is_bid, self.tick(level.price));
+                let state = match previous.get(&key) {
+                    Some(prev) => {
+                        let mut state = prev.clone();
+                        // Sin reposición el tamaño debería haber bajado lo ejecutado
+                        let expected = prev.displayed - prev.executed_since_snapshot;
+                        if prev.executed_since_snapshot > 0.0 && level.size > expected + f64::EPSILON {
+                            state.reloads += 1;
+                        }
+                        state.displayed = level.size;
+                        state.max_displayed = state.max_displayed.max(level.size);
+                        state.executed_since_snapshot = 0.0;
+                        state
+                    }
+                    None => LevelState {
+                        price: level.price,
+                        displayed: level.size,
+                        max_displayed: level.size,
+                        ..Default::default()
+                    },
+                };
+                levels.insert(key, state);
+            }
+        }
+        
+        let mut detections = Vec::new();
+        for ((is_bid, _), state) in levels.iter_mut() {
+            if state.reported || state.reloads < self.min_reloads {
+                continue;
+            }
+            if state.executed < self.min_ratio * state.max_displayed {
+                continue;
+            }
+            state.reported = true;
+            detections.push(IcebergDetection {
+                ts: snapshot.ts,
+                symbol: snapshot.symbol.clone(),
+                side: if *is_bid { "bid" } else { "ask" }.to_string(),
+                price: state.price,
+                executed_volume: state.executed,
+                displayed_size: state.displayed,
+                estimated_hidden_size: (state.executed - state.max_displayed).max(0.0),
+                reloads: state.reloads,
+                // Más reposiciones => más confianza (tiende a 1)
+                confidence: state.reloads as f64 / (state.reloads as f64 + 1.0),
+            });
+        }
+        detections.sort_by(|a, b| a.price.total_cmp(&b.price));
+        detections
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.levels.remove(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.levels.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("IcebergDetector(min_ratio={}, min_reloads={}, symbols={})",
+                self.min_ratio, self.min_reloads, self.levels.len())
+    }
+}
+}
+
+impl IcebergDetector {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.levels.len()
+    }
+    
+    fn tick(&self, price: f64) -> i64 {
+        (price / self.tick_size).round() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(ts: u64, ask_size: f64) -> BookSnapshot {
+        BookSnapshot::new(ts, "AAPL".to_string(),
+            vec![Level::new(99.99, 50.0)],
+            vec![Level::new(100.00, ask_size)])
+    }
+
+    fn buy(ts: u64, size: f64) -> Trade {
+        let mut trade = Trade::new(ts, 100.00, size, "AAPL".to_string());
+        trade.side = Some("BUY".to_string());
+        trade
+    }
+
+    #[test]
+    fn test_iceberg_detector_validation() {
+        assert!(IcebergDetector::new(0.0, 2, 0.01).is_err());
+        assert!(IcebergDetector::new(2.0, 2, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_iceberg_detected_after_reloads() {
+        let detector = IcebergDetector::new(2.0, 2, 0.01).unwrap();
+        assert!(detector.on_snapshot(&book(0, 10.0)).is_empty());
+        
+        // Se ejecutan 10 y el nivel vuelve a mostrar 10 (reposición)
+        detector.on_trade(&buy(1, 10.0));
+        assert!(detector.on_snapshot(&book(2, 10.0)).is_empty());
+        
+        detector.on_trade(&buy(3, 10.0));
+        let detections = detector.on_snapshot(&book(4, 10.0));
+        assert_eq!(detections.len(), 1);
+        
+        let detection = &detections[0];
+        assert_eq!(detection.side, "ask");
+        assert_eq!(detection.price, 100.00);
+        assert_eq!(detection.executed_volume, 20.0);
+        assert_eq!(detection.estimated_hidden_size, 10.0);
+        assert_eq!(detection.reloads, 2);
+        assert!(detection.confidence > 0.5);
+        
+        // No se vuelve a reportar el mismo nivel
+        detector.on_trade(&buy(5, 10.0));
+        assert!(detector.on_snapshot(&book(6, 10.0)).is_empty());
+    }
+
+    #[test]
+    fn test_iceberg_not_detected_when_level_depletes() {
+        let detector = IcebergDetector::new(2.0, 2, 0.01).unwrap();
+        detector.on_snapshot(&book(0, 30.0));
+        
+        // El tamaño baja exactamente lo ejecutado: liquidez normal
+        detector.on_trade(&buy(1, 10.0));
+        detector.on_snapshot(&book(2, 20.0));
+        detector.on_trade(&buy(3, 10.0));
+        assert!(detector.on_snapshot(&book(4, 10.0)).is_empty());
+    }
+}
@@ -0,0 +1,803 @@
+//! # Indicator Trait
+//! 
+//! Interfaz común de los engines para el `EngineManager`: cada engine recibe
+//! quotes, trades, snapshots y barras, y devuelve sus métricas envueltas en
+//! `IndicatorOutput`. Los eventos que un engine no usa tienen implementación
+//! vacía por defecto, así que un indicador nuevo solo implementa lo suyo y se
+//! registra sin tocar el código de despacho.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::config::ConfigRegistry;
+use crate::types::*;
+use super::*;
+
+/// Métricas emitidas por un indicador
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum IndicatorOutput {
+    Cvd(CVDMetrics),
+    CvdBar(CVDBar),
+    Footprint(FootprintBar),
+    Vwap(VWAPMetrics),
+    RollingVwap(VWAPMetrics),
+    VolumeProfile(VolumeProfileMetrics),
+    Liquidity(LiquidityMetrics),
+    Heatmap(HeatmapMetrics),
+    BookPressure(BookPressureMetrics),
+    Iceberg(IcebergDetection),
+    MovingAverage(MovingAverageMetrics),
+    Rsi(RSIMetrics),
+    Atr(ATRMetrics),
+    Volatility(VolatilityMetrics),
+    Tape(TapeMetrics),
+    Vpin(VPINMetrics),
+    EnrichedTrade(EnrichedTrade),
+    Sweep(SweepEvent),
+    Absorption(AbsorptionEvent),
+    Wall(WallEvent),
+    Spread(SpreadMetrics),
+    DataGap(DataGapAlert),
+    FlowSignal(FlowSignal),
+    /// Valores escalares con nombre, para indicadores sin tipo de métricas propio
+    Custom(String, HashMap<String, f64>),
+}
+
+/// Interfaz de un indicador registrable en el `EngineManager`
+pub trait Indicator: Send + Sync {
+    fn on_quote(&self, _quote: &Quote) {}
+    
+    fn on_trade(&self, _trade: &Trade) -> Vec<IndicatorOutput> {
+        Vec::new()
+    }
+    
+    fn on_snapshot(&self, _snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        Vec::new()
+    }
+    
+    fn on_bar(&self, _bar: &Bar) -> Vec<IndicatorOutput> {
+        Vec::new()
+    }
+    
+    /// Métricas del resto de indicadores para el mismo evento, tras
+    /// despacharlo a todos (indicadores derivados como `FlowSignalEngine`)
+    fn on_bundle(&self, _bundle: &MetricsBundle) -> Vec<IndicatorOutput> {
+        Vec::new()
+    }
+    
+    fn reset_symbol(&self, symbol: &str);
+    
+    fn reset(&self);
+    
+    /// Símbolos con estado en el engine (lo que `reset_symbol` liberaría)
+    fn state_len(&self) -> usize;
+    
+    /// Valores actuales del símbolo sin procesar ningún evento
+    fn snapshot_state(&self, _symbol: &str) -> HashMap<String, f64> {
+        HashMap::new()
+    }
+    
+    /// Aplica la configuración por símbolo (None = volver a la global)
+    fn configure(&mut self, _config: Option<&ConfigRegistry>) {}
+}
+
+/// Construye el mapa de estado descartando los valores ausentes
+fn state_of<const N: usize>(values: [(&str, Option<f64>); N]) -> HashMap<String, f64> {
+    values.into_iter()
+        .filter_map(|(key, value)| value.map(|v| (key.to_string(), v)))
+        .collect()
+}
+
+impl Indicator for CVDEngine {
+    fn on_quote(&self, quote: &Quote) {
+        CVDEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        CVDEngine::on_trade(self, trade).map(IndicatorOutput::Cvd).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        CVDEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        self.symbols().len()
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("cvd", self.get_cvd(symbol))])
+    }
+    
+    fn configure(&mut self, config: Option<&ConfigRegistry>) {
+        self.set_config(config.cloned());
+    }
+}
+
+impl Indicator for CVDBarEngine {
+    fn on_quote(&self, quote: &Quote) {
+        CVDBarEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        CVDBarEngine::on_trade(self, trade).map(IndicatorOutput::CvdBar).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        CVDBarEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        self.symbols().len()
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        let bar = self.get_current_bar(symbol);
+        state_of([
+            ("cvd", bar.as_ref().map(|b| b.close)),
+            ("delta", bar.as_ref().map(|b| b.delta)),
+        ])
+    }
+}
+
+impl Indicator for FootprintEngine {
+    fn on_quote(&self, quote: &Quote) {
+        FootprintEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        FootprintEngine::on_trade(self, trade).map(IndicatorOutput::Footprint).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        FootprintEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        self.symbols().len()
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        let bar = self.get_current_bar(symbol);
+        state_of([
+            ("volume", bar.as_ref().map(|b| b.volume)),
+            ("delta", bar.as_ref().map(|b| b.delta)),
+        ])
+    }
+}
+
+impl Indicator for VWAPEngine {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        VWAPEngine::on_trade(self, trade).map(IndicatorOutput::Vwap).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        VWAPEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        self.symbols().len()
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("vwap", self.get_vwap(symbol))])
+    }
+}
+
+impl Indicator for RollingVWAPEngine {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        RollingVWAPEngine::on_trade(self, trade).map(IndicatorOutput::RollingVwap).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        RollingVWAPEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        RollingVWAPEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("vwap", self.get_vwap(symbol))])
+    }
+}
+
+impl Indicator for VolumeProfileEngine {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        VolumeProfileEngine::on_trade(self, trade).map(IndicatorOutput::VolumeProfile).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        VolumeProfileEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        self.symbols().len()
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        let profile = self.get_profile(symbol);
+        state_of([
+            ("poc", profile.as_ref().map(|p| p.poc)),
+            ("value_area_high", profile.as_ref().map(|p| p.value_area_high)),
+            ("value_area_low", profile.as_ref().map(|p| p.value_area_low)),
+        ])
+    }
+}
+
+impl Indicator for LiquidityEngine {
+    fn on_quote(&self, quote: &Quote) {
+        LiquidityEngine::on_quote(self, quote);
+    }
+    
+    /// El spread efectivo por trade no se emite; se agrega en `get_spread_stats`
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        LiquidityEngine::on_trade(self, trade);
+        Vec::new()
+    }
+    
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        LiquidityEngine::on_snapshot(self, snapshot).map(IndicatorOutput::Liquidity).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        LiquidityEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        LiquidityEngine::state_len(self)
+    }
+    
+    fn configure(&mut self, config: Option<&ConfigRegistry>) {
+        self.set_config(config.cloned());
+    }
+}
+
+impl Indicator for HeatmapEngine {
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        HeatmapEngine::on_snapshot(self, snapshot).map(IndicatorOutput::Heatmap).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        HeatmapEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        HeatmapEngine::state_len(self)
+    }
+    
+    /// Un registro con tick sizes inválidos ya fue rechazado al cargarlo
+    fn configure(&mut self, config: Option<&ConfigRegistry>) {
+        let _ = self.set_config(config.cloned());
+    }
+}
+
+impl Indicator for BookPressureEngine {
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        BookPressureEngine::on_snapshot(self, snapshot).map(IndicatorOutput::BookPressure).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        BookPressureEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        BookPressureEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("pressure", self.get_pressure(symbol))])
+    }
+    
+    fn configure(&mut self, config: Option<&ConfigRegistry>) {
+        self.set_config(config.cloned());
+    }
+}
+
+impl Indicator for IcebergDetector {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        IcebergDetector::on_trade(self, trade);
+        Vec::new()
+    }
+    
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        IcebergDetector::on_snapshot(self, snapshot).into_iter().map(IndicatorOutput::Iceberg).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        IcebergDetector::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        IcebergDetector::state_len(self)
+    }
+}
+
+/// Con el manager las medias se calculan sobre cierres de barra; el modo
+/// por trade sigue disponible llamando al engine directamente.
+impl Indicator for MovingAverageEngine {
+    fn on_bar(&self, bar: &Bar) -> Vec<IndicatorOutput> {
+        MovingAverageEngine::on_bar(self, bar).into_iter().map(IndicatorOutput::MovingAverage).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        MovingAverageEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        MovingAverageEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        let mut state = HashMap::new();
+        for &period in &self.periods {
+            for kind in ["SMA", "EMA", "WMA"] {
+                if let Ok(Some(value)) = self.get_ma(symbol, kind, period) {
+                    state.insert(format!("{}_{}", kind.to_lowercase(), period), value);
+                }
+            }
+        }
+        state
+    }
+}
+
+impl Indicator for RSIEngine {
+    fn on_bar(&self, bar: &Bar) -> Vec<IndicatorOutput> {
+        RSIEngine::on_bar(self, bar).into_iter().map(IndicatorOutput::Rsi).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        RSIEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        RSIEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        self.periods.iter()
+            .filter_map(|&period| self.get_rsi(symbol, period).map(|v| (format!("rsi_{}", period), v)))
+            .collect()
+    }
+}
+
+impl Indicator for ATREngine {
+    fn on_bar(&self, bar: &Bar) -> Vec<IndicatorOutput> {
+        ATREngine::on_bar(self, bar).into_iter().map(IndicatorOutput::Atr).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        ATREngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        ATREngine::state_len(self)
+    }
+}
+
+impl Indicator for VolatilityEngine {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        VolatilityEngine::on_trade(self, trade)
+            .map(|rv| IndicatorOutput::Custom("realized_vol".to_string(), HashMap::from([("realized_vol".to_string(), rv)])))
+            .into_iter()
+            .collect()
+    }
+    
+    fn on_bar(&self, bar: &Bar) -> Vec<IndicatorOutput> {
+        VolatilityEngine::on_bar(self, bar).into_iter().map(IndicatorOutput::Volatility).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        VolatilityEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        VolatilityEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("realized_vol", self.get_realized_vol(symbol))])
+    }
+}
+
+impl Indicator for TapeEngine {
+    fn on_quote(&self, quote: &Quote) {
+        TapeEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        TapeEngine::on_trade(self, trade).map(IndicatorOutput::Tape).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        TapeEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        TapeEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("size_threshold", self.get_size_threshold(symbol))])
+    }
+}
+
+impl Indicator for VPINEngine {
+    fn on_quote(&self, quote: &Quote) {
+        VPINEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        VPINEngine::on_trade(self, trade).map(IndicatorOutput::Vpin).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        VPINEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        VPINEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("vpin", self.get_vpin(symbol))])
+    }
+}
+
+impl Indicator for QuoteEngine {
+    fn on_quote(&self, quote: &Quote) {
+        QuoteEngine::on_quote(self, quote);
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        QuoteEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        QuoteEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("spread", self.get_spread(symbol)), ("twa_spread", self.get_twa_spread(symbol))])
+    }
+}
+
+/// El OI y el funding llegan por sus propios métodos; el manager solo le
+/// reenvía quotes y trades para las divergencias.
+impl Indicator for OIEngine {
+    fn on_quote(&self, quote: &Quote) {
+        OIEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        OIEngine::on_trade(self, trade);
+        Vec::new()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        OIEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        OIEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([
+            ("open_interest", self.get_open_interest(symbol)),
+            ("funding_rate", self.get_funding_rate(symbol)),
+        ])
+    }
+}
+
+/// Las liquidaciones llegan por `on_liquidation`; aquí solo estado y resets
+impl Indicator for LiquidationEngine {
+    fn reset_symbol(&self, symbol: &str) {
+        LiquidationEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        LiquidationEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        let (long_notional, short_notional) = self.get_window_notional(symbol);
+        state_of([("long_notional", Some(long_notional)), ("short_notional", Some(short_notional))])
+    }
+}
+
+impl Indicator for TradeEnricher {
+    fn on_quote(&self, quote: &Quote) {
+        TradeEnricher::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        self.enrich(trade).map(IndicatorOutput::EnrichedTrade).into_iter().collect()
+    }
+    
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        TradeEnricher::on_snapshot(self, snapshot);
+        Vec::new()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        TradeEnricher::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        TradeEnricher::state_len(self)
+    }
+}
+
+impl Indicator for SweepDetector {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        SweepDetector::on_trade(self, trade).map(IndicatorOutput::Sweep).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        SweepDetector::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        SweepDetector::state_len(self)
+    }
+}
+
+impl Indicator for AbsorptionDetector {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        AbsorptionDetector::on_trade(self, trade);
+        Vec::new()
+    }
+    
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        AbsorptionDetector::on_snapshot(self, snapshot).into_iter().map(IndicatorOutput::Absorption).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        AbsorptionDetector::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        AbsorptionDetector::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([
+            ("absorbed_bid", self.get_absorbed_volume(symbol, "bid")),
+            ("absorbed_ask", self.get_absorbed_volume(symbol, "ask")),
+        ])
+    }
+}
+
+impl Indicator for WallTracker {
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        WallTracker::on_snapshot(self, snapshot).into_iter().map(IndicatorOutput::Wall).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        WallTracker::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        WallTracker::state_len(self)
+    }
+}
+
+/// `reset_symbol` vacía el histórico de los spreads en los que participa el
+/// símbolo; las definiciones se conservan
+impl Indicator for SpreadEngine {
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        SpreadEngine::on_trade(self, trade).into_iter().map(IndicatorOutput::Spread).collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        SpreadEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        SpreadEngine::state_len(self)
+    }
+}
+
+/// Las matrices se leen con `get_correlation` / `correlation_matrix`; los
+/// eventos solo alimentan el muestreo. `reset_symbol` vacía la ventana de
+/// retornos (cada fila incluye a todo el universo).
+impl Indicator for CorrelationEngine {
+    fn on_quote(&self, quote: &Quote) {
+        CorrelationEngine::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        CorrelationEngine::on_trade(self, trade);
+        Vec::new()
+    }
+    
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        CorrelationEngine::on_snapshot(self, snapshot);
+        Vec::new()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        CorrelationEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        CorrelationEngine::state_len(self)
+    }
+}
+
+/// Emite el cierre de un hueco al reanudarse el stream; los huecos abiertos
+/// se consultan con `check`. Las quotes actualizan su stream pero el trait
+/// no devuelve salidas para ellas.
+impl Indicator for GapDetector {
+    fn on_quote(&self, quote: &Quote) {
+        GapDetector::on_quote(self, quote);
+    }
+    
+    fn on_trade(&self, trade: &Trade) -> Vec<IndicatorOutput> {
+        GapDetector::on_trade(self, trade).map(IndicatorOutput::DataGap).into_iter().collect()
+    }
+    
+    fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<IndicatorOutput> {
+        GapDetector::on_snapshot(self, snapshot).map(IndicatorOutput::DataGap).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        GapDetector::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        GapDetector::state_len(self)
+    }
+}
+
+/// Combina el CVD, la liquidez y la cinta que el resto de indicadores
+/// dejaron en el bundle del evento
+impl Indicator for FlowSignalEngine {
+    fn on_bundle(&self, bundle: &MetricsBundle) -> Vec<IndicatorOutput> {
+        FlowSignalEngine::on_bundle(self, bundle).map(IndicatorOutput::FlowSignal).into_iter().collect()
+    }
+    
+    fn reset_symbol(&self, symbol: &str) {
+        FlowSignalEngine::reset_symbol(self, symbol);
+    }
+    
+    fn reset(&self) {
+        self.reset_all();
+    }
+    
+    fn state_len(&self) -> usize {
+        FlowSignalEngine::state_len(self)
+    }
+    
+    fn snapshot_state(&self, symbol: &str) -> HashMap<String, f64> {
+        state_of([("score", self.get_signal(symbol).map(|s| s.score))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indicator_dispatch_through_trait() {
+        let engines: Vec<Box<dyn Indicator>> = vec![
+            Box::new(CVDEngine::new()),
+            Box::new(VWAPEngine::new()),
+            Box::new(LiquidityEngine::new()),
+        ];
+        let trade = Trade::new(1000, 100.0, 5.0, "AAPL".to_string());
+        
+        let outputs: Vec<IndicatorOutput> = engines.iter().flat_map(|e| e.on_trade(&trade)).collect();
+        assert_eq!(outputs.len(), 2);
+        assert!(matches!(outputs[0], IndicatorOutput::Cvd(_)));
+        assert!(matches!(outputs[1], IndicatorOutput::Vwap(_)));
+        
+        assert_eq!(engines[1].snapshot_state("AAPL").get("vwap"), Some(&100.0));
+        engines[1].reset_symbol("AAPL");
+        assert!(engines[1].snapshot_state("AAPL").is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+//! # Liquidation Engine
+//! 
+//! Acumula el nocional liquidado de posiciones largas y cortas por bucket
+//! temporal y nivel de precio (estilo heatmap), y emite alertas de cascada
+//! cuando el nocional de un lado en la ventana supera un umbral.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use crate::types::{Liquidation, LiquidationCascade, LiquidationMetrics, Tile};
+use crate::utils::{calculate_bucket, count_symbols, price_to_tick, tick_to_price, TickSizeRegistry};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+const SIDE_LONG: &str = "long";
+const SIDE_SHORT: &str = "short";
+
+/// Clave del grid: (symbol, bucket_ts, tick, side)
+type GridKey = (String, u64, i64, &'static str);
+
+/// Normaliza el lado: posición ("long"/"short") o lado de la orden forzosa
+/// ("SELL" liquida un largo, "BUY" liquida un corto)
+fn position_side(side: &str) -> Option<&'static str> {
+    match side.to_ascii_lowercase().as_str() {
+        "long" | "sell" => Some(SIDE_LONG),
+        "short" | "buy" => Some(SIDE_SHORT),
+        _ => None,
+    }
+}
+
+/// Ventana de cascada de un lado: (ts, nocional) y si la alerta está activa
+#[derive(Clone, Debug, Default)]
+struct CascadeWindow {
+    events: VecDeque<(u64, f64)>,
+    notional: f64,
+    active: bool,
+}
+
+py_class! {
+/// Engine de liquidaciones por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct LiquidationEngine {
+    #[pyo3(get)]
+    pub bucket_ms: u64,
+    #[pyo3(get)]
+    pub retention_ms: u64,
+    #[pyo3(get)]
+    pub cascade_window_ms: u64,
+    #[pyo3(get)]
+    pub cascade_threshold: Option<f64>,
+    tick_sizes: TickSizeRegistry,
+    // Nocional liquidado por celda
+    grid: Arc<DashMap<GridKey, f64>>,
+    current_bucket: Arc<DashMap<String, u64>>,
+    cascades: Arc<DashMap<(String, &'static str), CascadeWindow>>,
+    pending_alerts: Arc<Mutex<Vec<LiquidationCascade>>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidationEngine {
+    #[new]
+    #[pyo3(signature = (bucket_ms=60_000, tick_size=1.0, retention_ms=3_600_000,
+                        cascade_window_ms=10_000, cascade_threshold=None))]
+    pub fn new(bucket_ms: u64, tick_size: f64, retention_ms: u64, cascade_window_ms: u64,
+               cascade_threshold: Option<f64>) -> Result<Self, ProcessError> {
+        if bucket_ms == 0 || retention_ms == 0 || cascade_window_ms == 0 {
+            return Err(ProcessError::invalid(
+                "bucket_ms, retention_ms and cascade_window_ms must be > 0"));
+        }
+        if cascade_threshold.is_some_and(|t| t.is_nan() || t <= 0.0) {
+            return Err(ProcessError::invalid("cascade_threshold must be > 0"));
+        }
+        Ok(Self {
+            bucket_ms,
+            retention_ms,
+            cascade_window_ms,
+            cascade_threshold,
+            tick_sizes: TickSizeRegistry::new(tick_size)?,
+            grid: Arc::new(DashMap::new()),
+            current_bucket: Arc::new(DashMap::new()),
+            cascades: Arc::new(DashMap::new()),
+            pending_alerts: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+    
+    /// Registro de tick sizes por símbolo (compartible con otros engines)
+    #[getter]
+    pub fn tick_registry(&self) -> TickSizeRegistry {
+        self.tick_sizes.clone()
+    }
+    
+    /// Usa un registro de tick sizes compartido. Limpia el grid.
+    #[setter]
+    pub fn set_tick_registry(&mut self, registry: TickSizeRegistry) {
+        self.tick_sizes = registry;
+        self.grid.clear();
+    }
+    
+    /// Procesa una liquidación y devuelve el bucket actualizado
+    pub fn on_liquidation(&self, liq: &Liquidation) -> Option<LiquidationMetrics> {
+        if liq.price <= 0.0 || liq.size <= 0.0 {
+            return None;
+        }
+        let side = position_side(&liq.side)?;
+        let notional = liq.notional();
+        let bucket_ts = calculate_bucket(liq.ts, self.bucket_ms);
+        
+        // Rollover: descartar buckets fuera de la retención
+        let rolled = {
+            let mut current = self.current_bucket.entry(liq.symbol.clone()).or_insert(bucket_ts);
+            let rolled = bucket_ts > *current;
+            if rolled {
+                *current = bucket_ts;
+            }
+            rolled
+        };
+        if rolled {
+            if let Some(cutoff) = bucket_ts.checked_sub(self.retention_ms) {
+                self.grid.retain(|k, _| k.0 != liq.symbol || k.1 > cutoff);
+            }
+        }
+        
+        let tick = price_to_tick(liq.price, self.tick_sizes.get_tick_size(&liq.symbol));
+        *self.grid.entry((liq.symbol.clone(), bucket_ts, tick, side)).or_insert(0.0) += notional;
+        
+        self.update_cascade(liq, side, notional);
+        self.get_bucket(&liq.symbol, bucket_ts)
+    }
+    
+    /// Liquidaciones de un símbolo en un bucket
+    pub fn get_bucket(&self, symbol: &str, bucket_ts: u64) -> Option<LiquidationMetrics> {
+        let tick_size = self.tick_sizes.get_tick_size(symbol);
+        let mut tiles: Vec<Tile> = self.grid.iter()
+            .filter(|e| e.key().0 == symbol && e.key().1 == bucket_ts)
+            .map(|e| Tile {
+                price_bin: tick_to_price(e.key().2, tick_size),
+                total_size: *e.value(),
+                side: e.key().3.to_string(),
+                value: *e.value(),
+            })
+            .collect();
+        if tiles.is_empty() {
+            return None;
+        }
+        tiles.sort_by(|a, b| a.price_bin.partial_cmp(&b.price_bin).unwrap_or(std::cmp::Ordering::Equal));
+        
+        let total = |side: &str| tiles.iter().filter(|t| t.side == side).map(|t| t.total_size).sum();
+        Some(LiquidationMetrics {
+            symbol: symbol.to_string(),
+            bucket_ts,
+            bucket_ms: self.bucket_ms,
+            long_notional: total(SIDE_LONG),
+            short_notional: total(SIDE_SHORT),
+            tiles,
+        })
+    }
+    
+    /// Nocional liquidado en la ventana de cascada actual: (long, short)
+    pub fn get_window_notional(&self, symbol: &str) -> (f64, f64) {
+        let get = |side| self.cascades.get(&(symbol.to_string(), side)).map(|w| w.notional).unwrap_or(0.0);
+        (get(SIDE_LONG), get(SIDE_SHORT))
+    }
+    
+    /// Extrae las alertas de cascada pendientes
+    pub fn drain_alerts(&self) -> Vec<LiquidationCascade> {
+        std::mem::take(&mut *self.pending_alerts.lock())
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.grid.retain(|k, _| k.0 != symbol);
+        self.current_bucket.remove(symbol);
+        self.cascades.retain(|k, _| k.0 != symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.grid.clear();
+        self.current_bucket.clear();
+        self.cascades.clear();
+        self.pending_alerts.lock().clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidationEngine(bucket_ms={}, cascade_threshold={:?}, entries={})",
+                self.bucket_ms, self.cascade_threshold, self.grid.len())
+    }
+}
+}
+
+impl LiquidationEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.grid.iter().map(|e| e.key().0.clone())
+            .chain(self.cascades.iter().map(|e| e.key().0.clone())))
+    }
+    
+    /// Actualiza la ventana del lado y emite la alerta al cruzar el umbral
+    fn update_cascade(&self, liq: &Liquidation, side: &'static str, notional: f64) {
+        let mut window = self.cascades.entry((liq.symbol.clone(), side)).or_default();
+        window.events.push_back((liq.ts, notional));
+        window.notional += notional;
+        if let Some(cutoff) = liq.ts.checked_sub(self.cascade_window_ms) {
+            while let Some(&(ts, old)) = window.events.front() {
+                if ts > cutoff {
+                    break;
+                }
+                window.notional -= old;
+                window.events.pop_front();
+            }
+        }
+        
+        let Some(threshold) = self.cascade_threshold else { return };
+        let triggered = window.notional >= threshold;
+        if triggered && !window.active {
+            tracing::debug!(symbol = %liq.symbol, side, notional = window.notional, threshold, "liquidation cascade");
+            self.pending_alerts.lock().push(LiquidationCascade {
+                ts: liq.ts,
+                symbol: liq.symbol.clone(),
+                side: side.to_string(),
+                notional: window.notional,
+                count: window.events.len(),
+                threshold,
+            });
+        }
+        window.active = triggered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn liq(ts: u64, side: &str, price: f64, size: f64) -> Liquidation {
+        Liquidation::new(ts, "BTCUSDT".to_string(), side.to_string(), price, size, None)
+    }
+
+    #[test]
+    fn test_liquidation_validation() {
+        assert!(LiquidationEngine::new(0, 1.0, 3_600_000, 10_000, None).is_err());
+        assert!(LiquidationEngine::new(60_000, 1.0, 3_600_000, 10_000, Some(0.0)).is_err());
+        
+        let engine = LiquidationEngine::new(60_000, 1.0, 3_600_000, 10_000, None).unwrap();
+        assert!(engine.on_liquidation(&liq(1, "sideways", 100.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn test_liquidation_buckets_and_bins() {
+        let engine = LiquidationEngine::new(60_000, 10.0, 3_600_000, 10_000, None).unwrap();
+        
+        engine.on_liquidation(&liq(1_000, "long", 60_004.0, 1.0));
+        engine.on_liquidation(&liq(2_000, "SELL", 59_998.0, 0.5));
+        let m = engine.on_liquidation(&liq(3_000, "short", 60_100.0, 2.0)).unwrap();
+        
+        assert_eq!(m.bucket_ts, 0);
+        assert_eq!(m.long_notional, 60_004.0 + 29_999.0);
+        assert_eq!(m.short_notional, 120_200.0);
+        // Los dos largos caen en el bin 60000
+        assert_eq!(m.tiles.len(), 2);
+        assert_eq!(m.tiles[0].side, "long");
+        assert_eq!(m.tiles[0].price_bin, 60_000.0);
+        
+        let next = engine.on_liquidation(&liq(61_000, "long", 60_000.0, 1.0)).unwrap();
+        assert_eq!(next.bucket_ts, 60_000);
+        assert!(engine.get_bucket("BTCUSDT", 0).is_some());
+    }
+
+    #[test]
+    fn test_liquidation_cascade_alerts() {
+        let engine = LiquidationEngine::new(60_000, 1.0, 3_600_000, 10_000, Some(250.0)).unwrap();
+        
+        engine.on_liquidation(&liq(0, "long", 100.0, 1.0));
+        engine.on_liquidation(&liq(1_000, "long", 100.0, 1.0));
+        assert!(engine.drain_alerts().is_empty());
+        
+        engine.on_liquidation(&liq(2_000, "long", 100.0, 1.0));
+        engine.on_liquidation(&liq(3_000, "long", 100.0, 1.0));
+        let alerts = engine.drain_alerts();
+        // Edge-triggered: una alerta por cascada
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].side, "long");
+        assert_eq!(alerts[0].notional, 300.0);
+        assert_eq!(alerts[0].count, 3);
+        
+        // Fuera de la ventana la cascada termina y puede volver a dispararse
+        engine.on_liquidation(&liq(30_000, "long", 100.0, 1.0));
+        assert_eq!(engine.get_window_notional("BTCUSDT"), (100.0, 0.0));
+        engine.on_liquidation(&liq(31_000, "long", 100.0, 2.0));
+        assert_eq!(engine.drain_alerts().len(), 1);
+    }
+}
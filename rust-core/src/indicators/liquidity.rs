@@ -1,32 +1,682 @@
 //! # Liquidity Engine
 //! 
 //! Order book liquidity analysis with compact data structures.
+//! Mantiene por símbolo medias móviles de spread, imbalance y profundidad
+//! para detectar picos respecto a la línea base reciente, y emite alertas
+//! configurables (spread, profundidad, imbalance) por símbolo.
+//! Antes de calcular valida el libro (lados vacíos, niveles duplicados o
+//! desordenados, libro cruzado o bloqueado) según `book_policy`.
+//! 
+//! Con el feed de quotes (`on_quote`) calcula además el spread cotizado
+//! medio ponderado por tiempo en cada `rolling_windows_ms`, y cruza cada
+//! trade (`on_trade`) con la quote vigente para obtener su spread efectivo
+//! y la mejora de precio respecto al lado cotizado.
+//! 
+//! Cada snapshot se compara con el anterior del símbolo nivel a nivel: el
+//! tamaño que crece cuenta como añadido y el que baja o desaparece como
+//! retirado, en total y en los `near_touch_levels` primeros niveles. Solo se
+//! comparan precios visibles en ambos snapshots, así que los niveles que
+//! salen de la profundidad publicada no cuentan como retirados; lo ejecutado
+//! sí (sin trades no se distingue de una cancelación).
+//! 
+//! Con `notional` activo las métricas incluyen además la profundidad en
+//! precio × tamaño, comparable entre símbolos de precios muy distintos.
+//! 
+//! `on_snapshot_with_depth` calcula las métricas de un snapshot con otra
+//! profundidad sin modificar el estado, para servir a consumidores de 5 y de
+//! 50 niveles con la misma instancia.
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use crate::types::{BookSnapshot, LiquidityMetrics};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::config::ConfigRegistry;
+use crate::types::{BookQualityAlert, BookSnapshot, EffectiveSpreadMetrics, Level, LiquidityMetrics,
+                   LiquidityRollingStats, LiquidityAlert, LiquidityAlertConfig, Quote, SpreadWindowStats,
+                   Trade};
+use crate::utils::{compensated_sum, count_symbols, linear_regression_slope, safe_div, NeumaierSum};
+use crate::utils::ewma::{Ewma, HalfLife};
+use crate::utils::rolling::{RollingQuantile, TimeWindow};
+use crate::errors::{validate_book, validate_quote, validate_trade, BookQualityPolicy, ProcessError, Rejections};
+use super::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+use crate::py_macros::{py_class, py_methods};
+
+/// Ventanas por defecto de las medias móviles (1m y 5m)
+pub const DEFAULT_ROLLING_WINDOWS_MS: [u64; 2] = [60_000, 300_000];
+
+/// Niveles por lado sumados en la profundidad por defecto
+pub const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+/// Niveles por lado que cuentan como cercanos al touch por defecto
+pub const DEFAULT_NEAR_TOUCH_LEVELS: usize = 3;
+
+/// Muestras mínimas en la ventana antes de evaluar la mediana del spread
+pub const MIN_BASELINE_SAMPLES: usize = 3;
+
+// Tipos de alerta (bits del estado activo por símbolo)
+const ALERT_SPREAD: u8 = 1;
+const ALERT_DEPTH: u8 = 1 << 1;
+const ALERT_IMBALANCE: u8 = 1 << 2;
+
+/// Muestra de liquidez: [spread, depth_imbalance, bids_depth, asks_depth]
+type LiquiditySample = [f64; 4];
+
+/// Niveles (bids, asks) completos de un snapshot
+type BookSides = (Vec<Level>, Vec<Level>);
 
+/// Últimos dos snapshots de un símbolo para los cambios de profundidad
+#[derive(Clone, Debug)]
+struct BookHistory {
+    ts: u64,
+    last: BookSides,
+    previous: Option<BookSides>,
+}
+
+impl BookHistory {
+    /// Snapshot anterior a uno con timestamp `ts`: el último, salvo que sea
+    /// el propio snapshot ya procesado
+    fn before(&self, ts: u64) -> Option<&BookSides> {
+        if ts == self.ts { self.previous.as_ref() } else { Some(&self.last) }
+    }
+}
+
+/// Ventana temporal de muestras con sumas incrementales
+#[derive(Clone, Debug)]
+pub struct RollingLiquidityWindow {
+    pub window_ms: u64,
+    samples: TimeWindow<LiquiditySample>,
+    // Sumas compensadas: la ventana puede vivir toda la sesión
+    sums: [NeumaierSum; 4],
+    spreads: RollingQuantile,
+}
+
+impl RollingLiquidityWindow {
+    pub fn new(window_ms: u64) -> Self {
+        Self {
+            window_ms,
+            samples: TimeWindow::default(),
+            sums: [NeumaierSum::default(); 4],
+            spreads: RollingQuantile::default(),
+        }
+    }
+    
+    pub fn push(&mut self, ts: u64, sample: LiquiditySample) {
+        self.samples.push(ts, sample);
+        for (sum, value) in self.sums.iter_mut().zip(sample) {
+            sum.add(value);
+        }
+        self.spreads.insert(sample[0]);
+        
+        // Desalojar muestras fuera de (ts - window_ms, ts]
+        let (sums, spreads) = (&mut self.sums, &mut self.spreads);
+        self.samples.evict(ts, self.window_ms, |old| {
+            for (sum, value) in sums.iter_mut().zip(old) {
+                sum.add(-value);
+            }
+            spreads.remove(old[0]);
+        });
+    }
+    
+    /// Mediana del spread de las muestras en ventana (DDSketch, error relativo del 1%)
+    pub fn median_spread(&self) -> Option<f64> {
+        self.spreads.median()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    
+    pub fn stats(&self, symbol: &str) -> LiquidityRollingStats {
+        let n = self.samples.len() as f64;
+        LiquidityRollingStats {
+            symbol: symbol.to_string(),
+            window_ms: self.window_ms,
+            samples: self.samples.len(),
+            avg_spread: safe_div(self.sums[0].value(), n),
+            avg_depth_imbalance: safe_div(self.sums[1].value(), n),
+            avg_bids_depth: safe_div(self.sums[2].value(), n),
+            avg_asks_depth: safe_div(self.sums[3].value(), n),
+        }
+    }
+}
+
+/// Quote vigente, tramos de spread cotizado y spreads efectivos recientes
+/// de un símbolo
+#[derive(Clone, Debug, Default)]
+struct SpreadState {
+    quote: Option<Quote>,
+    // (ts desde el que rige, spread cotizado)
+    segments: VecDeque<(u64, f64)>,
+    // (ts, spread efectivo, mejora de precio)
+    trades: VecDeque<(u64, f64, f64)>,
+    last_ts: u64,
+}
+
+impl SpreadState {
+    /// Registra la quote y olvida los tramos que terminaron antes de `ts - horizon_ms`
+    fn push_quote(&mut self, quote: &Quote, horizon_ms: u64) {
+        // Un ts que retrocede no reabre tiempo ya contado
+        let start = self.segments.back().map_or(quote.ts, |&(last, _)| quote.ts.max(last));
+        self.segments.push_back((start, quote.ask - quote.bid));
+        self.quote = Some(quote.clone());
+        self.last_ts = self.last_ts.max(quote.ts);
+        
+        let cutoff = self.last_ts.saturating_sub(horizon_ms);
+        while self.segments.get(1).is_some_and(|&(next, _)| next <= cutoff) {
+            self.segments.pop_front();
+        }
+    }
+    
+    fn push_trade(&mut self, ts: u64, effective_spread: f64, price_improvement: f64, horizon_ms: u64) {
+        self.trades.push_back((ts, effective_spread, price_improvement));
+        self.last_ts = self.last_ts.max(ts);
+        
+        let cutoff = self.last_ts.saturating_sub(horizon_ms);
+        while self.trades.front().is_some_and(|&(t, _, _)| t <= cutoff) {
+            self.trades.pop_front();
+        }
+    }
+    
+    /// Estadísticas de la ventana (now - window_ms, now]; el tiempo previo a
+    /// la primera quote no cuenta
+    fn stats(&self, symbol: &str, window_ms: u64, now: u64) -> SpreadWindowStats {
+        let from = now.saturating_sub(window_ms);
+        let mut area = NeumaierSum::default();
+        let mut quoted_ms = 0;
+        for (i, &(start, spread)) in self.segments.iter().enumerate() {
+            let end = self.segments.get(i + 1).map_or(now, |&(next, _)| next).min(now);
+            let start = start.max(from);
+            if end > start {
+                area.add(spread * (end - start) as f64);
+                quoted_ms += end - start;
+            }
+        }
+        // Sin tiempo transcurrido, el spread vigente
+        let twa_quoted_spread = match self.segments.back() {
+            Some(&(_, spread)) if quoted_ms == 0 => spread,
+            _ => safe_div(area.value(), quoted_ms as f64),
+        };
+        
+        let (mut trades, mut effective, mut improvement) = (0, 0.0, 0.0);
+        for &(_, eff, imp) in self.trades.iter().filter(|(ts, _, _)| *ts > from && *ts <= now) {
+            trades += 1;
+            effective += eff;
+            improvement += imp;
+        }
+        
+        SpreadWindowStats {
+            symbol: symbol.to_string(),
+            window_ms,
+            twa_quoted_spread,
+            quoted_ms,
+            trades,
+            avg_effective_spread: safe_div(effective, trades as f64),
+            avg_price_improvement: safe_div(improvement, trades as f64),
+        }
+    }
+}
+
+py_class! {
 /// Engine para calcular métricas de liquidez del libro de órdenes
 #[pyclass]
+#[derive(Clone)]
 pub struct LiquidityEngine {
+    /// Niveles por lado sumados en la profundidad (salvo override por símbolo)
+    #[pyo3(get)]
     pub depth_levels: usize,
+    /// Niveles por lado cercanos al touch en las métricas de añadido/retirado
+    #[pyo3(get)]
+    pub near_touch_levels: usize,
+    /// Niveles por lado copiados en `top_bids` / `top_asks` (0 = ninguno)
+    #[pyo3(get, set)]
+    pub top_levels: usize,
+    /// Modo nocional: reporta también la profundidad en precio × tamaño
+    #[pyo3(get, set)]
+    pub notional: bool,
+    /// Ventanas (ms) de las medias móviles por símbolo
+    #[pyo3(get)]
+    pub rolling_windows_ms: Vec<u64>,
+    // Estado por símbolo: una ventana por cada rolling_windows_ms
+    rolling: Arc<DashMap<String, Vec<RollingLiquidityWindow>>>,
+    // Configuración de alertas global y overrides por símbolo
+    alert_config: Option<LiquidityAlertConfig>,
+    alert_overrides: Arc<DashMap<String, LiquidityAlertConfig>>,
+    // Alertas activas por símbolo (bitmask) para emitir solo en el flanco
+    active_alerts: Arc<DashMap<String, u8>>,
+    pending_alerts: Arc<Mutex<Vec<LiquidityAlert>>>,
+    // Configuración por símbolo (depth_levels); None = depth_levels global
+    config: Option<ConfigRegistry>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+    // Política ante libros cruzados/mal formados y alertas pendientes
+    book_policy: BookQualityPolicy,
+    pending_book_alerts: Arc<Mutex<Vec<BookQualityAlert>>>,
+    // Quotes y trades por símbolo para spread cotizado y efectivo
+    spreads: Arc<DashMap<String, SpreadState>>,
+    classifier: TradeClassifier,
+    // Últimos snapshots por símbolo
+    books: Arc<DashMap<String, BookHistory>>,
+    // Vida media del spread y el imbalance suavizados (None = sin suavizado)
+    // y sus medias [spread, depth_imbalance] por símbolo
+    smoothing: Option<HalfLife>,
+    smoothed: Arc<DashMap<String, [Ewma; 2]>>,
+}
 }
 
+py_methods! {
 #[pymethods]
 impl LiquidityEngine {
     #[new]
+    #[pyo3(signature = (depth_levels=DEFAULT_DEPTH_LEVELS))]
+    pub fn py_new(depth_levels: usize) -> Result<Self, ProcessError> {
+        if depth_levels == 0 {
+            return Err(ProcessError::invalid("depth_levels must be > 0"));
+        }
+        Ok(Self { depth_levels, ..Self::new() })
+    }
+    
+    /// Configura los niveles por lado sumados en la profundidad
+    #[setter]
+    pub fn set_depth_levels(&mut self, depth_levels: usize) -> Result<(), ProcessError> {
+        if depth_levels == 0 {
+            return Err(ProcessError::invalid("depth_levels must be > 0"));
+        }
+        self.depth_levels = depth_levels;
+        Ok(())
+    }
+    
+    /// Configura cuántos niveles por lado cuentan como cercanos al touch
+    #[setter]
+    pub fn set_near_touch_levels(&mut self, near_touch_levels: usize) -> Result<(), ProcessError> {
+        if near_touch_levels == 0 {
+            return Err(ProcessError::invalid("near_touch_levels must be > 0"));
+        }
+        self.near_touch_levels = near_touch_levels;
+        Ok(())
+    }
+    
+    /// Usa la configuración por símbolo (depth_levels del registro)
+    #[setter]
+    pub fn set_config(&mut self, config: Option<ConfigRegistry>) {
+        self.config = config;
+    }
+    
+    /// Configura alertas globales o, si se indica símbolo, solo para ese símbolo
+    #[pyo3(signature = (config, symbol=None))]
+    pub fn set_alert_config(&mut self, config: LiquidityAlertConfig, symbol: Option<String>) {
+        match symbol {
+            Some(symbol) => {
+                self.alert_overrides.insert(symbol, config);
+            }
+            None => self.alert_config = Some(config),
+        }
+    }
+    
+    /// Desactiva las alertas globales o las de un símbolo
+    #[pyo3(signature = (symbol=None))]
+    pub fn clear_alert_config(&mut self, symbol: Option<String>) {
+        match symbol {
+            Some(symbol) => {
+                self.alert_overrides.remove(&symbol);
+            }
+            None => self.alert_config = None,
+        }
+    }
+    
+    /// Devuelve y vacía las alertas pendientes
+    pub fn drain_alerts(&self) -> Vec<LiquidityAlert> {
+        std::mem::take(&mut *self.pending_alerts.lock())
+    }
+    
+    /// Configura las ventanas de las medias móviles (descarta el estado acumulado)
+    #[setter]
+    pub fn set_rolling_windows_ms(&mut self, windows_ms: Vec<u64>) -> Result<(), ProcessError> {
+        if windows_ms.contains(&0) {
+            return Err(ProcessError::invalid("rolling windows must be > 0 ms"));
+        }
+        self.rolling_windows_ms = windows_ms;
+        self.rolling.clear();
+        Ok(())
+    }
+    
+    /// Activa `smoothed_spread` y `smoothed_depth_imbalance` con vida media
+    /// en ms (ponderada por tiempo) o en snapshots; sin argumentos lo
+    /// desactiva. Reinicia las medias.
+    #[pyo3(signature = (half_life_ms=None, half_life_events=None))]
+    pub fn set_smoothing(&mut self, half_life_ms: Option<u64>, half_life_events: Option<f64>) -> Result<(), ProcessError> {
+        self.smoothing = HalfLife::from_args(half_life_ms, half_life_events)?;
+        self.smoothed.clear();
+        Ok(())
+    }
+    
+    /// (half_life_ms, half_life_events) del suavizado
+    pub fn smoothing(&self) -> (Option<u64>, Option<f64>) {
+        HalfLife::to_args(self.smoothing)
+    }
+    
+    /// Medias móviles del símbolo, una por ventana configurada
+    pub fn get_rolling_stats(&self, symbol: &str) -> Vec<LiquidityRollingStats> {
+        self.rolling.get(symbol)
+            .map(|windows| windows.iter().map(|w| w.stats(symbol)).collect())
+            .unwrap_or_default()
+    }
+    
+    /// Registra la quote vigente del símbolo para el spread cotizado y el
+    /// cruce con trades. En modo `strict` una quote inválida lanza su excepción
+    #[pyo3(name = "on_quote")]
+    pub fn py_on_quote(&self, quote: &Quote) -> Result<(), ProcessError> {
+        self.rejections.resolve(self.try_on_quote(quote))
+    }
+    
+    /// Cruza el trade con la quote vigente y devuelve su spread efectivo
+    /// (None si aún no hay quote del símbolo o el trade no es válido)
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<EffectiveSpreadMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Spread cotizado ponderado por tiempo y spread efectivo medio del
+    /// símbolo, una entrada por ventana configurada, hasta `now_ts` (por
+    /// defecto el último evento visto)
+    #[pyo3(signature = (symbol, now_ts=None))]
+    pub fn get_spread_stats(&self, symbol: &str, now_ts: Option<u64>) -> Vec<SpreadWindowStats> {
+        self.spreads.get(symbol)
+            .map(|state| {
+                let now = now_ts.unwrap_or(state.last_ts);
+                self.rolling_windows_ms.iter().map(|w| state.stats(symbol, *w, now)).collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.rolling.remove(symbol);
+        self.active_alerts.remove(symbol);
+        self.spreads.remove(symbol);
+        self.books.remove(symbol);
+        self.smoothed.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.rolling.clear();
+        self.active_alerts.clear();
+        self.spreads.clear();
+        self.books.clear();
+        self.smoothed.clear();
+        self.classifier.reset_all();
+        self.pending_alerts.lock().clear();
+        self.pending_book_alerts.lock().clear();
+        self.rejections.reset_all();
+    }
+    
+    /// Procesa un snapshot del libro y calcula métricas de liquidez.
+    /// Un libro inválido lanza su excepción en modo `strict` o con
+    /// `book_policy = "raise"`; si no, devuelve None
+    #[pyo3(name = "on_snapshot")]
+    pub fn py_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<Option<LiquidityMetrics>, ProcessError> {
+        match self.try_on_snapshot(snapshot) {
+            Err(e) if e.is_book_quality() && self.book_policy == BookQualityPolicy::Raise => Err(e),
+            result => self.rejections.resolve(result),
+        }
+    }
+    
+    /// Métricas del snapshot con `levels` niveles por lado en lugar de
+    /// `depth_levels`, sin tocar el estado del engine (medias móviles,
+    /// alertas, snapshot anterior ni rechazos). Sirve para consultar otra
+    /// profundidad del mismo snapshot ya procesado con `on_snapshot`
+    #[pyo3(name = "on_snapshot_with_depth")]
+    pub fn py_on_snapshot_with_depth(&self, snapshot: &BookSnapshot, levels: usize) -> Result<Option<LiquidityMetrics>, ProcessError> {
+        if levels == 0 {
+            return Err(ProcessError::invalid("levels must be > 0"));
+        }
+        match self.try_on_snapshot_with_depth(snapshot, levels) {
+            Err(e) if self.rejections.strict || self.book_policy == BookQualityPolicy::Raise => Err(e),
+            result => Ok(result.ok()),
+        }
+    }
+    
+    /// Política ante libros inválidos: "skip" (descartar y contar), "alert"
+    /// (emitir BookQualityAlert y procesar si hay ambos lados) o "raise"
+    #[getter]
+    pub fn book_policy(&self) -> &'static str {
+        self.book_policy.as_str()
+    }
+    
+    #[setter]
+    pub fn set_book_policy(&mut self, policy: &str) -> Result<(), ProcessError> {
+        self.book_policy = BookQualityPolicy::parse(policy).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid book policy: {} (skip, alert or raise)", policy))
+        })?;
+        Ok(())
+    }
+    
+    /// Devuelve y vacía las alertas de calidad del libro pendientes
+    pub fn drain_book_alerts(&self) -> Vec<BookQualityAlert> {
+        std::mem::take(&mut *self.pending_book_alerts.lock())
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidityEngine(depth_levels={})", self.depth_levels)
+    }
+}
+}
+
+impl LiquidityEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.books.iter().map(|e| e.key().clone())
+            .chain(self.spreads.iter().map(|e| e.key().clone()))
+            .chain(self.rolling.iter().map(|e| e.key().clone())))
+    }
+    
     pub fn new() -> Self {
         Self {
-            depth_levels: 10,
+            depth_levels: DEFAULT_DEPTH_LEVELS,
+            near_touch_levels: DEFAULT_NEAR_TOUCH_LEVELS,
+            top_levels: 0,
+            notional: false,
+            rolling_windows_ms: DEFAULT_ROLLING_WINDOWS_MS.to_vec(),
+            rolling: Arc::new(DashMap::new()),
+            alert_config: None,
+            alert_overrides: Arc::new(DashMap::new()),
+            active_alerts: Arc::new(DashMap::new()),
+            pending_alerts: Arc::new(Mutex::new(Vec::new())),
+            config: None,
+            rejections: Rejections::default(),
+            book_policy: BookQualityPolicy::default(),
+            pending_book_alerts: Arc::new(Mutex::new(Vec::new())),
+            spreads: Arc::new(DashMap::new()),
+            classifier: TradeClassifier::new(),
+            books: Arc::new(DashMap::new()),
+            smoothing: None,
+            smoothed: Arc::new(DashMap::new()),
         }
     }
     
-    /// Procesa un snapshot del libro y calcula métricas de liquidez
+    /// Crea el engine con un clasificador compartido (p. ej. el de un
+    /// `TradeEnricher`) para el lado de los trades del spread efectivo
+    pub fn with_classifier(classifier: TradeClassifier) -> Self {
+        Self { classifier, ..Self::new() }
+    }
+    
+    /// Procesa un snapshot; si no es válido se descarta y queda contado en las rejections
     pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Option<LiquidityMetrics> {
-        // Validar que tenemos datos
-        if snapshot.bids.is_empty() || snapshot.asks.is_empty() {
-            return None;
+        self.try_on_snapshot(snapshot).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un snapshot; Err si no es válido (también queda contado)
+    pub fn try_on_snapshot(&self, snapshot: &BookSnapshot) -> Result<Option<LiquidityMetrics>, ProcessError> {
+        self.check_book(snapshot)?;
+        self.rejections.check_timestamp(&snapshot.symbol, snapshot.ts)?;
+        Ok(self.process_snapshot(snapshot))
+    }
+    
+    /// Métricas con `levels` niveles por lado sin modificar el estado; Err si
+    /// el libro no es válido según `book_policy` (no se cuenta ni se alerta)
+    pub fn try_on_snapshot_with_depth(&self, snapshot: &BookSnapshot, levels: usize)
+        -> Result<LiquidityMetrics, ProcessError> {
+        if let Err(e) = validate_book(snapshot) {
+            if self.book_policy != BookQualityPolicy::Alert || matches!(e, ProcessError::EmptyBook { .. }) {
+                return Err(e);
+            }
+        }
+        let history = self.books.get(&snapshot.symbol);
+        Ok(self.compute_metrics(snapshot, levels, history.as_deref().and_then(|h| h.before(snapshot.ts))))
+    }
+    
+    /// Registra una quote; si no es válida se descarta y queda contada en las rejections
+    pub fn on_quote(&self, quote: &Quote) {
+        let _ = self.try_on_quote(quote);
+    }
+    
+    /// Valida y registra una quote; Err si no es válida (también queda contada)
+    pub fn try_on_quote(&self, quote: &Quote) -> Result<(), ProcessError> {
+        self.rejections.check(validate_quote(quote))?;
+        self.rejections.check_timestamp(&quote.symbol, quote.ts)?;
+        self.classifier.on_quote(quote);
+        self.spreads.entry(quote.symbol.clone()).or_default().push_quote(quote, self.spread_horizon_ms());
+        Ok(())
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<EffectiveSpreadMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<EffectiveSpreadMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Spread efectivo y mejora de precio frente a la quote vigente
+    fn process_trade(&self, trade: &Trade) -> Option<EffectiveSpreadMetrics> {
+        let side = self.classifier.classify(trade);
+        let mut state = self.spreads.get_mut(&trade.symbol)?;
+        let quote = state.quote.clone()?;
+        
+        let mid = quote.mid();
+        let quoted_spread = quote.ask - quote.bid;
+        // Distancia al mid en la dirección del agresor; sin lado, la absoluta
+        let signed = match side {
+            SIDE_BUY => trade.price - mid,
+            SIDE_SELL => mid - trade.price,
+            _ => (trade.price - mid).abs(),
+        };
+        let effective_spread = 2.0 * signed;
+        let price_improvement = quoted_spread / 2.0 - signed;
+        state.push_trade(trade.ts, effective_spread, price_improvement, self.spread_horizon_ms());
+        
+        Some(EffectiveSpreadMetrics {
+            ts: trade.ts,
+            symbol: trade.symbol.clone(),
+            price: trade.price,
+            size: trade.size,
+            side: side.to_string(),
+            bid: quote.bid,
+            ask: quote.ask,
+            mid,
+            quoted_spread,
+            effective_spread,
+            effective_spread_bps: safe_div(effective_spread, mid) * 10_000.0,
+            price_improvement,
+            quote_age_ms: trade.ts.saturating_sub(quote.ts),
+        })
+    }
+    
+    /// Historia de quotes y trades que se conserva: la ventana más larga
+    fn spread_horizon_ms(&self) -> u64 {
+        self.rolling_windows_ms.iter().copied().max().unwrap_or(0)
+    }
+    
+    /// Valida el libro según `book_policy`; con "alert" solo rechaza libros
+    /// sin alguno de los lados (no hay métricas que calcular)
+    fn check_book(&self, snapshot: &BookSnapshot) -> Result<(), ProcessError> {
+        let Err(e) = validate_book(snapshot) else { return Ok(()) };
+        if self.book_policy == BookQualityPolicy::Alert {
+            tracing::warn!(symbol = %snapshot.symbol, kind = e.kind(), "book quality");
+            self.pending_book_alerts.lock().push(BookQualityAlert::new(
+                snapshot.ts, snapshot.symbol.clone(), e.kind().to_string(), e.to_string()));
+            if !matches!(e, ProcessError::EmptyBook { .. }) {
+                return Ok(());
+            }
+        }
+        self.rejections.check(Err(e))
+    }
+    
+    /// Procesa un snapshot del libro y calcula métricas de liquidez
+    fn process_snapshot(&self, snapshot: &BookSnapshot) -> Option<LiquidityMetrics> {
+        let depth_levels = self.config.as_ref()
+            .and_then(|c| c.depth_levels(&snapshot.symbol))
+            .unwrap_or(self.depth_levels);
+        let mut metrics = {
+            let history = self.books.get(&snapshot.symbol);
+            self.compute_metrics(snapshot, depth_levels, history.as_deref().map(|h| &h.last))
+        };
+        if let Some(half_life) = self.smoothing {
+            let mut ewmas = self.smoothed.entry(snapshot.symbol.clone())
+                .or_insert_with(|| [Ewma::new(half_life), Ewma::new(half_life)]);
+            metrics.smoothed_spread = Some(ewmas[0].update(snapshot.ts, metrics.spread));
+            metrics.smoothed_depth_imbalance = Some(ewmas[1].update(snapshot.ts, metrics.depth_imbalance));
         }
         
+        // Actualizar medias móviles del símbolo y evaluar alertas
+        self.record_sample(snapshot, [metrics.spread, metrics.depth_imbalance, metrics.bids_depth, metrics.asks_depth]);
+        self.evaluate_alerts(snapshot, metrics.spread, metrics.bids_depth.min(metrics.asks_depth), metrics.depth_imbalance);
+        self.remember_book(snapshot);
+        Some(metrics)
+    }
+    
+    /// Métricas de liquidez del snapshot con `depth_levels` niveles; los
+    /// cambios de profundidad se miden respecto a `previous`. No toca el estado.
+    fn compute_metrics(&self, snapshot: &BookSnapshot, depth_levels: usize,
+                       previous: Option<&BookSides>) -> LiquidityMetrics {
         // Obtener mejor bid y ask
         let best_bid = snapshot.bids[0].price;
         let best_ask = snapshot.asks[0].price;  // Corregido: usar .price en lugar de .ask
@@ -38,15 +688,23 @@ impl LiquidityEngine {
         let spread = best_ask - best_bid;
         
         // Calcular profundidad hasta N niveles
-        let bids_depth: f64 = snapshot.bids.iter()
-            .take(self.depth_levels)
-            .map(|level| level.size)
-            .sum();
+        let bids_depth = compensated_sum(snapshot.bids.iter()
+            .take(depth_levels)
+            .map(|level| level.size));
             
-        let asks_depth: f64 = snapshot.asks.iter()
-            .take(self.depth_levels)
-            .map(|level| level.size)
-            .sum();
+        let asks_depth = compensated_sum(snapshot.asks.iter()
+            .take(depth_levels)
+            .map(|level| level.size));
+        
+        // Profundidad nocional de los mismos niveles
+        let notional_depth = |levels: &[Level]| compensated_sum(levels.iter()
+            .take(depth_levels)
+            .map(|level| level.price * level.size));
+        let (bids_notional, asks_notional) = if self.notional {
+            (Some(notional_depth(&snapshot.bids)), Some(notional_depth(&snapshot.asks)))
+        } else {
+            (None, None)
+        };
         
         // Calcular imbalance
         let total_depth = bids_depth + asks_depth;
@@ -63,7 +721,40 @@ impl LiquidityEngine {
             0.0
         };
         
-        Some(LiquidityMetrics {
+        // Microprice: cada lado ponderado por el tamaño del lado contrario
+        let microprice = if (bid1_size + ask1_size) > 0.0 {
+            (best_bid * ask1_size + best_ask * bid1_size) / (bid1_size + ask1_size)
+        } else {
+            mid
+        };
+        
+        // Weighted mid: microprice generalizado a N niveles, usando el precio
+        // medio ponderado de cada lado y la profundidad del lado contrario
+        let weighted_mid = if total_depth > 0.0 {
+            let bid_px = Self::side_vwap(&snapshot.bids, depth_levels, best_bid);
+            let ask_px = Self::side_vwap(&snapshot.asks, depth_levels, best_ask);
+            (bid_px * asks_depth + ask_px * bids_depth) / total_depth
+        } else {
+            mid
+        };
+        
+        // Pendiente del libro: profundidad acumulada vs distancia al mid
+        let bid_slope = Self::book_slope(&snapshot.bids, depth_levels, mid);
+        let ask_slope = Self::book_slope(&snapshot.asks, depth_levels, mid);
+        
+        // Cambios de profundidad respecto al snapshot anterior
+        fn top(levels: &[Level], n: usize) -> &[Level] {
+            &levels[..levels.len().min(n)]
+        }
+        let (bid_changes, ask_changes) = match previous {
+            Some((bids, asks)) => (
+                Self::depth_changes(top(bids, depth_levels), top(&snapshot.bids, depth_levels), self.near_touch_levels, true),
+                Self::depth_changes(top(asks, depth_levels), top(&snapshot.asks, depth_levels), self.near_touch_levels, false),
+            ),
+            None => ([0.0; 4], [0.0; 4]),
+        };
+        
+        LiquidityMetrics {
             mid,
             spread,
             bids_depth,
@@ -75,11 +766,166 @@ impl LiquidityEngine {
             bid1_size,
             ask1_size,
             levels: format!("{}/{}", snapshot.bids.len(), snapshot.asks.len()),
-        })
+            bid_levels: snapshot.bids.len() as u32,
+            ask_levels: snapshot.asks.len() as u32,
+            top_bids: snapshot.bids.iter().take(self.top_levels).cloned().collect(),
+            top_asks: snapshot.asks.iter().take(self.top_levels).cloned().collect(),
+            microprice,
+            weighted_mid,
+            bid_slope,
+            ask_slope,
+            bid_added: bid_changes[0],
+            bid_pulled: bid_changes[1],
+            ask_added: ask_changes[0],
+            ask_pulled: ask_changes[1],
+            near_bid_added: bid_changes[2],
+            near_bid_pulled: bid_changes[3],
+            near_ask_added: ask_changes[2],
+            near_ask_pulled: ask_changes[3],
+            bids_notional,
+            asks_notional,
+            smoothed_spread: None,
+            smoothed_depth_imbalance: None,
+        }
     }
     
-    fn __repr__(&self) -> String {
-        format!("LiquidityEngine(depth_levels={})", self.depth_levels)
+    /// Guarda el snapshot como último del símbolo (el anterior pasa a `previous`)
+    fn remember_book(&self, snapshot: &BookSnapshot) {
+        let sides = (snapshot.bids.clone(), snapshot.asks.clone());
+        match self.books.get_mut(&snapshot.symbol) {
+            Some(mut history) => {
+                let last = std::mem::replace(&mut history.last, sides);
+                history.previous = Some(last);
+                history.ts = snapshot.ts;
+            }
+            None => {
+                self.books.insert(snapshot.symbol.clone(), BookHistory { ts: snapshot.ts, last: sides, previous: None });
+            }
+        }
+    }
+    
+    fn record_sample(&self, snapshot: &BookSnapshot, sample: LiquiditySample) {
+        if self.rolling_windows_ms.is_empty() {
+            return;
+        }
+        let mut windows = self.rolling.entry(snapshot.symbol.clone()).or_insert_with(|| {
+            self.rolling_windows_ms.iter().map(|w| RollingLiquidityWindow::new(*w)).collect()
+        });
+        for window in windows.iter_mut() {
+            window.push(snapshot.ts, sample);
+        }
+    }
+    
+    /// Evalúa los umbrales y encola las alertas que pasan a estar activas
+    fn evaluate_alerts(&self, snapshot: &BookSnapshot, spread: f64, min_side_depth: f64, imbalance: f64) {
+        let config = match self.alert_overrides.get(&snapshot.symbol) {
+            Some(config) => config.clone(),
+            None => match &self.alert_config {
+                Some(config) => config.clone(),
+                None => return,
+            },
+        };
+        
+        let mut triggered: Vec<(u8, &str, f64, f64)> = Vec::new();
+        if let Some(multiple) = config.spread_median_multiple {
+            let median = self.rolling.get(&snapshot.symbol).and_then(|windows| {
+                windows.iter()
+                    .min_by_key(|w| w.window_ms)
+                    .filter(|w| w.len() >= MIN_BASELINE_SAMPLES)
+                    .and_then(|w| w.median_spread())
+            });
+            if let Some(median) = median {
+                if median > 0.0 && spread > multiple * median {
+                    triggered.push((ALERT_SPREAD, "spread_spike", spread, multiple * median));
+                }
+            }
+        }
+        if let Some(min_depth) = config.min_depth {
+            if min_side_depth < min_depth {
+                triggered.push((ALERT_DEPTH, "low_depth", min_side_depth, min_depth));
+            }
+        }
+        if let Some(limit) = config.imbalance_limit {
+            if imbalance.abs() > limit {
+                triggered.push((ALERT_IMBALANCE, "imbalance", imbalance, limit));
+            }
+        }
+        
+        let now_active = triggered.iter().fold(0u8, |mask, t| mask | t.0);
+        let previous = self.active_alerts.insert(snapshot.symbol.clone(), now_active).unwrap_or(0);
+        
+        let mut pending = self.pending_alerts.lock();
+        for (bit, kind, value, threshold) in triggered {
+            if previous & bit == 0 {
+                tracing::debug!(symbol = %snapshot.symbol, kind, value, threshold, "liquidity alert");
+                pending.push(LiquidityAlert::new(snapshot.ts, snapshot.symbol.clone(),
+                                                 kind.to_string(), value, threshold));
+            }
+        }
+    }
+    
+    /// Precio medio ponderado por tamaño de los primeros N niveles de un lado
+    fn side_vwap(levels: &[Level], depth: usize, fallback: f64) -> f64 {
+        let (pv, v) = levels.iter()
+            .take(depth)
+            .fold((0.0, 0.0), |(pv, v), l| (pv + l.price * l.size, v + l.size));
+        if v > 0.0 { safe_div(pv, v) } else { fallback }
+    }
+    
+    /// Tamaño añadido y retirado en un lado entre dos snapshots como
+    /// [added, pulled, near_added, near_pulled]. Un nivel es cercano si está
+    /// entre los `near_levels` primeros del snapshot en que aparece (el
+    /// actual, o el anterior si se retiró entero).
+    fn depth_changes(previous: &[Level], current: &[Level], near_levels: usize, is_bid: bool) -> [f64; 4] {
+        let (Some(prev_deepest), Some(deepest)) = (previous.last(), current.last()) else { return [0.0; 4] };
+        // Precio más profundo visible en ambos snapshots
+        let visible = |price: f64| if is_bid {
+            price >= prev_deepest.price.max(deepest.price)
+        } else {
+            price <= prev_deepest.price.min(deepest.price)
+        };
+        let size_at = |levels: &[Level], price: f64| levels.iter().find(|l| l.price == price).map(|l| l.size);
+        
+        let mut changes = [0.0; 4];
+        let mut record = |delta: f64, near: bool| {
+            let index = if delta > 0.0 { 0 } else { 1 };
+            changes[index] += delta.abs();
+            if near {
+                changes[index + 2] += delta.abs();
+            }
+        };
+        for (rank, level) in current.iter().enumerate().filter(|(_, l)| visible(l.price)) {
+            let delta = level.size - size_at(previous, level.price).unwrap_or(0.0);
+            if delta != 0.0 {
+                record(delta, rank < near_levels);
+            }
+        }
+        for (rank, level) in previous.iter().enumerate().filter(|(_, l)| visible(l.price)) {
+            if size_at(current, level.price).is_none() {
+                record(-level.size, rank < near_levels);
+            }
+        }
+        changes
+    }
+    
+    /// Regresión de la profundidad acumulada sobre la distancia al mid.
+    /// Pendientes altas = la liquidez crece rápido al alejarse (libro resiliente).
+    fn book_slope(levels: &[Level], depth: usize, mid: f64) -> f64 {
+        let mut cumulative = 0.0;
+        let (xs, ys): (Vec<f64>, Vec<f64>) = levels.iter()
+            .take(depth)
+            .map(|l| {
+                cumulative += l.size;
+                ((l.price - mid).abs(), cumulative)
+            })
+            .unzip();
+        linear_regression_slope(&xs, &ys)
+    }
+}
+
+impl Default for LiquidityEngine {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -214,5 +1060,345 @@ mod tests {
         
         let metrics = result.unwrap();
         assert_eq!(metrics.levels, "3/3");
+        assert_eq!((metrics.bid_levels, metrics.ask_levels), (3, 3));
+        assert!(metrics.top_bids.is_empty());
+    }
+
+    #[test]
+    fn test_liquidity_top_levels() {
+        let mut engine = LiquidityEngine::new();
+        engine.top_levels = 2;
+        let metrics = engine.on_snapshot(&create_test_snapshot()).unwrap();
+        let prices: Vec<f64> = metrics.top_bids.iter().chain(&metrics.top_asks).map(|l| l.price).collect();
+        assert_eq!(prices, vec![149.99, 149.98, 150.01, 150.02]);
+        assert_eq!(metrics.top_asks[1].size, 200.0);
+    }
+
+    #[test]
+    fn test_liquidity_microprice() {
+        let engine = LiquidityEngine::new();
+        let snapshot = BookSnapshot {
+            ts: 1234567890,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 100.0, size: 300.0 }],
+            asks: vec![Level { price: 101.0, size: 100.0 }],
+        };
+        
+        let metrics = engine.on_snapshot(&snapshot).unwrap();
+        // (100*100 + 101*300) / 400 = 100.75 (más cerca del ask: presión compradora)
+        assert!((metrics.microprice - 100.75).abs() < 1e-9);
+        // Con un solo nivel el weighted mid coincide con el microprice
+        assert!((metrics.weighted_mid - metrics.microprice).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_liquidity_weighted_mid_multi_level() {
+        let engine = LiquidityEngine::new();
+        let snapshot = create_test_snapshot();
+        
+        let metrics = engine.on_snapshot(&snapshot).unwrap();
+        // Libro simétrico => weighted mid en el mid
+        assert!((metrics.weighted_mid - metrics.mid).abs() < 1e-9);
+        assert!((metrics.microprice - metrics.mid).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_liquidity_book_slope() {
+        let engine = LiquidityEngine::new();
+        let snapshot = BookSnapshot {
+            ts: 1234567890,
+            symbol: "AAPL".to_string(),
+            bids: vec![
+                Level { price: 99.0, size: 10.0 },
+                Level { price: 98.0, size: 10.0 },
+                Level { price: 97.0, size: 10.0 },
+            ],
+            asks: vec![
+                Level { price: 101.0, size: 10.0 },
+                Level { price: 102.0, size: 30.0 },
+                Level { price: 103.0, size: 50.0 },
+            ],
+        };
+        
+        let metrics = engine.on_snapshot(&snapshot).unwrap();
+        // Bids: profundidad acumulada 10,20,30 a distancias 1,2,3 => pendiente 10
+        assert!((metrics.bid_slope - 10.0).abs() < 1e-9);
+        // Asks: 10,40,90 a distancias 1,2,3 => pendiente 40
+        assert!((metrics.ask_slope - 40.0).abs() < 1e-9);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_liquidity_smoothing() {
+        let mut engine = LiquidityEngine::new();
+        let snapshot = |ts: u64, ask: f64, ask_size: f64| BookSnapshot {
+            ts,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 100.0, size: 10.0 }],
+            asks: vec![Level { price: ask, size: ask_size }],
+        };
+        assert_eq!(engine.on_snapshot(&snapshot(0, 101.0, 10.0)).unwrap().smoothed_spread, None);
+        
+        engine.set_smoothing(None, Some(1.0)).unwrap();
+        let metrics = engine.on_snapshot(&snapshot(100, 101.0, 10.0)).unwrap();
+        assert_eq!((metrics.smoothed_spread, metrics.smoothed_depth_imbalance), (Some(1.0), Some(0.0)));
+        // Vida media de un snapshot: mitad del valor previo y mitad del nuevo
+        let metrics = engine.on_snapshot(&snapshot(200, 103.0, 30.0)).unwrap();
+        assert_eq!(metrics.smoothed_spread, Some(2.0));
+        assert_eq!(metrics.smoothed_depth_imbalance, Some(-0.25));
+        
+        engine.reset_symbol("AAPL");
+        assert_eq!(engine.on_snapshot(&snapshot(300, 103.0, 30.0)).unwrap().smoothed_spread, Some(3.0));
+    }
+
+    #[test]
+    fn test_liquidity_rolling_stats() {
+        let mut engine = LiquidityEngine::new();
+        engine.set_rolling_windows_ms(vec![1000, 10_000]).unwrap();
+        assert!(engine.set_rolling_windows_ms(vec![0]).is_err());
+        
+        let snapshot = |ts: u64, ask: f64| BookSnapshot {
+            ts,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 100.0, size: 10.0 }],
+            asks: vec![Level { price: ask, size: 10.0 }],
+        };
+        
+        engine.on_snapshot(&snapshot(0, 101.0));
+        engine.on_snapshot(&snapshot(500, 103.0));
+        engine.on_snapshot(&snapshot(1400, 102.0));
+        
+        let stats = engine.get_rolling_stats("AAPL");
+        assert_eq!(stats.len(), 2);
+        
+        // Ventana de 1s en ts=1400: solo quedan las muestras de 500 y 1400
+        assert_eq!(stats[0].window_ms, 1000);
+        assert_eq!(stats[0].samples, 2);
+        assert!((stats[0].avg_spread - 2.5).abs() < 1e-9);
+        
+        // Ventana de 10s: las tres muestras
+        assert_eq!(stats[1].samples, 3);
+        assert!((stats[1].avg_spread - 2.0).abs() < 1e-9);
+        assert_eq!(stats[1].avg_bids_depth, 10.0);
+        
+        engine.reset_symbol("AAPL");
+        assert!(engine.get_rolling_stats("AAPL").is_empty());
+    }
+
+    #[test]
+    fn test_liquidity_alerts_edge_triggered() {
+        let mut engine = LiquidityEngine::new();
+        engine.set_alert_config(LiquidityAlertConfig::new(Some(3.0), Some(5.0), Some(0.5)), None);
+        
+        let snapshot = |ts: u64, ask: f64, bid_size: f64| BookSnapshot {
+            ts,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 100.0, size: bid_size }],
+            asks: vec![Level { price: ask, size: 10.0 }],
+        };
+        
+        // Línea base con spread 0.01
+        for ts in 0..5 {
+            engine.on_snapshot(&snapshot(ts, 100.01, 10.0));
+        }
+        assert!(engine.drain_alerts().is_empty());
+        
+        // Spread se dispara a 0.10 (> 3 × mediana)
+        engine.on_snapshot(&snapshot(5, 100.10, 10.0));
+        let alerts = engine.drain_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, "spread_spike");
+        
+        // La condición persiste => no se repite la alerta
+        engine.on_snapshot(&snapshot(6, 100.10, 10.0));
+        assert!(engine.drain_alerts().is_empty());
+        
+        // Profundidad bid baja e imbalance extremo a la vez
+        engine.on_snapshot(&snapshot(7, 100.01, 1.0));
+        let kinds: Vec<String> = engine.drain_alerts().into_iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, vec!["low_depth".to_string(), "imbalance".to_string()]);
+    }
+
+    #[test]
+    fn test_liquidity_alerts_symbol_override() {
+        let mut engine = LiquidityEngine::new();
+        engine.set_alert_config(LiquidityAlertConfig::new(None, Some(5.0), None), Some("MSFT".to_string()));
+        
+        let snapshot = |symbol: &str| BookSnapshot {
+            ts: 0,
+            symbol: symbol.to_string(),
+            bids: vec![Level { price: 100.0, size: 1.0 }],
+            asks: vec![Level { price: 100.01, size: 1.0 }],
+        };
+        
+        // Sin configuración global, AAPL no genera alertas
+        engine.on_snapshot(&snapshot("AAPL"));
+        engine.on_snapshot(&snapshot("MSFT"));
+        
+        let alerts = engine.drain_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].symbol, "MSFT");
+    }
+
+    #[test]
+    fn test_liquidity_one_sided_book_rejected() {
+        let mut engine = LiquidityEngine::new();
+        let snapshot = BookSnapshot {
+            ts: 0,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: 100.0, size: 1.0 }],
+            asks: vec![],
+        };
+        
+        assert!(engine.on_snapshot(&snapshot).is_none());
+        assert_eq!(engine.get_rejections(Some("AAPL")).get("empty_book"), Some(&1));
+        
+        engine.set_strict(true);
+        assert!(engine.py_on_snapshot(&snapshot).is_err());
+        assert_eq!(engine.get_rejections(None).get("empty_book"), Some(&2));
+    }
+
+    #[test]
+    fn test_liquidity_crossed_book_policies() {
+        let mut engine = LiquidityEngine::new();
+        let book = |bid: f64, ask: f64| BookSnapshot {
+            ts: 0,
+            symbol: "AAPL".to_string(),
+            bids: vec![Level { price: bid, size: 1.0 }],
+            asks: vec![Level { price: ask, size: 1.0 }],
+        };
+        
+        // skip (por defecto): descartado y contado
+        assert!(engine.on_snapshot(&book(100.02, 100.01)).is_none());
+        assert!(engine.on_snapshot(&book(100.0, 100.0)).is_none());
+        let rejections = engine.get_rejections(Some("AAPL"));
+        assert_eq!(rejections.get("crossed_book"), Some(&1));
+        assert_eq!(rejections.get("locked_book"), Some(&1));
+        
+        // alert: se procesa y queda la alerta
+        engine.set_book_policy("alert").unwrap();
+        assert!(engine.on_snapshot(&book(100.0, 100.0)).is_some());
+        let alerts = engine.drain_book_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, "locked_book");
+        
+        // raise: excepción aunque strict esté desactivado
+        engine.set_book_policy("raise").unwrap();
+        assert!(engine.py_on_snapshot(&book(100.02, 100.01)).is_err());
+        assert!(engine.set_book_policy("ignore").is_err());
+    }
+
+    #[test]
+    fn test_liquidity_effective_and_twa_spread() {
+        let mut engine = LiquidityEngine::new();
+        engine.set_rolling_windows_ms(vec![1000, 10_000]).unwrap();
+        let trade = |ts: u64, price: f64, side: &str| {
+            let mut trade = Trade::new(ts, price, 1.0, "AAPL".to_string());
+            trade.side = Some(side.to_string());
+            trade
+        };
+        
+        // Sin quote no hay referencia para el spread efectivo
+        assert!(engine.on_trade(&trade(100, 100.0, "BUY")).is_none());
+        
+        engine.on_quote(&Quote::new(0, "AAPL".to_string(), 100.00, 5.0, 100.10, 5.0));
+        engine.on_quote(&Quote::new(600, "AAPL".to_string(), 100.00, 5.0, 100.04, 5.0));
+        
+        // Compra dentro del spread: 0.01 sobre el mid, mejora 0.01 frente al ask
+        let buy = engine.on_trade(&trade(800, 100.03, "BUY")).unwrap();
+        assert!((buy.effective_spread - 0.02).abs() < 1e-9);
+        assert!((buy.price_improvement - 0.01).abs() < 1e-9);
+        assert!((buy.quoted_spread - 0.04).abs() < 1e-9);
+        assert_eq!(buy.quote_age_ms, 200);
+        
+        // Venta por debajo del bid: mejora negativa
+        let sell = engine.on_trade(&trade(900, 99.99, "SELL")).unwrap();
+        assert!((sell.effective_spread - 0.06).abs() < 1e-9);
+        assert!((sell.price_improvement + 0.01).abs() < 1e-9);
+        
+        // Hasta ts=900: 0.10 durante 600 ms y 0.04 durante 300 ms
+        let stats = engine.get_spread_stats("AAPL", None);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].quoted_ms, 900);
+        assert!((stats[0].twa_quoted_spread - 0.08).abs() < 1e-9);
+        assert_eq!(stats[0].trades, 2);
+        assert!((stats[0].avg_effective_spread - 0.04).abs() < 1e-9);
+        assert!(stats[0].avg_price_improvement.abs() < 1e-9);
+        
+        // Ventana de 1s en ts=1500: (500, 1500] => 0.10 durante 100 ms y 0.04 durante 900 ms
+        let stats = engine.get_spread_stats("AAPL", Some(1500));
+        assert_eq!(stats[0].quoted_ms, 1000);
+        assert!((stats[0].twa_quoted_spread - 0.046).abs() < 1e-9);
+        
+        engine.reset_symbol("AAPL");
+        assert!(engine.get_spread_stats("AAPL", None).is_empty());
+    }
+
+    #[test]
+    fn test_liquidity_added_and_pulled() {
+        let mut engine = LiquidityEngine::new();
+        assert!(engine.set_near_touch_levels(0).is_err());
+        engine.set_near_touch_levels(2).unwrap();
+        let book = |ts: u64, bids: &[(f64, f64)], asks: &[(f64, f64)]| BookSnapshot {
+            ts,
+            symbol: "AAPL".to_string(),
+            bids: bids.iter().map(|&(price, size)| Level { price, size }).collect(),
+            asks: asks.iter().map(|&(price, size)| Level { price, size }).collect(),
+        };
+        
+        let first = engine.on_snapshot(&book(1_000,
+            &[(100.00, 10.0), (99.99, 20.0), (99.98, 30.0), (99.97, 40.0)],
+            &[(100.01, 10.0), (100.02, 10.0), (100.03, 10.0)])).unwrap();
+        assert_eq!((first.bid_added, first.bid_pulled, first.ask_added, first.ask_pulled), (0.0, 0.0, 0.0, 0.0));
+        
+        // 99.97 y 100.04 quedan fuera de la profundidad común: no cuentan
+        let metrics = engine.on_snapshot(&book(2_000,
+            &[(100.00, 4.0), (99.99, 20.0), (99.98, 50.0)],
+            &[(100.02, 10.0), (100.03, 15.0), (100.04, 10.0)])).unwrap();
+        assert_eq!((metrics.bid_added, metrics.bid_pulled), (20.0, 6.0));
+        assert_eq!((metrics.near_bid_added, metrics.near_bid_pulled), (0.0, 6.0));
+        assert_eq!((metrics.ask_added, metrics.ask_pulled), (5.0, 10.0));
+        assert_eq!((metrics.near_ask_added, metrics.near_ask_pulled), (5.0, 10.0));
+        
+        engine.reset_symbol("AAPL");
+        let metrics = engine.on_snapshot(&book(3_000, &[(100.00, 1.0)], &[(100.01, 1.0)])).unwrap();
+        assert_eq!(metrics.bid_pulled, 0.0);
+    }
+
+    #[test]
+    fn test_liquidity_depth_override() {
+        assert!(LiquidityEngine::py_new(0).is_err());
+        let mut engine = LiquidityEngine::py_new(2).unwrap();
+        assert!(engine.set_depth_levels(0).is_err());
+        
+        let first = create_test_snapshot();
+        assert_eq!(engine.on_snapshot(&first).unwrap().bids_depth, 300.0);
+        let deep = engine.try_on_snapshot_with_depth(&first, 50).unwrap();
+        assert_eq!((deep.bids_depth, deep.asks_depth), (450.0, 450.0));
+        // Sin tocar el estado: una sola muestra en las medias móviles
+        assert_eq!(engine.get_rolling_stats("AAPL")[0].samples, 1);
+        
+        // El tercer nivel crece: solo lo ve la consulta con 3 niveles
+        let mut next = create_test_snapshot();
+        next.ts += 1_000;
+        next.bids[2].size = 250.0;
+        assert_eq!(engine.try_on_snapshot_with_depth(&next, 3).unwrap().bid_added, 100.0);
+        assert_eq!(engine.on_snapshot(&next).unwrap().bid_added, 0.0);
+        // Ya procesado, se sigue comparando con el snapshot anterior
+        assert_eq!(engine.try_on_snapshot_with_depth(&next, 3).unwrap().bid_added, 100.0);
+        assert_eq!(engine.get_rolling_stats("AAPL")[0].samples, 2);
+    }
+
+    #[test]
+    fn test_liquidity_notional_depth() {
+        let mut engine = LiquidityEngine::new();
+        let metrics = engine.on_snapshot(&create_test_snapshot()).unwrap();
+        assert_eq!(metrics.bids_notional, None);
+        
+        engine.notional = true;
+        let metrics = engine.on_snapshot(&create_test_snapshot()).unwrap();
+        assert_eq!(metrics.bids_depth, 450.0);
+        // 149.99*100 + 149.98*200 + 149.97*150
+        assert!((metrics.bids_notional.unwrap() - 67_490.5).abs() < 1e-6);
+        assert!((metrics.asks_notional.unwrap() - 67_509.5).abs() < 1e-6);
+    }
+}
@@ -2,13 +2,63 @@
 //! 
 //! Implementaciones de indicadores técnicos en Rust para máxima performance.
 
+pub mod absorption;
+pub mod atr;
+pub mod book_pressure;
+pub mod classifier;
+pub mod correlation;
 pub mod cvd;
+pub mod cvd_bars;
+pub mod enrichment;
+pub mod flow_signal;
+pub mod footprint;
+pub mod gaps;
 pub mod liquidity;
 pub mod heatmap;
+pub mod iceberg;
+pub mod indicator;
+pub mod liquidations;
+pub mod moving_average;
+pub mod open_interest;
+pub mod quote;
 pub mod vwap;
+pub mod rolling_vwap;
+pub mod rsi;
+pub mod spread;
+pub mod sweep;
+pub mod tape;
+pub mod volume_profile;
+pub mod volatility;
+pub mod vpin;
+pub mod walls;
 
 // Re-exportar engines principales
+pub use absorption::AbsorptionDetector;
+pub use atr::ATREngine;
+pub use book_pressure::BookPressureEngine;
+pub use classifier::TradeClassifier;
+pub use correlation::CorrelationEngine;
 pub use cvd::CVDEngine;
+pub use cvd_bars::CVDBarEngine;
+pub use enrichment::TradeEnricher;
+pub use flow_signal::FlowSignalEngine;
+pub use footprint::FootprintEngine;
+pub use gaps::GapDetector;
 pub use liquidity::LiquidityEngine;
 pub use heatmap::HeatmapEngine;
+pub use iceberg::IcebergDetector;
+pub use indicator::{Indicator, IndicatorOutput};
+pub use liquidations::LiquidationEngine;
+pub use moving_average::MovingAverageEngine;
+pub use open_interest::OIEngine;
+pub use quote::QuoteEngine;
 pub use vwap::VWAPEngine;
+pub use rolling_vwap::RollingVWAPEngine;
+pub use rsi::RSIEngine;
+pub use spread::SpreadEngine;
+pub use sweep::SweepDetector;
+pub use tape::TapeEngine;
+pub use volume_profile::VolumeProfileEngine;
+pub use volatility::VolatilityEngine;
+pub use vpin::VPINEngine;
+pub use walls::WallTracker;
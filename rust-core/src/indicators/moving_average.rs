@@ -0,0 +1,250 @@
+//! # Moving Average Engine
+//! 
+//! SMA, EMA y WMA incrementales (O(1) por valor) por símbolo y periodo.
+//! Se alimenta con el cierre de barras (`on_bar`) o el precio de trades (`on_trade`).
+
+#[cfg(feature = "python")]
+use numpy::PyReadonlyArray1;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+#[cfg(feature = "python")]
+use crate::numpy_batch;
+use crate::types::{Bar, MovingAverageMetrics, Trade};
+use crate::utils::count_symbols;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Tipo de media móvil
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MAKind {
+    Sma,
+    Ema,
+    Wma,
+}
+
+impl MAKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_ascii_uppercase().as_str() {
+            "SMA" => Some(Self::Sma),
+            "EMA" => Some(Self::Ema),
+            "WMA" => Some(Self::Wma),
+            _ => None,
+        }
+    }
+}
+
+/// Estado incremental de las tres medias para un periodo
+#[derive(Clone, Debug)]
+struct MAState {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    // Σ i·x_i con pesos 1..k (el más reciente pesa k)
+    weighted_sum: f64,
+    ema: Option<f64>,
+}
+
+impl MAState {
+    fn new(period: usize) -> Self {
+        Self { period, window: VecDeque::with_capacity(period), sum: 0.0, weighted_sum: 0.0, ema: None }
+    }
+    
+    fn push(&mut self, value: f64) {
+        if self.window.len() == self.period {
+            // Al desplazar la ventana cada peso baja en 1: W' = W + n·x - S
+            self.weighted_sum += self.period as f64 * value - self.sum;
+            self.sum += value - self.window.pop_front().unwrap_or(0.0);
+        } else {
+            self.weighted_sum += (self.window.len() + 1) as f64 * value;
+            self.sum += value;
+        }
+        self.window.push_back(value);
+        
+        // EMA sembrada con la SMA del primer periodo completo
+        if self.is_ready() {
+            let alpha = 2.0 / (self.period as f64 + 1.0);
+            self.ema = Some(match self.ema {
+                Some(ema) => ema + alpha * (value - ema),
+                None => self.sum / self.period as f64,
+            });
+        }
+    }
+    
+    fn is_ready(&self) -> bool {
+        self.window.len() == self.period
+    }
+    
+    fn average(&self, kind: MAKind) -> Option<f64> {
+        if !self.is_ready() {
+            return None;
+        }
+        let n = self.period as f64;
+        match kind {
+            MAKind::Sma => Some(self.sum / n),
+            MAKind::Ema => self.ema,
+            MAKind::Wma => Some(self.weighted_sum / (n * (n + 1.0) / 2.0)),
+        }
+    }
+}
+
+py_class! {
+/// Engine de medias móviles por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct MovingAverageEngine {
+    #[pyo3(get)]
+    pub periods: Vec<usize>,
+    // Estado por (symbol, period)
+    state: Arc<DashMap<(String, usize), MAState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl MovingAverageEngine {
+    #[new]
+    #[pyo3(signature = (periods=vec![9, 20, 50]))]
+    pub fn new(periods: Vec<usize>) -> Result<Self, ProcessError> {
+        if periods.is_empty() || periods.contains(&0) {
+            return Err(ProcessError::invalid("periods must be non-empty and > 0"));
+        }
+        let mut periods = periods;
+        periods.sort_unstable();
+        periods.dedup();
+        Ok(Self { periods, state: Arc::new(DashMap::new()) })
+    }
+    
+    /// Procesa una barra (usa el cierre)
+    pub fn on_bar(&self, bar: &Bar) -> Vec<MovingAverageMetrics> {
+        if bar.close <= 0.0 {
+            return Vec::new();
+        }
+        self.update(&bar.symbol, bar.ts, bar.close)
+    }
+    
+    /// Procesa un trade (usa el precio)
+    pub fn on_trade(&self, trade: &Trade) -> Vec<MovingAverageMetrics> {
+        if trade.price <= 0.0 || trade.size <= 0.0 {
+            return Vec::new();
+        }
+        self.update(&trade.symbol, trade.ts, trade.price)
+    }
+    
+    /// Procesa arrays numpy de un símbolo; devuelve dict de arrays sma_{p}, ema_{p}
+    /// y wma_{p} por periodo con una fila por trade
+    #[cfg(feature = "python")]
+    pub fn on_trade_numpy<'py>(&self, py: Python<'py>, symbol: &str, ts: PyReadonlyArray1<'py, i64>,
+                               price: PyReadonlyArray1<'py, f64>, size: PyReadonlyArray1<'py, f64>) -> PyResult<Bound<'py, PyDict>> {
+        let trades = numpy_batch::trades_from_numpy(symbol, ts, price, size, None)?;
+        let results: Vec<_> = py.allow_threads(|| trades.iter().map(|t| self.on_trade(t)).collect());
+        numpy_batch::to_dict(py, numpy_batch::ma_columns(&self.periods, &results))
+    }
+    
+    /// Media actual de un símbolo: kind = "SMA" | "EMA" | "WMA"
+    pub fn get_ma(&self, symbol: &str, kind: &str, period: usize) -> Result<Option<f64>, ProcessError> {
+        let kind = MAKind::parse(kind).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid moving average kind: {}", kind))
+        })?;
+        Ok(self.state.get(&(symbol.to_string(), period)).and_then(|s| s.average(kind)))
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.retain(|key, _| key.0 != symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("MovingAverageEngine(periods={:?}, entries={})", self.periods, self.state.len())
+    }
+}
+}
+
+impl MovingAverageEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.state.iter().map(|e| e.key().0.clone()))
+    }
+    
+    fn update(&self, symbol: &str, ts: u64, value: f64) -> Vec<MovingAverageMetrics> {
+        self.periods.iter()
+            .map(|&period| {
+                let mut state = self.state.entry((symbol.to_string(), period))
+                    .or_insert_with(|| MAState::new(period));
+                state.push(value);
+                MovingAverageMetrics {
+                    symbol: symbol.to_string(),
+                    ts,
+                    period,
+                    sma: state.average(MAKind::Sma),
+                    ema: state.average(MAKind::Ema),
+                    wma: state.average(MAKind::Wma),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: u64, close: f64) -> Bar {
+        Bar::new(ts, close, close, close, close, 1.0, "1m".to_string(), "AAPL".to_string())
+    }
+
+    #[test]
+    fn test_moving_average_validation() {
+        assert!(MovingAverageEngine::new(vec![]).is_err());
+        assert!(MovingAverageEngine::new(vec![0, 5]).is_err());
+        
+        let engine = MovingAverageEngine::new(vec![3]).unwrap();
+        assert!(engine.get_ma("AAPL", "HMA", 3).is_err());
+    }
+
+    #[test]
+    fn test_moving_average_values() {
+        let engine = MovingAverageEngine::new(vec![3]).unwrap();
+        
+        engine.on_bar(&bar(1, 1.0));
+        let metrics = engine.on_bar(&bar(2, 2.0));
+        assert_eq!(metrics[0].sma, None);
+        
+        engine.on_bar(&bar(3, 3.0));
+        assert_eq!(engine.get_ma("AAPL", "sma", 3).unwrap(), Some(2.0));
+        assert_eq!(engine.get_ma("AAPL", "EMA", 3).unwrap(), Some(2.0));
+        // (1·1 + 2·2 + 3·3) / 6
+        assert!((engine.get_ma("AAPL", "WMA", 3).unwrap().unwrap() - 14.0 / 6.0).abs() < 1e-12);
+        
+        let metrics = engine.on_bar(&bar(4, 4.0));
+        assert_eq!(metrics[0].sma, Some(3.0));
+        // EMA: 2 + 0.5·(4 - 2)
+        assert_eq!(metrics[0].ema, Some(3.0));
+        // (2·1 + 3·2 + 4·3) / 6
+        assert!((metrics[0].wma.unwrap() - 20.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_moving_average_multiple_periods_and_trades() {
+        let engine = MovingAverageEngine::new(vec![2, 1, 2]).unwrap();
+        assert_eq!(engine.periods, vec![1, 2]);
+        
+        let metrics = engine.on_trade(&Trade::new(1, 10.0, 1.0, "AAPL".to_string()));
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].sma, Some(10.0));
+        assert_eq!(metrics[1].sma, None);
+        
+        engine.reset_symbol("AAPL");
+        assert_eq!(engine.get_ma("AAPL", "SMA", 1).unwrap(), None);
+    }
+}
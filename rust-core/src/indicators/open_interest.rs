@@ -0,0 +1,284 @@
+//! # Open Interest Engine
+//! 
+//! Seguimiento de open interest y funding para futuros perpetuos:
+//! - Velocidad de cambio del OI entre lecturas.
+//! - Precio medio ponderado por aumentos de OI (precio de entrada de las
+//!   posiciones nuevas).
+//! - Divergencias en una ventana temporal entre OI y precio, y entre OI y CVD.
+//! 
+//! El OI del símbolo es la suma de la última lectura de cada exchange.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use crate::types::{FundingRate, OIMetrics, OpenInterest, Quote, Trade};
+use crate::utils::count_symbols;
+use super::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+use super::cvd::UNKNOWN_EXCHANGE;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Estado por símbolo
+#[derive(Clone, Debug, Default)]
+struct OIState {
+    last_price: Option<f64>,
+    cvd: f64,
+    last_oi: Option<(u64, f64)>,
+    // Σ precio·ΔOI y Σ ΔOI para aumentos de OI
+    added_notional: f64,
+    added_oi: f64,
+    // (ts, oi, price, cvd) de cada lectura dentro de la ventana
+    history: VecDeque<(u64, f64, f64, f64)>,
+    funding_rate: Option<f64>,
+}
+
+/// Signo con cero explícito (cambios nulos no cuentan como divergencia)
+fn direction(x: f64) -> i8 {
+    if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 }
+}
+
+py_class! {
+/// Engine de open interest por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct OIEngine {
+    #[pyo3(get)]
+    pub window_ms: u64,
+    state: Arc<DashMap<String, OIState>>,
+    oi_by_exchange: Arc<DashMap<(String, String), f64>>,
+    classifier: TradeClassifier,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl OIEngine {
+    #[new]
+    #[pyo3(signature = (window_ms=300_000))]
+    pub fn new(window_ms: u64) -> Result<Self, ProcessError> {
+        if window_ms == 0 {
+            return Err(ProcessError::invalid("window_ms must be > 0"));
+        }
+        Ok(Self {
+            window_ms,
+            state: Arc::new(DashMap::new()),
+            oi_by_exchange: Arc::new(DashMap::new()),
+            classifier: TradeClassifier::new(),
+        })
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade: actualiza último precio y CVD del símbolo
+    pub fn on_trade(&self, trade: &Trade) {
+        if trade.price <= 0.0 || trade.size <= 0.0 {
+            return;
+        }
+        let side = self.classifier.classify(trade);
+        let mut state = self.state.entry(trade.symbol.clone()).or_default();
+        state.last_price = Some(trade.price);
+        match side {
+            SIDE_BUY => state.cvd += trade.size,
+            SIDE_SELL => state.cvd -= trade.size,
+            _ => {}
+        }
+    }
+    
+    /// Registra el último funding rate del símbolo
+    pub fn on_funding(&self, funding: &FundingRate) {
+        if !funding.rate.is_finite() {
+            return;
+        }
+        let mut state = self.state.entry(funding.symbol.clone()).or_default();
+        state.funding_rate = Some(funding.rate);
+        if state.last_price.is_none() {
+            state.last_price = funding.mark_price.filter(|p| *p > 0.0);
+        }
+    }
+    
+    /// Procesa una lectura de OI y devuelve las métricas actualizadas
+    pub fn on_open_interest(&self, oi: &OpenInterest) -> Option<OIMetrics> {
+        if !(oi.open_interest.is_finite() && oi.open_interest >= 0.0) {
+            return None;
+        }
+        
+        let exchange = oi.exchange.as_deref().unwrap_or(UNKNOWN_EXCHANGE);
+        self.oi_by_exchange.insert((oi.symbol.clone(), exchange.to_string()), oi.open_interest);
+        let total_oi: f64 = self.oi_by_exchange.iter()
+            .filter(|e| e.key().0 == oi.symbol)
+            .map(|e| *e.value())
+            .sum();
+        
+        let mut state = self.state.entry(oi.symbol.clone()).or_default();
+        let price = state.last_price.unwrap_or(0.0);
+        
+        let (oi_change, oi_change_rate) = match state.last_oi {
+            Some((last_ts, last_oi)) => {
+                let change = total_oi - last_oi;
+                let dt_secs = oi.ts.saturating_sub(last_ts) as f64 / 1000.0;
+                (change, if dt_secs > 0.0 { change / dt_secs } else { 0.0 })
+            }
+            None => (0.0, 0.0),
+        };
+        state.last_oi = Some((oi.ts, total_oi));
+        
+        if oi_change > 0.0 && price > 0.0 {
+            state.added_notional += price * oi_change;
+            state.added_oi += oi_change;
+        }
+        
+        // Ventana (ts - window_ms, ts] para las divergencias
+        let cvd = state.cvd;
+        state.history.push_back((oi.ts, total_oi, price, cvd));
+        if let Some(cutoff) = oi.ts.checked_sub(self.window_ms) {
+            while state.history.front().is_some_and(|h| h.0 <= cutoff) {
+                state.history.pop_front();
+            }
+        }
+        let (_, first_oi, first_price, first_cvd) = *state.history.front()?;
+        
+        let pct = |now: f64, then: f64| if then > 0.0 { (now - then) / then * 100.0 } else { 0.0 };
+        let window_oi_change_pct = pct(total_oi, first_oi);
+        let window_price_change_pct = pct(price, first_price);
+        let window_cvd_change = cvd - first_cvd;
+        let oi_dir = direction(window_oi_change_pct);
+        
+        Some(OIMetrics {
+            symbol: oi.symbol.clone(),
+            ts: oi.ts,
+            open_interest: total_oi,
+            oi_change,
+            oi_change_rate,
+            oi_weighted_price: if state.added_oi > 0.0 { state.added_notional / state.added_oi } else { price },
+            window_oi_change_pct,
+            window_price_change_pct,
+            window_cvd_change,
+            price_divergence: oi_dir * direction(window_price_change_pct) < 0,
+            cvd_divergence: oi_dir * direction(window_cvd_change) < 0,
+            funding_rate: state.funding_rate,
+        })
+    }
+    
+    /// OI total actual de un símbolo
+    pub fn get_open_interest(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol).and_then(|s| s.last_oi.map(|(_, oi)| oi))
+    }
+    
+    /// Último funding rate de un símbolo
+    pub fn get_funding_rate(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol).and_then(|s| s.funding_rate)
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+        self.oi_by_exchange.retain(|key, _| key.0 != symbol);
+        self.classifier.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+        self.oi_by_exchange.clear();
+        self.classifier.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("OIEngine(window_ms={}, symbols={})", self.window_ms, self.state.len())
+    }
+}
+}
+
+impl OIEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.state.iter().map(|e| e.key().clone())
+            .chain(self.oi_by_exchange.iter().map(|e| e.key().0.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, price: f64, size: f64, side: &str) -> Trade {
+        Trade {
+            ts,
+            price,
+            size,
+            symbol: "BTCUSDT".to_string(),
+            side: Some(side.to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    fn oi(ts: u64, value: f64, exchange: Option<&str>) -> OpenInterest {
+        OpenInterest::new(ts, "BTCUSDT".to_string(), value, exchange.map(|e| e.to_string()))
+    }
+
+    #[test]
+    fn test_oi_change_rate_and_weighted_price() {
+        let engine = OIEngine::new(300_000).unwrap();
+        assert!(OIEngine::new(0).is_err());
+        
+        engine.on_trade(&trade(0, 100.0, 1.0, "BUY"));
+        let m = engine.on_open_interest(&oi(0, 1_000.0, None)).unwrap();
+        assert_eq!(m.oi_change, 0.0);
+        
+        engine.on_trade(&trade(1_000, 110.0, 1.0, "BUY"));
+        let m = engine.on_open_interest(&oi(2_000, 1_100.0, None)).unwrap();
+        assert_eq!(m.oi_change, 100.0);
+        assert_eq!(m.oi_change_rate, 50.0);
+        assert_eq!(m.oi_weighted_price, 110.0);
+        
+        // Las bajadas de OI no mueven el precio de entrada
+        engine.on_trade(&trade(3_000, 120.0, 1.0, "SELL"));
+        let m = engine.on_open_interest(&oi(4_000, 1_050.0, None)).unwrap();
+        assert_eq!(m.oi_weighted_price, 110.0);
+        assert_eq!(m.oi_change_rate, -25.0);
+    }
+
+    #[test]
+    fn test_oi_sums_exchanges() {
+        let engine = OIEngine::new(300_000).unwrap();
+        
+        engine.on_open_interest(&oi(0, 500.0, Some("binance")));
+        let m = engine.on_open_interest(&oi(1, 300.0, Some("bybit"))).unwrap();
+        assert_eq!(m.open_interest, 800.0);
+        
+        let m = engine.on_open_interest(&oi(2, 600.0, Some("binance"))).unwrap();
+        assert_eq!(m.open_interest, 900.0);
+        assert_eq!(engine.get_open_interest("BTCUSDT"), Some(900.0));
+    }
+
+    #[test]
+    fn test_oi_divergences_and_funding() {
+        let engine = OIEngine::new(60_000).unwrap();
+        
+        engine.on_trade(&trade(0, 100.0, 5.0, "BUY"));
+        engine.on_open_interest(&oi(0, 1_000.0, None));
+        engine.on_funding(&FundingRate::new(10, "BTCUSDT".to_string(), 0.0001, None, None));
+        
+        // OI sube mientras precio y CVD bajan: posiciones cortas nuevas
+        engine.on_trade(&trade(30_000, 95.0, 8.0, "SELL"));
+        let m = engine.on_open_interest(&oi(30_000, 1_200.0, None)).unwrap();
+        assert_eq!(m.window_oi_change_pct, 20.0);
+        assert_eq!(m.window_price_change_pct, -5.0);
+        assert_eq!(m.window_cvd_change, -8.0);
+        assert!(m.price_divergence);
+        assert!(m.cvd_divergence);
+        assert_eq!(m.funding_rate, Some(0.0001));
+        
+        // Fuera de la ventana solo queda la lectura actual
+        let m = engine.on_open_interest(&oi(200_000, 1_300.0, None)).unwrap();
+        assert_eq!(m.window_oi_change_pct, 0.0);
+        assert!(!m.price_divergence);
+    }
+}
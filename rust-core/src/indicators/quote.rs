@@ -0,0 +1,335 @@
+//! # Quote Engine
+//! 
+//! Analítica ligera de top-of-book por símbolo a partir de `Quote`:
+//! - mejor bid/ask entre venues (estilo NBBO): la última quote de cada
+//!   `exchange` se combina tomando el bid más alto y el ask más bajo, con el
+//!   tamaño sumado de los venues que están en el mejor precio
+//! - ritmo de actualización: quotes por segundo en una ventana rolling
+//! - spread medio ponderado por tiempo: cada spread del NBBO pesa lo que
+//!   estuvo vigente hasta la siguiente quote
+//! 
+//! Entre venues el NBBO puede quedar locked o cruzado (spread <= 0); se
+//! reporta tal cual. Una quote individual cruzada se rechaza.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::types::{Quote, QuoteMetrics};
+use crate::errors::{validate_quote, ProcessError, Rejections};
+use crate::utils::NeumaierSum;
+use super::cvd::UNKNOWN_EXCHANGE;
+use crate::py_macros::{py_class, py_methods};
+
+/// Última quote de un venue
+#[derive(Clone, Copy, Debug)]
+struct VenueQuote {
+    bid: f64,
+    bid_size: f64,
+    ask: f64,
+    ask_size: f64,
+}
+
+/// Mejor bid/ask combinado de los venues
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Nbbo {
+    bid: f64,
+    bid_size: f64,
+    ask: f64,
+    ask_size: f64,
+}
+
+impl Nbbo {
+    fn spread(&self) -> f64 {
+        self.ask - self.bid
+    }
+}
+
+/// Estado por símbolo
+#[derive(Clone, Debug, Default)]
+struct QuoteState {
+    venues: HashMap<String, VenueQuote>,
+    // ts de las quotes dentro de la ventana
+    updates: VecDeque<u64>,
+    // ts desde el que rige el spread actual del NBBO
+    last: Option<(u64, f64)>,
+    // Integral spread · dt y tiempo acumulado (ms)
+    spread_area: NeumaierSum,
+    duration_ms: u64,
+}
+
+impl QuoteState {
+    fn nbbo(&self) -> Option<Nbbo> {
+        let bid = self.venues.values().map(|q| q.bid).fold(f64::NEG_INFINITY, f64::max);
+        let ask = self.venues.values().map(|q| q.ask).fold(f64::INFINITY, f64::min);
+        if !bid.is_finite() || !ask.is_finite() {
+            return None;
+        }
+        Some(Nbbo {
+            bid,
+            bid_size: self.venues.values().filter(|q| q.bid == bid).map(|q| q.bid_size).sum(),
+            ask,
+            ask_size: self.venues.values().filter(|q| q.ask == ask).map(|q| q.ask_size).sum(),
+        })
+    }
+    
+    /// Spread medio ponderado por tiempo; sin tiempo transcurrido, el actual
+    fn twa_spread(&self) -> Option<f64> {
+        if self.duration_ms == 0 {
+            return self.last.map(|(_, spread)| spread);
+        }
+        Some(self.spread_area.value() / self.duration_ms as f64)
+    }
+}
+
+py_class! {
+/// Engine de NBBO, ritmo de quotes y spread ponderado por tiempo
+#[pyclass]
+#[derive(Clone)]
+pub struct QuoteEngine {
+    #[pyo3(get)]
+    pub window_ms: u64,
+    state: Arc<DashMap<String, QuoteState>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl QuoteEngine {
+    #[new]
+    #[pyo3(signature = (window_ms=1_000))]
+    pub fn new(window_ms: u64) -> Result<Self, ProcessError> {
+        if window_ms == 0 {
+            return Err(ProcessError::invalid("window_ms must be > 0"));
+        }
+        Ok(Self {
+            window_ms,
+            state: Arc::new(DashMap::new()),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Procesa una quote y devuelve el NBBO y sus métricas.
+    /// En modo `strict` una quote inválida lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_quote")]
+    pub fn py_on_quote(&self, quote: &Quote) -> Result<Option<QuoteMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_quote(quote))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// NBBO actual como (bid, bid_size, ask, ask_size)
+    pub fn get_nbbo(&self, symbol: &str) -> Option<(f64, f64, f64, f64)> {
+        self.state.get(symbol)
+            .and_then(|s| s.nbbo())
+            .map(|n| (n.bid, n.bid_size, n.ask, n.ask_size))
+    }
+    
+    /// Spread actual del NBBO
+    pub fn get_spread(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol).and_then(|s| s.nbbo()).map(|n| n.spread())
+    }
+    
+    /// Spread medio ponderado por tiempo desde la primera quote
+    pub fn get_twa_spread(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol).and_then(|s| s.twa_spread())
+    }
+    
+    /// Venues con quote vigente
+    pub fn get_venues(&self, symbol: &str) -> Vec<String> {
+        let mut venues: Vec<String> = self.state.get(symbol)
+            .map(|s| s.venues.keys().cloned().collect())
+            .unwrap_or_default();
+        venues.sort();
+        venues
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("QuoteEngine(window_ms={}, symbols={})", self.window_ms, self.state.len())
+    }
+}
+}
+
+impl QuoteEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.state.len()
+    }
+    
+    /// Procesa una quote; si no es válida se descarta y queda contada en las rejections
+    pub fn on_quote(&self, quote: &Quote) -> Option<QuoteMetrics> {
+        self.try_on_quote(quote).unwrap_or_default()
+    }
+    
+    /// Valida y procesa una quote; Err si no es válida (también queda contada)
+    pub fn try_on_quote(&self, quote: &Quote) -> Result<Option<QuoteMetrics>, ProcessError> {
+        self.rejections.check(validate_quote(quote))?;
+        self.rejections.check_timestamp(&quote.symbol, quote.ts)?;
+        Ok(self.process_quote(quote))
+    }
+    
+    /// Actualiza el venue, el NBBO y los acumuladores del símbolo
+    fn process_quote(&self, quote: &Quote) -> Option<QuoteMetrics> {
+        let mut state = self.state.entry(quote.symbol.clone()).or_default();
+        
+        let exchange = quote.exchange.as_deref().unwrap_or(UNKNOWN_EXCHANGE);
+        state.venues.insert(exchange.to_string(), VenueQuote {
+            bid: quote.bid,
+            bid_size: quote.bid_size,
+            ask: quote.ask,
+            ask_size: quote.ask_size,
+        });
+        let nbbo = state.nbbo()?;
+        
+        // El spread anterior rigió hasta esta quote; un ts que retrocede no suma tiempo
+        let mut now = quote.ts;
+        if let Some((since, spread)) = state.last {
+            let dt = quote.ts.saturating_sub(since);
+            state.spread_area.add(spread * dt as f64);
+            state.duration_ms += dt;
+            now = now.max(since);
+        }
+        state.last = Some((now, nbbo.spread()));
+        
+        // Ventana (ts - window_ms, ts]
+        state.updates.push_back(quote.ts);
+        if let Some(cutoff) = quote.ts.checked_sub(self.window_ms) {
+            while state.updates.front().is_some_and(|&ts| ts <= cutoff) {
+                state.updates.pop_front();
+            }
+        }
+        
+        let mid = (nbbo.bid + nbbo.ask) / 2.0;
+        let spread = nbbo.spread();
+        Some(QuoteMetrics {
+            ts: quote.ts,
+            symbol: quote.symbol.clone(),
+            best_bid: nbbo.bid,
+            best_bid_size: nbbo.bid_size,
+            best_ask: nbbo.ask,
+            best_ask_size: nbbo.ask_size,
+            mid,
+            spread,
+            spread_bps: spread / mid * 10_000.0,
+            updates_per_second: state.updates.len() as f64 / (self.window_ms as f64 / 1000.0),
+            twa_spread: state.twa_spread().unwrap_or(spread),
+            venues: state.venues.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn quote(ts: u64, exchange: Option<&str>, bid: f64, bid_size: f64, ask: f64, ask_size: f64) -> Quote {
+        let mut quote = Quote::new(ts, "AAPL".to_string(), bid, bid_size, ask, ask_size);
+        quote.exchange = exchange.map(str::to_string);
+        quote
+    }
+    
+    #[test]
+    fn test_nbbo_across_venues() {
+        let engine = QuoteEngine::new(1000).unwrap();
+        engine.on_quote(&quote(1000, Some("NASDAQ"), 100.00, 5.0, 100.04, 5.0)).unwrap();
+        engine.on_quote(&quote(1000, Some("NYSE"), 100.01, 3.0, 100.04, 2.0)).unwrap();
+        let m = engine.on_quote(&quote(1000, Some("ARCA"), 100.01, 1.0, 100.05, 9.0)).unwrap();
+        
+        assert_eq!(m.best_bid, 100.01);
+        assert_eq!(m.best_bid_size, 4.0);
+        assert_eq!(m.best_ask, 100.04);
+        assert_eq!(m.best_ask_size, 7.0);
+        assert_eq!(m.venues, 3);
+        assert!((m.spread - 0.03).abs() < 1e-9);
+        assert_eq!(engine.get_venues("AAPL"), vec!["ARCA", "NASDAQ", "NYSE"]);
+        
+        // Una nueva quote del venue reemplaza la anterior
+        let m = engine.on_quote(&quote(1001, Some("NYSE"), 99.99, 3.0, 100.06, 2.0)).unwrap();
+        assert_eq!((m.best_bid, m.best_bid_size), (100.01, 1.0));
+        assert_eq!((m.best_ask, m.best_ask_size), (100.04, 5.0));
+    }
+    
+    #[test]
+    fn test_twa_spread_and_update_rate() {
+        let engine = QuoteEngine::new(1000).unwrap();
+        let m = engine.on_quote(&quote(0, None, 100.0, 1.0, 101.0, 1.0)).unwrap();
+        assert_eq!(m.twa_spread, 1.0);
+        
+        // Spread 1.0 durante 300 ms, luego 2.0 durante 100 ms
+        engine.on_quote(&quote(300, None, 100.0, 1.0, 102.0, 1.0)).unwrap();
+        let m = engine.on_quote(&quote(400, None, 100.0, 1.0, 100.5, 1.0)).unwrap();
+        assert!((m.twa_spread - 500.0 / 400.0).abs() < 1e-9);
+        assert_eq!(m.spread, 0.5);
+        assert_eq!(m.updates_per_second, 3.0);
+        
+        // Las quotes de hace más de window_ms salen de la ventana
+        let m = engine.on_quote(&quote(1300, None, 100.0, 1.0, 100.5, 1.0)).unwrap();
+        assert_eq!(m.updates_per_second, 2.0);
+        assert_eq!(engine.get_twa_spread("AAPL"), Some(m.twa_spread));
+    }
+    
+    #[test]
+    fn test_invalid_quotes_are_rejected() {
+        let engine = QuoteEngine::new(1000).unwrap();
+        assert!(engine.on_quote(&quote(1000, None, 101.0, 1.0, 100.0, 1.0)).is_none());
+        assert!(engine.on_quote(&quote(1000, None, f64::NAN, 1.0, 100.0, 1.0)).is_none());
+        assert!(engine.on_quote(&quote(1000, None, 99.0, -1.0, 100.0, 1.0)).is_none());
+        
+        let rejections = engine.get_rejections(Some("AAPL"));
+        assert_eq!(rejections["crossed_book"], 1);
+        assert_eq!(rejections["invalid_price"], 1);
+        assert_eq!(rejections["invalid_size"], 1);
+        assert_eq!(engine.get_nbbo("AAPL"), None);
+        
+        assert!(QuoteEngine::new(0).is_err());
+    }
+}
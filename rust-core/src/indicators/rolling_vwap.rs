@@ -0,0 +1,277 @@
+//! # Rolling VWAP Engine
+//! 
+//! VWAP sobre ventana deslizante (últimos N ms y/o últimos X contratos)
+//! con ring buffer por símbolo y evicción incremental O(1) amortizada.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::types::{Trade, VWAPMetrics};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::utils::rolling::TimeWindow;
+use super::vwap::{VWAPAccumulator, DEFAULT_BAND_MULTIPLIERS};
+use crate::py_macros::{py_class, py_methods};
+
+/// Ventana deslizante de un símbolo: entradas (ts, price, size) + sumas
+#[derive(Clone, Debug, Default)]
+pub struct RollingWindow {
+    // (ts, (price, size))
+    entries: TimeWindow<(f64, f64)>,
+    acc: VWAPAccumulator,
+}
+
+impl RollingWindow {
+    pub fn push(&mut self, ts: u64, price: f64, size: f64) {
+        self.entries.push(ts, (price, size));
+        self.acc.update(price, size);
+    }
+    
+    /// Elimina entradas con ts <= now - window_ms
+    pub fn evict_time(&mut self, now: u64, window_ms: u64) {
+        let acc = &mut self.acc;
+        self.entries.evict(now, window_ms, |(price, size)| acc.remove(price, size));
+        self.resync_if_empty();
+    }
+    
+    /// Recorta las entradas más antiguas hasta que el volumen sea <= max_volume.
+    /// La entrada más antigua puede quedar parcialmente recortada.
+    pub fn evict_volume(&mut self, max_volume: f64) {
+        while self.acc.v_sum > max_volume {
+            let Some(front) = self.entries.front_mut() else { break };
+            let excess = self.acc.v_sum - max_volume;
+            let (_, (price, size)) = *front;
+            if size <= excess {
+                self.acc.remove(price, size);
+                self.entries.pop_front();
+            } else {
+                self.acc.remove(price, excess);
+                front.1 = (price, size - excess);
+                break;
+            }
+        }
+        self.resync_if_empty();
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    pub fn accumulator(&self) -> &VWAPAccumulator {
+        &self.acc
+    }
+    
+    /// Sin entradas las sumas deben ser exactamente 0 (elimina deriva flotante)
+    fn resync_if_empty(&mut self) {
+        if self.entries.is_empty() {
+            self.acc = VWAPAccumulator::default();
+        }
+    }
+}
+
+py_class! {
+/// Engine de VWAP sobre ventana deslizante por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct RollingVWAPEngine {
+    /// Ventana temporal en ms (None = sin límite temporal)
+    #[pyo3(get)]
+    pub window_ms: Option<u64>,
+    /// Ventana de volumen en contratos (None = sin límite de volumen)
+    #[pyo3(get)]
+    pub window_volume: Option<f64>,
+    windows: Arc<DashMap<String, RollingWindow>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl RollingVWAPEngine {
+    #[new]
+    #[pyo3(signature = (window_ms=None, window_volume=None))]
+    pub fn new(window_ms: Option<u64>, window_volume: Option<f64>) -> Result<Self, ProcessError> {
+        if window_ms.is_none() && window_volume.is_none() {
+            return Err(ProcessError::invalid(
+                "window_ms or window_volume must be set"));
+        }
+        if window_ms == Some(0) || window_volume.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+            return Err(ProcessError::invalid(
+                "window sizes must be positive"));
+        }
+        Ok(Self {
+            window_ms,
+            window_volume,
+            windows: Arc::new(DashMap::new()),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Procesa un trade, desaloja lo que sale de la ventana y devuelve el VWAP.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<VWAPMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Obtiene el VWAP de la ventana actual para un símbolo
+    pub fn get_vwap(&self, symbol: &str) -> Option<f64> {
+        self.windows.get(symbol).map(|w| w.accumulator().vwap())
+    }
+    
+    /// Número de trades retenidos en la ventana del símbolo
+    pub fn window_len(&self, symbol: &str) -> usize {
+        self.windows.get(symbol).map(|w| w.len()).unwrap_or(0)
+    }
+    
+    /// Resetea la ventana de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.windows.remove(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.windows.clear();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("RollingVWAPEngine(window_ms={:?}, window_volume={:?}, symbols={})",
+                self.window_ms, self.window_volume, self.windows.len())
+    }
+}
+}
+
+impl RollingVWAPEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.windows.len()
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<VWAPMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<VWAPMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade, desaloja lo que sale de la ventana y devuelve el VWAP
+    fn process_trade(&self, trade: &Trade) -> Option<VWAPMetrics> {
+        let mut window = self.windows.entry(trade.symbol.clone()).or_default();
+        window.push(trade.ts, trade.price, trade.size);
+        
+        if let Some(window_ms) = self.window_ms {
+            window.evict_time(trade.ts, window_ms);
+        }
+        if let Some(window_volume) = self.window_volume {
+            window.evict_volume(window_volume);
+        }
+        
+        Some(window.accumulator().to_metrics(None, DEFAULT_BAND_MULTIPLIERS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Trade;
+
+    fn trade(ts: u64, price: f64, size: f64) -> Trade {
+        Trade { ts, price, size, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None }
+    }
+
+    #[test]
+    fn test_rolling_vwap_requires_window() {
+        assert!(RollingVWAPEngine::new(None, None).is_err());
+        assert!(RollingVWAPEngine::new(Some(0), None).is_err());
+        assert!(RollingVWAPEngine::new(None, Some(-1.0)).is_err());
+        assert!(RollingVWAPEngine::new(Some(1000), None).is_ok());
+    }
+
+    #[test]
+    fn test_rolling_vwap_time_window() {
+        let engine = RollingVWAPEngine::new(Some(1000), None).unwrap();
+        
+        engine.on_trade(&trade(0, 100.0, 10.0));
+        engine.on_trade(&trade(500, 110.0, 10.0));
+        assert_eq!(engine.get_vwap("AAPL"), Some(105.0));
+        
+        // En ts=1500 el trade de ts=0 (y el de 500) caen fuera de la ventana
+        let metrics = engine.on_trade(&trade(1500, 120.0, 10.0)).unwrap();
+        assert!((metrics.vwap - 120.0).abs() < 1e-9);
+        assert_eq!(engine.window_len("AAPL"), 1);
+    }
+
+    #[test]
+    fn test_rolling_vwap_volume_window_partial_eviction() {
+        let engine = RollingVWAPEngine::new(None, Some(20.0)).unwrap();
+        
+        engine.on_trade(&trade(0, 100.0, 10.0));
+        engine.on_trade(&trade(1, 110.0, 10.0));
+        // Entra 5 @ 120 => se recortan 5 contratos del trade más antiguo
+        let metrics = engine.on_trade(&trade(2, 120.0, 5.0)).unwrap();
+        
+        assert!((metrics.v_sum - 20.0).abs() < 1e-9);
+        let expected = (100.0 * 5.0 + 110.0 * 10.0 + 120.0 * 5.0) / 20.0;
+        assert!((metrics.vwap - expected).abs() < 1e-9);
+        assert_eq!(engine.window_len("AAPL"), 3);
+    }
+
+    #[test]
+    fn test_rolling_vwap_reset() {
+        let engine = RollingVWAPEngine::new(Some(1000), None).unwrap();
+        engine.on_trade(&trade(0, 100.0, 10.0));
+        
+        engine.reset_symbol("AAPL");
+        assert_eq!(engine.get_vwap("AAPL"), None);
+        assert_eq!(engine.window_len("AAPL"), 0);
+    }
+}
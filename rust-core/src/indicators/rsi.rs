@@ -0,0 +1,235 @@
+//! # RSI Engine
+//! 
+//! RSI incremental con suavizado de Wilder por símbolo y periodo:
+//! las primeras `period` variaciones se promedian y después
+//! avg = (avg·(n-1) + x) / n. Se alimenta con barras (p. ej. del BarAggregator).
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::types::{Bar, RSIMetrics};
+use crate::utils::count_symbols;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Estado de Wilder para un periodo
+#[derive(Clone, Debug)]
+struct RSIState {
+    period: usize,
+    last_close: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl RSIState {
+    fn new(period: usize) -> Self {
+        Self { period, last_close: None, changes: 0, avg_gain: 0.0, avg_loss: 0.0 }
+    }
+    
+    fn push(&mut self, close: f64) {
+        let Some(last) = self.last_close.replace(close) else { return };
+        let change = close - last;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        
+        self.changes += 1;
+        let n = self.period as f64;
+        if self.changes <= self.period {
+            // Fase de semilla: media simple de las primeras variaciones
+            self.avg_gain += (gain - self.avg_gain) / self.changes as f64;
+            self.avg_loss += (loss - self.avg_loss) / self.changes as f64;
+        } else {
+            self.avg_gain = (self.avg_gain * (n - 1.0) + gain) / n;
+            self.avg_loss = (self.avg_loss * (n - 1.0) + loss) / n;
+        }
+    }
+    
+    fn rsi(&self) -> Option<f64> {
+        if self.changes < self.period {
+            return None;
+        }
+        Some(rsi_from_averages(self.avg_gain, self.avg_loss))
+    }
+}
+
+/// RSI = 100 - 100 / (1 + avg_gain / avg_loss); 50 si no hubo movimiento
+fn rsi_from_averages(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        return if avg_gain == 0.0 { 50.0 } else { 100.0 };
+    }
+    100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+}
+
+py_class! {
+/// Engine de RSI por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct RSIEngine {
+    #[pyo3(get)]
+    pub periods: Vec<usize>,
+    // Estado por (symbol, period)
+    state: Arc<DashMap<(String, usize), RSIState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl RSIEngine {
+    #[new]
+    #[pyo3(signature = (periods=vec![14]))]
+    pub fn new(periods: Vec<usize>) -> Result<Self, ProcessError> {
+        if periods.is_empty() || periods.contains(&0) {
+            return Err(ProcessError::invalid("periods must be non-empty and > 0"));
+        }
+        let mut periods = periods;
+        periods.sort_unstable();
+        periods.dedup();
+        Ok(Self { periods, state: Arc::new(DashMap::new()) })
+    }
+    
+    /// Procesa una barra (usa el cierre)
+    pub fn on_bar(&self, bar: &Bar) -> Vec<RSIMetrics> {
+        if bar.close <= 0.0 {
+            return Vec::new();
+        }
+        
+        self.periods.iter()
+            .map(|&period| {
+                let mut state = self.state.entry((bar.symbol.clone(), period))
+                    .or_insert_with(|| RSIState::new(period));
+                state.push(bar.close);
+                RSIMetrics {
+                    symbol: bar.symbol.clone(),
+                    ts: bar.ts,
+                    period,
+                    rsi: state.rsi(),
+                    avg_gain: state.avg_gain,
+                    avg_loss: state.avg_loss,
+                }
+            })
+            .collect()
+    }
+    
+    /// Procesa barras históricas en orden (backfill); libera el GIL mientras tanto
+    #[cfg(feature = "python")]
+    #[pyo3(name = "on_bar_batch")]
+    pub fn py_on_bar_batch(&self, py: Python<'_>, bars: Vec<Bar>) -> Vec<RSIMetrics> {
+        py.allow_threads(|| self.on_bar_batch(bars))
+    }
+    
+    /// RSI actual de un símbolo para un periodo
+    pub fn get_rsi(&self, symbol: &str, period: usize) -> Option<f64> {
+        self.state.get(&(symbol.to_string(), period)).and_then(|s| s.rsi())
+    }
+    
+    /// RSI de una serie de cierres sin tocar el estado (None en el warm-up);
+    /// libera el GIL mientras tanto
+    #[cfg(feature = "python")]
+    #[staticmethod]
+    #[pyo3(name = "compute_batch", signature = (closes, period=14))]
+    pub fn py_compute_batch(py: Python<'_>, closes: Vec<f64>, period: usize) -> Result<Vec<Option<f64>>, ProcessError> {
+        py.allow_threads(|| Self::compute_batch(closes, period))
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.retain(|key, _| key.0 != symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("RSIEngine(periods={:?}, entries={})", self.periods, self.state.len())
+    }
+}
+}
+
+impl RSIEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.state.iter().map(|e| e.key().0.clone()))
+    }
+    
+    /// Procesa barras históricas en orden (backfill); el estado queda listo para tiempo real
+    pub fn on_bar_batch(&self, bars: Vec<Bar>) -> Vec<RSIMetrics> {
+        bars.iter().flat_map(|bar| self.on_bar(bar)).collect()
+    }
+    
+    /// RSI de una serie de cierres sin tocar el estado (None en el warm-up)
+    pub fn compute_batch(closes: Vec<f64>, period: usize) -> Result<Vec<Option<f64>>, ProcessError> {
+        if period == 0 {
+            return Err(ProcessError::invalid("period must be > 0"));
+        }
+        let mut state = RSIState::new(period);
+        Ok(closes.into_iter()
+            .map(|close| {
+                state.push(close);
+                state.rsi()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: u64, close: f64) -> Bar {
+        Bar::new(ts, close, close, close, close, 1.0, "1m".to_string(), "AAPL".to_string())
+    }
+
+    #[test]
+    fn test_rsi_validation() {
+        assert!(RSIEngine::new(vec![]).is_err());
+        assert!(RSIEngine::new(vec![0]).is_err());
+        assert!(RSIEngine::compute_batch(vec![1.0], 0).is_err());
+    }
+
+    #[test]
+    fn test_rsi_wilder_smoothing() {
+        let engine = RSIEngine::new(vec![2]).unwrap();
+        
+        engine.on_bar(&bar(1, 10.0));
+        let metrics = engine.on_bar(&bar(2, 11.0));
+        assert_eq!(metrics[0].rsi, None);
+        
+        // Semilla: gain (1 + 0)/2, loss (0 + 1)/2 -> RSI 50
+        engine.on_bar(&bar(3, 10.0));
+        assert_eq!(engine.get_rsi("AAPL", 2), Some(50.0));
+        
+        // Wilder: gain (0.5 + 2)/2 = 1.25, loss (0.5 + 0)/2 = 0.25 -> RS 5
+        let metrics = engine.on_bar(&bar(4, 12.0));
+        assert_eq!(metrics[0].avg_gain, 1.25);
+        assert_eq!(metrics[0].avg_loss, 0.25);
+        assert!((metrics[0].rsi.unwrap() - (100.0 - 100.0 / 6.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rsi_extremes() {
+        let rsi = RSIEngine::compute_batch(vec![1.0, 2.0, 3.0, 4.0], 3).unwrap();
+        assert_eq!(rsi, vec![None, None, None, Some(100.0)]);
+        
+        let flat = RSIEngine::compute_batch(vec![5.0; 4], 3).unwrap();
+        assert_eq!(flat[3], Some(50.0));
+    }
+
+    #[test]
+    fn test_rsi_batch_matches_streaming() {
+        let closes = [44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08,
+                      45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64];
+        let engine = RSIEngine::new(vec![14]).unwrap();
+        let bars: Vec<Bar> = closes.iter().enumerate().map(|(i, c)| bar(i as u64, *c)).collect();
+        
+        let streamed: Vec<Option<f64>> = engine.on_bar_batch(bars).iter().map(|m| m.rsi).collect();
+        let batch = RSIEngine::compute_batch(closes.to_vec(), 14).unwrap();
+        assert_eq!(streamed, batch);
+        
+        // Serie de referencia de Wilder: avg_gain 0.2386, avg_loss 0.0996 -> 70.46
+        assert!((batch[14].unwrap() - 70.464).abs() < 0.001);
+    }
+}
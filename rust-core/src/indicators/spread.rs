@@ -0,0 +1,414 @@
+//! # Spread Engine
+//! 
+//! Instrumentos sintéticos definidos como combinación ponderada de símbolos
+//! (perp menos spot, pares, cestas) calculados a partir de los trades de
+//! cada pata:
+//! - "difference": Σ wᵢ·pᵢ (p. ej. `[("BTCUSDT-PERP", 1.0), ("BTCUSDT", -1.0)]`)
+//! - "ratio": (w₀·p₀) / (w₁·p₁), exactamente dos patas
+//! 
+//! Cada trade de una pata, una vez que todas tienen precio, añade una muestra
+//! a la ventana de `window` muestras de la que salen la media, la desviación
+//! típica y el z-score del spread. La correlación es la de los log-retornos
+//! de las dos primeras patas muestreados en "refresh time": un retorno se
+//! registra cuando ambas patas han cambiado de precio desde el anterior, así
+//! los trades asíncronos de cada pata no se emparejan con retornos nulos.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use crate::types::{SpreadMetrics, Trade};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::utils::pearson_correlation;
+use crate::py_macros::{py_class, py_methods};
+
+/// Cómo se combinan los precios de las patas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpreadKind {
+    #[default]
+    Difference,
+    Ratio,
+}
+
+impl SpreadKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_ascii_lowercase().as_str() {
+            "difference" => Some(Self::Difference),
+            "ratio" => Some(Self::Ratio),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Difference => "difference",
+            Self::Ratio => "ratio",
+        }
+    }
+    
+    fn value(&self, legs: &[(String, f64)], prices: &[f64]) -> f64 {
+        match self {
+            Self::Difference => legs.iter().zip(prices).map(|((_, w), p)| w * p).sum(),
+            Self::Ratio => (legs[0].1 * prices[0]) / (legs[1].1 * prices[1]),
+        }
+    }
+}
+
+/// Definición e histórico de un spread
+#[derive(Clone, Debug)]
+struct SpreadState {
+    legs: Vec<(String, f64)>,
+    kind: SpreadKind,
+    values: VecDeque<f64>,
+    // Log-retornos (pata 0, pata 1) en refresh time
+    returns: VecDeque<(f64, f64)>,
+    // Precios de las patas 0 y 1 al registrar el último retorno
+    anchor: Option<(f64, f64)>,
+    last: Option<SpreadMetrics>,
+}
+
+impl SpreadState {
+    fn new(legs: Vec<(String, f64)>, kind: SpreadKind) -> Self {
+        Self {
+            legs,
+            kind,
+            values: VecDeque::new(),
+            returns: VecDeque::new(),
+            anchor: None,
+            last: None,
+        }
+    }
+    
+    fn has_leg(&self, symbol: &str) -> bool {
+        self.legs.iter().any(|(leg, _)| leg == symbol)
+    }
+    
+    fn clear(&mut self) {
+        self.values.clear();
+        self.returns.clear();
+        self.anchor = None;
+        self.last = None;
+    }
+    
+    fn push(&mut self, name: &str, ts: u64, prices: Vec<f64>, window: usize) -> Option<SpreadMetrics> {
+        let value = self.kind.value(&self.legs, &prices);
+        if !value.is_finite() {
+            return None;
+        }
+        
+        if prices.len() >= 2 {
+            match self.anchor {
+                Some((p0, p1)) if prices[0] != p0 && prices[1] != p1 => {
+                    self.returns.push_back(((prices[0] / p0).ln(), (prices[1] / p1).ln()));
+                    if self.returns.len() > window {
+                        self.returns.pop_front();
+                    }
+                    self.anchor = Some((prices[0], prices[1]));
+                }
+                Some(_) => {}
+                None => self.anchor = Some((prices[0], prices[1])),
+            }
+        }
+        self.values.push_back(value);
+        if self.values.len() > window {
+            self.values.pop_front();
+        }
+        
+        let n = self.values.len();
+        let mean = self.values.iter().sum::<f64>() / n as f64;
+        let std = if n >= 2 {
+            (self.values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        let correlation = (self.returns.len() >= 2).then(|| {
+            let (xs, ys): (Vec<f64>, Vec<f64>) = self.returns.iter().copied().unzip();
+            pearson_correlation(&xs, &ys)
+        });
+        
+        let metrics = SpreadMetrics {
+            name: name.to_string(),
+            ts,
+            value,
+            mean,
+            std,
+            zscore: (std > 0.0).then(|| (value - mean) / std),
+            correlation,
+            samples: n,
+            leg_prices: prices,
+        };
+        self.last = Some(metrics.clone());
+        Some(metrics)
+    }
+}
+
+py_class! {
+/// Engine de spreads entre símbolos
+#[pyclass]
+#[derive(Clone)]
+pub struct SpreadEngine {
+    /// Muestras en la ventana de media, z-score y correlación
+    #[pyo3(get)]
+    pub window: usize,
+    // nombre -> definición e histórico
+    spreads: Arc<DashMap<String, SpreadState>>,
+    // símbolo -> último precio
+    prices: Arc<DashMap<String, f64>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl SpreadEngine {
+    #[new]
+    #[pyo3(signature = (window=100))]
+    pub fn new(window: usize) -> Result<Self, ProcessError> {
+        if window < 2 {
+            return Err(ProcessError::invalid("window must be >= 2"));
+        }
+        Ok(Self {
+            window,
+            spreads: Arc::new(DashMap::new()),
+            prices: Arc::new(DashMap::new()),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Define (o redefine, descartando su histórico) un spread a partir de
+    /// sus patas (símbolo, peso) y el tipo: "difference" o "ratio"
+    #[pyo3(signature = (name, legs, kind="difference"))]
+    pub fn add_spread(&self, name: &str, legs: Vec<(String, f64)>, kind: &str) -> Result<(), ProcessError> {
+        let kind = SpreadKind::parse(kind).ok_or_else(|| {
+            ProcessError::invalid(format!("invalid spread kind: {} (difference or ratio)", kind))
+        })?;
+        if name.is_empty() || legs.is_empty() {
+            return Err(ProcessError::invalid("name and legs must not be empty"));
+        }
+        if legs.iter().any(|(_, w)| !w.is_finite() || *w == 0.0) {
+            return Err(ProcessError::invalid("leg weights must be finite and != 0"));
+        }
+        if legs.iter().map(|(symbol, _)| symbol).collect::<HashSet<_>>().len() != legs.len() {
+            return Err(ProcessError::invalid("leg symbols must be distinct"));
+        }
+        if kind == SpreadKind::Ratio && legs.len() != 2 {
+            return Err(ProcessError::invalid("ratio spreads need exactly 2 legs"));
+        }
+        self.spreads.insert(name.to_string(), SpreadState::new(legs, kind));
+        Ok(())
+    }
+    
+    /// Elimina un spread; false si no existía
+    pub fn remove_spread(&self, name: &str) -> bool {
+        self.spreads.remove(name).is_some()
+    }
+    
+    /// Nombres de los spreads definidos, ordenados
+    pub fn get_spreads(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.spreads.iter().map(|e| e.key().clone()).collect();
+        names.sort();
+        names
+    }
+    
+    /// Patas y tipo de un spread
+    pub fn get_definition(&self, name: &str) -> Option<(Vec<(String, f64)>, String)> {
+        self.spreads.get(name).map(|s| (s.legs.clone(), s.kind.as_str().to_string()))
+    }
+    
+    /// Procesa un trade y devuelve las métricas de los spreads en los que
+    /// participa el símbolo. En modo `strict` un trade inválido lanza su
+    /// excepción; si no, devuelve una lista vacía
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Vec<SpreadMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Últimas métricas de un spread
+    pub fn get_spread(&self, name: &str) -> Option<SpreadMetrics> {
+        self.spreads.get(name).and_then(|s| s.last.clone())
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Olvida el precio de un símbolo y el histórico de los spreads en los
+    /// que participa (las definiciones se mantienen)
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.prices.remove(symbol);
+        for mut spread in self.spreads.iter_mut() {
+            if spread.has_leg(symbol) {
+                spread.clear();
+            }
+        }
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea precios e históricos de todos los spreads
+    pub fn reset_all(&self) {
+        self.prices.clear();
+        for mut spread in self.spreads.iter_mut() {
+            spread.clear();
+        }
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SpreadEngine(window={}, spreads={}, symbols={})",
+                self.window, self.spreads.len(), self.prices.len())
+    }
+}
+}
+
+impl SpreadEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.prices.len()
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Vec<SpreadMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Vec<SpreadMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Actualiza el precio de la pata y muestrea los spreads que ya tienen
+    /// precio en todas sus patas
+    fn process_trade(&self, trade: &Trade) -> Vec<SpreadMetrics> {
+        self.prices.insert(trade.symbol.clone(), trade.price);
+        
+        let mut metrics = Vec::new();
+        for mut spread in self.spreads.iter_mut() {
+            if !spread.has_leg(&trade.symbol) {
+                continue;
+            }
+            let prices: Option<Vec<f64>> = spread.legs.iter()
+                .map(|(symbol, _)| self.prices.get(symbol).map(|p| *p))
+                .collect();
+            let Some(prices) = prices else { continue };
+            let name = spread.key().clone();
+            metrics.extend(spread.value_mut().push(&name, trade.ts, prices, self.window));
+        }
+        metrics.sort_by(|a, b| a.name.cmp(&b.name));
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn trade(ts: u64, symbol: &str, price: f64) -> Trade {
+        Trade::new(ts, price, 1.0, symbol.to_string())
+    }
+    
+    #[test]
+    fn test_spread_definition_validation() {
+        assert!(SpreadEngine::new(1).is_err());
+        let engine = SpreadEngine::new(10).unwrap();
+        assert!(engine.add_spread("basis", vec![], "difference").is_err());
+        assert!(engine.add_spread("basis", vec![("A".to_string(), 0.0)], "difference").is_err());
+        assert!(engine.add_spread("basis", vec![("A".to_string(), 1.0), ("A".to_string(), -1.0)], "difference").is_err());
+        assert!(engine.add_spread("pair", vec![("A".to_string(), 1.0)], "ratio").is_err());
+        assert!(engine.add_spread("pair", vec![("A".to_string(), 1.0), ("B".to_string(), 1.0)], "product").is_err());
+        
+        engine.add_spread("pair", vec![("A".to_string(), 1.0), ("B".to_string(), 2.0)], "ratio").unwrap();
+        assert_eq!(engine.get_spreads(), vec!["pair".to_string()]);
+        assert_eq!(engine.get_definition("pair").unwrap().1, "ratio");
+        assert!(engine.remove_spread("pair"));
+        assert!(!engine.remove_spread("pair"));
+    }
+    
+    #[test]
+    fn test_difference_spread_zscore() {
+        let engine = SpreadEngine::new(3).unwrap();
+        engine.add_spread("basis", vec![("PERP".to_string(), 1.0), ("SPOT".to_string(), -1.0)], "difference").unwrap();
+        
+        // Sin precio de la otra pata no hay muestra
+        assert!(engine.on_trade(&trade(0, "PERP", 101.0)).is_empty());
+        let metrics = engine.on_trade(&trade(1, "SPOT", 100.0));
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].value, 1.0);
+        assert_eq!(metrics[0].zscore, None);
+        assert_eq!(metrics[0].correlation, None);
+        
+        engine.on_trade(&trade(2, "PERP", 102.0));
+        let m = engine.on_trade(&trade(3, "PERP", 104.0)).remove(0);
+        // Ventana [1, 2, 4]: media 7/3, std muestral sqrt(7/3)
+        assert_eq!(m.samples, 3);
+        assert_eq!(m.leg_prices, vec![104.0, 100.0]);
+        assert!((m.mean - 7.0 / 3.0).abs() < 1e-12);
+        assert!((m.std - (7.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!((m.zscore.unwrap() - (4.0 - 7.0 / 3.0) / (7.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        
+        // La ventana descarta la muestra más antigua
+        let m = engine.on_trade(&trade(4, "PERP", 104.0)).remove(0);
+        assert!((m.mean - 10.0 / 3.0).abs() < 1e-12);
+        assert_eq!(engine.get_spread("basis").unwrap().ts, 4);
+        
+        engine.reset_symbol("SPOT");
+        assert!(engine.get_spread("basis").is_none());
+        assert!(engine.on_trade(&trade(5, "PERP", 104.0)).is_empty());
+    }
+    
+    #[test]
+    fn test_ratio_spread_correlation() {
+        let engine = SpreadEngine::new(10).unwrap();
+        engine.add_spread("pair", vec![("A".to_string(), 1.0), ("B".to_string(), 2.0)], "ratio").unwrap();
+        
+        engine.on_trade(&trade(0, "A", 100.0));
+        let m = engine.on_trade(&trade(0, "B", 25.0)).remove(0);
+        assert_eq!(m.value, 2.0);
+        
+        // Las dos patas se mueven juntas; los trades de A con B sin cambiar
+        // no registran retorno
+        let mut last = None;
+        for (i, (a, b)) in [(101.0, 25.5), (103.0, 26.0), (102.0, 25.8)].into_iter().enumerate() {
+            engine.on_trade(&trade(i as u64 + 1, "A", a));
+            last = engine.on_trade(&trade(i as u64 + 1, "B", b)).pop();
+        }
+        let m = last.unwrap();
+        assert!(m.correlation.unwrap() > 0.0);
+        assert!((m.value - 102.0 / 51.6).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,311 @@
+//! # Sweep Detector
+//! 
+//! Detecta órdenes agresivas que barren el libro: ráfagas de trades de un
+//! símbolo separados como mucho `window_ms` entre sí, con precios que solo
+//! suben (barrido comprador) o solo bajan (vendedor). Trades al mismo precio
+//! continúan la ráfaga sin sumar nivel; un salto temporal o un cambio de
+//! dirección la cierra.
+//! 
+//! Una ráfaga se reporta como `SweepEvent` al cerrarse si alcanzó
+//! `min_levels` precios distintos y `min_notional`. El cierre llega con el
+//! trade siguiente o con `flush`; los eventos se pueden publicar en NATS con
+//! `NATSPublisher.publish_sweeps`.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::types::{SweepEvent, Trade};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use super::classifier::{SIDE_BUY, SIDE_SELL};
+use crate::py_macros::{py_class, py_methods};
+
+/// Ráfaga en curso de un símbolo
+#[derive(Clone, Debug)]
+struct Run {
+    start_ts: u64,
+    last_ts: u64,
+    // 1 ascendente, -1 descendente, 0 aún sin movimiento de precio
+    direction: i8,
+    trades: usize,
+    levels: usize,
+    start_price: f64,
+    last_price: f64,
+    volume: f64,
+    notional: f64,
+}
+
+impl Run {
+    fn start(trade: &Trade) -> Self {
+        Self {
+            start_ts: trade.ts,
+            last_ts: trade.ts,
+            direction: 0,
+            trades: 1,
+            levels: 1,
+            start_price: trade.price,
+            last_price: trade.price,
+            volume: trade.size,
+            notional: trade.price * trade.size,
+        }
+    }
+    
+    fn extend(&mut self, trade: &Trade, step: i8) {
+        if step != 0 {
+            self.direction = step;
+            self.levels += 1;
+        }
+        self.last_ts = self.last_ts.max(trade.ts);
+        self.last_price = trade.price;
+        self.trades += 1;
+        self.volume += trade.size;
+        self.notional += trade.price * trade.size;
+    }
+    
+    fn to_event(&self, symbol: &str) -> SweepEvent {
+        SweepEvent {
+            symbol: symbol.to_string(),
+            start_ts: self.start_ts,
+            end_ts: self.last_ts,
+            direction: if self.direction > 0 { SIDE_BUY } else { SIDE_SELL }.to_string(),
+            trades: self.trades,
+            levels: self.levels,
+            start_price: self.start_price,
+            end_price: self.last_price,
+            volume: self.volume,
+            notional: self.notional,
+            vwap: self.notional / self.volume,
+        }
+    }
+}
+
+py_class! {
+/// Detector de barridos por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct SweepDetector {
+    /// Separación máxima entre trades consecutivos de una ráfaga (ms)
+    #[pyo3(get)]
+    pub window_ms: u64,
+    #[pyo3(get)]
+    pub min_levels: usize,
+    #[pyo3(get)]
+    pub min_notional: f64,
+    runs: Arc<DashMap<String, Run>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl SweepDetector {
+    #[new]
+    #[pyo3(signature = (window_ms=50, min_levels=3, min_notional=0.0))]
+    pub fn new(window_ms: u64, min_levels: usize, min_notional: f64) -> Result<Self, ProcessError> {
+        if window_ms == 0 || min_levels < 2 {
+            return Err(ProcessError::invalid(
+                "window_ms must be > 0 and min_levels >= 2"));
+        }
+        if !min_notional.is_finite() || min_notional < 0.0 {
+            return Err(ProcessError::invalid("min_notional must be >= 0"));
+        }
+        Ok(Self {
+            window_ms,
+            min_levels,
+            min_notional,
+            runs: Arc::new(DashMap::new()),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Procesa un trade y devuelve el barrido que este cierra, si lo hay.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<SweepEvent>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Cierra las ráfagas sin trades desde hace más de `window_ms` a `now_ts`
+    /// (todas si es None) y devuelve los barridos resultantes
+    #[pyo3(signature = (now_ts=None))]
+    pub fn flush(&self, now_ts: Option<u64>) -> Vec<SweepEvent> {
+        let mut events = Vec::new();
+        self.runs.retain(|symbol, run| {
+            let expired = match now_ts {
+                Some(now) => now.saturating_sub(run.last_ts) > self.window_ms,
+                None => true,
+            };
+            if expired {
+                events.extend(self.qualify(symbol, run));
+            }
+            !expired
+        });
+        events.sort_by_key(|e| e.end_ts);
+        events
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.runs.remove(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.runs.clear();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SweepDetector(window_ms={}, min_levels={}, min_notional={})",
+                self.window_ms, self.min_levels, self.min_notional)
+    }
+}
+}
+
+impl SweepDetector {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.runs.len()
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<SweepEvent> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<SweepEvent>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Extiende la ráfaga del símbolo o la cierra y empieza otra con el trade
+    fn process_trade(&self, trade: &Trade) -> Option<SweepEvent> {
+        let Some(mut run) = self.runs.get_mut(&trade.symbol) else {
+            self.runs.insert(trade.symbol.clone(), Run::start(trade));
+            return None;
+        };
+        
+        let step = match trade.price.total_cmp(&run.last_price) {
+            Ordering::Greater => 1,
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+        };
+        let in_time = trade.ts.saturating_sub(run.last_ts) <= self.window_ms;
+        if in_time && (step == 0 || run.direction == 0 || step == run.direction) {
+            run.extend(trade, step);
+            return None;
+        }
+        let closed = std::mem::replace(&mut *run, Run::start(trade));
+        self.qualify(&trade.symbol, &closed)
+    }
+    
+    /// Barrido si la ráfaga alcanzó los umbrales
+    fn qualify(&self, symbol: &str, run: &Run) -> Option<SweepEvent> {
+        if run.direction == 0 || run.levels < self.min_levels || run.notional < self.min_notional {
+            return None;
+        }
+        let event = run.to_event(symbol);
+        tracing::debug!(symbol, direction = %event.direction, levels = event.levels,
+                        notional = event.notional, "sweep");
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn trade(ts: u64, price: f64, size: f64) -> Trade {
+        Trade::new(ts, price, size, "ES".to_string())
+    }
+    
+    #[test]
+    fn test_sweep_validation() {
+        assert!(SweepDetector::new(0, 3, 0.0).is_err());
+        assert!(SweepDetector::new(50, 1, 0.0).is_err());
+        assert!(SweepDetector::new(50, 3, -1.0).is_err());
+    }
+    
+    #[test]
+    fn test_ascending_sweep_closed_by_gap() {
+        let detector = SweepDetector::new(50, 3, 0.0).unwrap();
+        assert!(detector.on_trade(&trade(1000, 100.0, 1.0)).is_none());
+        assert!(detector.on_trade(&trade(1010, 100.0, 2.0)).is_none());
+        assert!(detector.on_trade(&trade(1020, 100.25, 1.0)).is_none());
+        assert!(detector.on_trade(&trade(1030, 100.5, 4.0)).is_none());
+        
+        // Más de window_ms sin trades: se cierra la ráfaga
+        let sweep = detector.on_trade(&trade(2000, 100.5, 1.0)).unwrap();
+        assert_eq!(sweep.direction, "BUY");
+        assert_eq!((sweep.start_ts, sweep.end_ts), (1000, 1030));
+        assert_eq!(sweep.trades, 4);
+        assert_eq!(sweep.levels, 3);
+        assert_eq!((sweep.start_price, sweep.end_price), (100.0, 100.5));
+        assert_eq!(sweep.volume, 8.0);
+        assert!((sweep.notional - 802.25).abs() < 1e-9);
+        assert!((sweep.vwap - 802.25 / 8.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_reversal_and_thresholds() {
+        let detector = SweepDetector::new(50, 3, 1_000.0).unwrap();
+        // Barrido vendedor de 3 niveles pero con poco nocional
+        for (ts, price) in [(0, 100.0), (10, 99.0), (20, 98.0)] {
+            assert!(detector.on_trade(&trade(ts, price, 1.0)).is_none());
+        }
+        // El cambio de dirección lo cierra sin evento
+        assert!(detector.on_trade(&trade(30, 99.0, 10.0)).is_none());
+        detector.on_trade(&trade(40, 100.0, 10.0));
+        detector.on_trade(&trade(50, 101.0, 10.0));
+        
+        assert!(detector.flush(Some(60)).is_empty());
+        let sweeps = detector.flush(Some(101));
+        assert_eq!(sweeps.len(), 1);
+        assert_eq!(sweeps[0].direction, "BUY");
+        assert_eq!(sweeps[0].levels, 3);
+        assert_eq!(sweeps[0].notional, 3_000.0);
+        assert!(detector.flush(None).is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+//! # Tape Engine
+//! 
+//! Analítica de la cinta por símbolo: trades por segundo, tamaño medio,
+//! volumen y nocional por segundo en una ventana rolling, y detección de
+//! prints de bloque cuyo tamaño supera un percentil de los últimos trades.
+//! La distribución de tamaños de toda la sesión se resume en un DDSketch
+//! (p50/p95/p99 con memoria acotada).
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::types::{LargePrint, Quote, TapeMetrics, Trade};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::utils::ewma::{Ewma, HalfLife};
+use crate::utils::sketch::{validate_quantiles, DDSketch, QuantileSketch};
+use super::classifier::TradeClassifier;
+use crate::py_macros::{py_class, py_methods};
+
+/// Estado por símbolo
+#[derive(Clone, Debug, Default)]
+struct TapeState {
+    // (ts, size, notional) dentro de la ventana
+    window: VecDeque<(u64, f64, f64)>,
+    volume: f64,
+    notional: f64,
+    // Últimos tamaños en orden de llegada y ordenados (para el percentil)
+    sizes: VecDeque<f64>,
+    sorted_sizes: Vec<f64>,
+    // Tamaños de la sesión
+    size_sketch: DDSketch,
+    // Media exponencial de trades_per_second (con suavizado)
+    tps_ewma: Option<Ewma>,
+}
+
+impl TapeState {
+    fn push_size(&mut self, size: f64, lookback: usize) {
+        self.sizes.push_back(size);
+        let pos = self.sorted_sizes.partition_point(|s| *s < size);
+        self.sorted_sizes.insert(pos, size);
+        
+        if self.sizes.len() > lookback {
+            if let Some(old) = self.sizes.pop_front() {
+                let pos = self.sorted_sizes.partition_point(|s| *s < old);
+                self.sorted_sizes.remove(pos);
+            }
+        }
+    }
+    
+    /// Tamaño en el percentil p (nearest-rank) de los últimos trades
+    fn size_percentile(&self, p: f64) -> Option<f64> {
+        let n = self.sorted_sizes.len();
+        let rank = ((p * n as f64).ceil() as usize).clamp(1, n.max(1));
+        self.sorted_sizes.get(rank - 1).copied()
+    }
+}
+
+py_class! {
+/// Engine de analítica de cinta por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct TapeEngine {
+    #[pyo3(get)]
+    pub window_ms: u64,
+    #[pyo3(get)]
+    pub percentile: f64,
+    #[pyo3(get)]
+    pub lookback: usize,
+    #[pyo3(get)]
+    pub min_samples: usize,
+    state: Arc<DashMap<String, TapeState>>,
+    classifier: TradeClassifier,
+    pending_prints: Arc<Mutex<Vec<LargePrint>>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+    // Vida media de smoothed_trades_per_second; None = sin suavizado
+    smoothing: Option<HalfLife>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl TapeEngine {
+    #[new]
+    #[pyo3(signature = (window_ms=10_000, percentile=0.99, lookback=1000, min_samples=100))]
+    pub fn new(window_ms: u64, percentile: f64, lookback: usize, min_samples: usize) -> Result<Self, ProcessError> {
+        if window_ms == 0 || lookback == 0 || min_samples > lookback {
+            return Err(ProcessError::invalid(
+                "window_ms and lookback must be > 0 and min_samples <= lookback"));
+        }
+        if !(percentile > 0.0 && percentile < 1.0) {
+            return Err(ProcessError::invalid("percentile must be in (0, 1)"));
+        }
+        Ok(Self {
+            window_ms,
+            percentile,
+            lookback,
+            min_samples,
+            state: Arc::new(DashMap::new()),
+            classifier: TradeClassifier::new(),
+            pending_prints: Arc::new(Mutex::new(Vec::new())),
+            rejections: Rejections::default(),
+            smoothing: None,
+        })
+    }
+    
+    /// Activa `smoothed_trades_per_second` con vida media en ms (ponderada
+    /// por tiempo) o en trades; sin argumentos lo desactiva. Reinicia la media.
+    #[pyo3(signature = (half_life_ms=None, half_life_events=None))]
+    pub fn set_smoothing(&mut self, half_life_ms: Option<u64>, half_life_events: Option<f64>) -> Result<(), ProcessError> {
+        self.smoothing = HalfLife::from_args(half_life_ms, half_life_events)?;
+        self.state.iter_mut().for_each(|mut state| state.tps_ewma = None);
+        Ok(())
+    }
+    
+    /// (half_life_ms, half_life_events) del suavizado
+    pub fn smoothing(&self) -> (Option<u64>, Option<f64>) {
+        HalfLife::to_args(self.smoothing)
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade y devuelve la velocidad de la cinta.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<TapeMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Umbral de tamaño actual para prints de bloque
+    pub fn get_size_threshold(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol)
+            .filter(|s| s.sizes.len() >= self.min_samples.max(1))
+            .and_then(|s| s.size_percentile(self.percentile))
+    }
+    
+    /// Cuantiles del tamaño de trade en la sesión (por defecto p50, p95 y
+    /// p99, con error relativo del 1%); None sin trades del símbolo
+    #[pyo3(signature = (symbol, quantiles=vec![0.5, 0.95, 0.99]))]
+    pub fn get_size_quantiles(&self, symbol: &str, quantiles: Vec<f64>) -> Result<Option<Vec<f64>>, ProcessError> {
+        validate_quantiles(&quantiles)?;
+        Ok(self.state.get(symbol).and_then(|s| quantiles.iter().map(|&q| s.size_sketch.quantile(q)).collect()))
+    }
+    
+    /// Copia del sketch de tamaños de la sesión (p. ej. para combinar símbolos)
+    pub fn get_size_sketch(&self, symbol: &str) -> Option<QuantileSketch> {
+        self.state.get(symbol).map(|s| QuantileSketch::from_sketch(s.size_sketch.clone()))
+    }
+    
+    /// Extrae los prints de bloque pendientes
+    pub fn drain_large_prints(&self) -> Vec<LargePrint> {
+        std::mem::take(&mut *self.pending_prints.lock())
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+        self.classifier.reset_all();
+        self.pending_prints.lock().clear();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("TapeEngine(window_ms={}, percentile={}, symbols={})",
+                self.window_ms, self.percentile, self.state.len())
+    }
+}
+}
+
+impl TapeEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.state.len()
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<TapeMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<TapeMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade y devuelve la velocidad de la cinta
+    fn process_trade(&self, trade: &Trade) -> Option<TapeMetrics> {
+        let side = self.classifier.classify(trade);
+        let notional = trade.price * trade.size;
+        let mut state = self.state.entry(trade.symbol.clone()).or_default();
+        
+        // El umbral se calcula con los trades previos, sin incluir este
+        if state.sizes.len() >= self.min_samples.max(1) {
+            if let Some(threshold) = state.size_percentile(self.percentile) {
+                if trade.size > threshold {
+                    tracing::debug!(symbol = %trade.symbol, size = trade.size, threshold, "large print");
+                    self.pending_prints.lock().push(LargePrint {
+                        ts: trade.ts,
+                        symbol: trade.symbol.clone(),
+                        price: trade.price,
+                        size: trade.size,
+                        side: side.to_string(),
+                        size_threshold: threshold,
+                    });
+                }
+            }
+        }
+        state.push_size(trade.size, self.lookback);
+        state.size_sketch.add(trade.size);
+        
+        // Ventana (ts - window_ms, ts]
+        state.window.push_back((trade.ts, trade.size, notional));
+        state.volume += trade.size;
+        state.notional += notional;
+        if let Some(cutoff) = trade.ts.checked_sub(self.window_ms) {
+            while let Some(&(ts, size, old_notional)) = state.window.front() {
+                if ts > cutoff {
+                    break;
+                }
+                state.volume -= size;
+                state.notional -= old_notional;
+                state.window.pop_front();
+            }
+        }
+        
+        let secs = self.window_ms as f64 / 1000.0;
+        let count = state.window.len();
+        let trades_per_second = count as f64 / secs;
+        let smoothed_trades_per_second = self.smoothing.map(|half_life| {
+            state.tps_ewma.get_or_insert_with(|| Ewma::new(half_life)).update(trade.ts, trades_per_second)
+        });
+        Some(TapeMetrics {
+            symbol: trade.symbol.clone(),
+            ts: trade.ts,
+            window_ms: self.window_ms,
+            trade_count: count,
+            trades_per_second,
+            avg_trade_size: state.volume / count as f64,
+            volume_per_second: state.volume / secs,
+            notional_per_second: state.notional / secs,
+            smoothed_trades_per_second,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, price: f64, size: f64) -> Trade {
+        Trade {
+            ts,
+            price,
+            size,
+            symbol: "AAPL".to_string(),
+            side: Some("BUY".to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_tape_validation() {
+        assert!(TapeEngine::new(0, 0.99, 1000, 100).is_err());
+        assert!(TapeEngine::new(1000, 1.0, 1000, 100).is_err());
+        assert!(TapeEngine::new(1000, 0.99, 10, 100).is_err());
+    }
+
+    #[test]
+    fn test_tape_speed_metrics() {
+        let engine = TapeEngine::new(2_000, 0.99, 1000, 100).unwrap();
+        
+        engine.on_trade(&trade(0, 100.0, 10.0));
+        engine.on_trade(&trade(1_000, 100.0, 30.0));
+        let m = engine.on_trade(&trade(1_500, 200.0, 20.0)).unwrap();
+        assert_eq!(m.trade_count, 3);
+        assert_eq!(m.trades_per_second, 1.5);
+        assert_eq!(m.avg_trade_size, 20.0);
+        assert_eq!(m.volume_per_second, 30.0);
+        assert_eq!(m.notional_per_second, 4_000.0);
+        
+        // El trade de ts=0 sale de la ventana (500, 2500]
+        let m = engine.on_trade(&trade(2_500, 100.0, 10.0)).unwrap();
+        assert_eq!(m.trade_count, 3);
+        assert_eq!(m.avg_trade_size, 20.0);
+        assert_eq!(m.notional_per_second, 4_000.0);
+    }
+
+    #[test]
+    fn test_tape_smoothing() {
+        let mut engine = TapeEngine::new(2_000, 0.99, 1000, 100).unwrap();
+        assert_eq!(engine.on_trade(&trade(0, 100.0, 1.0)).unwrap().smoothed_trades_per_second, None);
+        assert!(engine.set_smoothing(Some(1_000), Some(10.0)).is_err());
+        engine.set_smoothing(Some(1_000), None).unwrap();
+        assert_eq!(engine.smoothing(), (Some(1_000), None));
+        
+        // tps: 1.0 (ts=0 y 1000 en ventana), 1.0 (sale ts=0), 1.5
+        let smoothed = |m: Option<TapeMetrics>| m.unwrap().smoothed_trades_per_second.unwrap();
+        assert_eq!(smoothed(engine.on_trade(&trade(1_000, 100.0, 1.0))), 1.0);
+        assert_eq!(smoothed(engine.on_trade(&trade(2_000, 100.0, 1.0))), 1.0);
+        assert_eq!(smoothed(engine.on_trade(&trade(2_000, 100.0, 1.0))), 1.0);
+        // 1.5 vigente durante una vida media
+        assert_eq!(smoothed(engine.on_trade(&trade(3_000, 100.0, 1.0))), 1.25);
+    }
+
+    #[test]
+    fn test_tape_large_prints() {
+        let engine = TapeEngine::new(10_000, 0.9, 10, 10).unwrap();
+        
+        for i in 0..10 {
+            engine.on_trade(&trade(i, 100.0, (i + 1) as f64));
+        }
+        assert!(engine.drain_large_prints().is_empty());
+        // Percentil 90 de 1..=10 (nearest-rank) = 9
+        assert_eq!(engine.get_size_threshold("AAPL"), Some(9.0));
+        
+        engine.on_trade(&trade(10, 100.0, 9.0));
+        engine.on_trade(&trade(11, 101.0, 50.0));
+        let prints = engine.drain_large_prints();
+        assert_eq!(prints.len(), 1);
+        assert_eq!(prints[0].size, 50.0);
+        assert_eq!(prints[0].side, "BUY");
+        
+        // Lookback de 10: los tamaños 1 y 2 ya no cuentan
+        assert_eq!(engine.get_size_threshold("AAPL"), Some(10.0));
+    }
+
+    #[test]
+    fn test_tape_size_quantiles() {
+        let engine = TapeEngine::new(10_000, 0.9, 10, 10).unwrap();
+        assert_eq!(engine.get_size_quantiles("AAPL", vec![0.5]).unwrap(), None);
+        for i in 0..1_000 {
+            engine.on_trade(&trade(i, 100.0, (i + 1) as f64));
+        }
+        
+        // La sesión entera, no solo el lookback de 10
+        let quantiles = engine.get_size_quantiles("AAPL", vec![0.0, 0.5, 0.99]).unwrap().unwrap();
+        assert_eq!(quantiles[0], 1.0);
+        assert!((quantiles[1] - 500.0).abs() <= 5.0);
+        assert!((quantiles[2] - 990.0).abs() <= 9.9);
+        assert!(engine.get_size_quantiles("AAPL", vec![1.5]).is_err());
+        assert_eq!(engine.get_size_sketch("AAPL").unwrap().count(), 1_000);
+    }
+}
@@ -0,0 +1,275 @@
+//! # Volatility Engine
+//! 
+//! Media y desviación estándar rolling de cierres (momentos con ventana) por
+//! símbolo y periodo, con bandas de Bollinger, bandwidth y %B.
+//! También calcula volatilidad realizada a partir de retornos logarítmicos
+//! de trades muestreados a intervalo fijo.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::types::{Bar, Trade, VolatilityMetrics};
+use crate::utils::{calculate_bucket, count_symbols, safe_div};
+use crate::utils::moments::Moments;
+use crate::utils::rolling::{RingBuffer, RollingSum};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Media y varianza de una ventana fija (momentos con altas y bajas)
+#[derive(Clone, Debug)]
+struct RollingWelford {
+    window: RingBuffer<f64>,
+    moments: Moments,
+}
+
+impl RollingWelford {
+    fn new(period: usize) -> Self {
+        Self { window: RingBuffer::new(period), moments: Moments::default() }
+    }
+    
+    fn push(&mut self, value: f64) {
+        if let Some(old) = self.window.push(value) {
+            self.moments.remove(old);
+        }
+        self.moments.push(value);
+    }
+    
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+    
+    fn mean(&self) -> f64 {
+        self.moments.mean().unwrap_or(0.0)
+    }
+    
+    /// Desviación estándar poblacional (convención de Bollinger)
+    fn std_dev(&self) -> f64 {
+        self.moments.std_dev(0).unwrap_or(0.0)
+    }
+}
+
+/// Muestreo de precios y suma rolling de retornos al cuadrado
+#[derive(Clone, Debug)]
+struct RealizedVolState {
+    bucket_ts: u64,
+    last_price: f64,
+    last_sample: Option<f64>,
+    squared_returns: RollingSum,
+}
+
+py_class! {
+/// Engine de volatilidad por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct VolatilityEngine {
+    #[pyo3(get)]
+    pub periods: Vec<usize>,
+    #[pyo3(get)]
+    pub num_std: f64,
+    #[pyo3(get)]
+    pub sample_interval_ms: u64,
+    #[pyo3(get)]
+    pub rv_window: usize,
+    // Estado por (symbol, period)
+    bands: Arc<DashMap<(String, usize), RollingWelford>>,
+    realized: Arc<DashMap<String, RealizedVolState>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VolatilityEngine {
+    #[new]
+    #[pyo3(signature = (periods=vec![20], num_std=2.0, sample_interval_ms=1000, rv_window=60))]
+    pub fn new(periods: Vec<usize>, num_std: f64, sample_interval_ms: u64, rv_window: usize) -> Result<Self, ProcessError> {
+        if periods.is_empty() || periods.contains(&0) || sample_interval_ms == 0 || rv_window == 0 {
+            return Err(ProcessError::invalid(
+                "periods, sample_interval_ms and rv_window must be > 0"));
+        }
+        if !(num_std.is_finite() && num_std > 0.0) {
+            return Err(ProcessError::invalid("num_std must be > 0"));
+        }
+        let mut periods = periods;
+        periods.sort_unstable();
+        periods.dedup();
+        Ok(Self {
+            periods,
+            num_std,
+            sample_interval_ms,
+            rv_window,
+            bands: Arc::new(DashMap::new()),
+            realized: Arc::new(DashMap::new()),
+        })
+    }
+    
+    /// Procesa una barra; devuelve las bandas de los periodos ya completos
+    pub fn on_bar(&self, bar: &Bar) -> Vec<VolatilityMetrics> {
+        if bar.close <= 0.0 {
+            return Vec::new();
+        }
+        
+        self.periods.iter()
+            .filter_map(|&period| {
+                let mut state = self.bands.entry((bar.symbol.clone(), period))
+                    .or_insert_with(|| RollingWelford::new(period));
+                state.push(bar.close);
+                if !state.is_ready() {
+                    return None;
+                }
+                
+                let (mean, std_dev) = (state.mean(), state.std_dev());
+                let upper_band = mean + self.num_std * std_dev;
+                let lower_band = mean - self.num_std * std_dev;
+                Some(VolatilityMetrics {
+                    symbol: bar.symbol.clone(),
+                    ts: bar.ts,
+                    period,
+                    mean,
+                    std_dev,
+                    upper_band,
+                    lower_band,
+                    bandwidth: safe_div(upper_band - lower_band, mean),
+                    percent_b: if upper_band > lower_band {
+                        (bar.close - lower_band) / (upper_band - lower_band)
+                    } else {
+                        0.5
+                    },
+                })
+            })
+            .collect()
+    }
+    
+    /// Procesa un trade; devuelve la volatilidad realizada cuando se cierra una muestra
+    pub fn on_trade(&self, trade: &Trade) -> Option<f64> {
+        if trade.price <= 0.0 || trade.size <= 0.0 {
+            return None;
+        }
+        
+        let bucket_ts = calculate_bucket(trade.ts, self.sample_interval_ms);
+        let mut state = self.realized.entry(trade.symbol.clone()).or_insert_with(|| RealizedVolState {
+            bucket_ts,
+            last_price: trade.price,
+            last_sample: None,
+            squared_returns: RollingSum::new(self.rv_window),
+        });
+        
+        let mut sampled = None;
+        if bucket_ts > state.bucket_ts {
+            // El último precio del intervalo anterior es la muestra
+            let sample = state.last_price;
+            if let Some(prev) = state.last_sample {
+                state.squared_returns.push((sample / prev).ln().powi(2));
+                sampled = Some(state.squared_returns.sum().max(0.0).sqrt());
+            }
+            state.last_sample = Some(sample);
+            state.bucket_ts = bucket_ts;
+        }
+        state.last_price = trade.price;
+        
+        sampled
+    }
+    
+    /// Volatilidad realizada actual: sqrt(Σ r²) sobre los últimos rv_window retornos
+    pub fn get_realized_vol(&self, symbol: &str) -> Option<f64> {
+        self.realized.get(symbol)
+            .filter(|s| !s.squared_returns.is_empty())
+            .map(|s| s.squared_returns.sum().max(0.0).sqrt())
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.bands.retain(|key, _| key.0 != symbol);
+        self.realized.remove(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.bands.clear();
+        self.realized.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VolatilityEngine(periods={:?}, num_std={}, sample_interval_ms={})",
+                self.periods, self.num_std, self.sample_interval_ms)
+    }
+}
+}
+
+impl VolatilityEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        count_symbols(self.bands.iter().map(|e| e.key().0.clone())
+            .chain(self.realized.iter().map(|e| e.key().clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: u64, close: f64) -> Bar {
+        Bar::new(ts, close, close, close, close, 1.0, "1m".to_string(), "AAPL".to_string())
+    }
+
+    #[test]
+    fn test_volatility_validation() {
+        assert!(VolatilityEngine::new(vec![0], 2.0, 1000, 60).is_err());
+        assert!(VolatilityEngine::new(vec![20], 0.0, 1000, 60).is_err());
+        assert!(VolatilityEngine::new(vec![20], 2.0, 0, 60).is_err());
+    }
+
+    #[test]
+    fn test_bollinger_bands() {
+        let engine = VolatilityEngine::new(vec![4], 2.0, 1000, 60).unwrap();
+        
+        for (i, close) in [2.0, 4.0, 4.0].iter().enumerate() {
+            assert!(engine.on_bar(&bar(i as u64, *close)).is_empty());
+        }
+        // Ventana [2, 4, 4, 6]: media 4, σ poblacional 1.4142
+        let metrics = engine.on_bar(&bar(3, 6.0)).remove(0);
+        assert_eq!(metrics.mean, 4.0);
+        assert!((metrics.std_dev - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert!((metrics.upper_band - (4.0 + 2.0 * 2.0_f64.sqrt())).abs() < 1e-12);
+        assert!((metrics.bandwidth - 4.0 * 2.0_f64.sqrt() / 4.0).abs() < 1e-12);
+        assert!((metrics.percent_b - (6.0 - metrics.lower_band) / (metrics.upper_band - metrics.lower_band)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rolling_welford_matches_direct() {
+        let engine = VolatilityEngine::new(vec![5], 2.0, 1000, 60).unwrap();
+        let closes = [10.0, 11.5, 9.8, 12.1, 10.4, 13.3, 8.7, 11.0, 12.6, 10.9];
+        
+        let mut last = None;
+        for (i, close) in closes.iter().enumerate() {
+            last = engine.on_bar(&bar(i as u64, *close)).pop();
+        }
+        
+        let window = &closes[5..];
+        let mean = window.iter().sum::<f64>() / 5.0;
+        let var = window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 5.0;
+        let metrics = last.unwrap();
+        assert!((metrics.mean - mean).abs() < 1e-9);
+        assert!((metrics.std_dev - var.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_realized_volatility_sampling() {
+        let engine = VolatilityEngine::new(vec![20], 2.0, 1000, 2).unwrap();
+        let trade = |ts: u64, price: f64| Trade::new(ts, price, 1.0, "AAPL".to_string());
+        
+        // Muestras: 100 (t0), 110 (t1), 99 (t2), 99 (t3)
+        assert!(engine.on_trade(&trade(100, 100.0)).is_none());
+        assert!(engine.on_trade(&trade(1_100, 105.0)).is_none());
+        engine.on_trade(&trade(1_900, 110.0));
+        let rv = engine.on_trade(&trade(2_500, 99.0)).unwrap();
+        assert!((rv - (110.0_f64 / 100.0).ln().abs()).abs() < 1e-12);
+        
+        engine.on_trade(&trade(3_200, 99.0));
+        engine.on_trade(&trade(4_000, 99.0));
+        // Ventana de 2 retornos: ln(99/110) y 0
+        let expected = (99.0_f64 / 110.0).ln().abs();
+        assert!((engine.get_realized_vol("AAPL").unwrap() - expected).abs() < 1e-12);
+    }
+}
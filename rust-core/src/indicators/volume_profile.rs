@@ -0,0 +1,356 @@
+//! # Volume Profile Engine
+//! 
+//! Volumen ejecutado por nivel de precio cuantizado, por sesión o acumulado.
+//! Reporta POC (point of control), value area (VAH/VAL) y el perfil como tiles.
+//! Con `notional` activo los niveles acumulan precio × tamaño en vez del
+//! tamaño, así que POC, value area y tiles se calculan sobre el nocional.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use crate::indicators::classifier::SIDE_NA;
+use crate::types::{Tile, Trade, VolumeProfileMetrics};
+use crate::utils::{aggregate_volume_simd, price_to_tick, sorted_symbols, tick_to_price, SessionSchedule, TickSizeRegistry};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::py_macros::{py_class, py_methods};
+
+/// Perfil de un símbolo: tick -> volumen (o nocional)
+#[derive(Clone, Debug, Default)]
+struct Profile {
+    session: Option<u64>,
+    levels: BTreeMap<i64, f64>,
+    total_volume: f64,
+    total_notional: f64,
+    last_ts: u64,
+}
+
+impl Profile {
+    /// Índices (poc, low, high) sobre los niveles ordenados por precio.
+    /// La value area se expande desde el POC hacia el vecino con más volumen
+    /// hasta cubrir value_area_pct del volumen total.
+    fn value_area(volumes: &[f64], value_area_pct: f64) -> (usize, usize, usize) {
+        let mut poc = 0;
+        for (i, v) in volumes.iter().enumerate() {
+            if *v > volumes[poc] {
+                poc = i;
+            }
+        }
+        
+        let target = aggregate_volume_simd(volumes) * value_area_pct;
+        let (mut low, mut high) = (poc, poc);
+        let mut covered = volumes[poc];
+        while covered < target && (low > 0 || high + 1 < volumes.len()) {
+            let below = if low > 0 { volumes[low - 1] } else { f64::NEG_INFINITY };
+            let above = if high + 1 < volumes.len() { volumes[high + 1] } else { f64::NEG_INFINITY };
+            if above >= below {
+                high += 1;
+                covered += above;
+            } else {
+                low -= 1;
+                covered += below;
+            }
+        }
+        (poc, low, high)
+    }
+}
+
+py_class! {
+/// Engine de perfil de volumen por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct VolumeProfileEngine {
+    #[pyo3(get)]
+    pub value_area_pct: f64,
+    /// Modo nocional: los niveles acumulan precio × tamaño
+    #[pyo3(get)]
+    pub notional: bool,
+    tick_sizes: TickSizeRegistry,
+    reset_schedule: Option<SessionSchedule>,
+    profiles: Arc<DashMap<String, Profile>>,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VolumeProfileEngine {
+    #[new]
+    #[pyo3(signature = (value_area_pct=0.7, tick_size=0.01))]
+    pub fn new(value_area_pct: f64, tick_size: f64) -> Result<Self, ProcessError> {
+        if !(value_area_pct > 0.0 && value_area_pct <= 1.0) {
+            return Err(ProcessError::invalid("value_area_pct must be in (0, 1]"));
+        }
+        Ok(Self {
+            value_area_pct,
+            notional: false,
+            tick_sizes: TickSizeRegistry::new(tick_size)?,
+            reset_schedule: None,
+            profiles: Arc::new(DashMap::new()),
+            rejections: Rejections::default(),
+        })
+    }
+    
+    /// Registro de tick sizes por símbolo (compartible con HeatmapEngine)
+    #[getter]
+    pub fn tick_registry(&self) -> TickSizeRegistry {
+        self.tick_sizes.clone()
+    }
+    
+    /// Usa un registro de tick sizes compartido. Limpia los perfiles existentes.
+    #[setter]
+    pub fn set_tick_registry(&mut self, registry: TickSizeRegistry) {
+        self.tick_sizes = registry;
+        self.reset_all();
+    }
+    
+    /// Activa o desactiva el modo nocional; si cambia limpia los perfiles
+    #[setter]
+    pub fn set_notional(&mut self, notional: bool) {
+        if notional != self.notional {
+            self.reset_all();
+        }
+        self.notional = notional;
+    }
+    
+    /// Configura el perfil por sesión (por defecto diario a las 00:00 UTC)
+    #[pyo3(signature = (period_ms=86_400_000, offset_ms=0))]
+    pub fn set_reset_schedule(&mut self, period_ms: u64, offset_ms: u64) -> Result<(), ProcessError> {
+        let schedule = SessionSchedule::new(period_ms, offset_ms).ok_or_else(|| {
+            ProcessError::invalid("period_ms must be > 0 and offset_ms < period_ms")
+        })?;
+        self.reset_schedule = Some(schedule);
+        self.reset_all();
+        Ok(())
+    }
+    
+    /// Desactiva las sesiones: el perfil se acumula hasta un reset explícito
+    pub fn clear_reset_schedule(&mut self) {
+        self.reset_schedule = None;
+        self.reset_all();
+    }
+    
+    /// Calendario de sesiones activo como (period_ms, offset_ms)
+    #[getter]
+    pub fn reset_schedule(&self) -> Option<(u64, u64)> {
+        self.reset_schedule.map(|s| (s.period_ms, s.offset_ms))
+    }
+    
+    /// Procesa un trade y devuelve el perfil actualizado.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<VolumeProfileMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// Perfil actual de un símbolo
+    pub fn get_profile(&self, symbol: &str) -> Option<VolumeProfileMetrics> {
+        self.profiles.get(symbol).map(|p| self.build_metrics(symbol, &p))
+    }
+    
+    /// Símbolos con estado, ordenados
+    pub fn symbols(&self) -> Vec<String> {
+        sorted_symbols(&self.profiles)
+    }
+    
+    /// Perfiles actuales de todos los símbolos (symbol -> VolumeProfileMetrics)
+    pub fn get_all(&self) -> HashMap<String, VolumeProfileMetrics> {
+        self.symbols().into_iter()
+            .filter_map(|symbol| self.get_profile(&symbol).map(|m| (symbol, m)))
+            .collect()
+    }
+    
+    /// Resetea el perfil de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.profiles.remove(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los perfiles
+    pub fn reset_all(&self) {
+        self.profiles.clear();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VolumeProfileEngine(value_area_pct={}, symbols={})",
+                self.value_area_pct, self.profiles.len())
+    }
+}
+}
+
+impl VolumeProfileEngine {
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<VolumeProfileMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<VolumeProfileMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade y devuelve el perfil actualizado
+    fn process_trade(&self, trade: &Trade) -> Option<VolumeProfileMetrics> {
+        let session = self.reset_schedule.map(|s| s.session_id(trade.ts));
+        let tick = price_to_tick(trade.price, self.tick_sizes.get_tick_size(&trade.symbol));
+        
+        let mut profile = self.profiles.entry(trade.symbol.clone()).or_default();
+        if session > profile.session {
+            *profile = Profile { session, ..Profile::default() };
+        }
+        let notional = trade.price * trade.size;
+        *profile.levels.entry(tick).or_insert(0.0) += if self.notional { notional } else { trade.size };
+        profile.total_volume += trade.size;
+        profile.total_notional += notional;
+        profile.last_ts = profile.last_ts.max(trade.ts);
+        
+        Some(self.build_metrics(&trade.symbol, &profile))
+    }
+    
+    fn build_metrics(&self, symbol: &str, profile: &Profile) -> VolumeProfileMetrics {
+        let tick_size = self.tick_sizes.get_tick_size(symbol);
+        let prices: Vec<f64> = profile.levels.keys().map(|t| tick_to_price(*t, tick_size)).collect();
+        let volumes: Vec<f64> = profile.levels.values().copied().collect();
+        let (poc, low, high) = Profile::value_area(&volumes, self.value_area_pct);
+        
+        let tiles = prices.iter().zip(&volumes)
+            .map(|(price, volume)| Tile {
+                price_bin: *price,
+                total_size: *volume,
+                side: SIDE_NA.to_string(),
+                value: *volume,
+            })
+            .collect();
+        
+        VolumeProfileMetrics {
+            symbol: symbol.to_string(),
+            ts: profile.last_ts,
+            session_start: self.reset_schedule.map(|s| s.session_start(profile.last_ts)),
+            total_volume: profile.total_volume,
+            total_notional: self.notional.then_some(profile.total_notional),
+            poc: prices[poc],
+            value_area_high: prices[high],
+            value_area_low: prices[low],
+            tiles,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, price: f64, size: f64) -> Trade {
+        Trade::new(ts, price, size, "AAPL".to_string())
+    }
+
+    #[test]
+    fn test_volume_profile_validation() {
+        assert!(VolumeProfileEngine::new(0.0, 0.01).is_err());
+        assert!(VolumeProfileEngine::new(1.5, 0.01).is_err());
+        assert!(VolumeProfileEngine::new(0.7, 0.0).is_err());
+        
+        let engine = VolumeProfileEngine::new(0.7, 0.01).unwrap();
+        assert!(engine.on_trade(&trade(1, 0.0, 10.0)).is_none());
+    }
+
+    #[test]
+    fn test_volume_profile_poc_and_value_area() {
+        let engine = VolumeProfileEngine::new(0.7, 1.0).unwrap();
+        
+        // 100: 10, 101: 20, 102: 50, 103: 15, 104: 5 (total 100)
+        for (price, size) in [(100.0, 10.0), (101.0, 20.0), (102.0, 50.0), (103.0, 15.0), (104.0, 5.0)] {
+            engine.on_trade(&trade(1, price, size));
+        }
+        let metrics = engine.get_profile("AAPL").unwrap();
+        
+        assert_eq!(metrics.poc, 102.0);
+        assert_eq!(metrics.total_volume, 100.0);
+        // 50 -> +20 (101) = 70 ≥ 70%
+        assert_eq!(metrics.value_area_low, 101.0);
+        assert_eq!(metrics.value_area_high, 102.0);
+        assert_eq!(metrics.tiles.len(), 5);
+    }
+
+    #[test]
+    fn test_volume_profile_quantizes_prices() {
+        let engine = VolumeProfileEngine::new(0.7, 0.5).unwrap();
+        
+        engine.on_trade(&trade(1, 100.1, 1.0));
+        let metrics = engine.on_trade(&trade(2, 99.9, 2.0)).unwrap();
+        
+        assert_eq!(metrics.tiles.len(), 1);
+        assert_eq!(metrics.tiles[0].price_bin, 100.0);
+        assert_eq!(metrics.tiles[0].total_size, 3.0);
+    }
+
+    #[test]
+    fn test_volume_profile_session_reset() {
+        let mut engine = VolumeProfileEngine::new(0.7, 1.0).unwrap();
+        engine.set_reset_schedule(1_000, 0).unwrap();
+        
+        engine.on_trade(&trade(500, 100.0, 10.0));
+        let metrics = engine.on_trade(&trade(1_200, 105.0, 1.0)).unwrap();
+        
+        assert_eq!(metrics.session_start, Some(1_000));
+        assert_eq!(metrics.total_volume, 1.0);
+        assert_eq!(metrics.poc, 105.0);
+    }
+
+    #[test]
+    fn test_volume_profile_notional() {
+        let mut engine = VolumeProfileEngine::new(0.7, 1.0).unwrap();
+        engine.on_trade(&trade(1, 10.0, 5.0));
+        engine.set_notional(true);
+        assert!(engine.get_profile("AAPL").is_none());
+        
+        // Más tamaño a 10 pero más nocional a 100
+        engine.on_trade(&trade(1, 10.0, 50.0));
+        let metrics = engine.on_trade(&trade(2, 100.0, 6.0)).unwrap();
+        assert_eq!(metrics.poc, 100.0);
+        assert_eq!(metrics.total_volume, 56.0);
+        assert_eq!(metrics.total_notional, Some(1_100.0));
+        assert_eq!(metrics.tiles[1].total_size, 600.0);
+    }
+}
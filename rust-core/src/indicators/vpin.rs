@@ -0,0 +1,309 @@
+//! # VPIN Engine
+//! 
+//! Volume-synchronized probability of informed trading (Easley, López de
+//! Prado y O'Hara): los trades se agrupan en buckets de volumen fijo V
+//! (repartiendo un trade entre buckets si los desborda) y
+//! VPIN = Σ|V_buy - V_sell| / (n·V) sobre los últimos n buckets.
+//! El lado sale del mismo TradeClassifier que CVD; el volumen sin lado
+//! se reparte a partes iguales.
+//! 
+//! Cada VPIN con la ventana completa entra en la distribución de la sesión
+//! del símbolo; `vpin_zscore` sitúa el valor actual frente a los anteriores.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use crate::types::{Quote, Trade, VPINMetrics};
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::utils::moments::{Moments, StreamingStats};
+use super::classifier::{TradeClassifier, SIDE_BUY, SIDE_SELL};
+use crate::py_macros::{py_class, py_methods};
+
+/// Estado por símbolo: bucket en curso, desequilibrios de los completos y
+/// distribución de VPIN de la sesión
+#[derive(Clone, Debug, Default)]
+struct VPINState {
+    buy: f64,
+    sell: f64,
+    imbalances: VecDeque<f64>,
+    imbalance_sum: f64,
+    vpin_stats: Moments,
+    zscore: Option<f64>,
+}
+
+py_class! {
+/// Engine de VPIN por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct VPINEngine {
+    #[pyo3(get)]
+    pub bucket_volume: f64,
+    #[pyo3(get)]
+    pub window: usize,
+    state: Arc<DashMap<String, VPINState>>,
+    classifier: TradeClassifier,
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VPINEngine {
+    #[new]
+    #[pyo3(signature = (bucket_volume, window=50))]
+    pub fn new(bucket_volume: f64, window: usize) -> Result<Self, ProcessError> {
+        Self::with_classifier(bucket_volume, window, TradeClassifier::new())
+    }
+    
+    /// Actualiza la quote vigente usada para clasificar trades sin lado
+    pub fn on_quote(&self, quote: &Quote) {
+        self.classifier.on_quote(quote);
+    }
+    
+    /// Procesa un trade; devuelve métricas si completa al menos un bucket.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<VPINMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
+    }
+    
+    /// VPIN actual de un símbolo (None hasta completar la ventana)
+    pub fn get_vpin(&self, symbol: &str) -> Option<f64> {
+        self.state.get(symbol).and_then(|s| self.vpin(&s))
+    }
+    
+    /// Distribución de VPIN del símbolo en la sesión (un valor por bucket
+    /// completado con la ventana llena)
+    pub fn get_vpin_stats(&self, symbol: &str) -> Option<StreamingStats> {
+        self.state.get(symbol).map(|s| StreamingStats::from_moments(s.vpin_stats))
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.state.remove(symbol);
+        self.classifier.reset_symbol(symbol);
+        self.rejections.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.state.clear();
+        self.classifier.reset_all();
+        self.rejections.reset_all();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VPINEngine(bucket_volume={}, window={}, symbols={})",
+                self.bucket_volume, self.window, self.state.len())
+    }
+}
+}
+
+impl VPINEngine {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.state.len()
+    }
+    
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<VPINMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<VPINMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade; devuelve métricas si completa al menos un bucket
+    fn process_trade(&self, trade: &Trade) -> Option<VPINMetrics> {
+        let side = self.classifier.classify(trade);
+        let mut state = self.state.entry(trade.symbol.clone()).or_default();
+        let mut remaining = trade.size;
+        let mut completed = false;
+        
+        while remaining > 0.0 {
+            let room = self.bucket_volume - (state.buy + state.sell);
+            let fill = remaining.min(room);
+            match side {
+                SIDE_BUY => state.buy += fill,
+                SIDE_SELL => state.sell += fill,
+                _ => {
+                    state.buy += fill / 2.0;
+                    state.sell += fill / 2.0;
+                }
+            }
+            remaining -= fill;
+            
+            if fill >= room {
+                let imbalance = (state.buy - state.sell).abs();
+                state.imbalances.push_back(imbalance);
+                state.imbalance_sum += imbalance;
+                if state.imbalances.len() > self.window {
+                    let old = state.imbalances.pop_front().unwrap_or(0.0);
+                    state.imbalance_sum -= old;
+                }
+                state.buy = 0.0;
+                state.sell = 0.0;
+                completed = true;
+                
+                if let Some(vpin) = self.vpin(&state) {
+                    // z frente a los VPIN anteriores, sin incluir el actual
+                    let stats = &state.vpin_stats;
+                    let zscore = stats.mean().zip(stats.std_dev(1))
+                        .filter(|&(_, std_dev)| std_dev > 0.0)
+                        .map(|(mean, std_dev)| (vpin - mean) / std_dev);
+                    state.zscore = zscore;
+                    state.vpin_stats.push(vpin);
+                }
+            }
+        }
+        
+        completed.then(|| self.build_metrics(&trade.symbol, trade.ts, &state))
+    }
+    
+    /// Crea el engine con un clasificador compartido (p. ej. el de un CVDEngine)
+    pub fn with_classifier(bucket_volume: f64, window: usize, classifier: TradeClassifier) -> Result<Self, ProcessError> {
+        if !(bucket_volume.is_finite() && bucket_volume > 0.0) || window == 0 {
+            return Err(ProcessError::invalid("bucket_volume and window must be > 0"));
+        }
+        Ok(Self {
+            bucket_volume,
+            window,
+            state: Arc::new(DashMap::new()),
+            classifier,
+            rejections: Rejections::default(),
+        })
+    }
+    
+    fn vpin(&self, state: &VPINState) -> Option<f64> {
+        (state.imbalances.len() == self.window)
+            .then(|| state.imbalance_sum.max(0.0) / (self.window as f64 * self.bucket_volume))
+    }
+    
+    fn build_metrics(&self, symbol: &str, ts: u64, state: &VPINState) -> VPINMetrics {
+        VPINMetrics {
+            symbol: symbol.to_string(),
+            ts,
+            vpin: self.vpin(state),
+            buckets: state.imbalances.len(),
+            bucket_imbalance: state.imbalances.back().copied().unwrap_or(0.0) / self.bucket_volume,
+            vpin_zscore: state.zscore,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: u64, size: f64, side: Option<&str>) -> Trade {
+        Trade {
+            ts,
+            price: 100.0,
+            size,
+            symbol: "AAPL".to_string(),
+            side: side.map(|s| s.to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_vpin_validation() {
+        assert!(VPINEngine::new(0.0, 50).is_err());
+        assert!(VPINEngine::new(100.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_vpin_buckets_and_split() {
+        let engine = VPINEngine::new(10.0, 2).unwrap();
+        
+        assert!(engine.on_trade(&trade(1, 6.0, Some("BUY"))).is_none());
+        // 4 completan el primer bucket (todo compra) y 8 pasan al segundo
+        let m = engine.on_trade(&trade(2, 12.0, Some("SELL"))).unwrap();
+        assert_eq!(m.buckets, 1);
+        assert_eq!(m.vpin, None);
+        // Primer bucket: 6 compras y 4 ventas
+        assert_eq!(m.bucket_imbalance, 0.2);
+        
+        // Segundo bucket: 8 ventas + 2 compras -> |2 - 8| = 6
+        let m = engine.on_trade(&trade(3, 2.0, Some("BUY"))).unwrap();
+        assert_eq!(m.buckets, 2);
+        assert!((m.vpin.unwrap() - (2.0 + 6.0) / 20.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_vpin_rolling_window_and_na_volume() {
+        let engine = VPINEngine::new(10.0, 2).unwrap();
+        
+        engine.on_trade(&trade(1, 10.0, Some("BUY")));
+        engine.on_trade(&trade(2, 10.0, Some("BUY")));
+        assert_eq!(engine.get_vpin("AAPL"), Some(1.0));
+        
+        // Volumen no clasificable (NA): se reparte y no aporta desequilibrio
+        engine.on_trade(&trade(3, 10.0, None));
+        assert_eq!(engine.get_vpin("AAPL"), Some(0.5));
+    }
+
+    #[test]
+    fn test_vpin_session_stats_and_zscore() {
+        let engine = VPINEngine::new(10.0, 1).unwrap();
+        
+        // VPIN por bucket: 1, 0, 1, 0, 1
+        let sides = [Some("BUY"), None, Some("SELL"), None, Some("BUY")];
+        let mut last = None;
+        for (i, side) in sides.into_iter().enumerate() {
+            last = engine.on_trade(&trade(i as u64, 10.0, side));
+        }
+        // Previos [1, 0, 1, 0]: media 0.5, σ muestral sqrt(1/3)
+        let z = last.unwrap().vpin_zscore.unwrap();
+        assert!((z - 0.5 / (1.0_f64 / 3.0).sqrt()).abs() < 1e-12);
+        
+        let stats = engine.get_vpin_stats("AAPL").unwrap();
+        assert_eq!((stats.count(), stats.mean()), (5, Some(0.6)));
+        engine.reset_symbol("AAPL");
+        assert!(engine.get_vpin_stats("AAPL").is_none());
+    }
+}
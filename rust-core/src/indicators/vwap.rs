@@ -1,68 +1,306 @@
 //! # VWAP Engine
 //! 
 //! Volume Weighted Average Price calculator with session management.
+//! Soporta VWAP anclado (anchored VWAP) desde timestamps arbitrarios
+//! y bandas de desviación estándar ponderadas por volumen.
+//! 
+//! Con `set_fixed_point` pv_sum y v_sum se acumulan en punto fijo (ver
+//! `crate::fixed`); `get_sums_decimal` los devuelve exactos. Con
+//! `set_tick_arithmetic` se acumulan en ticks y lotes enteros
+//! (`VwapCore<i64>` de `crate::numeric`, el mismo núcleo que en f64).
+//! La varianza de las bandas sigue en f64, con media y M2 ponderados
+//! incrementales (West) en vez de E[p²] - E[p]², que a precios altos
+//! cancela todos los dígitos.
+//! En f64 las sumas son compensadas (Neumaier), así que un día de
+//! micro-lotes no acumula error de redondeo apreciable.
+//! 
+//! Con `session_calendar` el VWAP de sesión vuelve a cero en cada apertura
+//! del calendario de mercado; las anclas no se ven afectadas.
 
+#[cfg(feature = "python")]
+use numpy::PyReadonlyArray1;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::sync::Arc;
+#[cfg(feature = "python")]
+use crate::arrow_ffi;
+use crate::calendar::SessionCalendar;
+#[cfg(feature = "python")]
+use crate::numpy_batch;
 use crate::types::{Trade, Bar, VWAPMetrics};
-use crate::utils::safe_div;
+use crate::utils::{safe_div, simd};
+use crate::utils::moments::WeightedVariance;
+use crate::errors::{validate_trade, ProcessError, Rejections};
+use crate::fixed::{Fixed, FixedPointConfig, Precision, SymbolConfig};
+use crate::numeric::{Units, VwapCore};
+use crate::py_macros::{py_class, py_methods};
+
+/// Clave de estado: (symbol, session_id)
+type SessionKey = (String, Option<String>);
+
+/// Multiplicadores por defecto de las bandas (1σ, 2σ, 3σ)
+pub const DEFAULT_BAND_MULTIPLIERS: (f64, f64, f64) = (1.0, 2.0, 3.0);
+
+/// Sumas y varianza de un acumulador: f64 compensado o ticks y lotes
+/// enteros (ver `crate::numeric`)
+#[derive(Clone, Debug)]
+enum Sums {
+    Float(VwapCore<f64>),
+    Ticks(VwapCore<i64>),
+}
+
+impl Default for Sums {
+    fn default() -> Self {
+        Sums::Float(VwapCore::default())
+    }
+}
+
+impl Sums {
+    fn update(&mut self, price: f64, size: f64) {
+        match self {
+            Sums::Float(core) => core.update(price, size),
+            Sums::Ticks(core) => core.update(price, size),
+        }
+    }
+    
+    fn remove(&mut self, price: f64, size: f64) {
+        match self {
+            Sums::Float(core) => core.remove(price, size),
+            Sums::Ticks(core) => core.remove(price, size),
+        }
+    }
+    
+    fn totals(&self) -> (f64, f64) {
+        match self {
+            Sums::Float(core) => (core.pv_sum(), core.v_sum()),
+            Sums::Ticks(core) => (core.pv_sum(), core.v_sum()),
+        }
+    }
+    
+    fn std_dev(&self) -> f64 {
+        match self {
+            Sums::Float(core) => core.std_dev(),
+            Sums::Ticks(core) => core.std_dev(),
+        }
+    }
+}
 
+/// Acumulador incremental de VWAP y varianza ponderada por volumen
+#[derive(Clone, Debug, Default)]
+pub struct VWAPAccumulator {
+    pub pv_sum: f64,
+    pub v_sum: f64,
+    pub last_ts: u64,  // último evento acumulado
+    // pv_sum y v_sum exactos en modo punto fijo
+    pub fixed_pv: Fixed,
+    pub fixed_v: Fixed,
+    // Sumas de las que se leen pv_sum y v_sum fuera del modo punto fijo
+    sums: Sums,
+}
+
+impl VWAPAccumulator {
+    /// Acumulador en f64 (`units` None) o en ticks de precio y lotes de
+    /// tamaño enteros, con sumas exactas
+    pub fn new(units: Option<Units>) -> Self {
+        let sums = units.map_or_else(Sums::default, |units| Sums::Ticks(VwapCore::new(units)));
+        Self { sums, ..Self::default() }
+    }
+    
+    pub fn update(&mut self, price: f64, size: f64) {
+        self.sums.update(price, size);
+        (self.pv_sum, self.v_sum) = self.sums.totals();
+    }
+    
+    /// Como `update` con precio y tamaño ya redondeados: pv_sum y v_sum se
+    /// derivan de los acumulados exactos
+    pub fn update_fixed(&mut self, price: Fixed, size: Fixed) {
+        self.fixed_pv = self.fixed_pv + price * size;
+        self.fixed_v = self.fixed_v + size;
+        self.sums.update(price.to_f64(), size.to_f64());
+        self.pv_sum = self.fixed_pv.to_f64();
+        self.v_sum = self.fixed_v.to_f64();
+    }
+    
+    /// Acumula en punto fijo si hay precisión, si no en f64
+    pub fn update_with(&mut self, price: f64, size: f64, precision: Option<Precision>) {
+        match precision {
+            Some(precision) => self.update_fixed(precision.price(price), precision.size(size)),
+            None => self.update(price, size),
+        }
+    }
+    
+    /// Retira una contribución previa (ventanas deslizantes)
+    pub fn remove(&mut self, price: f64, size: f64) {
+        self.sums.remove(price, size);
+        (self.pv_sum, self.v_sum) = self.sums.totals();
+    }
+    
+    pub fn vwap(&self) -> f64 {
+        safe_div(self.pv_sum, self.v_sum)
+    }
+    
+    /// Desviación estándar ponderada por volumen: sqrt(Σ v·(p - vwap)² / Σv)
+    pub fn std_dev(&self) -> f64 {
+        self.sums.std_dev()
+    }
+    
+    pub fn to_metrics(&self, anchor_ts: Option<u64>, multipliers: (f64, f64, f64)) -> VWAPMetrics {
+        let mut metrics = VWAPMetrics::new(self.vwap(), self.pv_sum, self.v_sum, None, anchor_ts)
+            .with_bands(self.std_dev(), multipliers);
+        metrics.last_ts = self.last_ts;
+        metrics
+    }
+}
+
+/// Acumulador de VWAP anclado a un timestamp (swing high/low, noticia, etc.)
+#[derive(Clone, Debug)]
+pub struct AnchoredVWAP {
+    pub anchor_ts: u64,
+    pub acc: VWAPAccumulator,
+}
+
+impl AnchoredVWAP {
+    pub fn new(anchor_ts: u64) -> Self {
+        Self { anchor_ts, acc: VWAPAccumulator::default() }
+    }
+    
+    /// Acumula precio/volumen solo si el evento es posterior al ancla
+    pub fn update(&mut self, ts: u64, price: f64, size: f64, precision: Option<Precision>) -> bool {
+        if ts < self.anchor_ts {
+            return false;
+        }
+        self.acc.update_with(price, size, precision);
+        self.acc.last_ts = self.acc.last_ts.max(ts);
+        true
+    }
+    
+    /// VWAP actual desde el ancla
+    pub fn vwap(&self) -> f64 {
+        self.acc.vwap()
+    }
+    
+    /// Convierte el acumulador en métricas (anchor_ts informado)
+    pub fn to_metrics(&self, multipliers: (f64, f64, f64)) -> VWAPMetrics {
+        self.acc.to_metrics(Some(self.anchor_ts), multipliers)
+    }
+}
+
+py_class! {
 /// Engine para calcular VWAP por símbolo
 #[pyclass]
+#[derive(Clone)]
 pub struct VWAPEngine {
-    // Estado por símbolo: (symbol, session_id) -> (pv_sum, v_sum)
-    state: Arc<DashMap<(String, Option<String>), (f64, f64)>>,
+    // Estado por símbolo: (symbol, session_id) -> acumulador
+    state: Arc<DashMap<SessionKey, VWAPAccumulator>>,
+    // Anclas por símbolo (ordenadas por anchor_ts)
+    anchors: Arc<DashMap<String, Vec<AnchoredVWAP>>>,
+    /// Multiplicadores k de las bandas vwap ± k·σ
+    #[pyo3(get)]
+    pub band_multipliers: (f64, f64, f64),
+    // Modo strict y rechazos por símbolo
+    rejections: Rejections,
+    // Precisión de punto fijo (None = f64)
+    fixed_point: FixedPointConfig,
+    // Tick de precio y lote de tamaño de la aritmética entera (None = f64)
+    tick_units: SymbolConfig<Units>,
+    // Calendario de mercado para el rollover de sesión (None = sin reset)
+    session_calendar: Option<SessionCalendar>,
+    // Apertura de la sesión en curso por símbolo
+    session_by_symbol: Arc<DashMap<String, u64>>,
+}
 }
 
+py_methods! {
 #[pymethods]
 impl VWAPEngine {
     #[new]
     pub fn new() -> Self {
         Self {
             state: Arc::new(DashMap::new()),
+            anchors: Arc::new(DashMap::new()),
+            band_multipliers: DEFAULT_BAND_MULTIPLIERS,
+            rejections: Rejections::default(),
+            fixed_point: FixedPointConfig::default(),
+            tick_units: SymbolConfig::default(),
+            session_calendar: None,
+            session_by_symbol: Arc::new(DashMap::new()),
         }
     }
     
-    /// Procesa un trade y actualiza VWAP
-    pub fn on_trade(&self, trade: &Trade) -> Option<VWAPMetrics> {
-        // Validar datos
-        if trade.price <= 0.0 || trade.size <= 0.0 {
-            return None;
+    /// Configura los multiplicadores de las bandas (deben ser finitos y >= 0)
+    #[setter]
+    pub fn set_band_multipliers(&mut self, multipliers: (f64, f64, f64)) -> Result<(), ProcessError> {
+        let (k1, k2, k3) = multipliers;
+        if [k1, k2, k3].iter().any(|k| !k.is_finite() || *k < 0.0) {
+            return Err(ProcessError::invalid(
+                "band multipliers must be finite and >= 0"));
         }
-        
-        let key = (trade.symbol.clone(), None);
-        
-        // Actualizar estado usando entry API
-        let entry = self.state.entry(key);
-        let (pv_sum, v_sum) = match entry {
-            dashmap::mapref::entry::Entry::Occupied(mut e) => {
-                let (pv, v) = *e.get();
-                let new_pv = pv + (trade.price * trade.size);
-                let new_v = v + trade.size;
-                e.insert((new_pv, new_v));
-                (new_pv, new_v)
-            }
-            dashmap::mapref::entry::Entry::Vacant(e) => {
-                let pv = trade.price * trade.size;
-                let v = trade.size;
-                e.insert((pv, v));
-                (pv, v)
-            }
-        };
-        
-        let vwap = safe_div(pv_sum, v_sum);
-        
-        Some(VWAPMetrics {
-            vwap,
-            pv_sum,
-            v_sum,
-            session_id: None,
-        })
+        self.band_multipliers = multipliers;
+        Ok(())
+    }
+    
+    /// Resetea el VWAP de sesión en cada apertura del calendario de mercado
+    /// (None lo desactiva)
+    #[setter]
+    pub fn set_session_calendar(&mut self, calendar: Option<SessionCalendar>) {
+        self.session_calendar = calendar;
+        self.session_by_symbol.clear();
+    }
+    
+    /// Calendario de mercado activo
+    #[getter]
+    pub fn session_calendar(&self) -> Option<SessionCalendar> {
+        self.session_calendar.clone()
+    }
+    
+    /// Procesa un trade y actualiza VWAP.
+    /// En modo `strict` un trade inválido lanza su excepción; si no, devuelve None
+    #[pyo3(name = "on_trade")]
+    pub fn py_on_trade(&self, trade: &Trade) -> Result<Option<VWAPMetrics>, ProcessError> {
+        self.rejections.resolve(self.try_on_trade(trade))
+    }
+    
+    /// Modo strict: los eventos inválidos lanzan excepción en lugar de descartarse
+    #[getter]
+    pub fn strict(&self) -> bool {
+        self.rejections.strict
+    }
+    
+    #[setter]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.rejections.strict = strict;
+    }
+    
+    /// Eventos rechazados por tipo de error, de un símbolo o de todos (None)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_rejections(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.rejections.counts(symbol)
+    }
+    
+    /// Política para eventos cuyo ts retrocede más de `tolerance_ms` respecto
+    /// al máximo visto del símbolo: "accept" (por defecto), "warn" o "reject"
+    #[pyo3(signature = (policy, tolerance_ms=0))]
+    pub fn set_timestamp_policy(&mut self, policy: &str, tolerance_ms: u64) -> Result<(), ProcessError> {
+        self.rejections.set_timestamp_policy(policy, tolerance_ms)
+    }
+    
+    /// Política de timestamps activa como (policy, tolerance_ms)
+    #[getter]
+    pub fn timestamp_policy(&self) -> (String, u64) {
+        (self.rejections.ts_policy.as_str().to_string(), self.rejections.tolerance_ms)
+    }
+    
+    /// Máximo ts aceptado de un símbolo
+    pub fn get_max_ts(&self, symbol: &str) -> Option<u64> {
+        self.rejections.max_ts(symbol)
     }
     
     /// Procesa una barra y actualiza VWAP usando typical price
-    fn on_bar(&self, bar: &Bar) -> Option<VWAPMetrics> {
+    pub fn on_bar(&self, bar: &Bar) -> Option<VWAPMetrics> {
         // Validar datos
         if bar.volume <= 0.0 {
             return None;
@@ -71,86 +309,307 @@ impl VWAPEngine {
         // Typical price = (high + low + close) / 3
         let tp = (bar.high + bar.low + bar.close) / 3.0;
         
-        let key = (bar.symbol.clone(), None);
+        self.roll_session(&bar.symbol, bar.ts);
+        let metrics = self.accumulate(&bar.symbol, bar.ts, tp, bar.volume);
+        self.update_anchors(&bar.symbol, bar.ts, tp, bar.volume);
         
-        // Actualizar estado usando entry API
-        let entry = self.state.entry(key);
-        let (pv_sum, v_sum) = match entry {
-            dashmap::mapref::entry::Entry::Occupied(mut e) => {
-                let (pv, v) = *e.get();
-                let new_pv = pv + (tp * bar.volume);
-                let new_v = v + bar.volume;
-                e.insert((new_pv, new_v));
-                (new_pv, new_v)
-            }
-            dashmap::mapref::entry::Entry::Vacant(e) => {
-                let pv = tp * bar.volume;
-                let v = bar.volume;
-                e.insert((pv, v));
-                (pv, v)
-            }
-        };
-        
-        let vwap = safe_div(pv_sum, v_sum);
-        
-        Some(VWAPMetrics {
-            vwap,
-            pv_sum,
-            v_sum,
-            session_id: None,
-        })
+        Some(metrics)
     }
     
     /// Obtiene el VWAP actual para un símbolo
     pub fn get_vwap(&self, symbol: &str) -> Option<f64> {
         let key = (symbol.to_string(), None);
-        self.state.get(&key).map(|entry| {
-            let (pv_sum, v_sum) = *entry.value();
-            safe_div(pv_sum, v_sum)
+        self.state.get(&key).map(|entry| entry.value().vwap())
+    }
+    
+    /// Métricas completas del VWAP de sesión del símbolo (sumas, bandas,
+    /// sesión y último update), las mismas que devolvió el último on_trade
+    pub fn get_metrics(&self, symbol: &str) -> Option<VWAPMetrics> {
+        let key = (symbol.to_string(), None);
+        self.state.get(&key).map(|entry| self.session_metrics(symbol, &entry))
+    }
+    
+    /// Símbolos con VWAP de sesión o anclas, ordenados
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.state.iter()
+            .map(|e| e.key().0.clone())
+            .chain(self.anchors.iter().map(|e| e.key().clone()))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+    
+    /// Métricas de sesión de todos los símbolos que la tienen (symbol -> VWAPMetrics)
+    pub fn get_all(&self) -> HashMap<String, VWAPMetrics> {
+        self.symbols().into_iter()
+            .filter_map(|symbol| self.get_metrics(&symbol).map(|m| (symbol, m)))
+            .collect()
+    }
+    
+    /// Acumula pv_sum y v_sum en punto fijo redondeando precios a
+    /// `price_decimals` y tamaños a `size_decimals` (global o solo para
+    /// `symbol`). Resetea el estado afectado; las anclas vuelven a acumular
+    /// desde cero.
+    #[pyo3(signature = (price_decimals, size_decimals, symbol=None))]
+    pub fn set_fixed_point(&mut self, price_decimals: u32, size_decimals: u32, symbol: Option<&str>) -> Result<(), ProcessError> {
+        self.fixed_point.set(Some(Precision::new(price_decimals, size_decimals)?), symbol);
+        self.tick_units.set(None, symbol);
+        self.reset_fixed_state(symbol);
+        Ok(())
+    }
+    
+    /// Acumula pv_sum y v_sum en enteros exactos: precios en ticks de
+    /// `price_tick` y tamaños en lotes de `size_lot` (global o solo para
+    /// `symbol`). Pensado para backtesting con datos en múltiplos del tick;
+    /// sustituye al punto fijo y resetea el estado afectado como `set_fixed_point`.
+    #[pyo3(signature = (price_tick, size_lot, symbol=None))]
+    pub fn set_tick_arithmetic(&mut self, price_tick: f64, size_lot: f64, symbol: Option<&str>) -> Result<(), ProcessError> {
+        let units = Units::new(price_tick, size_lot)
+            .ok_or_else(|| ProcessError::invalid("price_tick and size_lot must be finite and > 0"))?;
+        self.tick_units.set(Some(units), symbol);
+        self.fixed_point.set(None, symbol);
+        self.reset_fixed_state(symbol);
+        Ok(())
+    }
+    
+    /// (price_tick, size_lot) del símbolo; None si no acumula en ticks
+    pub fn get_tick_arithmetic(&self, symbol: &str) -> Option<(f64, f64)> {
+        self.tick_units.get(symbol).map(|units| (units.price, units.size))
+    }
+    
+    /// Vuelve a acumular en f64, sin punto fijo ni ticks (global o solo para
+    /// `symbol`); resetea el estado afectado
+    #[pyo3(signature = (symbol=None))]
+    pub fn disable_fixed_point(&mut self, symbol: Option<&str>) {
+        self.fixed_point.set(None, symbol);
+        self.tick_units.set(None, symbol);
+        self.reset_fixed_state(symbol);
+    }
+    
+    /// Precisión (price_decimals, size_decimals) del símbolo; None si acumula en f64
+    pub fn get_fixed_point(&self, symbol: &str) -> Option<(u32, u32)> {
+        self.fixed_point.precision(symbol).map(|p| p.as_tuple())
+    }
+    
+    /// (pv_sum, v_sum) exactos como `decimal.Decimal`; None si no hay estado
+    /// o el símbolo acumula en f64
+    #[cfg(feature = "python")]
+    pub fn get_sums_decimal(&self, py: Python<'_>, symbol: &str) -> PyResult<Option<(PyObject, PyObject)>> {
+        if self.fixed_point.precision(symbol).is_none() {
+            return Ok(None);
+        }
+        let key = (symbol.to_string(), None);
+        let Some((pv, v)) = self.state.get(&key).map(|acc| (acc.fixed_pv, acc.fixed_v)) else { return Ok(None) };
+        Ok(Some((pv.to_py_decimal(py)?, v.to_py_decimal(py)?)))
+    }
+    
+    /// Registra un ancla para el símbolo; los trades con ts >= anchor_ts acumulan
+    pub fn anchor(&self, symbol: &str, anchor_ts: u64) {
+        let mut anchors = self.anchors.entry(symbol.to_string()).or_default();
+        if anchors.iter().any(|a| a.anchor_ts == anchor_ts) {
+            return;
+        }
+        anchors.push(AnchoredVWAP { anchor_ts, acc: self.new_accumulator(symbol) });
+        anchors.sort_by_key(|a| a.anchor_ts);
+    }
+    
+    /// Elimina un ancla; devuelve true si existía
+    pub fn remove_anchor(&self, symbol: &str, anchor_ts: u64) -> bool {
+        match self.anchors.get_mut(symbol) {
+            Some(mut anchors) => {
+                let before = anchors.len();
+                anchors.retain(|a| a.anchor_ts != anchor_ts);
+                anchors.len() != before
+            }
+            None => false,
+        }
+    }
+    
+    /// Obtiene el VWAP anclado para un símbolo y ancla concretos
+    pub fn get_anchored_vwap(&self, symbol: &str, anchor_ts: u64) -> Option<f64> {
+        self.anchors.get(symbol).and_then(|anchors| {
+            anchors.iter()
+                .find(|a| a.anchor_ts == anchor_ts)
+                .map(|a| a.vwap())
         })
     }
     
-    /// Resetea el VWAP para un símbolo
+    /// Obtiene las métricas de todas las anclas activas del símbolo
+    pub fn get_anchored_vwaps(&self, symbol: &str) -> Vec<VWAPMetrics> {
+        self.anchors.get(symbol)
+            .map(|anchors| anchors.iter().map(|a| a.to_metrics(self.band_multipliers)).collect())
+            .unwrap_or_default()
+    }
+    
+    /// Resetea el VWAP para un símbolo (incluye sus anclas)
     pub fn reset_symbol(&self, symbol: &str) {
         let key = (symbol.to_string(), None);
         self.state.remove(&key);
+        self.anchors.remove(symbol);
+        self.session_by_symbol.remove(symbol);
+        self.rejections.reset_symbol(symbol);
     }
     
     /// Resetea todos los símbolos
     pub fn reset_all(&self) {
         self.state.clear();
+        self.anchors.clear();
+        self.session_by_symbol.clear();
+        self.rejections.reset_all();
+    }
+    
+    /// Calcula VWAP en batch; libera el GIL mientras tanto
+    #[cfg(feature = "python")]
+    #[pyo3(name = "on_trade_batch")]
+    pub fn py_on_trade_batch(&self, py: Python<'_>, trades: Vec<Trade>) -> Vec<VWAPMetrics> {
+        py.allow_threads(|| self.on_trade_batch(trades))
+    }
+    
+    /// Procesa arrays numpy de un símbolo; devuelve dict de arrays (vwap, v_sum,
+    /// std_dev, bandas) con una fila por trade
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (symbol, ts, price, size, side=None))]
+    pub fn on_trade_numpy<'py>(&self, py: Python<'py>, symbol: &str, ts: PyReadonlyArray1<'py, i64>,
+                               price: PyReadonlyArray1<'py, f64>, size: PyReadonlyArray1<'py, f64>,
+                               side: Option<PyReadonlyArray1<'py, i8>>) -> PyResult<Bound<'py, PyDict>> {
+        let trades = numpy_batch::trades_from_numpy(symbol, ts, price, size, side)?;
+        let results: Vec<_> = py.allow_threads(|| trades.iter().map(|t| self.on_trade(t)).collect());
+        numpy_batch::to_dict(py, numpy_batch::vwap_columns(&results))
+    }
+    
+    /// Procesa un `pyarrow.RecordBatch` de trades en Rust y devuelve otro
+    /// RecordBatch con una fila por trade (nulos en trades descartados)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (batch, columns=None, symbol=None))]
+    pub fn on_trade_arrow(&self, py: Python<'_>, batch: &Bound<'_, PyAny>,
+                          columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> PyResult<PyObject> {
+        let trades = arrow_ffi::trades_from_arrow(batch, columns, symbol)?;
+        let results: Vec<_> = py.allow_threads(|| trades.iter().map(|t| self.on_trade(t)).collect());
+        arrow_ffi::export_record_batch(py, &arrow_ffi::vwap_frame(&trades, &results)?)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VWAPEngine(symbols={})", self.state.len())
+    }
+}
+}
+
+impl Default for VWAPEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VWAPEngine {
+    /// Procesa un trade; si no es válido se descarta y queda contado en las rejections
+    pub fn on_trade(&self, trade: &Trade) -> Option<VWAPMetrics> {
+        self.try_on_trade(trade).unwrap_or_default()
+    }
+    
+    /// Valida y procesa un trade; Err si no es válido (también queda contado)
+    pub fn try_on_trade(&self, trade: &Trade) -> Result<Option<VWAPMetrics>, ProcessError> {
+        self.rejections.check(validate_trade(trade))?;
+        self.rejections.check_timestamp(&trade.symbol, trade.ts)?;
+        Ok(self.process_trade(trade))
+    }
+    
+    /// Procesa un trade y actualiza VWAP
+    fn process_trade(&self, trade: &Trade) -> Option<VWAPMetrics> {
+        self.roll_session(&trade.symbol, trade.ts);
+        let metrics = self.accumulate(&trade.symbol, trade.ts, trade.price, trade.size);
+        self.update_anchors(&trade.symbol, trade.ts, trade.price, trade.size);
+        
+        Some(metrics)
     }
     
-    /// Calcula VWAP en batch usando Polars (mucho más rápido)
+    /// VWAP acumulado de un batch de trades (ver `trade_batch`)
     pub fn on_trade_batch(&self, trades: Vec<Trade>) -> Vec<VWAPMetrics> {
-        if trades.is_empty() {
-            return Vec::new();
-        }
+        self.trade_batch(&trades)
+    }
+    
+    /// Núcleo de `on_trade_batch` (acumulado del batch, sin tocar el estado).
+    /// precio × tamaño y las sumas acumuladas de PV y V salen de los núcleos
+    /// SIMD de `utils::simd` (escalares si la CPU no tiene AVX); la varianza
+    /// de las bandas sigue siendo la actualización secuencial ponderada.
+    pub fn trade_batch(&self, trades: &[Trade]) -> Vec<VWAPMetrics> {
+        let prices: Vec<f64> = trades.iter().map(|t| t.price).collect();
+        let sizes: Vec<f64> = trades.iter().map(|t| t.size).collect();
+        let pv_sums = simd::prefix_sums(&simd::products(&prices, &sizes));
+        let v_sums = simd::prefix_sums(&sizes);
         
-        // Calcular PV y V acumulado (implementación manual por ahora)
-        // TODO: Usar cumsum cuando esté disponible en la versión de Polars
-        let mut pv_cumsum = 0.0;
-        let mut v_cumsum = 0.0;
-        let mut results = Vec::new();
-        
-        for trade in trades {
-            pv_cumsum += trade.price * trade.size;
-            v_cumsum += trade.size;
-            let vwap = safe_div(pv_cumsum, v_cumsum);
-            
-            results.push(VWAPMetrics {
-                vwap,
-                pv_sum: pv_cumsum,
-                v_sum: v_cumsum,
-                session_id: None,
-            });
-        }
+        let mut variance = WeightedVariance::default();
+        let mut last_ts = 0;
+        trades.iter().zip(pv_sums.into_iter().zip(v_sums)).map(|(trade, (pv_sum, v_sum))| {
+            variance.push(trade.price, trade.size);
+            last_ts = last_ts.max(trade.ts);
+            let mut metrics = VWAPMetrics::new(safe_div(pv_sum, v_sum), pv_sum, v_sum, None, None)
+                .with_bands(variance.variance().sqrt(), self.band_multipliers);
+            metrics.last_ts = last_ts;
+            metrics
+        }).collect()
+    }
+    
+    /// Acumula precio/volumen en el estado de sesión y devuelve las métricas
+    fn accumulate(&self, symbol: &str, ts: u64, price: f64, size: f64) -> VWAPMetrics {
+        let key = (symbol.to_string(), None);
         
-        return results;
+        // Actualizar estado usando entry API
+        let mut entry = self.state.entry(key).or_insert_with(|| self.new_accumulator(symbol));
+        entry.update_with(price, size, self.fixed_point.precision(symbol));
+        entry.last_ts = entry.last_ts.max(ts);
+        self.session_metrics(symbol, &entry)
     }
     
-    fn __repr__(&self) -> String {
-        format!("VWAPEngine(symbols={})", self.state.len())
+    /// Acumulador vacío con la aritmética del símbolo (f64 o ticks)
+    fn new_accumulator(&self, symbol: &str) -> VWAPAccumulator {
+        VWAPAccumulator::new(self.tick_units.get(symbol))
+    }
+    
+    /// Métricas del VWAP de sesión; con `session_calendar` session_id es la
+    /// apertura de la sesión en curso (ms)
+    fn session_metrics(&self, symbol: &str, acc: &VWAPAccumulator) -> VWAPMetrics {
+        let mut metrics = acc.to_metrics(None, self.band_multipliers);
+        metrics.session_id = self.session_by_symbol.get(symbol).map(|open| open.to_string());
+        metrics
+    }
+    
+    /// Pone a cero el VWAP de sesión del símbolo cuando el evento pertenece a
+    /// una sesión posterior del calendario
+    fn roll_session(&self, symbol: &str, ts: u64) {
+        let Some(calendar) = &self.session_calendar else { return };
+        let Some(session) = calendar.session_start(ts) else { return };
+        let mut current = self.session_by_symbol.entry(symbol.to_string()).or_insert(session);
+        if session > *current {
+            *current = session;
+            self.state.remove(&(symbol.to_string(), None));
+        }
+    }
+    
+    /// Propaga el evento a todas las anclas del símbolo
+    fn update_anchors(&self, symbol: &str, ts: u64, price: f64, size: f64) {
+        if let Some(mut anchors) = self.anchors.get_mut(symbol) {
+            let precision = self.fixed_point.precision(symbol);
+            for anchor in anchors.iter_mut() {
+                anchor.update(ts, price, size, precision);
+            }
+        }
+    }
+    
+    /// Limpia los acumuladores que cambian de representación; las anclas se
+    /// conservan y vuelven a acumular desde cero
+    fn reset_fixed_state(&self, symbol: Option<&str>) {
+        match symbol {
+            Some(symbol) => { self.state.remove(&(symbol.to_string(), None)); }
+            None => self.state.clear(),
+        }
+        for mut entry in self.anchors.iter_mut() {
+            if symbol.is_none_or(|s| entry.key() == s) {
+                let acc = self.new_accumulator(entry.key());
+                entry.value_mut().iter_mut().for_each(|a| a.acc = acc.clone());
+            }
+        }
     }
 }
 
@@ -158,6 +617,8 @@ impl VWAPEngine {
 mod tests {
     use super::*;
     use crate::types::{Trade, Bar};
+    #[cfg(feature = "python")]
+    use crate::utils::with_py;
 
     #[test]
     fn test_vwap_engine_creation() {
@@ -175,6 +636,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         let result = engine.on_trade(&trade);
@@ -197,6 +659,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         let trade2 = Trade {
@@ -206,6 +669,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade1);
@@ -230,6 +694,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         let trade2 = Trade {
@@ -239,6 +704,7 @@ mod tests {
             symbol: "BTCUSDT".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade1);
@@ -262,11 +728,32 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         assert!(engine.on_trade(&trade).is_none());
     }
 
+    #[test]
+    fn test_vwap_rejects_replayed_trade() {
+        let mut engine = VWAPEngine::new();
+        engine.set_timestamp_policy("reject", 500).unwrap();
+        assert!(engine.set_timestamp_policy("drop", 0).is_err());
+        
+        engine.on_trade(&Trade::new(1000, 100.0, 10.0, "AAPL".to_string()));
+        engine.on_trade(&Trade::new(2000, 110.0, 10.0, "AAPL".to_string()));
+        
+        // Replay del primer trade: retrocede 1000 ms > tolerancia
+        let err = engine.try_on_trade(&Trade::new(1000, 100.0, 10.0, "AAPL".to_string())).unwrap_err();
+        assert_eq!(err.kind(), "stale_timestamp");
+        assert_eq!(engine.get_vwap("AAPL"), Some(105.0));
+        
+        // Desorden dentro de la tolerancia
+        assert!(engine.on_trade(&Trade::new(1600, 120.0, 10.0, "AAPL".to_string())).is_some());
+        assert_eq!(engine.get_max_ts("AAPL"), Some(2000));
+        assert_eq!(engine.get_rejections(Some("AAPL")).get("stale_timestamp"), Some(&1));
+    }
+
     #[test]
     fn test_vwap_on_bar() {
         let engine = VWAPEngine::new();
@@ -296,9 +783,9 @@ mod tests {
         let engine = VWAPEngine::new();
         
         let trades = vec![
-            Trade { ts: 1000, price: 150.0, size: 100.0, symbol: "AAPL".to_string(), side: None, exchange: None },
-            Trade { ts: 2000, price: 151.0, size: 50.0, symbol: "AAPL".to_string(), side: None, exchange: None },
-            Trade { ts: 3000, price: 152.0, size: 75.0, symbol: "AAPL".to_string(), side: None, exchange: None },
+            Trade { ts: 1000, price: 150.0, size: 100.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None },
+            Trade { ts: 2000, price: 151.0, size: 50.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None },
+            Trade { ts: 3000, price: 152.0, size: 75.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None },
         ];
         
         let results = engine.on_trade_batch(trades);
@@ -311,6 +798,24 @@ mod tests {
         assert!((results[1].vwap - expected2).abs() < 0.01);
     }
 
+    #[test]
+    fn test_vwap_batch_matches_accumulator() {
+        let engine = VWAPEngine::new();
+        let trades: Vec<Trade> = (0..11u64)
+            .map(|i| Trade::new(1000 + i, 100.0 + (i % 4) as f64 * 0.25, 1.0 + i as f64, "AAPL".to_string()))
+            .collect();
+
+        let results = engine.trade_batch(&trades);
+        let mut acc = VWAPAccumulator::default();
+        for (trade, metrics) in trades.iter().zip(&results) {
+            acc.update(trade.price, trade.size);
+            assert!((metrics.vwap - acc.vwap()).abs() < 1e-9);
+            assert!((metrics.v_sum - acc.v_sum).abs() < 1e-9);
+            assert!((metrics.std_dev - acc.std_dev()).abs() < 1e-9);
+            assert_eq!(metrics.last_ts, trade.ts);
+        }
+    }
+
     #[test]
     fn test_vwap_reset_symbol() {
         let engine = VWAPEngine::new();
@@ -322,6 +827,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade);
@@ -342,6 +848,7 @@ mod tests {
             symbol: "AAPL".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         let trade2 = Trade {
@@ -351,6 +858,7 @@ mod tests {
             symbol: "BTCUSDT".to_string(),
             side: None,
             exchange: None,
+            trade_id: None,
         };
         
         engine.on_trade(&trade1);
@@ -389,5 +897,260 @@ mod tests {
         let result = engine.on_bar(&bar);
         assert!(result.is_none());
     }
-}
 
+    #[test]
+    fn test_anchored_vwap_starts_at_anchor() {
+        let engine = VWAPEngine::new();
+        engine.anchor("AAPL", 2000);
+        
+        let trades = vec![
+            Trade { ts: 1000, price: 150.0, size: 100.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None },
+            Trade { ts: 2000, price: 152.0, size: 50.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None },
+            Trade { ts: 3000, price: 154.0, size: 50.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None },
+        ];
+        for trade in &trades {
+            engine.on_trade(trade);
+        }
+        
+        // Solo los trades con ts >= 2000 cuentan para el ancla
+        assert_eq!(engine.get_anchored_vwap("AAPL", 2000), Some(153.0));
+        // El VWAP de sesión incluye todos
+        let session = (150.0 * 100.0 + 152.0 * 50.0 + 154.0 * 50.0) / 200.0;
+        assert!((engine.get_vwap("AAPL").unwrap() - session).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vwap_session_calendar_rollover() {
+        let mut engine = VWAPEngine::new();
+        engine.set_session_calendar(Some(SessionCalendar::preset("nyse").unwrap()));
+        engine.anchor("AAPL", 0);
+        
+        // Lunes 2024-07-08 00:00 UTC; en verano NYSE abre a las 13:30 UTC
+        let monday = 1_720_396_800_000u64;
+        let minute = 60_000u64;
+        let trade = |ts: u64, price: f64| Trade {
+            ts, price, size: 100.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None,
+        };
+        
+        engine.on_trade(&trade(monday + 14 * 60 * minute, 100.0));
+        // Pre-market del martes: sigue contando para la sesión del lunes
+        engine.on_trade(&trade(monday + (24 + 13) * 60 * minute, 102.0));
+        assert_eq!(engine.get_vwap("AAPL"), Some(101.0));
+        
+        // Apertura del martes: el VWAP de sesión arranca de cero
+        let metrics = engine.on_trade(&trade(monday + (24 + 13) * 60 * minute + 30 * minute, 110.0)).unwrap();
+        assert_eq!(metrics.vwap, 110.0);
+        // El ancla no se resetea
+        let anchored = (100.0 + 102.0 + 110.0) / 3.0;
+        assert!((engine.get_anchored_vwap("AAPL", 0).unwrap() - anchored).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_anchored_vwap_multiple_anchors() {
+        let engine = VWAPEngine::new();
+        engine.anchor("AAPL", 3000);
+        engine.anchor("AAPL", 1000);
+        engine.anchor("AAPL", 1000); // Duplicado ignorado
+        
+        engine.on_trade(&Trade { ts: 1000, price: 100.0, size: 10.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None });
+        engine.on_trade(&Trade { ts: 3000, price: 110.0, size: 10.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None });
+        
+        let metrics = engine.get_anchored_vwaps("AAPL");
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].anchor_ts, Some(1000));
+        assert_eq!(metrics[0].vwap, 105.0);
+        assert_eq!(metrics[1].anchor_ts, Some(3000));
+        assert_eq!(metrics[1].vwap, 110.0);
+    }
+
+    #[test]
+    fn test_anchored_vwap_remove_and_reset() {
+        let engine = VWAPEngine::new();
+        engine.anchor("AAPL", 1000);
+        engine.anchor("MSFT", 1000);
+        
+        assert!(engine.remove_anchor("AAPL", 1000));
+        assert!(!engine.remove_anchor("AAPL", 1000));
+        assert!(engine.get_anchored_vwaps("AAPL").is_empty());
+        
+        engine.reset_symbol("MSFT");
+        assert_eq!(engine.get_anchored_vwap("MSFT", 1000), None);
+    }
+
+    #[test]
+    fn test_vwap_bands_single_price() {
+        let engine = VWAPEngine::new();
+        let trade = Trade { ts: 1000, price: 150.0, size: 100.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None };
+        
+        let metrics = engine.on_trade(&trade).unwrap();
+        // Un solo precio => desviación 0 y bandas colapsadas en el VWAP
+        assert_eq!(metrics.std_dev, 0.0);
+        assert_eq!(metrics.upper_band_3, 150.0);
+        assert_eq!(metrics.lower_band_3, 150.0);
+    }
+
+    #[test]
+    fn test_vwap_bands_weighted_std_dev() {
+        let engine = VWAPEngine::new();
+        engine.on_trade(&Trade { ts: 1000, price: 100.0, size: 10.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None });
+        let metrics = engine.on_trade(&Trade { ts: 2000, price: 110.0, size: 10.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None }).unwrap();
+        
+        // VWAP = 105, σ = 5
+        assert!((metrics.vwap - 105.0).abs() < 1e-9);
+        assert!((metrics.std_dev - 5.0).abs() < 1e-9);
+        assert!((metrics.upper_band_1 - 110.0).abs() < 1e-9);
+        assert!((metrics.lower_band_1 - 100.0).abs() < 1e-9);
+        assert!((metrics.upper_band_2 - 115.0).abs() < 1e-9);
+        assert!((metrics.lower_band_3 - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vwap_bands_large_price_level() {
+        // 5000.00 con un spread de 1-2 ticks: E[p²] - E[p]² perdería la varianza
+        let engine = VWAPEngine::new();
+        let trades: Vec<(f64, f64)> = (0..5_000)
+            .map(|i| (5_000.0 + (i % 3) as f64 * 0.01, 1.0 + (i % 5) as f64))
+            .collect();
+        let mut metrics = None;
+        for (i, &(price, size)) in trades.iter().enumerate() {
+            metrics = engine.on_trade(&Trade::new(i as u64, price, size, "ES".to_string()));
+        }
+        let metrics = metrics.unwrap();
+        
+        // Varianza ponderada en dos pasadas
+        let v: f64 = trades.iter().map(|t| t.1).sum();
+        let mean = trades.iter().map(|t| t.0 * t.1).sum::<f64>() / v;
+        let variance = trades.iter().map(|t| t.1 * (t.0 - mean).powi(2)).sum::<f64>() / v;
+        assert!((metrics.vwap - mean).abs() < 1e-9);
+        assert!((metrics.std_dev - variance.sqrt()).abs() <= 1e-9 * variance.sqrt(),
+                "{} != {}", metrics.std_dev, variance.sqrt());
+    }
+
+    #[test]
+    fn test_vwap_band_multipliers_configurable() {
+        let mut engine = VWAPEngine::new();
+        engine.set_band_multipliers((0.5, 1.0, 1.5)).unwrap();
+        assert!(engine.set_band_multipliers((-1.0, 1.0, 1.5)).is_err());
+        assert_eq!(engine.band_multipliers, (0.5, 1.0, 1.5));
+        
+        engine.on_trade(&Trade { ts: 1000, price: 100.0, size: 10.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None });
+        let metrics = engine.on_trade(&Trade { ts: 2000, price: 110.0, size: 10.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None }).unwrap();
+        
+        assert!((metrics.upper_band_1 - 107.5).abs() < 1e-9);
+        assert!((metrics.lower_band_3 - 97.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vwap_compensated_sums() {
+        let engine = VWAPEngine::new();
+        let mut naive = (0.0f64, 0.0f64);
+        let mut metrics = None;
+        for i in 0..100_000 {
+            naive.0 += 65_000.1 * 0.001;
+            naive.1 += 0.001;
+            metrics = engine.on_trade(&Trade::new(i, 65_000.1, 0.001, "BTC".to_string()));
+        }
+        let metrics = metrics.unwrap();
+        
+        // La suma directa se desvía; la compensada no
+        assert!((naive.1 - 100.0).abs() > 1e-10);
+        assert_eq!(metrics.v_sum, 100.0);
+        assert_eq!(metrics.pv_sum, 6_500_010.0);
+        assert_eq!(metrics.vwap, 65_000.1);
+        assert_ne!(naive.0 / naive.1, 65_000.1);
+    }
+
+    #[test]
+    fn test_vwap_fixed_point_sums_are_exact() {
+        let mut engine = VWAPEngine::new();
+        engine.set_fixed_point(2, 3, Some("BTC")).unwrap();
+        engine.anchor("BTC", 0);
+        assert_eq!(engine.get_fixed_point("BTC"), Some((2, 3)));
+        assert_eq!(engine.get_fixed_point("ETH"), None);
+        
+        for i in 0..1_000 {
+            engine.on_trade(&Trade::new(1000 + i, 100.01, 0.001, "BTC".to_string()));
+        }
+        assert_eq!(engine.get_vwap("BTC"), Some(100.01));
+        assert_eq!(engine.get_anchored_vwap("BTC", 0), Some(100.01));
+        #[cfg(feature = "python")]
+        {
+            let sums = with_py(|py| {
+                engine.get_sums_decimal(py, "BTC").unwrap()
+                    .map(|(pv, v)| (pv.bind(py).str().unwrap().to_string(), v.bind(py).str().unwrap().to_string()))
+            });
+            assert_eq!(sums, Some(("100.01000".to_string(), "1.000".to_string())));
+        }
+        
+        // Cambiar la precisión resetea los acumuladores pero conserva las anclas
+        engine.set_fixed_point(4, 3, None).unwrap();
+        assert_eq!(engine.get_vwap("BTC"), None);
+        assert_eq!(engine.get_anchored_vwap("BTC", 0), Some(0.0));
+        assert_eq!(engine.get_fixed_point("ETH"), Some((4, 3)));
+    }
+
+    #[test]
+    fn test_vwap_tick_arithmetic() {
+        let mut engine = VWAPEngine::new();
+        let float = VWAPEngine::new();
+        assert!(engine.set_tick_arithmetic(0.0, 1.0, None).is_err());
+        engine.set_fixed_point(2, 3, None).unwrap();
+        engine.set_tick_arithmetic(0.01, 0.001, Some("BTC")).unwrap();
+        engine.anchor("BTC", 0);
+        assert_eq!(engine.get_tick_arithmetic("BTC"), Some((0.01, 0.001)));
+        assert_eq!((engine.get_fixed_point("BTC"), engine.get_fixed_point("ETH")), (None, Some((2, 3))));
+
+        let mut metrics = None;
+        for i in 0..1_000 {
+            let trade = Trade::new(1000 + i, 100.01 + (i % 3) as f64 * 0.01, 0.001, "BTC".to_string());
+            metrics = engine.on_trade(&trade);
+            float.on_trade(&trade);
+        }
+        let metrics = metrics.unwrap();
+        let expected = float.get_metrics("BTC").unwrap();
+        assert_eq!(metrics.v_sum, 1.0);
+        assert!((metrics.vwap - expected.vwap).abs() < 1e-9);
+        assert!((metrics.std_dev - expected.std_dev).abs() < 1e-9);
+        assert_eq!(engine.get_anchored_vwap("BTC", 0), Some(metrics.vwap));
+
+        engine.disable_fixed_point(Some("BTC"));
+        assert_eq!((engine.get_tick_arithmetic("BTC"), engine.get_vwap("BTC")), (None, None));
+    }
+
+    #[test]
+    fn test_vwap_get_metrics() {
+        let mut engine = VWAPEngine::new();
+        assert!(engine.get_metrics("AAPL").is_none());
+        
+        let trade = |ts: u64, price: f64| Trade {
+            ts, price, size: 100.0, symbol: "AAPL".to_string(), side: None, exchange: None, trade_id: None,
+        };
+        engine.on_trade(&trade(1_000, 100.0));
+        let last = engine.on_trade(&trade(2_000, 102.0)).unwrap();
+        let metrics = engine.get_metrics("AAPL").unwrap();
+        assert_eq!((metrics.vwap, metrics.pv_sum, metrics.v_sum), (last.vwap, last.pv_sum, last.v_sum));
+        assert!((metrics.std_dev - last.std_dev).abs() < 1e-12);
+        assert_eq!((metrics.last_ts, metrics.session_id), (2_000, None));
+        
+        // Con calendario session_id es la apertura de la sesión (lunes 2024-07-08 13:30 UTC)
+        engine.set_session_calendar(Some(SessionCalendar::preset("nyse").unwrap()));
+        let open = 1_720_396_800_000u64 + 13 * 3_600_000 + 1_800_000;
+        engine.on_trade(&trade(open + 60_000, 101.0));
+        let metrics = engine.get_metrics("AAPL").unwrap();
+        assert_eq!(metrics.session_id, Some(open.to_string()));
+        assert_eq!(metrics.last_ts, open + 60_000);
+    }
+
+    #[test]
+    fn test_vwap_symbols_and_get_all() {
+        let engine = VWAPEngine::new();
+        engine.anchor("TSLA", 0);
+        engine.on_trade(&Trade::new(1_000, 100.0, 5.0, "MSFT".to_string()));
+        engine.on_trade(&Trade::new(1_000, 200.0, 5.0, "AAPL".to_string()));
+        // Las anclas cuentan como estado aunque aún no haya VWAP de sesión
+        assert_eq!(engine.symbols(), vec!["AAPL", "MSFT", "TSLA"]);
+        let all = engine.get_all();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["AAPL"].vwap, 200.0);
+    }
+}
@@ -0,0 +1,253 @@
+//! # Wall Tracker
+//! 
+//! Sigue la vida de los niveles significativos del libro ("muros") entre
+//! snapshots consecutivos. Un nivel es muro si su tamaño alcanza `min_size`
+//! y es al menos `size_ratio` veces el tamaño medio de los niveles de su lado
+//! en ese snapshot.
+//! 
+//! Cada snapshot se compara con el estado anterior y emite `WallEvent`:
+//! - "appear": el nivel pasa a ser muro
+//! - "refresh": el muro sigue y su tamaño vuelve a crecer (reposición)
+//! - "disappear": el nivel deja de ser muro o sale del snapshot, con su
+//!   tamaño máximo y su vida desde que apareció
+//! 
+//! Un muro que sale de la profundidad publicada cuenta como desaparecido.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::types::{BookSnapshot, Level, WallEvent};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+pub const EVENT_APPEAR: &str = "appear";
+pub const EVENT_REFRESH: &str = "refresh";
+pub const EVENT_DISAPPEAR: &str = "disappear";
+pub const EVENT_ACTIVE: &str = "active";
+
+/// Estado de un muro vivo
+#[derive(Clone, Debug)]
+struct WallState {
+    price: f64,
+    size: f64,
+    peak_size: f64,
+    first_seen_ts: u64,
+    refreshes: u32,
+}
+
+/// Clave de nivel: (es_bid, índice de tick)
+type LevelKey = (bool, i64);
+
+py_class! {
+/// Seguimiento de muros de liquidez por símbolo
+#[pyclass]
+#[derive(Clone)]
+pub struct WallTracker {
+    /// Tamaño mínimo absoluto de un muro
+    #[pyo3(get)]
+    pub min_size: f64,
+    /// Múltiplo mínimo del tamaño medio de los niveles de su lado
+    #[pyo3(get)]
+    pub size_ratio: f64,
+    #[pyo3(get)]
+    pub tick_size: f64,
+    walls: Arc<DashMap<String, HashMap<LevelKey, WallState>>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl WallTracker {
+    #[new]
+    #[pyo3(signature = (min_size=0.0, size_ratio=3.0, tick_size=0.01))]
+    pub fn new(min_size: f64, size_ratio: f64, tick_size: f64) -> Result<Self, ProcessError> {
+        if !(min_size.is_finite() && min_size >= 0.0) {
+            return Err(ProcessError::invalid("min_size must be finite and >= 0"));
+        }
+        if !(size_ratio.is_finite() && size_ratio >= 0.0 && tick_size.is_finite() && tick_size > 0.0) {
+            return Err(ProcessError::invalid(
+                "size_ratio must be >= 0 and tick_size positive"));
+        }
+        Ok(Self {
+            min_size,
+            size_ratio,
+            tick_size,
+            walls: Arc::new(DashMap::new()),
+        })
+    }
+    
+    /// Compara el snapshot con el anterior del símbolo y devuelve los eventos
+    /// de muros, ordenados por precio
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> Vec<WallEvent> {
+        let mut walls = self.walls.entry(snapshot.symbol.clone()).or_default();
+        let mut events = Vec::new();
+        let mut present: HashSet<LevelKey> = HashSet::new();
+        
+        let sides: [(bool, &Vec<Level>); 2] = [(true, &snapshot.bids), (false, &snapshot.asks)];
+        for (is_bid, book_side) in sides {
+            let levels: Vec<&Level> = book_side.iter().filter(|l| l.price > 0.0 && l.size > 0.0).collect();
+            if levels.is_empty() {
+                continue;
+            }
+            let mean = levels.iter().map(|l| l.size).sum::<f64>() / levels.len() as f64;
+            let threshold = self.min_size.max(self.size_ratio * mean);
+            
+            for level in levels.into_iter().filter(|l| l.size >= threshold) {
+                let key = (is_bid, self.tick(level.price));
+                present.insert(key);
+                match walls.get_mut(&key) {
+                    Some(wall) => {
+                        let refreshed = level.size > wall.size + f64::EPSILON;
+                        wall.size = level.size;
+                        wall.peak_size = wall.peak_size.max(level.size);
+                        if refreshed {
+                            wall.refreshes += 1;
+                            events.push(self.event(snapshot, is_bid, wall, EVENT_REFRESH));
+                        }
+                    }
+                    None => {
+                        let wall = WallState {
+                            price: level.price,
+                            size: level.size,
+                            peak_size: level.size,
+                            first_seen_ts: snapshot.ts,
+                            refreshes: 0,
+                        };
+                        events.push(self.event(snapshot, is_bid, &wall, EVENT_APPEAR));
+                        walls.insert(key, wall);
+                    }
+                }
+            }
+        }
+        
+        walls.retain(|key, wall| {
+            if present.contains(key) {
+                return true;
+            }
+            events.push(self.event(snapshot, key.0, wall, EVENT_DISAPPEAR));
+            false
+        });
+        events.sort_by(|a, b| a.price.total_cmp(&b.price));
+        events
+    }
+    
+    /// Muros vivos de un símbolo (event = "active", vida hasta `ts`)
+    pub fn get_walls(&self, symbol: &str, ts: u64) -> Vec<WallEvent> {
+        let Some(walls) = self.walls.get(symbol) else { return Vec::new() };
+        let mut active: Vec<WallEvent> = walls.iter()
+            .map(|(key, wall)| self.wall_event(symbol, ts, key.0, wall, EVENT_ACTIVE))
+            .collect();
+        active.sort_by(|a, b| a.price.total_cmp(&b.price));
+        active
+    }
+    
+    /// Resetea el estado de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.walls.remove(symbol);
+    }
+    
+    /// Resetea todos los símbolos
+    pub fn reset_all(&self) {
+        self.walls.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("WallTracker(min_size={}, size_ratio={}, symbols={})",
+                self.min_size, self.size_ratio, self.walls.len())
+    }
+}
+}
+
+impl WallTracker {
+    /// Símbolos con estado
+    pub fn state_len(&self) -> usize {
+        self.walls.len()
+    }
+    
+    fn tick(&self, price: f64) -> i64 {
+        (price / self.tick_size).round() as i64
+    }
+    
+    fn event(&self, snapshot: &BookSnapshot, is_bid: bool, wall: &WallState, event: &str) -> WallEvent {
+        self.wall_event(&snapshot.symbol, snapshot.ts, is_bid, wall, event)
+    }
+    
+    fn wall_event(&self, symbol: &str, ts: u64, is_bid: bool, wall: &WallState, event: &str) -> WallEvent {
+        WallEvent {
+            ts,
+            symbol: symbol.to_string(),
+            side: if is_bid { "bid" } else { "ask" }.to_string(),
+            price: wall.price,
+            event: event.to_string(),
+            size: wall.size,
+            peak_size: wall.peak_size,
+            first_seen_ts: wall.first_seen_ts,
+            lifetime_ms: ts.saturating_sub(wall.first_seen_ts),
+            refreshes: wall.refreshes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn book(ts: u64, bid_wall: Option<f64>, ask_wall: f64) -> BookSnapshot {
+        let mut bids = vec![Level::new(99.99, 10.0), Level::new(99.98, 10.0), Level::new(99.97, 10.0)];
+        if let Some(size) = bid_wall {
+            bids.push(Level::new(99.96, size));
+        }
+        let asks = vec![
+            Level::new(100.01, 10.0), Level::new(100.02, 10.0),
+            Level::new(100.03, 10.0), Level::new(100.04, ask_wall),
+        ];
+        BookSnapshot::new(ts, "AAPL".to_string(), bids, asks)
+    }
+    
+    fn kinds(events: &[WallEvent]) -> Vec<(&str, &str)> {
+        events.iter().map(|e| (e.side.as_str(), e.event.as_str())).collect()
+    }
+    
+    #[test]
+    fn test_wall_tracker_validation() {
+        assert!(WallTracker::new(-1.0, 3.0, 0.01).is_err());
+        assert!(WallTracker::new(0.0, 3.0, 0.0).is_err());
+    }
+    
+    #[test]
+    fn test_wall_lifecycle() {
+        let tracker = WallTracker::new(0.0, 2.0, 0.01).unwrap();
+        let events = tracker.on_snapshot(&book(1_000, Some(200.0), 10.0));
+        assert_eq!(kinds(&events), vec![("bid", "appear")]);
+        assert_eq!(events[0].price, 99.96);
+        
+        // Se consume parte y después se repone; aparece un muro en el ask
+        assert!(tracker.on_snapshot(&book(2_000, Some(150.0), 10.0)).is_empty());
+        let events = tracker.on_snapshot(&book(3_000, Some(180.0), 100.0));
+        assert_eq!(kinds(&events), vec![("bid", "refresh"), ("ask", "appear")]);
+        assert_eq!(events[0].refreshes, 1);
+        assert_eq!(tracker.get_walls("AAPL", 3_500).len(), 2);
+        
+        // El muro del bid se retira
+        let events = tracker.on_snapshot(&book(5_000, None, 100.0));
+        assert_eq!(kinds(&events), vec![("bid", "disappear")]);
+        let gone = &events[0];
+        assert_eq!((gone.peak_size, gone.size), (200.0, 180.0));
+        assert_eq!((gone.first_seen_ts, gone.lifetime_ms), (1_000, 4_000));
+        
+        let active = tracker.get_walls("AAPL", 6_000);
+        assert_eq!(active.len(), 1);
+        assert_eq!((active[0].event.as_str(), active[0].lifetime_ms), ("active", 3_000));
+    }
+    
+    #[test]
+    fn test_wall_min_size() {
+        let tracker = WallTracker::new(500.0, 2.0, 0.01).unwrap();
+        // Relativamente grande pero por debajo del mínimo absoluto
+        assert!(tracker.on_snapshot(&book(1_000, Some(200.0), 10.0)).is_empty());
+        assert_eq!(kinds(&tracker.on_snapshot(&book(2_000, Some(600.0), 10.0))), vec![("bid", "appear")]);
+    }
+}
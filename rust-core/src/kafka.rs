@@ -0,0 +1,611 @@
+//! # Kafka
+//! 
+//! Fuente y destino Kafka equivalentes a los de NATS (feature `kafka`):
+//! - `KafkaSubscriber`: consumer group sobre una lista de topics, cada uno
+//!   con su tipo de mensaje ("trades", "books", "bars" o "auto"). Cada
+//!   mensaje se decodifica según `wire_format`, pasa por el `EngineManager`
+//!   y su offset se confirma después de procesarlo.
+//! - `KafkaPublisher`: publica las métricas en el topic de una plantilla con
+//!   `{kind}` y `{symbol}` (por defecto `indicators.{kind}`) usando el
+//!   símbolo como clave, en JSON o MessagePack.
+//! 
+//! librdkafka gestiona la conexión, las reconexiones y el envío por lotes.
+
+use parking_lot::Mutex;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer};
+use rdkafka::ClientContext;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::manager::EngineManager;
+use crate::nats_publisher::{render_subject, KIND_CVD, KIND_HEATMAP, KIND_LIQUIDITY, KIND_VWAP};
+use crate::nats_subscriber::dispatch;
+use crate::types::{CVDMetrics, HeatmapMetrics, LiquidityMetrics, MetricsBundle, VWAPMetrics};
+use crate::wire::{decode, encode, MessageKind, WireFormat};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Espera máxima para vaciar la cola del producer al parar
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+py_class! {
+/// Configuración del consumer Kafka
+#[pyclass]
+#[derive(Clone)]
+pub struct KafkaConfig {
+    /// Lista de brokers ("host:9092,host2:9092")
+    #[pyo3(get, set)]
+    pub brokers: String,
+    #[pyo3(get, set)]
+    pub group_id: String,
+    /// Pares (topic, tipo de mensaje)
+    #[pyo3(get)]
+    pub topics: Vec<(String, String)>,
+    /// Dónde empezar sin offset confirmado: "earliest" o "latest"
+    #[pyo3(get, set)]
+    pub auto_offset_reset: String,
+    /// Propiedades adicionales de librdkafka (p. ej. "security.protocol")
+    #[pyo3(get, set)]
+    pub properties: HashMap<String, String>,
+    /// Formato de cable de los mensajes: "json", "msgpack" o "protobuf"
+    pub wire_format: WireFormat,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl KafkaConfig {
+    #[new]
+    #[pyo3(signature = (brokers, group_id, topics, wire_format="json", auto_offset_reset="earliest".to_string(),
+                        properties=HashMap::new()))]
+    pub fn new(brokers: String, group_id: String, topics: Vec<(String, String)>, wire_format: &str,
+               auto_offset_reset: String, properties: HashMap<String, String>) -> Result<Self, ProcessError> {
+        if group_id.is_empty() {
+            return Err(ProcessError::invalid("group_id must not be empty"));
+        }
+        let mut config = Self {
+            brokers,
+            group_id,
+            topics: Vec::new(),
+            auto_offset_reset,
+            properties,
+            wire_format: WireFormat::Json,
+        };
+        config.set_topics(topics)?;
+        config.set_wire_format(wire_format)?;
+        Ok(config)
+    }
+    
+    /// Reemplaza la lista de (topic, tipo); el tipo se valida aquí
+    #[setter]
+    pub fn set_topics(&mut self, topics: Vec<(String, String)>) -> Result<(), ProcessError> {
+        if topics.is_empty() {
+            return Err(ProcessError::invalid("topics must not be empty"));
+        }
+        for (topic, kind) in &topics {
+            if topic.is_empty() || MessageKind::parse(kind).is_none() {
+                return Err(ProcessError::invalid(format!(
+                    "invalid topic mapping ({}, {}): kind must be trades, books, bars or auto", topic, kind)));
+            }
+        }
+        self.topics = topics;
+        Ok(())
+    }
+    
+    #[getter]
+    pub fn get_wire_format(&self) -> &'static str {
+        self.wire_format.as_str()
+    }
+    
+    #[setter]
+    pub fn set_wire_format(&mut self, wire_format: &str) -> Result<(), ProcessError> {
+        self.wire_format = WireFormat::parse(wire_format).ok_or_else(|| ProcessError::invalid(
+            format!("invalid wire_format {}: must be json, msgpack or protobuf", wire_format)))?;
+        Ok(())
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("KafkaConfig(brokers={}, group_id={}, topics={:?}, wire_format={})",
+                self.brokers, self.group_id, self.topic_names(), self.wire_format.as_str())
+    }
+}
+}
+
+impl KafkaConfig {
+    /// Tipo de mensaje por topic
+    pub fn routes(&self) -> HashMap<String, MessageKind> {
+        self.topics.iter()
+            .filter_map(|(topic, kind)| MessageKind::parse(kind).map(|k| (topic.clone(), k)))
+            .collect()
+    }
+    
+    fn topic_names(&self) -> Vec<String> {
+        self.topics.iter().map(|(topic, _)| topic.clone()).collect()
+    }
+    
+    /// Configuración de librdkafka: offsets confirmados a mano tras procesar
+    fn client_config(&self) -> ClientConfig {
+        let mut client = ClientConfig::new();
+        client
+            .set("bootstrap.servers", &self.brokers)
+            .set("group.id", &self.group_id)
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", &self.auto_offset_reset);
+        for (key, value) in &self.properties {
+            client.set(key, value);
+        }
+        client
+    }
+}
+
+/// Contadores compartidos entre el hilo del consumer y Python
+#[derive(Default)]
+struct SubscriberStats {
+    received: AtomicU64,
+    processed: AtomicU64,
+    errors: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl SubscriberStats {
+    fn record_error(&self, error: String) {
+        tracing::warn!(error = %error, "kafka error");
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock() = Some(error);
+    }
+}
+
+py_class! {
+/// Consumer Kafka que despacha los mensajes al `EngineManager`
+#[pyclass]
+pub struct KafkaSubscriber {
+    config: KafkaConfig,
+    manager: Arc<EngineManager>,
+    stats: Arc<SubscriberStats>,
+    running: Arc<AtomicBool>,
+    stop_tx: Option<oneshot::Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl KafkaSubscriber {
+    #[new]
+    pub fn new(config: KafkaConfig) -> Result<Self, ProcessError> {
+        Ok(Self::with_manager(config, Arc::new(EngineManager::new(true, true, true, true, None, None, None)?)))
+    }
+    
+    /// Se une al consumer group y comienza a procesar en segundo plano
+    pub fn start(&mut self) -> Result<(), ProcessError> {
+        if self.is_running() {
+            return Err(ProcessError::runtime("subscriber already running"));
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ProcessError::runtime(format!("tokio runtime error: {}", e)))?;
+        // El StreamConsumer lanza tareas al crearse: dentro del runtime del hilo
+        let consumer: StreamConsumer = {
+            let _guard = runtime.enter();
+            self.config.client_config().create()
+                .map_err(|e| ProcessError::runtime(format!("Kafka consumer error: {}", e)))?
+        };
+        let topics = self.config.topic_names();
+        let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+        consumer.subscribe(&topics)
+            .map_err(|e| ProcessError::runtime(format!("Kafka subscribe error: {}", e)))?;
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let routes = self.config.routes();
+        let wire_format = self.config.wire_format;
+        let manager = self.manager.clone();
+        let stats = self.stats.clone();
+        let running = self.running.clone();
+        
+        running.store(true, Ordering::SeqCst);
+        let worker = std::thread::Builder::new()
+            .name("kafka-subscriber".to_string())
+            .spawn(move || {
+                runtime.block_on(consume(consumer, routes, wire_format, manager, stats, stop_rx));
+                running.store(false, Ordering::SeqCst);
+            })
+            .map_err(|e| {
+                self.running.store(false, Ordering::SeqCst);
+                ProcessError::runtime(format!("thread spawn error: {}", e))
+            })?;
+        
+        self.stop_tx = Some(stop_tx);
+        self.worker = Some(worker);
+        Ok(())
+    }
+    
+    /// Detiene el consumer (sale del grupo) y espera a que termine el hilo
+    pub fn stop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+    
+    #[getter]
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+    
+    /// Último error de Kafka o de decodificación
+    #[getter]
+    pub fn last_error(&self) -> Option<String> {
+        self.stats.last_error.lock().clone()
+    }
+    
+    /// Contadores: received, processed, errors
+    pub fn stats(&self) -> HashMap<String, u64> {
+        HashMap::from([
+            ("received".to_string(), self.stats.received.load(Ordering::Relaxed)),
+            ("processed".to_string(), self.stats.processed.load(Ordering::Relaxed)),
+            ("errors".to_string(), self.stats.errors.load(Ordering::Relaxed)),
+        ])
+    }
+    
+    /// Callback con cada `MetricsBundle` calculado (ver `EngineManager.on_metrics`)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (callback=None, throttle_ms=None))]
+    fn on_metrics(&self, callback: Option<PyObject>, throttle_ms: Option<u64>) {
+        self.manager.on_metrics(callback, throttle_ms);
+    }
+    
+    /// Estado actual de los indicadores para un símbolo
+    pub fn snapshot_state(&self, symbol: &str) -> HashMap<String, HashMap<String, f64>> {
+        self.manager.snapshot_state(symbol)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("KafkaSubscriber(brokers={}, group_id={}, running={})",
+                self.config.brokers, self.config.group_id, self.is_running())
+    }
+}
+}
+
+impl KafkaSubscriber {
+    /// Suscriptor que despacha a un manager ya configurado (p. ej. el del daemon)
+    pub fn with_manager(config: KafkaConfig, manager: Arc<EngineManager>) -> Self {
+        Self {
+            config,
+            manager,
+            stats: Arc::new(SubscriberStats::default()),
+            running: Arc::new(AtomicBool::new(false)),
+            stop_tx: None,
+            worker: None,
+        }
+    }
+}
+
+impl Drop for KafkaSubscriber {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Bucle del consumer hasta stop. Los errores de Kafka no lo detienen:
+/// librdkafka reintenta la conexión por su cuenta.
+async fn consume(
+    consumer: StreamConsumer,
+    routes: HashMap<String, MessageKind>,
+    wire_format: WireFormat,
+    manager: Arc<EngineManager>,
+    stats: Arc<SubscriberStats>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            next = consumer.recv() => {
+                let message = match next {
+                    Ok(message) => message,
+                    Err(e) => {
+                        stats.record_error(format!("Kafka error: {}", e));
+                        continue;
+                    }
+                };
+                stats.received.fetch_add(1, Ordering::Relaxed);
+                
+                let decoded = match (routes.get(message.topic()), message.payload()) {
+                    (Some(&kind), Some(payload)) => decode(wire_format, kind, payload),
+                    (None, _) => Err(format!("no route for topic {}", message.topic())),
+                    (_, None) => Err(format!("empty message on topic {}", message.topic())),
+                };
+                match decoded {
+                    Ok(event) => {
+                        dispatch(&manager, &event);
+                        stats.processed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) => stats.record_error(e),
+                }
+                // Los mensajes inválidos también se confirman para no releerlos
+                if let Err(e) = consumer.commit_message(&message, CommitMode::Async) {
+                    stats.record_error(format!("commit error: {}", e));
+                }
+            }
+        }
+    }
+    consumer.unsubscribe();
+}
+
+/// Contadores del publisher; las entregas se cuentan en el callback de librdkafka
+#[derive(Default)]
+struct PublisherStats {
+    queued: AtomicU64,
+    published: AtomicU64,
+    errors: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl PublisherStats {
+    fn record_error(&self, error: String) {
+        tracing::warn!(error = %error, "kafka error");
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock() = Some(error);
+    }
+}
+
+/// Contexto del producer que registra el resultado de cada entrega
+struct DeliveryStats(Arc<PublisherStats>);
+
+impl ClientContext for DeliveryStats {}
+
+impl ProducerContext for DeliveryStats {
+    type DeliveryOpaque = ();
+    
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        match result {
+            Ok(_) => { self.0.published.fetch_add(1, Ordering::Relaxed); }
+            Err((e, _)) => self.0.record_error(format!("delivery error: {}", e)),
+        }
+    }
+}
+
+py_class! {
+/// Publisher de métricas hacia Kafka
+#[pyclass]
+pub struct KafkaPublisher {
+    #[pyo3(get)]
+    pub brokers: String,
+    #[pyo3(get)]
+    pub topic_template: String,
+    // Plantillas por tipo de métrica que sustituyen a topic_template
+    topics: HashMap<String, String>,
+    format: WireFormat,
+    properties: HashMap<String, String>,
+    stats: Arc<PublisherStats>,
+    producer: Option<ThreadedProducer<DeliveryStats>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl KafkaPublisher {
+    #[new]
+    #[pyo3(signature = (brokers, topic_template="indicators.{kind}".to_string(), wire_format="json",
+                        properties=HashMap::new()))]
+    pub fn new(brokers: String, topic_template: String, wire_format: &str,
+               properties: HashMap<String, String>) -> Result<Self, ProcessError> {
+        let format = match WireFormat::parse(wire_format) {
+            Some(format @ (WireFormat::Json | WireFormat::MessagePack)) => format,
+            _ => return Err(ProcessError::invalid(
+                format!("invalid wire_format {}: must be json or msgpack", wire_format))),
+        };
+        Ok(Self {
+            brokers,
+            topic_template,
+            topics: HashMap::new(),
+            format,
+            properties,
+            stats: Arc::new(PublisherStats::default()),
+            producer: None,
+        })
+    }
+    
+    #[getter]
+    pub fn wire_format(&self) -> &'static str {
+        self.format.as_str()
+    }
+    
+    /// Plantilla de topic propia de un tipo ("cvd", "vwap", "liquidity", "heatmap")
+    pub fn set_topic(&mut self, kind: &str, template: &str) {
+        self.topics.insert(kind.to_string(), template.to_string());
+    }
+    
+    /// Topic al que se publica un tipo de métrica de un símbolo
+    pub fn topic_for(&self, kind: &str, symbol: &str) -> String {
+        let template = self.topics.get(kind).unwrap_or(&self.topic_template);
+        render_subject(template, kind, symbol)
+    }
+    
+    /// Crea el producer; librdkafka conecta y envía desde su propio hilo
+    pub fn start(&mut self) -> Result<(), ProcessError> {
+        if self.is_running() {
+            return Err(ProcessError::runtime("publisher already running"));
+        }
+        let mut client = ClientConfig::new();
+        client.set("bootstrap.servers", &self.brokers);
+        for (key, value) in &self.properties {
+            client.set(key, value);
+        }
+        let producer = client.create_with_context(DeliveryStats(self.stats.clone()))
+            .map_err(|e| ProcessError::runtime(format!("Kafka producer error: {}", e)))?;
+        self.producer = Some(producer);
+        Ok(())
+    }
+    
+    /// Entrega lo pendiente (acotado por FLUSH_TIMEOUT) y libera el producer
+    pub fn stop(&mut self) {
+        if let Some(producer) = self.producer.take() {
+            if let Err(e) = producer.flush(FLUSH_TIMEOUT) {
+                self.stats.record_error(format!("flush error: {}", e));
+            }
+        }
+    }
+    
+    #[getter]
+    pub fn is_running(&self) -> bool {
+        self.producer.is_some()
+    }
+    
+    /// Último error de serialización o entrega
+    #[getter]
+    pub fn last_error(&self) -> Option<String> {
+        self.stats.last_error.lock().clone()
+    }
+    
+    /// Contadores: queued, published, errors
+    pub fn stats(&self) -> HashMap<String, u64> {
+        HashMap::from([
+            ("queued".to_string(), self.stats.queued.load(Ordering::Relaxed)),
+            ("published".to_string(), self.stats.published.load(Ordering::Relaxed)),
+            ("errors".to_string(), self.stats.errors.load(Ordering::Relaxed)),
+        ])
+    }
+    
+    pub fn publish_cvd(&self, symbol: &str, metrics: &CVDMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_CVD, symbol, metrics)
+    }
+    
+    pub fn publish_vwap(&self, symbol: &str, metrics: &VWAPMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_VWAP, symbol, metrics)
+    }
+    
+    pub fn publish_liquidity(&self, symbol: &str, metrics: &LiquidityMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_LIQUIDITY, symbol, metrics)
+    }
+    
+    pub fn publish_heatmap(&self, metrics: &HeatmapMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_HEATMAP, &metrics.symbol, metrics)
+    }
+    
+    /// Publica todas las métricas presentes en el resultado del `EngineManager`
+    pub fn publish_bundle(&self, bundle: &MetricsBundle) -> Result<(), ProcessError> {
+        if let Some(m) = &bundle.cvd {
+            self.publish(KIND_CVD, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.vwap {
+            self.publish(KIND_VWAP, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.liquidity {
+            self.publish(KIND_LIQUIDITY, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.heatmap {
+            self.publish(KIND_HEATMAP, &bundle.symbol, m)?;
+        }
+        Ok(())
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("KafkaPublisher(brokers={}, topic_template={}, running={})",
+                self.brokers, self.topic_template, self.is_running())
+    }
+}
+}
+
+impl KafkaPublisher {
+    /// Serializa y encola una métrica con el símbolo como clave (mismo
+    /// símbolo, misma partición); falla si el publisher no está arrancado
+    pub fn publish<T: Serialize>(&self, kind: &str, symbol: &str, metrics: &T) -> Result<(), ProcessError> {
+        let producer = self.producer.as_ref().ok_or_else(|| {
+            ProcessError::runtime("publisher not running")
+        })?;
+        let payload = encode(self.format, metrics).map_err(ProcessError::invalid)?;
+        let topic = self.topic_for(kind, symbol);
+        producer.send(BaseRecord::to(&topic).key(symbol).payload(&payload))
+            .map_err(|(e, _)| ProcessError::runtime(format!("Kafka send error: {}", e)))?;
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Drop for KafkaPublisher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_kafka_config() {
+        let config = KafkaConfig::new(
+            "localhost:9092".to_string(), "indicators".to_string(),
+            vec![("md.trades".to_string(), "trades".to_string()), ("md.books".to_string(), "books".to_string())],
+            "msgpack", "earliest".to_string(), HashMap::new(),
+        ).unwrap();
+        assert_eq!(config.routes().get("md.books"), Some(&MessageKind::Books));
+        assert_eq!(config.get_wire_format(), "msgpack");
+        
+        assert!(KafkaConfig::new("b".to_string(), "g".to_string(), Vec::new(), "json", "earliest".to_string(), HashMap::new()).is_err());
+        assert!(KafkaConfig::new("b".to_string(), String::new(), vec![("t".to_string(), "auto".to_string())],
+                                 "json", "earliest".to_string(), HashMap::new()).is_err());
+        assert!(KafkaConfig::new("b".to_string(), "g".to_string(), vec![("t".to_string(), "quotes".to_string())],
+                                 "json", "earliest".to_string(), HashMap::new()).is_err());
+    }
+    
+    #[test]
+    fn test_publisher_topics() {
+        let mut publisher = KafkaPublisher::new("localhost:9092".to_string(), "indicators.{kind}".to_string(),
+                                                "json", HashMap::new()).unwrap();
+        assert_eq!(publisher.topic_for(KIND_CVD, "AAPL"), "indicators.cvd");
+        publisher.set_topic(KIND_HEATMAP, "heatmap.{symbol}");
+        assert_eq!(publisher.topic_for(KIND_HEATMAP, "ES"), "heatmap.ES");
+        
+        // Sin start no se encola nada
+        assert!(publisher.publish_bundle(&MetricsBundle::empty("AAPL", 1000)).is_ok());
+        let heatmap = HeatmapMetrics {
+            bucket_ts: 0,
+            bucket_ms: 1000,
+            tiles: Vec::new(),
+            max_sz: 0.0,
+            compression_ratio: 1.0,
+            symbol: "AAPL".to_string(),
+            normalization: "raw".to_string(),
+            mid: None,
+            notional: false,
+        };
+        assert!(publisher.publish_heatmap(&heatmap).is_err());
+        
+        assert!(KafkaPublisher::new("b".to_string(), "t".to_string(), "protobuf", HashMap::new()).is_err());
+    }
+    
+    #[test]
+    fn test_subscriber_start_stop_without_broker() {
+        // librdkafka conecta en segundo plano: sin broker el consumer arranca
+        // y se detiene limpiamente
+        let config = KafkaConfig::new("127.0.0.1:1".to_string(), "indicators".to_string(),
+                                      vec![("md.trades".to_string(), "trades".to_string())],
+                                      "json", "earliest".to_string(), HashMap::new()).unwrap();
+        let mut subscriber = KafkaSubscriber::new(config).unwrap();
+        subscriber.start().unwrap();
+        assert!(subscriber.is_running());
+        std::thread::sleep(Duration::from_millis(100));
+        subscriber.stop();
+        assert!(!subscriber.is_running());
+    }
+}
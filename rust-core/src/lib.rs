@@ -3,74 +3,246 @@
 //! High-performance indicators engine core written in Rust.
 //! Provides ultra-low latency calculations for critical indicators.
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use std::collections::HashMap;
 
+// py_class! / py_methods!: bindings PyO3 solo con la feature python
+mod py_macros;
+
 // Módulos de indicadores
 pub mod indicators;
 pub mod types;
+pub mod errors;
 pub mod utils;
+#[cfg(feature = "nats")]
+pub mod nats_connection;
+#[cfg(feature = "nats")]
 pub mod nats_subscriber;
+#[cfg(feature = "nats")]
+pub mod nats_publisher;
+pub mod wire;
+#[cfg(feature = "nats")]
+pub mod queue;
+pub mod telemetry;
+pub mod logging;
+#[cfg(feature = "loaders")]
+pub mod loaders;
+#[cfg(feature = "python")]
+pub mod arrow_ffi;
+#[cfg(feature = "python")]
+pub mod numpy_batch;
+pub mod bench;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "redis")]
+pub mod redis_publisher;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod order_book;
+pub mod bars;
+pub mod config;
+pub mod calendar;
+pub mod manager;
+pub mod dedup;
+pub mod fixed;
+pub mod numeric;
+#[cfg(feature = "python")]
+pub mod py_serde;
+#[cfg(feature = "python")]
+pub mod py_asyncio;
+#[cfg(feature = "nats")]
+pub mod daemon;
 
 // Re-exportar tipos principales para Python
 pub use types::*;
 pub use indicators::*;
+pub use order_book::OrderBookManager;
+pub use bars::BarAggregator;
+pub use manager::EngineManager;
+pub use utils::TickSizeRegistry;
+pub use utils::moments::StreamingStats;
+pub use utils::sketch::QuantileSketch;
+pub use config::{load_config, ConfigRegistry, SymbolConfig};
+pub use calendar::SessionCalendar;
+pub use errors::ProcessError;
 
 /// Inicializar el módulo Python
+#[cfg(feature = "python")]
 #[pymodule]
-fn indicators_core(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+fn indicators_core(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Registrar tipos de datos
     m.add_class::<Trade>()?;
     m.add_class::<Bar>()?;
     m.add_class::<Level>()?;
     m.add_class::<BookSnapshot>()?;
+    m.add_class::<Quote>()?;
+    m.add_class::<BookDelta>()?;
+    m.add_class::<OpenInterest>()?;
+    m.add_class::<FundingRate>()?;
+    m.add_class::<Liquidation>()?;
+    m.add_class::<TickSizeRegistry>()?;
+    m.add_class::<StreamingStats>()?;
+    m.add_class::<QuantileSketch>()?;
+    m.add_class::<SymbolConfig>()?;
+    m.add_class::<ConfigRegistry>()?;
+    m.add_class::<SessionCalendar>()?;
     
     // Registrar métricas
     m.add_class::<CVDMetrics>()?;
+    m.add_class::<CVDBar>()?;
+    m.add_class::<FootprintLevel>()?;
+    m.add_class::<FootprintBar>()?;
     m.add_class::<LiquidityMetrics>()?;
+    m.add_class::<LiquidityRollingStats>()?;
+    m.add_class::<EnrichedTrade>()?;
+    m.add_class::<EffectiveSpreadMetrics>()?;
+    m.add_class::<SpreadWindowStats>()?;
+    m.add_class::<LiquidityAlertConfig>()?;
+    m.add_class::<LiquidityAlert>()?;
+    m.add_class::<BookQualityAlert>()?;
+    m.add_class::<IcebergDetection>()?;
+    m.add_class::<AbsorptionEvent>()?;
+    m.add_class::<BookPressureMetrics>()?;
     m.add_class::<Tile>()?;
     m.add_class::<HeatmapMetrics>()?;
     m.add_class::<VWAPMetrics>()?;
+    m.add_class::<VolumeProfileMetrics>()?;
+    m.add_class::<MovingAverageMetrics>()?;
+    m.add_class::<RSIMetrics>()?;
+    m.add_class::<VolatilityMetrics>()?;
+    m.add_class::<ATRMetrics>()?;
+    m.add_class::<OIMetrics>()?;
+    m.add_class::<LiquidationMetrics>()?;
+    m.add_class::<LiquidationCascade>()?;
+    m.add_class::<VPINMetrics>()?;
+    m.add_class::<TapeMetrics>()?;
+    m.add_class::<LargePrint>()?;
+    m.add_class::<QuoteMetrics>()?;
+    m.add_class::<SweepEvent>()?;
+    m.add_class::<FlowSignal>()?;
+    m.add_class::<DataGapAlert>()?;
+    m.add_class::<WallEvent>()?;
+    m.add_class::<SpreadMetrics>()?;
+    m.add_class::<MetricsBundle>()?;
+    
+    // Registrar excepciones de procesamiento
+    m.add("ProcessingError", py.get_type_bound::<errors::ProcessingError>())?;
+    m.add("InvalidPriceError", py.get_type_bound::<errors::InvalidPriceError>())?;
+    m.add("InvalidSizeError", py.get_type_bound::<errors::InvalidSizeError>())?;
+    m.add("StaleTimestampError", py.get_type_bound::<errors::StaleTimestampError>())?;
+    m.add("CrossedBookError", py.get_type_bound::<errors::CrossedBookError>())?;
+    m.add("EmptyBookError", py.get_type_bound::<errors::EmptyBookError>())?;
+    m.add("MalformedBookError", py.get_type_bound::<errors::MalformedBookError>())?;
     
     // Registrar engines de indicadores
     m.add_class::<CVDEngine>()?;
+    m.add_class::<CVDBarEngine>()?;
+    m.add_class::<FootprintEngine>()?;
     m.add_class::<LiquidityEngine>()?;
     m.add_class::<HeatmapEngine>()?;
+    m.add_class::<IcebergDetector>()?;
+    m.add_class::<AbsorptionDetector>()?;
+    m.add_class::<BookPressureEngine>()?;
     m.add_class::<VWAPEngine>()?;
+    m.add_class::<RollingVWAPEngine>()?;
+    m.add_class::<VolumeProfileEngine>()?;
+    m.add_class::<MovingAverageEngine>()?;
+    m.add_class::<RSIEngine>()?;
+    m.add_class::<VolatilityEngine>()?;
+    m.add_class::<ATREngine>()?;
+    m.add_class::<OIEngine>()?;
+    m.add_class::<LiquidationEngine>()?;
+    m.add_class::<VPINEngine>()?;
+    m.add_class::<TapeEngine>()?;
+    m.add_class::<QuoteEngine>()?;
+    m.add_class::<TradeEnricher>()?;
+    m.add_class::<SweepDetector>()?;
+    m.add_class::<FlowSignalEngine>()?;
+    m.add_class::<SpreadEngine>()?;
+    m.add_class::<CorrelationEngine>()?;
+    m.add_class::<GapDetector>()?;
+    m.add_class::<WallTracker>()?;
+    m.add_class::<OrderBookManager>()?;
+    m.add_class::<BarAggregator>()?;
+    m.add_class::<EngineManager>()?;
     
     // Registrar NATS
     m.add_class::<crate::nats_subscriber::NATSConfig>()?;
     m.add_class::<crate::nats_subscriber::NATSSubscriber>()?;
+    m.add_class::<crate::py_asyncio::MetricsStream>()?;
+    m.add_class::<crate::nats_publisher::NATSPublisher>()?;
+    m.add_class::<crate::nats_connection::ConnectionEvent>()?;
+    #[cfg(feature = "kafka")]
+    {
+        m.add_class::<crate::kafka::KafkaConfig>()?;
+        m.add_class::<crate::kafka::KafkaSubscriber>()?;
+        m.add_class::<crate::kafka::KafkaPublisher>()?;
+    }
+    #[cfg(feature = "redis")]
+    m.add_class::<crate::redis_publisher::RedisPublisher>()?;
     
     // Registrar funciones de utilidad
     let benchmark_func = wrap_pyfunction!(benchmark_indicators, m)?;
     m.add_function(benchmark_func)?;
+    m.add_function(wrap_pyfunction!(load_config, m)?)?;
+    m.add_function(wrap_pyfunction!(logging::init_logging, m)?)?;
+    m.add_function(wrap_pyfunction!(logging::set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_trades_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_trades_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_bars_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_bars_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_books_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_books_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(py_serde::unpickle, m)?)?;
     
     Ok(())
 }
 
-/// Función de benchmark para comparar rendimiento
+/// Benchmark de todos los engines y paths batch. Usa `trades` si se pasan;
+/// si no, genera `n_events` trades sintéticos (snapshots y barras se derivan
+/// de los trades). Devuelve por engine: events, total_secs, ops_per_sec,
+/// p50_ns y p99_ns (latencia por evento).
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (trades=None, iterations=1, n_events=10_000, n_symbols=1, base_price=100.0,
+                    tick_size=0.01, depth_levels=10, seed=42))]
+#[allow(clippy::too_many_arguments)]
 fn benchmark_indicators(
-    trades: Vec<Trade>,
+    py: Python<'_>,
+    trades: Option<Vec<Trade>>,
     iterations: usize,
-) -> PyResult<HashMap<String, f64>> {
-    let mut results = HashMap::new();
-    
-    // Benchmark CVD
-    let cvd_engine = CVDEngine::new();
-    let start = std::time::Instant::now();
-    
-    for _ in 0..iterations {
-        for trade in &trades {
-            let _ = cvd_engine.on_trade(trade);
-        }
+    n_events: usize,
+    n_symbols: usize,
+    base_price: f64,
+    tick_size: f64,
+    depth_levels: usize,
+    seed: u64,
+) -> PyResult<HashMap<String, HashMap<String, f64>>> {
+    if iterations == 0 || n_symbols == 0 || base_price.is_nan() || base_price <= 0.0
+        || tick_size.is_nan() || tick_size <= 0.0 {
+        return Err(ProcessError::invalid("iterations, n_symbols, base_price and tick_size must be > 0").into());
     }
+    let config = bench::SyntheticConfig {
+        n_events, n_symbols, base_price, tick_size, depth_levels, seed,
+        ..Default::default()
+    };
     
-    let cvd_duration = start.elapsed().as_secs_f64();
-    results.insert("cvd".to_string(), cvd_duration);
+    // Sin GIL: el benchmark puede tardar segundos
+    let results = py.allow_threads(|| {
+        let data = match trades {
+            Some(trades) => bench::SyntheticData::from_trades(trades, &config),
+            None => bench::SyntheticData::generate(&config),
+        };
+        bench::run_all(&data, iterations, tick_size)
+    });
     
-    Ok(results)
+    Ok(results.into_iter().map(|(name, stats)| (name, stats.to_map())).collect())
 }
 
 #[cfg(test)]
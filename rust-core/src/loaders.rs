@@ -0,0 +1,334 @@
+//! # Loaders
+//! 
+//! Carga de datos históricos desde Parquet y CSV (vía Polars) directamente a
+//! los tipos nativos, sin construir filas en Python:
+//! - `load_trades_*`: columnas ts, price, size, symbol y opcionales side, exchange,
+//!   trade_id
+//! - `load_bars_*`: columnas ts, open, high, low, close, volume, symbol y opcional tf
+//! - `load_books_*`: una fila por nivel (ts, symbol, side, price, size); las
+//!   filas con el mismo (symbol, ts) forman un snapshot, con bids de mayor a
+//!   menor precio y asks de menor a mayor
+//! 
+//! `columns` renombra campos a columnas del fichero ({"ts": "timestamp"}).
+//! Si no hay columna de símbolo se usa el parámetro `symbol`. El ts puede ser
+//! entero (ms), fecha o datetime de cualquier unidad.
+//! 
+//! La lectura y la conversión se hacen sin GIL (`allow_threads`). Desde
+//! Rust: `trades_from(&read_parquet(path)?, columns, symbol)` (ídem con
+//! `read_csv`, `bars_from` y `books_from`).
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use crate::types::{Bar, BookSnapshot, Level, Trade};
+use crate::errors::ProcessError;
+
+/// Formato de origen
+#[derive(Clone, Copy, Debug)]
+enum Source {
+    Csv { separator: u8 },
+    Parquet,
+}
+
+pub(crate) fn polars_err(e: PolarsError) -> ProcessError {
+    ProcessError::invalid(format!("Polars error: {}", e))
+}
+
+fn read_frame(path: &str, source: Source) -> Result<DataFrame, ProcessError> {
+    match source {
+        Source::Csv { separator } => CsvReadOptions::default()
+            .with_has_header(true)
+            .map_parse_options(|o| o.with_separator(separator))
+            .try_into_reader_with_file_path(Some(path.into()))
+            .map_err(|e| ProcessError::io(format!("cannot read {}: {}", path, e)))?
+            .finish()
+            .map_err(polars_err),
+        Source::Parquet => {
+            let file = File::open(path)
+                .map_err(|e| ProcessError::io(format!("cannot open {}: {}", path, e)))?;
+            ParquetReader::new(file).finish().map_err(polars_err)
+        }
+    }
+}
+
+/// DataFrame de un fichero Parquet
+pub fn read_parquet(path: &str) -> Result<DataFrame, ProcessError> {
+    read_frame(path, Source::Parquet)
+}
+
+/// DataFrame de un CSV con cabecera; `separator` de un byte
+pub fn read_csv(path: &str, separator: &str) -> Result<DataFrame, ProcessError> {
+    match separator.as_bytes() {
+        [b] => read_frame(path, Source::Csv { separator: *b }),
+        _ => Err(ProcessError::invalid("separator must be a single byte")),
+    }
+}
+
+/// Acceso a las columnas de un DataFrame con el mapeo de nombres aplicado
+struct Columns<'a> {
+    df: &'a DataFrame,
+    names: HashMap<String, String>,
+}
+
+impl<'a> Columns<'a> {
+    fn new(df: &'a DataFrame, names: Option<HashMap<String, String>>) -> Self {
+        Self { df, names: names.unwrap_or_default() }
+    }
+    
+    fn name<'b>(&'b self, field: &'b str) -> &'b str {
+        self.names.get(field).map(String::as_str).unwrap_or(field)
+    }
+    
+    fn optional(&self, field: &str) -> Option<&'a Series> {
+        self.df.column(self.name(field)).ok()
+    }
+    
+    fn required(&self, field: &str) -> Result<&'a Series, ProcessError> {
+        self.optional(field).ok_or_else(|| ProcessError::invalid(
+            format!("missing column {} (field {})", self.name(field), field)))
+    }
+    
+    /// Timestamps en ms; los temporales se convierten según su unidad
+    fn timestamps(&self, field: &str) -> Result<Vec<u64>, ProcessError> {
+        let series = self.required(field)?;
+        let (physical, scale): (Series, fn(i64) -> i64) = match series.dtype() {
+            DataType::Datetime(TimeUnit::Nanoseconds, _) => (series.to_physical_repr().into_owned(), |v| v / 1_000_000),
+            DataType::Datetime(TimeUnit::Microseconds, _) => (series.to_physical_repr().into_owned(), |v| v / 1_000),
+            DataType::Datetime(TimeUnit::Milliseconds, _) => (series.to_physical_repr().into_owned(), |v| v),
+            DataType::Date => (series.to_physical_repr().into_owned(), |v| v * 86_400_000),
+            _ => (series.clone(), |v| v),
+        };
+        let values = physical.cast(&DataType::Int64).map_err(polars_err)?;
+        values.i64().map_err(polars_err)?.into_iter().enumerate()
+            .map(|(row, v)| match v {
+                Some(v) if v >= 0 => Ok(scale(v) as u64),
+                _ => Err(self.invalid(field, row)),
+            })
+            .collect()
+    }
+    
+    fn floats(&self, field: &str) -> Result<Vec<f64>, ProcessError> {
+        let values = self.required(field)?.cast(&DataType::Float64).map_err(polars_err)?;
+        values.f64().map_err(polars_err)?.into_iter().enumerate()
+            .map(|(row, v)| v.ok_or_else(|| self.invalid(field, row)))
+            .collect()
+    }
+    
+    /// Columna de texto opcional; None si no existe
+    fn strings(&self, field: &str) -> Result<Option<Vec<Option<String>>>, ProcessError> {
+        self.optional(field).map(Self::to_strings).transpose()
+    }
+    
+    fn to_strings(series: &Series) -> Result<Vec<Option<String>>, ProcessError> {
+        let values = series.cast(&DataType::String).map_err(polars_err)?;
+        Ok(values.str().map_err(polars_err)?.into_iter().map(|v| v.map(str::to_string)).collect())
+    }
+    
+    /// Símbolo por fila: columna o, si no existe, el valor por defecto
+    fn symbols(&self, default: Option<&str>) -> Result<Vec<String>, ProcessError> {
+        let rows = self.df.height();
+        match (self.strings("symbol")?, default) {
+            (Some(values), _) => values.into_iter().enumerate()
+                .map(|(row, v)| v.or_else(|| default.map(str::to_string)).ok_or_else(|| self.invalid("symbol", row)))
+                .collect(),
+            (None, Some(symbol)) => Ok(vec![symbol.to_string(); rows]),
+            (None, None) => Err(ProcessError::invalid(
+                format!("missing column {} and no symbol given", self.name("symbol")))),
+        }
+    }
+    
+    fn invalid(&self, field: &str, row: usize) -> ProcessError {
+        ProcessError::invalid(format!("null or invalid {} at row {}", self.name(field), row))
+    }
+}
+
+pub fn trades_from(df: &DataFrame, columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> Result<Vec<Trade>, ProcessError> {
+    let c = Columns::new(df, columns);
+    let ts = c.timestamps("ts")?;
+    let price = c.floats("price")?;
+    let size = c.floats("size")?;
+    let symbols = c.symbols(symbol)?;
+    let mut side = c.strings("side")?.map(Vec::into_iter);
+    let mut exchange = c.strings("exchange")?.map(Vec::into_iter);
+    let mut trade_id = c.strings("trade_id")?.map(Vec::into_iter);
+    
+    Ok(symbols.into_iter().enumerate().map(|(i, symbol)| Trade {
+        ts: ts[i],
+        price: price[i],
+        size: size[i],
+        symbol,
+        side: side.as_mut().and_then(|s| s.next().flatten()),
+        exchange: exchange.as_mut().and_then(|e| e.next().flatten()),
+        trade_id: trade_id.as_mut().and_then(|id| id.next().flatten()),
+    }).collect())
+}
+
+pub fn bars_from(df: &DataFrame, columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> Result<Vec<Bar>, ProcessError> {
+    let c = Columns::new(df, columns);
+    let ts = c.timestamps("ts")?;
+    let open = c.floats("open")?;
+    let high = c.floats("high")?;
+    let low = c.floats("low")?;
+    let close = c.floats("close")?;
+    let volume = c.floats("volume")?;
+    let symbols = c.symbols(symbol)?;
+    let mut tf = c.strings("tf")?.map(Vec::into_iter);
+    
+    Ok(symbols.into_iter().enumerate().map(|(i, symbol)| {
+        let tf = tf.as_mut().and_then(|t| t.next().flatten()).unwrap_or_default();
+        Bar::new(ts[i], open[i], high[i], low[i], close[i], volume[i], tf, symbol)
+    }).collect())
+}
+
+pub fn books_from(df: &DataFrame, columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> Result<Vec<BookSnapshot>, ProcessError> {
+    let c = Columns::new(df, columns);
+    let ts = c.timestamps("ts")?;
+    let price = c.floats("price")?;
+    let size = c.floats("size")?;
+    let symbols = c.symbols(symbol)?;
+    let sides = Columns::to_strings(c.required("side")?)?;
+    
+    // Snapshots en orden de primera aparición de (symbol, ts)
+    let mut index: HashMap<(String, u64), usize> = HashMap::new();
+    let mut books: Vec<BookSnapshot> = Vec::new();
+    for (i, symbol) in symbols.into_iter().enumerate() {
+        let is_bid = match sides[i].as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("bid" | "b" | "buy") => true,
+            Some("ask" | "a" | "sell") => false,
+            _ => return Err(c.invalid("side", i)),
+        };
+        let slot = *index.entry((symbol.clone(), ts[i])).or_insert_with(|| {
+            books.push(BookSnapshot::new(ts[i], symbol, Vec::new(), Vec::new()));
+            books.len() - 1
+        });
+        let level = Level::new(price[i], size[i]);
+        if is_bid {
+            books[slot].bids.push(level);
+        } else {
+            books[slot].asks.push(level);
+        }
+    }
+    for book in &mut books {
+        book.bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+        book.asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+    }
+    Ok(books)
+}
+
+/// Trades desde Parquet
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (path, columns=None, symbol=None))]
+pub fn load_trades_parquet(py: Python<'_>, path: &str, columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> Result<Vec<Trade>, ProcessError> {
+    py.allow_threads(|| trades_from(&read_parquet(path)?, columns, symbol))
+}
+
+/// Trades desde CSV con cabecera
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (path, columns=None, symbol=None, separator=","))]
+pub fn load_trades_csv(py: Python<'_>, path: &str, columns: Option<HashMap<String, String>>, symbol: Option<&str>,
+                       separator: &str) -> Result<Vec<Trade>, ProcessError> {
+    py.allow_threads(|| trades_from(&read_csv(path, separator)?, columns, symbol))
+}
+
+/// Barras desde Parquet
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (path, columns=None, symbol=None))]
+pub fn load_bars_parquet(py: Python<'_>, path: &str, columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> Result<Vec<Bar>, ProcessError> {
+    py.allow_threads(|| bars_from(&read_parquet(path)?, columns, symbol))
+}
+
+/// Barras desde CSV con cabecera
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (path, columns=None, symbol=None, separator=","))]
+pub fn load_bars_csv(py: Python<'_>, path: &str, columns: Option<HashMap<String, String>>, symbol: Option<&str>,
+                     separator: &str) -> Result<Vec<Bar>, ProcessError> {
+    py.allow_threads(|| bars_from(&read_csv(path, separator)?, columns, symbol))
+}
+
+/// Snapshots de libro desde Parquet (una fila por nivel)
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (path, columns=None, symbol=None))]
+pub fn load_books_parquet(py: Python<'_>, path: &str, columns: Option<HashMap<String, String>>, symbol: Option<&str>) -> Result<Vec<BookSnapshot>, ProcessError> {
+    py.allow_threads(|| books_from(&read_parquet(path)?, columns, symbol))
+}
+
+/// Snapshots de libro desde CSV con cabecera (una fila por nivel)
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (path, columns=None, symbol=None, separator=","))]
+pub fn load_books_csv(py: Python<'_>, path: &str, columns: Option<HashMap<String, String>>, symbol: Option<&str>,
+                      separator: &str) -> Result<Vec<BookSnapshot>, ProcessError> {
+    py.allow_threads(|| books_from(&read_csv(path, separator)?, columns, symbol))
+}
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+    use crate::utils::with_py;
+    
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("indicators-{}-{}", std::process::id(), name)).to_string_lossy().to_string()
+    }
+    
+    #[test]
+    fn test_load_trades_csv_with_mapping() {
+        let path = temp_path("trades.csv");
+        std::fs::write(&path, "timestamp;px;qty;side\n1000;100.5;2;BUY\n2000;101;1;\n").unwrap();
+        
+        let columns = HashMap::from([
+            ("ts".to_string(), "timestamp".to_string()),
+            ("price".to_string(), "px".to_string()),
+            ("size".to_string(), "qty".to_string()),
+        ]);
+        let trades = with_py(|py| load_trades_csv(py, &path, Some(columns.clone()), Some("AAPL"), ";")).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!((trades[0].ts, trades[0].price, trades[0].size), (1000, 100.5, 2.0));
+        assert_eq!(trades[0].side.as_deref(), Some("BUY"));
+        assert_eq!(trades[1].side, None);
+        assert_eq!(trades[1].symbol, "AAPL");
+        
+        // Sin símbolo ni columna, o sin la columna de precio, falla
+        assert!(with_py(|py| load_trades_csv(py, &path, Some(columns), None, ";")).is_err());
+        assert!(with_py(|py| load_trades_csv(py, &path, None, Some("AAPL"), ";")).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[test]
+    fn test_load_parquet_datetime_and_books() {
+        let path = temp_path("bars.parquet");
+        let ts = Series::new("ts", &[60_000_000_000i64, 120_000_000_000])
+            .cast(&DataType::Datetime(TimeUnit::Nanoseconds, None)).unwrap();
+        let mut df = DataFrame::new(vec![
+            ts,
+            Series::new("open", &[1.0, 2.0]),
+            Series::new("high", &[2.0, 3.0]),
+            Series::new("low", &[0.5, 1.5]),
+            Series::new("close", &[1.5, 2.5]),
+            Series::new("volume", &[10.0, 20.0]),
+            Series::new("symbol", &["ES", "ES"]),
+        ]).unwrap();
+        ParquetWriter::new(File::create(&path).unwrap()).finish(&mut df).unwrap();
+        
+        let bars = with_py(|py| load_bars_parquet(py, &path, None, None)).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[1].ts, 120_000);
+        assert_eq!(bars[1].close, 2.5);
+        let _ = std::fs::remove_file(&path);
+        
+        let path = temp_path("books.csv");
+        std::fs::write(&path, "ts,symbol,side,price,size\n1000,ES,bid,99,1\n1000,ES,bid,100,2\n1000,ES,ask,101,3\n2000,ES,ask,102,1\n").unwrap();
+        let books = with_py(|py| load_books_csv(py, &path, None, None, ",")).unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(books[0].bids.iter().map(|l| l.price).collect::<Vec<_>>(), vec![100.0, 99.0]);
+        assert_eq!(books[0].asks.len(), 1);
+        assert!(books[1].bids.is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
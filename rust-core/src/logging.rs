@@ -0,0 +1,100 @@
+//! # Logging
+//! 
+//! Inicialización de `tracing` desde Python. El núcleo emite:
+//! - span `nats_message` (debug) por mensaje recibido, con subject y secuencia
+//! - span `engine` (trace) por evento e indicador en el `EngineManager`
+//! - eventos `warn` con cada error registrado por suscriptores y publishers
+//! - eventos `info` con los cambios de estado de la conexión y `debug` con
+//!   las alertas de los engines (liquidez, large prints, cascadas)
+//! 
+//! El nivel admite directivas de `EnvFilter` ("info",
+//! "indicators_core=debug,async_nats=warn") y se puede cambiar en caliente
+//! con `set_log_level`.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fs::OpenOptions;
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+use crate::errors::ProcessError;
+
+/// Filtro activo, para cambiar el nivel tras inicializar
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+fn parse_filter(level: &str) -> Result<EnvFilter, ProcessError> {
+    EnvFilter::try_new(level).map_err(|e| ProcessError::invalid(
+        format!("invalid log level {}: {}", level, e)))
+}
+
+/// Instala el subscriber global de tracing: JSON (una línea por evento) o
+/// texto, en stderr o anexando a `path`. Solo puede llamarse una vez.
+#[cfg_attr(feature = "python", pyfunction)]
+#[cfg_attr(feature = "python", pyo3(signature = (level="info", json=true, path=None)))]
+pub fn init_logging(level: &str, json: bool, path: Option<String>) -> Result<(), ProcessError> {
+    let (filter, handle) = reload::Layer::new(parse_filter(level)?);
+    let writer = match path {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(&path)
+                .map_err(|e| ProcessError::io(format!("cannot open {}: {}", path, e)))?;
+            BoxMakeWriter::new(Mutex::new(file))
+        }
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let format = tracing_subscriber::fmt::layer().with_writer(writer).with_thread_names(true);
+    let format = if json {
+        format.json().with_current_span(true).with_span_list(true).boxed()
+    } else {
+        format.with_ansi(false).boxed()
+    };
+    
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(format)
+        .try_init()
+        .map_err(|_| ProcessError::runtime("logging already initialized"))?;
+    let _ = FILTER.set(handle);
+    Ok(())
+}
+
+/// Cambia el nivel del subscriber instalado con `init_logging`
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn set_log_level(level: &str) -> Result<(), ProcessError> {
+    let handle = FILTER.get().ok_or_else(|| ProcessError::runtime("logging not initialized"))?;
+    handle.reload(parse_filter(level)?)
+        .map_err(|e| ProcessError::runtime(format!("cannot set log level: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_init_logging() {
+        assert!(init_logging("not a level[", true, None).is_err());
+        assert!(set_log_level("debug").is_err());
+        
+        let path = std::env::temp_dir().join(format!("indicators-log-{}.json", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        init_logging("info", true, Some(path_str.clone())).unwrap();
+        assert!(init_logging("info", true, None).is_err());
+        
+        tracing::debug!("hidden");
+        set_log_level("debug").unwrap();
+        {
+            let _span = tracing::debug_span!("nats_message", subject = "md.trades").entered();
+            tracing::debug!(seq = 7, "visible");
+        }
+        
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(!text.contains("hidden"));
+        // Otros tests del proceso también pueden escribir en el subscriber global
+        let line = text.lines().find(|l| l.contains("visible")).unwrap();
+        let line: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(line["fields"]["message"], "visible");
+        assert_eq!(line["span"]["subject"], "md.trades");
+    }
+}
@@ -0,0 +1,1057 @@
+//! # Engine Manager
+//! 
+//! Punto de entrada único para un stream de eventos: cada `on_trade`,
+//! `on_snapshot` u `on_bar` se despacha a todos los indicadores registrados y
+//! devuelve un `MetricsBundle` con el resultado de cada uno, evitando una
+//! llamada FFI por engine desde Python.
+//! 
+//! Los indicadores implementan el trait `Indicator` y se registran por nombre;
+//! cada uno puede desactivarse globalmente o solo para algunos símbolos. Tras
+//! despachar un evento, el bundle completo pasa por `Indicator::on_bundle`
+//! para los indicadores derivados (`FlowSignalEngine`).
+//! 
+//! Con `on_metrics(callback, throttle_ms)` cada bundle no vacío se entrega
+//! además a un callable de Python, opcionalmente limitado a uno cada
+//! `throttle_ms` por símbolo. Desde Rust, `add_sink` registra destinos que
+//! reciben todos los bundles no vacíos sin pasar por el GIL (publisher del
+//! daemon, servidor gRPC).
+//! 
+//! `render_metrics()` expone en formato Prometheus los eventos procesados,
+//! la latencia de cada indicador y cuántos símbolos tienen estado en cada uno.
+//! 
+//! `process_batch_parallel(trades)` reparte un batch con varios símbolos en
+//! particiones por símbolo y las procesa en un pool de Rayon (`threads`):
+//! - los trades de un mismo símbolo se procesan en orden de llegada
+//! - entre símbolos distintos no hay orden; el estado de los engines va por
+//!   símbolo, así que el resultado coincide con el procesamiento secuencial
+//! - los bundles se devuelven (y se entregan al callback) en el orden del batch
+//! 
+//! Ciclo de vida de los símbolos: con `symbol_ttl_ms` el estado de un símbolo
+//! se elimina de todos los indicadores cuando pasa ese tiempo (en ts de los
+//! eventos) sin recibir ninguno. La barrida se hace al despachar, como mucho
+//! una vez cada `ttl / 4`; con el stream parado `evict_expired(now_ts)` la
+//! fuerza con el reloj que se indique.
+//! 
+//! Los trades con `trade_id` pasan por una caché acotada por símbolo
+//! (`dedup_capacity`, 0 la desactiva): las retransmisiones del exchange
+//! devuelven un bundle vacío y se cuentan en `get_duplicates()`.
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "python")]
+use std::time::Duration;
+use std::time::Instant;
+use crate::config::ConfigRegistry;
+use crate::dedup::TradeDeduplicator;
+use crate::indicators::{
+    ATREngine, CVDEngine, HeatmapEngine, Indicator, IndicatorOutput, LiquidityEngine, MovingAverageEngine, RSIEngine,
+    VWAPEngine,
+};
+#[cfg(feature = "python")]
+use crate::indicators::{
+    AbsorptionDetector, BookPressureEngine, CVDBarEngine, CorrelationEngine, FlowSignalEngine, FootprintEngine,
+    GapDetector, IcebergDetector, LiquidationEngine, OIEngine, QuoteEngine, RollingVWAPEngine, SpreadEngine,
+    SweepDetector, TapeEngine, TradeEnricher, VPINEngine, VolatilityEngine, VolumeProfileEngine, WallTracker,
+};
+use crate::telemetry::{label_value, write_header, write_sample, Histogram, RateGauge};
+use crate::types::{Bar, BookSnapshot, MetricsBundle, Quote, Trade};
+use crate::utils::sketch::validate_quantiles;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Indicador registrado con su estado de activación
+struct Registered {
+    name: String,
+    indicator: Box<dyn Indicator>,
+    enabled: bool,
+    disabled_symbols: HashSet<String>,
+    // Tiempo de procesamiento de cada evento en este indicador
+    latency: Histogram,
+}
+
+impl Registered {
+    fn is_enabled(&self, symbol: &str) -> bool {
+        self.enabled && !self.disabled_symbols.contains(symbol)
+    }
+}
+
+/// Limita las notificaciones a una cada `interval` por símbolo; los
+/// bundles intermedios se descartan
+#[cfg(feature = "python")]
+#[derive(Default)]
+struct Throttle {
+    interval: Option<Duration>,
+    last: HashMap<String, Instant>,
+}
+
+#[cfg(feature = "python")]
+impl Throttle {
+    fn new(throttle_ms: Option<u64>) -> Self {
+        Self { interval: throttle_ms.filter(|&ms| ms > 0).map(Duration::from_millis), last: HashMap::new() }
+    }
+    
+    fn allow(&mut self, symbol: &str, now: Instant) -> bool {
+        let Some(interval) = self.interval else { return true };
+        match self.last.get_mut(symbol) {
+            Some(last) if now.duration_since(*last) < interval => false,
+            Some(last) => {
+                *last = now;
+                true
+            }
+            None => {
+                self.last.insert(symbol.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+/// Destino Rust de los bundles (p. ej. un publisher)
+pub type MetricsSink = Arc<dyn Fn(&MetricsBundle) + Send + Sync>;
+
+/// Callable de Python registrado con `on_metrics`
+#[cfg(feature = "python")]
+struct MetricsCallback {
+    callback: Arc<PyObject>,
+    throttle: Throttle,
+}
+
+py_class! {
+/// Manager que posee los indicadores y reparte los eventos entre ellos
+#[pyclass]
+pub struct EngineManager {
+    // En orden de registro, que es el orden de despacho
+    indicators: Vec<Registered>,
+    // Indicadores activos y parámetros por símbolo
+    config: Option<ConfigRegistry>,
+    // Notificación de cada bundle a Python y a Rust
+    #[cfg(feature = "python")]
+    callback: Mutex<Option<MetricsCallback>>,
+    sinks: Mutex<Vec<(u64, MetricsSink)>>,
+    next_sink: AtomicU64,
+    // Telemetría: eventos despachados y símbolos vistos desde el último reset
+    events: AtomicU64,
+    events_rate: RateGauge,
+    // Último ts de evento por símbolo
+    symbols: DashMap<String, u64>,
+    // TTL de los símbolos sin eventos; reloj (máximo ts visto), próxima
+    // barrida y símbolos desalojados
+    symbol_ttl_ms: Option<u64>,
+    clock: AtomicU64,
+    next_sweep: AtomicU64,
+    evicted: AtomicU64,
+    // Pool de process_batch_parallel (None = pool global de Rayon)
+    pool: Option<Arc<rayon::ThreadPool>>,
+    // Trade ids vistos por símbolo y duplicados descartados
+    dedup: TradeDeduplicator,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl EngineManager {
+    /// Los engines de trades y libro se activan con flags; los de barras
+    /// se activan pasando sus periodos.
+    #[new]
+    #[pyo3(signature = (cvd=true, vwap=true, liquidity=true, heatmap=true, ma_periods=None, rsi_periods=None, atr_periods=None))]
+    pub fn new(
+        cvd: bool,
+        vwap: bool,
+        liquidity: bool,
+        heatmap: bool,
+        ma_periods: Option<Vec<usize>>,
+        rsi_periods: Option<Vec<usize>>,
+        atr_periods: Option<Vec<usize>>,
+    ) -> Result<Self, ProcessError> {
+        let mut manager = Self {
+            indicators: Vec::new(),
+            config: None,
+            #[cfg(feature = "python")]
+            callback: Mutex::new(None),
+            sinks: Mutex::new(Vec::new()),
+            next_sink: AtomicU64::new(0),
+            events: AtomicU64::new(0),
+            events_rate: RateGauge::default(),
+            symbols: DashMap::new(),
+            symbol_ttl_ms: None,
+            clock: AtomicU64::new(0),
+            next_sweep: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+            pool: None,
+            dedup: TradeDeduplicator::default(),
+        };
+        if cvd {
+            manager.register("cvd", Box::new(CVDEngine::new()))?;
+        }
+        if vwap {
+            manager.register("vwap", Box::new(VWAPEngine::new()))?;
+        }
+        if liquidity {
+            manager.register("liquidity", Box::new(LiquidityEngine::new()))?;
+        }
+        if heatmap {
+            manager.register("heatmap", Box::new(HeatmapEngine::default()))?;
+        }
+        if let Some(periods) = ma_periods {
+            manager.register("moving_averages", Box::new(MovingAverageEngine::new(periods)?))?;
+        }
+        if let Some(periods) = rsi_periods {
+            manager.register("rsi", Box::new(RSIEngine::new(periods)?))?;
+        }
+        if let Some(periods) = atr_periods {
+            manager.register("atr", Box::new(ATREngine::new(periods)?))?;
+        }
+        Ok(manager)
+    }
+    
+    /// Nombres de los indicadores registrados, en orden de despacho
+    pub fn engines(&self) -> Vec<String> {
+        self.indicators.iter().map(|r| r.name.clone()).collect()
+    }
+    
+    /// Registra con `name` un engine creado desde Python (CVDEngine,
+    /// HeatmapEngine, ...). El manager despacha a una copia que comparte el
+    /// estado con `engine`: sus getters ven lo que procesa el manager, pero los
+    /// cambios de configuración posteriores no se propagan.
+    #[cfg(feature = "python")]
+    #[pyo3(name = "register")]
+    pub fn py_register(&mut self, name: &str, engine: &Bound<'_, PyAny>) -> PyResult<()> {
+        Ok(self.register(name, indicator_from_py(engine)?)?)
+    }
+    
+    /// Elimina un indicador; devuelve si existía
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.indicators.len();
+        self.indicators.retain(|r| r.name != name);
+        self.indicators.len() != before
+    }
+    
+    /// Activa un indicador globalmente o solo para un símbolo
+    #[pyo3(signature = (name, symbol=None))]
+    pub fn enable(&mut self, name: &str, symbol: Option<&str>) -> Result<(), ProcessError> {
+        let entry = self.entry_mut(name)?;
+        match symbol {
+            Some(symbol) => { entry.disabled_symbols.remove(symbol); }
+            None => entry.enabled = true,
+        }
+        Ok(())
+    }
+    
+    /// Desactiva un indicador globalmente o solo para un símbolo
+    #[pyo3(signature = (name, symbol=None))]
+    pub fn disable(&mut self, name: &str, symbol: Option<&str>) -> Result<(), ProcessError> {
+        let entry = self.entry_mut(name)?;
+        match symbol {
+            Some(symbol) => { entry.disabled_symbols.insert(symbol.to_string()); }
+            None => entry.enabled = false,
+        }
+        Ok(())
+    }
+    
+    /// Indica si el indicador procesa eventos del símbolo
+    pub fn is_enabled(&self, name: &str, symbol: &str) -> bool {
+        self.indicators.iter().any(|r| r.name == name && self.dispatches(r, symbol))
+    }
+    
+    /// Configuración por símbolo compartida con los indicadores registrados
+    #[getter]
+    pub fn config(&self) -> Option<ConfigRegistry> {
+        self.config.clone()
+    }
+    
+    /// Aplica la configuración a todos los indicadores; los que se registren
+    /// después la reciben al registrarse
+    #[setter]
+    pub fn set_config(&mut self, config: Option<ConfigRegistry>) {
+        for r in &mut self.indicators {
+            r.indicator.configure(config.as_ref());
+        }
+        self.config = config;
+    }
+    
+    /// Registra un callable que recibe cada `MetricsBundle` no vacío (None lo
+    /// quita). Con `throttle_ms` se llama como mucho una vez cada N ms por símbolo.
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (callback=None, throttle_ms=None))]
+    pub fn on_metrics(&self, callback: Option<PyObject>, throttle_ms: Option<u64>) {
+        *self.callback.lock() = callback.map(|callback| MetricsCallback { callback: Arc::new(callback), throttle: Throttle::new(throttle_ms) });
+    }
+    
+    /// Actualiza la quote vigente de los indicadores que clasifican trades
+    pub fn on_quote(&self, quote: &Quote) {
+        for r in self.active(&quote.symbol) {
+            r.indicator.on_quote(quote);
+        }
+    }
+    
+    /// Despacha un trade a todos los indicadores activos
+    pub fn on_trade(&self, trade: &Trade) -> MetricsBundle {
+        let bundle = self.dispatch_trade(trade);
+        self.emit(&bundle);
+        bundle
+    }
+    
+    /// Procesa un batch de trades de varios símbolos en paralelo, sin GIL.
+    /// Cada símbolo se procesa en orden; los bundles vuelven en el orden del batch.
+    #[cfg(feature = "python")]
+    pub fn process_batch_parallel(&self, py: Python<'_>, trades: Vec<Trade>) -> Vec<MetricsBundle> {
+        let bundles = py.allow_threads(|| self.dispatch_parallel(&trades));
+        for bundle in &bundles {
+            self.emit(bundle);
+        }
+        bundles
+    }
+    
+    /// Hilos usados por `process_batch_parallel`
+    #[getter]
+    pub fn threads(&self) -> usize {
+        self.pool.as_ref().map_or_else(rayon::current_num_threads, |pool| pool.current_num_threads())
+    }
+    
+    /// Fija los hilos de `process_batch_parallel`; None (o 0) usa el pool global de Rayon
+    #[setter]
+    pub fn set_threads(&mut self, threads: Option<usize>) -> Result<(), ProcessError> {
+        self.pool = match threads.filter(|&n| n > 0) {
+            Some(n) => Some(Arc::new(rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .thread_name(|i| format!("indicators-batch-{}", i))
+                .build()
+                .map_err(|e| ProcessError::runtime(e.to_string()))?)),
+            None => None,
+        };
+        Ok(())
+    }
+    
+    /// Trade ids recordados por símbolo para descartar retransmisiones
+    #[getter]
+    pub fn dedup_capacity(&self) -> usize {
+        self.dedup.capacity()
+    }
+    
+    /// 0 desactiva la deduplicación
+    #[setter]
+    pub fn set_dedup_capacity(&mut self, capacity: usize) {
+        self.dedup.set_capacity(capacity);
+    }
+    
+    /// Trades duplicados descartados por símbolo
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_duplicates(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.dedup.duplicates(symbol)
+    }
+    
+    /// Despacha un snapshot del libro a todos los indicadores activos
+    pub fn on_snapshot(&self, snapshot: &BookSnapshot) -> MetricsBundle {
+        self.record_event(&snapshot.symbol, snapshot.ts);
+        let mut bundle = MetricsBundle::empty(&snapshot.symbol, snapshot.ts);
+        for r in self.active(&snapshot.symbol) {
+            let _span = tracing::trace_span!("engine", engine = %r.name, symbol = %snapshot.symbol).entered();
+            let start = Instant::now();
+            let outputs = r.indicator.on_snapshot(snapshot);
+            r.latency.observe(start.elapsed());
+            outputs.into_iter().for_each(|o| bundle.push(o));
+        }
+        self.derive(&mut bundle);
+        self.emit(&bundle);
+        bundle
+    }
+    
+    /// Despacha una barra cerrada a todos los indicadores activos
+    pub fn on_bar(&self, bar: &Bar) -> MetricsBundle {
+        self.record_event(&bar.symbol, bar.ts);
+        let mut bundle = MetricsBundle::empty(&bar.symbol, bar.ts);
+        for r in self.active(&bar.symbol) {
+            let _span = tracing::trace_span!("engine", engine = %r.name, symbol = %bar.symbol).entered();
+            let start = Instant::now();
+            let outputs = r.indicator.on_bar(bar);
+            r.latency.observe(start.elapsed());
+            outputs.into_iter().for_each(|o| bundle.push(o));
+        }
+        self.derive(&mut bundle);
+        self.emit(&bundle);
+        bundle
+    }
+    
+    /// Estado actual del símbolo por indicador (solo los que tienen valores)
+    pub fn snapshot_state(&self, symbol: &str) -> HashMap<String, HashMap<String, f64>> {
+        self.indicators.iter()
+            .map(|r| (r.name.clone(), r.indicator.snapshot_state(symbol)))
+            .filter(|(_, state)| !state.is_empty())
+            .collect()
+    }
+    
+    /// Resetea el estado de un símbolo en todos los indicadores
+    pub fn reset_symbol(&self, symbol: &str) {
+        for r in &self.indicators {
+            r.indicator.reset_symbol(symbol);
+        }
+        self.symbols.remove(symbol);
+        self.dedup.reset_symbol(symbol);
+    }
+    
+    /// Resetea todos los indicadores
+    pub fn reset_all(&self) {
+        for r in &self.indicators {
+            r.indicator.reset();
+        }
+        self.symbols.clear();
+        self.dedup.reset_all();
+    }
+    
+    /// Tiempo sin eventos tras el que se desaloja un símbolo (None = nunca)
+    #[getter]
+    pub fn symbol_ttl_ms(&self) -> Option<u64> {
+        self.symbol_ttl_ms
+    }
+    
+    #[setter]
+    pub fn set_symbol_ttl_ms(&mut self, ttl_ms: Option<u64>) -> Result<(), ProcessError> {
+        if ttl_ms == Some(0) {
+            return Err(ProcessError::invalid("symbol_ttl_ms must be > 0"));
+        }
+        self.symbol_ttl_ms = ttl_ms;
+        self.next_sweep.store(0, Ordering::Relaxed);
+        Ok(())
+    }
+    
+    /// Símbolos con eventos desde el último reset o desalojo, ordenados
+    pub fn active_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.symbols.iter().map(|e| e.key().clone()).collect();
+        symbols.sort_unstable();
+        symbols
+    }
+    
+    /// Último ts de evento del símbolo
+    pub fn last_event_ts(&self, symbol: &str) -> Option<u64> {
+        self.symbols.get(symbol).map(|ts| *ts)
+    }
+    
+    /// Elimina el estado del símbolo en todos los indicadores; devuelve si estaba activo
+    pub fn evict(&self, symbol: &str) -> bool {
+        let active = self.symbols.contains_key(symbol);
+        self.reset_symbol(symbol);
+        if active {
+            self.evicted.fetch_add(1, Ordering::Relaxed);
+        }
+        active
+    }
+    
+    /// Desaloja los símbolos sin eventos en el último `symbol_ttl_ms` respecto
+    /// a `now_ts` (por defecto el máximo ts visto); devuelve los desalojados
+    #[pyo3(signature = (now_ts=None))]
+    pub fn evict_expired(&self, now_ts: Option<u64>) -> Vec<String> {
+        let Some(ttl) = self.symbol_ttl_ms else { return Vec::new() };
+        let now = now_ts.unwrap_or_else(|| self.clock.load(Ordering::Relaxed));
+        let mut expired: Vec<String> = self.symbols.iter()
+            .filter(|e| e.value().saturating_add(ttl) < now)
+            .map(|e| e.key().clone())
+            .collect();
+        expired.sort_unstable();
+        expired.retain(|symbol| self.evict(symbol));
+        expired
+    }
+    
+    /// Entradas (indicador, símbolo) con estado, sumadas en todos los indicadores
+    pub fn state_size(&self) -> usize {
+        self.state_sizes().values().sum()
+    }
+    
+    /// Símbolos con estado por indicador
+    pub fn state_sizes(&self) -> HashMap<String, usize> {
+        self.indicators.iter()
+            .map(|r| (r.name.clone(), r.indicator.state_len()))
+            .collect()
+    }
+    
+    /// Cuantiles de latencia por evento de cada indicador, en segundos
+    /// (por defecto p50, p95 y p99; None sin eventos)
+    #[pyo3(signature = (quantiles=vec![0.5, 0.95, 0.99]))]
+    pub fn get_latency_quantiles(&self, quantiles: Vec<f64>) -> Result<HashMap<String, Vec<Option<f64>>>, ProcessError> {
+        validate_quantiles(&quantiles)?;
+        Ok(self.indicators.iter()
+            .map(|r| (r.name.clone(), quantiles.iter().map(|&q| r.latency.quantile(q)).collect()))
+            .collect())
+    }
+    
+    /// Métricas operativas en formato de texto de Prometheus
+    pub fn render_metrics(&self) -> String {
+        let mut out = String::new();
+        self.write_metrics(&mut out);
+        out
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("EngineManager(engines={:?})", self.engines())
+    }
+}
+}
+
+impl EngineManager {
+    /// Despacha un trade sin notificar al callback; los duplicados devuelven
+    /// un bundle vacío
+    fn dispatch_trade(&self, trade: &Trade) -> MetricsBundle {
+        if self.dedup.is_duplicate(trade) {
+            return MetricsBundle::empty(&trade.symbol, trade.ts);
+        }
+        self.record_event(&trade.symbol, trade.ts);
+        let mut bundle = MetricsBundle::empty(&trade.symbol, trade.ts);
+        for r in self.active(&trade.symbol) {
+            let _span = tracing::trace_span!("engine", engine = %r.name, symbol = %trade.symbol).entered();
+            let start = Instant::now();
+            let outputs = r.indicator.on_trade(trade);
+            r.latency.observe(start.elapsed());
+            outputs.into_iter().for_each(|o| bundle.push(o));
+        }
+        self.derive(&mut bundle);
+        bundle
+    }
+    
+    /// Particiona por símbolo (conservando el orden de llegada) y procesa las
+    /// particiones en paralelo; devuelve los bundles en el orden de `trades`
+    pub fn dispatch_parallel(&self, trades: &[Trade]) -> Vec<MetricsBundle> {
+        let mut partitions: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, trade) in trades.iter().enumerate() {
+            partitions.entry(trade.symbol.as_str()).or_default().push(i);
+        }
+        let run = || {
+            let mut bundles: Vec<(usize, MetricsBundle)> = partitions.into_par_iter()
+                .flat_map_iter(|(_, rows)| rows.into_iter().map(|i| (i, self.dispatch_trade(&trades[i]))))
+                .collect();
+            bundles.sort_unstable_by_key(|(i, _)| *i);
+            bundles.into_iter().map(|(_, bundle)| bundle).collect()
+        };
+        match &self.pool {
+            Some(pool) => pool.install(run),
+            None => run(),
+        }
+    }
+    
+    /// Pasa el bundle ya completo a los indicadores derivados (`on_bundle`)
+    /// y añade sus salidas
+    fn derive(&self, bundle: &mut MetricsBundle) {
+        if bundle.is_empty() {
+            return;
+        }
+        let outputs: Vec<IndicatorOutput> = self.active(&bundle.symbol)
+            .flat_map(|r| r.indicator.on_bundle(bundle))
+            .collect();
+        outputs.into_iter().for_each(|o| bundle.push(o));
+    }
+    
+    fn record_event(&self, symbol: &str, ts: u64) {
+        self.events.fetch_add(1, Ordering::Relaxed);
+        match self.symbols.get_mut(symbol) {
+            Some(mut last) => *last = (*last).max(ts),
+            None => { self.symbols.insert(symbol.to_string(), ts); }
+        }
+        let now = self.clock.fetch_max(ts, Ordering::Relaxed).max(ts);
+        self.sweep_expired(now);
+    }
+    
+    /// Barrida del TTL, como mucho una vez cada ttl / 4 de tiempo de eventos
+    fn sweep_expired(&self, now: u64) {
+        let Some(ttl) = self.symbol_ttl_ms else { return };
+        let next = self.next_sweep.load(Ordering::Relaxed);
+        if now < next {
+            return;
+        }
+        // Solo un hilo hace la barrida
+        let interval = (ttl / 4).max(1);
+        if self.next_sweep.compare_exchange(next, now + interval, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            let expired = self.evict_expired(Some(now));
+            if !expired.is_empty() {
+                tracing::debug!(symbols = ?expired, "evicted idle symbols");
+            }
+        }
+    }
+    
+    /// Añade las métricas del manager a `out` (también las usa el suscriptor)
+    pub fn write_metrics(&self, out: &mut String) {
+        let events = self.events.load(Ordering::Relaxed);
+        write_header(out, "indicators_events_total", "Events dispatched to the indicators", "counter");
+        write_sample(out, "indicators_events_total", "", events as f64);
+        write_header(out, "indicators_events_per_second", "Events per second since the previous scrape", "gauge");
+        write_sample(out, "indicators_events_per_second", "", self.events_rate.rate(events, Instant::now()));
+        write_header(out, "indicators_symbols", "Symbols seen since the last reset", "gauge");
+        write_sample(out, "indicators_symbols", "", self.symbols.len() as f64);
+        write_header(out, "indicators_symbols_evicted_total", "Symbols evicted by TTL or evict()", "counter");
+        write_sample(out, "indicators_symbols_evicted_total", "", self.evicted.load(Ordering::Relaxed) as f64);
+        write_header(out, "indicators_duplicate_trades_total", "Retransmitted trades dropped by trade id", "counter");
+        for (symbol, count) in self.dedup.duplicates(None) {
+            write_sample(out, "indicators_duplicate_trades_total", &format!("symbol=\"{}\"", label_value(&symbol)), count as f64);
+        }
+        
+        write_header(out, "indicators_engine_latency_seconds", "Processing time per event and indicator", "histogram");
+        for r in &self.indicators {
+            r.latency.render(out, "indicators_engine_latency_seconds", &format!("engine=\"{}\"", label_value(&r.name)));
+        }
+        write_header(out, "indicators_engine_latency_quantile_seconds", "Processing time quantiles per indicator (1% relative error)", "gauge");
+        for r in &self.indicators {
+            r.latency.render_quantiles(out, "indicators_engine_latency_quantile_seconds", &format!("engine=\"{}\"", label_value(&r.name)));
+        }
+        
+        // Símbolos con estado no vacío en cada indicador
+        write_header(out, "indicators_engine_symbols", "Symbols with state per indicator", "gauge");
+        for r in &self.indicators {
+            write_sample(out, "indicators_engine_symbols", &format!("engine=\"{}\"", label_value(&r.name)), r.indicator.state_len() as f64);
+        }
+    }
+    
+    /// Entrega el bundle a los sinks y al callback registrado; los errores
+    /// del callback se imprimen y no interrumpen el despacho
+    fn emit(&self, bundle: &MetricsBundle) {
+        if bundle.is_empty() {
+            return;
+        }
+        // Copia de la lista: un sink puede registrar o quitar otros
+        let sinks: Vec<MetricsSink> = self.sinks.lock().iter().map(|(_, sink)| sink.clone()).collect();
+        for sink in sinks {
+            sink(bundle);
+        }
+        #[cfg(feature = "python")]
+        self.notify_callback(bundle);
+    }
+    
+    #[cfg(feature = "python")]
+    fn notify_callback(&self, bundle: &MetricsBundle) {
+        let callback = {
+            let mut guard = self.callback.lock();
+            let Some(registered) = guard.as_mut() else { return };
+            if !registered.throttle.allow(&bundle.symbol, Instant::now()) {
+                return;
+            }
+            registered.callback.clone()
+        };
+        // Tomar el GIL fuera del lock (otro hilo puede tener el GIL y esperar
+        // el lock); además el callback puede volver a llamar a on_metrics
+        Python::with_gil(|py| {
+            if let Err(e) = callback.call1(py, (bundle.clone(),)) {
+                e.print(py);
+            }
+        });
+    }
+    
+    /// Registra un destino Rust de los bundles no vacíos; devuelve su id
+    pub fn add_sink(&self, sink: MetricsSink) -> u64 {
+        let id = self.next_sink.fetch_add(1, Ordering::Relaxed);
+        self.sinks.lock().push((id, sink));
+        id
+    }
+    
+    /// Quita un destino registrado con `add_sink`; devuelve si existía
+    pub fn remove_sink(&self, id: u64) -> bool {
+        let mut sinks = self.sinks.lock();
+        let before = sinks.len();
+        sinks.retain(|(sink_id, _)| *sink_id != id);
+        sinks.len() != before
+    }
+    
+    /// Registra un indicador con un nombre único; se despacha tras los ya registrados
+    pub fn register(&mut self, name: &str, mut indicator: Box<dyn Indicator>) -> Result<(), ProcessError> {
+        if self.indicators.iter().any(|r| r.name == name) {
+            return Err(ProcessError::invalid(format!("indicator already registered: {}", name)));
+        }
+        if self.config.is_some() {
+            indicator.configure(self.config.as_ref());
+        }
+        self.indicators.push(Registered {
+            name: name.to_string(),
+            indicator,
+            enabled: true,
+            disabled_symbols: HashSet::new(),
+            latency: Histogram::default(),
+        });
+        Ok(())
+    }
+    
+    /// Acceso a un indicador registrado
+    pub fn get(&self, name: &str) -> Option<&dyn Indicator> {
+        self.indicators.iter().find(|r| r.name == name).map(|r| r.indicator.as_ref())
+    }
+    
+    fn entry_mut(&mut self, name: &str) -> Result<&mut Registered, ProcessError> {
+        self.indicators.iter_mut().find(|r| r.name == name).ok_or_else(|| {
+            ProcessError::not_found(format!("unknown indicator: {}", name))
+        })
+    }
+    
+    /// Activo en el manager y en la lista de indicadores del símbolo (si la hay)
+    fn dispatches(&self, r: &Registered, symbol: &str) -> bool {
+        r.is_enabled(symbol) && self.config.as_ref().is_none_or(|c| c.is_indicator_enabled(symbol, &r.name))
+    }
+    
+    fn active<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = &'a Registered> + 'a {
+        self.indicators.iter().filter(move |r| self.dispatches(r, symbol))
+    }
+}
+
+/// Copia registrable (con el estado compartido) de un engine de Python
+#[cfg(feature = "python")]
+fn indicator_from_py(engine: &Bound<'_, PyAny>) -> PyResult<Box<dyn Indicator>> {
+    macro_rules! downcast_engine {
+        ($($engine:ty),* $(,)?) => {
+            $(
+                if let Ok(engine) = engine.downcast::<$engine>() {
+                    return Ok(Box::new(engine.borrow().clone()));
+                }
+            )*
+        };
+    }
+    downcast_engine!(
+        CVDEngine, CVDBarEngine, FootprintEngine, VWAPEngine, RollingVWAPEngine, VolumeProfileEngine,
+        LiquidityEngine, HeatmapEngine, BookPressureEngine, IcebergDetector, MovingAverageEngine, RSIEngine,
+        ATREngine, VolatilityEngine, TapeEngine, VPINEngine, QuoteEngine, OIEngine, LiquidationEngine,
+        TradeEnricher, SweepDetector, AbsorptionDetector, WallTracker, SpreadEngine, CorrelationEngine,
+        GapDetector, FlowSignalEngine,
+    );
+    Err(pyo3::exceptions::PyTypeError::new_err(format!(
+        "not an indicator engine: {}", engine.get_type().name()?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::{FlowSignalEngine, SweepDetector, TapeEngine, TradeEnricher};
+    #[cfg(feature = "python")]
+    use crate::utils::with_py;
+    use crate::types::Level;
+
+    fn trade(symbol: &str) -> Trade {
+        Trade::new(1000, 100.0, 5.0, symbol.to_string())
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_metrics_throttle() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(Some(100));
+        assert!(throttle.allow("AAPL", start));
+        assert!(!throttle.allow("AAPL", start + Duration::from_millis(50)));
+        assert!(throttle.allow("MSFT", start + Duration::from_millis(50)));
+        assert!(throttle.allow("AAPL", start + Duration::from_millis(100)));
+        
+        // Sin intervalo (o 0) se notifica cada actualización
+        let mut every = Throttle::new(Some(0));
+        assert!(every.allow("AAPL", start));
+        assert!(every.allow("AAPL", start));
+    }
+    
+    #[test]
+    fn test_manager_dispatches_trade() {
+        let manager = EngineManager::new(true, true, true, true, None, None, None).unwrap();
+        let bundle = manager.on_trade(&trade("AAPL"));
+        
+        assert_eq!(bundle.symbol, "AAPL");
+        assert!(!bundle.is_empty());
+        assert!(bundle.cvd.is_some());
+        assert_eq!(bundle.vwap.unwrap().vwap, 100.0);
+        assert!(bundle.liquidity.is_none());
+        assert!(bundle.heatmap.is_none());
+        assert!(manager.on_bar(&Bar::new(60_000, 1.0, 2.0, 0.5, 1.5, 10.0, "1m".to_string(), "AAPL".to_string())).is_empty());
+    }
+    
+    #[test]
+    fn test_manager_sinks() {
+        let manager = EngineManager::new(true, false, false, false, None, None, None).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = seen.clone();
+        let id = manager.add_sink(Arc::new(move |bundle: &MetricsBundle| sink_seen.lock().push(bundle.ts)));
+        
+        manager.on_trade(&trade("AAPL"));
+        // Los bundles vacíos no llegan a los sinks
+        manager.on_bar(&Bar::new(60_000, 1.0, 2.0, 0.5, 1.5, 10.0, "1m".to_string(), "AAPL".to_string()));
+        assert!(manager.remove_sink(id));
+        assert!(!manager.remove_sink(id));
+        manager.on_trade(&Trade::new(2000, 100.0, 5.0, "AAPL".to_string()));
+        assert_eq!(*seen.lock(), vec![1000]);
+    }
+
+    #[test]
+    fn test_process_batch_parallel_matches_sequential() {
+        let symbols = ["AAPL", "MSFT", "ES", "NQ"];
+        let trades: Vec<Trade> = (0..400u64)
+            .map(|i| {
+                let mut t = Trade::new(i, 100.0 + (i % 7) as f64, 1.0 + (i % 3) as f64, symbols[(i % 4) as usize].to_string());
+                t.side = Some(if i % 5 < 2 { "SELL" } else { "BUY" }.to_string());
+                t
+            })
+            .collect();
+        
+        let sequential = EngineManager::new(true, true, false, false, None, None, None).unwrap();
+        let expected: Vec<MetricsBundle> = trades.iter().map(|t| sequential.on_trade(t)).collect();
+        
+        let mut parallel = EngineManager::new(true, true, false, false, None, None, None).unwrap();
+        parallel.set_threads(Some(3)).unwrap();
+        assert_eq!(parallel.threads(), 3);
+        let bundles = parallel.dispatch_parallel(&trades);
+        
+        assert_eq!(bundles.len(), trades.len());
+        for (got, want) in bundles.iter().zip(&expected) {
+            assert_eq!((got.symbol.as_str(), got.ts), (want.symbol.as_str(), want.ts));
+            assert_eq!(got.cvd.as_ref().map(|m| m.cvd), want.cvd.as_ref().map(|m| m.cvd));
+            assert_eq!(got.vwap.as_ref().map(|m| m.vwap), want.vwap.as_ref().map(|m| m.vwap));
+        }
+        assert!(parallel.render_metrics().contains("indicators_events_total 400\n"));
+    }
+
+    #[test]
+    fn test_manager_render_metrics() {
+        let manager = EngineManager::new(true, true, false, false, None, None, None).unwrap();
+        manager.on_trade(&trade("AAPL"));
+        manager.on_trade(&trade("MSFT"));
+        manager.reset_symbol("MSFT");
+        
+        let text = manager.render_metrics();
+        assert!(text.contains("indicators_events_total 2\n"));
+        assert!(text.contains("indicators_symbols 1\n"));
+        assert!(text.contains("indicators_engine_latency_seconds_count{engine=\"cvd\"} 2\n"));
+        assert!(text.contains("indicators_engine_symbols{engine=\"vwap\"} 1\n"));
+        assert!(text.contains("indicators_engine_latency_quantile_seconds{engine=\"cvd\",quantile=\"0.99\"} "));
+        
+        let quantiles = manager.get_latency_quantiles(vec![0.5]).unwrap();
+        assert!(quantiles["cvd"][0].is_some());
+        assert!(manager.get_latency_quantiles(vec![2.0]).is_err());
+    }
+    
+    #[test]
+    fn test_manager_dispatches_snapshot() {
+        let manager = EngineManager::new(true, false, true, true, None, None, None).unwrap();
+        let snapshot = BookSnapshot::new(
+            1000,
+            "AAPL".to_string(),
+            vec![Level::new(99.99, 100.0)],
+            vec![Level::new(100.01, 100.0)],
+        );
+        let bundle = manager.on_snapshot(&snapshot);
+        
+        assert_eq!(bundle.liquidity.unwrap().best_bid, 99.99);
+        assert!(bundle.heatmap.is_some());
+        assert!(bundle.cvd.is_none());
+    }
+
+    #[test]
+    fn test_manager_bar_engines_and_disabled() {
+        let manager = EngineManager::new(false, false, false, false, Some(vec![2]), Some(vec![2]), None).unwrap();
+        assert_eq!(manager.engines(), vec!["moving_averages", "rsi"]);
+        assert!(manager.on_trade(&trade("AAPL")).cvd.is_none());
+        
+        let bar = Bar::new(60_000, 100.0, 101.0, 99.0, 100.5, 10.0, "1m".to_string(), "AAPL".to_string());
+        let bundle = manager.on_bar(&bar);
+        assert_eq!(bundle.moving_averages.len(), 1);
+        assert_eq!(bundle.rsi.len(), 1);
+        assert!(bundle.atr.is_empty());
+        
+        assert!(EngineManager::new(true, true, true, true, Some(vec![0]), None, None).is_err());
+    }
+
+    #[test]
+    fn test_manager_register_and_toggle() {
+        let mut manager = EngineManager::new(true, false, false, false, None, None, None).unwrap();
+        manager.register("tape", Box::new(TapeEngine::new(1000, 0.99, 100, 10).unwrap())).unwrap();
+        assert!(manager.register("tape", Box::new(TapeEngine::new(1000, 0.99, 100, 10).unwrap())).is_err());
+        assert!(manager.on_trade(&trade("AAPL")).tape.is_some());
+        
+        // Desactivado solo para MSFT
+        manager.disable("tape", Some("MSFT")).unwrap();
+        assert!(manager.on_trade(&trade("MSFT")).tape.is_none());
+        assert!(manager.on_trade(&trade("AAPL")).tape.is_some());
+        manager.enable("tape", Some("MSFT")).unwrap();
+        assert!(manager.is_enabled("tape", "MSFT"));
+        
+        // Desactivado globalmente
+        manager.disable("cvd", None).unwrap();
+        assert!(manager.on_trade(&trade("AAPL")).cvd.is_none());
+        assert!(manager.disable("missing", None).is_err());
+        
+        assert!(manager.unregister("tape"));
+        assert_eq!(manager.engines(), vec!["cvd"]);
+    }
+
+    #[test]
+    fn test_manager_symbol_config() {
+        let mut manager = EngineManager::new(true, true, true, false, None, None, None).unwrap();
+        manager.set_config(Some(crate::config::load_config(r#"
+            [default]
+            depth_levels = 1
+
+            [symbols.ESZ5]
+            indicators = ["cvd", "liquidity"]
+        "#, None).unwrap()));
+        
+        let es = manager.on_trade(&trade("ESZ5"));
+        assert!(es.cvd.is_some());
+        assert!(es.vwap.is_none());
+        assert!(manager.on_trade(&trade("AAPL")).vwap.is_some());
+        assert!(!manager.is_enabled("vwap", "ESZ5"));
+        
+        // depth_levels = 1 llega al engine de liquidez a través del manager
+        let snapshot = BookSnapshot::new(
+            1000,
+            "ESZ5".to_string(),
+            vec![Level::new(99.0, 10.0), Level::new(98.0, 50.0)],
+            vec![Level::new(101.0, 10.0), Level::new(102.0, 10.0)],
+        );
+        assert_eq!(manager.on_snapshot(&snapshot).liquidity.unwrap().bids_depth, 10.0);
+    }
+
+    #[test]
+    fn test_state_sizes_book_engines() {
+        let manager = EngineManager::new(false, false, true, true, None, None, None).unwrap();
+        let snapshot = |symbol: &str| BookSnapshot::new(
+            1000,
+            symbol.to_string(),
+            vec![Level::new(99.0, 10.0)],
+            vec![Level::new(101.0, 10.0)],
+        );
+        manager.on_snapshot(&snapshot("AAPL"));
+        manager.on_snapshot(&snapshot("MSFT"));
+        
+        // Liquidez y heatmap no exponen snapshot_state, pero sí su estado
+        let sizes = manager.state_sizes();
+        assert_eq!((sizes["liquidity"], sizes["heatmap"]), (2, 2));
+        assert!(manager.render_metrics().contains("indicators_engine_symbols{engine=\"heatmap\"} 2\n"));
+        
+        manager.reset_symbol("AAPL");
+        assert_eq!(manager.state_size(), 2);
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_register_from_python() {
+        with_py(|py| {
+            let manager = Py::new(py, EngineManager::new(false, false, false, false, None, None, None).unwrap()).unwrap();
+            let manager = manager.bind(py);
+            let cvd = Py::new(py, CVDEngine::new()).unwrap();
+            manager.call_method1("register", ("cvd", cvd.clone_ref(py))).unwrap();
+            
+            // La copia del manager comparte el estado con el objeto de Python
+            manager.borrow().on_trade(&Trade { side: Some("BUY".to_string()), ..trade("AAPL") });
+            assert_eq!(cvd.borrow(py).get_cvd("AAPL"), Some(5.0));
+            assert_eq!(manager.borrow().engines(), vec!["cvd"]);
+            
+            let err = manager.call_method1("register", ("cvd", cvd)).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+            let err = manager.call_method1("register", ("other", "cvd")).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyTypeError>(py));
+        });
+    }
+
+    struct Counter(std::sync::atomic::AtomicUsize);
+
+    impl Indicator for Counter {
+        fn on_trade(&self, _trade: &Trade) -> Vec<IndicatorOutput> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+            vec![IndicatorOutput::Custom("counter".to_string(), HashMap::from([("trades".to_string(), n as f64)]))]
+        }
+        
+        fn reset_symbol(&self, _symbol: &str) {}
+        
+        fn reset(&self) {
+            self.0.store(0, std::sync::atomic::Ordering::Relaxed);
+        }
+        
+        fn state_len(&self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn test_manager_custom_indicator() {
+        let mut manager = EngineManager::new(false, true, false, false, None, None, None).unwrap();
+        manager.register("counter", Box::new(Counter(Default::default()))).unwrap();
+        
+        manager.on_trade(&trade("AAPL"));
+        let bundle = manager.on_trade(&trade("AAPL"));
+        assert_eq!(bundle.custom["counter"]["trades"], 2.0);
+        assert_eq!(manager.snapshot_state("AAPL")["vwap"]["vwap"], 100.0);
+        
+        manager.reset_all();
+        assert_eq!(manager.on_trade(&trade("AAPL")).custom["counter"]["trades"], 1.0);
+    }
+    
+    #[test]
+    fn test_manager_drops_retransmitted_trades() {
+        let mut manager = EngineManager::new(true, true, false, false, None, None, None).unwrap();
+        let with_id = |id: &str| {
+            let mut t = trade("AAPL");
+            t.side = Some("BUY".to_string());
+            t.trade_id = Some(id.to_string());
+            t
+        };
+        
+        assert!(!manager.on_trade(&with_id("1")).is_empty());
+        assert!(manager.on_trade(&with_id("1")).is_empty());
+        manager.on_trade(&with_id("2"));
+        assert_eq!(manager.snapshot_state("AAPL")["cvd"]["cvd"], 10.0);
+        assert_eq!(manager.get_duplicates(Some("AAPL")), HashMap::from([("AAPL".to_string(), 1)]));
+        assert!(manager.render_metrics().contains("indicators_duplicate_trades_total{symbol=\"AAPL\"} 1\n"));
+        
+        // Sin caché los ids repetidos se procesan
+        manager.set_dedup_capacity(0);
+        assert!(!manager.on_trade(&with_id("1")).is_empty());
+        
+        manager.reset_all();
+        assert!(manager.get_duplicates(None).is_empty());
+    }
+    
+    #[test]
+    fn test_symbol_ttl_eviction() {
+        let mut manager = EngineManager::new(true, false, false, false, None, None, None).unwrap();
+        assert!(manager.set_symbol_ttl_ms(Some(0)).is_err());
+        manager.set_symbol_ttl_ms(Some(60_000)).unwrap();
+        let at = |ts: u64, symbol: &str| Trade::new(ts, 100.0, 1.0, symbol.to_string());
+        
+        manager.on_trade(&at(1_000, "AAPL"));
+        manager.on_trade(&at(1_000, "MSFT"));
+        assert_eq!(manager.active_symbols(), vec!["AAPL", "MSFT"]);
+        assert_eq!(manager.state_size(), 2);
+        
+        // AAPL lleva más de 60 s sin eventos cuando llega el de MSFT
+        manager.on_trade(&at(70_000, "MSFT"));
+        assert_eq!(manager.active_symbols(), vec!["MSFT"]);
+        assert!(manager.snapshot_state("AAPL").is_empty());
+        assert_eq!(manager.last_event_ts("MSFT"), Some(70_000));
+        assert_eq!(manager.state_sizes()["cvd"], 1);
+        
+        // Con el stream parado se fuerza con un reloj externo
+        assert!(manager.evict_expired(Some(100_000)).is_empty());
+        assert_eq!(manager.evict_expired(Some(200_000)), vec!["MSFT"]);
+        assert_eq!(manager.state_size(), 0);
+        
+        manager.on_trade(&at(200_000, "NQ"));
+        assert!(manager.evict("NQ"));
+        assert!(!manager.evict("NQ"));
+        assert!(manager.render_metrics().contains("indicators_symbols_evicted_total 3\n"));
+    }
+    
+    #[test]
+    fn test_manager_event_engines_and_flow_signal() {
+        let mut manager = EngineManager::new(true, false, false, false, None, None, None).unwrap();
+        manager.register("enricher", Box::new(TradeEnricher::new())).unwrap();
+        manager.register("sweeps", Box::new(SweepDetector::new(1_000, 2, 0.0).unwrap())).unwrap();
+        manager.register("flow", Box::new(FlowSignalEngine::new(60_000, 1.0, 1.0, 1.0).unwrap())).unwrap();
+        manager.set_symbol_ttl_ms(Some(60_000)).unwrap();
+        let buy = |ts: u64, price: f64, symbol: &str| Trade { side: Some("BUY".to_string()), ..Trade::new(ts, price, 1.0, symbol.to_string()) };
+        
+        // El flow signal se calcula con el CVD del mismo bundle
+        let bundle = manager.on_trade(&buy(1_000, 100.0, "AAPL"));
+        assert_eq!(bundle.enriched_trade.as_ref().map(|t| t.side.as_str()), Some("BUY"));
+        assert_eq!(bundle.flow_signal.as_ref().map(|s| s.score), Some(1.0));
+        manager.on_trade(&buy(1_100, 100.5, "AAPL"));
+        let bundle = manager.on_trade(&buy(5_000, 100.0, "AAPL"));
+        assert_eq!(bundle.sweep.as_ref().map(|s| s.levels), Some(2));
+        assert_eq!(manager.snapshot_state("AAPL")["flow"]["score"], 1.0);
+        
+        // El TTL también desaloja el estado de estos engines
+        manager.on_trade(&buy(70_000, 100.0, "MSFT"));
+        let sizes = manager.state_sizes();
+        assert_eq!((sizes["enricher"], sizes["sweeps"], sizes["flow"]), (0, 1, 1));
+    }
+}
@@ -0,0 +1,237 @@
+//! # NATS Connection
+//! 
+//! Gestión de la conexión compartida por suscriptor y publisher:
+//! - Backoff exponencial para los reintentos (lo usa también el cliente
+//!   async-nats en sus reconexiones internas).
+//! - Estado de la conexión visible desde Python: getter, eventos pendientes
+//!   (`drain_connection_events`) y callback opcional en cada cambio.
+
+use parking_lot::Mutex;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::py_macros::{py_class, py_methods};
+
+/// Máximo de eventos de conexión guardados sin drenar
+const MAX_PENDING_EVENTS: usize = 1000;
+
+/// Backoff exponencial: initial_ms · 2^(intento-1), acotado a max_ms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial_ms: u64,
+    pub max_ms: u64,
+}
+
+impl Backoff {
+    /// Devuelve None si initial_ms es 0 o mayor que max_ms
+    pub fn new(initial_ms: u64, max_ms: u64) -> Option<Self> {
+        if initial_ms == 0 || initial_ms > max_ms {
+            return None;
+        }
+        Some(Self { initial_ms, max_ms })
+    }
+    
+    /// Espera antes del intento n (1 = primer reintento)
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let exp = attempt.saturating_sub(1).min(32) as u32;
+        let ms = self.initial_ms.saturating_mul(1u64 << exp).min(self.max_ms);
+        Duration::from_millis(ms)
+    }
+}
+
+/// Estado de la conexión
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Reconnecting,
+    Stopped,
+}
+
+impl ConnectionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Disconnected => "disconnected",
+            Self::Connecting => "connecting",
+            Self::Connected => "connected",
+            Self::Reconnecting => "reconnecting",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+py_class! {
+/// Cambio de estado de la conexión
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    #[pyo3(get, set)]
+    pub ts: u64,  // epoch ms
+    #[pyo3(get, set)]
+    pub state: String,
+    #[pyo3(get, set)]
+    pub detail: Option<String>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl ConnectionEvent {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("ConnectionEvent(state={}, detail={:?}, ts={})", self.state, self.detail, self.ts)
+    }
+}
+}
+
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Estado compartido de la conexión entre el hilo de red y Python
+pub struct ConnectionMonitor {
+    state: Mutex<ConnectionState>,
+    events: Mutex<Vec<ConnectionEvent>>,
+    #[cfg(feature = "python")]
+    callback: Mutex<Option<PyObject>>,
+    // Último mensaje recibido (epoch ms, 0 = ninguno)
+    last_message_ms: AtomicU64,
+}
+
+impl Default for ConnectionMonitor {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(ConnectionState::Disconnected),
+            events: Mutex::new(Vec::new()),
+            #[cfg(feature = "python")]
+            callback: Mutex::new(None),
+            last_message_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ConnectionMonitor {
+    pub fn state(&self) -> ConnectionState {
+        *self.state.lock()
+    }
+    
+    /// Cambia el estado; solo registra evento y avisa al callback si cambia
+    pub fn set(&self, state: ConnectionState, detail: Option<String>) {
+        {
+            let mut current = self.state.lock();
+            if *current == state {
+                return;
+            }
+            *current = state;
+        }
+        tracing::info!(state = state.as_str(), detail = detail.as_deref().unwrap_or(""), "nats connection state");
+        let event = ConnectionEvent { ts: now_ms(), state: state.as_str().to_string(), detail };
+        {
+            let mut events = self.events.lock();
+            if events.len() >= MAX_PENDING_EVENTS {
+                events.remove(0);
+            }
+            events.push(event.clone());
+        }
+        #[cfg(feature = "python")]
+        self.notify(event);
+    }
+    
+    /// Llama al callback de Python (si hay) con el evento; los errores del
+    /// callback se imprimen y no detienen el hilo de red
+    #[cfg(feature = "python")]
+    fn notify(&self, event: ConnectionEvent) {
+        if self.callback.lock().is_none() {
+            return;
+        }
+        Python::with_gil(|py| {
+            // Clonar fuera del lock: el callback puede reemplazarse a sí mismo
+            let callback = self.callback.lock().as_ref().map(|cb| cb.clone_ref(py));
+            if let Some(callback) = callback {
+                if let Err(e) = callback.call1(py, (event,)) {
+                    e.print(py);
+                }
+            }
+        });
+    }
+    
+    #[cfg(feature = "python")]
+    pub fn set_callback(&self, callback: Option<PyObject>) {
+        *self.callback.lock() = callback;
+    }
+    
+    pub fn drain_events(&self) -> Vec<ConnectionEvent> {
+        std::mem::take(&mut *self.events.lock())
+    }
+    
+    pub fn touch(&self) {
+        self.last_message_ms.store(now_ms(), Ordering::Relaxed);
+    }
+    
+    /// Milisegundos desde el último mensaje (None si no ha llegado ninguno)
+    pub fn last_message_age_ms(&self) -> Option<u64> {
+        match self.last_message_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ts => Some(now_ms().saturating_sub(ts)),
+        }
+    }
+}
+
+/// Opciones de conexión con backoff en las reconexiones internas y
+/// seguimiento del estado
+pub fn connect_options(backoff: Backoff, monitor: Arc<ConnectionMonitor>) -> async_nats::ConnectOptions {
+    async_nats::ConnectOptions::new()
+        .reconnect_delay_callback(move |attempts| backoff.delay(attempts))
+        .event_callback(move |event| {
+            let monitor = monitor.clone();
+            async move {
+                match event {
+                    async_nats::Event::Connected => monitor.set(ConnectionState::Connected, None),
+                    async_nats::Event::Disconnected => monitor.set(ConnectionState::Reconnecting, Some("disconnected".to_string())),
+                    _ => {}
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        assert!(Backoff::new(0, 1000).is_none());
+        assert!(Backoff::new(2000, 1000).is_none());
+        
+        let backoff = Backoff::new(500, 4000).unwrap();
+        assert_eq!(backoff.delay(0), Duration::from_millis(500));
+        assert_eq!(backoff.delay(1), Duration::from_millis(500));
+        assert_eq!(backoff.delay(2), Duration::from_millis(1000));
+        assert_eq!(backoff.delay(4), Duration::from_millis(4000));
+        assert_eq!(backoff.delay(100), Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_monitor_events_on_change_only() {
+        let monitor = ConnectionMonitor::default();
+        assert_eq!(monitor.state(), ConnectionState::Disconnected);
+        
+        monitor.set(ConnectionState::Connecting, None);
+        monitor.set(ConnectionState::Connecting, None);
+        monitor.set(ConnectionState::Connected, None);
+        let events = monitor.drain_events();
+        assert_eq!(events.iter().map(|e| e.state.as_str()).collect::<Vec<_>>(), vec!["connecting", "connected"]);
+        assert!(monitor.drain_events().is_empty());
+        
+        assert_eq!(monitor.last_message_age_ms(), None);
+        monitor.touch();
+        assert!(monitor.last_message_age_ms().is_some());
+    }
+}
@@ -0,0 +1,473 @@
+//! # NATS Publisher
+//! 
+//! Publica las métricas calculadas (CVD, VWAP, liquidez, heatmap) y los
+//! eventos de barrido en NATS.
+//! El subject sale de una plantilla con `{kind}` y `{symbol}`
+//! (por defecto `indicators.{kind}.{symbol}`), configurable por tipo.
+//! 
+//! Los mensajes se encolan desde Python y un hilo con runtime Tokio propio
+//! los publica en lotes de hasta `batch_size` con un flush por lote. Con
+//! `max_rate` se limita el ritmo (mensajes/s); mientras se espera, los
+//! mensajes de un mismo subject se fusionan y solo se publica el último.
+//! 
+//! La conexión se establece en segundo plano: lo publicado antes de conectar
+//! o durante una reconexión queda en el buffer del cliente y se envía al
+//! recuperarla (reintentos con backoff exponencial).
+
+use parking_lot::Mutex;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::nats_connection::{connect_options, Backoff, ConnectionEvent, ConnectionMonitor, ConnectionState};
+use crate::types::{CVDMetrics, HeatmapMetrics, LiquidityMetrics, MetricsBundle, SweepEvent, VWAPMetrics};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+pub const KIND_CVD: &str = "cvd";
+pub const KIND_VWAP: &str = "vwap";
+pub const KIND_LIQUIDITY: &str = "liquidity";
+pub const KIND_HEATMAP: &str = "heatmap";
+pub const KIND_SWEEP: &str = "sweep";
+
+/// Espera máxima de un flush
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Mensaje pendiente de publicar
+type Outgoing = (String, Vec<u8>);
+
+/// Sustituye `{kind}` y `{symbol}` en la plantilla de subject
+pub fn render_subject(template: &str, kind: &str, symbol: &str) -> String {
+    template.replace("{kind}", kind).replace("{symbol}", symbol)
+}
+
+/// Fusiona mensajes por subject: se queda el último de cada uno, en el orden
+/// de su última aparición. Devuelve cuántos se descartaron.
+fn conflate(batch: &mut Vec<Outgoing>) -> usize {
+    let before = batch.len();
+    let mut last_index: HashMap<&str, usize> = HashMap::new();
+    for (i, (subject, _)) in batch.iter().enumerate() {
+        last_index.insert(subject.as_str(), i);
+    }
+    let keep: Vec<bool> = batch.iter().enumerate().map(|(i, (s, _))| last_index[s.as_str()] == i).collect();
+    let mut keep = keep.into_iter();
+    batch.retain(|_| keep.next().unwrap_or(false));
+    before - batch.len()
+}
+
+/// Token bucket: `rate` mensajes por segundo con ráfaga máxima de un segundo
+struct RateLimiter {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self { rate, tokens: rate, last: now }
+    }
+    
+    /// Espera necesaria para poder enviar n mensajes; si es cero los consume
+    fn reserve(&mut self, n: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate.max(n as f64));
+        self.last = now;
+        
+        let missing = n as f64 - self.tokens;
+        if missing > 0.0 {
+            return Duration::from_secs_f64(missing / self.rate);
+        }
+        self.tokens -= n as f64;
+        Duration::ZERO
+    }
+}
+
+/// Contadores compartidos entre el hilo del publisher y Python
+#[derive(Default)]
+struct PublisherStats {
+    queued: AtomicU64,
+    published: AtomicU64,
+    conflated: AtomicU64,
+    errors: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl PublisherStats {
+    fn record_error(&self, error: String) {
+        tracing::warn!(error = %error, "nats publisher error");
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock() = Some(error);
+    }
+}
+
+py_class! {
+/// Publisher de métricas hacia NATS
+#[pyclass]
+pub struct NATSPublisher {
+    #[pyo3(get)]
+    pub url: String,
+    #[pyo3(get)]
+    pub subject_template: String,
+    #[pyo3(get)]
+    pub batch_size: usize,
+    #[pyo3(get)]
+    pub max_rate: Option<f64>,
+    // Plantillas por tipo de métrica que sustituyen a subject_template
+    subjects: HashMap<String, String>,
+    stats: Arc<PublisherStats>,
+    monitor: Arc<ConnectionMonitor>,
+    running: Arc<AtomicBool>,
+    tx: Option<mpsc::UnboundedSender<Outgoing>>,
+    stop_tx: Option<oneshot::Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl NATSPublisher {
+    #[new]
+    #[pyo3(signature = (url, subject_template="indicators.{kind}.{symbol}".to_string(), batch_size=100, max_rate=None))]
+    pub fn new(url: String, subject_template: String, batch_size: usize, max_rate: Option<f64>) -> Result<Self, ProcessError> {
+        if batch_size == 0 {
+            return Err(ProcessError::invalid("batch_size must be > 0"));
+        }
+        if max_rate.is_some_and(|r| !(r.is_finite() && r > 0.0)) {
+            return Err(ProcessError::invalid("max_rate must be > 0"));
+        }
+        Ok(Self {
+            url,
+            subject_template,
+            batch_size,
+            max_rate,
+            subjects: HashMap::new(),
+            stats: Arc::new(PublisherStats::default()),
+            monitor: Arc::new(ConnectionMonitor::default()),
+            running: Arc::new(AtomicBool::new(false)),
+            tx: None,
+            stop_tx: None,
+            worker: None,
+        })
+    }
+    
+    /// Plantilla de subject propia de un tipo ("cvd", "vwap", "liquidity", "heatmap", "sweep")
+    pub fn set_subject(&mut self, kind: &str, template: &str) {
+        self.subjects.insert(kind.to_string(), template.to_string());
+    }
+    
+    /// Subject al que se publica un tipo de métrica de un símbolo
+    pub fn subject_for(&self, kind: &str, symbol: &str) -> String {
+        let template = self.subjects.get(kind).unwrap_or(&self.subject_template);
+        render_subject(template, kind, symbol)
+    }
+    
+    /// Conecta a NATS y arranca el hilo de publicación
+    pub fn start(&mut self) -> Result<(), ProcessError> {
+        if self.is_running() {
+            return Err(ProcessError::runtime("publisher already running"));
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ProcessError::runtime(format!("tokio runtime error: {}", e)))?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let url = self.url.clone();
+        let batch_size = self.batch_size;
+        let max_rate = self.max_rate;
+        let stats = self.stats.clone();
+        let monitor = self.monitor.clone();
+        let running = self.running.clone();
+        
+        running.store(true, Ordering::SeqCst);
+        let worker = std::thread::Builder::new()
+            .name("nats-publisher".to_string())
+            .spawn(move || {
+                let result = runtime.block_on(publish_loop(url, batch_size, max_rate, rx, stats.clone(), monitor.clone(), stop_rx));
+                if let Err(e) = result {
+                    stats.record_error(e);
+                }
+                monitor.set(ConnectionState::Stopped, None);
+                running.store(false, Ordering::SeqCst);
+            })
+            .map_err(|e| {
+                self.running.store(false, Ordering::SeqCst);
+                ProcessError::runtime(format!("thread spawn error: {}", e))
+            })?;
+        
+        self.tx = Some(tx);
+        self.stop_tx = Some(stop_tx);
+        self.worker = Some(worker);
+        Ok(())
+    }
+    
+    /// Publica lo pendiente, cierra la conexión y espera al hilo
+    pub fn stop(&mut self) {
+        self.tx = None;
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+    
+    #[getter]
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+    
+    /// Último error de conexión, serialización o publicación
+    #[getter]
+    pub fn last_error(&self) -> Option<String> {
+        self.stats.last_error.lock().clone()
+    }
+    
+    /// Estado de la conexión: disconnected | connecting | connected | reconnecting | stopped
+    #[getter]
+    pub fn connection_state(&self) -> &'static str {
+        self.monitor.state().as_str()
+    }
+    
+    /// Cambios de estado pendientes desde la última llamada
+    pub fn drain_connection_events(&self) -> Vec<ConnectionEvent> {
+        self.monitor.drain_events()
+    }
+    
+    /// Callback llamado con cada `ConnectionEvent` desde el hilo de red (None lo quita)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (callback=None))]
+    pub fn set_state_callback(&self, callback: Option<PyObject>) {
+        self.monitor.set_callback(callback);
+    }
+    
+    /// Contadores: queued, published, conflated, errors
+    pub fn stats(&self) -> HashMap<String, u64> {
+        HashMap::from([
+            ("queued".to_string(), self.stats.queued.load(Ordering::Relaxed)),
+            ("published".to_string(), self.stats.published.load(Ordering::Relaxed)),
+            ("conflated".to_string(), self.stats.conflated.load(Ordering::Relaxed)),
+            ("errors".to_string(), self.stats.errors.load(Ordering::Relaxed)),
+        ])
+    }
+    
+    pub fn publish_cvd(&self, symbol: &str, metrics: &CVDMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_CVD, symbol, metrics)
+    }
+    
+    pub fn publish_vwap(&self, symbol: &str, metrics: &VWAPMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_VWAP, symbol, metrics)
+    }
+    
+    pub fn publish_liquidity(&self, symbol: &str, metrics: &LiquidityMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_LIQUIDITY, symbol, metrics)
+    }
+    
+    pub fn publish_heatmap(&self, metrics: &HeatmapMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_HEATMAP, &metrics.symbol, metrics)
+    }
+    
+    pub fn publish_sweep(&self, event: &SweepEvent) -> Result<(), ProcessError> {
+        self.publish(KIND_SWEEP, &event.symbol, event)
+    }
+    
+    /// Publica una lista de barridos (p. ej. el resultado de `SweepDetector.flush()`)
+    pub fn publish_sweeps(&self, events: Vec<SweepEvent>) -> Result<(), ProcessError> {
+        for event in &events {
+            self.publish_sweep(event)?;
+        }
+        Ok(())
+    }
+    
+    /// Publica todas las métricas presentes en el resultado del `EngineManager`
+    pub fn publish_bundle(&self, bundle: &MetricsBundle) -> Result<(), ProcessError> {
+        if let Some(m) = &bundle.cvd {
+            self.publish(KIND_CVD, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.vwap {
+            self.publish(KIND_VWAP, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.liquidity {
+            self.publish(KIND_LIQUIDITY, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.heatmap {
+            self.publish(KIND_HEATMAP, &bundle.symbol, m)?;
+        }
+        Ok(())
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("NATSPublisher(url={}, subject_template={}, running={})",
+                self.url, self.subject_template, self.is_running())
+    }
+}
+}
+
+impl NATSPublisher {
+    /// Serializa y encola una métrica; falla si el publisher no está arrancado
+    pub fn publish<T: Serialize>(&self, kind: &str, symbol: &str, metrics: &T) -> Result<(), ProcessError> {
+        let tx = self.tx.as_ref().filter(|_| self.is_running()).ok_or_else(|| {
+            ProcessError::runtime("publisher not running")
+        })?;
+        let payload = serde_json::to_vec(metrics)
+            .map_err(|e| ProcessError::invalid(format!("JSON error: {}", e)))?;
+        tx.send((self.subject_for(kind, symbol), payload))
+            .map_err(|_| ProcessError::runtime("publisher not running"))?;
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Drop for NATSPublisher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Bucle del publisher: agrupa lo encolado en lotes, aplica el límite de
+/// ritmo y publica con un flush por lote
+async fn publish_loop(
+    url: String,
+    batch_size: usize,
+    max_rate: Option<f64>,
+    mut rx: mpsc::UnboundedReceiver<Outgoing>,
+    stats: Arc<PublisherStats>,
+    monitor: Arc<ConnectionMonitor>,
+    mut stop_rx: oneshot::Receiver<()>,
+) -> Result<(), String> {
+    monitor.set(ConnectionState::Connecting, None);
+    let backoff = Backoff { initial_ms: 500, max_ms: 30_000 };
+    let client = connect_options(backoff, monitor)
+        .retry_on_initial_connect()
+        .connect(url.as_str())
+        .await
+        .map_err(|e| format!("NATS connect error: {}", e))?;
+    let mut limiter = max_rate.map(|rate| RateLimiter::new(rate, Instant::now()));
+    let mut stopping = false;
+    
+    while !stopping {
+        let mut batch: Vec<Outgoing> = Vec::new();
+        tokio::select! {
+            _ = &mut stop_rx => stopping = true,
+            next = rx.recv() => match next {
+                Some(message) => batch.push(message),
+                None => stopping = true,
+            },
+        }
+        // Al parar se vacía la cola completa
+        let limit = if stopping { usize::MAX } else { batch_size };
+        while batch.len() < limit {
+            match rx.try_recv() {
+                Ok(message) => batch.push(message),
+                Err(_) => break,
+            }
+        }
+        
+        if let Some(limiter) = limiter.as_mut() {
+            loop {
+                // Lo que llega durante la espera se fusiona con el lote
+                while let Ok(message) = rx.try_recv() {
+                    batch.push(message);
+                }
+                stats.conflated.fetch_add(conflate(&mut batch) as u64, Ordering::Relaxed);
+                let wait = limiter.reserve(batch.len(), Instant::now());
+                if wait.is_zero() || stopping {
+                    break;
+                }
+                tokio::time::sleep(wait).await;
+            }
+        }
+        
+        if batch.is_empty() {
+            continue;
+        }
+        for (subject, payload) in batch {
+            match client.publish(subject, payload.into()).await {
+                Ok(()) => { stats.published.fetch_add(1, Ordering::Relaxed); }
+                Err(e) => stats.record_error(format!("publish error: {}", e)),
+            }
+        }
+        // Acotado: sin conexión el flush no terminaría y bloquearía stop()
+        match tokio::time::timeout(FLUSH_TIMEOUT, client.flush()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => stats.record_error(format!("flush error: {}", e)),
+            Err(_) => stats.record_error("flush timeout".to_string()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subjects() {
+        let mut publisher = NATSPublisher::new("nats://localhost:4222".to_string(),
+                                               "indicators.{kind}.{symbol}".to_string(), 100, None).unwrap();
+        assert_eq!(publisher.subject_for(KIND_CVD, "AAPL"), "indicators.cvd.AAPL");
+        
+        publisher.set_subject(KIND_HEATMAP, "md.heatmap.{symbol}");
+        assert_eq!(publisher.subject_for(KIND_HEATMAP, "ES"), "md.heatmap.ES");
+        assert_eq!(publisher.subject_for(KIND_VWAP, "ES"), "indicators.vwap.ES");
+        
+        assert!(NATSPublisher::new("nats://x".to_string(), "s".to_string(), 0, None).is_err());
+        assert!(NATSPublisher::new("nats://x".to_string(), "s".to_string(), 10, Some(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_publish_requires_start() {
+        let publisher = NATSPublisher::new("nats://localhost:4222".to_string(),
+                                           "indicators.{kind}.{symbol}".to_string(), 100, None).unwrap();
+        let bundle = MetricsBundle::empty("AAPL", 1000);
+        // Sin métricas no se encola nada
+        assert!(publisher.publish_bundle(&bundle).is_ok());
+        
+        let heatmap = HeatmapMetrics {
+            bucket_ts: 0,
+            bucket_ms: 1000,
+            tiles: Vec::new(),
+            max_sz: 0.0,
+            compression_ratio: 1.0,
+            symbol: "AAPL".to_string(),
+            normalization: "raw".to_string(),
+            mid: None,
+            notional: false,
+        };
+        assert!(publisher.publish_heatmap(&heatmap).is_err());
+    }
+
+    #[test]
+    fn test_conflate_keeps_last_per_subject() {
+        let mut batch: Vec<Outgoing> = vec![
+            ("a".to_string(), vec![1]),
+            ("b".to_string(), vec![2]),
+            ("a".to_string(), vec![3]),
+        ];
+        assert_eq!(conflate(&mut batch), 1);
+        assert_eq!(batch, vec![("b".to_string(), vec![2]), ("a".to_string(), vec![3])]);
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10.0, start);
+        
+        // Ráfaga inicial de un segundo
+        assert_eq!(limiter.reserve(10, start), Duration::ZERO);
+        assert_eq!(limiter.reserve(5, start), Duration::from_millis(500));
+        // Medio segundo después hay 5 tokens
+        assert_eq!(limiter.reserve(5, start + Duration::from_millis(500)), Duration::ZERO);
+    }
+}
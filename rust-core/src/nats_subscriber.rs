@@ -2,14 +2,60 @@
 //! 
 //! Async NATS subscriber para JetStream con procesamiento de mensajes
 //! y publicación de métricas de indicadores.
+//! 
+//! `start()` lanza un hilo con un runtime Tokio propio que crea (o reutiliza)
+//! el stream y un consumer pull durable, decodifica cada mensaje como
+//! Trade, BookSnapshot o Bar y lo pasa por el `EngineManager`. `stop()` cierra
+//! el consumer y espera al hilo.
+//! 
+//! Con `subjects` un mismo suscriptor consume varios subjects (admite los
+//! comodines `*` y `>`), cada uno con su tipo de mensaje: "trades", "books",
+//! "bars" o "auto" (se deduce de los campos del mensaje).
+//! 
+//! `wire_format` selecciona la codificación de los mensajes: "json" (por
+//! defecto), "msgpack" o "protobuf" (ver `crate::wire`).
+//! 
+//! Entre la recepción y los engines hay una cola acotada (`queue_capacity`)
+//! con política de desbordamiento `overflow_policy` (ver `crate::queue`).
+//! 
+//! Los trades retransmitidos (mismo `trade_id`) se descartan en el manager
+//! y se cuentan por símbolo en `get_duplicates()`.
+//! 
+//! `start_metrics_server(addr)` sirve en `GET /metrics` (Prometheus) los
+//! contadores del suscriptor, el lag del consumer y las métricas del manager.
+//! 
+//! Desde asyncio (ver `crate::py_asyncio`):
+//! - `await subscriber.run()` arranca el suscriptor y termina cuando se
+//!   detiene; cancelar la tarea lo detiene
+//! - `async for metrics in subscriber.stream()` recibe cada `MetricsBundle`
+//!   no vacío sin bloquear el event loop
 
-// use async_nats::jetstream::Context;
-use serde_json;
+use async_nats::jetstream;
+use futures::StreamExt;
+use parking_lot::Mutex;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tokio::sync::oneshot;
 
-use crate::types::{Trade, BookSnapshot};
-use crate::indicators::{CVDEngine, HeatmapEngine, VWAPEngine, LiquidityEngine};
+use crate::manager::{EngineManager, MetricsSink};
+use crate::nats_connection::{connect_options, now_ms, Backoff, ConnectionEvent, ConnectionMonitor, ConnectionState};
+#[cfg(feature = "python")]
+use crate::py_asyncio::{LoopFuture, MetricStreams, MetricsStream, DEFAULT_STREAM_CAPACITY};
+use crate::queue::{BoundedQueue, OverflowPolicy};
+use crate::telemetry::{write_header, write_sample, MetricsServer};
+use crate::types::{BookSnapshot, MetricsBundle, Trade};
+use crate::wire::{decode, WireFormat};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+pub use crate::wire::{MarketEvent, MessageKind};
 
+py_class! {
 /// Configuración del suscriptor NATS
 #[pyclass]
 #[derive(Clone)]
@@ -20,56 +66,431 @@ pub struct NATSConfig {
     pub subject: String,
     #[pyo3(get, set)]
     pub stream_name: String,
+    /// Nombre del consumer durable (por defecto "<stream_name>-indicators")
+    #[pyo3(get, set)]
+    pub consumer_name: Option<String>,
+    /// Pares (subject, tipo de mensaje); si está vacío se usa `subject` en modo "auto"
+    #[pyo3(get)]
+    pub subjects: Vec<(String, String)>,
+    /// Backoff de reconexión: primera espera y espera máxima (ms)
+    #[pyo3(get)]
+    pub reconnect_initial_ms: u64,
+    #[pyo3(get)]
+    pub reconnect_max_ms: u64,
+    /// Formato de cable de los mensajes: "json", "msgpack" o "protobuf"
+    pub wire_format: WireFormat,
+    /// Capacidad de la cola entre la recepción y el procesamiento
+    #[pyo3(get)]
+    pub queue_capacity: usize,
+    /// Política con la cola llena: "block", "drop-oldest" o "drop-newest"
+    pub overflow_policy: OverflowPolicy,
+}
 }
 
+py_methods! {
 #[pymethods]
 impl NATSConfig {
     #[new]
-    fn new(url: String, subject: String, stream_name: String) -> Self {
-        Self { url, subject, stream_name }
+    #[pyo3(signature = (url, subject, stream_name, consumer_name=None, subjects=Vec::new(),
+                        reconnect_initial_ms=500, reconnect_max_ms=30_000, wire_format="json",
+                        queue_capacity=10_000, overflow_policy="block"))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(url: String, subject: String, stream_name: String, consumer_name: Option<String>,
+               subjects: Vec<(String, String)>, reconnect_initial_ms: u64, reconnect_max_ms: u64,
+               wire_format: &str, queue_capacity: usize, overflow_policy: &str) -> Result<Self, ProcessError> {
+        if Backoff::new(reconnect_initial_ms, reconnect_max_ms).is_none() {
+            return Err(ProcessError::invalid(
+                "reconnect_initial_ms must be > 0 and <= reconnect_max_ms"));
+        }
+        if queue_capacity == 0 {
+            return Err(ProcessError::invalid("queue_capacity must be > 0"));
+        }
+        let mut config = Self {
+            url,
+            subject,
+            stream_name,
+            consumer_name,
+            subjects: Vec::new(),
+            reconnect_initial_ms,
+            reconnect_max_ms,
+            wire_format: WireFormat::Json,
+            queue_capacity,
+            overflow_policy: OverflowPolicy::Block,
+        };
+        config.set_subjects(subjects)?;
+        config.set_wire_format(wire_format)?;
+        config.set_overflow_policy(overflow_policy)?;
+        Ok(config)
+    }
+    
+    #[getter]
+    pub fn get_wire_format(&self) -> &'static str {
+        self.wire_format.as_str()
+    }
+    
+    #[setter]
+    pub fn set_wire_format(&mut self, wire_format: &str) -> Result<(), ProcessError> {
+        self.wire_format = WireFormat::parse(wire_format).ok_or_else(|| ProcessError::invalid(
+            format!("invalid wire_format {}: must be json, msgpack or protobuf", wire_format)))?;
+        Ok(())
+    }
+    
+    /// Reemplaza la lista de (subject, tipo); el tipo se valida aquí
+    #[setter]
+    pub fn set_subjects(&mut self, subjects: Vec<(String, String)>) -> Result<(), ProcessError> {
+        for (subject, kind) in &subjects {
+            if subject.is_empty() || MessageKind::parse(kind).is_none() {
+                return Err(ProcessError::invalid(format!(
+                    "invalid subject mapping ({}, {}): kind must be trades, books, bars or auto", subject, kind)));
+            }
+        }
+        self.subjects = subjects;
+        Ok(())
+    }
+    
+    #[getter]
+    pub fn get_overflow_policy(&self) -> &'static str {
+        self.overflow_policy.as_str()
+    }
+    
+    #[setter]
+    pub fn set_overflow_policy(&mut self, overflow_policy: &str) -> Result<(), ProcessError> {
+        self.overflow_policy = OverflowPolicy::parse(overflow_policy).ok_or_else(|| ProcessError::invalid(
+            format!("invalid overflow_policy {}: must be block, drop-oldest or drop-newest", overflow_policy)))?;
+        Ok(())
     }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("NATSConfig(url={}, subjects={:?}, stream={}, wire_format={})",
+                self.url, self.subject_filters(), self.stream_name, self.wire_format.as_str())
+    }
+}
+}
+
+impl NATSConfig {
+    fn backoff(&self) -> Backoff {
+        Backoff::new(self.reconnect_initial_ms, self.reconnect_max_ms).unwrap_or(Backoff { initial_ms: 500, max_ms: 30_000 })
+    }
+    
+    fn durable_name(&self) -> String {
+        self.consumer_name.clone().unwrap_or_else(|| format!("{}-indicators", self.stream_name))
+    }
+    
+    /// Rutas efectivas (patrón, tipo) en orden de prioridad
+    pub fn routes(&self) -> Vec<(String, MessageKind)> {
+        if self.subjects.is_empty() {
+            return vec![(self.subject.clone(), MessageKind::Auto)];
+        }
+        self.subjects.iter()
+            .filter_map(|(subject, kind)| MessageKind::parse(kind).map(|k| (subject.clone(), k)))
+            .collect()
+    }
+    
+    fn subject_filters(&self) -> Vec<String> {
+        self.routes().into_iter().map(|(subject, _)| subject).collect()
+    }
+}
+
+/// Coincidencia de subject NATS: `*` es un token, `>` uno o más tokens finales
+pub fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut tokens = subject.split('.');
+    for p in pattern.split('.') {
+        match (p, tokens.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (p, Some(t)) if p == t => {}
+            _ => return false,
+        }
+    }
+    tokens.next().is_none()
+}
+
+/// Tipo de mensaje del primer patrón que casa con el subject
+fn route(routes: &[(String, MessageKind)], subject: &str) -> Option<MessageKind> {
+    routes.iter().find(|(pattern, _)| subject_matches(pattern, subject)).map(|(_, kind)| *kind)
 }
 
+/// Decodifica un mensaje JSON: con bids/asks es un snapshot, con OHLC una
+/// barra y en otro caso un trade
+pub fn decode_message(payload: &[u8]) -> Result<MarketEvent, String> {
+    decode_as(MessageKind::Auto, payload)
+}
+
+/// Decodifica un mensaje JSON como el tipo indicado ("auto" deduce el tipo)
+pub fn decode_as(kind: MessageKind, payload: &[u8]) -> Result<MarketEvent, String> {
+    decode(WireFormat::Json, kind, payload)
+}
+
+/// Contadores compartidos entre el hilo del consumer y Python
+#[derive(Default)]
+struct SubscriberStats {
+    received: AtomicU64,
+    processed: AtomicU64,
+    errors: AtomicU64,
+    // Mensajes descartados por la cola llena y mensajes en cola
+    dropped: AtomicU64,
+    queued: AtomicU64,
+    // Lag del consumer en el último mensaje: pendientes en el servidor y
+    // retraso desde su publicación (ms)
+    pending: AtomicU64,
+    lag_ms: AtomicU64,
+    // Última secuencia del stream confirmada (para retomar el consumer)
+    last_acked: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl SubscriberStats {
+    fn record_error(&self, error: String) {
+        tracing::warn!(error = %error, "nats subscriber error");
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock() = Some(error);
+    }
+}
+
+py_class! {
 /// Runner async para procesar mensajes NATS
 #[pyclass]
 pub struct NATSSubscriber {
     config: NATSConfig,
-    cvd_engine: CVDEngine,
-    heatmap_engine: HeatmapEngine,
-    #[allow(dead_code)]
-    vwap_engine: VWAPEngine,
-    #[allow(dead_code)]
-    liquidity_engine: LiquidityEngine,
+    manager: Arc<EngineManager>,
+    stats: Arc<SubscriberStats>,
+    monitor: Arc<ConnectionMonitor>,
+    running: Arc<AtomicBool>,
+    // Compartido con los futures de `run()`: cancelarlos detiene el consumer
+    stop_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    worker: Option<JoinHandle<()>>,
+    metrics_server: Option<MetricsServer>,
+    // Futures de `run()` pendientes y streams async de métricas
+    #[cfg(feature = "python")]
+    waiters: Arc<Mutex<Vec<LoopFuture>>>,
+    #[cfg(feature = "python")]
+    streams: Arc<MetricStreams>,
+}
 }
 
+py_methods! {
 #[pymethods]
 impl NATSSubscriber {
     #[new]
-    fn new(config: NATSConfig) -> Self {
-        Self {
-            config,
-            cvd_engine: CVDEngine::new(),
-            heatmap_engine: HeatmapEngine::new(),
-            vwap_engine: VWAPEngine::new(),
-            liquidity_engine: LiquidityEngine::new(),
+    pub fn new(config: NATSConfig) -> Result<Self, ProcessError> {
+        Ok(Self::with_manager(config, Arc::new(EngineManager::new(true, true, true, true, None, None, None)?)))
+    }
+    
+    /// Conecta a NATS y comienza a procesar mensajes en segundo plano
+    pub fn start(&mut self) -> Result<(), ProcessError> {
+        if self.is_running() {
+            return Err(ProcessError::runtime("subscriber already running"));
+        }
+        // Recoger un hilo previo que terminó por error
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ProcessError::runtime(format!("tokio runtime error: {}", e)))?;
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let config = self.config.clone();
+        let manager = self.manager.clone();
+        let stats = self.stats.clone();
+        let monitor = self.monitor.clone();
+        let running = self.running.clone();
+        #[cfg(feature = "python")]
+        let (waiters, streams) = (self.waiters.clone(), self.streams.clone());
+        // Cada bundle calculado va a los streams async de Python
+        #[cfg(feature = "python")]
+        let publish: MetricsSink = {
+            let streams = streams.clone();
+            Arc::new(move |bundle: &MetricsBundle| streams.publish(bundle))
+        };
+        #[cfg(not(feature = "python"))]
+        let publish: MetricsSink = Arc::new(|_: &MetricsBundle| {});
+        
+        running.store(true, Ordering::SeqCst);
+        let worker = std::thread::Builder::new()
+            .name("nats-subscriber".to_string())
+            .spawn(move || {
+                let result = runtime.block_on(consume(config, manager, stats.clone(), monitor, publish, stop_rx));
+                if let Err(e) = result {
+                    stats.record_error(e);
+                }
+                // Primero running = false: `run()` registra su future con el
+                // lock de waiters y solo si sigue en marcha
+                running.store(false, Ordering::SeqCst);
+                #[cfg(feature = "python")]
+                {
+                    let finished = std::mem::take(&mut *waiters.lock());
+                    if !finished.is_empty() {
+                        Python::with_gil(|py| finished.iter().for_each(|waiter| waiter.set_result(py, py.None())));
+                    }
+                    streams.close_all();
+                }
+            })
+            .map_err(|e| {
+                self.running.store(false, Ordering::SeqCst);
+                ProcessError::runtime(format!("thread spawn error: {}", e))
+            })?;
+        
+        *self.stop_tx.lock() = Some(stop_tx);
+        self.worker = Some(worker);
+        Ok(())
+    }
+    
+    /// Detiene el consumer y espera a que termine el hilo (sin el GIL, que el
+    /// hilo puede necesitar para entregar métricas)
+    #[cfg(feature = "python")]
+    fn stop(&mut self, py: Python<'_>) {
+        py.allow_threads(|| self.shutdown());
+    }
+    
+    /// Awaitable para asyncio: arranca el suscriptor si no está en marcha y
+    /// se completa cuando se detiene. Cancelar el await detiene el consumer.
+    #[cfg(feature = "python")]
+    fn run(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<PyObject> {
+        let waiter = LoopFuture::new(py)?;
+        if !slf.is_running() {
+            slf.start()?;
+        }
+        let future = waiter.future(py);
+        let finished = {
+            let mut waiters = slf.waiters.lock();
+            if slf.is_running() {
+                waiters.push(waiter);
+                None
+            } else {
+                Some(waiter)
+            }
+        };
+        // El hilo ya terminó (p. ej. no se pudo arrancar el consumer)
+        if let Some(waiter) = finished {
+            waiter.set_result(py, py.None());
         }
+        
+        // El callback retiene el suscriptor: `await NATSSubscriber(config).run()`
+        // no lo libera mientras corre
+        let stop_tx = slf.stop_tx.clone();
+        let subscriber: Py<Self> = slf.into();
+        let on_done = PyCFunction::new_closure_bound(py, None, None,
+            move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+                let _alive = &subscriber;
+                if args.get_item(0)?.call_method0("cancelled")?.extract::<bool>()? {
+                    if let Some(stop_tx) = stop_tx.lock().take() {
+                        let _ = stop_tx.send(());
+                    }
+                }
+                Ok(())
+            })?;
+        future.call_method1(py, "add_done_callback", (on_done,))?;
+        Ok(future)
+    }
+    
+    /// Iterador async de los `MetricsBundle` no vacíos calculados a partir de
+    /// ahora (`async for metrics in subscriber.stream()`). Con el buffer lleno
+    /// se descartan los más antiguos; la iteración termina al detenerse.
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (capacity=DEFAULT_STREAM_CAPACITY))]
+    fn stream(&self, capacity: usize) -> PyResult<MetricsStream> {
+        self.streams.open(capacity)
     }
     
-    /// Conecta a NATS y comienza a procesar mensajes (async)
-    fn start(&self) -> PyResult<String> {
-        // Esta función será llamada desde Python
-        // El trabajo real se hace en Rust con async
-        Ok(format!("Conectando a NATS: {}", self.config.url))
+    #[getter]
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+    
+    /// Último error de conexión o de decodificación
+    #[getter]
+    pub fn last_error(&self) -> Option<String> {
+        self.stats.last_error.lock().clone()
+    }
+    
+    /// Contadores: received, processed, errors, dropped, queued, last_acked_sequence
+    pub fn stats(&self) -> HashMap<String, u64> {
+        HashMap::from([
+            ("received".to_string(), self.stats.received.load(Ordering::Relaxed)),
+            ("processed".to_string(), self.stats.processed.load(Ordering::Relaxed)),
+            ("errors".to_string(), self.stats.errors.load(Ordering::Relaxed)),
+            ("dropped".to_string(), self.stats.dropped.load(Ordering::Relaxed)),
+            ("queued".to_string(), self.stats.queued.load(Ordering::Relaxed)),
+            ("last_acked_sequence".to_string(), self.stats.last_acked.load(Ordering::Relaxed)),
+        ])
+    }
+    
+    /// Estado de la conexión: disconnected | connecting | connected | reconnecting | stopped
+    #[getter]
+    pub fn connection_state(&self) -> &'static str {
+        self.monitor.state().as_str()
+    }
+    
+    /// Cambios de estado pendientes desde la última llamada
+    pub fn drain_connection_events(&self) -> Vec<ConnectionEvent> {
+        self.monitor.drain_events()
+    }
+    
+    /// Callback llamado con cada `ConnectionEvent` desde el hilo de red (None lo quita)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (callback=None))]
+    fn set_state_callback(&self, callback: Option<PyObject>) {
+        self.monitor.set_callback(callback);
+    }
+    
+    /// Callback con cada `MetricsBundle` calculado (ver `EngineManager.on_metrics`)
+    #[cfg(feature = "python")]
+    #[pyo3(signature = (callback=None, throttle_ms=None))]
+    fn on_metrics(&self, callback: Option<PyObject>, throttle_ms: Option<u64>) {
+        self.manager.on_metrics(callback, throttle_ms);
+    }
+    
+    /// Trades retransmitidos descartados por símbolo (ver `EngineManager.get_duplicates`)
+    #[pyo3(signature = (symbol=None))]
+    pub fn get_duplicates(&self, symbol: Option<&str>) -> HashMap<String, u64> {
+        self.manager.get_duplicates(symbol)
+    }
+    
+    /// Milisegundos desde el último mensaje recibido, para detectar datos obsoletos
+    pub fn last_message_age_ms(&self) -> Option<u64> {
+        self.monitor.last_message_age_ms()
+    }
+    
+    /// Métricas operativas en formato de texto de Prometheus
+    pub fn render_metrics(&self) -> String {
+        render_metrics(&self.manager, &self.stats, &self.monitor)
+    }
+    
+    /// Sirve `render_metrics()` en `GET /metrics`; devuelve la dirección
+    /// efectiva ("host:0" elige un puerto libre)
+    #[pyo3(signature = (addr="0.0.0.0:9100"))]
+    pub fn start_metrics_server(&mut self, addr: &str) -> Result<String, ProcessError> {
+        if self.metrics_server.is_some() {
+            return Err(ProcessError::runtime("metrics server already running"));
+        }
+        let (manager, stats, monitor) = (self.manager.clone(), self.stats.clone(), self.monitor.clone());
+        let server = MetricsServer::start(addr, Arc::new(move || render_metrics(&manager, &stats, &monitor)))
+            .map_err(|e| ProcessError::runtime(format!("metrics server error: {}", e)))?;
+        let bound = server.addr().to_string();
+        self.metrics_server = Some(server);
+        Ok(bound)
+    }
+    
+    pub fn stop_metrics_server(&mut self) {
+        self.metrics_server = None;
+    }
+    
+    /// Estado actual de los indicadores para un símbolo
+    pub fn snapshot_state(&self, symbol: &str) -> HashMap<String, HashMap<String, f64>> {
+        self.manager.snapshot_state(symbol)
     }
     
     /// Procesa un trade recibido de NATS
-    fn process_trade(&self, trade: &Trade) -> PyResult<String> {
-        let cvd_metrics = self.cvd_engine.on_trade(trade);
+    pub fn process_trade(&self, trade: &Trade) -> Result<String, ProcessError> {
+        let cvd_metrics = self.manager.on_trade(trade).cvd;
         
         if let Some(metrics) = cvd_metrics {
             // Serializar y publicar
             let json = serde_json::to_string(&metrics)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("JSON error: {}", e)))?;
+                .map_err(|e| ProcessError::invalid(format!("JSON error: {}", e)))?;
             Ok(format!("CVD: {}", json))
         } else {
             Ok("No metrics".to_string())
@@ -77,28 +498,411 @@ impl NATSSubscriber {
     }
     
     /// Procesa un snapshot de libro
-    fn process_book(&self, snapshot: &BookSnapshot) -> PyResult<String> {
-        let heatmap_metrics = self.heatmap_engine.on_snapshot(snapshot);
+    pub fn process_book(&self, snapshot: &BookSnapshot) -> Result<String, ProcessError> {
+        let heatmap_metrics = self.manager.on_snapshot(snapshot).heatmap;
         
         if let Some(metrics) = heatmap_metrics {
             let json = serde_json::to_string(&metrics)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("JSON error: {}", e)))?;
+                .map_err(|e| ProcessError::invalid(format!("JSON error: {}", e)))?;
             Ok(format!("Heatmap: {}", json))
         } else {
             Ok("No metrics".to_string())
         }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("NATSSubscriber(url={})", self.config.url)
+        format!("NATSSubscriber(url={}, running={})", self.config.url, self.is_running())
+    }
+}
+}
+
+impl NATSSubscriber {
+    /// Suscriptor que despacha a un manager ya configurado (p. ej. el del daemon)
+    pub fn with_manager(config: NATSConfig, manager: Arc<EngineManager>) -> Self {
+        Self {
+            config,
+            manager,
+            stats: Arc::new(SubscriberStats::default()),
+            monitor: Arc::new(ConnectionMonitor::default()),
+            running: Arc::new(AtomicBool::new(false)),
+            stop_tx: Arc::new(Mutex::new(None)),
+            worker: None,
+            metrics_server: None,
+            #[cfg(feature = "python")]
+            waiters: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "python")]
+            streams: Arc::new(MetricStreams::default()),
+        }
+    }
+    
+    /// Detiene el consumer y espera a que termine el hilo
+    pub fn shutdown(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.lock().take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for NATSSubscriber {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Texto Prometheus: contadores del suscriptor, conexión y lag, más las
+/// métricas del manager
+fn render_metrics(manager: &EngineManager, stats: &SubscriberStats, monitor: &ConnectionMonitor) -> String {
+    let mut out = String::new();
+    let counters = [
+        ("nats_messages_received_total", "Messages received from JetStream", &stats.received),
+        ("nats_messages_processed_total", "Messages decoded and dispatched", &stats.processed),
+        ("nats_messages_dropped_total", "Messages dropped by the queue overflow policy", &stats.dropped),
+        ("nats_errors_total", "Connection, decode and ack errors", &stats.errors),
+    ];
+    for (name, help, counter) in counters {
+        write_header(&mut out, name, help, "counter");
+        write_sample(&mut out, name, "", counter.load(Ordering::Relaxed) as f64);
+    }
+    let gauges = [
+        ("nats_queue_depth", "Messages waiting in the internal queue", stats.queued.load(Ordering::Relaxed) as f64),
+        ("nats_consumer_pending", "Messages pending on the server for the consumer", stats.pending.load(Ordering::Relaxed) as f64),
+        ("nats_lag_seconds", "Delay between publish and receipt of the last message", stats.lag_ms.load(Ordering::Relaxed) as f64 / 1000.0),
+        ("nats_connected", "1 if connected to NATS", (monitor.state() == ConnectionState::Connected) as u8 as f64),
+    ];
+    for (name, help, value) in gauges {
+        write_header(&mut out, name, help, "gauge");
+        write_sample(&mut out, name, "", value);
+    }
+    if let Some(age_ms) = monitor.last_message_age_ms() {
+        write_header(&mut out, "nats_last_message_age_seconds", "Time since the last message", "gauge");
+        write_sample(&mut out, "nats_last_message_age_seconds", "", age_ms as f64 / 1000.0);
+    }
+    manager.write_metrics(&mut out);
+    out
+}
+
+/// Despacha un evento decodificado al manager
+pub(crate) fn dispatch(manager: &EngineManager, event: &MarketEvent) -> MetricsBundle {
+    match event {
+        MarketEvent::Trade(trade) => manager.on_trade(trade),
+        MarketEvent::Book(snapshot) => manager.on_snapshot(snapshot),
+        MarketEvent::Bar(bar) => manager.on_bar(bar),
+    }
+}
+
+/// Bucle del consumer: abre sesiones hasta stop; si una sesión falla se
+/// reintenta con backoff exponencial (el contador se reinicia en cuanto una
+/// sesión llega a consumir)
+async fn consume(
+    config: NATSConfig,
+    manager: Arc<EngineManager>,
+    stats: Arc<SubscriberStats>,
+    monitor: Arc<ConnectionMonitor>,
+    publish: MetricsSink,
+    mut stop_rx: oneshot::Receiver<()>,
+) -> Result<(), String> {
+    let backoff = config.backoff();
+    let mut attempt = 0;
+    loop {
+        monitor.set(if attempt == 0 { ConnectionState::Connecting } else { ConnectionState::Reconnecting }, None);
+        match consume_session(&config, &manager, &stats, &monitor, &publish, &mut stop_rx, &mut attempt).await {
+            Ok(()) => {
+                monitor.set(ConnectionState::Stopped, None);
+                return Ok(());
+            }
+            Err(e) => {
+                attempt += 1;
+                monitor.set(ConnectionState::Reconnecting, Some(e.clone()));
+                stats.record_error(e);
+            }
+        }
+        tokio::select! {
+            _ = &mut stop_rx => {
+                monitor.set(ConnectionState::Stopped, None);
+                return Ok(());
+            }
+            _ = tokio::time::sleep(backoff.delay(attempt)) => {}
+        }
+    }
+}
+
+/// Una sesión: conecta, enlaza el consumer durable y procesa mensajes.
+/// Ok(()) solo al recibir stop; cualquier corte devuelve Err para reconectar.
+async fn consume_session(
+    config: &NATSConfig,
+    manager: &EngineManager,
+    stats: &SubscriberStats,
+    monitor: &Arc<ConnectionMonitor>,
+    publish: &MetricsSink,
+    stop_rx: &mut oneshot::Receiver<()>,
+    attempt: &mut usize,
+) -> Result<(), String> {
+    let client = connect_options(config.backoff(), monitor.clone())
+        .connect(config.url.as_str())
+        .await
+        .map_err(|e| format!("NATS connect error: {}", e))?;
+    let context = jetstream::new(client);
+    
+    let routes = config.routes();
+    let wire_format = config.wire_format;
+    let filters = config.subject_filters();
+    let stream = context
+        .get_or_create_stream(jetstream::stream::Config {
+            name: config.stream_name.clone(),
+            subjects: filters.clone(),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| format!("JetStream stream error: {}", e))?;
+    
+    // filter_subject y filter_subjects son excluyentes en el servidor
+    let durable = config.durable_name();
+    let mut consumer_config = jetstream::consumer::pull::Config {
+        durable_name: Some(durable.clone()),
+        ..Default::default()
+    };
+    if filters.len() == 1 {
+        consumer_config.filter_subject = filters[0].clone();
+    } else {
+        consumer_config.filter_subjects = filters;
+    }
+    // Un consumer durable existente retoma desde su ack floor; si hubo que
+    // recrearlo, se arranca tras la última secuencia confirmada
+    let last_acked = stats.last_acked.load(Ordering::Relaxed);
+    if last_acked > 0 {
+        consumer_config.deliver_policy = jetstream::consumer::DeliverPolicy::ByStartSequence {
+            start_sequence: last_acked + 1,
+        };
     }
+    let consumer = stream
+        .get_or_create_consumer(&durable, consumer_config)
+        .await
+        .map_err(|e| format!("JetStream consumer error: {}", e))?;
+    
+    let mut messages = consumer.messages().await
+        .map_err(|e| format!("JetStream messages error: {}", e))?;
+    monitor.set(ConnectionState::Connected, None);
+    *attempt = 0;
+    
+    // La recepción solo encola; el procesamiento drena la cola a su ritmo.
+    // Al parar (o cortarse la sesión) se procesa lo ya encolado.
+    let queue = BoundedQueue::new(config.queue_capacity, config.overflow_policy)
+        .ok_or_else(|| "queue_capacity must be > 0".to_string())?;
+    let receive = async {
+        let result = loop {
+            tokio::select! {
+                _ = &mut *stop_rx => break Ok(()),
+                next = messages.next() => {
+                    let Some(message) = next else { break Err("JetStream message stream closed".to_string()) };
+                    let message = match message {
+                        Ok(message) => message,
+                        Err(e) => {
+                            stats.record_error(format!("JetStream message error: {}", e));
+                            continue;
+                        }
+                    };
+                    stats.received.fetch_add(1, Ordering::Relaxed);
+                    monitor.touch();
+                    if let Ok(info) = message.info() {
+                        stats.pending.store(info.pending, Ordering::Relaxed);
+                        let published_ms = (info.published.unix_timestamp_nanos() / 1_000_000).max(0) as u64;
+                        stats.lag_ms.store(now_ms().saturating_sub(published_ms), Ordering::Relaxed);
+                    }
+                    
+                    // Los descartados se confirman: la política ya decidió perderlos
+                    if let Some(dropped) = queue.push(message).await {
+                        stats.dropped.fetch_add(1, Ordering::Relaxed);
+                        ack(&dropped, stats).await;
+                    }
+                    stats.queued.store(queue.len() as u64, Ordering::Relaxed);
+                }
+            }
+        };
+        queue.close();
+        result
+    };
+    let process = async {
+        while let Some(message) = queue.pop().await {
+            stats.queued.store(queue.len() as u64, Ordering::Relaxed);
+            let sequence = message.info().map(|info| info.stream_sequence).unwrap_or(0);
+            let _span = tracing::debug_span!("nats_message", subject = %message.subject, sequence).entered();
+            let decoded = match route(&routes, message.subject.as_str()) {
+                Some(kind) => decode(wire_format, kind, &message.payload),
+                None => Err(format!("no route for subject {}", message.subject)),
+            };
+            match decoded {
+                Ok(event) => {
+                    publish(&dispatch(manager, &event));
+                    stats.processed.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => stats.record_error(e),
+            }
+            // Los mensajes inválidos también se confirman para no redeliverlos
+            ack(&message, stats).await;
+        }
+    };
+    let (result, ()) = tokio::join!(receive, process);
+    result
 }
 
-/// Función async nativa para conectar y procesar
-#[pyfunction]
-pub fn subscribe_to_nats_async(url: &str, subject: &str) -> PyResult<String> {
-    // TODO: Implementar con async-nats real
-    // Por ahora retornamos placeholder
-    Ok(format!("Async NATS: {} @ {}", url, subject))
+/// Confirma un mensaje y registra su secuencia en el stream
+async fn ack(message: &jetstream::Message, stats: &SubscriberStats) {
+    match message.ack().await {
+        Ok(()) => {
+            if let Ok(info) = message.info() {
+                stats.last_acked.fetch_max(info.stream_sequence, Ordering::Relaxed);
+            }
+        }
+        Err(e) => stats.record_error(format!("ack error: {}", e)),
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "python")]
+    use crate::utils::with_py;
+    
+    #[test]
+    fn test_decode_message_kinds() {
+        let trade = decode_message(br#"{"ts": 1000, "price": 100.0, "size": 2.0, "symbol": "AAPL", "side": "BUY"}"#).unwrap();
+        assert!(matches!(trade, MarketEvent::Trade(ref t) if t.side.as_deref() == Some("BUY") && t.exchange.is_none()));
+        
+        let book = decode_message(br#"{"ts": 1000, "symbol": "AAPL", "bids": [{"price": 99.0, "size": 1.0}], "asks": []}"#).unwrap();
+        assert!(matches!(book, MarketEvent::Book(ref b) if b.bids.len() == 1));
+        
+        let bar = decode_message(br#"{"ts": 60000, "open": 1.0, "high": 2.0, "low": 0.5, "close": 1.5, "volume": 10.0, "tf": "1m", "symbol": "AAPL"}"#).unwrap();
+        assert!(matches!(bar, MarketEvent::Bar(_)));
+        
+        assert!(decode_message(b"not json").is_err());
+        assert!(decode_message(br#"{"ts": 1000, "symbol": "AAPL"}"#).is_err());
+    }
+    
+    #[test]
+    fn test_subject_routing() {
+        assert!(subject_matches("md.trades.>", "md.trades.AAPL"));
+        assert!(subject_matches("md.trades.>", "md.trades.us.AAPL"));
+        assert!(!subject_matches("md.trades.>", "md.trades"));
+        assert!(subject_matches("md.*.AAPL", "md.books.AAPL"));
+        assert!(!subject_matches("md.*", "md.books.AAPL"));
+        assert!(subject_matches("md.bars", "md.bars"));
+        
+        let config = NATSConfig::new(
+            "nats://localhost:4222".to_string(), String::new(), "MD".to_string(), None,
+            vec![("md.trades.>".to_string(), "trades".to_string()), ("md.books.>".to_string(), "books".to_string())],
+            500, 30_000, "json", 10_000, "block",
+        ).unwrap();
+        let routes = config.routes();
+        assert_eq!(route(&routes, "md.trades.AAPL"), Some(MessageKind::Trades));
+        assert_eq!(route(&routes, "md.books.AAPL"), Some(MessageKind::Books));
+        assert_eq!(route(&routes, "md.bars.AAPL"), None);
+        
+        // Sin lista se usa el subject único en modo auto
+        let single = NATSConfig::new("nats://x".to_string(), "md.>".to_string(), "MD".to_string(), None, Vec::new(), 500, 30_000, "json", 10_000, "block").unwrap();
+        assert_eq!(single.routes(), vec![("md.>".to_string(), MessageKind::Auto)]);
+        
+        assert!(NATSConfig::new("nats://x".to_string(), String::new(), "MD".to_string(), None,
+                                vec![("md.>".to_string(), "quotes".to_string())], 500, 30_000, "json", 10_000, "block").is_err());
+    }
+
+    #[test]
+    fn test_decode_as_forced_kind() {
+        // Un trade en un subject de books no es un snapshot válido
+        let payload = br#"{"ts": 1000, "price": 100.0, "size": 2.0, "symbol": "AAPL"}"#;
+        assert!(decode_as(MessageKind::Books, payload).is_err());
+        assert!(matches!(decode_as(MessageKind::Trades, payload), Ok(MarketEvent::Trade(_))));
+    }
+
+    #[test]
+    fn test_dispatch_decoded_events() {
+        let manager = EngineManager::new(true, true, true, true, None, None, None).unwrap();
+        let event = decode_message(br#"{"ts": 1000, "price": 100.0, "size": 2.0, "symbol": "AAPL"}"#).unwrap();
+        
+        let bundle = dispatch(&manager, &event);
+        assert!(bundle.cvd.is_some());
+        assert_eq!(bundle.vwap.unwrap().vwap, 100.0);
+    }
+    
+    #[test]
+    fn test_metrics_endpoint() {
+        let config = NATSConfig::new("nats://127.0.0.1:1".to_string(), "md.trades".to_string(), "MD".to_string(),
+                                     None, Vec::new(), 500, 30_000, "json", 10_000, "block").unwrap();
+        let mut subscriber = NATSSubscriber::new(config).unwrap();
+        subscriber.manager.on_trade(&Trade::new(1000, 100.0, 1.0, "AAPL".to_string()));
+        
+        let text = subscriber.render_metrics();
+        assert!(text.contains("nats_messages_dropped_total 0\n"));
+        assert!(text.contains("nats_connected 0\n"));
+        assert!(text.contains("indicators_events_total 1\n"));
+        
+        let addr = subscriber.start_metrics_server("127.0.0.1:0").unwrap();
+        assert!(subscriber.start_metrics_server("127.0.0.1:0").is_err());
+        let mut stream = std::net::TcpStream::connect(addr.as_str()).unwrap();
+        std::io::Write::write_all(&mut stream, b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        std::io::Read::read_to_string(&mut stream, &mut response).unwrap();
+        assert!(response.contains("nats_queue_depth 0\n"));
+        
+        subscriber.stop_metrics_server();
+        assert!(std::net::TcpStream::connect(addr.as_str()).is_err());
+    }
+    
+    #[test]
+    fn test_reconnects_until_stopped() {
+        // Puerto sin servidor: el consumer reintenta con backoff hasta stop
+        let config = NATSConfig::new("nats://127.0.0.1:1".to_string(), "md.trades".to_string(), "MD".to_string(),
+                                     None, Vec::new(), 10, 50, "json", 10_000, "block").unwrap();
+        let mut subscriber = NATSSubscriber::new(config).unwrap();
+        subscriber.start().unwrap();
+        
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while subscriber.stats()["errors"] < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(subscriber.is_running());
+        assert_eq!(subscriber.connection_state(), "reconnecting");
+        assert!(subscriber.last_error().unwrap().contains("connect"));
+        
+        #[cfg(feature = "python")]
+        with_py(|py| subscriber.stop(py));
+        #[cfg(not(feature = "python"))]
+        subscriber.shutdown();
+        assert!(!subscriber.is_running());
+        let states: Vec<String> = subscriber.drain_connection_events().into_iter().map(|e| e.state).collect();
+        assert_eq!(states.first().map(String::as_str), Some("connecting"));
+        assert_eq!(states.last().map(String::as_str), Some("stopped"));
+        
+        assert!(NATSConfig::new("nats://x".to_string(), "s".to_string(), "MD".to_string(), None, Vec::new(), 0, 50, "json", 10_000, "block").is_err());
+    }
+    
+    #[test]
+    fn test_wire_format_config() {
+        let mut config = NATSConfig::new("nats://x".to_string(), "md.>".to_string(), "MD".to_string(),
+                                         None, Vec::new(), 500, 30_000, "MessagePack", 10_000, "block").unwrap();
+        assert_eq!(config.wire_format, WireFormat::MessagePack);
+        assert_eq!(config.get_wire_format(), "msgpack");
+        
+        config.set_wire_format("proto").unwrap();
+        assert_eq!(config.wire_format, WireFormat::Protobuf);
+        assert!(config.set_wire_format("avro").is_err());
+        assert_eq!(config.wire_format, WireFormat::Protobuf);
+        
+        assert!(NATSConfig::new("nats://x".to_string(), "s".to_string(), "MD".to_string(), None, Vec::new(), 500, 30_000, "xml", 10_000, "block").is_err());
+    }
+    
+    #[test]
+    fn test_queue_config() {
+        let mut config = NATSConfig::new("nats://x".to_string(), "md.>".to_string(), "MD".to_string(),
+                                         None, Vec::new(), 500, 30_000, "json", 100, "drop_oldest").unwrap();
+        assert_eq!(config.overflow_policy, OverflowPolicy::DropOldest);
+        assert_eq!(config.get_overflow_policy(), "drop-oldest");
+        assert!(config.set_overflow_policy("spill").is_err());
+        
+        assert!(NATSConfig::new("nats://x".to_string(), "s".to_string(), "MD".to_string(), None, Vec::new(), 500, 30_000, "json", 0, "block").is_err());
+        assert!(NATSConfig::new("nats://x".to_string(), "s".to_string(), "MD".to_string(), None, Vec::new(), 500, 30_000, "json", 10, "lifo").is_err());
+    }
+}
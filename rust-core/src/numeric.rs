@@ -0,0 +1,279 @@
+//! # Numeric
+//! 
+//! Aritmética de CVD y VWAP genérica sobre el tipo de precio y tamaño. Es el
+//! núcleo de las sumas de `CVDEngine` y de `VWAPAccumulator` (VWAP de
+//! sesión, anclado y rolling), y se puede usar directamente desde Rust.
+//! 
+//! - `f64`: sumas compensadas (Neumaier); la opción por defecto de los engines.
+//! - `i64`: precios en ticks y tamaños en lotes, con sumas enteras exactas
+//!   en i128 (p. ej. backtesting; en `VWAPEngine` con `set_tick_arithmetic`).
+//!   La varianza de las bandas sigue en f64 (West), igual que en el modo
+//!   punto fijo de `VWAPEngine`.
+//! 
+//! Las entradas se cuantizan con `Units` (tick de precio y lote de tamaño) y
+//! los resultados se devuelven en f64 en las unidades originales. Con datos
+//! limpios (precios múltiplos del tick, tamaños múltiplos del lote) las dos
+//! instanciaciones dan el mismo resultado. Para decimales exactos con
+//! precisión variable ver `crate::fixed`.
+
+use std::fmt;
+use std::ops::Mul;
+use crate::indicators::classifier::{SIDE_BUY, SIDE_SELL};
+use crate::utils::{safe_div, NeumaierSum};
+use crate::utils::moments::WeightedVariance;
+
+/// Tipo numérico de los acumuladores
+pub trait Numeric: Copy + Default + PartialEq + fmt::Debug + Mul<Output = Self> {
+    /// Acumulador de sumas largas
+    type Sum: Clone + Default + fmt::Debug;
+    
+    /// Valor de entrada en unidades de `unit` (tick o lote)
+    fn quantize(value: f64, unit: f64) -> Self;
+    
+    /// Valor en unidades internas
+    fn raw(self) -> f64;
+    
+    /// Pasa un valor en unidades internas a unidades originales
+    fn scale(raw: f64, unit: f64) -> f64;
+    
+    fn sum_add(sum: &mut Self::Sum, value: Self);
+    
+    fn sum_sub(sum: &mut Self::Sum, value: Self);
+    
+    /// Total en unidades internas
+    fn sum_raw(sum: &Self::Sum) -> f64;
+}
+
+impl Numeric for f64 {
+    type Sum = NeumaierSum;
+    
+    fn quantize(value: f64, _unit: f64) -> Self {
+        value
+    }
+    
+    fn raw(self) -> f64 {
+        self
+    }
+    
+    fn scale(raw: f64, _unit: f64) -> f64 {
+        raw
+    }
+    
+    fn sum_add(sum: &mut NeumaierSum, value: f64) {
+        sum.add(value);
+    }
+    
+    fn sum_sub(sum: &mut NeumaierSum, value: f64) {
+        sum.add(-value);
+    }
+    
+    fn sum_raw(sum: &NeumaierSum) -> f64 {
+        sum.value()
+    }
+}
+
+/// Ticks o lotes enteros. Un pv (ticks · lotes) por trade debe caber en i64
+impl Numeric for i64 {
+    type Sum = i128;
+    
+    /// Redondea al múltiplo de `unit` más cercano (no finitos => 0)
+    fn quantize(value: f64, unit: f64) -> Self {
+        if !value.is_finite() {
+            return 0;
+        }
+        (value / unit).round() as i64
+    }
+    
+    fn raw(self) -> f64 {
+        self as f64
+    }
+    
+    fn scale(raw: f64, unit: f64) -> f64 {
+        raw * unit
+    }
+    
+    fn sum_add(sum: &mut i128, value: i64) {
+        *sum += value as i128;
+    }
+    
+    fn sum_sub(sum: &mut i128, value: i64) {
+        *sum -= value as i128;
+    }
+    
+    fn sum_raw(sum: &i128) -> f64 {
+        *sum as f64
+    }
+}
+
+/// Tick de precio y lote de tamaño con los que se cuantizan las entradas
+/// (las instanciaciones en f64 los ignoran)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    pub price: f64,
+    pub size: f64,
+}
+
+impl Units {
+    /// None si alguna unidad no es finita y > 0
+    pub fn new(price: f64, size: f64) -> Option<Self> {
+        let valid = |unit: f64| unit.is_finite() && unit > 0.0;
+        (valid(price) && valid(size)).then_some(Self { price, size })
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self { price: 1.0, size: 1.0 }
+    }
+}
+
+/// CVD, volumen comprador y vendedor
+#[derive(Clone, Debug, Default)]
+pub struct CvdCore<N: Numeric> {
+    units: Units,
+    cvd: N::Sum,
+    buy: N::Sum,
+    sell: N::Sum,
+}
+
+impl<N: Numeric> CvdCore<N> {
+    pub fn new(units: Units) -> Self {
+        Self { units, cvd: N::Sum::default(), buy: N::Sum::default(), sell: N::Sum::default() }
+    }
+    
+    /// Aplica un trade clasificado ("BUY", "SELL"; el resto no cambia el CVD)
+    pub fn apply(&mut self, side: &str, size: f64) {
+        let size = N::quantize(size, self.units.size);
+        match side {
+            SIDE_BUY => {
+                N::sum_add(&mut self.cvd, size);
+                N::sum_add(&mut self.buy, size);
+            }
+            SIDE_SELL => {
+                N::sum_sub(&mut self.cvd, size);
+                N::sum_add(&mut self.sell, size);
+            }
+            _ => {}
+        }
+    }
+    
+    pub fn cvd(&self) -> f64 {
+        N::scale(N::sum_raw(&self.cvd), self.units.size)
+    }
+    
+    pub fn buy_volume(&self) -> f64 {
+        N::scale(N::sum_raw(&self.buy), self.units.size)
+    }
+    
+    pub fn sell_volume(&self) -> f64 {
+        N::scale(N::sum_raw(&self.sell), self.units.size)
+    }
+}
+
+/// VWAP y desviación estándar ponderada por volumen
+#[derive(Clone, Debug, Default)]
+pub struct VwapCore<N: Numeric> {
+    units: Units,
+    pv: N::Sum,
+    v: N::Sum,
+    // Media y varianza de precio ponderadas, en unidades internas
+    variance: WeightedVariance,
+}
+
+impl<N: Numeric> VwapCore<N> {
+    pub fn new(units: Units) -> Self {
+        Self { units, pv: N::Sum::default(), v: N::Sum::default(), variance: WeightedVariance::default() }
+    }
+    
+    pub fn update(&mut self, price: f64, size: f64) {
+        let (price, size) = self.quantize(price, size);
+        N::sum_add(&mut self.pv, price * size);
+        N::sum_add(&mut self.v, size);
+        self.variance.push(price.raw(), size.raw());
+    }
+    
+    /// Retira una contribución previa (ventanas deslizantes)
+    pub fn remove(&mut self, price: f64, size: f64) {
+        let (price, size) = self.quantize(price, size);
+        N::sum_sub(&mut self.pv, price * size);
+        N::sum_sub(&mut self.v, size);
+        self.variance.remove(price.raw(), size.raw());
+    }
+    
+    fn quantize(&self, price: f64, size: f64) -> (N, N) {
+        (N::quantize(price, self.units.price), N::quantize(size, self.units.size))
+    }
+    
+    pub fn pv_sum(&self) -> f64 {
+        N::scale(N::sum_raw(&self.pv), self.units.price * self.units.size)
+    }
+    
+    pub fn v_sum(&self) -> f64 {
+        N::scale(N::sum_raw(&self.v), self.units.size)
+    }
+    
+    pub fn vwap(&self) -> f64 {
+        N::scale(safe_div(N::sum_raw(&self.pv), N::sum_raw(&self.v)), self.units.price)
+    }
+    
+    /// sqrt(Σ v·(p - vwap)² / Σv), calculada en unidades internas
+    pub fn std_dev(&self) -> f64 {
+        N::scale(self.variance.variance().sqrt(), self.units.price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * a.abs().max(1.0), "{} != {}", a, b);
+    }
+    
+    #[test]
+    fn test_cvd_core_f64_and_ticks() {
+        let units = Units::new(0.25, 0.5).unwrap();
+        let mut float = CvdCore::<f64>::new(units);
+        let mut ticks = CvdCore::<i64>::new(units);
+        let trades = [("BUY", 1.5), ("SELL", 0.5), ("NA", 10.0), ("BUY", 2.0), ("SELL", 3.5)];
+        for (side, size) in trades {
+            float.apply(side, size);
+            ticks.apply(side, size);
+        }
+        assert_eq!(float.cvd(), -0.5);
+        assert_eq!((ticks.cvd(), ticks.buy_volume(), ticks.sell_volume()),
+                   (float.cvd(), float.buy_volume(), float.sell_volume()));
+    }
+    
+    #[test]
+    fn test_vwap_core_f64_and_ticks() {
+        let units = Units::new(0.01, 1.0).unwrap();
+        let mut float = VwapCore::<f64>::new(units);
+        let mut ticks = VwapCore::<i64>::new(units);
+        let trades = [(100.01, 3.0), (100.05, 1.0), (99.98, 7.0), (100.10, 2.0)];
+        for (price, size) in trades {
+            float.update(price, size);
+            ticks.update(price, size);
+        }
+        float.remove(100.05, 1.0);
+        ticks.remove(100.05, 1.0);
+        
+        assert_eq!(ticks.v_sum(), float.v_sum());
+        assert_close(ticks.pv_sum(), float.pv_sum());
+        assert_close(ticks.vwap(), float.vwap());
+        assert_close(ticks.std_dev(), float.std_dev());
+        assert_close(float.vwap(), (100.01 * 3.0 + 99.98 * 7.0 + 100.10 * 2.0) / 12.0);
+    }
+    
+    #[test]
+    fn test_tick_quantization() {
+        assert_eq!(i64::quantize(100.37, 0.25), 401);
+        assert_eq!(i64::quantize(f64::NAN, 0.25), 0);
+        assert_eq!(f64::quantize(100.37, 0.25), 100.37);
+        assert_eq!(Units::new(0.0, 1.0), None);
+        assert_eq!(Units::new(0.01, f64::INFINITY), None);
+        
+        let empty = VwapCore::<i64>::new(Units::default());
+        assert_eq!((empty.vwap(), empty.std_dev()), (0.0, 0.0));
+    }
+}
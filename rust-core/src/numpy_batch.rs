@@ -0,0 +1,192 @@
+//! # Numpy Batch
+//! 
+//! Entradas batch con arrays numpy (rust-numpy) para evitar construir un
+//! objeto `Trade` Python por fila:
+//! - entrada: `ts` (int64, ms), `price` y `size` (float64) y, opcional,
+//!   `side` (int8: 1 = BUY, -1 = SELL, 0 = sin lado → clasificador)
+//! - salida: dict nombre → array numpy, una fila por trade de entrada
+//!   (NaN donde el engine descartó el trade)
+//! 
+//! Los kernels trabajan sobre slices y columnas Rust; solo `to_dict`
+//! requiere Python.
+
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use crate::indicators::classifier::{SIDE_BUY, SIDE_SELL};
+use crate::types::{Bar, CVDMetrics, MovingAverageMetrics, Trade, VWAPMetrics};
+
+/// Columna de resultados
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    F64(Vec<f64>),
+    U64(Vec<u64>),
+    I8(Vec<i8>),
+}
+
+/// Columnas de resultados en orden
+pub type Columns = Vec<(String, Column)>;
+
+/// Código numpy de un lado
+pub fn side_code(side: Option<&str>) -> i8 {
+    match side {
+        Some(SIDE_BUY) => 1,
+        Some(SIDE_SELL) => -1,
+        _ => 0,
+    }
+}
+
+/// Lado de un código numpy (0 = sin lado)
+pub fn side_from_code(code: i8) -> Option<&'static str> {
+    match code {
+        1 => Some(SIDE_BUY),
+        -1 => Some(SIDE_SELL),
+        _ => None,
+    }
+}
+
+/// Construye los trades de un símbolo a partir de columnas
+pub fn trades_from_slices(symbol: &str, ts: &[i64], price: &[f64], size: &[f64],
+                          side: Option<&[i8]>) -> PyResult<Vec<Trade>> {
+    let n = ts.len();
+    if price.len() != n || size.len() != n || side.is_some_and(|s| s.len() != n) {
+        return Err(PyValueError::new_err("ts, price, size and side must have the same length"));
+    }
+    (0..n)
+        .map(|i| {
+            if ts[i] < 0 {
+                return Err(PyValueError::new_err(format!("invalid ts at row {}: {}", i, ts[i])));
+            }
+            let mut trade = Trade::new(ts[i] as u64, price[i], size[i], symbol.to_string());
+            trade.side = side.and_then(|s| side_from_code(s[i])).map(str::to_string);
+            Ok(trade)
+        })
+        .collect()
+}
+
+/// Trades de arrays numpy (acepta arrays no contiguos)
+pub fn trades_from_numpy(symbol: &str, ts: PyReadonlyArray1<'_, i64>, price: PyReadonlyArray1<'_, f64>,
+                         size: PyReadonlyArray1<'_, f64>, side: Option<PyReadonlyArray1<'_, i8>>) -> PyResult<Vec<Trade>> {
+    let ts = ts.as_array().to_vec();
+    let price = price.as_array().to_vec();
+    let size = size.as_array().to_vec();
+    let side = side.map(|s| s.as_array().to_vec());
+    trades_from_slices(symbol, &ts, &price, &size, side.as_deref())
+}
+
+/// Convierte las columnas en un dict de arrays numpy
+pub fn to_dict(py: Python<'_>, columns: Columns) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (name, column) in columns {
+        match column {
+            Column::F64(values) => dict.set_item(name, PyArray1::from_vec_bound(py, values))?,
+            Column::U64(values) => dict.set_item(name, PyArray1::from_vec_bound(py, values))?,
+            Column::I8(values) => dict.set_item(name, PyArray1::from_vec_bound(py, values))?,
+        }
+    }
+    Ok(dict)
+}
+
+fn f64_column<M>(name: &str, results: &[Option<M>], f: fn(&M) -> f64) -> (String, Column) {
+    let values = results.iter().map(|m| m.as_ref().map_or(f64::NAN, f)).collect();
+    (name.to_string(), Column::F64(values))
+}
+
+/// cvd, buy_volume, sell_volume y side (código del lado aplicado)
+pub fn cvd_columns(results: &[Option<CVDMetrics>]) -> Columns {
+    vec![
+        f64_column("cvd", results, |m| m.cvd),
+        f64_column("buy_volume", results, |m| m.buy_volume),
+        f64_column("sell_volume", results, |m| m.sell_volume),
+        ("side".to_string(), Column::I8(results.iter()
+            .map(|m| side_code(m.as_ref().map(|m| m.last_side.as_str())))
+            .collect())),
+    ]
+}
+
+/// vwap, v_sum, std_dev y bandas ±1σ
+pub fn vwap_columns(results: &[Option<VWAPMetrics>]) -> Columns {
+    vec![
+        f64_column("vwap", results, |m| m.vwap),
+        f64_column("v_sum", results, |m| m.v_sum),
+        f64_column("std_dev", results, |m| m.std_dev),
+        f64_column("upper_band_1", results, |m| m.upper_band_1),
+        f64_column("lower_band_1", results, |m| m.lower_band_1),
+    ]
+}
+
+/// Barras cerradas; `spec` es el índice de la suscripción en `labels`
+pub fn bar_columns(bars: &[Bar], labels: &[String]) -> Columns {
+    let f64s = |f: fn(&Bar) -> f64| Column::F64(bars.iter().map(f).collect());
+    vec![
+        ("ts".to_string(), Column::U64(bars.iter().map(|b| b.ts).collect())),
+        ("open".to_string(), f64s(|b| b.open)),
+        ("high".to_string(), f64s(|b| b.high)),
+        ("low".to_string(), f64s(|b| b.low)),
+        ("close".to_string(), f64s(|b| b.close)),
+        ("volume".to_string(), f64s(|b| b.volume)),
+        ("spec".to_string(), Column::U64(bars.iter()
+            .map(|b| labels.iter().position(|l| *l == b.tf).unwrap_or(labels.len()) as u64)
+            .collect())),
+    ]
+}
+
+/// sma_{p}, ema_{p} y wma_{p} por periodo (NaN hasta completar la ventana)
+pub fn ma_columns(periods: &[usize], results: &[Vec<MovingAverageMetrics>]) -> Columns {
+    let mut columns = Columns::new();
+    for (i, period) in periods.iter().enumerate() {
+        let values = |f: fn(&MovingAverageMetrics) -> Option<f64>| {
+            Column::F64(results.iter().map(|r| r.get(i).and_then(f).unwrap_or(f64::NAN)).collect())
+        };
+        columns.push((format!("sma_{}", period), values(|m| m.sma)));
+        columns.push((format!("ema_{}", period), values(|m| m.ema)));
+        columns.push((format!("wma_{}", period), values(|m| m.wma)));
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bars::BarAggregator;
+    use crate::indicators::cvd::CVDEngine;
+    
+    #[test]
+    fn test_trades_from_slices() {
+        let trades = trades_from_slices("AAPL", &[1000, 2000], &[100.0, 101.0], &[1.0, 2.0], Some(&[1, 0])).unwrap();
+        assert_eq!(trades[0].side.as_deref(), Some(SIDE_BUY));
+        assert_eq!(trades[1].side, None);
+        assert_eq!(trades[1].symbol, "AAPL");
+        
+        assert!(trades_from_slices("AAPL", &[1000], &[100.0, 101.0], &[1.0], None).is_err());
+        assert!(trades_from_slices("AAPL", &[-1], &[100.0], &[1.0], None).is_err());
+    }
+    
+    #[test]
+    fn test_cvd_columns() {
+        let engine = CVDEngine::new();
+        let trades = trades_from_slices("AAPL", &[1000, 2000, 3000], &[100.0, 0.0, 100.0], &[2.0, 1.0, 1.0],
+                                        Some(&[1, 1, -1])).unwrap();
+        let results: Vec<_> = trades.iter().map(|t| engine.on_trade(t)).collect();
+        let columns = cvd_columns(&results);
+        
+        let Column::F64(cvd) = &columns[0].1 else { panic!("cvd must be f64") };
+        assert_eq!(cvd[0], 2.0);
+        assert!(cvd[1].is_nan());
+        assert_eq!(cvd[2], 1.0);
+        assert_eq!(columns[3].1, Column::I8(vec![1, 0, -1]));
+    }
+    
+    #[test]
+    fn test_bar_columns_spec_index() {
+        let aggregator = BarAggregator::new(vec!["1m".to_string(), "tick:2".to_string()]).unwrap();
+        let trades = trades_from_slices("ES", &[0, 1000, 61_000], &[10.0, 11.0, 12.0], &[1.0; 3], None).unwrap();
+        let bars: Vec<_> = trades.iter().flat_map(|t| aggregator.on_trade(t)).collect();
+        let columns = bar_columns(&bars, &aggregator.subscriptions());
+        
+        // tick:2 cierra con el segundo trade, 1m con el tercero
+        assert_eq!(columns[6].1, Column::U64(vec![1, 0]));
+        assert_eq!(columns[4].1, Column::F64(vec![11.0, 11.0]));
+    }
+}
@@ -0,0 +1,269 @@
+//! # Order Book Manager
+//! 
+//! Reconstruye libros L2 por símbolo a partir de deltas incrementales,
+//! detecta huecos de secuencia y emite `BookSnapshot`s completos bajo
+//! demanda o con una cadencia configurable (para Liquidity/Heatmap).
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::types::{BookDelta, BookSnapshot, Level};
+use crate::py_macros::{py_class, py_methods};
+
+/// Clave ordenable de precio: para f64 finitos positivos el patrón de bits
+/// conserva el orden numérico.
+fn price_key(price: f64) -> u64 {
+    price.to_bits()
+}
+
+/// Libro L2 de un símbolo
+#[derive(Clone, Debug, Default)]
+pub struct OrderBook {
+    bids: BTreeMap<Reverse<u64>, f64>,  // mejor bid primero
+    asks: BTreeMap<u64, f64>,           // mejor ask primero
+    pub last_sequence: Option<u64>,
+    pub last_ts: u64,
+    pub last_emit_ts: Option<u64>,
+    pub gap_count: u64,
+    pub needs_resync: bool,
+}
+
+impl OrderBook {
+    /// Sustituye el contenido del libro por un snapshot completo
+    pub fn load_snapshot(&mut self, snapshot: &BookSnapshot, sequence: Option<u64>) {
+        self.bids = snapshot.bids.iter()
+            .filter(|l| l.price > 0.0 && l.size > 0.0)
+            .map(|l| (Reverse(price_key(l.price)), l.size))
+            .collect();
+        self.asks = snapshot.asks.iter()
+            .filter(|l| l.price > 0.0 && l.size > 0.0)
+            .map(|l| (price_key(l.price), l.size))
+            .collect();
+        self.last_sequence = sequence;
+        self.last_ts = snapshot.ts;
+        self.needs_resync = false;
+    }
+    
+    /// Aplica un delta; devuelve false si el delta no es válido
+    pub fn apply(&mut self, delta: &BookDelta) -> bool {
+        if !delta.price.is_finite() || delta.price <= 0.0 || !delta.size.is_finite() {
+            return false;
+        }
+        
+        // Detectar huecos de secuencia
+        if let Some(seq) = delta.sequence {
+            if let Some(last) = self.last_sequence {
+                if seq <= last {
+                    return false; // Duplicado o retrasado
+                }
+                if seq != last + 1 {
+                    self.gap_count += 1;
+                    self.needs_resync = true;
+                }
+            }
+            self.last_sequence = Some(seq);
+        }
+        
+        let remove = delta.action.eq_ignore_ascii_case("delete") || delta.size <= 0.0;
+        let key = price_key(delta.price);
+        if delta.side.eq_ignore_ascii_case("bid") {
+            if remove {
+                self.bids.remove(&Reverse(key));
+            } else {
+                self.bids.insert(Reverse(key), delta.size);
+            }
+        } else if delta.side.eq_ignore_ascii_case("ask") {
+            if remove {
+                self.asks.remove(&key);
+            } else {
+                self.asks.insert(key, delta.size);
+            }
+        } else {
+            return false;
+        }
+        
+        self.last_ts = self.last_ts.max(delta.ts);
+        true
+    }
+    
+    /// Construye un snapshot con los `depth` mejores niveles por lado
+    pub fn to_snapshot(&self, symbol: &str, depth: Option<usize>) -> BookSnapshot {
+        let depth = depth.unwrap_or(usize::MAX);
+        let bids = self.bids.iter()
+            .take(depth)
+            .map(|(Reverse(k), size)| Level::new(f64::from_bits(*k), *size))
+            .collect();
+        let asks = self.asks.iter()
+            .take(depth)
+            .map(|(k, size)| Level::new(f64::from_bits(*k), *size))
+            .collect();
+        BookSnapshot::new(self.last_ts, symbol.to_string(), bids, asks)
+    }
+    
+    pub fn depth(&self) -> (usize, usize) {
+        (self.bids.len(), self.asks.len())
+    }
+}
+
+py_class! {
+/// Gestor de libros incrementales por símbolo
+#[pyclass]
+pub struct OrderBookManager {
+    /// Cadencia de emisión automática de snapshots (None = solo bajo demanda)
+    #[pyo3(get)]
+    pub snapshot_interval_ms: Option<u64>,
+    /// Niveles por lado en los snapshots emitidos (None = libro completo)
+    #[pyo3(get)]
+    pub snapshot_depth: Option<usize>,
+    books: Arc<DashMap<String, OrderBook>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl OrderBookManager {
+    #[new]
+    #[pyo3(signature = (snapshot_interval_ms=None, snapshot_depth=None))]
+    pub fn new(snapshot_interval_ms: Option<u64>, snapshot_depth: Option<usize>) -> Self {
+        Self {
+            snapshot_interval_ms,
+            snapshot_depth,
+            books: Arc::new(DashMap::new()),
+        }
+    }
+    
+    /// Carga (o resincroniza) el libro de un símbolo desde un snapshot completo
+    #[pyo3(signature = (snapshot, sequence=None))]
+    pub fn apply_snapshot(&self, snapshot: &BookSnapshot, sequence: Option<u64>) {
+        self.books.entry(snapshot.symbol.clone())
+            .or_default()
+            .load_snapshot(snapshot, sequence);
+    }
+    
+    /// Aplica un delta; devuelve un snapshot si se cumple la cadencia configurada
+    pub fn on_delta(&self, delta: &BookDelta) -> Option<BookSnapshot> {
+        let mut book = self.books.entry(delta.symbol.clone()).or_default();
+        if !book.apply(delta) {
+            return None;
+        }
+        
+        let interval = self.snapshot_interval_ms?;
+        let due = match book.last_emit_ts {
+            Some(last) => delta.ts >= last + interval,
+            None => true,
+        };
+        if !due {
+            return None;
+        }
+        book.last_emit_ts = Some(delta.ts);
+        Some(book.to_snapshot(&delta.symbol, self.snapshot_depth))
+    }
+    
+    /// Snapshot del libro actual (depth = niveles por lado, None = todos)
+    #[pyo3(signature = (symbol, depth=None))]
+    pub fn get_snapshot(&self, symbol: &str, depth: Option<usize>) -> Option<BookSnapshot> {
+        self.books.get(symbol).map(|book| book.to_snapshot(symbol, depth))
+    }
+    
+    /// Indica si se detectó un hueco de secuencia desde la última resincronización
+    pub fn needs_resync(&self, symbol: &str) -> bool {
+        self.books.get(symbol).map(|book| book.needs_resync).unwrap_or(false)
+    }
+    
+    /// Número total de huecos de secuencia detectados para el símbolo
+    pub fn gap_count(&self, symbol: &str) -> u64 {
+        self.books.get(symbol).map(|book| book.gap_count).unwrap_or(0)
+    }
+    
+    /// Símbolos con libro
+    pub fn symbols(&self) -> Vec<String> {
+        self.books.iter().map(|entry| entry.key().clone()).collect()
+    }
+    
+    /// Elimina el libro de un símbolo
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.books.remove(symbol);
+    }
+    
+    /// Elimina todos los libros
+    pub fn reset_all(&self) {
+        self.books.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("OrderBookManager(symbols={}, interval_ms={:?})",
+                self.books.len(), self.snapshot_interval_ms)
+    }
+}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(ts: u64, side: &str, action: &str, price: f64, size: f64, seq: u64) -> BookDelta {
+        BookDelta::new(ts, "AAPL".to_string(), side.to_string(), action.to_string(), price, size, Some(seq))
+    }
+
+    #[test]
+    fn test_book_sorted_from_deltas() {
+        let manager = OrderBookManager::new(None, None);
+        manager.on_delta(&delta(1, "bid", "insert", 99.0, 10.0, 1));
+        manager.on_delta(&delta(2, "bid", "insert", 100.0, 5.0, 2));
+        manager.on_delta(&delta(3, "ask", "insert", 102.0, 7.0, 3));
+        manager.on_delta(&delta(4, "ask", "insert", 101.0, 3.0, 4));
+        
+        let snapshot = manager.get_snapshot("AAPL", None).unwrap();
+        assert_eq!(snapshot.bids[0].price, 100.0);
+        assert_eq!(snapshot.bids[1].price, 99.0);
+        assert_eq!(snapshot.asks[0].price, 101.0);
+        assert_eq!(snapshot.asks[1].price, 102.0);
+        assert_eq!(snapshot.ts, 4);
+    }
+
+    #[test]
+    fn test_book_update_and_delete() {
+        let manager = OrderBookManager::new(None, None);
+        manager.on_delta(&delta(1, "bid", "insert", 100.0, 5.0, 1));
+        manager.on_delta(&delta(2, "bid", "update", 100.0, 8.0, 2));
+        assert_eq!(manager.get_snapshot("AAPL", None).unwrap().bids[0].size, 8.0);
+        
+        manager.on_delta(&delta(3, "bid", "delete", 100.0, 0.0, 3));
+        assert!(manager.get_snapshot("AAPL", None).unwrap().bids.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_gap_detection() {
+        let manager = OrderBookManager::new(None, None);
+        manager.on_delta(&delta(1, "bid", "insert", 100.0, 5.0, 1));
+        manager.on_delta(&delta(2, "bid", "insert", 99.0, 5.0, 3));
+        
+        assert!(manager.needs_resync("AAPL"));
+        assert_eq!(manager.gap_count("AAPL"), 1);
+        
+        // Resincronizar con snapshot completo
+        let snapshot = BookSnapshot::new(3, "AAPL".to_string(),
+            vec![Level::new(100.0, 1.0)], vec![Level::new(101.0, 1.0)]);
+        manager.apply_snapshot(&snapshot, Some(10));
+        assert!(!manager.needs_resync("AAPL"));
+        
+        // Deltas antiguos se descartan
+        manager.on_delta(&delta(4, "bid", "insert", 98.0, 5.0, 9));
+        assert_eq!(manager.get_snapshot("AAPL", None).unwrap().bids.len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_cadence_and_depth() {
+        let manager = OrderBookManager::new(Some(100), Some(1));
+        assert!(manager.on_delta(&delta(1000, "bid", "insert", 100.0, 5.0, 1)).is_some());
+        assert!(manager.on_delta(&delta(1050, "bid", "insert", 99.0, 5.0, 2)).is_none());
+        
+        let snapshot = manager.on_delta(&delta(1100, "ask", "insert", 101.0, 5.0, 3)).unwrap();
+        assert_eq!(snapshot.bids.len(), 1);
+        assert_eq!(snapshot.asks.len(), 1);
+    }
+}
@@ -0,0 +1,282 @@
+//! # Integración con asyncio
+//! 
+//! Puente entre los hilos de red (con su runtime Tokio propio) y el event
+//! loop de asyncio de Python, sin bloquear el loop:
+//! - `LoopFuture` crea un `asyncio.Future` en el loop en marcha y lo resuelve
+//!   desde cualquier hilo con `call_soon_threadsafe`
+//! - `MetricsStream` es un iterador async de `MetricsBundle`
+//!   (`async for metrics in subscriber.stream()`) con buffer acotado: si
+//!   Python no consume a tiempo se descartan los bundles más antiguos
+//! 
+//! Un stream admite un solo consumidor; al detenerse el suscriptor se
+//! entregan los bundles pendientes y después termina la iteración.
+
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use crate::types::MetricsBundle;
+
+/// Bundles en buffer por stream por defecto
+pub const DEFAULT_STREAM_CAPACITY: usize = 1024;
+
+/// `asyncio.Future` ligado al loop en el que se creó
+pub struct LoopFuture {
+    event_loop: PyObject,
+    future: PyObject,
+}
+
+impl LoopFuture {
+    /// Crea el future en el loop en marcha (RuntimeError fuera de una corrutina)
+    pub fn new(py: Python<'_>) -> PyResult<Self> {
+        let event_loop = py.import_bound("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+        Ok(Self { event_loop: event_loop.unbind(), future: future.unbind() })
+    }
+    
+    pub fn future(&self, py: Python<'_>) -> PyObject {
+        self.future.clone_ref(py)
+    }
+    
+    pub fn clone_ref(&self, py: Python<'_>) -> Self {
+        Self { event_loop: self.event_loop.clone_ref(py), future: self.future(py) }
+    }
+    
+    /// Si el future ya terminó (p. ej. porque se canceló el await)
+    pub fn is_done(&self, py: Python<'_>) -> bool {
+        self.future.call_method0(py, "done").and_then(|done| done.extract(py)).unwrap_or(true)
+    }
+    
+    pub fn set_result(&self, py: Python<'_>, value: PyObject) {
+        self.complete(py, "set_result", value);
+    }
+    
+    pub fn set_exception(&self, py: Python<'_>, error: PyErr) {
+        self.complete(py, "set_exception", error.into_value(py).into_py(py));
+    }
+    
+    /// Programa la resolución en el hilo del loop. Si para entonces el future
+    /// ya terminó no hace nada; con el loop cerrado se ignora.
+    fn complete(&self, py: Python<'_>, method: &'static str, value: PyObject) {
+        let future = self.future.clone_ref(py);
+        let callback = PyCFunction::new_closure_bound(py, None, None,
+            move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+                let future = future.bind(args.py());
+                if !future.call_method0("done")?.extract::<bool>()? {
+                    future.call_method1(method, (value.clone_ref(args.py()),))?;
+                }
+                Ok(())
+            });
+        let scheduled = callback.and_then(|callback| {
+            self.event_loop.call_method1(py, "call_soon_threadsafe", (callback,))
+        });
+        if let Err(e) = scheduled {
+            tracing::debug!(error = %e, "asyncio loop unavailable");
+        }
+    }
+}
+
+#[derive(Default)]
+struct StreamState {
+    buffer: VecDeque<MetricsBundle>,
+    // `__anext__` pendiente (solo con el buffer vacío)
+    waiter: Option<LoopFuture>,
+    closed: bool,
+    dropped: u64,
+}
+
+/// Estado compartido entre el hilo de red y el `MetricsStream` de Python
+struct StreamShared {
+    capacity: usize,
+    state: Mutex<StreamState>,
+}
+
+impl StreamShared {
+    fn push(&self, bundle: &MetricsBundle) {
+        let waiter = {
+            let mut state = self.state.lock();
+            if state.closed {
+                return;
+            }
+            match state.waiter.take() {
+                Some(waiter) => waiter,
+                None => {
+                    if state.buffer.len() >= self.capacity {
+                        state.buffer.pop_front();
+                        state.dropped += 1;
+                    }
+                    state.buffer.push_back(bundle.clone());
+                    return;
+                }
+            }
+        };
+        // El GIL se toma sin el lock: `__anext__` lo toma con el GIL
+        let delivered = Python::with_gil(|py| {
+            if waiter.is_done(py) {
+                return false;
+            }
+            waiter.set_result(py, bundle.clone().into_py(py));
+            true
+        });
+        // El await se canceló: el bundle queda para el siguiente `__anext__`
+        if !delivered {
+            self.push(bundle);
+        }
+    }
+    
+    fn close(&self) {
+        let waiter = {
+            let mut state = self.state.lock();
+            state.closed = true;
+            state.waiter.take()
+        };
+        if let Some(waiter) = waiter {
+            Python::with_gil(|py| {
+                waiter.set_exception(py, pyo3::exceptions::PyStopAsyncIteration::new_err(()));
+            });
+        }
+    }
+    
+    fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+}
+
+/// Streams abiertos de un suscriptor
+#[derive(Default)]
+pub struct MetricStreams {
+    streams: Mutex<Vec<Arc<StreamShared>>>,
+}
+
+impl MetricStreams {
+    pub fn open(&self, capacity: usize) -> PyResult<MetricsStream> {
+        if capacity == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("capacity must be > 0"));
+        }
+        let shared = Arc::new(StreamShared { capacity, state: Mutex::new(StreamState::default()) });
+        let mut streams = self.streams.lock();
+        streams.retain(|s| !s.is_closed());
+        streams.push(shared.clone());
+        Ok(MetricsStream { shared })
+    }
+    
+    /// Entrega un bundle no vacío a todos los streams abiertos
+    pub fn publish(&self, bundle: &MetricsBundle) {
+        if bundle.is_empty() {
+            return;
+        }
+        // Copia de la lista para no retener el lock mientras se toma el GIL
+        let streams = self.streams.lock().clone();
+        for stream in streams {
+            stream.push(bundle);
+        }
+    }
+    
+    /// Cierra todos los streams (fin de la iteración tras vaciar su buffer)
+    pub fn close_all(&self) {
+        let streams = std::mem::take(&mut *self.streams.lock());
+        for stream in streams {
+            stream.close();
+        }
+    }
+}
+
+/// Iterador async de los `MetricsBundle` de un suscriptor
+#[pyclass]
+pub struct MetricsStream {
+    shared: Arc<StreamShared>,
+}
+
+#[pymethods]
+impl MetricsStream {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    /// Awaitable con el siguiente bundle; StopAsyncIteration al cerrarse
+    fn __anext__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        // El future se crea antes del lock: no se llama a Python con él tomado
+        let next = LoopFuture::new(py)?;
+        let (bundle, replaced) = {
+            let mut state = self.shared.state.lock();
+            match state.buffer.pop_front() {
+                Some(bundle) => (Some(bundle), None),
+                None if state.closed => return Err(pyo3::exceptions::PyStopAsyncIteration::new_err(())),
+                None => (None, state.waiter.replace(next.clone_ref(py))),
+            }
+        };
+        if let Some(bundle) = bundle {
+            next.future.call_method1(py, "set_result", (bundle.into_py(py),))?;
+        }
+        // Un solo consumidor: un `__anext__` anterior sin terminar se cancela
+        if let Some(replaced) = replaced {
+            replaced.future.call_method0(py, "cancel")?;
+        }
+        Ok(next.future(py))
+    }
+    
+    /// Deja de recibir bundles; un `__anext__` pendiente termina la iteración
+    fn close(&self) {
+        self.shared.close();
+    }
+    
+    #[getter]
+    fn closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+    
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+    
+    /// Bundles descartados por buffer lleno
+    #[getter]
+    fn dropped(&self) -> u64 {
+        self.shared.state.lock().dropped
+    }
+    
+    /// Bundles en buffer pendientes de consumir
+    fn __len__(&self) -> usize {
+        self.shared.state.lock().buffer.len()
+    }
+    
+    fn __repr__(&self) -> String {
+        let state = self.shared.state.lock();
+        format!("MetricsStream(buffered={}, dropped={}, closed={})", state.buffer.len(), state.dropped, state.closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::EngineManager;
+    use crate::types::Trade;
+    
+    fn bundle(ts: u64) -> MetricsBundle {
+        let manager = EngineManager::new(true, false, false, false, None, None, None).unwrap();
+        manager.on_trade(&Trade::new(ts, 100.0, 1.0, "AAPL".to_string()))
+    }
+    
+    #[test]
+    fn test_stream_buffer_drops_oldest() {
+        let streams = MetricStreams::default();
+        let stream = streams.open(2).unwrap();
+        assert!(streams.open(0).is_err());
+        
+        streams.publish(&MetricsBundle::empty("AAPL", 500));
+        assert_eq!(stream.__len__(), 0);
+        for ts in [1_000, 2_000, 3_000] {
+            streams.publish(&bundle(ts));
+        }
+        assert_eq!((stream.__len__(), stream.dropped()), (2, 1));
+        assert_eq!(stream.shared.state.lock().buffer[0].ts, 2_000);
+        
+        // Tras cerrar se conservan los pendientes pero no entran más
+        streams.close_all();
+        streams.publish(&bundle(4_000));
+        assert!(stream.closed());
+        assert_eq!(stream.__len__(), 2);
+    }
+}
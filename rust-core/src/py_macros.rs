@@ -0,0 +1,129 @@
+//! # py_class! / py_methods!
+//!
+//! Envuelven un `#[pyclass] struct` y un `#[pymethods] impl`: con la
+//! feature `python` los dejan tal cual y sin ella quitan los atributos de
+//! PyO3, de modo que los engines exponen a Python los mismos métodos que
+//! usa Rust sin duplicarlos ni depender de libpython.
+//!
+//! ```ignore
+//! py_class! {
+//! #[pyclass]
+//! pub struct ATREngine {
+//!     #[pyo3(get)]
+//!     pub periods: Vec<usize>,
+//!     state: Arc<DashMap<(String, String, usize), ATRState>>,
+//! }
+//! }
+//!
+//! py_methods! {
+//! #[pymethods]
+//! impl ATREngine {
+//!     #[new]
+//!     #[pyo3(signature = (periods=vec![14]))]
+//!     pub fn new(periods: Vec<usize>) -> Result<Self, ProcessError> { ... }
+//!
+//!     #[cfg(feature = "python")]
+//!     fn __repr__(&self) -> String { ... }
+//! }
+//! }
+//! ```
+//!
+//! Sin `python` se quitan `#[pyclass]`, `#[pymethods]`, los `#[pyo3(...)]`
+//! de campos y métodos, `#[new]`, `#[staticmethod]`, `#[getter]` y
+//! `#[setter]`; el resto (docs, `#[cfg]`, `#[serde]`, `#[allow]`) se
+//! conserva. Los métodos que usan tipos de Python (`Python<'_>`, `Bound`,
+//! numpy) y los dunder van con `#[cfg(feature = "python")]`.
+
+#[cfg(feature = "python")]
+macro_rules! py_class {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "python"))]
+macro_rules! py_class {
+    ($(#[doc $($doc:tt)*])* #[pyclass $($args:tt)*] $(#[$($attr:tt)*])* $vis:vis struct $name:ident { $($fields:tt)* }) => {
+        $crate::py_macros::strip_py_fields! {
+            [$(#[doc $($doc)*])* $(#[$($attr)*])* $vis struct $name] [] [] $($fields)*
+        }
+    };
+}
+
+/// Recorre los campos acumulando `[cabecera] [campos hechos] [atributos
+/// del campo en curso]` y descarta sus `#[pyo3(...)]`
+#[cfg(not(feature = "python"))]
+macro_rules! strip_py_fields {
+    ([$($head:tt)*] [$($done:tt)*] [$($cur:tt)*] #[pyo3 $($args:tt)*] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_fields! { [$($head)*] [$($done)*] [$($cur)*] $($rest)* }
+    };
+    ([$($head:tt)*] [$($done:tt)*] [$($cur:tt)*] #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_fields! { [$($head)*] [$($done)*] [$($cur)* #[$($attr)*]] $($rest)* }
+    };
+    ([$($head:tt)*] [$($done:tt)*] [$($cur:tt)*] $fvis:vis $field:ident : $ty:ty $(, $($rest:tt)*)?) => {
+        $crate::py_macros::strip_py_fields! { [$($head)*] [$($done)* $($cur)* $fvis $field: $ty,] [] $($($rest)*)? }
+    };
+    ([$($head:tt)*] [$($done:tt)*] []) => {
+        $($head)* { $($done)* }
+    };
+}
+
+#[cfg(feature = "python")]
+macro_rules! py_methods {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "python"))]
+macro_rules! py_methods {
+    ($(#[doc $($doc:tt)*])* #[pymethods] impl $class:ident {
+        $(
+            $(#[$($attr:tt)*])*
+            $vis:vis fn $name:ident $(<$($lt:lifetime),*>)? ($($args:tt)*) $(-> $ret:ty)? $body:block
+        )*
+    }) => {
+        $(#[doc $($doc)*])*
+        impl $class {
+            $(
+                $crate::py_macros::strip_py_attrs! {
+                    [] $(#[$($attr)*])*
+                    $vis fn $name $(<$($lt),*>)? ($($args)*) $(-> $ret)? $body
+                }
+            )*
+        }
+    };
+}
+
+/// Acumula en `[...]` los atributos del método que no son de PyO3 y lo emite
+#[cfg(not(feature = "python"))]
+macro_rules! strip_py_attrs {
+    ([$($kept:tt)*] #[new] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[staticmethod] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[getter $($args:tt)*] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[setter $($args:tt)*] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[pyo3 $($args:tt)*] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::py_macros::strip_py_attrs! { [$($kept)* #[$($attr)*]] $($rest)* }
+    };
+    ([$($kept:tt)*] $($item:tt)*) => {
+        $($kept)* $($item)*
+    };
+}
+
+pub(crate) use py_class;
+pub(crate) use py_methods;
+#[cfg(not(feature = "python"))]
+pub(crate) use strip_py_attrs;
+#[cfg(not(feature = "python"))]
+pub(crate) use strip_py_fields;
@@ -0,0 +1,249 @@
+//! # Conversión serde <-> Python
+//! 
+//! Los tipos de `crate::types` exponen `to_dict` / `from_dict` y `to_json` /
+//! `from_json` a partir de su `Serialize` / `Deserialize`, así que Python
+//! puede hacer round-trip de cualquier métrica sin mapear campos a mano.
+//! 
+//! Los diccionarios usan los mismos nombres de campo que el JSON. `from_dict`
+//! acepta también objetos anidados con `to_dict` (p. ej. una lista de
+//! `Level` en un `BookSnapshot`). Los f64 no finitos se serializan como None.
+//! 
+//! Para pickle (multiprocessing, cachés) `__reduce__` guarda el estado en
+//! MessagePack con nombres de campo, que sí conserva NaN/inf y tolera campos
+//! nuevos con `#[serde(default)]`; `__deepcopy__` clona el valor en Rust.
+//! `_unpickle` se busca en el `__module__` de la clase, así que cada clase
+//! declara su módulo en `#[pyclass(module = ...)]`.
+//! 
+//! `impl_py_serde!` genera todos estos métodos para una lista de clases (con
+//! la feature `multiple-pymethods` de pyo3, en un `#[pymethods]` aparte).
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use pyo3::PyTypeInfo;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+/// Métodos Python de serialización para cada clase de la lista (que debe
+/// implementar `Clone`, `Serialize` y `Deserialize`)
+macro_rules! impl_py_serde {
+    ($($class:ty),* $(,)?) => {
+        $(
+            #[pyo3::pymethods]
+            impl $class {
+                /// Dict con los campos (mismos nombres que el JSON)
+                fn to_dict(&self, py: pyo3::Python<'_>) -> pyo3::PyResult<pyo3::PyObject> {
+                    $crate::py_serde::to_dict(py, self)
+                }
+                
+                #[staticmethod]
+                fn from_dict(data: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
+                    $crate::py_serde::from_dict(data)
+                }
+                
+                fn to_json(&self) -> pyo3::PyResult<String> {
+                    $crate::py_serde::to_json(self)
+                }
+                
+                #[staticmethod]
+                fn from_json(json: &str) -> pyo3::PyResult<Self> {
+                    $crate::py_serde::from_json(json)
+                }
+                
+                #[staticmethod]
+                fn from_msgpack(data: &[u8]) -> pyo3::PyResult<Self> {
+                    $crate::py_serde::from_msgpack(data)
+                }
+                
+                fn __reduce__(&self, py: pyo3::Python<'_>)
+                              -> pyo3::PyResult<(pyo3::PyObject, (&'static str, pyo3::PyObject))> {
+                    $crate::py_serde::reduce(py, self)
+                }
+                
+                fn __deepcopy__(&self, _memo: &pyo3::Bound<'_, pyo3::PyAny>) -> Self {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+pub(crate) use impl_py_serde;
+
+/// Nombre corto del tipo para los mensajes de error
+fn type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn invalid<T>(e: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("invalid {}: {}", type_name::<T>(), e))
+}
+
+/// Valor JSON como objeto Python (dict, list, str, int, float, bool o None)
+pub fn value_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => (*b).into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.as_str().into_py(py),
+        Value::Array(items) => {
+            let items = items.iter().map(|item| value_to_py(py, item)).collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, items).into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, item) in map {
+                dict.set_item(key, value_to_py(py, item)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+/// Objeto Python como valor JSON
+pub fn py_to_value(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    // bool antes que int: en Python bool es subclase de int
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if obj.is_instance_of::<PyLong>() {
+        return match obj.extract::<i64>() {
+            Ok(i) => Ok(Value::from(i)),
+            Err(_) => Ok(Value::from(obj.extract::<u64>()?)),
+        };
+    }
+    if let Ok(f) = obj.downcast::<PyFloat>() {
+        return Number::from_f64(f.value()).map(Value::Number).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("non-finite floats are not supported")
+        });
+    }
+    if obj.is_instance_of::<PyString>() {
+        return Ok(Value::String(obj.extract()?));
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = Map::new();
+        for (key, item) in dict.iter() {
+            map.insert(key.extract::<String>()?, py_to_value(&item)?);
+        }
+        return Ok(Value::Object(map));
+    }
+    if let Ok(list) = obj.downcast::<PyList>() {
+        return list.iter().map(|item| py_to_value(&item)).collect::<PyResult<Vec<_>>>().map(Value::Array);
+    }
+    if let Ok(tuple) = obj.downcast::<PyTuple>() {
+        return tuple.iter().map(|item| py_to_value(&item)).collect::<PyResult<Vec<_>>>().map(Value::Array);
+    }
+    // Objetos anidados de este módulo (Level, Tile, ...)
+    if obj.hasattr("to_dict")? {
+        return py_to_value(&obj.call_method0("to_dict")?);
+    }
+    Err(pyo3::exceptions::PyTypeError::new_err(format!(
+        "unsupported type: {}", obj.get_type().name()?)))
+}
+
+/// Serializa el valor como dict de Python
+pub fn to_dict<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(invalid::<T>)?;
+    value_to_py(py, &value)
+}
+
+/// Construye el valor a partir de un dict (faltan campos => ValueError)
+pub fn from_dict<T: DeserializeOwned>(data: &Bound<'_, PyAny>) -> PyResult<T> {
+    serde_json::from_value(py_to_value(data)?).map_err(invalid::<T>)
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> PyResult<String> {
+    serde_json::to_string(value).map_err(invalid::<T>)
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> PyResult<T> {
+    serde_json::from_str(json).map_err(invalid::<T>)
+}
+
+pub fn from_msgpack<T: DeserializeOwned>(data: &[u8]) -> PyResult<T> {
+    rmp_serde::from_slice(data).map_err(invalid::<T>)
+}
+
+/// `__reduce__`: (`_unpickle` del módulo de la clase, (nombre de la clase,
+/// estado en MessagePack))
+pub fn reduce<T: Serialize + PyTypeInfo>(py: Python<'_>, value: &T) -> PyResult<(PyObject, (&'static str, PyObject))> {
+    let state = rmp_serde::to_vec_named(value).map_err(invalid::<T>)?;
+    let module: String = T::type_object_bound(py).getattr("__module__")?.extract()?;
+    let unpickle = py.import_bound(module.as_str())?.getattr("_unpickle")?;
+    Ok((unpickle.unbind(), (T::NAME, PyBytes::new_bound(py, &state).into_py(py))))
+}
+
+/// Reconstruye un objeto guardado con `__reduce__`
+#[pyfunction]
+#[pyo3(name = "_unpickle", pass_module)]
+pub fn unpickle(module: &Bound<'_, PyModule>, class: &str, state: &Bound<'_, PyBytes>) -> PyResult<PyObject> {
+    Ok(module.getattr(class)?.call_method1("from_msgpack", (state,))?.unbind())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BookSnapshot, Level, Trade};
+    use crate::utils::with_py;
+    
+    #[test]
+    fn test_json_round_trip() {
+        let trade = Trade::new(1_000, 100.5, 2.0, "AAPL".to_string());
+        let json = to_json(&trade).unwrap();
+        let back: Trade = from_json(&json).unwrap();
+        assert_eq!((back.ts, back.price, back.size, back.symbol.as_str()), (1_000, 100.5, 2.0, "AAPL"));
+        assert!(from_json::<Trade>("{\"ts\": 1}").is_err());
+    }
+    
+    #[test]
+    fn test_msgpack_keeps_non_finite_floats() {
+        let trade = Trade::new(1_000, f64::NAN, f64::INFINITY, "AAPL".to_string());
+        let back: Trade = from_msgpack(&rmp_serde::to_vec_named(&trade).unwrap()).unwrap();
+        assert!(back.price.is_nan());
+        assert_eq!(back.size, f64::INFINITY);
+        assert!(from_msgpack::<Trade>(b"garbage").is_err());
+    }
+    
+    #[test]
+    fn test_dict_round_trip_with_nested_objects() {
+        with_py(|py| {
+            let snapshot = BookSnapshot::new(1_000, "AAPL".to_string(),
+                                             vec![Level::new(99.0, 1.0)], vec![Level::new(101.0, 2.0)]);
+            let dict = to_dict(py, &snapshot).unwrap();
+            let dict = dict.bind(py).downcast::<PyDict>().unwrap().clone();
+            assert_eq!(dict.get_item("ts").unwrap().unwrap().extract::<u64>().unwrap(), 1_000);
+            
+            // Niveles como objetos Level en lugar de dicts
+            let level = Py::new(py, Level::new(100.0, 5.0)).unwrap();
+            dict.set_item("bids", PyList::new_bound(py, [level])).unwrap();
+            let back: BookSnapshot = from_dict(dict.as_any()).unwrap();
+            assert_eq!((back.bids[0].price, back.asks[0].size), (100.0, 2.0));
+        });
+    }
+    
+    #[test]
+    fn test_pickle_round_trip() {
+        with_py(|py| {
+            // Módulo registrado con el nombre que declaran las clases
+            let module = PyModule::new_bound(py, "indicators_core").unwrap();
+            module.add_class::<Trade>().unwrap();
+            module.add_function(wrap_pyfunction!(unpickle, &module).unwrap()).unwrap();
+            py.import_bound("sys").unwrap().getattr("modules").unwrap()
+                .set_item("indicators_core", &module).unwrap();
+            
+            let trade = Py::new(py, Trade::new(1_000, f64::NAN, 2.0, "AAPL".to_string())).unwrap();
+            let pickle = py.import_bound("pickle").unwrap();
+            let data = pickle.call_method1("dumps", (trade,)).unwrap();
+            let back: Trade = pickle.call_method1("loads", (data,)).unwrap().extract().unwrap();
+            assert!(back.price.is_nan());
+            assert_eq!((back.ts, back.size, back.symbol.as_str()), (1_000, 2.0, "AAPL"));
+        });
+    }
+}
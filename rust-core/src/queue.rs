@@ -0,0 +1,242 @@
+//! # Bounded Queue
+//! 
+//! Cola acotada entre la recepción de mensajes y su procesamiento. Cuando
+//! está llena aplica la política de desbordamiento configurada:
+//! - `block`: el productor espera hueco (la presión se propaga al origen)
+//! - `drop-oldest`: se descarta el elemento más antiguo de la cola
+//! - `drop-newest`: se descarta el elemento que llega
+//! 
+//! Los elementos descartados se devuelven al productor para que pueda
+//! liberarlos (p. ej. confirmar el mensaje en JetStream).
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::Notify;
+
+/// Política cuando la cola está llena
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    #[default]
+    Block,
+    DropOldest,
+    DropNewest,
+}
+
+impl OverflowPolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "block" => Some(Self::Block),
+            "drop-oldest" => Some(Self::DropOldest),
+            "drop-newest" => Some(Self::DropNewest),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::DropOldest => "drop-oldest",
+            Self::DropNewest => "drop-newest",
+        }
+    }
+}
+
+/// Cola acotada multi-productor / multi-consumidor para tareas async
+pub struct BoundedQueue<T> {
+    items: Mutex<VecDeque<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    closed: AtomicBool,
+    not_empty: Notify,
+    not_full: Notify,
+    dropped: AtomicU64,
+    high_watermark: AtomicU64,
+}
+
+impl<T> BoundedQueue<T> {
+    /// None si capacity es 0
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Option<Self> {
+        if capacity == 0 {
+            return None;
+        }
+        Some(Self {
+            items: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+            capacity,
+            policy,
+            closed: AtomicBool::new(false),
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+            dropped: AtomicU64::new(0),
+            high_watermark: AtomicU64::new(0),
+        })
+    }
+    
+    /// Encola un elemento. Devuelve el elemento descartado por la política
+    /// (el más antiguo o el propio `item`), o `item` si la cola está cerrada.
+    pub async fn push(&self, item: T) -> Option<T> {
+        let mut item = item;
+        loop {
+            // Registrar el interés antes de comprobar para no perder avisos
+            let not_full = self.not_full.notified();
+            match self.try_push(item) {
+                Err(back) if !self.is_closed() => {
+                    item = back;
+                    not_full.await;
+                }
+                Err(back) => return Some(back),
+                Ok(dropped) => return dropped,
+            }
+        }
+    }
+    
+    /// Intento sin espera: Err(item) solo si la política es `block` y la
+    /// cola está llena, o si está cerrada
+    fn try_push(&self, item: T) -> Result<Option<T>, T> {
+        if self.is_closed() {
+            return Err(item);
+        }
+        let mut items = self.items.lock();
+        let dropped = if items.len() < self.capacity {
+            None
+        } else {
+            match self.policy {
+                OverflowPolicy::Block => return Err(item),
+                OverflowPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(Some(item));
+                }
+                OverflowPolicy::DropOldest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    items.pop_front()
+                }
+            }
+        };
+        items.push_back(item);
+        self.high_watermark.fetch_max(items.len() as u64, Ordering::Relaxed);
+        drop(items);
+        self.not_empty.notify_one();
+        Ok(dropped)
+    }
+    
+    /// Desencola esperando si está vacía; None cuando está cerrada y vacía
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            let not_empty = self.not_empty.notified();
+            if let Some(item) = self.try_pop() {
+                return Some(item);
+            }
+            if self.is_closed() {
+                return None;
+            }
+            not_empty.await;
+        }
+    }
+    
+    pub fn try_pop(&self) -> Option<T> {
+        let item = self.items.lock().pop_front();
+        if item.is_some() {
+            self.not_full.notify_one();
+        }
+        item
+    }
+    
+    /// Cierra la cola: los productores dejan de encolar y los consumidores
+    /// terminan al vaciarla
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.not_empty.notify_waiters();
+        self.not_full.notify_waiters();
+    }
+    
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.items.lock().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.items.lock().is_empty()
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    /// Elementos descartados por desbordamiento
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    
+    /// Ocupación máxima observada
+    pub fn high_watermark(&self) -> u64 {
+        self.high_watermark.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(future)
+    }
+    
+    #[test]
+    fn test_overflow_policy_parse() {
+        assert_eq!(OverflowPolicy::parse("drop_oldest"), Some(OverflowPolicy::DropOldest));
+        assert_eq!(OverflowPolicy::parse("Drop-Newest"), Some(OverflowPolicy::DropNewest));
+        assert_eq!(OverflowPolicy::parse("block"), Some(OverflowPolicy::Block));
+        assert_eq!(OverflowPolicy::parse("spill"), None);
+        assert!(BoundedQueue::<u32>::new(0, OverflowPolicy::Block).is_none());
+    }
+    
+    #[test]
+    fn test_drop_oldest_and_newest() {
+        block_on(async {
+            let queue = BoundedQueue::new(2, OverflowPolicy::DropOldest).unwrap();
+            assert_eq!(queue.push(1).await, None);
+            assert_eq!(queue.push(2).await, None);
+            assert_eq!(queue.push(3).await, Some(1));
+            assert_eq!(queue.pop().await, Some(2));
+            assert_eq!(queue.dropped(), 1);
+            
+            let queue = BoundedQueue::new(2, OverflowPolicy::DropNewest).unwrap();
+            queue.push(1).await;
+            queue.push(2).await;
+            assert_eq!(queue.push(3).await, Some(3));
+            assert_eq!(queue.pop().await, Some(1));
+            assert_eq!(queue.pop().await, Some(2));
+            assert_eq!(queue.dropped(), 1);
+            assert_eq!(queue.high_watermark(), 2);
+        });
+    }
+    
+    #[test]
+    fn test_block_waits_for_consumer() {
+        block_on(async {
+            let queue = BoundedQueue::new(1, OverflowPolicy::Block).unwrap();
+            queue.push(1).await;
+            
+            let producer = async {
+                assert_eq!(queue.push(2).await, None);
+                queue.close();
+            };
+            let consumer = async {
+                let mut seen = Vec::new();
+                while let Some(item) = queue.pop().await {
+                    seen.push(item);
+                }
+                seen
+            };
+            let ((), seen) = tokio::join!(producer, consumer);
+            assert_eq!(seen, vec![1, 2]);
+            assert_eq!(queue.dropped(), 0);
+            
+            // Cerrada: se devuelve el elemento sin encolar
+            assert_eq!(queue.push(3).await, Some(3));
+        });
+    }
+}
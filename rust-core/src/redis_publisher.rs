@@ -0,0 +1,419 @@
+//! # Redis Publisher
+//! 
+//! Publica las métricas en Redis (feature `redis`), en Streams (`XADD`) o en
+//! canales pub/sub (`PUBLISH`). La clave o canal sale de una plantilla con
+//! `{kind}` y `{symbol}` (por defecto `indicators:{kind}:{symbol}`),
+//! configurable por tipo.
+//! 
+//! Los mensajes se encolan desde Python y un hilo propio los envía en
+//! pipelines de hasta `batch_size` comandos. En modo stream cada entrada
+//! lleva el campo `data` con la métrica serializada y, con `max_len`, el
+//! stream se recorta de forma aproximada (`MAXLEN ~`).
+//! 
+//! La conexión se abre al primer envío; si un pipeline falla se reconecta y
+//! se reintenta una vez, y si vuelve a fallar el lote se descarta (`failed`).
+
+use parking_lot::Mutex;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::nats_publisher::{render_subject, KIND_CVD, KIND_HEATMAP, KIND_LIQUIDITY, KIND_VWAP};
+use crate::types::{CVDMetrics, HeatmapMetrics, LiquidityMetrics, MetricsBundle, VWAPMetrics};
+use crate::wire::{encode, WireFormat};
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Espera máxima al conectar
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Mensaje pendiente de publicar: (clave o canal, payload)
+type Outgoing = (String, Vec<u8>);
+
+/// Destino de las métricas en Redis
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedisMode {
+    Stream,
+    PubSub,
+}
+
+impl RedisMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "stream" | "streams" => Some(Self::Stream),
+            "pubsub" | "pub/sub" => Some(Self::PubSub),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stream => "stream",
+            Self::PubSub => "pubsub",
+        }
+    }
+}
+
+/// Pipeline con un XADD o PUBLISH por mensaje
+fn build_pipeline(batch: &[Outgoing], mode: RedisMode, max_len: Option<usize>) -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+    for (key, payload) in batch {
+        match mode {
+            RedisMode::Stream => {
+                let cmd = pipe.cmd("XADD").arg(key);
+                if let Some(max_len) = max_len {
+                    cmd.arg("MAXLEN").arg("~").arg(max_len);
+                }
+                cmd.arg("*").arg("data").arg(payload.as_slice()).ignore();
+            }
+            RedisMode::PubSub => {
+                pipe.cmd("PUBLISH").arg(key).arg(payload.as_slice()).ignore();
+            }
+        }
+    }
+    pipe
+}
+
+/// Contadores compartidos entre el hilo del publisher y Python
+#[derive(Default)]
+struct PublisherStats {
+    queued: AtomicU64,
+    published: AtomicU64,
+    failed: AtomicU64,
+    errors: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl PublisherStats {
+    fn record_error(&self, error: String) {
+        tracing::warn!(error = %error, "redis publisher error");
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock() = Some(error);
+    }
+}
+
+py_class! {
+/// Publisher de métricas hacia Redis
+#[pyclass]
+pub struct RedisPublisher {
+    #[pyo3(get)]
+    pub url: String,
+    #[pyo3(get)]
+    pub key_template: String,
+    #[pyo3(get)]
+    pub batch_size: usize,
+    /// Longitud máxima aproximada de cada stream (solo modo stream)
+    #[pyo3(get)]
+    pub max_len: Option<usize>,
+    mode: RedisMode,
+    format: WireFormat,
+    // Plantillas por tipo de métrica que sustituyen a key_template
+    keys: HashMap<String, String>,
+    stats: Arc<PublisherStats>,
+    running: Arc<AtomicBool>,
+    tx: Option<mpsc::Sender<Outgoing>>,
+    worker: Option<JoinHandle<()>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl RedisPublisher {
+    #[new]
+    #[pyo3(signature = (url, key_template="indicators:{kind}:{symbol}".to_string(), mode="stream",
+                        max_len=None, batch_size=100, wire_format="json"))]
+    pub fn new(url: String, key_template: String, mode: &str, max_len: Option<usize>, batch_size: usize,
+               wire_format: &str) -> Result<Self, ProcessError> {
+        let mode = RedisMode::parse(mode).ok_or_else(|| ProcessError::invalid(
+            format!("invalid mode {}: must be stream or pubsub", mode)))?;
+        let format = match WireFormat::parse(wire_format) {
+            Some(format @ (WireFormat::Json | WireFormat::MessagePack)) => format,
+            _ => return Err(ProcessError::invalid(
+                format!("invalid wire_format {}: must be json or msgpack", wire_format))),
+        };
+        if batch_size == 0 || max_len == Some(0) {
+            return Err(ProcessError::invalid("batch_size and max_len must be > 0"));
+        }
+        Ok(Self {
+            url,
+            key_template,
+            batch_size,
+            max_len,
+            mode,
+            format,
+            keys: HashMap::new(),
+            stats: Arc::new(PublisherStats::default()),
+            running: Arc::new(AtomicBool::new(false)),
+            tx: None,
+            worker: None,
+        })
+    }
+    
+    #[getter]
+    pub fn mode(&self) -> &'static str {
+        self.mode.as_str()
+    }
+    
+    #[getter]
+    pub fn wire_format(&self) -> &'static str {
+        self.format.as_str()
+    }
+    
+    /// Plantilla de clave propia de un tipo ("cvd", "vwap", "liquidity", "heatmap")
+    pub fn set_key(&mut self, kind: &str, template: &str) {
+        self.keys.insert(kind.to_string(), template.to_string());
+    }
+    
+    /// Clave (stream) o canal (pub/sub) de un tipo de métrica de un símbolo
+    pub fn key_for(&self, kind: &str, symbol: &str) -> String {
+        let template = self.keys.get(kind).unwrap_or(&self.key_template);
+        render_subject(template, kind, symbol)
+    }
+    
+    /// Arranca el hilo de publicación; la URL se valida aquí
+    pub fn start(&mut self) -> Result<(), ProcessError> {
+        if self.is_running() {
+            return Err(ProcessError::runtime("publisher already running"));
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        
+        let client = redis::Client::open(self.url.as_str())
+            .map_err(|e| ProcessError::invalid(format!("Redis URL error: {}", e)))?;
+        let (tx, rx) = mpsc::channel();
+        let mode = self.mode;
+        let max_len = self.max_len;
+        let batch_size = self.batch_size;
+        let stats = self.stats.clone();
+        let running = self.running.clone();
+        
+        running.store(true, Ordering::SeqCst);
+        let worker = std::thread::Builder::new()
+            .name("redis-publisher".to_string())
+            .spawn(move || {
+                publish_loop(client, mode, max_len, batch_size, rx, &stats);
+                running.store(false, Ordering::SeqCst);
+            })
+            .map_err(|e| {
+                self.running.store(false, Ordering::SeqCst);
+                ProcessError::runtime(format!("thread spawn error: {}", e))
+            })?;
+        
+        self.tx = Some(tx);
+        self.worker = Some(worker);
+        Ok(())
+    }
+    
+    /// Publica lo pendiente y espera al hilo
+    pub fn stop(&mut self) {
+        // Cerrar el canal: el hilo vacía la cola y termina
+        self.tx = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+    
+    #[getter]
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+    
+    /// Último error de conexión, serialización o publicación
+    #[getter]
+    pub fn last_error(&self) -> Option<String> {
+        self.stats.last_error.lock().clone()
+    }
+    
+    /// Contadores: queued, published, failed (mensajes descartados), errors
+    pub fn stats(&self) -> HashMap<String, u64> {
+        HashMap::from([
+            ("queued".to_string(), self.stats.queued.load(Ordering::Relaxed)),
+            ("published".to_string(), self.stats.published.load(Ordering::Relaxed)),
+            ("failed".to_string(), self.stats.failed.load(Ordering::Relaxed)),
+            ("errors".to_string(), self.stats.errors.load(Ordering::Relaxed)),
+        ])
+    }
+    
+    pub fn publish_cvd(&self, symbol: &str, metrics: &CVDMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_CVD, symbol, metrics)
+    }
+    
+    pub fn publish_vwap(&self, symbol: &str, metrics: &VWAPMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_VWAP, symbol, metrics)
+    }
+    
+    pub fn publish_liquidity(&self, symbol: &str, metrics: &LiquidityMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_LIQUIDITY, symbol, metrics)
+    }
+    
+    pub fn publish_heatmap(&self, metrics: &HeatmapMetrics) -> Result<(), ProcessError> {
+        self.publish(KIND_HEATMAP, &metrics.symbol, metrics)
+    }
+    
+    /// Publica todas las métricas presentes en el resultado del `EngineManager`
+    pub fn publish_bundle(&self, bundle: &MetricsBundle) -> Result<(), ProcessError> {
+        if let Some(m) = &bundle.cvd {
+            self.publish(KIND_CVD, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.vwap {
+            self.publish(KIND_VWAP, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.liquidity {
+            self.publish(KIND_LIQUIDITY, &bundle.symbol, m)?;
+        }
+        if let Some(m) = &bundle.heatmap {
+            self.publish(KIND_HEATMAP, &bundle.symbol, m)?;
+        }
+        Ok(())
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("RedisPublisher(url={}, key_template={}, mode={}, running={})",
+                self.url, self.key_template, self.mode.as_str(), self.is_running())
+    }
+}
+}
+
+impl RedisPublisher {
+    /// Serializa y encola una métrica; falla si el publisher no está arrancado
+    pub fn publish<T: Serialize>(&self, kind: &str, symbol: &str, metrics: &T) -> Result<(), ProcessError> {
+        let tx = self.tx.as_ref().filter(|_| self.is_running()).ok_or_else(|| {
+            ProcessError::runtime("publisher not running")
+        })?;
+        let payload = encode(self.format, metrics).map_err(ProcessError::invalid)?;
+        tx.send((self.key_for(kind, symbol), payload))
+            .map_err(|_| ProcessError::runtime("publisher not running"))?;
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Drop for RedisPublisher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Bucle del publisher: agrupa lo encolado en pipelines hasta que se cierra
+/// el canal y se vacía la cola
+fn publish_loop(
+    client: redis::Client,
+    mode: RedisMode,
+    max_len: Option<usize>,
+    batch_size: usize,
+    rx: mpsc::Receiver<Outgoing>,
+    stats: &PublisherStats,
+) {
+    let mut connection: Option<redis::Connection> = None;
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        while batch.len() < batch_size {
+            match rx.try_recv() {
+                Ok(message) => batch.push(message),
+                Err(_) => break,
+            }
+        }
+        
+        let pipe = build_pipeline(&batch, mode, max_len);
+        let mut result = Err("not sent".to_string());
+        // Un reintento con conexión nueva por si la anterior se cerró
+        for _ in 0..2 {
+            if connection.is_none() {
+                match client.get_connection_with_timeout(CONNECT_TIMEOUT) {
+                    Ok(c) => connection = Some(c),
+                    Err(e) => {
+                        result = Err(format!("Redis connect error: {}", e));
+                        continue;
+                    }
+                }
+            }
+            if let Some(c) = connection.as_mut() {
+                match pipe.query::<()>(c) {
+                    Ok(()) => {
+                        result = Ok(());
+                        break;
+                    }
+                    Err(e) => {
+                        connection = None;
+                        result = Err(format!("Redis pipeline error: {}", e));
+                    }
+                }
+            }
+        }
+        match result {
+            Ok(()) => { stats.published.fetch_add(batch.len() as u64, Ordering::Relaxed); }
+            Err(e) => {
+                stats.failed.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                stats.record_error(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_keys_and_validation() {
+        let mut publisher = RedisPublisher::new("redis://127.0.0.1:6379".to_string(),
+                                                "indicators:{kind}:{symbol}".to_string(), "stream", Some(1000), 100, "json").unwrap();
+        assert_eq!(publisher.key_for(KIND_CVD, "AAPL"), "indicators:cvd:AAPL");
+        publisher.set_key(KIND_HEATMAP, "heatmap:{symbol}");
+        assert_eq!(publisher.key_for(KIND_HEATMAP, "ES"), "heatmap:ES");
+        
+        assert!(RedisPublisher::new("redis://x".to_string(), "k".to_string(), "list", None, 100, "json").is_err());
+        assert!(RedisPublisher::new("redis://x".to_string(), "k".to_string(), "pubsub", None, 100, "protobuf").is_err());
+        assert!(RedisPublisher::new("redis://x".to_string(), "k".to_string(), "stream", Some(0), 100, "json").is_err());
+        assert!(RedisPublisher::new("redis://x".to_string(), "k".to_string(), "stream", None, 0, "json").is_err());
+    }
+    
+    #[test]
+    fn test_build_pipeline() {
+        let batch: Vec<Outgoing> = vec![("indicators:cvd:AAPL".to_string(), b"{}".to_vec())];
+        
+        let packed = String::from_utf8(build_pipeline(&batch, RedisMode::Stream, Some(500)).get_packed_pipeline()).unwrap();
+        assert!(packed.contains("XADD"));
+        assert!(packed.contains("MAXLEN\r\n$1\r\n~\r\n$3\r\n500"));
+        assert!(packed.contains("data"));
+        
+        let packed = String::from_utf8(build_pipeline(&batch, RedisMode::PubSub, Some(500)).get_packed_pipeline()).unwrap();
+        assert!(packed.contains("PUBLISH"));
+        assert!(!packed.contains("MAXLEN"));
+    }
+    
+    #[test]
+    fn test_failed_batches_without_server() {
+        let mut publisher = RedisPublisher::new("redis://127.0.0.1:1".to_string(),
+                                                "indicators:{kind}:{symbol}".to_string(), "stream", None, 100, "json").unwrap();
+        let heatmap = HeatmapMetrics {
+            bucket_ts: 0,
+            bucket_ms: 1000,
+            tiles: Vec::new(),
+            max_sz: 0.0,
+            compression_ratio: 1.0,
+            symbol: "AAPL".to_string(),
+            normalization: "raw".to_string(),
+            mid: None,
+            notional: false,
+        };
+        assert!(publisher.publish_heatmap(&heatmap).is_err());
+        
+        publisher.start().unwrap();
+        publisher.publish_heatmap(&heatmap).unwrap();
+        publisher.stop();
+        
+        let stats = publisher.stats();
+        assert_eq!(stats["queued"], 1);
+        assert_eq!(stats["failed"], 1);
+        assert!(publisher.last_error().unwrap().contains("connect"));
+    }
+}
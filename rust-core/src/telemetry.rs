@@ -0,0 +1,256 @@
+//! # Telemetry
+//! 
+//! Métricas operativas en formato de texto de Prometheus (0.0.4), sin
+//! dependencias externas:
+//! - `Histogram`: histograma de latencias con buckets fijos y cuantiles
+//!   (p50/p95/p99) de un DDSketch
+//! - `RateGauge`: ritmo (eventos/s) entre dos lecturas consecutivas
+//! - `MetricsServer`: endpoint HTTP mínimo que sirve `GET /metrics` desde
+//!   un hilo propio
+//! 
+//! Los contadores se leen con atomics, así que renderizar no bloquea el
+//! procesamiento; solo el sketch de cuantiles va tras un mutex, que se
+//! retiene lo que dura un alta o una consulta.
+
+use parking_lot::Mutex;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use crate::utils::sketch::{DDSketch, DEFAULT_QUANTILES};
+
+/// Límites superiores de los buckets de latencia (segundos)
+pub const LATENCY_BUCKETS: [f64; 10] = [1e-6, 5e-6, 1e-5, 5e-5, 1e-4, 5e-4, 1e-3, 5e-3, 1e-2, 1e-1];
+
+/// Cada cuánto comprueba el servidor si debe parar
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Cabecera HELP/TYPE de una métrica
+pub fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Una muestra; `labels` ya formateadas (`engine="cvd"`) o vacías
+pub fn write_sample(out: &mut String, name: &str, labels: &str, value: f64) {
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+/// Escapa un valor de etiqueta (`\`, `"` y saltos de línea)
+pub fn label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Histograma de duraciones con los buckets de `LATENCY_BUCKETS`
+#[derive(Default)]
+pub struct Histogram {
+    // Conteos no acumulados; el último es +Inf
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    count: AtomicU64,
+    sum_ns: AtomicU64,
+    // Cuantiles en segundos con error relativo acotado, sin depender de los buckets
+    sketch: Mutex<DDSketch>,
+}
+
+impl Histogram {
+    pub fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let index = LATENCY_BUCKETS.iter().position(|&b| secs <= b).unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.sketch.lock().add(secs);
+    }
+    
+    /// Cuantil q de las duraciones en segundos; None sin observaciones
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.sketch.lock().quantile(q)
+    }
+    
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+    
+    /// Muestras `_bucket` (acumuladas), `_sum` y `_count`
+    pub fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let prefix = if labels.is_empty() { String::new() } else { format!("{},", labels) };
+        let mut cumulative = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = LATENCY_BUCKETS.get(i).map_or("+Inf".to_string(), |b| b.to_string());
+            write_sample(out, &format!("{}_bucket", name), &format!("{}le=\"{}\"", prefix, le), cumulative as f64);
+        }
+        write_sample(out, &format!("{}_sum", name), labels, self.sum_ns.load(Ordering::Relaxed) as f64 / 1e9);
+        write_sample(out, &format!("{}_count", name), labels, self.count() as f64);
+    }
+    
+    /// Una muestra por cuantil de `DEFAULT_QUANTILES` (etiqueta `quantile`)
+    pub fn render_quantiles(&self, out: &mut String, name: &str, labels: &str) {
+        let prefix = if labels.is_empty() { String::new() } else { format!("{},", labels) };
+        let sketch = self.sketch.lock();
+        for q in DEFAULT_QUANTILES {
+            if let Some(value) = sketch.quantile(q) {
+                write_sample(out, name, &format!("{}quantile=\"{}\"", prefix, q), value);
+            }
+        }
+    }
+}
+
+/// Ritmo medio de un contador entre la lectura anterior y la actual
+pub struct RateGauge {
+    last: Mutex<(Instant, u64)>,
+}
+
+impl Default for RateGauge {
+    fn default() -> Self {
+        Self { last: Mutex::new((Instant::now(), 0)) }
+    }
+}
+
+impl RateGauge {
+    pub fn rate(&self, total: u64, now: Instant) -> f64 {
+        let mut last = self.last.lock();
+        let elapsed = now.saturating_duration_since(last.0).as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        let rate = total.saturating_sub(last.1) as f64 / elapsed;
+        *last = (now, total);
+        rate
+    }
+}
+
+/// Servidor HTTP de métricas; se detiene con `stop()` o al soltarlo
+pub struct MetricsServer {
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// Escucha en `addr` (puerto 0 = cualquiera libre) y responde a
+    /// `GET /metrics` con el texto de `render`
+    pub fn start(addr: &str, render: Arc<dyn Fn() -> String + Send + Sync>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+        let worker = std::thread::Builder::new()
+            .name("metrics-server".to_string())
+            .spawn(move || {
+                while flag.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            // Un cliente lento no debe bloquear el bucle indefinidamente
+                            let _ = stream.set_nonblocking(false);
+                            let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+                            let _ = stream.set_write_timeout(Some(Duration::from_secs(2)));
+                            handle(stream, render.as_ref());
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+                        Err(_) => std::thread::sleep(POLL_INTERVAL),
+                    }
+                }
+            })?;
+        Ok(Self { addr, running, worker: Some(worker) })
+    }
+    
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+    
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Atiende una petición: solo interesa la línea `GET <path>`
+fn handle(mut stream: TcpStream, render: &(dyn Fn() -> String + Send + Sync)) {
+    let mut buffer = [0u8; 1024];
+    let Ok(n) = stream.read(&mut buffer) else { return };
+    let request = String::from_utf8_lossy(&buffer[..n]);
+    let mut parts = request.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = render();
+            format!("HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body)
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    let _ = stream.write_all(response.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_histogram_render() {
+        let histogram = Histogram::default();
+        histogram.observe(Duration::from_nanos(500));
+        histogram.observe(Duration::from_micros(20));
+        histogram.observe(Duration::from_secs(1));
+        
+        let mut out = String::new();
+        histogram.render(&mut out, "latency_seconds", "engine=\"cvd\"");
+        assert!(out.contains("latency_seconds_bucket{engine=\"cvd\",le=\"0.000001\"} 1\n"));
+        assert!(out.contains("latency_seconds_bucket{engine=\"cvd\",le=\"0.00005\"} 2\n"));
+        assert!(out.contains("latency_seconds_bucket{engine=\"cvd\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("latency_seconds_count{engine=\"cvd\"} 3\n"));
+        
+        let p50 = histogram.quantile(0.5).unwrap();
+        assert!((p50 - 2e-5).abs() <= 0.01 * 2e-5);
+        assert_eq!(histogram.quantile(1.0), Some(1.0));
+        let mut out = String::new();
+        histogram.render_quantiles(&mut out, "latency_quantile_seconds", "engine=\"cvd\"");
+        assert!(out.contains("latency_quantile_seconds{engine=\"cvd\",quantile=\"0.99\"} "));
+        assert_eq!(out.lines().count(), DEFAULT_QUANTILES.len());
+    }
+    
+    #[test]
+    fn test_rate_gauge() {
+        let gauge = RateGauge::default();
+        let start = Instant::now();
+        gauge.rate(0, start);
+        assert_eq!(gauge.rate(500, start + Duration::from_millis(500)), 1000.0);
+        assert_eq!(gauge.rate(500, start + Duration::from_secs(1)), 0.0);
+    }
+    
+    #[test]
+    fn test_metrics_server() {
+        let mut server = MetricsServer::start("127.0.0.1:0", Arc::new(|| "up 1\n".to_string())).unwrap();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(server.addr()).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("\r\n\r\nup 1\n"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+        
+        server.stop();
+        assert!(TcpStream::connect(server.addr()).is_err());
+    }
+}
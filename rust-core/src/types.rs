@@ -2,12 +2,27 @@
 //! 
 //! Definiciones de tipos que se comparten entre Python y Rust.
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "python")]
+use pyo3::pyclass::CompareOp;
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "python")]
+use std::cmp::Ordering;
+#[cfg(feature = "python")]
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::hash::{Hash, Hasher};
+use crate::indicators::IndicatorOutput;
+#[cfg(feature = "python")]
+use crate::py_serde::impl_py_serde;
+use crate::py_macros::{py_class, py_methods};
 
+py_class! {
 /// Trade individual
-#[pyclass]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Trade {
     #[pyo3(get, set)]
     pub ts: u64,
@@ -21,11 +36,87 @@ pub struct Trade {
     pub side: Option<String>,
     #[pyo3(get, set)]
     pub exchange: Option<String>,
+    /// Identificador del trade en el exchange; los trades con el mismo id
+    /// se descartan como retransmisiones en el `EngineManager`
+    #[pyo3(get, set)]
+    #[serde(default, deserialize_with = "deserialize_trade_id")]
+    pub trade_id: Option<String>,
+}
+}
+
+/// Acepta el id como texto o como número (muchos exchanges lo envían entero)
+fn deserialize_trade_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TradeId {
+        Text(String),
+        Number(u64),
+    }
+    
+    Ok(Option::<TradeId>::deserialize(deserializer)?.map(|id| match id {
+        TradeId::Text(id) => id,
+        TradeId::Number(id) => id.to_string(),
+    }))
+}
+
+/// Hash de un f64 coherente con `==` (0.0 y -0.0 son iguales)
+#[cfg(feature = "python")]
+fn hash_f64<H: Hasher>(value: f64, state: &mut H) {
+    let value = if value == 0.0 { 0.0 } else { value };
+    value.to_bits().hash(state);
+}
+
+/// `__richcmp__` de los tipos de datos: == y != comparan todos los campos;
+/// <, <=, > y >= usan el orden de su clave (False si hay NaN)
+#[cfg(feature = "python")]
+fn richcmp<T: PartialEq>(a: &T, b: &T, op: CompareOp, order: Option<Ordering>) -> bool {
+    match op {
+        CompareOp::Eq => a == b,
+        CompareOp::Ne => a != b,
+        _ => order.is_some_and(|order| op.matches(order)),
+    }
 }
 
+py_methods! {
 #[pymethods]
 impl Trade {
+    /// Constructor de Python: los campos opcionales se pueden pasar por nombre,
+    /// p. ej. `Trade(ts=..., price=..., size=..., symbol=..., side="BUY")`
+    #[cfg(feature = "python")]
     #[new]
+    #[pyo3(signature = (ts, price, size, symbol, side=None, exchange=None, trade_id=None))]
+    fn py_new(ts: u64, price: f64, size: f64, symbol: String, side: Option<String>,
+              exchange: Option<String>, trade_id: Option<String>) -> Self {
+        Self { side, exchange, trade_id, ..Self::new(ts, price, size, symbol) }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("Trade(symbol={}, price={}, size={}, ts={})", 
+                self.symbol, self.price, self.size, self.ts)
+    }
+    
+    /// Igualdad por valor de todos los campos; el orden es (ts, symbol, price, size)
+    #[cfg(feature = "python")]
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        let order = (self.ts, &self.symbol, self.price, self.size)
+            .partial_cmp(&(other.ts, &other.symbol, other.price, other.size));
+        richcmp(self, other, op, order)
+    }
+    
+    /// Hash de todos los campos, coherente con `==` (no mutar un trade usado como clave)
+    #[cfg(feature = "python")]
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.ts, &self.symbol, &self.side, &self.exchange, &self.trade_id).hash(&mut hasher);
+        hash_f64(self.price, &mut hasher);
+        hash_f64(self.size, &mut hasher);
+        hasher.finish()
+    }
+}
+}
+
+impl Trade {
     pub fn new(ts: u64, price: f64, size: f64, symbol: String) -> Self {
         Self {
             ts,
@@ -34,18 +125,18 @@ impl Trade {
             symbol,
             side: None,
             exchange: None,
+            trade_id: None,
         }
     }
-    
-    fn __repr__(&self) -> String {
-        format!("Trade(symbol={}, price={}, size={}, ts={})", 
-                self.symbol, self.price, self.size, self.ts)
-    }
 }
 
+/// Timeframe de `Bar` cuando no se indica
+pub const DEFAULT_BAR_TF: &str = "1m";
+
+py_class! {
 /// Barra OHLCV
-#[pyclass]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Bar {
     #[pyo3(get, set)]
     pub ts: u64,
@@ -64,10 +155,14 @@ pub struct Bar {
     #[pyo3(get, set)]
     pub symbol: String,
 }
+}
 
+py_methods! {
 #[pymethods]
 impl Bar {
     #[new]
+    #[pyo3(signature = (ts, open, high, low, close, volume=0.0, tf=DEFAULT_BAR_TF.to_string(), symbol=String::new()))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(ts: u64, open: f64, high: f64, low: f64, close: f64, volume: f64, tf: String, symbol: String) -> Self {
         Self {
             ts,
@@ -81,22 +176,45 @@ impl Bar {
         }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
         format!("Bar(symbol={}, tf={}, ohlc=({},{},{},{}), vol={}, ts={})", 
                 self.symbol, self.tf, self.open, self.high, self.low, self.close, self.volume, self.ts)
     }
+    
+    /// Igualdad por valor de todos los campos; el orden es (ts, symbol, tf)
+    #[cfg(feature = "python")]
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        let order = (self.ts, &self.symbol, &self.tf).partial_cmp(&(other.ts, &other.symbol, &other.tf));
+        richcmp(self, other, op, order)
+    }
+    
+    /// Hash de todos los campos, coherente con `==`
+    #[cfg(feature = "python")]
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.ts, &self.symbol, &self.tf).hash(&mut hasher);
+        for value in [self.open, self.high, self.low, self.close, self.volume] {
+            hash_f64(value, &mut hasher);
+        }
+        hasher.finish()
+    }
+}
 }
 
+py_class! {
 /// Nivel del libro de órdenes
-#[pyclass]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Level {
     #[pyo3(get, set)]
     pub price: f64,
     #[pyo3(get, set)]
     pub size: f64,
 }
+}
 
+py_methods! {
 #[pymethods]
 impl Level {
     #[new]
@@ -104,14 +222,39 @@ impl Level {
         Self { price, size }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
         format!("Level(price={}, size={})", self.price, self.size)
     }
+    
+    /// Igualdad por valor; el orden es por precio y después tamaño
+    #[cfg(feature = "python")]
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        let order = (self.price, self.size).partial_cmp(&(other.price, other.size));
+        richcmp(self, other, op, order)
+    }
+    
+    #[cfg(feature = "python")]
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash_into(&mut hasher);
+        hasher.finish()
+    }
+}
+}
+
+#[cfg(feature = "python")]
+impl Level {
+    fn hash_into<H: Hasher>(&self, state: &mut H) {
+        hash_f64(self.price, state);
+        hash_f64(self.size, state);
+    }
 }
 
+py_class! {
 /// Snapshot del libro de órdenes
-#[pyclass]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BookSnapshot {
     #[pyo3(get, set)]
     pub ts: u64,
@@ -122,10 +265,13 @@ pub struct BookSnapshot {
     #[pyo3(get, set)]
     pub asks: Vec<Level>,
 }
+}
 
+py_methods! {
 #[pymethods]
 impl BookSnapshot {
     #[new]
+    #[pyo3(signature = (ts, symbol, bids=Vec::new(), asks=Vec::new()))]
     pub fn new(ts: u64, symbol: String, bids: Vec<Level>, asks: Vec<Level>) -> Self {
         Self {
             ts,
@@ -135,158 +281,1905 @@ impl BookSnapshot {
         }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
         format!("BookSnapshot(symbol={}, bids={}, asks={}, ts={})", 
                 self.symbol, self.bids.len(), self.asks.len(), self.ts)
     }
+    
+    /// Igualdad por valor (incluidos todos los niveles); el orden es (ts, symbol)
+    #[cfg(feature = "python")]
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        let order = (self.ts, &self.symbol).partial_cmp(&(other.ts, &other.symbol));
+        richcmp(self, other, op, order)
+    }
+    
+    /// Hash de ts, símbolo y todos los niveles, coherente con `==`
+    #[cfg(feature = "python")]
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.ts, &self.symbol, self.bids.len()).hash(&mut hasher);
+        for level in self.bids.iter().chain(&self.asks) {
+            level.hash_into(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
 }
 
-/// Métricas de CVD
-#[pyclass]
+py_class! {
+/// Actualización incremental L2 de un nivel de precio
+#[pyclass(module = "indicators_core")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct CVDMetrics {
+pub struct BookDelta {
     #[pyo3(get, set)]
-    pub cvd: f64,
+    pub ts: u64,
     #[pyo3(get, set)]
-    pub last_side: String,
+    pub symbol: String,
     #[pyo3(get, set)]
-    pub last_size: f64,
+    pub side: String,    // "bid" | "ask"
     #[pyo3(get, set)]
-    pub timestamp: u64,
+    pub action: String,  // "insert" | "update" | "delete"
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub size: f64,
+    #[pyo3(get, set)]
+    pub sequence: Option<u64>,
+}
 }
 
+py_methods! {
 #[pymethods]
-impl CVDMetrics {
+impl BookDelta {
     #[new]
-    pub fn new(cvd: f64, last_side: String, last_size: f64, timestamp: u64) -> Self {
-        Self { cvd, last_side, last_size, timestamp }
+    #[pyo3(signature = (ts, symbol, side, action, price, size, sequence=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(ts: u64, symbol: String, side: String, action: String, price: f64, size: f64,
+               sequence: Option<u64>) -> Self {
+        Self { ts, symbol, side, action, price, size, sequence }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("CVDMetrics(cvd={}, side={}, size={}, ts={})",
-                self.cvd, self.last_side, self.last_size, self.timestamp)
+        format!("BookDelta(symbol={}, {} {} {}@{}, seq={:?}, ts={})",
+                self.symbol, self.action, self.side, self.size, self.price, self.sequence, self.ts)
     }
 }
+}
 
-/// Métricas de Liquidity
-#[pyclass]
+py_class! {
+/// Cotización top-of-book (BBO)
+#[pyclass(module = "indicators_core")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct LiquidityMetrics {
+pub struct Quote {
     #[pyo3(get, set)]
-    pub mid: f64,
+    pub ts: u64,
     #[pyo3(get, set)]
-    pub spread: f64,
+    pub symbol: String,
     #[pyo3(get, set)]
-    pub bids_depth: f64,
+    pub bid: f64,
     #[pyo3(get, set)]
-    pub asks_depth: f64,
+    pub bid_size: f64,
     #[pyo3(get, set)]
-    pub depth_imbalance: f64,
+    pub ask: f64,
     #[pyo3(get, set)]
-    pub top_imbalance: f64,
+    pub ask_size: f64,
+    /// Venue de la cotización; el `QuoteEngine` combina las de todos los
+    /// venues de un símbolo en un NBBO
     #[pyo3(get, set)]
-    pub best_bid: f64,
+    #[serde(default)]
+    pub exchange: Option<String>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl Quote {
+    #[new]
+    pub fn new(ts: u64, symbol: String, bid: f64, bid_size: f64, ask: f64, ask_size: f64) -> Self {
+        Self { ts, symbol, bid, bid_size, ask, ask_size, exchange: None }
+    }
+    
+    /// Precio medio entre bid y ask
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("Quote(symbol={}, bid={}x{}, ask={}x{}, ts={})",
+                self.symbol, self.bid, self.bid_size, self.ask, self.ask_size, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Open interest de un contrato (perpetuo/futuro)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenInterest {
     #[pyo3(get, set)]
-    pub best_ask: f64,
+    pub ts: u64,
     #[pyo3(get, set)]
-    pub bid1_size: f64,
+    pub symbol: String,
     #[pyo3(get, set)]
-    pub ask1_size: f64,
+    pub open_interest: f64,
     #[pyo3(get, set)]
-    pub levels: String,
+    pub exchange: Option<String>,
+}
 }
 
+py_methods! {
 #[pymethods]
-impl LiquidityMetrics {
+impl OpenInterest {
     #[new]
-    pub fn new(mid: f64, spread: f64, bids_depth: f64, asks_depth: f64, depth_imbalance: f64, top_imbalance: f64,
-           best_bid: f64, best_ask: f64, bid1_size: f64, ask1_size: f64, levels: String) -> Self {
-        Self { mid, spread, bids_depth, asks_depth, depth_imbalance, top_imbalance,
-               best_bid, best_ask, bid1_size, ask1_size, levels }
+    #[pyo3(signature = (ts, symbol, open_interest, exchange=None))]
+    pub fn new(ts: u64, symbol: String, open_interest: f64, exchange: Option<String>) -> Self {
+        Self { ts, symbol, open_interest, exchange }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("LiquidityMetrics(mid={}, spread={}, imbalance={})",
-                self.mid, self.spread, self.depth_imbalance)
+        format!("OpenInterest(symbol={}, oi={}, exchange={:?}, ts={})",
+                self.symbol, self.open_interest, self.exchange, self.ts)
     }
 }
+}
 
-/// Tile individual (precio + tamaño comprimido)
-#[pyclass]
+py_class! {
+/// Funding rate de un perpetuo
+#[pyclass(module = "indicators_core")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Tile {
+pub struct FundingRate {
     #[pyo3(get, set)]
-    pub price_bin: f64,
+    pub ts: u64,
     #[pyo3(get, set)]
-    pub total_size: f64,
+    pub symbol: String,
     #[pyo3(get, set)]
-    pub side: String,
+    pub rate: f64,
+    #[pyo3(get, set)]
+    pub mark_price: Option<f64>,
+    #[pyo3(get, set)]
+    pub next_funding_ts: Option<u64>,
+}
 }
 
+py_methods! {
 #[pymethods]
-impl Tile {
+impl FundingRate {
     #[new]
-    pub fn new(price_bin: f64, total_size: f64, side: String) -> Self {
-        Self { price_bin, total_size, side }
+    #[pyo3(signature = (ts, symbol, rate, mark_price=None, next_funding_ts=None))]
+    pub fn new(ts: u64, symbol: String, rate: f64, mark_price: Option<f64>, next_funding_ts: Option<u64>) -> Self {
+        Self { ts, symbol, rate, mark_price, next_funding_ts }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("Tile(price={}, size={}, side={})", self.price_bin, self.total_size, self.side)
+        format!("FundingRate(symbol={}, rate={}, ts={})", self.symbol, self.rate, self.ts)
     }
 }
+}
 
-/// Métricas de Heatmap con tiles comprimidos
-#[pyclass]
+py_class! {
+/// Liquidación forzosa de una posición (feeds de exchanges cripto)
+#[pyclass(module = "indicators_core")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct HeatmapMetrics {
+pub struct Liquidation {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub side: String,  // Posición liquidada: "long" | "short"
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub size: f64,
+    #[pyo3(get, set)]
+    pub exchange: Option<String>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl Liquidation {
+    #[new]
+    #[pyo3(signature = (ts, symbol, side, price, size, exchange=None))]
+    pub fn new(ts: u64, symbol: String, side: String, price: f64, size: f64, exchange: Option<String>) -> Self {
+        Self { ts, symbol, side, price, size, exchange }
+    }
+    
+    /// Nocional liquidado (price × size)
+    pub fn notional(&self) -> f64 {
+        self.price * self.size
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("Liquidation(symbol={}, side={}, price={}, size={}, ts={})",
+                self.symbol, self.side, self.price, self.size, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Métricas de CVD
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CVDMetrics {
+    #[pyo3(get, set)]
+    pub cvd: f64,
+    #[pyo3(get, set)]
+    pub last_side: String,
+    #[pyo3(get, set)]
+    pub last_size: f64,
+    #[pyo3(get, set)]
+    pub timestamp: u64,
+    #[pyo3(get, set)]
+    pub buy_volume: f64,
+    #[pyo3(get, set)]
+    pub sell_volume: f64,
+    #[pyo3(get, set)]
+    pub trade_count: u64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl CVDMetrics {
+    #[new]
+    #[pyo3(signature = (cvd, last_side, last_size, timestamp, buy_volume=0.0, sell_volume=0.0, trade_count=0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(cvd: f64, last_side: String, last_size: f64, timestamp: u64,
+               buy_volume: f64, sell_volume: f64, trade_count: u64) -> Self {
+        Self { cvd, last_side, last_size, timestamp, buy_volume, sell_volume, trade_count }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("CVDMetrics(cvd={}, side={}, size={}, buy={}, sell={}, trades={}, ts={})",
+                self.cvd, self.last_side, self.last_size, self.buy_volume, self.sell_volume,
+                self.trade_count, self.timestamp)
+    }
+}
+}
+
+py_class! {
+/// Barra de delta (CVD en forma OHLC por timeframe)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CVDBar {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub tf: String,
     #[pyo3(get, set)]
     pub bucket_ts: u64,
     #[pyo3(get, set)]
-    pub bucket_ms: u64,
+    pub open: f64,   // CVD acumulado al abrir la barra
     #[pyo3(get, set)]
-    pub tiles: Vec<Tile>,  // ← Comprimido, NO todas las rows
+    pub high: f64,
     #[pyo3(get, set)]
-    pub max_sz: f64,
+    pub low: f64,
     #[pyo3(get, set)]
-    pub compression_ratio: f64,
+    pub close: f64,
+    #[pyo3(get, set)]
+    pub buy_volume: f64,
+    #[pyo3(get, set)]
+    pub sell_volume: f64,
+    #[pyo3(get, set)]
+    pub delta: f64,
+}
 }
 
+py_methods! {
 #[pymethods]
-impl HeatmapMetrics {
+impl CVDBar {
     #[new]
-    fn new(bucket_ts: u64, bucket_ms: u64, tiles: Vec<Tile>, max_sz: f64, compression_ratio: f64) -> Self {
-        Self { bucket_ts, bucket_ms, tiles, max_sz, compression_ratio }
+    pub fn new(symbol: String, tf: String, bucket_ts: u64, open: f64) -> Self {
+        Self {
+            symbol, tf, bucket_ts,
+            open, high: open, low: open, close: open,
+            buy_volume: 0.0, sell_volume: 0.0, delta: 0.0,
+        }
     }
     
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("HeatmapMetrics(bucket_ts={}, bucket_ms={}, tiles={}, max_sz={}, comp={})",
-                self.bucket_ts, self.bucket_ms, self.tiles.len(), self.max_sz, self.compression_ratio)
+        format!("CVDBar(symbol={}, tf={}, ts={}, ohlc=({},{},{},{}), delta={})",
+                self.symbol, self.tf, self.bucket_ts, self.open, self.high, self.low, self.close, self.delta)
     }
 }
+}
 
-/// Métricas de VWAP
-#[pyclass]
+py_class! {
+/// Volumen por lado en un nivel de precio de un footprint
+#[pyclass(module = "indicators_core")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct VWAPMetrics {
+pub struct FootprintLevel {
     #[pyo3(get, set)]
-    pub vwap: f64,
+    pub price: f64,
     #[pyo3(get, set)]
-    pub pv_sum: f64,
+    pub buy_volume: f64,
     #[pyo3(get, set)]
-    pub v_sum: f64,
+    pub sell_volume: f64,
     #[pyo3(get, set)]
-    pub session_id: Option<String>,
+    pub delta: f64,
+    #[pyo3(get, set)]
+    pub volume: f64,  // Incluye trades sin lado ("NA")
+}
 }
 
+py_methods! {
 #[pymethods]
-impl VWAPMetrics {
+impl FootprintLevel {
     #[new]
-    pub fn new(vwap: f64, pv_sum: f64, v_sum: f64, session_id: Option<String>) -> Self {
-        Self { vwap, pv_sum, v_sum, session_id }
+    pub fn new(price: f64, buy_volume: f64, sell_volume: f64, volume: f64) -> Self {
+        Self { price, buy_volume, sell_volume, delta: buy_volume - sell_volume, volume }
     }
     
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("FootprintLevel(price={}, buy={}, sell={}, delta={})",
+                self.price, self.buy_volume, self.sell_volume, self.delta)
+    }
+}
+}
+
+py_class! {
+/// Barra footprint: OHLC de precio y volumen por lado en cada nivel
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FootprintBar {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub tf: String,
+    #[pyo3(get, set)]
+    pub bucket_ts: u64,
+    #[pyo3(get, set)]
+    pub open: f64,
+    #[pyo3(get, set)]
+    pub high: f64,
+    #[pyo3(get, set)]
+    pub low: f64,
+    #[pyo3(get, set)]
+    pub close: f64,
+    #[pyo3(get, set)]
+    pub volume: f64,
+    #[pyo3(get, set)]
+    pub delta: f64,
+    #[pyo3(get, set)]
+    pub levels: Vec<FootprintLevel>,  // Ordenados por precio
+}
+}
+
+py_methods! {
+#[pymethods]
+impl FootprintBar {
+    #[cfg(feature = "python")]
     fn __repr__(&self) -> String {
-        format!("VWAPMetrics(vwap={}, pv_sum={}, v_sum={})",
-                self.vwap, self.pv_sum, self.v_sum)
+        format!("FootprintBar(symbol={}, tf={}, ts={}, ohlc=({},{},{},{}), delta={}, levels={})",
+                self.symbol, self.tf, self.bucket_ts, self.open, self.high, self.low, self.close,
+                self.delta, self.levels.len())
+    }
+}
+}
+
+py_class! {
+/// Métricas de Liquidity
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LiquidityMetrics {
+    #[pyo3(get, set)]
+    pub mid: f64,
+    #[pyo3(get, set)]
+    pub spread: f64,
+    #[pyo3(get, set)]
+    pub bids_depth: f64,
+    #[pyo3(get, set)]
+    pub asks_depth: f64,
+    #[pyo3(get, set)]
+    pub depth_imbalance: f64,
+    #[pyo3(get, set)]
+    pub top_imbalance: f64,
+    #[pyo3(get, set)]
+    pub best_bid: f64,
+    #[pyo3(get, set)]
+    pub best_ask: f64,
+    #[pyo3(get, set)]
+    pub bid1_size: f64,
+    #[pyo3(get, set)]
+    pub ask1_size: f64,
+    /// Obsoleto: "bids/asks" como texto; usar `bid_levels` / `ask_levels`
+    pub levels: String,
+    /// Niveles recibidos en cada lado del snapshot
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub bid_levels: u32,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub ask_levels: u32,
+    /// Primeros `top_levels` niveles de cada lado (vacíos si el engine no los incluye)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub top_bids: Vec<Level>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub top_asks: Vec<Level>,
+    #[pyo3(get, set)]
+    pub microprice: f64,
+    #[pyo3(get, set)]
+    pub weighted_mid: f64,
+    #[pyo3(get, set)]
+    pub bid_slope: f64,
+    #[pyo3(get, set)]
+    pub ask_slope: f64,
+    /// Tamaño añadido / retirado por lado desde el snapshot anterior (lo
+    /// profundo es el total menos lo cercano al touch)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub bid_added: f64,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub bid_pulled: f64,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub ask_added: f64,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub ask_pulled: f64,
+    /// Lo mismo en los primeros `near_touch_levels` niveles
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub near_bid_added: f64,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub near_bid_pulled: f64,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub near_ask_added: f64,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub near_ask_pulled: f64,
+    /// Profundidad nocional (precio × tamaño) de los mismos niveles que
+    /// bids_depth / asks_depth; None si el engine no está en modo nocional
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub bids_notional: Option<f64>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub asks_notional: Option<f64>,
+    /// Spread y depth imbalance suavizados (EWMA, ver
+    /// `LiquidityEngine.set_smoothing`); None sin suavizado
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub smoothed_spread: Option<f64>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub smoothed_depth_imbalance: Option<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidityMetrics {
+    #[new]
+    #[pyo3(signature = (mid, spread, bids_depth, asks_depth, depth_imbalance, top_imbalance,
+                        best_bid, best_ask, bid1_size, ask1_size, levels,
+                        microprice=0.0, weighted_mid=0.0, bid_slope=0.0, ask_slope=0.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(mid: f64, spread: f64, bids_depth: f64, asks_depth: f64, depth_imbalance: f64, top_imbalance: f64,
+           best_bid: f64, best_ask: f64, bid1_size: f64, ask1_size: f64, levels: String,
+           microprice: f64, weighted_mid: f64, bid_slope: f64, ask_slope: f64) -> Self {
+        Self { mid, spread, bids_depth, asks_depth, depth_imbalance, top_imbalance,
+               best_bid, best_ask, bid1_size, ask1_size, levels, microprice, weighted_mid,
+               bid_slope, ask_slope, ..Default::default() }
+    }
+    
+    /// Obsoleto: emite DeprecationWarning; usar `bid_levels` / `ask_levels`
+    #[cfg(feature = "python")]
+    #[getter(levels)]
+    fn py_levels(&self, py: Python<'_>) -> PyResult<String> {
+        let category = py.get_type_bound::<pyo3::exceptions::PyDeprecationWarning>();
+        PyErr::warn_bound(py, category.as_any(),
+                          "LiquidityMetrics.levels is deprecated, use bid_levels and ask_levels", 1)?;
+        Ok(self.levels.clone())
+    }
+    
+    #[cfg(feature = "python")]
+    #[setter(levels)]
+    fn set_levels(&mut self, levels: String) {
+        self.levels = levels;
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidityMetrics(mid={}, spread={}, imbalance={})",
+                self.mid, self.spread, self.depth_imbalance)
+    }
+}
+}
+
+py_class! {
+/// Medias móviles de liquidez de un símbolo sobre una ventana temporal
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidityRollingStats {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub window_ms: u64,
+    #[pyo3(get, set)]
+    pub samples: usize,
+    #[pyo3(get, set)]
+    pub avg_spread: f64,
+    #[pyo3(get, set)]
+    pub avg_depth_imbalance: f64,
+    #[pyo3(get, set)]
+    pub avg_bids_depth: f64,
+    #[pyo3(get, set)]
+    pub avg_asks_depth: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidityRollingStats {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidityRollingStats(symbol={}, window_ms={}, samples={}, avg_spread={}, avg_imbalance={})",
+                self.symbol, self.window_ms, self.samples, self.avg_spread, self.avg_depth_imbalance)
+    }
+}
+}
+
+py_class! {
+/// Trade anotado con el contexto del libro vigente al ejecutarse
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnrichedTrade {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub size: f64,
+    /// Lado agresor según el libro: "BUY", "SELL" o "NA"
+    #[pyo3(get, set)]
+    pub side: String,
+    #[pyo3(get, set)]
+    pub exchange: Option<String>,
+    #[pyo3(get, set)]
+    pub trade_id: Option<String>,
+    #[pyo3(get, set)]
+    pub mid: Option<f64>,
+    /// Distancia al mid en bps (positiva por encima)
+    #[pyo3(get, set)]
+    pub distance_bps: Option<f64>,
+    /// Niveles del lado contrario que el precio atraviesa (> 1 = barrido)
+    #[pyo3(get, set)]
+    pub levels_swept: usize,
+    /// Antigüedad del top-of-book usado (ms)
+    #[pyo3(get, set)]
+    pub quote_age_ms: Option<u64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl EnrichedTrade {
+    /// El trade consumió más de un nivel del libro
+    #[getter]
+    pub fn is_sweep(&self) -> bool {
+        self.levels_swept > 1
+    }
+    
+    /// Trade original con el lado del libro explícito, para alimentar otros
+    /// engines con la misma clasificación
+    pub fn to_trade(&self) -> Trade {
+        Trade {
+            ts: self.ts,
+            price: self.price,
+            size: self.size,
+            symbol: self.symbol.clone(),
+            side: Some(self.side.clone()).filter(|side| side != "NA"),
+            exchange: self.exchange.clone(),
+            trade_id: self.trade_id.clone(),
+        }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("EnrichedTrade(symbol={}, {} {}@{}, distance_bps={:?}, levels_swept={}, ts={})",
+                self.symbol, self.side, self.size, self.price, self.distance_bps, self.levels_swept, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Spread efectivo de un trade respecto a la quote vigente al ejecutarse
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EffectiveSpreadMetrics {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub size: f64,
+    #[pyo3(get, set)]
+    pub side: String,
+    #[pyo3(get, set)]
+    pub bid: f64,
+    #[pyo3(get, set)]
+    pub ask: f64,
+    #[pyo3(get, set)]
+    pub mid: f64,
+    #[pyo3(get, set)]
+    pub quoted_spread: f64,
+    /// 2 × distancia del precio al mid en la dirección del agresor
+    #[pyo3(get, set)]
+    pub effective_spread: f64,
+    #[pyo3(get, set)]
+    pub effective_spread_bps: f64,
+    /// Mejora frente al lado cotizado (ask - precio en compras, precio - bid
+    /// en ventas); negativa si el trade se ejecutó fuera de la quote
+    #[pyo3(get, set)]
+    pub price_improvement: f64,
+    /// Antigüedad de la quote usada (ms)
+    #[pyo3(get, set)]
+    pub quote_age_ms: u64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl EffectiveSpreadMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("EffectiveSpreadMetrics(symbol={}, {} {}@{}, effective_spread={}, improvement={}, ts={})",
+                self.symbol, self.side, self.size, self.price, self.effective_spread, self.price_improvement, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Spread cotizado medio ponderado por tiempo y spread efectivo medio de un
+/// símbolo sobre una ventana temporal
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpreadWindowStats {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub window_ms: u64,
+    #[pyo3(get, set)]
+    pub twa_quoted_spread: f64,
+    /// Tiempo de la ventana con quote vigente (ms)
+    #[pyo3(get, set)]
+    pub quoted_ms: u64,
+    #[pyo3(get, set)]
+    pub trades: usize,
+    #[pyo3(get, set)]
+    pub avg_effective_spread: f64,
+    #[pyo3(get, set)]
+    pub avg_price_improvement: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl SpreadWindowStats {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SpreadWindowStats(symbol={}, window_ms={}, twa_quoted_spread={}, trades={}, avg_effective_spread={})",
+                self.symbol, self.window_ms, self.twa_quoted_spread, self.trades, self.avg_effective_spread)
+    }
+}
+}
+
+py_class! {
+/// Umbrales de alertas de liquidez (None = alerta desactivada)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LiquidityAlertConfig {
+    /// Alerta si spread > k × mediana del spread en la ventana más corta
+    #[pyo3(get, set)]
+    pub spread_median_multiple: Option<f64>,
+    /// Alerta si la profundidad de cualquiera de los lados cae por debajo
+    #[pyo3(get, set)]
+    pub min_depth: Option<f64>,
+    /// Alerta si |depth_imbalance| supera el límite
+    #[pyo3(get, set)]
+    pub imbalance_limit: Option<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidityAlertConfig {
+    #[new]
+    #[pyo3(signature = (spread_median_multiple=None, min_depth=None, imbalance_limit=None))]
+    pub fn new(spread_median_multiple: Option<f64>, min_depth: Option<f64>, imbalance_limit: Option<f64>) -> Self {
+        Self { spread_median_multiple, min_depth, imbalance_limit }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidityAlertConfig(spread_x={:?}, min_depth={:?}, imbalance={:?})",
+                self.spread_median_multiple, self.min_depth, self.imbalance_limit)
+    }
+}
+}
+
+py_class! {
+/// Evento de alerta de liquidez
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidityAlert {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub kind: String,  // "spread_spike" | "low_depth" | "imbalance"
+    #[pyo3(get, set)]
+    pub value: f64,
+    #[pyo3(get, set)]
+    pub threshold: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidityAlert {
+    #[new]
+    pub fn new(ts: u64, symbol: String, kind: String, value: f64, threshold: f64) -> Self {
+        Self { ts, symbol, kind, value, threshold }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidityAlert(symbol={}, kind={}, value={}, threshold={}, ts={})",
+                self.symbol, self.kind, self.value, self.threshold, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Problema de calidad en un snapshot del libro
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookQualityAlert {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub kind: String,  // "crossed_book" | "locked_book" | "empty_book" | "duplicate_level" | "unsorted_levels"
+    #[pyo3(get, set)]
+    pub message: String,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl BookQualityAlert {
+    #[new]
+    pub fn new(ts: u64, symbol: String, kind: String, message: String) -> Self {
+        Self { ts, symbol, kind, message }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("BookQualityAlert(symbol={}, kind={}, ts={}, message={})",
+                self.symbol, self.kind, self.ts, self.message)
+    }
+}
+}
+
+py_class! {
+/// Detección de iceberg (liquidez oculta que se repone en un nivel)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IcebergDetection {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub side: String,  // "bid" | "ask"
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub executed_volume: f64,
+    #[pyo3(get, set)]
+    pub displayed_size: f64,
+    #[pyo3(get, set)]
+    pub estimated_hidden_size: f64,
+    #[pyo3(get, set)]
+    pub reloads: u32,
+    #[pyo3(get, set)]
+    pub confidence: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl IcebergDetection {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("IcebergDetection(symbol={}, side={}, price={}, executed={}, hidden={}, conf={:.2})",
+                self.symbol, self.side, self.price, self.executed_volume,
+                self.estimated_hidden_size, self.confidence)
+    }
+}
+}
+
+py_class! {
+/// Absorción: volumen agresivo ejecutado contra un nivel que no cede
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AbsorptionEvent {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub side: String,  // "bid" | "ask"
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub absorbed_volume: f64,
+    #[pyo3(get, set)]
+    pub trades: usize,
+    #[pyo3(get, set)]
+    pub start_ts: u64,
+    #[pyo3(get, set)]
+    pub duration_ms: u64,
+    /// Tamaño que sigue mostrando el nivel
+    #[pyo3(get, set)]
+    pub resting_size: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl AbsorptionEvent {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("AbsorptionEvent(symbol={}, side={}, price={}, absorbed={}, duration_ms={}, ts={})",
+                self.symbol, self.side, self.price, self.absorbed_volume, self.duration_ms, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Presión del libro: imbalance integrado en el tiempo
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookPressureMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub imbalance: f64,
+    /// Σ imbalance × dt (segundos) en la ventana
+    #[pyo3(get, set)]
+    pub pressure: f64,
+    /// pressure / duración cubierta: imbalance medio ponderado en tiempo [-1, 1]
+    #[pyo3(get, set)]
+    pub normalized_pressure: f64,
+    /// Integral con decaimiento exponencial (0 si no hay half-life)
+    #[pyo3(get, set)]
+    pub decayed_pressure: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl BookPressureMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("BookPressureMetrics(symbol={}, pressure={}, normalized={}, decayed={}, ts={})",
+                self.symbol, self.pressure, self.normalized_pressure, self.decayed_pressure, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Tile individual (precio + tamaño comprimido)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tile {
+    #[pyo3(get, set)]
+    pub price_bin: f64,
+    #[pyo3(get, set)]
+    pub total_size: f64,
+    #[pyo3(get, set)]
+    pub side: String,
+    /// Tamaño normalizado según el modo del engine (total_size en modo raw)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub value: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl Tile {
+    #[new]
+    #[pyo3(signature = (price_bin, total_size, side, value=None))]
+    pub fn new(price_bin: f64, total_size: f64, side: String, value: Option<f64>) -> Self {
+        Self { price_bin, total_size, side, value: value.unwrap_or(total_size) }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("Tile(price={}, size={}, side={})", self.price_bin, self.total_size, self.side)
+    }
+}
+}
+
+py_class! {
+/// Métricas de Heatmap con tiles comprimidos
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeatmapMetrics {
+    #[pyo3(get, set)]
+    pub bucket_ts: u64,
+    #[pyo3(get, set)]
+    pub bucket_ms: u64,
+    #[pyo3(get, set)]
+    pub tiles: Vec<Tile>,  // ← Comprimido, NO todas las rows
+    #[pyo3(get, set)]
+    pub max_sz: f64,
+    #[pyo3(get, set)]
+    pub compression_ratio: f64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    /// Modo de normalización de `Tile.value`: "raw", "percent_max",
+    /// "percent_side" o "log"
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub normalization: String,
+    /// Mid de referencia con bins relativos (price_bin en bps respecto a él)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub mid: Option<f64>,
+    /// Tamaños acumulados como nocional (precio × tamaño)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub notional: bool,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl HeatmapMetrics {
+    #[new]
+    #[pyo3(signature = (bucket_ts, bucket_ms, tiles, max_sz, compression_ratio, symbol=String::new(),
+                        normalization="raw".to_string(), mid=None, notional=false))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(bucket_ts: u64, bucket_ms: u64, tiles: Vec<Tile>, max_sz: f64, compression_ratio: f64, symbol: String,
+           normalization: String, mid: Option<f64>, notional: bool) -> Self {
+        Self { bucket_ts, bucket_ms, tiles, max_sz, compression_ratio, symbol, normalization, mid, notional }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("HeatmapMetrics(symbol={}, bucket_ts={}, bucket_ms={}, tiles={}, max_sz={}, comp={})",
+                self.symbol, self.bucket_ts, self.bucket_ms, self.tiles.len(), self.max_sz, self.compression_ratio)
+    }
+}
+}
+
+py_class! {
+/// Perfil de volumen (volumen ejecutado por nivel de precio)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VolumeProfileMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub session_start: Option<u64>,  // None = perfil acumulado sin sesiones
+    #[pyo3(get, set)]
+    pub total_volume: f64,
+    /// Nocional ejecutado (precio × tamaño); None si el engine no está en modo nocional
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub total_notional: Option<f64>,
+    #[pyo3(get, set)]
+    pub poc: f64,  // Point of control: precio con más volumen
+    #[pyo3(get, set)]
+    pub value_area_high: f64,
+    #[pyo3(get, set)]
+    pub value_area_low: f64,
+    #[pyo3(get, set)]
+    pub tiles: Vec<Tile>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VolumeProfileMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VolumeProfileMetrics(symbol={}, poc={}, vah={}, val={}, volume={}, levels={})",
+                self.symbol, self.poc, self.value_area_high, self.value_area_low,
+                self.total_volume, self.tiles.len())
+    }
+}
+}
+
+py_class! {
+/// Medias móviles de un símbolo para un periodo (None hasta completar el periodo)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MovingAverageMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub period: usize,
+    #[pyo3(get, set)]
+    pub sma: Option<f64>,
+    #[pyo3(get, set)]
+    pub ema: Option<f64>,
+    #[pyo3(get, set)]
+    pub wma: Option<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl MovingAverageMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("MovingAverageMetrics(symbol={}, period={}, sma={:?}, ema={:?}, wma={:?}, ts={})",
+                self.symbol, self.period, self.sma, self.ema, self.wma, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Métricas de RSI (Wilder) de un símbolo para un periodo
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RSIMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub period: usize,
+    #[pyo3(get, set)]
+    pub rsi: Option<f64>,  // None hasta tener `period` cambios
+    #[pyo3(get, set)]
+    pub avg_gain: f64,
+    #[pyo3(get, set)]
+    pub avg_loss: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl RSIMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("RSIMetrics(symbol={}, period={}, rsi={:?}, ts={})",
+                self.symbol, self.period, self.rsi, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Bandas de Bollinger y volatilidad rolling de cierres
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VolatilityMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub period: usize,
+    #[pyo3(get, set)]
+    pub mean: f64,
+    #[pyo3(get, set)]
+    pub std_dev: f64,
+    #[pyo3(get, set)]
+    pub upper_band: f64,
+    #[pyo3(get, set)]
+    pub lower_band: f64,
+    #[pyo3(get, set)]
+    pub bandwidth: f64,  // (upper - lower) / mean
+    #[pyo3(get, set)]
+    pub percent_b: f64,  // (close - lower) / (upper - lower)
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VolatilityMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VolatilityMetrics(symbol={}, period={}, mean={}, std={}, bands=({},{}), ts={})",
+                self.symbol, self.period, self.mean, self.std_dev, self.lower_band, self.upper_band, self.ts)
+    }
+}
+}
+
+py_class! {
+/// True range y ATR (Wilder) de un símbolo/timeframe para un periodo
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ATRMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub tf: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub period: usize,
+    #[pyo3(get, set)]
+    pub true_range: f64,
+    #[pyo3(get, set)]
+    pub atr: Option<f64>,  // None hasta tener `period` true ranges
+}
+}
+
+py_methods! {
+#[pymethods]
+impl ATRMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("ATRMetrics(symbol={}, tf={}, period={}, tr={}, atr={:?}, ts={})",
+                self.symbol, self.tf, self.period, self.true_range, self.atr, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Métricas de open interest: cambio, precio medio de entrada y divergencias
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OIMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub open_interest: f64,  // Suma de todos los exchanges
+    #[pyo3(get, set)]
+    pub oi_change: f64,  // Respecto a la lectura anterior
+    #[pyo3(get, set)]
+    pub oi_change_rate: f64,  // Contratos por segundo
+    #[pyo3(get, set)]
+    pub oi_weighted_price: f64,  // Precio medio ponderado por aumentos de OI
+    #[pyo3(get, set)]
+    pub window_oi_change_pct: f64,
+    #[pyo3(get, set)]
+    pub window_price_change_pct: f64,
+    #[pyo3(get, set)]
+    pub window_cvd_change: f64,
+    #[pyo3(get, set)]
+    pub price_divergence: bool,  // OI y precio en direcciones opuestas
+    #[pyo3(get, set)]
+    pub cvd_divergence: bool,  // OI y CVD en direcciones opuestas
+    #[pyo3(get, set)]
+    pub funding_rate: Option<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl OIMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("OIMetrics(symbol={}, oi={}, change={}, rate={}, price_div={}, cvd_div={}, ts={})",
+                self.symbol, self.open_interest, self.oi_change, self.oi_change_rate,
+                self.price_divergence, self.cvd_divergence, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Liquidaciones agregadas de un bucket temporal, con tiles por precio
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidationMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub bucket_ts: u64,
+    #[pyo3(get, set)]
+    pub bucket_ms: u64,
+    #[pyo3(get, set)]
+    pub long_notional: f64,
+    #[pyo3(get, set)]
+    pub short_notional: f64,
+    #[pyo3(get, set)]
+    pub tiles: Vec<Tile>,  // side = "long" | "short", total_size = nocional
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidationMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidationMetrics(symbol={}, bucket_ts={}, long={}, short={}, tiles={})",
+                self.symbol, self.bucket_ts, self.long_notional, self.short_notional, self.tiles.len())
+    }
+}
+}
+
+py_class! {
+/// Alerta de cascada: nocional liquidado de un lado en la ventana supera el umbral
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidationCascade {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub side: String,
+    #[pyo3(get, set)]
+    pub notional: f64,
+    #[pyo3(get, set)]
+    pub count: usize,
+    #[pyo3(get, set)]
+    pub threshold: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LiquidationCascade {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LiquidationCascade(symbol={}, side={}, notional={}, count={}, ts={})",
+                self.symbol, self.side, self.notional, self.count, self.ts)
+    }
+}
+}
+
+py_class! {
+/// VPIN (volume-synchronized probability of informed trading)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VPINMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub vpin: Option<f64>,  // None hasta completar la ventana de buckets
+    #[pyo3(get, set)]
+    pub buckets: usize,  // Buckets completos en la ventana
+    #[pyo3(get, set)]
+    pub bucket_imbalance: f64,  // |V_buy - V_sell| / V del último bucket
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub vpin_zscore: Option<f64>,  // z del VPIN frente a los anteriores de la sesión
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VPINMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("VPINMetrics(symbol={}, vpin={:?}, buckets={}, ts={})",
+                self.symbol, self.vpin, self.buckets, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Velocidad de la cinta en una ventana rolling
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TapeMetrics {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub window_ms: u64,
+    #[pyo3(get, set)]
+    pub trade_count: usize,
+    #[pyo3(get, set)]
+    pub trades_per_second: f64,
+    #[pyo3(get, set)]
+    pub avg_trade_size: f64,
+    #[pyo3(get, set)]
+    pub volume_per_second: f64,
+    #[pyo3(get, set)]
+    pub notional_per_second: f64,
+    /// trades_per_second suavizado (EWMA, ver `TapeEngine.set_smoothing`);
+    /// None sin suavizado
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub smoothed_trades_per_second: Option<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl TapeMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("TapeMetrics(symbol={}, tps={}, avg_size={}, notional/s={}, ts={})",
+                self.symbol, self.trades_per_second, self.avg_trade_size, self.notional_per_second, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Barrido: ráfaga de trades a precios crecientes (compra) o decrecientes
+/// (venta) que consume varios niveles del libro en poco tiempo
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SweepEvent {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub start_ts: u64,
+    #[pyo3(get, set)]
+    pub end_ts: u64,
+    /// "BUY" (precios ascendentes) o "SELL" (descendentes)
+    #[pyo3(get, set)]
+    pub direction: String,
+    #[pyo3(get, set)]
+    pub trades: usize,
+    /// Precios distintos alcanzados
+    #[pyo3(get, set)]
+    pub levels: usize,
+    #[pyo3(get, set)]
+    pub start_price: f64,
+    #[pyo3(get, set)]
+    pub end_price: f64,
+    #[pyo3(get, set)]
+    pub volume: f64,
+    #[pyo3(get, set)]
+    pub notional: f64,
+    #[pyo3(get, set)]
+    pub vwap: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl SweepEvent {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SweepEvent(symbol={}, {} {} levels {}->{}, notional={}, ts={})",
+                self.symbol, self.direction, self.levels, self.start_price, self.end_price, self.notional, self.end_ts)
+    }
+}
+}
+
+py_class! {
+/// Mejor bid/ask entre venues (estilo NBBO), ritmo de quotes y spread
+/// medio ponderado por tiempo
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuoteMetrics {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub best_bid: f64,
+    #[pyo3(get, set)]
+    pub best_bid_size: f64,
+    #[pyo3(get, set)]
+    pub best_ask: f64,
+    #[pyo3(get, set)]
+    pub best_ask_size: f64,
+    #[pyo3(get, set)]
+    pub mid: f64,
+    #[pyo3(get, set)]
+    pub spread: f64,
+    #[pyo3(get, set)]
+    pub spread_bps: f64,
+    #[pyo3(get, set)]
+    pub updates_per_second: f64,
+    #[pyo3(get, set)]
+    pub twa_spread: f64,
+    #[pyo3(get, set)]
+    pub venues: usize,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl QuoteMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("QuoteMetrics(symbol={}, bid={}x{}, ask={}x{}, spread={}, twa_spread={}, ts={})",
+                self.symbol, self.best_bid, self.best_bid_size, self.best_ask, self.best_ask_size,
+                self.spread, self.twa_spread, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Señal de flujo compuesta: CVD, imbalance del libro y velocidad de la
+/// cinta combinados en un score en [-1, 1] (positivo = presión compradora)
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FlowSignal {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub score: f64,
+    /// Volumen neto comprador / volumen total en la ventana
+    #[pyo3(get, set)]
+    pub cvd_flow: Option<f64>,
+    #[pyo3(get, set)]
+    pub book_imbalance: Option<f64>,
+    /// Aceleración de la cinta respecto a su línea base, con el signo del flujo
+    #[pyo3(get, set)]
+    pub tape_intensity: Option<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl FlowSignal {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("FlowSignal(symbol={}, score={:.3}, cvd_flow={:?}, imbalance={:?}, tape={:?}, ts={})",
+                self.symbol, self.score, self.cvd_flow, self.book_imbalance, self.tape_intensity, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Valor de un instrumento sintético (combinación ponderada de símbolos) con
+/// su z-score sobre la ventana y la correlación de retornos de las dos
+/// primeras patas
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpreadMetrics {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub value: f64,
+    #[pyo3(get, set)]
+    pub mean: f64,
+    #[pyo3(get, set)]
+    pub std: f64,
+    /// (value - mean) / std; None con menos de dos muestras o sin varianza
+    #[pyo3(get, set)]
+    pub zscore: Option<f64>,
+    /// Correlación de Pearson de los log-retornos de las dos primeras patas
+    #[pyo3(get, set)]
+    pub correlation: Option<f64>,
+    /// Muestras en la ventana
+    #[pyo3(get, set)]
+    pub samples: usize,
+    /// Último precio de cada pata, en el orden de la definición
+    #[pyo3(get, set)]
+    pub leg_prices: Vec<f64>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl SpreadMetrics {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("SpreadMetrics(name={}, value={}, zscore={:?}, correlation={:?}, samples={}, ts={})",
+                self.name, self.value, self.zscore, self.correlation, self.samples, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Hueco de datos: un símbolo sin trades o sin quotes durante más del
+/// intervalo máximo en horario de mercado
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DataGapAlert {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    /// "trade" o "quote"
+    #[pyo3(get, set)]
+    pub stream: String,
+    /// Último evento antes del hueco
+    #[pyo3(get, set)]
+    pub last_ts: u64,
+    /// Momento de la detección o de la reanudación
+    #[pyo3(get, set)]
+    pub ts: u64,
+    /// Tiempo de mercado abierto sin eventos (ms)
+    #[pyo3(get, set)]
+    pub gap_ms: u64,
+    /// True si el stream ya se reanudó y el hueco está cerrado
+    #[pyo3(get, set)]
+    pub resumed: bool,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl DataGapAlert {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("DataGapAlert(symbol={}, stream={}, gap_ms={}, resumed={}, last_ts={}, ts={})",
+                self.symbol, self.stream, self.gap_ms, self.resumed, self.last_ts, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Evento de un muro de liquidez: nivel significativo que aparece, se
+/// repone o desaparece del libro
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WallEvent {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub side: String,  // "bid" | "ask"
+    #[pyo3(get, set)]
+    pub price: f64,
+    /// "appear" | "refresh" | "disappear" | "active"
+    #[pyo3(get, set)]
+    pub event: String,
+    /// Tamaño mostrado en el último snapshot en que el nivel era muro
+    #[pyo3(get, set)]
+    pub size: f64,
+    #[pyo3(get, set)]
+    pub peak_size: f64,
+    #[pyo3(get, set)]
+    pub first_seen_ts: u64,
+    /// ts - first_seen_ts
+    #[pyo3(get, set)]
+    pub lifetime_ms: u64,
+    /// Veces que el tamaño del nivel volvió a crecer
+    #[pyo3(get, set)]
+    pub refreshes: u32,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl WallEvent {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("WallEvent(symbol={}, side={}, price={}, event={}, size={}, peak={}, lifetime_ms={})",
+                self.symbol, self.side, self.price, self.event, self.size, self.peak_size, self.lifetime_ms)
+    }
+}
+}
+
+py_class! {
+/// Print de bloque: trade con tamaño por encima del percentil configurado
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LargePrint {
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub price: f64,
+    #[pyo3(get, set)]
+    pub size: f64,
+    #[pyo3(get, set)]
+    pub side: String,
+    #[pyo3(get, set)]
+    pub size_threshold: f64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl LargePrint {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("LargePrint(symbol={}, side={}, price={}, size={}, threshold={}, ts={})",
+                self.symbol, self.side, self.price, self.size, self.size_threshold, self.ts)
+    }
+}
+}
+
+py_class! {
+/// Resultado combinado de despachar un evento a todos los engines del manager
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetricsBundle {
+    #[pyo3(get, set)]
+    pub symbol: String,
+    #[pyo3(get, set)]
+    pub ts: u64,
+    #[pyo3(get, set)]
+    pub cvd: Option<CVDMetrics>,
+    #[pyo3(get, set)]
+    pub cvd_bar: Option<CVDBar>,
+    #[pyo3(get, set)]
+    pub footprint: Option<FootprintBar>,
+    #[pyo3(get, set)]
+    pub vwap: Option<VWAPMetrics>,
+    #[pyo3(get, set)]
+    pub rolling_vwap: Option<VWAPMetrics>,
+    #[pyo3(get, set)]
+    pub volume_profile: Option<VolumeProfileMetrics>,
+    #[pyo3(get, set)]
+    pub liquidity: Option<LiquidityMetrics>,
+    #[pyo3(get, set)]
+    pub heatmap: Option<HeatmapMetrics>,
+    #[pyo3(get, set)]
+    pub book_pressure: Option<BookPressureMetrics>,
+    #[pyo3(get, set)]
+    pub icebergs: Vec<IcebergDetection>,
+    #[pyo3(get, set)]
+    pub moving_averages: Vec<MovingAverageMetrics>,
+    #[pyo3(get, set)]
+    pub rsi: Vec<RSIMetrics>,
+    #[pyo3(get, set)]
+    pub atr: Vec<ATRMetrics>,
+    #[pyo3(get, set)]
+    pub volatility: Vec<VolatilityMetrics>,
+    #[pyo3(get, set)]
+    pub tape: Option<TapeMetrics>,
+    #[pyo3(get, set)]
+    pub vpin: Option<VPINMetrics>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub enriched_trade: Option<EnrichedTrade>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub sweep: Option<SweepEvent>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub absorptions: Vec<AbsorptionEvent>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub walls: Vec<WallEvent>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub spreads: Vec<SpreadMetrics>,
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub data_gaps: Vec<DataGapAlert>,
+    /// Señal de `FlowSignalEngine` a partir del resto del bundle
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub flow_signal: Option<FlowSignal>,
+    #[pyo3(get, set)]
+    pub custom: HashMap<String, HashMap<String, f64>>,
+}
+}
+
+impl MetricsBundle {
+    pub fn empty(symbol: &str, ts: u64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            ts,
+            cvd: None,
+            cvd_bar: None,
+            footprint: None,
+            vwap: None,
+            rolling_vwap: None,
+            volume_profile: None,
+            liquidity: None,
+            heatmap: None,
+            book_pressure: None,
+            icebergs: Vec::new(),
+            moving_averages: Vec::new(),
+            rsi: Vec::new(),
+            atr: Vec::new(),
+            volatility: Vec::new(),
+            tape: None,
+            vpin: None,
+            enriched_trade: None,
+            sweep: None,
+            absorptions: Vec::new(),
+            walls: Vec::new(),
+            spreads: Vec::new(),
+            data_gaps: Vec::new(),
+            flow_signal: None,
+            custom: HashMap::new(),
+        }
+    }
+    
+    /// Coloca una salida de indicador en su campo
+    pub fn push(&mut self, output: IndicatorOutput) {
+        match output {
+            IndicatorOutput::Cvd(m) => self.cvd = Some(m),
+            IndicatorOutput::CvdBar(m) => self.cvd_bar = Some(m),
+            IndicatorOutput::Footprint(m) => self.footprint = Some(m),
+            IndicatorOutput::Vwap(m) => self.vwap = Some(m),
+            IndicatorOutput::RollingVwap(m) => self.rolling_vwap = Some(m),
+            IndicatorOutput::VolumeProfile(m) => self.volume_profile = Some(m),
+            IndicatorOutput::Liquidity(m) => self.liquidity = Some(m),
+            IndicatorOutput::Heatmap(m) => self.heatmap = Some(m),
+            IndicatorOutput::BookPressure(m) => self.book_pressure = Some(m),
+            IndicatorOutput::Iceberg(m) => self.icebergs.push(m),
+            IndicatorOutput::MovingAverage(m) => self.moving_averages.push(m),
+            IndicatorOutput::Rsi(m) => self.rsi.push(m),
+            IndicatorOutput::Atr(m) => self.atr.push(m),
+            IndicatorOutput::Volatility(m) => self.volatility.push(m),
+            IndicatorOutput::Tape(m) => self.tape = Some(m),
+            IndicatorOutput::Vpin(m) => self.vpin = Some(m),
+            IndicatorOutput::EnrichedTrade(m) => self.enriched_trade = Some(m),
+            IndicatorOutput::Sweep(m) => self.sweep = Some(m),
+            IndicatorOutput::Absorption(m) => self.absorptions.push(m),
+            IndicatorOutput::Wall(m) => self.walls.push(m),
+            IndicatorOutput::Spread(m) => self.spreads.push(m),
+            IndicatorOutput::DataGap(m) => self.data_gaps.push(m),
+            IndicatorOutput::FlowSignal(m) => self.flow_signal = Some(m),
+            IndicatorOutput::Custom(name, values) => {
+                self.custom.entry(name).or_default().extend(values);
+            }
+        }
+    }
+    
+    /// Sin ninguna salida de indicador
+    pub fn is_empty(&self) -> bool {
+        self.cvd.is_none() && self.cvd_bar.is_none() && self.footprint.is_none() && self.vwap.is_none()
+            && self.rolling_vwap.is_none() && self.volume_profile.is_none() && self.liquidity.is_none()
+            && self.heatmap.is_none() && self.book_pressure.is_none() && self.icebergs.is_empty()
+            && self.moving_averages.is_empty() && self.rsi.is_empty() && self.atr.is_empty()
+            && self.volatility.is_empty() && self.tape.is_none() && self.vpin.is_none()
+            && self.enriched_trade.is_none() && self.sweep.is_none() && self.absorptions.is_empty()
+            && self.walls.is_empty() && self.spreads.is_empty() && self.data_gaps.is_empty()
+            && self.flow_signal.is_none() && self.custom.is_empty()
+    }
+}
+
+py_methods! {
+#[pymethods]
+impl MetricsBundle {
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("MetricsBundle(symbol={}, ts={}, cvd={}, vwap={}, liquidity={}, heatmap={}, ma={}, rsi={}, atr={}, custom={})",
+                self.symbol, self.ts, self.cvd.is_some(), self.vwap.is_some(),
+                self.liquidity.is_some(), self.heatmap.is_some(),
+                self.moving_averages.len(), self.rsi.len(), self.atr.len(), self.custom.len())
+    }
+}
+}
+
+py_class! {
+/// Métricas de VWAP
+#[pyclass(module = "indicators_core")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VWAPMetrics {
+    #[pyo3(get, set)]
+    pub vwap: f64,
+    #[pyo3(get, set)]
+    pub pv_sum: f64,
+    #[pyo3(get, set)]
+    pub v_sum: f64,
+    #[pyo3(get, set)]
+    pub session_id: Option<String>,
+    #[pyo3(get, set)]
+    pub anchor_ts: Option<u64>,  // None = VWAP de sesión
+    #[pyo3(get, set)]
+    pub std_dev: f64,
+    #[pyo3(get, set)]
+    pub upper_band_1: f64,
+    #[pyo3(get, set)]
+    pub lower_band_1: f64,
+    #[pyo3(get, set)]
+    pub upper_band_2: f64,
+    #[pyo3(get, set)]
+    pub lower_band_2: f64,
+    #[pyo3(get, set)]
+    pub upper_band_3: f64,
+    #[pyo3(get, set)]
+    pub lower_band_3: f64,
+    /// ts del último trade o barra acumulado (0 si no se conoce)
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub last_ts: u64,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl VWAPMetrics {
+    /// Las bandas se inicializan sobre el VWAP (desviación 0)
+    #[new]
+    #[pyo3(signature = (vwap, pv_sum, v_sum, session_id=None, anchor_ts=None))]
+    pub fn new(vwap: f64, pv_sum: f64, v_sum: f64, session_id: Option<String>, anchor_ts: Option<u64>) -> Self {
+        Self {
+            vwap, pv_sum, v_sum, session_id, anchor_ts,
+            std_dev: 0.0,
+            upper_band_1: vwap, lower_band_1: vwap,
+            upper_band_2: vwap, lower_band_2: vwap,
+            upper_band_3: vwap, lower_band_3: vwap,
+            last_ts: 0,
+        }
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        match self.anchor_ts {
+            Some(anchor_ts) => format!("VWAPMetrics(vwap={}, pv_sum={}, v_sum={}, anchor_ts={})",
+                                       self.vwap, self.pv_sum, self.v_sum, anchor_ts),
+            None => format!("VWAPMetrics(vwap={}, pv_sum={}, v_sum={})",
+                            self.vwap, self.pv_sum, self.v_sum),
+        }
+    }
+}
+}
+
+impl VWAPMetrics {
+    /// Calcula las bandas vwap ± k·σ para los tres multiplicadores
+    pub fn with_bands(mut self, std_dev: f64, multipliers: (f64, f64, f64)) -> Self {
+        let (k1, k2, k3) = multipliers;
+        self.std_dev = std_dev;
+        self.upper_band_1 = self.vwap + k1 * std_dev;
+        self.lower_band_1 = self.vwap - k1 * std_dev;
+        self.upper_band_2 = self.vwap + k2 * std_dev;
+        self.lower_band_2 = self.vwap - k2 * std_dev;
+        self.upper_band_3 = self.vwap + k3 * std_dev;
+        self.lower_band_3 = self.vwap - k3 * std_dev;
+        self
+    }
+}
+
+// to_dict / from_dict, JSON, MessagePack, pickle y deepcopy (ver py_serde)
+#[cfg(feature = "python")]
+impl_py_serde!(
+    Trade, Bar, Level, BookSnapshot, BookDelta, Quote, OpenInterest, FundingRate, Liquidation,
+    CVDMetrics, CVDBar, FootprintLevel, FootprintBar, LiquidityMetrics, LiquidityRollingStats,
+    EnrichedTrade, EffectiveSpreadMetrics, SpreadWindowStats, LiquidityAlertConfig, LiquidityAlert,
+    BookQualityAlert, IcebergDetection, AbsorptionEvent, BookPressureMetrics, Tile, HeatmapMetrics,
+    VolumeProfileMetrics, MovingAverageMetrics, RSIMetrics, VolatilityMetrics, ATRMetrics,
+    OIMetrics, LiquidationMetrics, LiquidationCascade, VPINMetrics, TapeMetrics, SweepEvent,
+    QuoteMetrics, FlowSignal, SpreadMetrics, DataGapAlert, WallEvent, LargePrint, MetricsBundle,
+    VWAPMetrics,
+);
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_equality_hash_and_order() {
+        let a = Trade::new(1_000, 100.0, 0.0, "AAPL".to_string());
+        let b = Trade::new(1_000, 100.0, -0.0, "AAPL".to_string());
+        assert!(a.__richcmp__(&b, CompareOp::Eq));
+        assert_eq!(a.__hash__(), b.__hash__());
+        
+        let later = Trade::new(2_000, 99.0, 1.0, "AAPL".to_string());
+        assert!(a.__richcmp__(&later, CompareOp::Lt));
+        assert!(later.__richcmp__(&a, CompareOp::Ge));
+        
+        let nan = Level::new(f64::NAN, 1.0);
+        assert!(!nan.__richcmp__(&Level::new(1.0, 1.0), CompareOp::Lt));
+        assert!(nan.__richcmp__(&Level::new(1.0, 1.0), CompareOp::Ne));
+        
+        let book = |size| BookSnapshot::new(1_000, "AAPL".to_string(), vec![Level::new(99.0, size)], vec![]);
+        assert!(book(1.0).__richcmp__(&book(1.0), CompareOp::Eq));
+        assert!(book(1.0).__richcmp__(&book(2.0), CompareOp::Ne));
+        assert_ne!(book(1.0).__hash__(), book(2.0).__hash__());
     }
 }
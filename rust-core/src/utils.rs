@@ -2,6 +2,19 @@
 //! 
 //! Funciones de utilidad para el núcleo de indicadores.
 
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use dashmap::DashMap;
+use std::sync::Arc;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+pub mod ewma;
+pub mod moments;
+pub mod rolling;
+pub mod sketch;
+pub mod simd;
+
 /// División segura (evita NaNs e Infinitos)
 pub fn safe_div(num: f64, den: f64) -> f64 {
     if den.is_finite() && den > 0.0 && num.is_finite() {
@@ -26,30 +39,158 @@ pub fn calculate_spread(bid: f64, ask: f64) -> f64 {
     ask - bid
 }
 
+/// Pendiente de la regresión lineal por mínimos cuadrados de ys sobre xs.
+/// Devuelve 0 con menos de dos puntos o sin varianza en xs.
+pub fn linear_regression_slope(xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let mean_x = xs[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = ys[..n].iter().sum::<f64>() / n as f64;
+    let (cov, var) = xs[..n].iter().zip(&ys[..n]).fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+    safe_div(cov, var)
+}
+
+/// Coeficiente de correlación de Pearson entre xs e ys.
+/// Devuelve 0 con menos de dos puntos o si alguna serie no tiene varianza.
+pub fn pearson_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let mean_x = xs[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = ys[..n].iter().sum::<f64>() / n as f64;
+    let (cov, var_x, var_y) = xs[..n].iter().zip(&ys[..n]).fold((0.0, 0.0, 0.0), |(cov, vx, vy), (x, y)| {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        (cov + dx * dy, vx + dx * dx, vy + dy * dy)
+    });
+    safe_div(cov, (var_x * var_y).sqrt()).clamp(-1.0, 1.0)
+}
+
 /// Cuantiza un precio al tick más cercano
 pub fn quantize_price(price: f64, tick_size: f64) -> f64 {
     (price / tick_size).round() * tick_size
 }
 
+/// Índice entero del tick más cercano a un precio
+pub fn price_to_tick(price: f64, tick_size: f64) -> i64 {
+    (price / tick_size).round() as i64
+}
+
+/// Precio correspondiente a un índice de tick
+pub fn tick_to_price(tick: i64, tick_size: f64) -> f64 {
+    tick as f64 * tick_size
+}
+
 /// Calcula el bucket temporal
 pub fn calculate_bucket(ts: u64, bucket_ms: u64) -> u64 {
     (ts / bucket_ms) * bucket_ms
 }
 
-/// Agregación SIMD de volumen (optimizada con chunks)
-/// 
-/// Para arrays grandes, usa procesamiento por chunks para mejor caché locality
-pub fn aggregate_volume_simd(volumes: &[f64]) -> f64 {
-    // Procesar en chunks de 4 para mejor caché
-    let chunk_size = 4;
-    let mut sum = 0.0;
+/// Calendario periódico de sesiones: cada sesión empieza en offset_ms + n·period_ms
+/// (epoch UTC). Por ejemplo, reset diario a las 00:00 UTC = (86_400_000, 0).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionSchedule {
+    pub period_ms: u64,
+    pub offset_ms: u64,
+}
+
+impl SessionSchedule {
+    pub const DAILY_UTC: SessionSchedule = SessionSchedule { period_ms: 86_400_000, offset_ms: 0 };
     
-    // Procesar chunks completos
-    for chunk in volumes.chunks(chunk_size) {
-        sum += chunk.iter().sum::<f64>();
+    /// Devuelve None si el periodo es 0 o el offset no cabe en el periodo
+    pub fn new(period_ms: u64, offset_ms: u64) -> Option<Self> {
+        if period_ms == 0 || offset_ms >= period_ms {
+            return None;
+        }
+        Some(Self { period_ms, offset_ms })
+    }
+    
+    /// Índice de la sesión a la que pertenece el timestamp
+    pub fn session_id(&self, ts: u64) -> u64 {
+        // Desplazar un periodo para no hacer underflow antes del primer offset
+        (ts + self.period_ms - self.offset_ms) / self.period_ms
+    }
+    
+    /// Timestamp de inicio de la sesión que contiene ts
+    pub fn session_start(&self, ts: u64) -> u64 {
+        (self.session_id(ts) * self.period_ms + self.offset_ms).saturating_sub(self.period_ms)
+    }
+}
+
+/// Convierte un timeframe ("500ms", "1s", "5m", "1h", "1d") a milisegundos
+pub fn parse_timeframe_ms(tf: &str) -> Option<u64> {
+    let tf = tf.trim();
+    let split = tf.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = tf.split_at(split);
+    let value: u64 = value.parse().ok()?;
+    let factor = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return None,
+    };
+    match value.checked_mul(factor)? {
+        0 => None,
+        ms => Some(ms),
+    }
+}
+
+/// Suma compensada de Neumaier: el error de redondeo de cada suma se acumula
+/// en un término aparte, así que un total que crece durante todo el día no
+/// pierde los sumandos pequeños. A diferencia de Kahan también es correcta
+/// cuando el sumando es mayor que el total.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NeumaierSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl NeumaierSum {
+    pub fn add(&mut self, value: f64) {
+        let total = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - total) + value;
+        } else {
+            self.compensation += (value - total) + self.sum;
+        }
+        self.sum = total;
     }
     
-    sum
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// Suma compensada (Neumaier) de una secuencia
+pub fn compensated_sum(values: impl IntoIterator<Item = f64>) -> f64 {
+    let mut sum = NeumaierSum::default();
+    values.into_iter().for_each(|v| sum.add(v));
+    sum.value()
+}
+
+/// Claves (símbolos) de un estado por símbolo, ordenadas
+pub fn sorted_symbols<V>(map: &DashMap<String, V>) -> Vec<String> {
+    let mut symbols: Vec<String> = map.iter().map(|e| e.key().clone()).collect();
+    symbols.sort();
+    symbols
+}
+
+/// Número de símbolos distintos entre las claves de uno o varios estados
+pub fn count_symbols(symbols: impl IntoIterator<Item = String>) -> usize {
+    symbols.into_iter().collect::<std::collections::HashSet<_>>().len()
+}
+
+/// Suma de volúmenes con SIMD (AVX en x86_64 si la CPU lo soporta, si no
+/// escalar); ver `utils::simd`
+pub fn aggregate_volume_simd(volumes: &[f64]) -> f64 {
+    simd::sum(volumes)
 }
 
 /// Suma incremental optimizada para slides de ventana deslizante
@@ -71,17 +212,114 @@ pub fn sliding_window_sum(values: &[f64], window_size: usize) -> Vec<f64> {
     result
 }
 
-/// Binning de precios con SIMD (placeholder)
-pub fn price_binning_simd(prices: &[f64], tick_size: f64) -> Vec<u64> {
-    prices.iter()
-        .map(|&p| quantize_price(p, tick_size) as u64)
-        .collect()
+/// Índice de tick de cada precio (`price_to_tick`) con SIMD
+pub fn price_binning_simd(prices: &[f64], tick_size: f64) -> Vec<i64> {
+    simd::price_to_ticks(prices, tick_size)
+}
+
+py_class! {
+/// Registro de tick size por símbolo con un valor por defecto.
+/// Clonar el registro comparte el estado (Arc), de modo que varios engines
+/// pueden consumir el mismo registro.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct TickSizeRegistry {
+    #[pyo3(get)]
+    pub default_tick: f64,
+    ticks: Arc<DashMap<String, f64>>,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl TickSizeRegistry {
+    #[new]
+    #[pyo3(signature = (default_tick=0.01))]
+    pub fn new(default_tick: f64) -> Result<Self, ProcessError> {
+        validate_tick(default_tick)?;
+        Ok(Self { default_tick, ticks: Arc::new(DashMap::new()) })
+    }
+    
+    /// Configura el tick por defecto (símbolos sin tick propio)
+    #[setter]
+    pub fn set_default_tick(&mut self, default_tick: f64) -> Result<(), ProcessError> {
+        validate_tick(default_tick)?;
+        self.default_tick = default_tick;
+        Ok(())
+    }
+    
+    /// Registra el tick size de un símbolo
+    pub fn set_tick_size(&self, symbol: &str, tick_size: f64) -> Result<(), ProcessError> {
+        validate_tick(tick_size)?;
+        self.ticks.insert(symbol.to_string(), tick_size);
+        Ok(())
+    }
+    
+    /// Tick size de un símbolo (o el de por defecto)
+    pub fn get_tick_size(&self, symbol: &str) -> f64 {
+        self.ticks.get(symbol).map(|t| *t).unwrap_or(self.default_tick)
+    }
+    
+    /// Elimina el tick propio de un símbolo (vuelve al de por defecto)
+    pub fn remove(&self, symbol: &str) {
+        self.ticks.remove(symbol);
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("TickSizeRegistry(default_tick={}, symbols={})", self.default_tick, self.ticks.len())
+    }
+}
+}
+
+impl Default for TickSizeRegistry {
+    fn default() -> Self {
+        Self { default_tick: 0.01, ticks: Arc::new(DashMap::new()) }
+    }
+}
+
+fn validate_tick(tick_size: f64) -> Result<(), ProcessError> {
+    if !(tick_size.is_finite() && tick_size > 0.0) {
+        return Err(ProcessError::invalid("tick_size must be > 0"));
+    }
+    Ok(())
+}
+
+/// Ejecuta `f` con el GIL (tests de métodos que lo liberan con `allow_threads`)
+#[cfg(all(test, feature = "python"))]
+pub(crate) fn with_py<R>(f: impl FnOnce(Python<'_>) -> R) -> R {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(f)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_price_tick_round_trip() {
+        assert_eq!(price_to_tick(150.01, 0.01), 15001);
+        assert_eq!(price_to_tick(-0.26, 0.25), -1);
+        assert!((tick_to_price(15001, 0.01) - 150.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compensated_sum_vs_naive() {
+        // Un día de micro-lotes: 1e6 trades de 0.001
+        let sizes = vec![0.001; 1_000_000];
+        let naive: f64 = sizes.iter().sum();
+        assert!((naive - 1000.0).abs() > 1e-9);
+        assert_eq!(compensated_sum(sizes.iter().copied()), 1000.0);
+        
+        // pv de un activo caro con tamaños pequeños
+        let mut pv = NeumaierSum::default();
+        (0..1_000_000).for_each(|_| pv.add(65_000.1 * 0.001));
+        assert_eq!(pv.value(), 65_000_100.0);
+        
+        // Sumandos mayores que el total (donde Kahan falla)
+        assert_eq!(compensated_sum([1.0, 1e100, 1.0, -1e100]), 2.0);
+    }
+
     #[test]
     fn test_safe_div_normal() {
         assert_eq!(safe_div(10.0, 2.0), 5.0);
@@ -118,6 +356,22 @@ mod tests {
         assert!((calculate_spread(149.99, 150.01) - 0.02).abs() < 0.001);
     }
 
+    #[test]
+    fn test_linear_regression_slope() {
+        assert_eq!(linear_regression_slope(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]), 2.0);
+        assert_eq!(linear_regression_slope(&[1.0, 2.0, 3.0], &[5.0, 5.0, 5.0]), 0.0);
+        assert_eq!(linear_regression_slope(&[1.0], &[5.0]), 0.0);
+        assert_eq!(linear_regression_slope(&[1.0, 1.0], &[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn test_pearson_correlation() {
+        assert!((pearson_correlation(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]) - 1.0).abs() < 1e-12);
+        assert!((pearson_correlation(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]) + 1.0).abs() < 1e-12);
+        assert_eq!(pearson_correlation(&[1.0, 2.0, 3.0], &[5.0, 5.0, 5.0]), 0.0);
+        assert_eq!(pearson_correlation(&[1.0], &[5.0]), 0.0);
+    }
+
     #[test]
     fn test_quantize_price() {
         assert_eq!(quantize_price(150.23, 0.01), 150.23);
@@ -133,6 +387,35 @@ mod tests {
         assert_eq!(calculate_bucket(1234569000, 1000), 1234569000);
     }
 
+    #[test]
+    fn test_session_schedule() {
+        assert!(SessionSchedule::new(0, 0).is_none());
+        assert!(SessionSchedule::new(1000, 1000).is_none());
+        
+        // Sesiones de 1s que abren en x.500
+        let schedule = SessionSchedule::new(1000, 500).unwrap();
+        assert_eq!(schedule.session_id(499), schedule.session_id(0));
+        assert_ne!(schedule.session_id(499), schedule.session_id(500));
+        assert_eq!(schedule.session_id(500), schedule.session_id(1499));
+        assert_eq!(schedule.session_start(1234), 500);
+        assert_eq!(schedule.session_start(1500), 1500);
+        
+        let daily = SessionSchedule::DAILY_UTC;
+        assert_eq!(daily.session_start(86_400_000 + 5), 86_400_000);
+    }
+
+    #[test]
+    fn test_parse_timeframe_ms() {
+        assert_eq!(parse_timeframe_ms("500ms"), Some(500));
+        assert_eq!(parse_timeframe_ms("1s"), Some(1_000));
+        assert_eq!(parse_timeframe_ms("5m"), Some(300_000));
+        assert_eq!(parse_timeframe_ms("1h"), Some(3_600_000));
+        assert_eq!(parse_timeframe_ms("1d"), Some(86_400_000));
+        assert_eq!(parse_timeframe_ms("0s"), None);
+        assert_eq!(parse_timeframe_ms("m"), None);
+        assert_eq!(parse_timeframe_ms("5x"), None);
+    }
+
     #[test]
     fn test_aggregate_volume_simd() {
         let volumes = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
@@ -166,11 +449,25 @@ mod tests {
         let prices = vec![150.23, 150.27, 150.25];
         let result = price_binning_simd(&prices, 0.01);
         
-        // quantize_price redondea y luego se castea a u64, truncando decimales
-        // Así que 150.23 -> 150 (como u64)
-        assert_eq!(result.len(), 3);
-        assert_eq!(result[0], 150);
-        assert_eq!(result[1], 150);
-        assert_eq!(result[2], 150);
-    }
-}
\ No newline at end of file
+        assert_eq!(result, vec![15023, 15027, 15025]);
+        assert_eq!(price_binning_simd(&[-0.26, 0.13], 0.25), vec![-1, 1]);
+    }
+
+    #[test]
+    fn test_tick_size_registry() {
+        let registry = TickSizeRegistry::new(0.01).unwrap();
+        registry.set_tick_size("BTCUSDT", 0.1).unwrap();
+        assert!(registry.set_tick_size("BTCUSDT", 0.0).is_err());
+        
+        assert_eq!(registry.get_tick_size("BTCUSDT"), 0.1);
+        assert_eq!(registry.get_tick_size("AAPL"), 0.01);
+        
+        // Los clones comparten estado
+        let shared = registry.clone();
+        shared.set_tick_size("SPY_OPT", 0.05).unwrap();
+        assert_eq!(registry.get_tick_size("SPY_OPT"), 0.05);
+        
+        registry.remove("BTCUSDT");
+        assert_eq!(registry.get_tick_size("BTCUSDT"), 0.01);
+    }
+}
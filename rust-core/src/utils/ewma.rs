@@ -0,0 +1,193 @@
+//! # EWMA
+//! 
+//! Media y varianza exponenciales con vida media en tiempo o en eventos.
+//! 
+//! Los datos de mercado llegan a intervalos irregulares, así que un alpha
+//! fijo pesa igual un segundo con cien actualizaciones que una hora con
+//! una. Con vida media en ms cada valor pesa por el tiempo que estuvo
+//! vigente: se mantiene hasta la siguiente observación (como el spread del
+//! libro) y repartir el mismo tramo en más actualizaciones no cambia el
+//! resultado. Con vida media en eventos cada observación pesa lo mismo.
+
+use crate::errors::ProcessError;
+
+/// Vida media del suavizado
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HalfLife {
+    /// Milisegundos en los que el peso de un valor se reduce a la mitad
+    Millis(u64),
+    /// Observaciones tras las que el peso de un valor se reduce a la mitad
+    Events(f64),
+}
+
+impl HalfLife {
+    /// Desde los argumentos Python (`half_life_ms` o `half_life_events`);
+    /// ninguno = sin suavizado
+    pub fn from_args(half_life_ms: Option<u64>, half_life_events: Option<f64>) -> Result<Option<Self>, ProcessError> {
+        match (half_life_ms, half_life_events) {
+            (Some(_), Some(_)) => Err(ProcessError::invalid(
+                "set half_life_ms or half_life_events, not both")),
+            (Some(0), None) => Err(ProcessError::invalid("half_life_ms must be > 0")),
+            (Some(ms), None) => Ok(Some(Self::Millis(ms))),
+            (None, Some(events)) if !(events.is_finite() && events > 0.0) => Err(
+                ProcessError::invalid("half_life_events must be > 0")),
+            (None, Some(events)) => Ok(Some(Self::Events(events))),
+            (None, None) => Ok(None),
+        }
+    }
+    
+    /// Inverso de `from_args`: (half_life_ms, half_life_events)
+    pub fn to_args(half_life: Option<Self>) -> (Option<u64>, Option<f64>) {
+        match half_life {
+            Some(Self::Millis(ms)) => (Some(ms), None),
+            Some(Self::Events(events)) => (None, Some(events)),
+            None => (None, None),
+        }
+    }
+    
+    /// Peso que conserva la media tras `dt_ms` (en eventos, tras uno)
+    pub fn decay(&self, dt_ms: u64) -> f64 {
+        match *self {
+            Self::Millis(ms) => 0.5f64.powf(dt_ms as f64 / ms as f64),
+            Self::Events(events) => 0.5f64.powf(1.0 / events),
+        }
+    }
+    
+    /// Valor que entra en la media y su peso al observar `value` en `ts`;
+    /// None en la primera observación
+    fn step(&self, last: Option<(u64, f64)>, ts: u64, value: f64) -> Option<(f64, f64)> {
+        let (last_ts, last_value) = last?;
+        Some(match self {
+            // El valor previo estuvo vigente desde last_ts hasta ts
+            Self::Millis(_) => (last_value, 1.0 - self.decay(ts.saturating_sub(last_ts))),
+            Self::Events(_) => (value, 1.0 - self.decay(0)),
+        })
+    }
+}
+
+/// Media exponencial
+#[derive(Clone, Debug)]
+pub struct Ewma {
+    half_life: HalfLife,
+    mean: f64,
+    // Última observación (ts, valor); un ts anterior no retrocede el reloj
+    last: Option<(u64, f64)>,
+}
+
+impl Ewma {
+    pub fn new(half_life: HalfLife) -> Self {
+        Self { half_life, mean: 0.0, last: None }
+    }
+    
+    /// Incorpora un valor observado en `ts` y devuelve la media
+    pub fn update(&mut self, ts: u64, value: f64) -> f64 {
+        match self.half_life.step(self.last, ts, value) {
+            Some((x, alpha)) => self.mean += alpha * (x - self.mean),
+            None => self.mean = value,
+        }
+        self.last = Some((self.last.map_or(ts, |(last_ts, _)| last_ts.max(ts)), value));
+        self.mean
+    }
+    
+    /// Media actual; None sin observaciones
+    pub fn value(&self) -> Option<f64> {
+        self.last.map(|_| self.mean)
+    }
+}
+
+/// Media y varianza exponenciales
+#[derive(Clone, Debug)]
+pub struct EwVar {
+    half_life: HalfLife,
+    mean: f64,
+    variance: f64,
+    last: Option<(u64, f64)>,
+}
+
+impl EwVar {
+    pub fn new(half_life: HalfLife) -> Self {
+        Self { half_life, mean: 0.0, variance: 0.0, last: None }
+    }
+    
+    /// Incorpora un valor observado en `ts`
+    pub fn update(&mut self, ts: u64, value: f64) {
+        match self.half_life.step(self.last, ts, value) {
+            Some((x, alpha)) => {
+                let diff = x - self.mean;
+                let increment = alpha * diff;
+                self.mean += increment;
+                self.variance = (1.0 - alpha) * (self.variance + diff * increment);
+            }
+            None => {
+                self.mean = value;
+                self.variance = 0.0;
+            }
+        }
+        self.last = Some((self.last.map_or(ts, |(last_ts, _)| last_ts.max(ts)), value));
+    }
+    
+    pub fn mean(&self) -> Option<f64> {
+        self.last.map(|_| self.mean)
+    }
+    
+    pub fn variance(&self) -> Option<f64> {
+        self.last.map(|_| self.variance.max(0.0))
+    }
+    
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_ewma_events() {
+        let mut ewma = Ewma::new(HalfLife::Events(1.0));
+        assert_eq!(ewma.value(), None);
+        assert_eq!(ewma.update(0, 0.0), 0.0);
+        assert_eq!(ewma.update(0, 10.0), 5.0);
+        assert_eq!(ewma.update(0, 10.0), 7.5);
+        
+        let mut ewvar = EwVar::new(HalfLife::Events(1.0));
+        ewvar.update(0, 0.0);
+        ewvar.update(0, 10.0);
+        assert_eq!((ewvar.mean(), ewvar.std_dev()), (Some(5.0), Some(5.0)));
+    }
+    
+    #[test]
+    fn test_ewma_irregular_time() {
+        let half_life = HalfLife::Millis(1_000);
+        let mut ewma = Ewma::new(half_life);
+        ewma.update(0, 10.0);
+        // 10 vigente durante una vida media
+        assert_eq!(ewma.update(1_000, 20.0), 10.0);
+        assert_eq!(ewma.update(2_000, 20.0), 15.0);
+        // Sin tiempo transcurrido no cambia, pero 30 pasa a ser el vigente
+        assert_eq!(ewma.update(2_000, 30.0), 15.0);
+        assert_eq!(ewma.update(3_000, 30.0), 22.5);
+        
+        // Más actualizaciones del mismo valor no cambian la media
+        let mut sparse = EwVar::new(half_life);
+        let mut dense = EwVar::new(half_life);
+        for (ts, value) in [(0, 1.0), (700, 3.0), (2_000, 2.0)] {
+            sparse.update(ts, value);
+        }
+        for (ts, value) in [(0, 1.0), (300, 1.0), (700, 3.0), (1_100, 3.0), (1_500, 3.0), (2_000, 2.0)] {
+            dense.update(ts, value);
+        }
+        assert!((sparse.mean().unwrap() - dense.mean().unwrap()).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_half_life_args() {
+        assert_eq!(HalfLife::from_args(None, None).unwrap(), None);
+        assert_eq!(HalfLife::from_args(Some(500), None).unwrap(), Some(HalfLife::Millis(500)));
+        assert!(HalfLife::from_args(Some(0), None).is_err());
+        assert!(HalfLife::from_args(None, Some(-1.0)).is_err());
+        assert!(HalfLife::from_args(Some(500), Some(2.0)).is_err());
+        assert_eq!(HalfLife::to_args(Some(HalfLife::Events(2.0))), (None, Some(2.0)));
+    }
+}
@@ -0,0 +1,390 @@
+//! # Moments
+//! 
+//! Media, varianza, asimetría y curtosis en streaming (Welford generalizado
+//! a momentos de orden 3 y 4, fórmulas de Pébay). Dos acumuladores se
+//! combinan con `merge` sin volver a recorrer los datos, y `remove` deshace
+//! un alta para mantener ventanas deslizantes.
+//! 
+//! `WeightedVariance` es la versión ponderada de media y varianza (West),
+//! para las bandas de VWAP: no resta Σp²v/Σv - vwap², que con precios
+//! grandes y poca dispersión pierde todos los dígitos significativos.
+//! 
+//! `StreamingStats` expone el acumulador a Python.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use crate::py_macros::{py_class, py_methods};
+
+/// Momentos centrales acumulados: n, media y Σ(x - media)^k para k = 2, 3, 4
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Moments {
+    n: u64,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+}
+
+impl Moments {
+    /// Añade un valor (los no finitos se ignoran)
+    pub fn push(&mut self, value: f64) {
+        if value.is_finite() {
+            self.merge(&Self { n: 1, mean: value, ..Self::default() });
+        }
+    }
+    
+    /// Retira un valor añadido antes (los no finitos se ignoran, como en `push`)
+    pub fn remove(&mut self, value: f64) {
+        if !value.is_finite() || self.n == 0 {
+            return;
+        }
+        if self.n == 1 {
+            *self = Self::default();
+            return;
+        }
+        // Inverso de merge(resto, {value})
+        let n = self.n as f64;
+        let na = n - 1.0;
+        let mean = self.mean - (value - self.mean) / na;
+        let delta = value - mean;
+        let delta2 = delta * delta;
+        let m2 = self.m2 - delta2 * na / n;
+        let m3 = self.m3 - delta2 * delta * na * (na - 1.0) / (n * n) + 3.0 * delta * m2 / n;
+        let m4 = self.m4 - delta2 * delta2 * na * (na * na - na + 1.0) / (n * n * n)
+            - 6.0 * delta2 * m2 / (n * n) + 4.0 * delta * m3 / n;
+        *self = Self { n: self.n - 1, mean, m2, m3, m4 };
+    }
+    
+    /// Combina con otro acumulador: el resultado es el de la unión de los datos
+    pub fn merge(&mut self, other: &Self) {
+        if other.n == 0 {
+            return;
+        }
+        if self.n == 0 {
+            *self = *other;
+            return;
+        }
+        let (na, nb) = (self.n as f64, other.n as f64);
+        let n = na + nb;
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+        let mean = self.mean + delta * nb / n;
+        let m2 = self.m2 + other.m2 + delta2 * na * nb / n;
+        let m3 = self.m3 + other.m3 + delta2 * delta * na * nb * (na - nb) / (n * n)
+            + 3.0 * delta * (na * other.m2 - nb * self.m2) / n;
+        let m4 = self.m4 + other.m4 + delta2 * delta2 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+            + 6.0 * delta2 * (na * na * other.m2 + nb * nb * self.m2) / (n * n)
+            + 4.0 * delta * (na * other.m3 - nb * self.m3) / n;
+        *self = Self { n: self.n + other.n, mean, m2, m3, m4 };
+    }
+    
+    pub fn count(&self) -> u64 {
+        self.n
+    }
+    
+    pub fn mean(&self) -> Option<f64> {
+        (self.n > 0).then_some(self.mean)
+    }
+    
+    /// Varianza con divisor n - ddof (0 poblacional, 1 muestral); None si n <= ddof
+    pub fn variance(&self, ddof: u64) -> Option<f64> {
+        (self.n > ddof).then(|| self.m2.max(0.0) / (self.n - ddof) as f64)
+    }
+    
+    pub fn std_dev(&self, ddof: u64) -> Option<f64> {
+        self.variance(ddof).map(f64::sqrt)
+    }
+    
+    /// Asimetría poblacional m3 / m2^1.5; None con varianza nula
+    pub fn skewness(&self) -> Option<f64> {
+        let variance = self.spread()?;
+        Some(self.m3 / self.n as f64 / variance.powf(1.5))
+    }
+    
+    /// Curtosis en exceso m4 / m2² - 3 (0 para la normal); None con varianza nula
+    pub fn kurtosis(&self) -> Option<f64> {
+        let variance = self.spread()?;
+        Some(self.m4 / self.n as f64 / (variance * variance) - 3.0)
+    }
+    
+    /// Varianza poblacional si no es nula frente a la escala de la media
+    /// (los residuos de `remove` no cuentan como dispersión)
+    fn spread(&self) -> Option<f64> {
+        let variance = self.variance(0)?;
+        (variance > (f64::EPSILON * self.mean).powi(2)).then_some(variance)
+    }
+}
+
+/// Media y varianza ponderadas incrementales (West, 1979): m2 = Σ w·(x - media)²
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeightedVariance {
+    weight: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl WeightedVariance {
+    /// Añade un valor con peso `weight` (los no finitos o de peso <= 0 se ignoran)
+    pub fn push(&mut self, value: f64, weight: f64) {
+        if value.is_finite() && weight.is_finite() && weight > 0.0 {
+            self.apply(value, weight);
+        }
+    }
+    
+    /// Retira un valor añadido antes con el mismo peso; si no queda peso
+    /// (salvo residuo de redondeo), vacía el acumulador
+    pub fn remove(&mut self, value: f64, weight: f64) {
+        if !value.is_finite() || !weight.is_finite() || weight <= 0.0 {
+            return;
+        }
+        if self.weight - weight <= self.weight * 1e-12 {
+            *self = Self::default();
+            return;
+        }
+        self.apply(value, -weight);
+    }
+    
+    fn apply(&mut self, value: f64, weight: f64) {
+        let total = self.weight + weight;
+        let delta = value - self.mean;
+        let r = delta * weight / total;
+        self.mean += r;
+        self.m2 += self.weight * delta * r;
+        self.weight = total;
+    }
+    
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+    
+    /// Media ponderada; None sin peso acumulado
+    pub fn mean(&self) -> Option<f64> {
+        (self.weight > 0.0).then_some(self.mean)
+    }
+    
+    /// Varianza poblacional ponderada m2 / Σw (0 sin peso acumulado)
+    pub fn variance(&self) -> f64 {
+        if self.weight > 0.0 { self.m2.max(0.0) / self.weight } else { 0.0 }
+    }
+}
+
+py_class! {
+/// Estadísticos en streaming de una serie: media, varianza, asimetría y curtosis
+#[pyclass]
+#[derive(Clone, Debug, Default)]
+pub struct StreamingStats {
+    moments: Moments,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl StreamingStats {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Añade un valor (los no finitos se ignoran)
+    pub fn update(&mut self, value: f64) {
+        self.moments.push(value);
+    }
+    
+    /// Añade varios valores en orden
+    pub fn update_many(&mut self, values: Vec<f64>) {
+        for value in values {
+            self.moments.push(value);
+        }
+    }
+    
+    /// Incorpora los datos de otro acumulador (p. ej. de otra partición o proceso)
+    pub fn merge(&mut self, other: StreamingStats) {
+        self.moments.merge(&other.moments);
+    }
+    
+    /// Valores acumulados
+    #[getter]
+    pub fn count(&self) -> u64 {
+        self.moments.count()
+    }
+    
+    #[getter]
+    pub fn mean(&self) -> Option<f64> {
+        self.moments.mean()
+    }
+    
+    /// Varianza con divisor n - ddof; None si n <= ddof
+    #[pyo3(signature = (ddof=0))]
+    pub fn variance(&self, ddof: u64) -> Option<f64> {
+        self.moments.variance(ddof)
+    }
+    
+    #[pyo3(signature = (ddof=0))]
+    pub fn std_dev(&self, ddof: u64) -> Option<f64> {
+        self.moments.std_dev(ddof)
+    }
+    
+    /// Asimetría poblacional; None con varianza nula
+    #[getter]
+    pub fn skewness(&self) -> Option<f64> {
+        self.moments.skewness()
+    }
+    
+    /// Curtosis en exceso (0 para la normal); None con varianza nula
+    #[getter]
+    pub fn kurtosis(&self) -> Option<f64> {
+        self.moments.kurtosis()
+    }
+    
+    /// Descarta los valores acumulados
+    pub fn reset(&mut self) {
+        self.moments = Moments::default();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __len__(&self) -> usize {
+        self.moments.count() as usize
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("StreamingStats(count={}, mean={:?}, std_dev={:?})",
+                self.moments.count(), self.moments.mean(), self.moments.std_dev(0))
+    }
+}
+}
+
+impl StreamingStats {
+    pub fn from_moments(moments: Moments) -> Self {
+        Self { moments }
+    }
+    
+    pub fn moments(&self) -> &Moments {
+        &self.moments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn assert_close(a: Option<f64>, b: f64) {
+        let a = a.unwrap();
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+    
+    /// Momentos recorriendo los datos dos veces
+    fn direct(values: &[f64]) -> (f64, f64, f64, f64) {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let central = |k: i32| values.iter().map(|x| (x - mean).powi(k)).sum::<f64>() / n;
+        let variance = central(2);
+        (mean, variance, central(3) / variance.powf(1.5), central(4) / (variance * variance) - 3.0)
+    }
+    
+    #[test]
+    fn test_moments_match_direct() {
+        let values = [101.5, 99.0, 100.25, 104.0, 98.5, 100.0, 97.75, 103.0, 100.5];
+        let mut moments = Moments::default();
+        assert_eq!((moments.mean(), moments.variance(0), moments.skewness()), (None, None, None));
+        for value in values {
+            moments.push(value);
+        }
+        moments.push(f64::NAN);
+        
+        let (mean, variance, skewness, kurtosis) = direct(&values);
+        assert_eq!(moments.count(), 9);
+        assert_close(moments.mean(), mean);
+        assert_close(moments.variance(0), variance);
+        assert_close(moments.variance(1), variance * 9.0 / 8.0);
+        assert_close(moments.skewness(), skewness);
+        assert_close(moments.kurtosis(), kurtosis);
+        
+        // Serie constante: sin dispersión no hay asimetría ni curtosis
+        let mut flat = Moments::default();
+        flat.push(5.0);
+        flat.push(5.0);
+        assert_eq!((flat.variance(0), flat.skewness(), flat.kurtosis()), (Some(0.0), None, None));
+        assert_eq!(flat.variance(2), None);
+    }
+    
+    #[test]
+    fn test_moments_merge_and_remove() {
+        let values: Vec<f64> = (0..40).map(|i| ((i * 37) % 11) as f64 * 0.75 + i as f64 * 0.1).collect();
+        let mut all = Moments::default();
+        let (mut left, mut right) = (Moments::default(), Moments::default());
+        for (i, &value) in values.iter().enumerate() {
+            all.push(value);
+            if i < 15 { left.push(value) } else { right.push(value) }
+        }
+        left.merge(&right);
+        assert_eq!(left.count(), all.count());
+        assert_close(left.mean(), all.mean().unwrap());
+        assert_close(left.variance(0), all.variance(0).unwrap());
+        assert_close(left.skewness(), all.skewness().unwrap());
+        assert_close(left.kurtosis(), all.kurtosis().unwrap());
+        
+        // Ventana: retirar los 30 primeros deja los momentos de los 10 últimos
+        for &value in &values[..30] {
+            all.remove(value);
+        }
+        let (mean, variance, skewness, kurtosis) = direct(&values[30..]);
+        assert_close(all.mean(), mean);
+        assert_close(all.variance(0), variance);
+        assert_close(all.skewness(), skewness);
+        assert_close(all.kurtosis(), kurtosis);
+        
+        for &value in &values[30..] {
+            all.remove(value);
+        }
+        assert_eq!(all, Moments::default());
+    }
+    
+    #[test]
+    fn test_weighted_variance_large_prices() {
+        // Precio alto con spread de un tick: E[p²] - E[p]² cancela casi todos los dígitos
+        let trades: Vec<(f64, f64)> = (0..2_000)
+            .map(|i| (5_000.0 + (i % 3) as f64 * 0.01, 0.5 + (i % 7) as f64 * 0.25))
+            .collect();
+        let two_pass = |trades: &[(f64, f64)]| {
+            let weight: f64 = trades.iter().map(|t| t.1).sum();
+            let mean = trades.iter().map(|t| t.0 * t.1).sum::<f64>() / weight;
+            (mean, trades.iter().map(|t| t.1 * (t.0 - mean).powi(2)).sum::<f64>() / weight)
+        };
+        
+        let mut acc = WeightedVariance::default();
+        for &(price, size) in &trades {
+            acc.push(price, size);
+        }
+        let (mean, variance) = two_pass(&trades);
+        assert_close(acc.mean(), mean);
+        assert!((acc.variance() - variance).abs() <= 1e-9 * variance, "{} != {}", acc.variance(), variance);
+        
+        // Ventana: retirar los primeros deja la varianza del resto
+        for &(price, size) in &trades[..1_500] {
+            acc.remove(price, size);
+        }
+        let (mean, variance) = two_pass(&trades[1_500..]);
+        assert_close(acc.mean(), mean);
+        assert!((acc.variance() - variance).abs() <= 1e-9 * variance, "{} != {}", acc.variance(), variance);
+        
+        for &(price, size) in &trades[1_500..] {
+            acc.remove(price, size);
+        }
+        assert_eq!((acc.mean(), acc.variance()), (None, 0.0));
+    }
+    
+    #[test]
+    fn test_streaming_stats() {
+        let mut stats = StreamingStats::new();
+        stats.update_many(vec![1.0, 2.0, 3.0]);
+        let mut other = StreamingStats::new();
+        other.update(4.0);
+        stats.merge(other);
+        assert_eq!((stats.count(), stats.mean()), (4, Some(2.5)));
+        assert_eq!(stats.variance(1), Some(5.0 / 3.0));
+        assert_eq!(stats.skewness(), Some(0.0));
+        stats.reset();
+        assert_eq!(stats.count(), 0);
+    }
+}
@@ -0,0 +1,273 @@
+//! # Rolling
+//! 
+//! Ventanas deslizantes compartidas por los engines:
+//! 
+//! - `RingBuffer`: últimos N valores con capacidad fija.
+//! - `RollingSum`: suma y media de los últimos N valores en O(1), con suma
+//!   compensada para ventanas que viven toda la sesión.
+//! - `RollingQuantile`: cuantiles aproximados de una ventana con altas y
+//!   bajas explícitas, sobre un `DDSketch` de memoria acotada.
+//! - `TimeWindow`: entradas con timestamp desalojadas por antigüedad.
+
+use std::collections::VecDeque;
+use super::NeumaierSum;
+use super::sketch::DDSketch;
+
+/// Últimos `capacity` valores; al llenarse cada alta desaloja el más antiguo
+#[derive(Clone, Debug)]
+pub struct RingBuffer<T> {
+    values: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// `capacity` 0 se trata como 1
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { values: VecDeque::with_capacity(capacity), capacity }
+    }
+    
+    /// Añade un valor; devuelve el desalojado si la ventana estaba llena
+    pub fn push(&mut self, value: T) -> Option<T> {
+        let evicted = if self.is_full() { self.values.pop_front() } else { None };
+        self.values.push_back(value);
+        evicted
+    }
+    
+    pub fn front(&self) -> Option<&T> {
+        self.values.front()
+    }
+    
+    pub fn back(&self) -> Option<&T> {
+        self.values.back()
+    }
+    
+    /// Del más antiguo al más reciente
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values.iter()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+/// Suma y media de los últimos `capacity` valores
+#[derive(Clone, Debug)]
+pub struct RollingSum {
+    values: RingBuffer<f64>,
+    sum: NeumaierSum,
+}
+
+impl RollingSum {
+    pub fn new(capacity: usize) -> Self {
+        Self { values: RingBuffer::new(capacity), sum: NeumaierSum::default() }
+    }
+    
+    /// Añade un valor; devuelve el desalojado si la ventana estaba llena
+    pub fn push(&mut self, value: f64) -> Option<f64> {
+        let evicted = self.values.push(value);
+        self.sum.add(value);
+        if let Some(old) = evicted {
+            self.sum.add(-old);
+        }
+        evicted
+    }
+    
+    pub fn sum(&self) -> f64 {
+        self.sum.value()
+    }
+    
+    /// Media de la ventana (0 si está vacía)
+    pub fn mean(&self) -> f64 {
+        super::safe_div(self.sum(), self.values.len() as f64)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    
+    pub fn is_full(&self) -> bool {
+        self.values.is_full()
+    }
+}
+
+/// Cuantiles de una ventana cuyas altas y bajas gestiona el llamador
+/// (p. ej. junto a una `TimeWindow`). Es un `DDSketch`: memoria acotada
+/// por `max_bins`, altas y bajas O(log bins) y cuantiles a error relativo α.
+#[derive(Clone, Debug, Default)]
+pub struct RollingQuantile {
+    sketch: DDSketch,
+}
+
+impl RollingQuantile {
+    /// None si `relative_accuracy` no está en (0, 1) o `max_bins` es 0
+    pub fn new(relative_accuracy: f64, max_bins: usize) -> Option<Self> {
+        DDSketch::new(relative_accuracy, max_bins).map(|sketch| Self { sketch })
+    }
+    
+    /// Añade un valor (los no finitos se ignoran)
+    pub fn insert(&mut self, value: f64) {
+        self.sketch.add(value);
+    }
+    
+    /// Retira un valor añadido antes; false si no hay ninguno en su bucket
+    pub fn remove(&mut self, value: f64) -> bool {
+        self.sketch.remove(value)
+    }
+    
+    /// Cuantil q (se recorta a [0, 1]); None si está vacía
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if q.is_nan() {
+            return None;
+        }
+        self.sketch.quantile(q.clamp(0.0, 1.0))
+    }
+    
+    pub fn median(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.sketch.count() as usize
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.sketch.count() == 0
+    }
+    
+    pub fn clear(&mut self) {
+        self.sketch.clear();
+    }
+}
+
+/// Entradas con timestamp en orden de llegada
+#[derive(Clone, Debug)]
+pub struct TimeWindow<T> {
+    entries: VecDeque<(u64, T)>,
+}
+
+impl<T> Default for TimeWindow<T> {
+    fn default() -> Self {
+        Self { entries: VecDeque::new() }
+    }
+}
+
+impl<T> TimeWindow<T> {
+    pub fn push(&mut self, ts: u64, value: T) {
+        self.entries.push_back((ts, value));
+    }
+    
+    /// Desaloja las entradas con ts <= now - window_ms (la ventana cubre
+    /// (now - window_ms, now]) salvo la más reciente, pasando cada una a
+    /// `on_evict`
+    pub fn evict(&mut self, now: u64, window_ms: u64, mut on_evict: impl FnMut(T)) {
+        let Some(cutoff) = now.checked_sub(window_ms) else { return };
+        while self.entries.len() > 1 && self.entries.front().is_some_and(|(ts, _)| *ts <= cutoff) {
+            if let Some((_, value)) = self.entries.pop_front() {
+                on_evict(value);
+            }
+        }
+    }
+    
+    pub fn front(&self) -> Option<&(u64, T)> {
+        self.entries.front()
+    }
+    
+    pub fn front_mut(&mut self) -> Option<&mut (u64, T)> {
+        self.entries.front_mut()
+    }
+    
+    pub fn pop_front(&mut self) -> Option<(u64, T)> {
+        self.entries.pop_front()
+    }
+    
+    /// De la más antigua a la más reciente
+    pub fn iter(&self) -> impl Iterator<Item = &(u64, T)> {
+        self.entries.iter()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_ring_buffer_and_rolling_sum() {
+        let mut ring = RingBuffer::new(2);
+        assert_eq!(ring.push(1), None);
+        assert_eq!(ring.push(2), None);
+        assert_eq!(ring.push(3), Some(1));
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(RingBuffer::<u8>::new(0).capacity(), 1);
+        
+        let mut sum = RollingSum::new(3);
+        assert_eq!(sum.mean(), 0.0);
+        for value in [1.0, 2.0, 3.0, 10.0] {
+            sum.push(value);
+        }
+        assert_eq!((sum.sum(), sum.mean(), sum.len()), (15.0, 5.0, 3));
+    }
+    
+    #[test]
+    fn test_rolling_quantile() {
+        let mut window = RollingQuantile::default();
+        assert_eq!(window.median(), None);
+        assert!(RollingQuantile::new(0.0, 10).is_none());
+        for value in [3.0, 1.0, 4.0, 1.0, 5.0] {
+            window.insert(value);
+        }
+        let close = |value: Option<f64>, expected: f64| (value.unwrap() - expected).abs() <= 0.01 * expected;
+        assert!(close(window.median(), 3.0));
+        assert_eq!((window.quantile(0.0), window.quantile(1.0)), (Some(1.0), Some(5.0)));
+        
+        assert!(window.remove(1.0));
+        assert!(!window.remove(2.0));
+        assert!(close(window.median(), 3.0));
+        assert_eq!(window.len(), 4);
+    }
+    
+    #[test]
+    fn test_time_window_eviction() {
+        let mut window = TimeWindow::default();
+        for ts in [0, 500, 1_000, 1_500] {
+            window.push(ts, ts);
+        }
+        let mut evicted = Vec::new();
+        window.evict(1_500, 1_000, |v| evicted.push(v));
+        assert_eq!(evicted, vec![0, 500]);
+        assert_eq!(window.len(), 2);
+        
+        // La más reciente se conserva aunque haya caducado
+        window.evict(10_000, 1_000, |v| evicted.push(v));
+        assert_eq!(window.front(), Some(&(1_500, 1_500)));
+    }
+}
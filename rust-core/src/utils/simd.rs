@@ -0,0 +1,208 @@
+//! # SIMD
+//! 
+//! Núcleos de `aggregate_volume_simd`, `price_binning_simd` y del batch de
+//! VWAP. En x86_64 se usa AVX si la CPU lo soporta (detección en tiempo de
+//! ejecución); si no, en el resto de arquitecturas o con
+//! `set_enabled(false)`, el bucle escalar.
+//! 
+//! El binning da los mismos ticks que `price_to_tick` (redondeo "half away
+//! from zero", como f64::round). Las sumas vectoriales agrupan los sumandos
+//! de otra forma, así que pueden diferir del orden secuencial en el último bit.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::price_to_tick;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Activa o desactiva los núcleos vectoriales (para comparar con el escalar)
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn use_avx() -> bool {
+    ENABLED.load(Ordering::Relaxed) && is_x86_feature_detected!("avx")
+}
+
+/// Suma de los valores
+pub fn sum(values: &[f64]) -> f64 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if use_avx() {
+            // SAFETY: la CPU soporta AVX
+            return unsafe { avx::sum(values) };
+        }
+    }
+    values.iter().sum()
+}
+
+/// Índice de tick de cada precio
+pub fn price_to_ticks(prices: &[f64], tick_size: f64) -> Vec<i64> {
+    let mut ticks = Vec::with_capacity(prices.len());
+    for_each_tick(prices, |&p| p, tick_size, |_, tick| ticks.push(tick));
+    ticks
+}
+
+/// Llama a `f` con cada item y el tick de su precio, en orden, leyendo los
+/// precios directamente del slice (sin copiarlos a un buffer)
+pub fn for_each_tick<T>(items: &[T], price: impl Fn(&T) -> f64, tick_size: f64, mut f: impl FnMut(&T, i64)) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if use_avx() {
+            // SAFETY: la CPU soporta AVX
+            return unsafe { avx::for_each_tick(items, price, tick_size, f) };
+        }
+    }
+    items.iter().for_each(|item| f(item, price_to_tick(price(item), tick_size)));
+}
+
+/// Producto elemento a elemento (`a[i] * b[i]`, hasta el más corto)
+pub fn products(a: &[f64], b: &[f64]) -> Vec<f64> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if use_avx() {
+            // SAFETY: la CPU soporta AVX
+            return unsafe { avx::products(a, b) };
+        }
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).collect()
+}
+
+/// Sumas acumuladas: `out[i] = values[0] + .. + values[i]`
+pub fn prefix_sums(values: &[f64]) -> Vec<f64> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if use_avx() {
+            // SAFETY: la CPU soporta AVX
+            return unsafe { avx::prefix_sums(values) };
+        }
+    }
+    values.iter().scan(0.0, |acc, v| {
+        *acc += v;
+        Some(*acc)
+    }).collect()
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx {
+    use std::arch::x86_64::*;
+    use super::price_to_tick;
+    
+    /// Mayor f64 por debajo de 0.5: sumado antes de truncar redondea como
+    /// f64::round sin desplazar los valores justo por debajo de .5
+    const HALF_DOWN: f64 = 0.49999999999999994;
+    
+    /// Dos acumuladores de 4 lanes para no encadenar todas las sumas
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn sum(values: &[f64]) -> f64 {
+        let chunks = values.chunks_exact(8);
+        let rest = chunks.remainder();
+        let (mut a, mut b) = (_mm256_setzero_pd(), _mm256_setzero_pd());
+        for chunk in chunks {
+            a = _mm256_add_pd(a, _mm256_loadu_pd(chunk.as_ptr()));
+            b = _mm256_add_pd(b, _mm256_loadu_pd(chunk.as_ptr().add(4)));
+        }
+        let mut lanes = [0.0; 4];
+        _mm256_storeu_pd(lanes.as_mut_ptr(), _mm256_add_pd(a, b));
+        lanes.iter().sum::<f64>() + rest.iter().sum::<f64>()
+    }
+    
+    /// División y redondeo en vector; la conversión a i64 (sin instrucción
+    /// en AVX) es por lane, con la misma saturación que `as i64`
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn for_each_tick<T>(items: &[T], price: impl Fn(&T) -> f64, tick_size: f64,
+                                          mut f: impl FnMut(&T, i64)) {
+        let chunks = items.chunks_exact(4);
+        let rest = chunks.remainder();
+        let tick = _mm256_set1_pd(tick_size);
+        let half = _mm256_set1_pd(HALF_DOWN);
+        let sign = _mm256_set1_pd(-0.0);
+        let mut lanes = [0.0; 4];
+        for chunk in chunks {
+            let prices = _mm256_set_pd(price(&chunk[3]), price(&chunk[2]), price(&chunk[1]), price(&chunk[0]));
+            let x = _mm256_div_pd(prices, tick);
+            // ±HALF_DOWN con el signo de x
+            let bias = _mm256_or_pd(half, _mm256_and_pd(x, sign));
+            let rounded = _mm256_round_pd::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(_mm256_add_pd(x, bias));
+            _mm256_storeu_pd(lanes.as_mut_ptr(), rounded);
+            for (item, &t) in chunk.iter().zip(&lanes) {
+                f(item, t as i64);
+            }
+        }
+        rest.iter().for_each(|item| f(item, price_to_tick(price(item), tick_size)));
+    }
+    
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn products(a: &[f64], b: &[f64]) -> Vec<f64> {
+        let n = a.len().min(b.len());
+        let mut out = vec![0.0; n];
+        let full = n - n % 4;
+        for i in (0..full).step_by(4) {
+            let x = _mm256_mul_pd(_mm256_loadu_pd(a.as_ptr().add(i)), _mm256_loadu_pd(b.as_ptr().add(i)));
+            _mm256_storeu_pd(out.as_mut_ptr().add(i), x);
+        }
+        for i in full..n {
+            out[i] = a[i] * b[i];
+        }
+        out
+    }
+    
+    /// Scan de 4 lanes en registro (desplazamientos de 1 y 2 posiciones)
+    /// más el acumulado de los bloques anteriores en broadcast
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn prefix_sums(values: &[f64]) -> Vec<f64> {
+        let n = values.len();
+        let mut out = vec![0.0; n];
+        let full = n - n % 4;
+        let mut carry = _mm256_setzero_pd();
+        for i in (0..full).step_by(4) {
+            let x = _mm256_loadu_pd(values.as_ptr().add(i));
+            // [a, b, c, d] -> [0, a, b, c]
+            let swapped = _mm256_permute_pd::<0b0101>(x);
+            let low_to_high = _mm256_permute2f128_pd::<0x08>(swapped, swapped);
+            let x = _mm256_add_pd(x, _mm256_blend_pd::<0b0101>(swapped, low_to_high));
+            // [a, a+b, b+c, c+d] -> + [0, 0, a, a+b]
+            let x = _mm256_add_pd(x, _mm256_permute2f128_pd::<0x08>(x, x));
+            let x = _mm256_add_pd(x, carry);
+            _mm256_storeu_pd(out.as_mut_ptr().add(i), x);
+            // Último lane en los 4
+            let last = _mm256_permute_pd::<0b1111>(x);
+            carry = _mm256_permute2f128_pd::<0x11>(last, last);
+        }
+        let mut acc = if full > 0 { out[full - 1] } else { 0.0 };
+        for i in full..n {
+            acc += values[i];
+            out[i] = acc;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_simd_matches_scalar() {
+        let mut prices: Vec<f64> = (0..1_003).map(|i| 100.0 + i as f64 * 0.005 - 2.5).collect();
+        prices.extend([-0.125, -0.375, 0.125, 0.375, f64::NAN, f64::INFINITY, -0.0, 1e300]);
+        let expected: Vec<i64> = prices.iter().map(|&p| price_to_tick(p, 0.25)).collect();
+        assert_eq!(price_to_ticks(&prices, 0.25), expected);
+        let expected: Vec<i64> = prices.iter().map(|&p| price_to_tick(p, 0.01)).collect();
+        assert_eq!(price_to_ticks(&prices, 0.01), expected);
+        
+        let volumes: Vec<f64> = (1..=1_001).map(|i| i as f64 * 0.5).collect();
+        assert_eq!(sum(&volumes), 1_001.0 * 1_002.0 / 4.0);
+        assert_eq!(sum(&[]), 0.0);
+        
+        // Valores enteros: sumas y productos exactos en cualquier orden
+        let sizes: Vec<f64> = (0..1_003).map(|i| (i % 7) as f64).collect();
+        let expected: Vec<f64> = volumes.iter().zip(&sizes).map(|(v, s)| v * s).collect();
+        assert_eq!(products(&volumes, &sizes), expected);
+        let expected: Vec<f64> = sizes.iter().scan(0.0, |acc, s| { *acc += s; Some(*acc) }).collect();
+        assert_eq!(prefix_sums(&sizes), expected);
+        assert_eq!(prefix_sums(&sizes[..3]), expected[..3]);
+        assert!(prefix_sums(&[]).is_empty());
+    }
+}
@@ -0,0 +1,482 @@
+//! # Sketch
+//! 
+//! Cuantiles aproximados con memoria acotada (DDSketch): cada valor cae en
+//! un bucket logarítmico de base gamma = (1 + α) / (1 - α), así que el
+//! cuantil devuelto está a un error relativo α del exacto para valores de
+//! cualquier escala (tamaños de trade, spreads, latencias en ns o en s).
+//! 
+//! Si se supera `max_bins` se colapsan los buckets de menor magnitud: los
+//! cuantiles bajos pierden precisión y los altos (p95, p99) la conservan.
+//! Dos sketches con la misma α se combinan sin pérdida con `merge`.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use crate::py_macros::{py_class, py_methods};
+use crate::errors::ProcessError;
+
+/// Error relativo por defecto
+pub const DEFAULT_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Buckets por signo por defecto (2048 cubren 1e-9..1e9 con α = 1%)
+pub const DEFAULT_MAX_BINS: usize = 2048;
+
+/// Cuantiles por defecto en los informes (p50, p95, p99)
+pub const DEFAULT_QUANTILES: [f64; 3] = [0.5, 0.95, 0.99];
+
+/// Sketch de cuantiles con error relativo acotado
+#[derive(Clone, Debug)]
+pub struct DDSketch {
+    relative_accuracy: f64,
+    ln_gamma: f64,
+    max_bins: usize,
+    // Conteos por índice de bucket de |x|, uno por signo
+    positive: BTreeMap<i32, u64>,
+    negative: BTreeMap<i32, u64>,
+    zero_count: u64,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for DDSketch {
+    fn default() -> Self {
+        Self::with_params(DEFAULT_RELATIVE_ACCURACY, DEFAULT_MAX_BINS)
+    }
+}
+
+impl DDSketch {
+    /// None si `relative_accuracy` no está en (0, 1) o `max_bins` es 0
+    pub fn new(relative_accuracy: f64, max_bins: usize) -> Option<Self> {
+        (relative_accuracy > 0.0 && relative_accuracy < 1.0 && max_bins > 0)
+            .then(|| Self::with_params(relative_accuracy, max_bins))
+    }
+    
+    fn with_params(relative_accuracy: f64, max_bins: usize) -> Self {
+        let gamma = (1.0 + relative_accuracy) / (1.0 - relative_accuracy);
+        Self {
+            relative_accuracy,
+            ln_gamma: gamma.ln(),
+            max_bins,
+            positive: BTreeMap::new(),
+            negative: BTreeMap::new(),
+            zero_count: 0,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+    
+    /// Añade un valor (los no finitos se ignoran)
+    pub fn add(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if value.abs() < f64::MIN_POSITIVE {
+            self.zero_count += 1;
+        } else {
+            let index = self.index(value.abs());
+            let store = if value > 0.0 { &mut self.positive } else { &mut self.negative };
+            *store.entry(index).or_default() += 1;
+            collapse(store, self.max_bins);
+        }
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+    
+    /// Retira un valor añadido antes (ventanas deslizantes); false si su
+    /// bucket está vacío. Un valor de un bucket ya colapsado se descuenta
+    /// del bucket más bajo. Al retirar el mínimo o el máximo pasan a ser los
+    /// representantes de los buckets extremos (a error relativo α).
+    pub fn remove(&mut self, value: f64) -> bool {
+        if !value.is_finite() {
+            return false;
+        }
+        if value.abs() < f64::MIN_POSITIVE {
+            if self.zero_count == 0 {
+                return false;
+            }
+            self.zero_count -= 1;
+        } else {
+            let index = self.index(value.abs());
+            let store = if value > 0.0 { &mut self.positive } else { &mut self.negative };
+            let index = match store.first_key_value() {
+                Some((&lowest, _)) if index < lowest => lowest,
+                Some(_) if store.contains_key(&index) => index,
+                _ => return false,
+            };
+            let count = store.entry(index).or_default();
+            *count -= 1;
+            if *count == 0 {
+                store.remove(&index);
+            }
+        }
+        self.count -= 1;
+        if self.count == 0 {
+            self.clear();
+            return true;
+        }
+        self.sum -= value;
+        if value <= self.min {
+            self.min = self.bucket_bound(true);
+        }
+        if value >= self.max {
+            self.max = self.bucket_bound(false);
+        }
+        true
+    }
+    
+    /// Combina con otro sketch; false (sin cambios) si la precisión no coincide
+    pub fn merge(&mut self, other: &Self) -> bool {
+        if other.relative_accuracy != self.relative_accuracy {
+            return false;
+        }
+        for (store, other_store) in [(&mut self.positive, &other.positive), (&mut self.negative, &other.negative)] {
+            for (&index, &count) in other_store {
+                *store.entry(index).or_default() += count;
+            }
+            collapse(store, self.max_bins);
+        }
+        self.zero_count += other.zero_count;
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        true
+    }
+    
+    /// Cuantil q en [0, 1]; None sin valores o con q fuera de rango
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 || !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        // Los extremos se conocen exactos
+        if q == 0.0 {
+            return Some(self.min);
+        }
+        if q == 1.0 {
+            return Some(self.max);
+        }
+        // Primer bucket cuyo conteo acumulado supera el rango
+        let rank = q * (self.count - 1) as f64;
+        let mut seen = 0u64;
+        let mut found = None;
+        for (&index, &count) in self.negative.iter().rev() {
+            seen += count;
+            if seen as f64 > rank {
+                found = Some(-self.value(index));
+                break;
+            }
+        }
+        if found.is_none() {
+            seen += self.zero_count;
+            if seen as f64 > rank {
+                found = Some(0.0);
+            }
+        }
+        if found.is_none() {
+            found = self.positive.iter()
+                .find(|(_, &count)| {
+                    seen += count;
+                    seen as f64 > rank
+                })
+                .map(|(&index, _)| self.value(index));
+        }
+        // El representante del bucket puede quedar fuera del rango observado
+        found.map(|value| value.clamp(self.min, self.max))
+    }
+    
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+    
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+    
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+    
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+    
+    pub fn relative_accuracy(&self) -> f64 {
+        self.relative_accuracy
+    }
+    
+    pub fn max_bins(&self) -> usize {
+        self.max_bins
+    }
+    
+    /// Buckets ocupados (memoria en uso)
+    pub fn bins(&self) -> usize {
+        self.positive.len() + self.negative.len()
+    }
+    
+    pub fn clear(&mut self) {
+        *self = Self::with_params(self.relative_accuracy, self.max_bins);
+    }
+    
+    /// Representante del bucket ocupado más bajo (`lowest`) o más alto
+    fn bucket_bound(&self, lowest: bool) -> f64 {
+        let negative = if lowest { self.negative.last_key_value() } else { self.negative.first_key_value() };
+        let positive = if lowest { self.positive.first_key_value() } else { self.positive.last_key_value() };
+        let negative = negative.map(|(&index, _)| -self.value(index));
+        let positive = positive.map(|(&index, _)| self.value(index));
+        let zero = (self.zero_count > 0).then_some(0.0);
+        let candidates = [negative, zero, positive].into_iter().flatten();
+        if lowest { candidates.fold(f64::INFINITY, f64::min) } else { candidates.fold(f64::NEG_INFINITY, f64::max) }
+    }
+    
+    /// Índice del bucket (gamma^(i-1), gamma^i] de un valor > 0
+    fn index(&self, magnitude: f64) -> i32 {
+        (magnitude.ln() / self.ln_gamma).ceil() as i32
+    }
+    
+    /// Representante del bucket: a error relativo α de todo el intervalo
+    fn value(&self, index: i32) -> f64 {
+        (index as f64 * self.ln_gamma).exp() * 2.0 / (1.0 + self.ln_gamma.exp())
+    }
+}
+
+/// Junta los buckets de menor magnitud hasta dejar `max_bins`
+fn collapse(store: &mut BTreeMap<i32, u64>, max_bins: usize) {
+    while store.len() > max_bins {
+        let Some((_, count)) = store.pop_first() else { break };
+        if let Some(mut lowest) = store.first_entry() {
+            *lowest.get_mut() += count;
+        }
+    }
+}
+
+/// Valida cuantiles recibidos desde Python
+pub fn validate_quantiles(quantiles: &[f64]) -> Result<(), ProcessError> {
+    if quantiles.iter().any(|q| !(0.0..=1.0).contains(q)) {
+        return Err(ProcessError::invalid("quantiles must be in [0, 1]"));
+    }
+    Ok(())
+}
+
+py_class! {
+/// Sketch de cuantiles (DDSketch) para usar desde Python
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct QuantileSketch {
+    sketch: DDSketch,
+}
+}
+
+py_methods! {
+#[pymethods]
+impl QuantileSketch {
+    #[new]
+    #[pyo3(signature = (relative_accuracy=0.01, max_bins=2048))]
+    pub fn new(relative_accuracy: f64, max_bins: usize) -> Result<Self, ProcessError> {
+        DDSketch::new(relative_accuracy, max_bins)
+            .map(|sketch| Self { sketch })
+            .ok_or_else(|| ProcessError::invalid(
+                "relative_accuracy must be in (0, 1) and max_bins > 0"))
+    }
+    
+    /// Añade un valor (los no finitos se ignoran)
+    pub fn add(&mut self, value: f64) {
+        self.sketch.add(value);
+    }
+    
+    /// Añade varios valores
+    pub fn add_many(&mut self, values: Vec<f64>) {
+        for value in values {
+            self.sketch.add(value);
+        }
+    }
+    
+    /// Incorpora los valores de otro sketch con la misma relative_accuracy
+    pub fn merge(&mut self, other: QuantileSketch) -> Result<(), ProcessError> {
+        if !self.sketch.merge(&other.sketch) {
+            return Err(ProcessError::invalid("relative_accuracy must match to merge"));
+        }
+        Ok(())
+    }
+    
+    /// Cuantil q en [0, 1]; None sin valores
+    pub fn quantile(&self, q: f64) -> Result<Option<f64>, ProcessError> {
+        validate_quantiles(&[q])?;
+        Ok(self.sketch.quantile(q))
+    }
+    
+    /// Varios cuantiles (por defecto p50, p95 y p99)
+    #[pyo3(signature = (quantiles=vec![0.5, 0.95, 0.99]))]
+    pub fn quantiles(&self, quantiles: Vec<f64>) -> Result<Vec<Option<f64>>, ProcessError> {
+        validate_quantiles(&quantiles)?;
+        Ok(quantiles.iter().map(|&q| self.sketch.quantile(q)).collect())
+    }
+    
+    /// Valores añadidos
+    #[getter]
+    pub fn count(&self) -> u64 {
+        self.sketch.count()
+    }
+    
+    #[getter]
+    pub fn mean(&self) -> Option<f64> {
+        self.sketch.mean()
+    }
+    
+    #[getter]
+    pub fn min(&self) -> Option<f64> {
+        self.sketch.min()
+    }
+    
+    #[getter]
+    pub fn max(&self) -> Option<f64> {
+        self.sketch.max()
+    }
+    
+    #[getter]
+    pub fn relative_accuracy(&self) -> f64 {
+        self.sketch.relative_accuracy()
+    }
+    
+    #[getter]
+    pub fn max_bins(&self) -> usize {
+        self.sketch.max_bins()
+    }
+    
+    /// Buckets ocupados
+    #[getter]
+    pub fn bins(&self) -> usize {
+        self.sketch.bins()
+    }
+    
+    /// Descarta los valores añadidos
+    pub fn reset(&mut self) {
+        self.sketch.clear();
+    }
+    
+    #[cfg(feature = "python")]
+    fn __len__(&self) -> usize {
+        self.sketch.count() as usize
+    }
+    
+    #[cfg(feature = "python")]
+    fn __repr__(&self) -> String {
+        format!("QuantileSketch(relative_accuracy={}, count={}, bins={})",
+                self.sketch.relative_accuracy(), self.sketch.count(), self.sketch.bins())
+    }
+}
+}
+
+impl QuantileSketch {
+    pub fn from_sketch(sketch: DDSketch) -> Self {
+        Self { sketch }
+    }
+    
+    pub fn sketch(&self) -> &DDSketch {
+        &self.sketch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Cuantil exacto con la misma definición de rango
+    fn exact(sorted: &[f64], q: f64) -> f64 {
+        sorted[(q * (sorted.len() - 1) as f64).floor() as usize]
+    }
+    
+    #[test]
+    fn test_sketch_relative_error() {
+        // Log-uniforme en 6 órdenes de magnitud, con negativos y ceros
+        let mut values: Vec<f64> = (0..10_000).map(|i| 10f64.powf(i as f64 * 6.0 / 10_000.0 - 3.0)).collect();
+        values.extend((0..500).map(|i| -(i as f64 + 1.0) * 0.37));
+        values.extend([0.0; 100]);
+        let mut sketch = DDSketch::default();
+        for &value in &values {
+            sketch.add(value);
+        }
+        sketch.add(f64::NAN);
+        values.sort_by(f64::total_cmp);
+        
+        assert_eq!(sketch.count(), values.len() as u64);
+        for q in [0.0, 0.01, 0.04, 0.045, 0.25, 0.5, 0.95, 0.99, 1.0] {
+            let (approx, exact) = (sketch.quantile(q).unwrap(), exact(&values, q));
+            assert!((approx - exact).abs() <= 0.01 * exact.abs(), "q={}: {} vs {}", q, approx, exact);
+        }
+        assert_eq!((sketch.min(), sketch.max()), (Some(values[0]), Some(values[values.len() - 1])));
+        assert_eq!(sketch.quantile(1.5), None);
+        assert_eq!(DDSketch::default().quantile(0.5), None);
+    }
+    
+    #[test]
+    fn test_sketch_remove() {
+        let mut sketch = DDSketch::default();
+        for value in [1.0, 2.0, 3.0, 4.0, 100.0, 0.0] {
+            sketch.add(value);
+        }
+        assert!(sketch.remove(100.0));
+        assert!(sketch.remove(0.0));
+        assert!(!sketch.remove(0.0));
+        assert!(!sketch.remove(-1.0));
+        assert_eq!(sketch.count(), 4);
+        assert!((sketch.max().unwrap() - 4.0).abs() <= 0.01 * 4.0);
+        assert!((sketch.quantile(0.5).unwrap() - 2.0).abs() <= 0.01 * 2.0);
+        
+        // Con buckets colapsados se descuenta del más bajo
+        let mut small = DDSketch::new(0.01, 2).unwrap();
+        for value in [1.0, 10.0, 100.0] {
+            small.add(value);
+        }
+        assert!(small.remove(1.0));
+        assert_eq!((small.count(), small.bins()), (2, 2));
+        assert!(small.remove(10.0) && small.remove(100.0));
+        assert_eq!((small.count(), small.min()), (0, None));
+    }
+    
+    #[test]
+    fn test_sketch_merge_and_collapse() {
+        let (mut left, mut right, mut all) = (DDSketch::default(), DDSketch::default(), DDSketch::default());
+        for i in 1..=2_000 {
+            let value = i as f64 * 0.25;
+            all.add(value);
+            if i % 2 == 0 { left.add(value) } else { right.add(value) }
+        }
+        assert!(left.merge(&right));
+        for q in DEFAULT_QUANTILES {
+            assert_eq!(left.quantile(q), all.quantile(q));
+        }
+        assert!(!left.merge(&DDSketch::new(0.05, 2048).unwrap()));
+        
+        // Con pocos buckets los cuantiles altos se conservan
+        let mut small = DDSketch::new(0.01, 50).unwrap();
+        for i in 1..=2_000 {
+            small.add(i as f64 * 0.25);
+        }
+        assert_eq!(small.bins(), 50);
+        assert_eq!(small.quantile(0.99), all.quantile(0.99));
+        assert!(DDSketch::new(1.0, 10).is_none());
+    }
+    
+    #[test]
+    fn test_quantile_sketch() {
+        let mut sketch = QuantileSketch::new(0.01, 2048).unwrap();
+        sketch.add_many(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(sketch.quantile(0.0).unwrap(), Some(1.0));
+        assert_eq!(sketch.quantiles(vec![1.0]).unwrap(), vec![Some(4.0)]);
+        assert!(sketch.quantile(-0.1).is_err());
+        assert!(sketch.merge(QuantileSketch::new(0.02, 2048).unwrap()).is_err());
+        assert!(QuantileSketch::new(0.0, 2048).is_err());
+        sketch.reset();
+        assert_eq!((sketch.count(), sketch.mean()), (0, None));
+    }
+}
@@ -0,0 +1,162 @@
+//! # WASM
+//!
+//! Fachada `wasm-bindgen` (feature `wasm`) con `CVDEngine`, `VWAPEngine` y
+//! `HeatmapEngine` para navegador o Node. Los eventos y las métricas son
+//! objetos JS con los mismos campos que el JSON de `wire` (`ts`, `price`,
+//! `size`, `symbol`, `side`; `bids`/`asks` como `[{price, size}]`).
+//!
+//! ```js
+//! import { CVDEngine } from "./pkg/indicators_core.js";
+//! const cvd = new CVDEngine();
+//! const metrics = cvd.onTrade({ ts: 1000, price: 100.0, size: 2.0, symbol: "AAPL", side: "BUY" });
+//! ```
+//!
+//! Para `wasm32-unknown-unknown` se compila sin PyO3 ni NATS:
+//! `cargo build --target wasm32-unknown-unknown --no-default-features --features wasm`.
+//! Un evento inválido devuelve `null` (igual que `on_trade` en Rust); un
+//! objeto que no se puede leer como evento lanza un `Error`.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::indicators::{CVDEngine, HeatmapEngine, VWAPEngine};
+use crate::types::{BookSnapshot, Trade};
+
+/// Valor JS de las métricas: mapas como objetos y None como `null`
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// CVD por símbolo
+#[wasm_bindgen(js_name = CVDEngine)]
+pub struct WasmCVDEngine {
+    engine: CVDEngine,
+}
+
+#[wasm_bindgen(js_class = CVDEngine)]
+impl WasmCVDEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { engine: CVDEngine::new() }
+    }
+
+    /// Procesa un trade; devuelve sus métricas o `null` si se descartó
+    #[wasm_bindgen(js_name = onTrade)]
+    pub fn on_trade(&self, trade: JsValue) -> Result<JsValue, JsError> {
+        let trade: Trade = serde_wasm_bindgen::from_value(trade)?;
+        to_js(&self.engine.on_trade(&trade))
+    }
+
+    #[wasm_bindgen(js_name = getCvd)]
+    pub fn get_cvd(&self, symbol: &str) -> Option<f64> {
+        self.engine.get_cvd(symbol)
+    }
+
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self, symbol: &str) -> Result<JsValue, JsError> {
+        to_js(&self.engine.get_metrics(symbol))
+    }
+
+    #[wasm_bindgen(js_name = resetSymbol)]
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.engine.reset_symbol(symbol);
+    }
+
+    #[wasm_bindgen(js_name = resetAll)]
+    pub fn reset_all(&self) {
+        self.engine.reset_all();
+    }
+}
+
+impl Default for WasmCVDEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// VWAP de sesión con bandas por símbolo
+#[wasm_bindgen(js_name = VWAPEngine)]
+pub struct WasmVWAPEngine {
+    engine: VWAPEngine,
+}
+
+#[wasm_bindgen(js_class = VWAPEngine)]
+impl WasmVWAPEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { engine: VWAPEngine::new() }
+    }
+
+    /// Procesa un trade; devuelve sus métricas o `null` si se descartó
+    #[wasm_bindgen(js_name = onTrade)]
+    pub fn on_trade(&self, trade: JsValue) -> Result<JsValue, JsError> {
+        let trade: Trade = serde_wasm_bindgen::from_value(trade)?;
+        to_js(&self.engine.on_trade(&trade))
+    }
+
+    #[wasm_bindgen(js_name = getVwap)]
+    pub fn get_vwap(&self, symbol: &str) -> Option<f64> {
+        self.engine.get_vwap(symbol)
+    }
+
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self, symbol: &str) -> Result<JsValue, JsError> {
+        to_js(&self.engine.get_metrics(symbol))
+    }
+
+    #[wasm_bindgen(js_name = resetSymbol)]
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.engine.reset_symbol(symbol);
+    }
+
+    #[wasm_bindgen(js_name = resetAll)]
+    pub fn reset_all(&self) {
+        self.engine.reset_all();
+    }
+}
+
+impl Default for WasmVWAPEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Heatmap de liquidez por bucket temporal y nivel de precio
+#[wasm_bindgen(js_name = HeatmapEngine)]
+pub struct WasmHeatmapEngine {
+    engine: HeatmapEngine,
+}
+
+#[wasm_bindgen(js_class = HeatmapEngine)]
+impl WasmHeatmapEngine {
+    /// `bucketMs` y `tickSize` como en `HeatmapEngine::new`; sin
+    /// `retentionBuckets` no se purgan buckets antiguos
+    #[wasm_bindgen(constructor)]
+    pub fn new(bucket_ms: u32, tick_size: f64, retention_buckets: Option<u32>) -> Result<WasmHeatmapEngine, JsError> {
+        let engine = HeatmapEngine::new(bucket_ms.into(), tick_size, retention_buckets.map(|n| n as usize))?;
+        Ok(Self { engine })
+    }
+
+    /// Procesa un snapshot; devuelve el heatmap del bucket o `null`
+    #[wasm_bindgen(js_name = onSnapshot)]
+    pub fn on_snapshot(&self, snapshot: JsValue) -> Result<JsValue, JsError> {
+        let snapshot: BookSnapshot = serde_wasm_bindgen::from_value(snapshot)?;
+        to_js(&self.engine.on_snapshot(&snapshot))
+    }
+
+    /// Heatmap de un bucket ya acumulado (`bucketTs` en ms)
+    #[wasm_bindgen(js_name = getHeatmap)]
+    pub fn get_heatmap(&self, symbol: &str, bucket_ts: f64) -> Result<JsValue, JsError> {
+        to_js(&self.engine.get_heatmap(symbol, bucket_ts as u64))
+    }
+
+    #[wasm_bindgen(js_name = resetSymbol)]
+    pub fn reset_symbol(&self, symbol: &str) {
+        self.engine.reset_symbol(symbol);
+    }
+
+    #[wasm_bindgen(js_name = resetAll)]
+    pub fn reset_all(&self) {
+        self.engine.reset_all();
+    }
+}
@@ -0,0 +1,335 @@
+//! # Wire Formats
+//! 
+//! Decodificación de mensajes de mercado (Trade, BookSnapshot, Bar) en los
+//! formatos de cable soportados:
+//! - JSON y MessagePack: mismos nombres de campo que los tipos de Rust. En
+//!   modo "auto" el tipo se deduce de los campos, así que en MessagePack los
+//!   structs deben ir como mapas con nombre (`rmp_serde::to_vec_named`).
+//! - Protobuf: mensajes de `proto/market_data.proto`. En modo "auto" el
+//!   mensaje debe ser el sobre `MarketData`.
+
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::types::{Bar, BookSnapshot, Level, Trade};
+
+/// Formato de cable de los mensajes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Protobuf,
+}
+
+impl WireFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "msgpack" | "messagepack" => Some(Self::MessagePack),
+            "protobuf" | "proto" => Some(Self::Protobuf),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+            Self::Protobuf => "protobuf",
+        }
+    }
+}
+
+/// Tipo de mensaje asociado a un subject
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    Trades,
+    Books,
+    Bars,
+    Auto,
+}
+
+impl MessageKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "trades" | "trade" => Some(Self::Trades),
+            "books" | "book" => Some(Self::Books),
+            "bars" | "bar" => Some(Self::Bars),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+}
+
+/// Evento de mercado decodificado de un mensaje
+#[derive(Clone, Debug)]
+pub enum MarketEvent {
+    Trade(Trade),
+    Book(BookSnapshot),
+    Bar(Bar),
+}
+
+/// Decodifica un mensaje en el formato y tipo indicados
+pub fn decode(format: WireFormat, kind: MessageKind, payload: &[u8]) -> Result<MarketEvent, String> {
+    match format {
+        WireFormat::Json => decode_serde(kind, payload, |p| serde_json::from_slice(p).map_err(|e| e.to_string())),
+        WireFormat::MessagePack => decode_serde(kind, payload, |p| rmp_serde::from_slice(p).map_err(|e| e.to_string())),
+        WireFormat::Protobuf => decode_protobuf(kind, payload),
+    }
+}
+
+/// Codifica métricas para publicarlas. Solo JSON y MessagePack (mapas con
+/// nombre); las métricas no tienen esquema Protobuf.
+pub fn encode<T: Serialize>(format: WireFormat, value: &T) -> Result<Vec<u8>, String> {
+    match format {
+        WireFormat::Json => serde_json::to_vec(value).map_err(|e| format!("JSON error: {}", e)),
+        WireFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| format!("MessagePack error: {}", e)),
+        WireFormat::Protobuf => Err("protobuf encoding is only supported for market data".to_string()),
+    }
+}
+
+/// JSON y MessagePack comparten el modelo de serde. Con tipo conocido se
+/// decodifica directo al struct; en modo auto se pasa por un valor genérico
+/// para inspeccionar los campos.
+fn decode_serde<F>(kind: MessageKind, payload: &[u8], from_slice: F) -> Result<MarketEvent, String>
+where
+    F: Fn(&[u8]) -> Result<serde_json::Value, String>,
+{
+    fn typed<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, String> {
+        serde_json::from_value(value).map_err(|e| format!("invalid message: {}", e))
+    }
+    
+    let value = from_slice(payload).map_err(|e| format!("decode error: {}", e))?;
+    let kind = match kind {
+        MessageKind::Auto => {
+            let has = |key: &str| value.get(key).is_some();
+            if has("bids") || has("asks") {
+                MessageKind::Books
+            } else if has("open") && has("close") {
+                MessageKind::Bars
+            } else {
+                MessageKind::Trades
+            }
+        }
+        kind => kind,
+    };
+    
+    match kind {
+        MessageKind::Books => typed(value).map(MarketEvent::Book),
+        MessageKind::Bars => typed(value).map(MarketEvent::Bar),
+        _ => typed(value).map(MarketEvent::Trade),
+    }
+}
+
+fn decode_protobuf(kind: MessageKind, payload: &[u8]) -> Result<MarketEvent, String> {
+    let error = |e: prost::DecodeError| format!("protobuf decode error: {}", e);
+    match kind {
+        MessageKind::Trades => proto::Trade::decode(payload).map(|m| MarketEvent::Trade(m.into())).map_err(error),
+        MessageKind::Books => proto::BookSnapshot::decode(payload).map(|m| MarketEvent::Book(m.into())).map_err(error),
+        MessageKind::Bars => proto::Bar::decode(payload).map(|m| MarketEvent::Bar(m.into())).map_err(error),
+        MessageKind::Auto => match proto::MarketData::decode(payload).map_err(error)?.event {
+            Some(proto::market_data::Event::Trade(m)) => Ok(MarketEvent::Trade(m.into())),
+            Some(proto::market_data::Event::Book(m)) => Ok(MarketEvent::Book(m.into())),
+            Some(proto::market_data::Event::Bar(m)) => Ok(MarketEvent::Bar(m.into())),
+            None => Err("invalid message: empty MarketData".to_string()),
+        },
+    }
+}
+
+/// Mensajes de `proto/market_data.proto` (equivalente a la salida de prost-build)
+pub mod proto {
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Trade {
+        #[prost(uint64, tag = "1")]
+        pub ts: u64,
+        #[prost(double, tag = "2")]
+        pub price: f64,
+        #[prost(double, tag = "3")]
+        pub size: f64,
+        #[prost(string, tag = "4")]
+        pub symbol: String,
+        #[prost(string, optional, tag = "5")]
+        pub side: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub exchange: Option<String>,
+        #[prost(string, optional, tag = "7")]
+        pub trade_id: Option<String>,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Level {
+        #[prost(double, tag = "1")]
+        pub price: f64,
+        #[prost(double, tag = "2")]
+        pub size: f64,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct BookSnapshot {
+        #[prost(uint64, tag = "1")]
+        pub ts: u64,
+        #[prost(string, tag = "2")]
+        pub symbol: String,
+        #[prost(message, repeated, tag = "3")]
+        pub bids: Vec<Level>,
+        #[prost(message, repeated, tag = "4")]
+        pub asks: Vec<Level>,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Bar {
+        #[prost(uint64, tag = "1")]
+        pub ts: u64,
+        #[prost(double, tag = "2")]
+        pub open: f64,
+        #[prost(double, tag = "3")]
+        pub high: f64,
+        #[prost(double, tag = "4")]
+        pub low: f64,
+        #[prost(double, tag = "5")]
+        pub close: f64,
+        #[prost(double, tag = "6")]
+        pub volume: f64,
+        #[prost(string, tag = "7")]
+        pub tf: String,
+        #[prost(string, tag = "8")]
+        pub symbol: String,
+    }
+    
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct MarketData {
+        #[prost(oneof = "market_data::Event", tags = "1, 2, 3")]
+        pub event: Option<market_data::Event>,
+    }
+    
+    pub mod market_data {
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Event {
+            #[prost(message, tag = "1")]
+            Trade(super::Trade),
+            #[prost(message, tag = "2")]
+            Book(super::BookSnapshot),
+            #[prost(message, tag = "3")]
+            Bar(super::Bar),
+        }
+    }
+}
+
+impl From<proto::Trade> for Trade {
+    fn from(m: proto::Trade) -> Self {
+        Self { ts: m.ts, price: m.price, size: m.size, symbol: m.symbol, side: m.side, exchange: m.exchange, trade_id: m.trade_id }
+    }
+}
+
+impl From<proto::Level> for Level {
+    fn from(m: proto::Level) -> Self {
+        Level::new(m.price, m.size)
+    }
+}
+
+impl From<proto::BookSnapshot> for BookSnapshot {
+    fn from(m: proto::BookSnapshot) -> Self {
+        BookSnapshot::new(
+            m.ts,
+            m.symbol,
+            m.bids.into_iter().map(Level::from).collect(),
+            m.asks.into_iter().map(Level::from).collect(),
+        )
+    }
+}
+
+impl From<proto::Bar> for Bar {
+    fn from(m: proto::Bar) -> Self {
+        Bar::new(m.ts, m.open, m.high, m.low, m.close, m.volume, m.tf, m.symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn trade() -> Trade {
+        Trade {
+            ts: 1000,
+            price: 100.0,
+            size: 2.0,
+            symbol: "AAPL".to_string(),
+            side: Some("BUY".to_string()),
+            exchange: None,
+            trade_id: None,
+        }
+    }
+    
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(WireFormat::parse("JSON"), Some(WireFormat::Json));
+        assert_eq!(WireFormat::parse("msgpack"), Some(WireFormat::MessagePack));
+        assert_eq!(WireFormat::parse("proto"), Some(WireFormat::Protobuf));
+        assert_eq!(WireFormat::parse("avro"), None);
+    }
+    
+    #[test]
+    fn test_encode_round_trip() {
+        for format in [WireFormat::Json, WireFormat::MessagePack] {
+            let payload = encode(format, &trade()).unwrap();
+            assert!(matches!(decode(format, MessageKind::Auto, &payload), Ok(MarketEvent::Trade(t)) if t.size == 2.0));
+        }
+        assert!(encode(WireFormat::Protobuf, &trade()).is_err());
+    }
+
+    #[test]
+    fn test_trade_id_text_or_number() {
+        let event = decode(WireFormat::Json, MessageKind::Auto, br#"{"ts":1,"price":1.0,"size":1.0,"symbol":"BTC","trade_id":12345}"#).unwrap();
+        assert!(matches!(event, MarketEvent::Trade(ref t) if t.trade_id.as_deref() == Some("12345")));
+        let event = decode(WireFormat::Json, MessageKind::Trades, br#"{"ts":1,"price":1.0,"size":1.0,"symbol":"BTC","trade_id":"a-1"}"#).unwrap();
+        assert!(matches!(event, MarketEvent::Trade(ref t) if t.trade_id.as_deref() == Some("a-1")));
+        let event = decode(WireFormat::Json, MessageKind::Trades, br#"{"ts":1,"price":1.0,"size":1.0,"symbol":"BTC"}"#).unwrap();
+        assert!(matches!(event, MarketEvent::Trade(ref t) if t.trade_id.is_none()));
+    }
+    
+    #[test]
+    fn test_msgpack_decode() {
+        let named = rmp_serde::to_vec_named(&trade()).unwrap();
+        let event = decode(WireFormat::MessagePack, MessageKind::Auto, &named).unwrap();
+        assert!(matches!(event, MarketEvent::Trade(ref t) if t.side.as_deref() == Some("BUY")));
+        
+        // Con tipo explícito también vale la codificación compacta (arrays)
+        let compact = rmp_serde::to_vec(&trade()).unwrap();
+        assert!(decode(WireFormat::MessagePack, MessageKind::Trades, &compact).is_ok());
+        
+        let book = BookSnapshot::new(1000, "AAPL".to_string(), vec![Level::new(99.0, 1.0)], Vec::new());
+        let event = decode(WireFormat::MessagePack, MessageKind::Auto, &rmp_serde::to_vec_named(&book).unwrap()).unwrap();
+        assert!(matches!(event, MarketEvent::Book(ref b) if b.bids.len() == 1));
+    }
+    
+    #[test]
+    fn test_protobuf_decode() {
+        let message = proto::Trade {
+            ts: 1000,
+            price: 100.0,
+            size: 2.0,
+            symbol: "AAPL".to_string(),
+            side: None,
+            exchange: Some("XNAS".to_string()),
+            trade_id: Some("T-1".to_string()),
+        };
+        let event = decode(WireFormat::Protobuf, MessageKind::Trades, &message.encode_to_vec()).unwrap();
+        assert!(matches!(event, MarketEvent::Trade(ref t) if t.exchange.as_deref() == Some("XNAS") && t.side.is_none()));
+        assert!(matches!(event, MarketEvent::Trade(ref t) if t.trade_id.as_deref() == Some("T-1")));
+        
+        let envelope = proto::MarketData {
+            event: Some(proto::market_data::Event::Book(proto::BookSnapshot {
+                ts: 1000,
+                symbol: "AAPL".to_string(),
+                bids: vec![proto::Level { price: 99.0, size: 1.0 }],
+                asks: Vec::new(),
+            })),
+        };
+        let event = decode(WireFormat::Protobuf, MessageKind::Auto, &envelope.encode_to_vec()).unwrap();
+        assert!(matches!(event, MarketEvent::Book(ref b) if b.bids[0].price == 99.0));
+        
+        assert!(decode(WireFormat::Protobuf, MessageKind::Auto, &proto::MarketData { event: None }.encode_to_vec()).is_err());
+        assert!(decode(WireFormat::Protobuf, MessageKind::Bars, &[0xff, 0xff]).is_err());
+    }
+}
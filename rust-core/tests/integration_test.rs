@@ -5,8 +5,10 @@
 use indicators_core::*;
 
 /// Crea un trade de prueba
-fn create_trade(ts: u64, price: f64, size: f64, symbol: &str, _side: &str) -> Trade {
-    Trade::new(ts, price, size, symbol.to_string())
+fn create_trade(ts: u64, price: f64, size: f64, symbol: &str, side: &str) -> Trade {
+    let mut trade = Trade::new(ts, price, size, symbol.to_string());
+    trade.side = Some(side.to_string());
+    trade
 }
 
 /// Crea un snapshot del libro de prueba
@@ -50,7 +52,7 @@ fn test_liquidity_and_heatmap_together() {
     // Test: Liquidity y Heatmap deben funcionar juntos procesando el mismo libro
     
     let liquidity_engine = LiquidityEngine::new();
-    let heatmap_engine = HeatmapEngine::new();
+    let heatmap_engine = HeatmapEngine::default();
     
     let snapshots = vec![
         create_book_snapshot(1000, "AAPL", 149.99, 150.01),
@@ -74,28 +76,28 @@ fn test_all_engines_multiple_symbols() {
     let cvd_engine = CVDEngine::new();
     let vwap_engine = VWAPEngine::new();
     let liquidity_engine = LiquidityEngine::new();
-    let heatmap_engine = HeatmapEngine::new();
+    let heatmap_engine = HeatmapEngine::default();
     
     // Trades para AAPL
-    let aapl_trades = vec![
+    let aapl_trades = [
         create_trade(1000, 150.0, 100.0, "AAPL", "BUY"),
         create_trade(2000, 151.0, 50.0, "AAPL", "SELL"),
     ];
     
     // Trades para MSFT
-    let msft_trades = vec![
+    let msft_trades = [
         create_trade(1000, 300.0, 200.0, "MSFT", "BUY"),
         create_trade(2000, 301.0, 100.0, "MSFT", "SELL"),
     ];
     
     // Snapshots para AAPL
-    let aapl_snapshots = vec![
+    let aapl_snapshots = [
         create_book_snapshot(1000, "AAPL", 149.99, 150.01),
         create_book_snapshot(2000, "AAPL", 150.00, 150.02),
     ];
     
     // Snapshots para MSFT
-    let msft_snapshots = vec![
+    let msft_snapshots = [
         create_book_snapshot(1000, "MSFT", 299.99, 300.01),
         create_book_snapshot(2000, "MSFT", 300.00, 300.02),
     ];
@@ -223,7 +225,7 @@ fn test_reset_functions() {
 fn test_heatmap_compression() {
     // Test: Heatmap debe comprimir correctamente
     
-    let engine = HeatmapEngine::new();
+    let engine = HeatmapEngine::default();
     
     // Crear múltiples snapshots en el mismo bucket
     let snapshot1 = create_book_snapshot(1234567890, "AAPL", 149.99, 150.01);
@@ -238,7 +240,7 @@ fn test_heatmap_compression() {
     let metrics = result.unwrap();
     
     // Debe tener tiles significativos
-    assert!(metrics.tiles.len() > 0);
+    assert!(!metrics.tiles.is_empty());
     // Compression ratio debe ser >= 1.0
     assert!(metrics.compression_ratio >= 1.0);
 }
@@ -306,7 +308,7 @@ fn test_edge_cases() {
     let cvd_engine = CVDEngine::new();
     let vwap_engine = VWAPEngine::new();
     let liquidity_engine = LiquidityEngine::new();
-    let heatmap_engine = HeatmapEngine::new();
+    let heatmap_engine = HeatmapEngine::default();
     
     // Trade con tamaño muy pequeño
     let small_trade = create_trade(1000, 150.0, 0.0001, "AAPL", "BUY");
@@ -0,0 +1,80 @@
+//! Generadores de los ficheros versionados que describen la API:
+//!
+//! - `cargo xtask stubs`: `indicators_core.pyi` (stubs de tipos para IDEs y
+//!   mypy) a partir de las firmas `#[pyclass]` / `#[pymethods]` /
+//!   `#[pyfunction]`. maturin lo empaqueta junto a `py.typed`.
+//! - `cargo xtask header`: `include/indicators_core.h` con cbindgen a partir
+//!   de `src/capi.rs`.
+//!
+//! Con `--check` no escriben nada y fallan si el fichero versionado no está
+//! al día (para CI). `stubs` admite `--features kafka,redis` para incluir las
+//! clases de features opcionales; por defecto usa las del wheel.
+
+mod stubs;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const MODULE: &str = "indicators_core";
+
+/// Features con las que maturin compila el wheel (las de por defecto)
+const WHEEL_FEATURES: &str = "python,nats,loaders";
+
+const USAGE: &str = "uso: cargo xtask <stubs|header> [--check] [--features a,b]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let check = args.iter().any(|a| a == "--check");
+    let features = args.iter().position(|a| a == "--features")
+        .and_then(|i| args.get(i + 1))
+        .map_or(WHEEL_FEATURES.to_string(), |f| format!("{},{}", WHEEL_FEATURES, f));
+
+    let root = crate_root();
+    let result = match args.first().map(String::as_str) {
+        Some("stubs") => stubs::generate(&root, &features.split(',').map(str::trim).collect::<Vec<_>>())
+            .and_then(|pyi| emit(&root.join(format!("{}.pyi", MODULE)), &pyi, check)),
+        Some("header") => header(&root).and_then(|h| emit(&root.join("include").join(format!("{}.h", MODULE)), &h, check)),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Directorio de `indicators-core` (el padre de `xtask/`)
+fn crate_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("xtask dentro de rust-core").to_path_buf()
+}
+
+/// Escribe `contents` si cambia; con `check` solo compara
+fn emit(path: &Path, contents: &str, check: bool) -> Result<(), String> {
+    let current = fs::read_to_string(path).ok();
+    if current.as_deref() == Some(contents) {
+        return Ok(());
+    }
+    if check {
+        return Err(format!("{} no está al día: regenerar con cargo xtask", path.display()));
+    }
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    println!("escrito {}", path.display());
+    Ok(())
+}
+
+/// Cabecera C de la API de `src/capi.rs` (solo ese fichero: el resto del
+/// crate no es parte de la interfaz C)
+fn header(root: &Path) -> Result<String, String> {
+    let config = cbindgen::Config::from_file(root.join("cbindgen.toml"))?;
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(root.join("src/capi.rs"))
+        .generate()
+        .map_err(|e| format!("cbindgen: {}", e))?;
+    let mut out = Vec::new();
+    bindings.write(&mut out);
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
@@ -0,0 +1,771 @@
+//! `indicators_core.pyi` a partir de las firmas Rust: clases `#[pyclass]`
+//! con sus campos `#[pyo3(get)]`, métodos de `#[pymethods]` y funciones
+//! `#[pyfunction]`. Solo se incluye lo que registra el `#[pymodule]` de
+//! `lib.rs`, en el mismo orden y con las features pedidas.
+//!
+//! Además de los items normales reconoce los envoltorios `py_class!` /
+//! `py_methods!` de `src/py_macros.rs` y los `macro_rules!` que generan un
+//! `#[pymethods]` (como `impl_py_serde!`). Los tipos que no sabe traducir
+//! salen como `Any` y se avisan por stderr.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::parse::{ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_quote, Attribute, Expr, FnArg, GenericArgument, ImplItem, Item, ItemMacro, Lit, Meta, Pat,
+    PathArguments, ReturnType, Signature, Token, Type, UnOp,
+};
+
+const PY_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else",
+    "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not",
+    "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+#[derive(Clone)]
+struct Param {
+    /// Nombre con prefijo `*` / `**` para args/kwargs
+    name: String,
+    /// Tipo Rust (se traduce al generar, cuando ya se conocen las clases)
+    ty: Option<Type>,
+    default: Option<String>,
+}
+
+#[derive(Clone)]
+enum MethodKind {
+    Init,
+    Instance,
+    Static,
+    Class,
+    Getter(String),
+    Setter(String),
+}
+
+#[derive(Clone)]
+struct Method {
+    name: String,
+    kind: MethodKind,
+    params: Vec<Param>,
+    ret: Type,
+    docs: Vec<String>,
+}
+
+struct Field {
+    name: String,
+    ty: Type,
+    settable: bool,
+    docs: Vec<String>,
+}
+
+#[derive(Default)]
+struct Class {
+    docs: Vec<String>,
+    fields: Vec<Field>,
+    methods: Vec<Method>,
+}
+
+#[derive(Default)]
+struct Api {
+    classes: HashMap<String, Class>,
+    /// Nombre Rust de la función -> firma Python
+    functions: HashMap<String, Method>,
+    /// Excepción (nombre Rust) -> (base, docstring)
+    exceptions: HashMap<String, (String, String)>,
+    aliases: HashMap<String, Type>,
+    /// Macro -> métodos del `#[pymethods]` que genera
+    macros: HashMap<String, Vec<Method>>,
+    /// Invocaciones de macros a nivel de item: (macro, tipos)
+    macro_calls: Vec<(String, Vec<String>)>,
+    unknown_types: BTreeSet<String>,
+    uses_numpy: bool,
+}
+
+/// Lo que registra el `#[pymodule]`, en orden
+enum Export {
+    Class(String),
+    Function(String),
+    Exception { name: String, rust: String },
+}
+
+pub fn generate(root: &Path, features: &[&str]) -> Result<String, String> {
+    let mut files = Vec::new();
+    collect_sources(&root.join("src"), &mut files);
+    files.sort();
+
+    let mut api = Api::default();
+    let mut exports = Vec::new();
+    for file in &files {
+        let text = fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+        let parsed = syn::parse_file(&text).map_err(|e| format!("{}: {}", file.display(), e))?;
+        api.parse_items(&parsed.items);
+        if *file == root.join("src/lib.rs") {
+            module_exports(&parsed.items, features, &mut exports);
+        }
+    }
+    api.expand_macros();
+
+    let stubs = api.render(&exports);
+    for unknown in &api.unknown_types {
+        eprintln!("aviso: tipo sin traducir a Python: {}", unknown);
+    }
+    Ok(stubs)
+}
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lectura del código fuente
+// ---------------------------------------------------------------------------
+
+impl Api {
+    fn parse_items(&mut self, items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Type(alias) => {
+                    self.aliases.insert(alias.ident.to_string(), (*alias.ty).clone());
+                }
+                Item::Macro(mac) => self.parse_macro(mac),
+                Item::Struct(s) if has(&s.attrs, "pyclass") => {
+                    let fields = s.fields.iter().filter_map(|f| {
+                        let get = py_attrs(&f.attrs).into_iter().find(|m| meta_name(m) == "pyo3")
+                            .map(|m| meta_args(&m).iter().map(meta_name).collect::<Vec<_>>())
+                            .filter(|opts| opts.iter().any(|o| o == "get"))?;
+                        Some(Field {
+                            name: f.ident.as_ref()?.to_string(),
+                            ty: f.ty.clone(),
+                            settable: get.iter().any(|o| o == "set"),
+                            docs: docs(&f.attrs),
+                        })
+                    }).collect();
+                    let class = self.classes.entry(s.ident.to_string()).or_default();
+                    class.docs = docs(&s.attrs);
+                    class.fields = fields;
+                }
+                Item::Impl(imp) if has(&imp.attrs, "pymethods") => {
+                    let methods: Vec<Method> = imp.items.iter().filter_map(|item| match item {
+                        ImplItem::Fn(f) => method(&f.attrs, &f.sig, false),
+                        _ => None,
+                    }).collect();
+                    self.classes.entry(type_ident(&imp.self_ty)).or_default().methods.extend(methods);
+                }
+                Item::Fn(f) if has(&f.attrs, "pyfunction") => {
+                    if let Some(m) = method(&f.attrs, &f.sig, true) {
+                        self.functions.insert(f.sig.ident.to_string(), m);
+                    }
+                }
+                Item::Mod(m) if !is_test(&m.attrs) => {
+                    if let Some((_, items)) = &m.content {
+                        self.parse_items(items);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_macro(&mut self, item: &ItemMacro) {
+        let name = item.mac.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+        let tokens = item.mac.tokens.clone();
+        let args = || Punctuated::<Expr, Token![,]>::parse_terminated.parse2(tokens.clone());
+        match name.as_str() {
+            "create_exception" => {
+                let Ok(args) = args() else { return };
+                let args: Vec<&Expr> = args.iter().collect();
+                let (Some(rust), Some(base)) = (args.get(1).and_then(|e| expr_name(e)), args.get(2).and_then(|e| expr_name(e))) else { return };
+                let doc = args.get(3).and_then(|e| lit_str(e)).unwrap_or_default();
+                // PyValueError -> ValueError; las excepciones propias quedan igual
+                let base = base.strip_prefix("Py").map(str::to_string).unwrap_or(base);
+                self.exceptions.insert(rust, (base, doc));
+            }
+            // Envoltorios de src/py_macros.rs: el item va dentro tal cual
+            "py_class" | "py_methods" => {
+                if let Ok(file) = syn::parse2::<syn::File>(tokens) {
+                    self.parse_items(&file.items);
+                }
+            }
+            "macro_rules" => {
+                if let (Some(ident), Some(methods)) = (&item.ident, macro_methods(tokens)) {
+                    self.macros.insert(ident.to_string(), methods);
+                }
+            }
+            _ => {
+                if let Ok(args) = Punctuated::<Type, Token![,]>::parse_terminated.parse2(tokens) {
+                    self.macro_calls.push((name, args.iter().map(type_ident).collect()));
+                }
+            }
+        }
+    }
+
+    /// Añade a cada clase los métodos de los macros invocados con ella
+    fn expand_macros(&mut self) {
+        for (name, classes) in std::mem::take(&mut self.macro_calls) {
+            let Some(methods) = self.macros.get(&name) else { continue };
+            for class in classes {
+                self.classes.entry(class).or_default().methods.extend(methods.iter().cloned());
+            }
+        }
+    }
+}
+
+/// Métodos del `#[pymethods] impl $class { .. }` que genera un `macro_rules!`.
+/// Solo se leen las firmas: los cuerpos llevan `$metavariables`.
+fn macro_methods(tokens: TokenStream) -> Option<Vec<Method>> {
+    let mut pymethods = false;
+    for tt in tokens {
+        let TokenTree::Group(group) = tt else { continue };
+        match group.delimiter() {
+            Delimiter::Bracket if group.stream().to_string().replace(' ', "").ends_with("pymethods") => pymethods = true,
+            Delimiter::Brace if pymethods => {
+                let fns = |input: ParseStream| {
+                    let mut fns = Vec::new();
+                    while !input.is_empty() {
+                        let attrs = input.call(Attribute::parse_outer)?;
+                        input.parse::<syn::Visibility>()?;
+                        let sig: Signature = input.parse()?;
+                        input.parse::<proc_macro2::Group>()?;
+                        fns.extend(method(&attrs, &sig, false));
+                    }
+                    Ok(fns)
+                };
+                return fns.parse2(group.stream()).ok();
+            }
+            _ => {
+                if let Some(methods) = macro_methods(group.stream()) {
+                    return Some(methods);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Firma Python de un `fn` de `#[pymethods]` o `#[pyfunction]`
+fn method(attrs: &[Attribute], sig: &Signature, free: bool) -> Option<Method> {
+    if is_test(attrs) {
+        return None;
+    }
+    let rust_name = sig.ident.to_string();
+    let mut name = rust_name.clone();
+    let mut kind = if free { MethodKind::Static } else { MethodKind::Instance };
+    let mut signature = None;
+    let mut pass_module = false;
+    for meta in py_attrs(attrs) {
+        let arg = || meta_args(&meta).first().map(meta_name);
+        match meta_name(&meta).as_str() {
+            "new" => kind = MethodKind::Init,
+            "staticmethod" => kind = MethodKind::Static,
+            "classmethod" => kind = MethodKind::Class,
+            "getter" => kind = MethodKind::Getter(arg().unwrap_or_else(|| rust_name.trim_start_matches("get_").to_string())),
+            "setter" => kind = MethodKind::Setter(arg().unwrap_or_else(|| rust_name.trim_start_matches("set_").to_string())),
+            "pyo3" => {
+                for option in meta_args(&meta) {
+                    match option {
+                        Meta::Path(p) if p.is_ident("pass_module") => pass_module = true,
+                        Meta::NameValue(nv) if nv.path.is_ident("name") => name = lit_str(&nv.value).unwrap_or(name),
+                        Meta::NameValue(nv) if nv.path.is_ident("signature") => signature = Some(nv.value),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Parámetros Rust (sin self, Python<'_>, módulo ni cls)
+    let mut rust_params: Vec<(String, Type)> = Vec::new();
+    for (i, arg) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(arg) = arg else { continue };
+        let Pat::Ident(pat) = &*arg.pat else { continue };
+        let pname = pat.ident.to_string();
+        let is_self = pname == "slf" || generic_args(&arg.ty).iter().any(|t| type_ident(t) == "Self");
+        let is_first = rust_params.is_empty() && i == 0;
+        if type_ident(&arg.ty) == "Python" || (is_first && (is_self || pass_module || matches!(kind, MethodKind::Class))) {
+            continue;
+        }
+        rust_params.push((pname, (*arg.ty).clone()));
+    }
+
+    let params = match signature {
+        Some(sig) => {
+            let entries = match sig {
+                Expr::Tuple(t) => t.elems.into_iter().collect(),
+                Expr::Paren(p) => vec![*p.expr],
+                _ => Vec::new(),
+            };
+            entries.iter().filter_map(|entry| {
+                let (pname, default) = match entry {
+                    Expr::Assign(a) => (expr_name(&a.left)?, Some(py_default(&a.right))),
+                    e => (expr_name(e)?, None),
+                };
+                let ty = match pname.starts_with('*') {
+                    true => Some(parse_quote!(PyAny)),
+                    false => rust_params.iter().find(|(n, _)| *n == pname).map(|(_, t)| t.clone()),
+                };
+                Some(Param { name: pname, ty, default })
+            }).collect()
+        }
+        None => {
+            // pyo3 0.21: los Option<T> finales tienen None por defecto
+            let optional_from = rust_params.iter()
+                .rposition(|(_, ty)| type_ident(ty) != "Option")
+                .map_or(0, |i| i + 1);
+            rust_params.into_iter().enumerate().map(|(i, (name, ty))| Param {
+                name,
+                ty: Some(ty),
+                default: (i >= optional_from).then(|| "None".to_string()),
+            }).collect()
+        }
+    };
+
+    let ret = match &sig.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+    Some(Method { name, kind, params, ret, docs: docs(attrs) })
+}
+
+/// Clases, funciones y excepciones registradas en el `#[pymodule]`
+fn module_exports(items: &[Item], features: &[&str], out: &mut Vec<Export>) {
+    for item in items {
+        if let Item::Fn(f) = item {
+            if has(&f.attrs, "pymodule") {
+                collect_exports(f.block.to_token_stream(), features, out);
+            }
+        }
+    }
+}
+
+/// Recorre las sentencias del cuerpo saltando las de features no pedidas
+fn collect_exports(tokens: TokenStream, features: &[&str], out: &mut Vec<Export>) {
+    let mut enabled = true;
+    let mut stmt = String::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(tt) = tokens.next() {
+        match tt {
+            TokenTree::Punct(p) if p.as_char() == '#' && stmt.is_empty() => {
+                if let Some(TokenTree::Group(attr)) = tokens.next() {
+                    let attr = attr.stream().to_string().replace(' ', "");
+                    if let Some(feature) = attr.strip_prefix("cfg(feature=\"").and_then(|f| f.strip_suffix("\")")) {
+                        enabled &= features.contains(&feature);
+                    }
+                }
+            }
+            TokenTree::Group(block) if block.delimiter() == Delimiter::Brace && stmt.is_empty() => {
+                if enabled {
+                    collect_exports(block.stream(), features, out);
+                }
+                enabled = true;
+            }
+            TokenTree::Punct(p) if p.as_char() == ';' => {
+                if enabled {
+                    out.extend(export(&stmt));
+                }
+                stmt.clear();
+                enabled = true;
+            }
+            tt => stmt.push_str(&tt.to_string().replace(' ', "")),
+        }
+    }
+}
+
+fn export(stmt: &str) -> Option<Export> {
+    let last_segment = |path: &str| path.rsplit("::").next().unwrap_or("").to_string();
+    if let Some(rest) = stmt.split("add_class::<").nth(1) {
+        Some(Export::Class(last_segment(rest.split('>').next()?)))
+    } else if let Some(rest) = stmt.split("wrap_pyfunction!(").nth(1) {
+        Some(Export::Function(last_segment(rest.split(',').next()?)))
+    } else if let (Some(name), Some(rust)) = (stmt.split("add(\"").nth(1), stmt.split("get_type_bound::<").nth(1)) {
+        Some(Export::Exception {
+            name: name.split('"').next()?.to_string(),
+            rust: last_segment(rust.split('>').next()?),
+        })
+    } else {
+        None
+    }
+}
+
+/// Atributos con la feature `python` activa: `#[cfg_attr(feature =
+/// "python", X)]` cuenta como `#[X]`
+fn py_attrs(attrs: &[Attribute]) -> Vec<Meta> {
+    let mut metas = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("cfg_attr") {
+            metas.push(attr.meta.clone());
+            continue;
+        }
+        let mut args = meta_args(&attr.meta).into_iter();
+        let python = matches!(args.next(), Some(Meta::NameValue(nv))
+            if nv.path.is_ident("feature") && lit_str(&nv.value).as_deref() == Some("python"));
+        if python {
+            metas.extend(args);
+        }
+    }
+    metas
+}
+
+fn has(attrs: &[Attribute], name: &str) -> bool {
+    py_attrs(attrs).iter().any(|m| meta_name(m) == name)
+}
+
+fn is_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| a.path().is_ident("cfg") && a.meta.to_token_stream().to_string().contains("test"))
+}
+
+fn meta_name(meta: &Meta) -> String {
+    meta.path().segments.last().map(|s| s.ident.to_string()).unwrap_or_default()
+}
+
+/// Contenido de `#[name(a, b = ..)]`
+fn meta_args(meta: &Meta) -> Vec<Meta> {
+    match meta {
+        Meta::List(list) => list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .map(|args| args.into_iter().collect())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn docs(attrs: &[Attribute]) -> Vec<String> {
+    attrs.iter().filter_map(|attr| match &attr.meta {
+        Meta::NameValue(nv) if nv.path.is_ident("doc") => lit_str(&nv.value),
+        _ => None,
+    }).map(|doc| doc.strip_prefix(' ').unwrap_or(&doc).trim_end().to_string()).collect()
+}
+
+fn lit_str(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Str(s), .. }) => Some(s.value()),
+        _ => None,
+    }
+}
+
+/// `x` / `path::x` -> "x"; `*args` -> "*args"
+fn expr_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        Expr::Unary(u) if matches!(u.op, UnOp::Deref(_)) => expr_name(&u.expr).map(|n| format!("*{}", n)),
+        _ => None,
+    }
+}
+
+/// Valor por defecto en el stub: literales simples tal cual, el resto `...`
+fn py_default(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) if !s.token().to_string().contains('\\') => s.token().to_string(),
+            Lit::Int(i) => i.base10_digits().to_string(),
+            Lit::Float(f) => f.base10_digits().to_string(),
+            Lit::Bool(b) => if b.value { "True" } else { "False" }.to_string(),
+            _ => "...".to_string(),
+        },
+        Expr::Path(p) if p.path.is_ident("None") => "None".to_string(),
+        Expr::Unary(u) if matches!(u.op, UnOp::Neg(_)) && matches!(&*u.expr, Expr::Lit(_)) => {
+            format!("-{}", py_default(&u.expr))
+        }
+        // "x".to_string(), String::from("x"), String::new()
+        Expr::MethodCall(call) if call.method == "to_string" => {
+            Some(py_default(&call.receiver)).filter(|s| s.starts_with('"')).unwrap_or_else(|| "...".to_string())
+        }
+        Expr::Call(call) => match (&*call.func, call.args.first()) {
+            (Expr::Path(p), Some(arg)) if p.path.segments.last().is_some_and(|s| s.ident == "from") => {
+                Some(py_default(arg)).filter(|s| s.starts_with('"')).unwrap_or_else(|| "...".to_string())
+            }
+            (Expr::Path(p), None) if p.path.segments.iter().map(|s| s.ident.to_string()).eq(["String", "new"]) => {
+                "\"\"".to_string()
+            }
+            _ => "...".to_string(),
+        },
+        _ => "...".to_string(),
+    }
+}
+
+fn strip_ref(mut ty: &Type) -> &Type {
+    while let Type::Reference(r) = ty {
+        ty = &r.elem;
+    }
+    ty
+}
+
+/// Último segmento del tipo sin referencias: `&'a pyo3::Bound<T>` -> "Bound"
+fn type_ident(ty: &Type) -> String {
+    match strip_ref(ty) {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Argumentos de tipo del último segmento (sin lifetimes)
+fn generic_args(ty: &Type) -> Vec<&Type> {
+    let Type::Path(p) = strip_ref(ty) else { return Vec::new() };
+    match p.path.segments.last().map(|s| &s.arguments) {
+        Some(PathArguments::AngleBracketed(args)) => args.args.iter()
+            .filter_map(|a| match a {
+                GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tipos Rust -> Python
+// ---------------------------------------------------------------------------
+
+impl Api {
+    fn py_type(&mut self, ty: &Type, this: Option<&str>) -> String {
+        let ty = strip_ref(ty);
+        match ty {
+            Type::Tuple(t) if t.elems.is_empty() => return "None".into(),
+            Type::Tuple(t) => {
+                let parts: Vec<String> = t.elems.iter().map(|p| self.py_type(p, this)).collect();
+                return format!("tuple[{}]", parts.join(", "));
+            }
+            Type::Array(syn::TypeArray { elem, .. }) | Type::Slice(syn::TypeSlice { elem, .. }) => {
+                return if type_ident(elem) == "u8" { "bytes".into() } else { format!("list[{}]", self.py_type(elem, this)) };
+            }
+            Type::Paren(p) => return self.py_type(&p.elem, this),
+            Type::Path(_) => {}
+            _ => return "Any".into(),
+        }
+
+        let name = type_ident(ty);
+        let args = generic_args(ty);
+        let arg = |i: usize, api: &mut Api| args.get(i).map_or("Any".to_string(), |a| api.py_type(a, this));
+
+        match name.as_str() {
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "int".into(),
+            "f32" | "f64" => "float".into(),
+            "bool" => "bool".into(),
+            "String" | "str" | "char" | "PathBuf" | "Path" | "Cow" => "str".into(),
+            "Self" => this.unwrap_or("Any").to_string(),
+            "Option" => format!("{} | None", arg(0, self)),
+            "PyResult" | "Result" | "Box" | "Arc" | "Rc" | "Py" | "Bound" | "Borrowed" | "PyRef" | "PyRefMut" => arg(0, self),
+            "Vec" | "VecDeque" => {
+                if args.first().is_some_and(|a| type_ident(a) == "u8") { "bytes".into() } else { format!("list[{}]", arg(0, self)) }
+            }
+            "HashMap" | "BTreeMap" | "IndexMap" => format!("dict[{}, {}]", arg(0, self), arg(1, self)),
+            "HashSet" | "BTreeSet" => format!("set[{}]", arg(0, self)),
+            "PyAny" | "PyObject" => "Any".into(),
+            "PyDict" => "dict[Any, Any]".into(),
+            "PyList" => "list[Any]".into(),
+            "PyTuple" => "tuple[Any, ...]".into(),
+            "PyBytes" => "bytes".into(),
+            "PyString" => "str".into(),
+            "PyFloat" => "float".into(),
+            "PyLong" => "int".into(),
+            "PyBool" => "bool".into(),
+            "PyType" => "type".into(),
+            "PyReadonlyArray1" | "PyReadonlyArray2" | "PyArray1" | "PyArray2" | "PyArrayDyn" | "PyReadonlyArrayDyn" => {
+                self.uses_numpy = true;
+                let dtype = match args.first().map(|a| type_ident(a)).as_deref() {
+                    Some("f64") => "numpy.float64",
+                    Some("f32") => "numpy.float32",
+                    Some("i64") => "numpy.int64",
+                    Some("i32") => "numpy.int32",
+                    Some("i8") => "numpy.int8",
+                    Some("u64") => "numpy.uint64",
+                    Some("bool") => "numpy.bool_",
+                    _ => "Any",
+                };
+                format!("numpy.typing.NDArray[{}]", dtype)
+            }
+            _ if self.classes.contains_key(&name) => name,
+            _ => match self.aliases.get(&name).cloned() {
+                Some(alias) => self.py_type(&alias, this),
+                None => {
+                    self.unknown_types.insert(ty.to_token_stream().to_string());
+                    "Any".into()
+                }
+            },
+        }
+    }
+
+    fn render(&mut self, exports: &[Export]) -> String {
+        let mut body = String::new();
+        for export in exports {
+            match export {
+                Export::Class(name) => {
+                    let class = std::mem::take(self.classes.get_mut(name).expect("clase registrada sin #[pyclass]"));
+                    body.push_str(&self.render_class(name, &class));
+                    self.classes.insert(name.clone(), class);
+                }
+                Export::Function(rust) => {
+                    if let Some(f) = self.functions.get(rust).cloned() {
+                        body.push('\n');
+                        body.push_str(&self.render_fn(&f, "", None));
+                    }
+                }
+                Export::Exception { name, rust } => {
+                    let (base, doc) = self.exceptions.get(rust).cloned().unwrap_or(("Exception".into(), String::new()));
+                    body.push_str(&format!("\nclass {}({}):\n", name, base));
+                    body.push_str(&format!("    {}\n", docstring(&[doc], "    ").unwrap_or_else(|| "...".into()).trim_start()));
+                }
+            }
+        }
+
+        let mut out = String::new();
+        out.push_str("# Generado por `cargo xtask stubs` a partir de las firmas #[pyclass] /\n");
+        out.push_str("# #[pymethods] / #[pyfunction] del crate. No editar a mano.\n\n");
+        out.push_str("from typing import Any\n");
+        if self.uses_numpy {
+            out.push_str("\nimport numpy\nimport numpy.typing\n");
+        }
+        out.push('\n');
+        out.push_str(&body);
+        out
+    }
+
+    fn render_class(&mut self, name: &str, class: &Class) -> String {
+        let mut out = format!("\nclass {}:\n", name);
+        let mut lines = Vec::new();
+        if let Some(doc) = docstring(&class.docs, "    ") {
+            lines.push(format!("    {}\n", doc));
+        }
+
+        // Propiedades de #[getter] / #[setter], con el setter tras su getter
+        let setters: HashMap<String, &Method> = class.methods.iter()
+            .filter_map(|m| match &m.kind { MethodKind::Setter(p) => Some((p.clone(), m)), _ => None })
+            .collect();
+
+        for field in &class.fields {
+            let ty = self.py_type(&field.ty, Some(name));
+            // Un campo `#[pyo3(get)]` con `#[setter]` propio también es asignable
+            if field.settable || setters.contains_key(&field.name) {
+                lines.push(format!("    {}: {}\n", field.name, ty));
+            } else {
+                let mut prop = format!("    @property\n    def {}(self) -> {}:", field.name, ty);
+                match docstring(&field.docs, "        ") {
+                    Some(doc) => prop.push_str(&format!("\n        {}\n", doc)),
+                    None => prop.push_str(" ...\n"),
+                }
+                lines.push(prop);
+            }
+        }
+        for method in &class.methods {
+            match &method.kind {
+                MethodKind::Getter(prop) => {
+                    let getter = Method { name: prop.clone(), ..method.clone() };
+                    lines.push(self.render_fn(&getter, "    ", Some(name)));
+                    if let Some(setter) = setters.get(prop) {
+                        let setter = Method { name: prop.clone(), docs: Vec::new(), ..(*setter).clone() };
+                        lines.push(self.render_fn(&setter, "    ", Some(name)));
+                    }
+                }
+                MethodKind::Setter(_) => {}
+                _ if method.name == "__richcmp__" => {
+                    let other = match method.params.first().and_then(|p| p.ty.clone()) {
+                        Some(ty) => self.py_type(&ty, Some(name)),
+                        None => "Any".into(),
+                    };
+                    let ret = self.py_type(&method.ret, Some(name));
+                    for op in ["eq", "ne"] {
+                        lines.push(format!("    def __{}__(self, other: object) -> {}: ...\n", op, ret));
+                    }
+                    for op in ["lt", "le", "gt", "ge"] {
+                        lines.push(format!("    def __{}__(self, other: {}) -> {}: ...\n", op, other, ret));
+                    }
+                }
+                _ => lines.push(self.render_fn(method, "    ", Some(name))),
+            }
+        }
+
+        if lines.is_empty() {
+            lines.push("    ...\n".to_string());
+        }
+        out.push_str(&lines.concat());
+        out
+    }
+
+    fn render_fn(&mut self, method: &Method, indent: &str, class: Option<&str>) -> String {
+        let mut out = String::new();
+        let (name, ret) = match &method.kind {
+            MethodKind::Init => ("__init__".to_string(), "None".to_string()),
+            MethodKind::Setter(prop) => {
+                out.push_str(&format!("{}@{}.setter\n", indent, prop));
+                (method.name.clone(), "None".to_string())
+            }
+            kind => {
+                match kind {
+                    MethodKind::Getter(_) => out.push_str(&format!("{}@property\n", indent)),
+                    MethodKind::Static if class.is_some() => out.push_str(&format!("{}@staticmethod\n", indent)),
+                    MethodKind::Class => out.push_str(&format!("{}@classmethod\n", indent)),
+                    _ => {}
+                }
+                (method.name.clone(), self.py_type(&method.ret, class))
+            }
+        };
+        let mut params: Vec<String> = match &method.kind {
+            MethodKind::Static => Vec::new(),
+            MethodKind::Class => vec!["cls".into()],
+            _ => vec!["self".into()],
+        };
+        for param in &method.params {
+            // Un nombre reservado en Python no se puede pasar por keyword
+            let mut text = param.name.clone();
+            if PY_KEYWORDS.contains(&text.as_str()) {
+                text.push('_');
+            }
+            if let Some(ty) = &param.ty {
+                text.push_str(&format!(": {}", self.py_type(ty, class)));
+            }
+            if let (Some(default), false) = (&param.default, matches!(method.kind, MethodKind::Setter(_))) {
+                text.push_str(&format!(" = {}", default));
+            }
+            params.push(text);
+        }
+
+        out.push_str(&format!("{}def {}({}) -> {}:", indent, name, params.join(", "), ret));
+        let inner = format!("{}    ", indent);
+        match docstring(&method.docs, &inner) {
+            Some(doc) => out.push_str(&format!("\n{}{}\n", inner, doc)),
+            None => out.push_str(" ...\n"),
+        }
+        out
+    }
+}
+
+/// Docstring de triple comilla con la indentación del cuerpo
+fn docstring(docs: &[String], indent: &str) -> Option<String> {
+    let lines: Vec<String> = docs.iter().map(|l| l.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"")).collect();
+    let first = lines.iter().position(|l| !l.trim().is_empty())?;
+    let last = lines.iter().rposition(|l| !l.trim().is_empty())?;
+    let mut lines = lines[first..=last].to_vec();
+    // Una comilla final cerraría el docstring antes de tiempo
+    if let Some(line) = lines.last_mut().filter(|l| l.ends_with('"')) {
+        line.pop();
+        line.push_str("\\\"");
+    }
+    if lines.len() == 1 {
+        return Some(format!("\"\"\"{}\"\"\"", lines[0]));
+    }
+    let mut doc = String::from("\"\"\"");
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            doc.push('\n');
+            if !line.is_empty() {
+                doc.push_str(indent);
+            }
+        }
+        doc.push_str(line);
+    }
+    doc.push('\n');
+    doc.push_str(indent);
+    doc.push_str("\"\"\"");
+    Some(doc)
+}